@@ -0,0 +1,731 @@
+use bevy::prelude::*;
+use bevy::ecs::system::SystemParam;
+use rand::Rng;
+use std::collections::HashSet;
+use super::hex::HexCoord;
+use super::map::{MapTile, TileLookup};
+use super::cities::{Building, City, CityFoundedEvent, ProductionItem, UnitType, WonderRegistry};
+use super::civilization::CivilizationManager;
+use super::combat::{CombatRecorders, get_attack_range, resolve_attack, target_preview};
+use super::diplomacy::{WarDeclaredEvent, WarStrength};
+use super::automation::automate_worker;
+use super::city_founding::{MIN_CITY_DISTANCE, can_found_city_at, found_city};
+use super::game_initialization::{GamePhase, GameSettings, GameState, TurnStartedEvent, advance_turn, rate_starting_position};
+use super::rng::GameRng;
+use super::units::{Unit, enemy_positions, impassable_positions};
+
+// How far a settler looks for a city site
+const SETTLER_SEARCH_RADIUS: i32 = 6;
+// Rating a site loses for each hex the settler has to walk to it
+const SETTLER_DISTANCE_PENALTY: f32 = 4.0;
+// Win chance the AI wants before it attacks
+const AI_ATTACK_ODDS: f32 = 0.6;
+// The AI keeps training settlers until it has this many cities
+const AI_TARGET_CITIES: usize = 4;
+// Siege engines the AI keeps per city of its own while at war
+const AI_SIEGE_PER_CITY: usize = 1;
+// Times a rival's military the AI's must be before it declares war on it
+const AI_WAR_STRENGTH_RATIO: f32 = 2.0;
+// Furthest a rival's nearest city may be from one of the AI's for a war on it to be worth marching to
+const AI_WAR_REACH: i32 = 10;
+// The AI keeps the peace until this turn, while everyone is still settling
+const AI_EARLIEST_WAR_TURN: u32 = 30;
+// Shortest time an AI turn stays on screen, in seconds
+pub const AI_TURN_DELAY: f32 = 0.5;
+// Without quick AI movement, the pause after each AI unit acts, in seconds
+const AI_UNIT_STEP_SECONDS: f32 = 0.15;
+
+/// Units still waiting to act during the current AI turn
+#[derive(Default)]
+pub struct AiTurnState {
+    civ_id: Option<u32>,
+    pending: Vec<Entity>,
+    founded_sites: Vec<HexCoord>, // Cities founded this turn, not yet visible to the city query
+    step: Timer, // Until the next unit may act, when units go one at a time
+}
+
+/// Minimum length of an AI turn, restarted whenever one begins. A zero duration
+/// ends AI turns as soon as their units have acted.
+#[derive(Resource)]
+pub struct AiTurnTimer(pub Timer);
+
+impl AiTurnTimer {
+    pub fn new(delay_secs: f32) -> Self {
+        Self(Timer::from_seconds(delay_secs, TimerMode::Once))
+    }
+}
+
+impl Default for AiTurnTimer {
+    fn default() -> Self {
+        Self::new(AI_TURN_DELAY)
+    }
+}
+
+/// Progress through the current AI turn and its pacing
+#[derive(SystemParam)]
+pub struct AiTurn<'w, 's> {
+    state: Local<'s, AiTurnState>,
+    timer: ResMut<'w, AiTurnTimer>,
+    time: Res<'w, Time>,
+    settings: Res<'w, GameSettings>,
+}
+
+impl AiTurnState {
+    // How many units act this frame: one with quick AI movement, otherwise one each time
+    // the step runs out, so the player can follow them. Never more than one, so each unit
+    // sees what the one before it did, and a game plays the same at either pace.
+    fn units_this_frame(&mut self, quick: bool, delta: std::time::Duration) -> usize {
+        if quick {
+            return 1;
+        }
+        self.step.tick(delta);
+        if !self.step.finished() {
+            return 0;
+        }
+        self.step = Timer::from_seconds(AI_UNIT_STEP_SECONDS, TimerMode::Once);
+        1
+    }
+}
+
+/// Map access for AI decisions: the tile index for movement, the tile query for combat odds
+#[derive(SystemParam)]
+pub struct AiWorld<'w, 's> {
+    pub(super) tiles: TileLookup<'w, 's>,
+    pub(super) tile_query: Query<'w, 's, &'static MapTile>,
+}
+
+/// Everything the AI's actions can spawn, despawn or announce
+#[derive(SystemParam)]
+pub struct AiCommands<'w, 's> {
+    pub(super) commands: Commands<'w, 's>,
+    pub(super) turn_events: EventWriter<'w, TurnStartedEvent>,
+    pub(super) founded_events: EventWriter<'w, CityFoundedEvent>,
+    pub(super) war_events: EventWriter<'w, WarDeclaredEvent>,
+    pub(super) combat: CombatRecorders<'w>,
+}
+
+// Plays the turn of the AI civilization whose turn it is, a unit at a time,
+// then ends it once every unit has acted and the turn timer has run out. Units go
+// through the same movement, founding and combat rules as the player's.
+pub fn ai_turn_system(
+    mut game_state: ResMut<GameState>,
+    mut civ_manager: ResMut<CivilizationManager>,
+    mut unit_query: Query<(Entity, &mut Unit)>,
+    mut city_query: Query<(Entity, &mut City)>,
+    world: AiWorld,
+    mut actions: AiCommands,
+    mut turn: AiTurn,
+) {
+    let state = &mut *turn.state;
+    let GamePhase::AITurn(civ_id) = game_state.current_phase else {
+        state.civ_id = None;
+        return;
+    };
+    
+    // First frame of the turn: decide on war, then line the units up. Their movement is
+    // refreshed by start_unit_turns later this frame, so nobody acts before the next one.
+    if state.civ_id != Some(civ_id) {
+        consider_war(civ_id, &unit_query, &city_query, &mut civ_manager, &mut actions.war_events);
+        let mut pending: Vec<(Entity, bool)> = unit_query
+            .iter()
+            .filter(|(_, unit)| unit.civilization_id == civ_id)
+            .map(|(entity, unit)| (entity, unit.can_found_cities))
+            .collect();
+        // Popped from the back: settlers go first so they claim sites before others crowd them
+        pending.sort_by_key(|&(entity, settler)| (settler, entity));
+        *state = AiTurnState {
+            civ_id: Some(civ_id),
+            pending: pending.into_iter().map(|(entity, _)| entity).collect(),
+            founded_sites: Vec::new(),
+            step: Timer::default(),
+        };
+        turn.timer.0.reset();
+        return;
+    }
+    turn.timer.0.tick(turn.time.delta());
+    
+    for _ in 0..state.units_this_frame(turn.settings.quick_movement_ai, turn.time.delta()) {
+        let Some(unit_entity) = state.pending.pop() else { break };
+        let Ok((_, unit)) = unit_query.get(unit_entity) else { continue }; // Died earlier this turn
+        if unit.movement_points == 0 {
+            continue;
+        }
+        
+        let game_turn = game_state.game_turn;
+        if unit.can_found_cities {
+            settle(unit_entity, &mut unit_query, &city_query, &world, &mut actions, &mut civ_manager, &mut state.founded_sites, game_turn);
+        } else if unit.can_build_improvements {
+            work_land(unit_entity, &mut unit_query, &city_query, &world, &civ_manager);
+        } else if unit.unit_type == UnitType::Scout {
+            explore(unit_entity, &mut unit_query, &city_query, &world, &civ_manager, &mut actions.combat.rng);
+        } else if unit.can_attack && !unit.naval_unit {
+            fight(unit_entity, &mut unit_query, &mut city_query, &world, &mut actions, &civ_manager);
+        }
+        // Ships and Great People wait where they are
+    }
+    
+    if state.pending.is_empty() && turn.timer.0.finished() {
+        state.civ_id = None;
+        advance_turn(&mut game_state, &mut civ_manager, &mut actions.turn_events);
+    }
+}
+
+// An AI civilization at peace with everyone declares war on the weakest rival it has met
+// whose cities are within reach, once its military is AI_WAR_STRENGTH_RATIO times theirs
+fn consider_war(
+    civ_id: u32,
+    unit_query: &Query<(Entity, &mut Unit)>,
+    city_query: &Query<(Entity, &mut City)>,
+    civ_manager: &mut CivilizationManager,
+    war_events: &mut EventWriter<WarDeclaredEvent>,
+) {
+    let turn = civ_manager.turn_number;
+    if turn < AI_EARLIEST_WAR_TURN || civ_manager.diplomacy.wars().iter().any(|(a, b)| *a == civ_id || *b == civ_id) {
+        return;
+    }
+    let Some(civ) = civ_manager.get_civilization(civ_id) else { return };
+    let strength = |id| WarStrength::measure(id, unit_query.iter().map(|(_, unit)| unit), city_query.iter().map(|(_, city)| city));
+    let ours = strength(civ_id);
+    let own_cities: Vec<HexCoord> = city_query.iter().map(|(_, city)| city).filter(|city| city.civilization_id == civ_id).map(|city| city.hex_coord).collect();
+    let within_reach = |rival: u32| {
+        city_query
+            .iter()
+            .filter(|(_, city)| city.civilization_id == rival)
+            .any(|(_, city)| own_cities.iter().any(|own| own.distance(city.hex_coord) <= AI_WAR_REACH))
+    };
+    
+    let mut rivals: Vec<u32> = civ_manager
+        .civilizations
+        .values()
+        .filter(|rival| rival.id != civ_id && !rival.eliminated && civ.has_met(rival.id))
+        .map(|rival| rival.id)
+        .collect();
+    rivals.sort_unstable(); // Ties go the same way every game
+    let target = rivals
+        .into_iter()
+        .filter(|rival| civ_manager.diplomacy.can_declare_war(civ_id, *rival, turn) && within_reach(*rival))
+        .map(|rival| (rival, strength(rival)))
+        .filter(|(_, theirs)| ours.military > 0.0 && ours.military >= theirs.military * AI_WAR_STRENGTH_RATIO)
+        .min_by(|(_, a), (_, b)| a.military.total_cmp(&b.military));
+    let Some((rival, theirs)) = target else { return };
+    
+    if civ_manager.diplomacy.declare_war((civ_id, ours), (rival, theirs), turn) {
+        println!("The {} have declared war on the {}!", civ_manager.civ_name(civ_id), civ_manager.civ_name(rival));
+        war_events.write(WarDeclaredEvent { civilization_id: civ_id, other_id: rival });
+    }
+}
+
+// Hexes the unit may not enter, as for the player's move orders
+pub(super) fn blocked_for(
+    unit_entity: Entity,
+    unit_query: &Query<(Entity, &mut Unit)>,
+    city_query: &Query<(Entity, &mut City)>,
+    civ_manager: &CivilizationManager,
+) -> HashSet<HexCoord> {
+    let Ok((_, unit)) = unit_query.get(unit_entity) else { return HashSet::new() };
+    let unit_positions = unit_query.iter().map(|(_, other)| (other.civilization_id, other.hex_coord));
+    impassable_positions(unit, unit_positions, city_query.iter().map(|(_, city)| city), civ_manager)
+}
+
+// Walks toward `target` with a GoTo order; false if there's no way there
+pub(super) fn move_toward(
+    unit_entity: Entity,
+    target: HexCoord,
+    unit_query: &mut Query<(Entity, &mut Unit)>,
+    tiles: &TileLookup,
+    blocked: &HashSet<HexCoord>,
+) -> bool {
+    let Ok((_, mut unit)) = unit_query.get_mut(unit_entity) else { return false };
+    if !unit.set_goto(target, tiles, blocked) {
+        return false;
+    }
+    unit.advance_goto(tiles, blocked);
+    true
+}
+
+// Spends the rest of the unit's turn in place
+pub(super) fn end_unit_turn(unit_entity: Entity, unit_query: &mut Query<(Entity, &mut Unit)>) {
+    if let Ok((_, mut unit)) = unit_query.get_mut(unit_entity) {
+        unit.clear_order();
+        unit.movement_points = 0;
+        unit.has_moved = true;
+    }
+}
+
+// Settlers walk to the best site nearby, weighing site quality against the walk, and found a city there
+#[allow(clippy::too_many_arguments)]
+fn settle(
+    unit_entity: Entity,
+    unit_query: &mut Query<(Entity, &mut Unit)>,
+    city_query: &Query<(Entity, &mut City)>,
+    world: &AiWorld,
+    actions: &mut AiCommands,
+    civ_manager: &mut CivilizationManager,
+    founded_sites: &mut Vec<HexCoord>,
+    turn: u32,
+) {
+    let blocked = blocked_for(unit_entity, unit_query, city_query, civ_manager);
+    let Ok((_, unit)) = unit_query.get(unit_entity) else { return };
+    let cities = || city_query.iter().map(|(_, city)| city);
+    let foreign_tiles: HashSet<HexCoord> = cities()
+        .filter(|city| city.civilization_id != unit.civilization_id)
+        .flat_map(|city| city.territory_tiles.iter().copied())
+        .collect();
+    
+    let start = unit.hex_coord;
+    let site = (0..=SETTLER_SEARCH_RADIUS)
+        .flat_map(|radius| start.ring(radius))
+        .filter(|coord| *coord == start || !blocked.contains(coord))
+        .filter(|coord| !foreign_tiles.contains(coord) && unit.can_move_to(*coord, &world.tiles))
+        .filter(|coord| founded_sites.iter().all(|site| coord.distance(*site) >= MIN_CITY_DISTANCE))
+        .filter(|coord| can_found_city_at(*coord, cities(), &world.tiles))
+        .filter_map(|coord| {
+            let tile = world.tiles.get(coord)?;
+            let rating = rate_starting_position(tile, |coord| world.tiles.get(coord));
+            Some((coord, rating - start.distance(coord) as f32 * SETTLER_DISTANCE_PENALTY))
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(coord, _)| coord);
+    let Some(site) = site else {
+        end_unit_turn(unit_entity, unit_query);
+        return;
+    };
+    
+    if site != start && !move_toward(unit_entity, site, unit_query, &world.tiles, &blocked) {
+        end_unit_turn(unit_entity, unit_query);
+        return;
+    }
+    let Ok((_, unit)) = unit_query.get(unit_entity) else { return };
+    if unit.hex_coord == site && unit.movement_points > 0 {
+        found_city(&mut actions.commands, unit_entity, unit, cities(), turn, civ_manager, &mut actions.founded_events);
+        founded_sites.push(site);
+    }
+}
+
+// Workers work their civilization's land the way the player's automated workers do
+fn work_land(
+    unit_entity: Entity,
+    unit_query: &mut Query<(Entity, &mut Unit)>,
+    city_query: &Query<(Entity, &mut City)>,
+    world: &AiWorld,
+    civ_manager: &CivilizationManager,
+) {
+    automate_worker(unit_entity, unit_query, city_query.iter().map(|(_, city)| city), &world.tiles, civ_manager);
+}
+
+// Scouts push outward, preferring hexes far from their own cities, with some randomness
+// from the game's seeded rng
+fn explore(
+    unit_entity: Entity,
+    unit_query: &mut Query<(Entity, &mut Unit)>,
+    city_query: &Query<(Entity, &mut City)>,
+    world: &AiWorld,
+    civ_manager: &CivilizationManager,
+    rng: &mut GameRng,
+) {
+    let blocked = blocked_for(unit_entity, unit_query, city_query, civ_manager);
+    let Ok((_, unit)) = unit_query.get(unit_entity) else { return };
+    let home: Vec<HexCoord> = city_query
+        .iter()
+        .filter(|(_, city)| city.civilization_id == unit.civilization_id)
+        .map(|(_, city)| city.hex_coord)
+        .collect();
+    
+    let target = unit
+        .calculate_valid_moves(&world.tiles, &blocked)
+        .into_iter()
+        .max_by_key(|coord| {
+            let from_home = home.iter().map(|city| city.distance(*coord)).min().unwrap_or(0);
+            from_home * 4 + rng.random_range(0..12)
+        });
+    match target {
+        Some(target) => {
+            move_toward(unit_entity, target, unit_query, &world.tiles, &blocked);
+        }
+        None => end_unit_turn(unit_entity, unit_query),
+    }
+}
+
+// Attacks the hostile unit or city in range with the best odds, if they're at least `min_odds`
+pub(super) fn try_attack(
+    unit_entity: Entity,
+    unit_query: &mut Query<(Entity, &mut Unit)>,
+    city_query: &mut Query<(Entity, &mut City)>,
+    world: &AiWorld,
+    actions: &mut AiCommands,
+    civ_manager: &CivilizationManager,
+    min_odds: f32,
+) -> bool {
+    let Ok((_, unit)) = unit_query.get(unit_entity) else { return false };
+    if unit.has_attacked || unit.movement_points == 0 || unit.must_set_up() {
+        return false;
+    }
+    let (civ_id, position, range) = (unit.civilization_id, unit.hex_coord, get_attack_range(unit));
+    
+    let mut targets: Vec<HexCoord> = enemy_positions(
+        civ_id,
+        unit_query.iter().map(|(_, other)| other),
+        city_query.iter().map(|(_, city)| city),
+        civ_manager,
+    )
+    .into_iter()
+    .collect();
+    targets.sort_unstable_by_key(|coord| (coord.q, coord.r)); // Ties go the same way every game
+    let best = targets
+        .into_iter()
+        .filter(|target| position.distance(*target) <= range)
+        .filter_map(|target| target_preview(unit_entity, target, unit_query, city_query, &world.tile_query, civ_manager))
+        .max_by(|a, b| a.attacker_win_chance.total_cmp(&b.attacker_win_chance));
+    match best {
+        Some(preview) if preview.attacker_win_chance >= min_odds => {
+            resolve_attack(&mut actions.commands, unit_query, city_query, &world.tiles, preview, civ_manager, &mut actions.combat);
+            true
+        }
+        _ => false,
+    }
+}
+
+// Military units attack when the odds are good, march on the nearest city of a civ
+// they're at war with, and otherwise garrison and fortify in their own cities
+fn fight(
+    unit_entity: Entity,
+    unit_query: &mut Query<(Entity, &mut Unit)>,
+    city_query: &mut Query<(Entity, &mut City)>,
+    world: &AiWorld,
+    actions: &mut AiCommands,
+    civ_manager: &CivilizationManager,
+) {
+    if try_attack(unit_entity, unit_query, city_query, world, actions, civ_manager, AI_ATTACK_ODDS) {
+        return;
+    }
+    
+    let blocked = blocked_for(unit_entity, unit_query, city_query, civ_manager);
+    let Ok((_, unit)) = unit_query.get(unit_entity) else { return };
+    let (civ_id, position, range) = (unit.civilization_id, unit.hex_coord, get_attack_range(unit));
+    
+    let enemy_city = city_query
+        .iter()
+        .map(|(_, city)| city)
+        .filter(|city| civ_manager.diplomacy.at_war(civ_id, city.civilization_id))
+        .min_by_key(|city| position.distance(city.hex_coord))
+        .map(|city| city.hex_coord);
+    if let Some(city_coord) = enemy_city
+        && position.distance(city_coord) > range
+    {
+        // Siege engines stop at the edge of their range, out of reach of the garrison
+        let standoff = if unit.unit_type.is_siege() { range } else { 1 };
+        let approach = city_coord
+            .ring(standoff)
+            .into_iter()
+            .filter(|coord| !blocked.contains(coord) && unit.can_move_to(*coord, &world.tiles))
+            .min_by_key(|coord| position.distance(*coord));
+        if let Some(approach) = approach
+            && move_toward(unit_entity, approach, unit_query, &world.tiles, &blocked)
+        {
+            try_attack(unit_entity, unit_query, city_query, world, actions, civ_manager, AI_ATTACK_ODDS);
+            return;
+        }
+    }
+    
+    // Nothing to fight: garrison the nearest own city without a defender
+    let Ok((_, unit)) = unit_query.get(unit_entity) else { return };
+    let in_own_city = city_query.iter().any(|(_, city)| city.civilization_id == civ_id && city.hex_coord == position);
+    if !in_own_city {
+        let undefended = city_query
+            .iter()
+            .map(|(_, city)| city)
+            .filter(|city| city.civilization_id == civ_id)
+            .filter(|city| {
+                !unit_query.iter().any(|(other_entity, other)| {
+                    other_entity != unit_entity
+                        && other.civilization_id == civ_id
+                        && other.can_attack
+                        && other.hex_coord == city.hex_coord
+                })
+            })
+            .min_by_key(|city| position.distance(city.hex_coord))
+            .map(|city| city.hex_coord);
+        if let Some(city_coord) = undefended
+            && unit.movement_points > 0
+            && move_toward(unit_entity, city_coord, unit_query, &world.tiles, &blocked)
+        {
+            return;
+        }
+    }
+    
+    if let Ok((_, mut unit)) = unit_query.get_mut(unit_entity) {
+        unit.clear_order();
+        unit.fortify();
+    }
+}
+
+// What an AI civilization has, counting units on order, when choosing what to build
+struct AiHoldings {
+    cities: usize,
+    settlers: usize,
+    workers: usize,
+    siege: usize,
+    at_war: bool,
+}
+
+// What an idle AI city builds next: a defender if it has none, settlers until the
+// civ has a few cities, a worker per city, siege engines while at war, then buildings
+fn choose_ai_production(
+    city: &City,
+    defended: bool,
+    holdings: &AiHoldings,
+    wonders: &WonderRegistry,
+    civ_manager: &CivilizationManager,
+) -> Option<ProductionItem> {
+    let warrior = ProductionItem::Unit(UnitType::Warrior);
+    let candidates = [
+        (!defended).then_some(warrior.clone()),
+        (holdings.cities + holdings.settlers < AI_TARGET_CITIES).then_some(ProductionItem::Unit(UnitType::Settler)),
+        (holdings.workers < holdings.cities).then_some(ProductionItem::Unit(UnitType::Worker)),
+        (holdings.at_war && holdings.siege < holdings.cities * AI_SIEGE_PER_CITY).then_some(ProductionItem::Unit(UnitType::Catapult)),
+    ];
+    let buildings = ProductionItem::all()
+        .into_iter()
+        .filter(|item| matches!(item, ProductionItem::Building(building) if *building != Building::Walls || !defended));
+    
+    candidates
+        .into_iter()
+        .flatten()
+        .chain(buildings)
+        .chain(std::iter::once(warrior))
+        .find(|item| city.can_build(item, wonders, civ_manager))
+}
+
+// Gives every idle city of the AI civilization whose turn it is something to build
+pub fn ai_production_system(
+    mut city_query: Query<&mut City>,
+    unit_query: Query<&Unit>,
+    wonders: Res<WonderRegistry>,
+    civ_manager: Res<CivilizationManager>,
+    game_state: Res<GameState>,
+) {
+    let GamePhase::AITurn(civ_id) = game_state.current_phase else { return };
+    assign_idle_production(civ_id, &mut city_query, &unit_query, &wonders, &civ_manager);
+}
+
+// With GameSettings::auto_production, a human's idle cities are given work the way the
+// AI's are when their turn starts, once the last turn's production has come in
+pub fn auto_production_system(
+    mut turn_events: EventReader<TurnStartedEvent>,
+    mut city_query: Query<&mut City>,
+    unit_query: Query<&Unit>,
+    wonders: Res<WonderRegistry>,
+    civ_manager: Res<CivilizationManager>,
+    settings: Res<GameSettings>,
+) {
+    for event in turn_events.read() {
+        let human = civ_manager.get_civilization(event.civilization_id).is_some_and(|civ| !civ.is_ai);
+        if settings.auto_production && human {
+            assign_idle_production(event.civilization_id, &mut city_query, &unit_query, &wonders, &civ_manager);
+        }
+    }
+}
+
+// Picks something to build in each of the civilization's cities that needs it
+fn assign_idle_production(
+    civ_id: u32,
+    city_query: &mut Query<&mut City>,
+    unit_query: &Query<&Unit>,
+    wonders: &WonderRegistry,
+    civ_manager: &CivilizationManager,
+) {
+    let producing = |unit_type: UnitType, city: &City| {
+        city.current_production == Some(ProductionItem::Unit(unit_type))
+            || city.production_queue.contains(&ProductionItem::Unit(unit_type))
+    };
+    let own_units = || unit_query.iter().filter(|unit| unit.civilization_id == civ_id);
+    let own_cities: Vec<&City> = city_query.iter().filter(|city| city.civilization_id == civ_id).collect();
+    let mut holdings = AiHoldings {
+        cities: own_cities.len(),
+        settlers: own_units().filter(|unit| unit.can_found_cities).count()
+            + own_cities.iter().filter(|city| producing(UnitType::Settler, city)).count(),
+        workers: own_units().filter(|unit| unit.can_build_improvements).count()
+            + own_cities.iter().filter(|city| producing(UnitType::Worker, city)).count(),
+        siege: own_units().filter(|unit| unit.unit_type.is_siege()).count()
+            + own_cities.iter().filter(|city| producing(UnitType::Catapult, city)).count(),
+        at_war: civ_manager.civilizations.keys().any(|other| civ_manager.diplomacy.at_war(civ_id, *other)),
+    };
+    
+    for mut city in city_query.iter_mut() {
+        if city.civilization_id != civ_id || !city.needs_production() {
+            continue;
+        }
+        
+        let defended = own_units().any(|unit| {
+            unit.can_attack && !unit.naval_unit && !unit.unit_type.is_siege() && unit.hex_coord == city.hex_coord
+        });
+        let Some(item) = choose_ai_production(&city, defended, &holdings, wonders, civ_manager) else { continue };
+        match item {
+            ProductionItem::Unit(UnitType::Settler) => holdings.settlers += 1,
+            ProductionItem::Unit(UnitType::Worker) => holdings.workers += 1,
+            ProductionItem::Unit(UnitType::Catapult) => holdings.siege += 1,
+            _ => {}
+        }
+        city.set_production(item);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use crate::game::civilization::create_default_civilizations;
+    use crate::game::combat::fight_shown_in_full;
+    use crate::game::combat_log::{CombatLog, CombatLogEntry};
+    use crate::game::diplomacy::{WarDeclaredEvent, WarStrength};
+    use crate::game::map::TerrainType;
+    use crate::game::test_support::{area, game_app};
+    
+    const ORIGIN: HexCoord = HexCoord { q: 0, r: 0 };
+    // Longer than AI_UNIT_STEP_SECONDS, so one-at-a-time units still get a turn each frame or two
+    const FRAME: Duration = Duration::from_millis(100);
+    
+    type Survivor = (UnitType, u32, HexCoord, u32, u32);
+    
+    // Civilization 2's AI turn against civilization 1, both computer players and at war:
+    // three Horsemen around three Warriors stacked on ORIGIN. Returns the fights and
+    // where everyone ended up.
+    fn ai_battle(quick_movement: bool, quick_combat: bool) -> (Vec<CombatLogEntry>, Vec<Survivor>) {
+        let mut app = game_app(area(ORIGIN, 4, TerrainType::TemperateGrassland));
+        let mut manager = CivilizationManager::default();
+        for mut civ in create_default_civilizations().into_iter().take(2) {
+            civ.traits.clear();
+            civ.set_player(false);
+            manager.add_civilization(civ);
+        }
+        manager.diplomacy.declare_war((1, WarStrength::default()), (2, WarStrength::default()), 0);
+        manager.current_turn_civ = 2;
+        app.insert_resource(manager)
+            .insert_resource(AiTurnTimer::new(0.0))
+            .init_resource::<CombatLog>()
+            .add_systems(Update, ai_turn_system);
+        {
+            let mut settings = app.world_mut().resource_mut::<GameSettings>();
+            settings.quick_movement_ai = quick_movement;
+            settings.quick_combat_ai = quick_combat;
+        }
+        app.world_mut().resource_mut::<GameState>().current_phase = GamePhase::AITurn(2);
+        for _ in 0..3 {
+            app.world_mut().spawn(Unit::new(UnitType::Warrior, 1, ORIGIN));
+        }
+        for coord in ORIGIN.ring(1).into_iter().take(3) {
+            app.world_mut().spawn(Unit::new(UnitType::Horseman, 2, coord));
+        }
+        
+        for _ in 0..50 {
+            if app.world().resource::<GameState>().current_phase != GamePhase::AITurn(2) {
+                break;
+            }
+            app.world_mut().resource_mut::<Time>().advance_by(FRAME);
+            app.update();
+        }
+        assert_ne!(app.world().resource::<GameState>().current_phase, GamePhase::AITurn(2), "the AI turn should end");
+        
+        let log = app.world().resource::<CombatLog>().recorded_since(0).cloned().collect();
+        let mut units: Vec<(Entity, Unit)> = app.world_mut()
+            .query::<(Entity, &Unit)>()
+            .iter(app.world())
+            .map(|(entity, unit)| (entity, unit.clone()))
+            .collect();
+        units.sort_by_key(|(entity, _)| *entity);
+        let units = units
+            .into_iter()
+            .map(|(_, unit)| (unit.unit_type, unit.civilization_id, unit.hex_coord, unit.health, unit.combat_experience))
+            .collect();
+        (log, units)
+    }
+    
+    // Quick AI movement and quick AI combat only change how an AI turn is shown: the same
+    // seed fights the same battles with the same survivors whichever is switched on
+    #[test]
+    fn presentation_settings_do_not_change_ai_battles() {
+        let quick = ai_battle(true, true);
+        assert!(quick.0.len() >= 3, "every Horseman should attack: {:?}", quick.0);
+        for (quick_movement, quick_combat) in [(false, false), (true, false), (false, true)] {
+            assert_eq!(ai_battle(quick_movement, quick_combat), quick, "quick movement {}, quick combat {}", quick_movement, quick_combat);
+        }
+        
+        // While the two settings do change what's shown of a fight between computer players
+        let manager = {
+            let mut manager = CivilizationManager::default();
+            for mut civ in create_default_civilizations().into_iter().take(2) {
+                civ.set_player(false);
+                manager.add_civilization(civ);
+            }
+            manager
+        };
+        let shown = |quick_combat_ai| fight_shown_in_full(&GameSettings { quick_combat_ai, ..default() }, &manager, (2, 1));
+        assert!(!shown(true) && shown(false));
+    }
+    
+    // Civilization 2's AI turn on `turn`, both computer players who have met and are at
+    // peace: three Horsemen from civilization 2's city around a lone Warrior of
+    // civilization 1's, whose city lies six hexes away. Hands back the wars declared on the way.
+    fn ai_rivals(turn: u32) -> (App, Vec<(u32, u32)>) {
+        let mut app = game_app(area(ORIGIN, 5, TerrainType::TemperateGrassland));
+        let mut manager = CivilizationManager::default();
+        for mut civ in create_default_civilizations().into_iter().take(2) {
+            civ.traits.clear();
+            civ.set_player(false);
+            civ.met_civilizations.extend([1, 2]);
+            manager.add_civilization(civ);
+        }
+        manager.current_turn_civ = 2;
+        manager.turn_number = turn;
+        app.insert_resource(manager)
+            .insert_resource(AiTurnTimer::new(0.0))
+            .init_resource::<CombatLog>()
+            .add_systems(Update, ai_turn_system);
+        app.world_mut().resource_mut::<GameState>().current_phase = GamePhase::AITurn(2);
+        app.world_mut().spawn(City::new("Rome".to_string(), HexCoord { q: 3, r: 0 }, 1, 1, true));
+        app.world_mut().spawn(City::new("Babylon".to_string(), HexCoord { q: -3, r: 0 }, 2, 1, true));
+        app.world_mut().spawn(Unit::new(UnitType::Warrior, 1, ORIGIN));
+        for coord in ORIGIN.ring(1).into_iter().take(3) {
+            app.world_mut().spawn(Unit::new(UnitType::Horseman, 2, coord));
+        }
+        
+        let mut declared = Vec::new();
+        for _ in 0..50 {
+            if app.world().resource::<GameState>().current_phase != GamePhase::AITurn(2) {
+                break;
+            }
+            app.world_mut().resource_mut::<Time>().advance_by(FRAME);
+            app.update();
+            let mut events = app.world_mut().resource_mut::<Events<WarDeclaredEvent>>();
+            declared.extend(events.drain().map(|event| (event.civilization_id, event.other_id)));
+        }
+        (app, declared)
+    }
+    
+    // An AI at peace keeps it while everyone is settling, then turns on a weaker
+    // neighbor: it declares war and its army attacks the same turn
+    #[test]
+    fn a_stronger_ai_declares_war_and_attacks() {
+        let (app, _) = ai_rivals(AI_EARLIEST_WAR_TURN - 1);
+        assert!(!app.world().resource::<CivilizationManager>().diplomacy.at_war(1, 2));
+        
+        let (app, declared) = ai_rivals(AI_EARLIEST_WAR_TURN);
+        assert!(app.world().resource::<CivilizationManager>().diplomacy.at_war(1, 2), "the AI should have declared war");
+        assert_eq!(declared, vec![(2, 1)]);
+        let log = app.world().resource::<CombatLog>();
+        assert!(
+            log.recorded_since(0).any(|entry| entry.attacker_civ == 2 && entry.defender_civ == Some(1)),
+            "the AI should have attacked the civilization it declared war on"
+        );
+    }
+    
+    // Without quick movement the units act one per step instead of one per frame
+    #[test]
+    fn slow_ai_movement_acts_one_unit_per_step() {
+        let mut state = AiTurnState::default();
+        assert_eq!(state.units_this_frame(true, FRAME), 1);
+        let steps: Vec<usize> = (0..6).map(|_| state.units_this_frame(false, FRAME)).collect();
+        // The first unit goes at once, then one every AI_UNIT_STEP_SECONDS
+        assert_eq!(steps, vec![1, 0, 1, 0, 1, 0]);
+    }
+}
@@ -0,0 +1,99 @@
+use bevy::prelude::*;
+use std::collections::HashSet;
+use super::cities::City;
+use super::civilization::CivilizationManager;
+use super::hex::HexCoord;
+use super::map::TileLookup;
+use super::units::{Unit, UnitOrder, enemy_positions, impassable_positions};
+
+// Where an automated worker may not go: anywhere the unit couldn't move anyway, every
+// tile inside another civilization's borders, and every tile next to an enemy unit
+pub fn worker_no_go<'u, 'c>(
+    unit: &Unit,
+    units: impl Iterator<Item = &'u Unit> + Clone,
+    cities: impl Iterator<Item = &'c City> + Clone,
+    civ_manager: &CivilizationManager,
+) -> HashSet<HexCoord> {
+    let unit_positions = units.clone().map(|other| (other.civilization_id, other.hex_coord));
+    let mut blocked = impassable_positions(unit, unit_positions, cities.clone(), civ_manager);
+    for city in cities.filter(|city| city.civilization_id != unit.civilization_id) {
+        blocked.extend(city.territory_tiles.iter().copied());
+    }
+    for enemy in enemy_positions(unit.civilization_id, units, std::iter::empty(), civ_manager) {
+        blocked.insert(enemy);
+        blocked.extend(enemy.neighbors());
+    }
+    blocked
+}
+
+// Tiles the civilization's other automated workers are already headed for
+pub fn reserved_tasks<'a>(worker: Entity, civ_id: u32, units: impl Iterator<Item = (Entity, &'a Unit)>) -> HashSet<HexCoord> {
+    units
+        .filter(|(entity, unit)| *entity != worker && unit.civilization_id == civ_id)
+        .filter_map(|(_, unit)| match unit.order {
+            UnitOrder::Automate(task) => task,
+            _ => None,
+        })
+        .collect()
+}
+
+/// The tile a worker should work next, or None if there's nothing it can safely reach.
+/// Tiles its civilization's cities work come first, then the rest of their territory,
+/// nearest first. City centers, tiles another worker has taken and tiles it may not enter
+/// are passed over. Roads and pillaged tiles would rank between the two once they exist.
+pub fn pick_worker_task<'a>(
+    unit: &Unit,
+    cities: impl Iterator<Item = &'a City>,
+    reserved: &HashSet<HexCoord>,
+    blocked: &HashSet<HexCoord>,
+    tiles: &TileLookup,
+) -> Option<HexCoord> {
+    let own_cities: Vec<&City> = cities.filter(|city| city.civilization_id == unit.civilization_id).collect();
+    let centers: HashSet<HexCoord> = own_cities.iter().map(|city| city.hex_coord).collect();
+    let worked: HashSet<HexCoord> = own_cities.iter().flat_map(|city| city.worked_tiles.iter().copied()).collect();
+    
+    own_cities
+        .iter()
+        .flat_map(|city| city.territory_tiles.iter().copied())
+        .filter(|coord| !centers.contains(coord) && !reserved.contains(coord) && !blocked.contains(coord))
+        .filter(|coord| *coord == unit.hex_coord || unit.can_move_to(*coord, tiles))
+        .min_by_key(|coord| (!worked.contains(coord), unit.hex_coord.distance(*coord), coord.q, coord.r))
+}
+
+// Picks the worker's task and spends its turn on it. The player's automated workers and
+// every AI worker go through this.
+pub fn automate_worker<'a>(
+    worker: Entity,
+    unit_query: &mut Query<(Entity, &mut Unit)>,
+    cities: impl Iterator<Item = &'a City> + Clone,
+    tiles: &TileLookup,
+    civ_manager: &CivilizationManager,
+) {
+    let Ok((_, unit)) = unit_query.get(worker) else { return };
+    let blocked = worker_no_go(unit, unit_query.iter().map(|(_, other)| other), cities.clone(), civ_manager);
+    let reserved = reserved_tasks(worker, unit.civilization_id, unit_query.iter());
+    let task = pick_worker_task(unit, cities, &reserved, &blocked, tiles);
+    if let Ok((_, mut unit)) = unit_query.get_mut(worker) {
+        unit.work_automated(task, tiles, &blocked);
+    }
+}
+
+// Carries on the player's automated workers once their turn has begun. AI workers are
+// automated by the AI's own turn.
+pub fn worker_automation_system(
+    mut unit_query: Query<(Entity, &mut Unit)>,
+    city_query: Query<&City>,
+    tiles: TileLookup,
+    civ_manager: Res<CivilizationManager>,
+) {
+    let workers: Vec<Entity> = unit_query
+        .iter()
+        .filter(|(_, unit)| matches!(unit.order, UnitOrder::Automate(_)) && unit.movement_points > 0)
+        .filter(|(_, unit)| civ_manager.is_current_turn(unit.civilization_id))
+        .filter(|(_, unit)| civ_manager.get_civilization(unit.civilization_id).is_some_and(|civ| civ.is_player))
+        .map(|(entity, _)| entity)
+        .collect();
+    for worker in workers {
+        automate_worker(worker, &mut unit_query, city_query.iter(), &tiles, &civ_manager);
+    }
+}
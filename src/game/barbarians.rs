@@ -0,0 +1,337 @@
+use bevy::prelude::*;
+use rand::Rng;
+use rand::seq::SliceRandom;
+use std::collections::{HashSet, VecDeque};
+use super::hex::HexCoord;
+use super::map::{MapTile, TileLookup};
+use super::cities::{City, UnitType};
+use super::civilization::{BARBARIAN_CIV_ID, BARBARIAN_COLOR, CivilizationManager};
+use super::combat::get_attack_range;
+use super::combat_log::{CombatLog, CombatLogEntry, CombatOutcome};
+use super::ai::{AiCommands, AiWorld, blocked_for, end_unit_turn, move_toward, try_attack};
+use super::game_initialization::{GamePhase, GameSettings, GameState, TurnStartedEvent, advance_turn};
+use super::rng::GameRng;
+use super::units::{Unit, spawn_unit};
+use serde::{Deserialize, Serialize};
+
+// Camp placement: one camp per this many tiles of a landmass, up to a few per landmass
+const LAND_TILES_PER_CAMP: usize = 150;
+const MAX_CAMPS_PER_CONTINENT: usize = 3;
+// Camps start at least this far from any city, and this far from each other
+const CAMP_MIN_CITY_DISTANCE: i32 = 8;
+const CAMP_MIN_SPACING: i32 = 6;
+// A camp sends out a raider this often, while fewer than CAMP_UNIT_CAP are near it
+const CAMP_SPAWN_INTERVAL: u32 = 8;
+const CAMP_UNIT_CAP: usize = 2;
+const CAMP_LEASH: i32 = 3;
+// From this turn camps also train Archers
+const BARBARIAN_ARCHER_TURN: u32 = 40;
+// Gold for the civilization that clears a camp
+const CAMP_REWARD: f32 = 25.0;
+// How far raiders look for prey, and the odds they need to attack
+const RAID_RADIUS: i32 = 8;
+const BARBARIAN_ATTACK_ODDS: f32 = 0.4;
+// Raiders moved per frame, so a large horde doesn't hitch the app
+const BARBARIANS_PER_FRAME: usize = 3;
+
+/// A barbarian encampment; it spawns raiders until a unit walks into it
+#[derive(Component, Clone, Serialize, Deserialize)]
+pub struct BarbarianCamp {
+    pub hex_coord: HexCoord,
+    pub next_spawn_turn: u32,
+}
+
+/// A civilization's unit cleared a barbarian camp
+#[derive(Event)]
+pub struct BarbarianCampClearedEvent {
+    pub civilization_id: u32,
+    pub hex_coord: HexCoord,
+    pub gold: f32,
+}
+
+/// A camp sent out a new raider
+#[derive(Event)]
+pub struct BarbarianSpawnedEvent {
+    pub hex_coord: HexCoord,
+}
+
+/// Raiders still waiting to act during the current barbarian phase
+#[derive(Default)]
+pub struct BarbarianTurnState {
+    started: bool,
+    pending: Vec<Entity>,
+}
+
+// Places barbarian camps once the starting cities are down: a few per landmass, on
+// unclaimed land well away from every city. Runs while a new game loads, right after
+// initialize_game; loading moves on to the game in the same frame, so it runs once.
+pub fn place_barbarian_camps(
+    mut commands: Commands,
+    game_state: Res<GameState>,
+    settings: Res<GameSettings>,
+    tiles: TileLookup,
+    tile_query: Query<&MapTile>,
+    city_query: Query<&City>,
+    mut rng: ResMut<GameRng>,
+) {
+    if !game_state.is_initialized || !settings.barbarians {
+        return;
+    }
+    
+    let walker = |coord: HexCoord| Unit::new(UnitType::Warrior, BARBARIAN_CIV_ID, coord);
+    let claimed: HashSet<HexCoord> = city_query.iter().flat_map(|city| city.territory_tiles.iter().copied()).collect();
+    let mut unvisited: HashSet<HexCoord> = tile_query
+        .iter()
+        .map(|tile| tile.hex_coord)
+        .filter(|coord| walker(*coord).can_move_to(*coord, &tiles))
+        .collect();
+    
+    let mut camps: Vec<HexCoord> = Vec::new();
+    while let Some(&start) = unvisited.iter().next() {
+        // Flood-fill one landmass
+        unvisited.remove(&start);
+        let mut continent = vec![start];
+        let mut frontier = VecDeque::from([start]);
+        while let Some(coord) = frontier.pop_front() {
+            for neighbor in coord.neighbors() {
+                if unvisited.remove(&neighbor) {
+                    continent.push(neighbor);
+                    frontier.push_back(neighbor);
+                }
+            }
+        }
+        
+        let wanted = (continent.len() / LAND_TILES_PER_CAMP).min(MAX_CAMPS_PER_CONTINENT);
+        continent.sort_unstable_by_key(|coord| (coord.q, coord.r)); // Shuffled the same way every game
+        continent.shuffle(&mut *rng);
+        let sites: Vec<HexCoord> = continent
+            .into_iter()
+            .filter(|coord| !claimed.contains(coord))
+            .filter(|coord| city_query.iter().all(|city| city.hex_coord.distance(*coord) >= CAMP_MIN_CITY_DISTANCE))
+            .fold(Vec::new(), |mut sites, coord| {
+                if sites.len() < wanted && sites.iter().all(|site: &HexCoord| site.distance(coord) >= CAMP_MIN_SPACING) {
+                    sites.push(coord);
+                }
+                sites
+            });
+        camps.extend(sites);
+    }
+    
+    for &coord in &camps {
+        spawn_camp(&mut commands, BarbarianCamp {
+            hex_coord: coord,
+            next_spawn_turn: game_state.game_turn + 1, // A defender turns up at once
+        });
+    }
+    println!("Placed {} barbarian camps", camps.len());
+}
+
+// A camp with its marker on the map
+pub fn spawn_camp(commands: &mut Commands, camp: BarbarianCamp) {
+    let world_pos = camp.hex_coord.to_world_pos(super::map::HEX_SIZE);
+    commands.spawn((
+        camp,
+        Text2d::new("▲"),
+        TextColor(BARBARIAN_COLOR),
+        TextFont {
+            font_size: 18.0,
+            ..default()
+        },
+        Transform::from_translation(Vec3::new(world_pos.x, world_pos.y, 2.5)), // Between cities and units
+    ));
+}
+
+// At the start of each barbarian phase, camps due a raider send one out, unless
+// enough of their raiders are still hanging around
+#[allow(clippy::too_many_arguments)]
+pub fn barbarian_spawn_system(
+    mut commands: Commands,
+    mut camp_query: Query<&mut BarbarianCamp>,
+    unit_query: Query<&Unit>,
+    tiles: TileLookup,
+    mut civ_manager: ResMut<CivilizationManager>,
+    mut turn_events: EventReader<TurnStartedEvent>,
+    mut spawned_events: EventWriter<BarbarianSpawnedEvent>,
+    mut rng: ResMut<GameRng>,
+) {
+    if !turn_events.read().any(|event| event.civilization_id == BARBARIAN_CIV_ID) {
+        return;
+    }
+    let turn = civ_manager.turn_number;
+    let occupied: HashSet<HexCoord> = unit_query.iter().map(|unit| unit.hex_coord).collect();
+    
+    for mut camp in camp_query.iter_mut() {
+        if turn < camp.next_spawn_turn {
+            continue;
+        }
+        let nearby = unit_query
+            .iter()
+            .filter(|unit| unit.civilization_id == BARBARIAN_CIV_ID && unit.hex_coord.distance(camp.hex_coord) <= CAMP_LEASH)
+            .count();
+        if nearby >= CAMP_UNIT_CAP {
+            continue;
+        }
+        
+        let unit_type = if turn >= BARBARIAN_ARCHER_TURN && rng.random_bool(0.5) {
+            UnitType::Archer
+        } else {
+            UnitType::Warrior
+        };
+        let walker = Unit::new(unit_type, BARBARIAN_CIV_ID, camp.hex_coord);
+        let spawn_pos = (0..=1)
+            .flat_map(|radius| camp.hex_coord.ring(radius))
+            .find(|coord| !occupied.contains(coord) && walker.can_move_to(*coord, &tiles));
+        if let Some(spawn_pos) = spawn_pos {
+            spawn_unit(&mut commands, unit_type, BARBARIAN_CIV_ID, spawn_pos, &mut civ_manager);
+            spawned_events.write(BarbarianSpawnedEvent { hex_coord: spawn_pos });
+            camp.next_spawn_turn = turn + CAMP_SPAWN_INTERVAL;
+        }
+    }
+}
+
+// Plays the barbarian phase: raiders attack whatever they can beat, go after nearby
+// units and undefended cities, and otherwise roam. Then the first civilization's turn begins.
+pub fn barbarian_turn_system(
+    mut game_state: ResMut<GameState>,
+    mut civ_manager: ResMut<CivilizationManager>,
+    mut unit_query: Query<(Entity, &mut Unit)>,
+    mut city_query: Query<(Entity, &mut City)>,
+    world: AiWorld,
+    mut actions: AiCommands,
+    mut state: Local<BarbarianTurnState>,
+) {
+    if game_state.current_phase != GamePhase::BarbarianTurn {
+        state.started = false;
+        return;
+    }
+    
+    // As with AI turns, movement is refreshed later in the phase's first frame
+    if !state.started {
+        state.started = true;
+        state.pending = unit_query
+            .iter()
+            .filter(|(_, unit)| unit.civilization_id == BARBARIAN_CIV_ID)
+            .map(|(entity, _)| entity)
+            .collect();
+        return;
+    }
+    
+    for _ in 0..BARBARIANS_PER_FRAME {
+        let Some(unit_entity) = state.pending.pop() else { break };
+        let Ok((_, unit)) = unit_query.get(unit_entity) else { continue };
+        if unit.movement_points > 0 {
+            raid(unit_entity, &mut unit_query, &mut city_query, &world, &mut actions, &civ_manager);
+        }
+    }
+    
+    if state.pending.is_empty() {
+        state.started = false;
+        advance_turn(&mut game_state, &mut civ_manager, &mut actions.turn_events);
+    }
+}
+
+fn raid(
+    unit_entity: Entity,
+    unit_query: &mut Query<(Entity, &mut Unit)>,
+    city_query: &mut Query<(Entity, &mut City)>,
+    world: &AiWorld,
+    actions: &mut AiCommands,
+    civ_manager: &CivilizationManager,
+) {
+    if try_attack(unit_entity, unit_query, city_query, world, actions, civ_manager, BARBARIAN_ATTACK_ODDS) {
+        return;
+    }
+    
+    let blocked = blocked_for(unit_entity, unit_query, city_query, civ_manager);
+    let Ok((_, unit)) = unit_query.get(unit_entity) else { return };
+    let (position, range) = (unit.hex_coord, get_attack_range(unit));
+    
+    // Prey: any civilization's units, and cities nobody is guarding
+    let guarded: HashSet<HexCoord> = unit_query
+        .iter()
+        .filter(|(_, other)| other.civilization_id != BARBARIAN_CIV_ID && other.can_attack)
+        .map(|(_, other)| other.hex_coord)
+        .collect();
+    let prey = unit_query
+        .iter()
+        .filter(|(_, other)| other.civilization_id != BARBARIAN_CIV_ID)
+        .map(|(_, other)| other.hex_coord)
+        .chain(
+            city_query
+                .iter()
+                .filter(|(_, city)| city.civilization_id != BARBARIAN_CIV_ID && !guarded.contains(&city.hex_coord))
+                .map(|(_, city)| city.hex_coord),
+        )
+        .filter(|coord| position.distance(*coord) <= RAID_RADIUS)
+        .min_by_key(|coord| position.distance(*coord));
+    
+    if let Some(target) = prey {
+        if position.distance(target) <= range {
+            // Too strong to take on; wait for it here
+            end_unit_turn(unit_entity, unit_query);
+            return;
+        }
+        let approach = target
+            .neighbors()
+            .into_iter()
+            .filter(|coord| !blocked.contains(coord) && unit.can_move_to(*coord, &world.tiles))
+            .min_by_key(|coord| position.distance(*coord));
+        if let Some(approach) = approach
+            && move_toward(unit_entity, approach, unit_query, &world.tiles, &blocked)
+        {
+            try_attack(unit_entity, unit_query, city_query, world, actions, civ_manager, BARBARIAN_ATTACK_ODDS);
+            return;
+        }
+    }
+    
+    // Nothing in reach: roam
+    let Ok((_, unit)) = unit_query.get(unit_entity) else { return };
+    let moves = unit.calculate_valid_moves(&world.tiles, &blocked);
+    match moves.get(actions.combat.rng.random_range(0..moves.len().max(1))) {
+        Some(&target) => {
+            move_toward(unit_entity, target, unit_query, &world.tiles, &blocked);
+        }
+        None => end_unit_turn(unit_entity, unit_query),
+    }
+}
+
+// A civilization's unit entering a camp scatters it and loots its gold
+pub fn barbarian_camp_clearing_system(
+    mut commands: Commands,
+    camp_query: Query<(Entity, &BarbarianCamp)>,
+    unit_query: Query<&Unit, Changed<Unit>>,
+    mut civ_manager: ResMut<CivilizationManager>,
+    mut cleared_events: EventWriter<BarbarianCampClearedEvent>,
+    mut combat_log: ResMut<CombatLog>,
+) {
+    for (camp_entity, camp) in camp_query.iter() {
+        let Some(unit) = unit_query
+            .iter()
+            .find(|unit| unit.hex_coord == camp.hex_coord && unit.civilization_id != BARBARIAN_CIV_ID)
+        else {
+            continue;
+        };
+        
+        commands.entity(camp_entity).despawn();
+        if let Some(civ) = civ_manager.get_civilization_mut(unit.civilization_id) {
+            civ.gold += CAMP_REWARD;
+            println!("{} cleared a barbarian camp at ({}, {}) and took {:.0} gold", civ.name, camp.hex_coord.q, camp.hex_coord.r, CAMP_REWARD);
+        }
+        combat_log.push(CombatLogEntry {
+            turn: civ_manager.turn_number,
+            location: camp.hex_coord,
+            attacker: unit.label(&civ_manager),
+            attacker_civ: unit.civilization_id,
+            defender: String::new(),
+            defender_civ: None,
+            damage_to_attacker: 0,
+            damage_to_defender: 0,
+            outcome: CombatOutcome::CampCleared { gold: CAMP_REWARD },
+        });
+        cleared_events.write(BarbarianCampClearedEvent {
+            civilization_id: unit.civilization_id,
+            hex_coord: camp.hex_coord,
+            gold: CAMP_REWARD,
+        });
+    }
+}
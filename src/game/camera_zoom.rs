@@ -1,7 +1,67 @@
 use bevy::prelude::*;
 use bevy::input::mouse::MouseWheel;
+use super::cities::{City, CitySelection};
+use super::civilization::CivilizationManager;
+use super::game_initialization::GameSettings;
+use super::hex::HexCoord;
+use super::input::TextEntry;
+use super::input_map::{Action, InputMap};
+use super::map::{HEX_SIZE, MAP_RADIUS};
+use super::units::{Unit, UnitSelection};
 
-/// System to zoom the camera in and out with the mouse wheel.
+// How long the camera takes to glide to a place it's sent
+const CAMERA_TWEEN_SECONDS: f32 = 0.25;
+
+// Half the width and height of the map in world space. The map is a hexagon of pointy-topped
+// hexes around the origin: widest across its middle row, tallest from the top row to the
+// bottom one.
+fn map_half_extents() -> Vec2 {
+    let radius = MAP_RADIUS as f32;
+    Vec2::new(HEX_SIZE * 3.0_f32.sqrt() * (radius + 0.5), HEX_SIZE * (1.5 * radius + 1.0))
+}
+
+// The furthest the camera may sit from the map's center along one axis, so that `overlap`
+// of the view's width there stays over the map; 0 when the whole map fits, to center it
+fn axis_limit(map_half: f32, view_half: f32, overlap: f32) -> f32 {
+    if view_half >= map_half {
+        return 0.0;
+    }
+    (map_half + view_half - 2.0 * view_half * overlap).max(0.0)
+}
+
+// The nearest place to `position` the camera may sit at its current zoom
+fn clamp_to_map(position: Vec2, window: &Window, scale: Vec3, settings: &GameSettings) -> Vec2 {
+    if settings.free_camera {
+        return position;
+    }
+    let map_half = map_half_extents();
+    let view_half = window.size() / 2.0 * scale.truncate();
+    let overlap = settings.camera_map_overlap;
+    let limit = Vec2::new(axis_limit(map_half.x, view_half.x, overlap), axis_limit(map_half.y, view_half.y, overlap));
+    position.clamp(-limit, limit)
+}
+
+/// Keeps the camera over the map after anything has moved it (keys, edge panning, jumps to
+/// a unit or city), allowing for the zoom: the further out, the more of the map is on screen.
+/// `--free-camera` or the console's `free_camera` lets it go anywhere.
+pub fn clamp_camera_system(
+    windows: Query<&Window>,
+    settings: Res<GameSettings>,
+    mut camera_query: Query<&mut Transform, With<Camera>>,
+) {
+    let Ok(window) = windows.single() else { return };
+    let Ok(mut camera_transform) = camera_query.single_mut() else { return };
+    
+    let position = camera_transform.translation.truncate();
+    let clamped = clamp_to_map(position, window, camera_transform.scale, &settings);
+    if clamped != position {
+        camera_transform.translation.x = clamped.x;
+        camera_transform.translation.y = clamped.y;
+    }
+}
+
+/// System to zoom the camera in and out with the mouse wheel. Runs only while the
+/// cursor is off the UI, so the wheel over a panel scrolls the panel instead.
 pub fn camera_zoom_system(
     mut scroll_evr: EventReader<MouseWheel>,
     mut query: Query<&mut Transform, With<Camera>>,
@@ -21,3 +81,85 @@ pub fn camera_zoom_system(
         }
     }
 }
+
+/// A glide of the camera to somewhere it's been sent, easing out as it arrives. Panning by
+/// hand cancels it.
+#[derive(Resource, Default)]
+pub struct CameraTween {
+    target: Option<Vec2>,
+    start: Option<Vec2>, // Where the camera was when the glide began; taken on its first frame
+    elapsed: f32,
+}
+
+impl CameraTween {
+    // Glides the camera to center on a hex
+    pub fn center_on(&mut self, coord: HexCoord) {
+        self.target = Some(coord.to_world_pos(HEX_SIZE));
+        self.start = None;
+        self.elapsed = 0.0;
+    }
+    
+    pub fn cancel(&mut self) {
+        self.target = None;
+    }
+}
+
+// Moves the camera along the glide in progress. The destination is brought within the
+// camera's bounds at the current zoom first, so a glide toward the map's edge stops where
+// the camera can stop rather than running into the limit partway.
+pub fn camera_tween_system(
+    time: Res<Time>,
+    windows: Query<&Window>,
+    settings: Res<GameSettings>,
+    mut tween: ResMut<CameraTween>,
+    mut camera_query: Query<&mut Transform, With<Camera>>,
+) {
+    let Some(target) = tween.target else { return };
+    let Ok(mut camera_transform) = camera_query.single_mut() else { return };
+    let position = camera_transform.translation.truncate();
+    let target = match windows.single() {
+        Ok(window) => clamp_to_map(target, window, camera_transform.scale, &settings),
+        Err(_) => target,
+    };
+    let start = *tween.start.get_or_insert(position);
+    
+    tween.elapsed += time.delta_secs();
+    let progress = (tween.elapsed / CAMERA_TWEEN_SECONDS).min(1.0);
+    let eased = 1.0 - (1.0 - progress).powi(3);
+    let next = start.lerp(target, eased);
+    camera_transform.translation.x = next.x;
+    camera_transform.translation.y = next.y;
+    if progress >= 1.0 {
+        tween.cancel();
+    }
+}
+
+// Home looks at the capital and C at the selected unit or city
+#[allow(clippy::too_many_arguments)]
+pub fn camera_hotkey_system(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    input_map: Res<InputMap>,
+    text_entry: Res<TextEntry>,
+    civ_manager: Res<CivilizationManager>,
+    unit_selection: Res<UnitSelection>,
+    city_selection: Res<CitySelection>,
+    unit_query: Query<&Unit>,
+    city_query: Query<&City>,
+    mut tween: ResMut<CameraTween>,
+) {
+    if text_entry.is_active() {
+        return;
+    }
+    let target = if input_map.just_pressed(Action::CenterOnCapital, &keyboard) {
+        let Some(civ) = civ_manager.get_viewed_civilization() else { return };
+        city_query.iter().find(|city| city.civilization_id == civ.id && city.is_capital).map(|city| city.hex_coord)
+    } else if input_map.just_pressed(Action::CenterOnSelection, &keyboard) {
+        let unit = unit_selection.selected_unit.and_then(|entity| unit_query.get(entity).ok()).map(|unit| unit.hex_coord);
+        unit.or_else(|| city_selection.selected_city.and_then(|entity| city_query.get(entity).ok()).map(|city| city.hex_coord))
+    } else {
+        None
+    };
+    if let Some(target) = target {
+        tween.center_on(target);
+    }
+}
@@ -1,9 +1,30 @@
 use bevy::prelude::*;
+use bevy::ecs::entity::{EntityMapper, MapEntities};
+use bevy::ecs::system::SystemParam;
 use super::hex::HexCoord;
-use super::map::{MapTile, TerrainType};
-use super::civilization::{CivilizationManager, CivTrait};
+use super::culling::Cullable;
+use super::fonts::GameFonts;
+use super::map::{MapTile, TerrainType, TileIndex, TileLookup};
+use super::civilization::{
+    BARBARIAN_CIV_ID, CivilizationManager, CivTrait, GameSpeed, Technology,
+    COMMERCIAL_EXTRA_TRADE_ROUTES, COMMERCIAL_ROUTE_GOLD, EXPANSIONIST_BORDER_COST, EXPANSIONIST_SETTLER_COST,
+    INDUSTRIOUS_BUILDING_PRODUCTION, MILITARISTIC_UNIT_COST, ORGANIZED_MAINTENANCE, SPIRITUAL_BORDER_COST,
+    SPIRITUAL_CULTURE_BONUS,
+};
+use super::units::{Unit, UnitSelection, spawn_unit};
+use super::stable_id::{as_stable_id, as_stable_ids, issue_stable_id};
+use super::input::{TextEntry, TextEntrySubmitted, TextEntryTarget};
+use super::input_map::{Action, InputMap};
+use super::combat::are_enemies;
+use super::resources::ResourceType;
+use super::rules::{BuildingRules, WonderRules, rules};
+use serde::{Deserialize, Serialize};
+use super::game_initialization::{GameSettings, TurnStartedEvent, has_freshwater_nearby};
+use super::notifications::{GameNotification, MapEvent, NotificationCategory};
+use std::collections::{HashMap, HashSet};
 
-#[derive(Component)]
+#[derive(Component, Clone, Serialize, Deserialize)]
+#[component(on_add = issue_stable_id)]
 pub struct City {
     pub name: String,
     pub hex_coord: HexCoord,
@@ -17,6 +38,7 @@ pub struct City {
     pub science_per_turn: f32,
     pub gold_per_turn: f32,
     pub culture_per_turn: f32,
+    pub maintenance_per_turn: f32,          // Building upkeep, paid out of gold_per_turn
     
     // Growth and development
     pub food_stored: f32,
@@ -27,22 +49,171 @@ pub struct City {
     // Territory and worked tiles
     pub territory_tiles: Vec<HexCoord>,     // All tiles in city's influence
     pub worked_tiles: Vec<HexCoord>,        // Tiles currently being worked by population
+    pub specialists: Vec<SpecialistType>,   // Citizens working in buildings instead of tiles
+    pub specialists_locked: bool,           // Player set specialists by hand; don't reassign them
+    #[serde(default)]
+    pub tiles_locked: bool,                 // Player picked the worked tiles by hand; keep them
+    #[serde(default)]
+    pub idle_citizens: u32,                 // Taken off their tiles by the player and not placed again
     pub territory_radius: u32,              // How far the territory extends
     
     // Buildings and improvements
+    #[serde(with = "as_stable_ids")]
+    pub trade_routes: Vec<Entity>,          // Destination cities
+    pub trade_gold_per_turn: f32,
+    
     pub buildings: Vec<Building>,
+    pub wonders: Vec<Wonder>,
     pub production_queue: Vec<ProductionItem>,
     pub current_production: Option<ProductionItem>,
     pub production_progress: f32,
+    pub unit_cost_multiplier: f32,          // Difficulty discount on units, refreshed with the yields
+    #[serde(default)]
+    pub speed: GameSpeed,                   // The game's speed, refreshed with the yields
+    pub owner_traits: Vec<CivTrait>,        // The owner's traits, refreshed with the yields
     
     // City status
     pub is_capital: bool,
+    pub is_coastal: bool,                   // Set when founded; gates Harbor and Lighthouse
+    pub is_blockaded: bool,                 // An enemy ship lies off the coast; kept up to date by blockade_system
     pub happiness: f32,
     pub health: f32,
-    pub defense_strength: f32,
+    pub defense_strength: f32,              // Total of defense_breakdown, kept up to date by update_city_defense
+    pub defense_breakdown: CityDefense,
+    pub hit_points: u32,
+    pub original_civilization_id: u32,      // Founder, kept through captures
+    pub is_original_capital: bool,          // Founded as a capital; held by others counts toward conquest
+    pub resistance_turns: u32,              // Turns left before a conquered city cooperates
+    pub is_razing: bool,                    // Loses a citizen every turn until it's gone
+    pub awaiting_capture_decision: bool,    // Conquered by the player, who hasn't chosen annex or raze yet
+    pub focus: CityFocus,
+}
+
+// Trade routes point at other cities, which get new entities when a game is loaded
+impl MapEntities for City {
+    fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+        self.trade_routes.map_entities(entity_mapper);
+    }
+}
+
+/// Where a city's defense strength comes from
+#[derive(Clone, Copy, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub struct CityDefense {
+    pub base: f32,
+    pub garrison: f32,  // Best garrisoned unit, already scaled by its health
+    pub buildings: f32,
+    pub terrain: f32,   // Hills and rivers
+}
+
+impl CityDefense {
+    pub fn total(&self) -> f32 {
+        self.base + self.garrison + self.buildings + self.terrain
+    }
+}
+
+/// What citizens favour when the city picks tiles to work
+#[derive(Clone, Copy, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub enum CityFocus {
+    #[default]
+    Default,    // Balanced, slight preference for growth
+    Food,
+    Production,
+    Gold,
+    Science,
+}
+
+impl CityFocus {
+    pub const ALL: [CityFocus; 5] = [
+        CityFocus::Default,
+        CityFocus::Food,
+        CityFocus::Production,
+        CityFocus::Gold,
+        CityFocus::Science,
+    ];
+    
+    // Tile scoring weights as (food, production, gold, science)
+    pub fn weights(self) -> (f32, f32, f32, f32) {
+        match self {
+            CityFocus::Default => (1.2, 1.0, 0.6, 0.6),
+            CityFocus::Food => (3.0, 0.8, 0.4, 0.4),
+            CityFocus::Production => (0.8, 3.0, 0.4, 0.4),
+            CityFocus::Gold => (0.8, 0.6, 3.0, 0.4),
+            CityFocus::Science => (0.8, 0.6, 0.4, 3.0),
+        }
+    }
+    
+    pub fn get_name(self) -> &'static str {
+        match self {
+            CityFocus::Default => "Balanced",
+            CityFocus::Food => "Food",
+            CityFocus::Production => "Production",
+            CityFocus::Gold => "Gold",
+            CityFocus::Science => "Science",
+        }
+    }
+}
+
+/// Citizens employed by a building rather than a tile. They eat but grow no food.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum SpecialistType {
+    Scientist,  // +3 science, Library slots
+    Merchant,   // +3 gold, Marketplace slots
+    Artist,     // +3 culture, Temple slots
+}
+
+impl SpecialistType {
+    pub const ALL: [SpecialistType; 3] = [SpecialistType::Scientist, SpecialistType::Merchant, SpecialistType::Artist];
+    
+    // Returns (science, gold, culture)
+    pub fn get_yields(self) -> (f32, f32, f32) {
+        match self {
+            SpecialistType::Scientist => (3.0, 0.0, 0.0),
+            SpecialistType::Merchant => (0.0, 3.0, 0.0),
+            SpecialistType::Artist => (0.0, 0.0, 3.0),
+        }
+    }
+    
+    // Worth of one specialist to a city with the given focus, comparable to tile_score
+    fn score(self, focus: CityFocus) -> f32 {
+        let (science, gold, culture) = self.get_yields();
+        let (_, _, gold_weight, science_weight) = focus.weights();
+        science * science_weight + gold * gold_weight + culture * 0.6
+    }
+    
+    pub fn get_name(self) -> &'static str {
+        match self {
+            SpecialistType::Scientist => "Scientist",
+            SpecialistType::Merchant => "Merchant",
+            SpecialistType::Artist => "Artist",
+        }
+    }
+}
+
+/// Kinds of Great People. Each civilization collects points toward each kind separately.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GreatPersonType {
+    Scientist,  // From Scientist specialists
+    Engineer,   // From wonders
+    Merchant,   // From Merchant specialists
+}
+
+impl GreatPersonType {
+    pub const ALL: [GreatPersonType; 3] = [GreatPersonType::Scientist, GreatPersonType::Engineer, GreatPersonType::Merchant];
+    
+    pub fn unit_type(self) -> UnitType {
+        match self {
+            GreatPersonType::Scientist => UnitType::GreatScientist,
+            GreatPersonType::Engineer => UnitType::GreatEngineer,
+            GreatPersonType::Merchant => UnitType::GreatMerchant,
+        }
+    }
 }
 
-#[derive(Clone, Copy , Debug, PartialEq)]
+// Great People points a specialist or wonder adds each turn
+const SPECIALIST_GREAT_PEOPLE_POINTS: f32 = 2.0;
+const WONDER_GREAT_PEOPLE_POINTS: f32 = 3.0;
+
+#[derive(Clone, Copy , Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Building {
     Granary,        // +2 food, 25% food storage bonus
     Barracks,       // +2 military unit experience, +1 defense
@@ -52,30 +223,57 @@ pub enum Building {
     Walls,          // +3 defense, +50% defense against attacks
     Aqueduct,       // +2 health, allows city growth beyond size 6
     Workshop,       // +1 production, +25% production for buildings
-    Harbor,         // +1 food, +2 gold from water tiles (coastal cities only)
-    Lighthouse,     // +1 food from water tiles, +2 trade routes (coastal cities only)
+    Harbor,         // +1 food, +2 gold per worked water tile (coastal cities only)
+    Lighthouse,     // +1 food per worked water tile, +2 trade routes (coastal cities only)
+}
+
+/// Percentage bonuses from buildings, as fractions (0.25 = +25%). Applied after
+/// tile and flat building yields are summed, and before civilization traits.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct BuildingModifiers {
+    pub food_storage: f32,        // Share of the growth threshold kept after growing
+    pub science: f32,
+    pub gold: f32,
+    pub building_production: f32, // Only while producing buildings
+}
+
+impl BuildingModifiers {
+    fn add(&mut self, other: BuildingModifiers) {
+        self.food_storage += other.food_storage;
+        self.science += other.science;
+        self.gold += other.gold;
+        self.building_production += other.building_production;
+    }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum ProductionItem {
     Building(Building),
     Unit(UnitType),
     Wonder(Wonder),
+    Wealth, // Never finishes; turns the city's production into gold instead
 }
 
-#[derive(Clone, Copy, Eq, Hash, Debug, PartialEq)]
+#[derive(Clone, Copy, Eq, Hash, Debug, PartialEq, Serialize, Deserialize)]
 pub enum UnitType {
     Warrior,
     Archer,
     Spearman,
+    Horseman,
     Settler,
     Worker,
     Scout,
     Galley,      // Basic naval unit
     Trireme,     // Advanced naval unit
+    Catapult,    // Siege engine for breaching city walls
+    
+    // Great People: born from great-people points, never built
+    GreatScientist,
+    GreatEngineer,
+    GreatMerchant,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Wonder {
     Pyramids,       // +2 culture, free granary in every city
     Stonehenge,     // +4 culture, +1 culture from temples
@@ -83,17 +281,370 @@ pub enum Wonder {
     GreatLibrary,   // +4 science, free library in every city
 }
 
+/// Which wonders have been completed, and where. Each wonder exists once per world.
+#[derive(Resource, Clone, Default, Serialize, Deserialize)]
+pub struct WonderRegistry {
+    built: HashMap<Wonder, WonderSite>,
+    ruined: HashSet<Wonder>, // Lost with their razed city; they can't be built again
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WonderSite {
+    #[serde(with = "as_stable_id")]
+    pub city: Entity,
+    pub civilization_id: u32,
+}
+
+impl WonderRegistry {
+    pub fn is_built(&self, wonder: Wonder) -> bool {
+        self.built.contains_key(&wonder) || self.ruined.contains(&wonder)
+    }
+    
+    pub fn site(&self, wonder: Wonder) -> Option<WonderSite> {
+        self.built.get(&wonder).copied()
+    }
+    
+    pub fn civ_has(&self, civilization_id: u32, wonder: Wonder) -> bool {
+        self.site(wonder).is_some_and(|site| site.civilization_id == civilization_id)
+    }
+    
+    // Buildings every city of the civ gets for free from its wonders
+    pub fn free_buildings(&self, civilization_id: u32) -> Vec<Building> {
+        self.built
+            .iter()
+            .filter(|(_, site)| site.civilization_id == civilization_id)
+            .filter_map(|(wonder, _)| wonder.free_building())
+            .collect()
+    }
+    
+    fn record(&mut self, wonder: Wonder, city: Entity, civilization_id: u32) {
+        self.built.insert(wonder, WonderSite { city, civilization_id });
+    }
+    
+    // Wonders change hands with their city
+    pub fn transfer_city(&mut self, city: Entity, new_civilization_id: u32) {
+        for site in self.built.values_mut().filter(|site| site.city == city) {
+            site.civilization_id = new_civilization_id;
+        }
+    }
+    
+    // Wonders burn with a razed city
+    pub fn destroy_city(&mut self, city: Entity) {
+        let lost: Vec<Wonder> = self.built
+            .iter()
+            .filter(|(_, site)| site.city == city)
+            .map(|(wonder, _)| *wonder)
+            .collect();
+        for wonder in lost {
+            self.built.remove(&wonder);
+            self.ruined.insert(wonder);
+        }
+    }
+}
+
+impl MapEntities for WonderRegistry {
+    fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+        for site in self.built.values_mut() {
+            site.city = entity_mapper.get_mapped(site.city);
+        }
+    }
+}
+
+/// The player's city whose production panel is open
+#[derive(Resource, Default)]
+pub struct CitySelection {
+    pub selected_city: Option<Entity>,
+}
+
+/// A city finished building a unit; the unit is spawned by `spawn_produced_units`
+#[derive(Event)]
+pub struct UnitProduced {
+    pub city_entity: Entity,
+    pub unit_type: UnitType,
+}
+
+/// A city gained a citizen
+#[derive(Event)]
+pub struct CityGrewEvent {
+    pub city_entity: Entity,
+    pub population: u32,
+}
+
+/// A city ran out of food: it lost a citizen, or at population 1 just went hungry
+#[derive(Event)]
+pub struct CityStarvingEvent {
+    pub city_entity: Entity,
+    pub population: u32,
+    pub shrank: bool,
+}
+
+/// A city claimed a new tile for its territory
+#[derive(Event)]
+pub struct TerritoryExpandedEvent {
+    pub city_entity: Entity,
+    pub tile: HexCoord,
+}
+
+/// A city finished (or bought) a building, unit or wonder
+#[derive(Event)]
+pub struct ProductionCompletedEvent {
+    pub city_entity: Entity,
+    pub item: ProductionItem,
+}
+
+/// A settler founded a new city
+#[derive(Event)]
+pub struct CityFoundedEvent {
+    pub city_entity: Entity,
+    pub civilization_id: u32,
+    pub hex_coord: HexCoord,
+}
+
+/// An enemy ship moved in off a coastal city that wasn't blockaded yet
+#[derive(Event)]
+pub struct BlockadeStartedEvent {
+    pub city_entity: Entity,
+}
+
+/// Everything a city can announce while taking its turn, bundled so the
+/// city methods only need one parameter for it
+#[derive(SystemParam)]
+pub struct CityEventWriters<'w> {
+    pub units_produced: EventWriter<'w, UnitProduced>,
+    pub grew: EventWriter<'w, CityGrewEvent>,
+    pub starving: EventWriter<'w, CityStarvingEvent>,
+    pub territory_expanded: EventWriter<'w, TerritoryExpandedEvent>,
+    pub production_completed: EventWriter<'w, ProductionCompletedEvent>,
+}
+
+/// Readers for the city announcements, for anything that reports them to the player
+#[derive(SystemParam)]
+pub struct CityEventReaders<'w, 's> {
+    grew: EventReader<'w, 's, CityGrewEvent>,
+    starving: EventReader<'w, 's, CityStarvingEvent>,
+    territory_expanded: EventReader<'w, 's, TerritoryExpandedEvent>,
+    production_completed: EventReader<'w, 's, ProductionCompletedEvent>,
+    founded: EventReader<'w, 's, CityFoundedEvent>,
+    destroyed: EventReader<'w, 's, CityDestroyedEvent>,
+    blockades: EventReader<'w, 's, BlockadeStartedEvent>,
+}
+
+impl CityEventReaders<'_, '_> {
+    /// This frame's announcements as notifications for the civilization each concerns
+    pub fn read_notifications(&mut self, city_query: &Query<&City>) -> Vec<GameNotification> {
+        let about = |city_entity: Entity| {
+            city_query.get(city_entity).map(|city| (city.civilization_id, city.name.clone(), city.hex_coord)).ok()
+        };
+        let news = |text: String, civ_id: u32| GameNotification::new(NotificationCategory::City, text).for_civ(civ_id);
+        
+        let mut notifications = Vec::new();
+        for event in self.founded.read() {
+            let name = about(event.city_entity).map_or_else(|| "A new city".to_string(), |(_, name, _)| name);
+            let text = format!("{} founded at ({}, {})", name, event.hex_coord.q, event.hex_coord.r);
+            notifications.push(news(text, event.civilization_id).at(event.hex_coord));
+        }
+        for event in self.grew.read() {
+            if let Some((civ_id, name, hex)) = about(event.city_entity) {
+                notifications.push(news(format!("{} has grown to population {}", name, event.population), civ_id).at(hex));
+            }
+        }
+        for event in self.starving.read() {
+            if let Some((civ_id, name, hex)) = about(event.city_entity) {
+                let text = if event.shrank {
+                    format!("{} is starving and has shrunk to population {}!", name, event.population)
+                } else {
+                    format!("{} is starving!", name)
+                };
+                notifications.push(GameNotification::new(NotificationCategory::Warning, text).at(hex).for_civ(civ_id));
+            }
+        }
+        for event in self.territory_expanded.read() {
+            if let Some((civ_id, name, _)) = about(event.city_entity) {
+                notifications.push(news(format!("{} claimed tile ({}, {})", name, event.tile.q, event.tile.r), civ_id).at(event.tile));
+            }
+        }
+        for event in self.production_completed.read() {
+            if let Some((civ_id, name, hex)) = about(event.city_entity) {
+                notifications.push(news(format!("{} completed {}", name, event.item.get_name()), civ_id).at(hex));
+            }
+        }
+        for event in self.destroyed.read() {
+            let last = if event.was_last_city { " - its owner has no cities left" } else { "" };
+            let text = format!("{} has been razed to the ground{}", event.name, last);
+            notifications.push(GameNotification::new(NotificationCategory::Military, text).for_civ(event.civilization_id));
+        }
+        for event in self.blockades.read() {
+            if let Some((civ_id, name, hex)) = about(event.city_entity) {
+                let text = format!("{} is blockaded! Its sea tiles, harbor and sea trade are cut off", name);
+                notifications.push(GameNotification::new(NotificationCategory::Warning, text).at(hex).for_civ(civ_id));
+            }
+        }
+        notifications
+    }
+}
+
+/// Which city owns each tile. Territory never overlaps: a tile belongs to at most one city.
+#[derive(Resource, Clone, Default, Serialize, Deserialize)]
+pub struct TerritoryMap {
+    owners: HashMap<HexCoord, TileOwner>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TileOwner {
+    #[serde(with = "as_stable_id")]
+    pub city: Entity,
+    pub civilization_id: u32,
+}
+
+impl TerritoryMap {
+    pub fn owner(&self, coord: HexCoord) -> Option<TileOwner> {
+        self.owners.get(&coord).copied()
+    }
+    
+    pub fn is_owned(&self, coord: HexCoord) -> bool {
+        self.owners.contains_key(&coord)
+    }
+    
+    // Claim an unowned tile; returns false if another city already holds it
+    pub fn claim(&mut self, coord: HexCoord, city: Entity, civilization_id: u32) -> bool {
+        match self.owners.get(&coord) {
+            Some(owner) => owner.city == city,
+            None => {
+                self.owners.insert(coord, TileOwner { city, civilization_id });
+                true
+            }
+        }
+    }
+    
+    // Take a tile regardless of owner, returning the previous owner
+    pub fn seize(&mut self, coord: HexCoord, city: Entity, civilization_id: u32) -> Option<TileOwner> {
+        self.owners
+            .insert(coord, TileOwner { city, civilization_id })
+            .filter(|previous| previous.city != city)
+    }
+    
+    // All tiles of a city change hands with it
+    pub fn transfer_city(&mut self, city: Entity, new_civilization_id: u32) {
+        for owner in self.owners.values_mut().filter(|owner| owner.city == city) {
+            owner.civilization_id = new_civilization_id;
+        }
+    }
+    
+    // A razed city's tiles become unclaimed
+    pub fn release_city(&mut self, city: Entity) {
+        self.owners.retain(|_, owner| owner.city != city);
+    }
+    
+    // Where each civilization's territory ends, as (tile inside, neighbor outside, owner),
+    // following which tiles it owns rather than how far its cities reach
+    pub fn border_edges(&self) -> impl Iterator<Item = (HexCoord, HexCoord, u32)> + '_ {
+        self.owners.iter().flat_map(move |(coord, owner)| {
+            coord
+                .neighbors()
+                .into_iter()
+                .filter(move |neighbor| self.owner(*neighbor).is_none_or(|other| other.civilization_id != owner.civilization_id))
+                .map(move |neighbor| (*coord, neighbor, owner.civilization_id))
+        })
+    }
+}
+
+impl MapEntities for TerritoryMap {
+    fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+        for owner in self.owners.values_mut() {
+            owner.city = entity_mapper.get_mapped(owner.city);
+        }
+    }
+}
+
+/// A city was taken in battle; ownership is transferred by `city_capture_system`
+#[derive(Event)]
+pub struct CityCaptured {
+    pub city_entity: Entity,
+    pub new_civilization_id: u32,
+}
+
+/// A razed city has been wiped off the map. `was_last_city` is set when its owner
+/// has no cities left.
+#[derive(Event)]
+pub struct CityDestroyedEvent {
+    pub city_entity: Entity,
+    pub civilization_id: u32,
+    pub name: String,
+    pub was_last_city: bool,
+}
+
+
+// Happiness model
+const BASE_HAPPINESS: f32 = 4.0;
+const CAPITAL_HAPPINESS: f32 = 2.0;
+pub const LUXURY_HAPPINESS: f32 = 1.0;            // Per distinct luxury in territory
+const POPULATION_UNHAPPINESS: f32 = 1.0;          // Per citizen
+const CAPTURED_CITY_UNHAPPINESS: f32 = 1.0;       // Per captured city in the empire
+pub const WAR_WEARINESS_UNHAPPINESS: f32 = 0.05;  // Per point of the empire's war weariness
+const UNHAPPY_PRODUCTION_PENALTY: f32 = 0.1;      // Per point below zero
+const MAX_UNHAPPY_PRODUCTION_PENALTY: f32 = 0.5;
+pub const UNREST_THRESHOLD: f32 = -3.0;           // At or below this the city is in unrest
+
+// Furthest a city's borders can reach
+const MAX_TERRITORY_RADIUS: i32 = 4;
+
+// Gold-rush price of one point of production
+pub const GOLD_PER_PRODUCTION: f32 = 2.0;
+
+// Gold from each point of production a city puts into Wealth, or leaves idle. Half the
+// gold-rush price, so building something is always the better use.
+const WEALTH_GOLD_PER_PRODUCTION: f32 = 1.0;
+
+// Share of a building's production cost recovered when it's sold off
+const BUILDING_SALE_FRACTION: f32 = 0.25;
+
+// Trade routes reach at most this many hexes
+pub const TRADE_ROUTE_RANGE: i32 = 10;
+
+// Growth limits
+pub const MAX_POPULATION_WITHOUT_AQUEDUCT: u32 = 6;
+const FOOD_PER_CITIZEN: f32 = 2.0;
+const BASE_HEALTH: f32 = 2.0;
+const FRESHWATER_HEALTH: f32 = 2.0;
+const POPULATION_UNHEALTHINESS: f32 = 0.5;        // Per citizen
+const UNHEALTHY_GROWTH_FACTOR: f32 = 0.5;         // Share of the food surplus kept while unhealthy
+
+// City hit points, on the same scale as unit health
+pub const CITY_MAX_HIT_POINTS: u32 = 100;
+const CITY_HEAL_PER_TURN: u32 = 10;
+
+// Least the city center yields, however poor the terrain
+const CENTER_MIN_FOOD: f32 = 2.0;
+const CENTER_MIN_PRODUCTION: f32 = 1.0;
+const CENTER_MIN_GOLD: f32 = 1.0;
+
+// City defense contributions
+const CITY_BASE_DEFENSE: f32 = 2.0;
+const CITY_HILLS_DEFENSE: f32 = 2.0;
+const CITY_RIVER_DEFENSE: f32 = 1.0;
+
+// Turns a conquered city resists its new owner, per citizen
+const RESISTANCE_TURNS_PER_POPULATION: u32 = 1;
+
+/// A city on the map. Its symbol and name label are its children, so they go with it.
 #[derive(Component)]
 pub struct CityMarker {
-    pub civilization_id: u32,
-    pub city_name: String,
+    pub city_entity: Entity,
 }
 
+/// The dot marking a city, under its CityMarker
+#[derive(Component)]
+pub struct CitySymbol;
+
+/// The name label shown under a city marker
+#[derive(Component)]
+pub struct CityNameLabel;
+
 impl City {
     pub fn new(name: String, hex_coord: HexCoord, civilization_id: u32, turn: u32, is_capital: bool) -> Self {
         let initial_territory = Self::calculate_initial_territory(hex_coord);
-        let food_needed = Self::calculate_food_needed_for_growth(1);
-        let culture_needed = Self::calculate_culture_needed_for_expansion(1);
+        let food_needed = Self::calculate_food_needed_for_growth(1, GameSpeed::default());
+        let culture_needed = Self::calculate_culture_needed_for_expansion(initial_territory.len());
         
         Self {
             name,
@@ -106,25 +657,46 @@ impl City {
             science_per_turn: 1.0,    // Base science
             gold_per_turn: 2.0,       // Base gold
             culture_per_turn: 1.0,    // Base culture
+            maintenance_per_turn: 0.0,
             food_stored: 0.0,
             food_needed_for_growth: food_needed,
             culture_stored: 0.0,
             culture_needed_for_expansion: culture_needed,
             territory_tiles: initial_territory,
             worked_tiles: vec![hex_coord], // Start by working the city center
+            specialists: Vec::new(),
+            specialists_locked: false,
+            tiles_locked: false,
+            idle_citizens: 0,
             territory_radius: 1,
+            trade_routes: Vec::new(),
+            trade_gold_per_turn: 0.0,
             buildings: Vec::new(),
+            wonders: Vec::new(),
             production_queue: Vec::new(),
             current_production: None,
             production_progress: 0.0,
+            unit_cost_multiplier: 1.0,
+            speed: GameSpeed::default(),
+            owner_traits: Vec::new(),
             is_capital,
+            is_coastal: false,
+            is_blockaded: false,
             happiness: 5.0,           // Base happiness
             health: 5.0,              // Base health
-            defense_strength: 2.0,    // Base defense
+            defense_strength: CITY_BASE_DEFENSE,
+            defense_breakdown: CityDefense { base: CITY_BASE_DEFENSE, ..default() },
+            hit_points: CITY_MAX_HIT_POINTS,
+            original_civilization_id: civilization_id,
+            is_original_capital: is_capital,
+            resistance_turns: 0,
+            is_razing: false,
+            awaiting_capture_decision: false,
+            focus: CityFocus::Default,
         }
     }
     
-    fn calculate_initial_territory(center: HexCoord) -> Vec<HexCoord> {
+    pub fn calculate_initial_territory(center: HexCoord) -> Vec<HexCoord> {
         let mut territory = vec![center]; // City center
         
         // Add immediate neighbors (6 tiles around city)
@@ -135,48 +707,44 @@ impl City {
         territory
     }
     
-    fn calculate_food_needed_for_growth(population: u32) -> f32 {
-        // Formula: 10 + (population * 2)
-        10.0 + (population as f32 * 2.0)
+    fn calculate_food_needed_for_growth(population: u32, speed: GameSpeed) -> f32 {
+        // Formula: 10 + (population * 2), scaled by the game speed
+        (10.0 + (population as f32 * 2.0)) * speed.multiplier()
     }
     
-    fn calculate_culture_needed_for_expansion(territory_radius: u32) -> f32 {
-        // Formula: 20 * (radius^2)
-        20.0 * (territory_radius as f32).powi(2)
+    fn calculate_culture_needed_for_expansion(territory_size: usize) -> f32 {
+        // Formula: 3 * tiles owned, so each new tile costs a little more
+        3.0 * territory_size as f32
     }
     
-    pub fn calculate_yields(&mut self, tile_query: &Query<&MapTile>, civ_manager: &CivilizationManager) {
+    pub fn calculate_yields(&mut self, tiles: &TileLookup, civ_manager: &CivilizationManager, wonders: &WonderRegistry) {
         let mut total_food = 0.0;
         let mut total_production = 0.0;
         let mut total_science = 0.0;
         let mut total_gold = 0.0;
         let mut total_culture = 1.0; // Base culture
         
-        // Get civilization bonuses
-        let civ_bonuses = if let Some(civ) = civ_manager.get_civilization(self.civilization_id) {
-            (
-                civ.get_trait_bonus(CivTrait::Expansionist),
-                civ.get_trait_bonus(CivTrait::Commercial),
-                civ.get_trait_bonus(CivTrait::Scientific),
-                civ.get_trait_bonus(CivTrait::Spiritual),
-            )
-        } else {
-            (1.0, 1.0, 1.0, 1.0)
-        };
+        self.owner_traits = civ_manager
+            .get_civilization(self.civilization_id)
+            .map(|civ| civ.traits.clone())
+            .unwrap_or_default();
         
         // Calculate yields from worked tiles
         for &tile_coord in &self.worked_tiles {
-            if let Some(tile) = tile_query.iter().find(|t| t.hex_coord == tile_coord) {
-                let (food, production, science) = self.get_tile_yields(tile);
+            if let Some(tile) = tiles.get(tile_coord) {
+                let (food, production, science, gold) = self.worked_tile_output(tile_coord, tile);
                 total_food += food;
                 total_production += production;
                 total_science += science;
-                total_gold += self.get_tile_gold_yield(tile);
+                total_gold += gold;
             }
         }
         
-        // Apply building bonuses
+        // Apply building bonuses; sea buildings do nothing under blockade
         for building in &self.buildings {
+            if self.is_blockaded && matches!(building, Building::Harbor | Building::Lighthouse) {
+                continue;
+            }
             let (food_bonus, prod_bonus, sci_bonus, gold_bonus, culture_bonus) = building.get_yields();
             total_food += food_bonus;
             total_production += prod_bonus;
@@ -185,10 +753,55 @@ impl City {
             total_culture += culture_bonus;
         }
         
-        // Apply civilization trait bonuses
-        total_gold *= civ_bonuses.1;      // Commercial bonus
-        total_science *= civ_bonuses.2;   // Scientific bonus
-        total_culture *= civ_bonuses.3;   // Spiritual bonus
+        for specialist in &self.specialists {
+            let (science, gold, culture) = specialist.get_yields();
+            total_science += science;
+            total_gold += gold;
+            total_culture += culture;
+        }
+        
+        for wonder in &self.wonders {
+            let (food_bonus, prod_bonus, sci_bonus, gold_bonus, culture_bonus) = wonder.get_yields();
+            total_food += food_bonus;
+            total_production += prod_bonus;
+            total_science += sci_bonus;
+            total_gold += gold_bonus;
+            total_culture += culture_bonus;
+        }
+        
+        // Stonehenge makes every temple of its owner more inspiring
+        if self.buildings.contains(&Building::Temple) && wonders.civ_has(self.civilization_id, Wonder::Stonehenge) {
+            total_culture += 1.0;
+        }
+        
+        total_gold += self.trade_gold_per_turn;
+        
+        let (food_multiplier, production_multiplier, science_multiplier, gold_multiplier) = self.yield_multipliers(civ_manager);
+        total_food *= food_multiplier;
+        total_production *= production_multiplier;
+        total_science *= science_multiplier;
+        total_gold *= gold_multiplier;
+        
+        // Spiritual
+        if self.has_trait(CivTrait::Spiritual) {
+            total_culture *= 1.0 + SPIRITUAL_CULTURE_BONUS;
+        }
+        
+        let difficulty = civ_manager.difficulty_modifiers(self.civilization_id);
+        total_culture *= 1.0 + difficulty.yield_bonus;
+        self.unit_cost_multiplier = difficulty.unit_cost;
+        self.speed = civ_manager.speed;
+        
+        // Production with nowhere to go is sold off rather than wasted
+        if self.is_producing_wealth() {
+            total_gold += total_production * WEALTH_GOLD_PER_PRODUCTION;
+        }
+        
+        // Citizens eat; an unhealthy city turns less of its surplus into growth
+        total_food -= self.population as f32 * FOOD_PER_CITIZEN;
+        if total_food > 0.0 && self.health < 0.0 {
+            total_food *= UNHEALTHY_GROWTH_FACTOR;
+        }
         
         // Store calculated yields
         self.food_per_turn = total_food;
@@ -196,22 +809,99 @@ impl City {
         self.science_per_turn = total_science;
         self.gold_per_turn = total_gold;
         self.culture_per_turn = total_culture;
+        
+        // Buildings granted by wonders are free to run
+        let free_buildings = wonders.free_buildings(self.civilization_id);
+        self.maintenance_per_turn = self.buildings
+            .iter()
+            .filter(|building| !free_buildings.contains(building))
+            .map(|building| building.get_maintenance())
+            .sum::<f32>();
+        if self.has_trait(CivTrait::Organized) {
+            self.maintenance_per_turn *= ORGANIZED_MAINTENANCE;
+        }
+        self.culture_needed_for_expansion = self.culture_needed_for_expansion();
+        self.food_needed_for_growth = Self::calculate_food_needed_for_growth(self.population, self.speed);
     }
     
-    fn get_tile_yields(&self, tile: &MapTile) -> (f32, f32, f32) {
+    // What working one tile adds to this city before its percentage bonuses: the tile's
+    // own yields, the center's floor and the sea buildings' extras on water. A blockade
+    // leaves the water tiles idle. Food, production, science, gold.
+    fn worked_tile_output(&self, coord: HexCoord, tile: &MapTile) -> (f32, f32, f32, f32) {
+        let water = matches!(TerrainType::from_u8(tile.terrain), TerrainType::Ocean | TerrainType::Lake);
+        if water && self.is_blockaded {
+            return (0.0, 0.0, 0.0, 0.0);
+        }
+        let (mut food, mut production, science) = Self::get_tile_yields(tile);
+        let mut gold = Self::get_tile_gold_yield(tile);
+        if coord == self.hex_coord {
+            // The center is always worked and never yields less than the floor
+            food = food.max(CENTER_MIN_FOOD);
+            production = production.max(CENTER_MIN_PRODUCTION);
+            gold = gold.max(CENTER_MIN_GOLD);
+        }
+        
+        // Sea buildings improve worked water tiles
+        if water {
+            if self.buildings.contains(&Building::Harbor) {
+                gold += 2.0;
+            }
+            if self.buildings.contains(&Building::Lighthouse) {
+                food += 1.0;
+            }
+            if self.wonders.contains(&Wonder::Colossus) {
+                gold += 1.0;
+            }
+        }
+        (food, production, science, gold)
+    }
+    
+    // What the city's flat totals are scaled by: percentage bonuses from buildings, which
+    // stack additively, the owner's difficulty level, and unhappy citizens working less.
+    // Food, production, science, gold.
+    fn yield_multipliers(&self, civ_manager: &CivilizationManager) -> (f32, f32, f32, f32) {
+        let modifiers = self.building_modifiers();
+        let difficulty = civ_manager.difficulty_modifiers(self.civilization_id);
+        (
+            1.0 + difficulty.yield_bonus,
+            (1.0 + difficulty.yield_bonus + difficulty.production_bonus) * (1.0 - self.unhappiness_production_penalty()),
+            (1.0 + modifiers.science) * (1.0 + difficulty.yield_bonus),
+            (1.0 + modifiers.gold) * (1.0 + difficulty.yield_bonus),
+        )
+    }
+    
+    /// What one tile is worth to this city while worked, with every bonus the city's own
+    /// yields get: food, production, science, gold
+    pub fn tile_yields(&self, coord: HexCoord, tile: &MapTile, civ_manager: &CivilizationManager) -> (f32, f32, f32, f32) {
+        let (food, production, science, gold) = self.worked_tile_output(coord, tile);
+        let (food_multiplier, production_multiplier, science_multiplier, gold_multiplier) = self.yield_multipliers(civ_manager);
+        (food * food_multiplier, production * production_multiplier, science * science_multiplier, gold * gold_multiplier)
+    }
+    
+    pub fn has_trait(&self, trait_type: CivTrait) -> bool {
+        self.owner_traits.contains(&trait_type)
+    }
+    
+    // Culture for the next tile, which Expansionist and Spiritual owners need less of
+    fn culture_needed_for_expansion(&self) -> f32 {
+        let mut needed = Self::calculate_culture_needed_for_expansion(self.territory_tiles.len()) * self.speed.multiplier();
+        if self.has_trait(CivTrait::Expansionist) {
+            needed *= EXPANSIONIST_BORDER_COST;
+        }
+        if self.has_trait(CivTrait::Spiritual) {
+            needed *= SPIRITUAL_BORDER_COST;
+        }
+        needed
+    }
+    
+    pub fn get_tile_yields(tile: &MapTile) -> (f32, f32, f32) {
         let terrain = TerrainType::from_u8(tile.terrain);
         let (mut food, mut production, science) = terrain.base_yields();
         
         // Resource bonuses
-        if tile.resource != 0 {
-            match tile.resource {
-                3 => food += 2.0,        // Wheat
-                4 => food += 2.0,        // Fish
-                6 => production += 2.0,  // Wood
-                2 | 5 => production += 1.0, // Iron, Stone
-                _ => {}
-            }
-        }
+        let (resource_food, resource_production, _) = ResourceType::from_u8(tile.resource).bonus_yields();
+        food += resource_food;
+        production += resource_production;
         
         // River bonus
         if tile.has_river {
@@ -224,7 +914,7 @@ impl City {
         (food, production, science)
     }
     
-    fn get_tile_gold_yield(&self, tile: &MapTile) -> f32 {
+    pub fn get_tile_gold_yield(tile: &MapTile) -> f32 {
         let mut gold = 0.0;
         
         // Trade value from tile
@@ -236,193 +926,959 @@ impl City {
         }
         
         // Resource bonuses
-        if tile.resource != 0 {
-            match tile.resource {
-                1 => gold += 3.0,  // Gold
-                9 => gold += 2.0,  // Gems
-                13 => gold += 1.0, // Spices
-                14 => gold += 1.0, // Silk
-                _ => {}
-            }
-        }
+        gold += ResourceType::from_u8(tile.resource).bonus_yields().2;
         
         gold
     }
     
-    pub fn process_turn(&mut self, civ_manager: &mut CivilizationManager) {
+    pub fn process_turn(
+        &mut self,
+        city_entity: Entity,
+        civ_manager: &mut CivilizationManager,
+        events: &mut CityEventWriters,
+    ) {
+        // Repair battle damage
+        self.hit_points = (self.hit_points + CITY_HEAL_PER_TURN).min(CITY_MAX_HIT_POINTS);
+        
         // Add food and check for growth
         self.food_stored += self.food_per_turn;
-        if self.food_stored >= self.food_needed_for_growth {
-            self.grow_population();
+        if self.food_stored < 0.0 {
+            let shrank = self.starve();
+            events.starving.write(CityStarvingEvent { city_entity, population: self.population, shrank });
+        } else if self.food_stored >= self.food_needed_for_growth && self.happiness >= 0.0 {
+            if self.population < self.max_population() {
+                self.grow_population();
+                events.grew.write(CityGrewEvent { city_entity, population: self.population });
+            } else {
+                // Hold at the cap; the surplus has nowhere to go
+                self.food_stored = self.food_needed_for_growth;
+            }
         }
         
-        // Add culture and check for territory expansion
+        // Add culture; process_city_turns claims a tile once enough is stored
         self.culture_stored += self.culture_per_turn;
-        if self.culture_stored >= self.culture_needed_for_expansion {
-            self.expand_territory();
-        }
         
         // Process production
-        if let Some(ref production_item) = self.current_production.clone() {
-            self.production_progress += self.production_per_turn;
-            
-            let required_production = production_item.get_required_production();
-            if self.production_progress >= required_production {
-                self.complete_production(civ_manager);
+        if let Some(ref production_item) = self.current_production.clone().filter(|item| *item != ProductionItem::Wealth) {
+            // Losing the resource mid-build pauses the item; progress is kept for when it's back
+            if let Some(resource) = self.missing_resource(production_item, civ_manager) {
+                println!("{}: {} is on hold until {} is available again", self.name, production_item.get_name(), resource.get_name());
+            } else {
+                self.production_progress += self.production_rate_for(production_item);
+                
+                let required_production = self.required_production(production_item);
+                if self.production_progress >= required_production {
+                    self.complete_production(city_entity, civ_manager, events);
+                }
             }
         } else {
-            // Auto-assign production if queue has items
+            // Idle or on Wealth: move on to the queue if it has items
             if !self.production_queue.is_empty() {
-                self.current_production = Some(self.production_queue.remove(0));
-                self.production_progress = 0.0;
+                let next = self.production_queue.remove(0);
+                self.set_production(next);
             }
         }
         
         // Update civilization totals
         if let Some(civ) = civ_manager.get_civilization_mut(self.civilization_id) {
             civ.science_points += self.science_per_turn;
-            civ.gold += self.gold_per_turn;
+            civ.gold += self.gold_per_turn - self.maintenance_per_turn;
             civ.culture += self.culture_per_turn;
+            for (kind, points) in self.great_people_points() {
+                *civ.great_people_points.entry(kind).or_default() += points;
+            }
         }
     }
     
-    fn grow_population(&mut self) {
-        self.population += 1;
-        self.food_stored = 0.0;
-        self.food_needed_for_growth = Self::calculate_food_needed_for_growth(self.population);
-        
-        // Can work one more tile
-        self.assign_best_available_tile();
-        
-        println!("City {} has grown to population {}!", self.name, self.population);
+    // Great People points this city adds each turn, by type
+    pub fn great_people_points(&self) -> Vec<(GreatPersonType, f32)> {
+        let mut points = Vec::new();
+        for specialist in &self.specialists {
+            match specialist {
+                SpecialistType::Scientist => points.push((GreatPersonType::Scientist, SPECIALIST_GREAT_PEOPLE_POINTS)),
+                SpecialistType::Merchant => points.push((GreatPersonType::Merchant, SPECIALIST_GREAT_PEOPLE_POINTS)),
+                SpecialistType::Artist => {}
+            }
+        }
+        if !self.wonders.is_empty() {
+            points.push((GreatPersonType::Engineer, self.wonders.len() as f32 * WONDER_GREAT_PEOPLE_POINTS));
+        }
+        points
     }
     
-    fn expand_territory(&mut self) {
-        self.territory_radius += 1;
-        self.culture_stored = 0.0;
-        self.culture_needed_for_expansion = Self::calculate_culture_needed_for_expansion(self.territory_radius);
-        
-        // Add new tiles to territory (simplified - would need better algorithm)
-        let new_tiles = self.calculate_territory_expansion();
-        self.territory_tiles.extend(new_tiles);
+    // Grows by one pop per turn at most. Surplus beyond the threshold carries over, a
+    // Granary keeps part of the spent threshold too, and the result is capped at the new
+    // threshold so a big food stockpile can't grow the city twice in one turn.
+    fn grow_population(&mut self) {
+        let threshold = self.food_needed_for_growth;
+        let retained = threshold * self.building_modifiers().food_storage.min(1.0);
         
-        println!("City {} has expanded its territory! (Radius: {})", self.name, self.territory_radius);
+        self.population += 1;
+        self.food_needed_for_growth = Self::calculate_food_needed_for_growth(self.population, self.speed);
+        self.food_stored = (self.food_stored - threshold + retained).min(self.food_needed_for_growth);
     }
     
-    fn calculate_territory_expansion(&self) -> Vec<HexCoord> {
-        // This is a simplified expansion - in a full game you'd want more sophisticated territory calculation
-        let mut new_tiles = Vec::new();
-        
-        // Add tiles at the new radius distance
-        for existing_tile in &self.territory_tiles {
-            for neighbor in existing_tile.neighbors() {
-                if !self.territory_tiles.contains(&neighbor) && !new_tiles.contains(&neighbor) {
-                    let distance = self.hex_distance(neighbor, self.hex_coord);
-                    if distance <= self.territory_radius as i32 {
-                        new_tiles.push(neighbor);
-                    }
-                }
-            }
+    // Out of stored food: lose a citizen and the tile they worked. A size 1 city
+    // just stays hungry rather than starving to nothing. True if it shrank.
+    fn starve(&mut self) -> bool {
+        self.food_stored = 0.0;
+        if self.population <= 1 {
+            return false;
         }
         
-        new_tiles
+        self.population -= 1;
+        self.food_needed_for_growth = Self::calculate_food_needed_for_growth(self.population, self.speed);
+        self.specialists.truncate(self.population as usize);
+        let tile_workers = self.population as usize - self.specialists.len();
+        self.worked_tiles.truncate(tile_workers + 1); // Center plus one per tile-working citizen
+        true
     }
     
-    fn assign_best_available_tile(&mut self) {
-        // Find the best unworked tile in territory
-        let mut best_tile = None;
-        let mut best_value = 0.0;
-        
-        for &tile_coord in &self.territory_tiles {
-            if !self.worked_tiles.contains(&tile_coord) {
-                // Would need tile_query access here - simplified for now
-                let estimated_value = 3.0; // Placeholder
-                if estimated_value > best_value {
-                    best_value = estimated_value;
-                    best_tile = Some(tile_coord);
-                }
-            }
-        }
-        
-        if let Some(tile) = best_tile {
-            self.worked_tiles.push(tile);
-        }
+    // Resistance and razing both stop the city working: no tiles, no yields, no production
+    pub fn is_occupied(&self) -> bool {
+        self.is_razing || self.resistance_turns > 0
     }
     
-    fn complete_production(&mut self, _civ_manager: &mut CivilizationManager) {
-        if let Some(item) = self.current_production.take() {
-            match item {
-                ProductionItem::Building(building) => {
-                    self.buildings.push(building);
-                    println!("City {} completed building: {:?}", self.name, building);
-                }
-                ProductionItem::Unit(unit_type) => {
-                    // Would spawn unit entity here
-                    println!("City {} completed unit: {:?}", self.name, unit_type);
-                }
-                ProductionItem::Wonder(wonder) => {
-                    // Apply wonder effects
-                    println!("City {} completed wonder: {:?}", self.name, wonder);
-                }
-            }
-            
-            self.production_progress = 0.0;
-            
-            // Start next item in queue if available
-            if !self.production_queue.is_empty() {
-                self.current_production = Some(self.production_queue.remove(0));
-            }
-        }
+    // Nothing underway or queued in a city able to build: time to pick something. The
+    // production prompt and the AI both go by this; Wealth counts as a pick.
+    pub fn needs_production(&self) -> bool {
+        self.current_production.is_none() && self.production_queue.is_empty() && !self.is_occupied()
     }
     
-    fn hex_distance(&self, a: HexCoord, b: HexCoord) -> i32 {
-        let dq = (a.q - b.q).abs();
-        let dr = (a.r - b.r).abs();
-        let ds = (-(a.q + a.r) + (b.q + b.r)).abs();
-        (dq.max(dr)).max(ds)
+    // Whether this turn's production is going into gold, chosen or for want of anything else
+    pub fn is_producing_wealth(&self) -> bool {
+        matches!(self.current_production, None | Some(ProductionItem::Wealth)) && self.production_queue.is_empty()
     }
     
-    pub fn can_build(&self, item: &ProductionItem) -> bool {
-        match item {
+    // Gold the city would make by putting its production into Wealth
+    pub fn wealth_gold(&self) -> f32 {
+        self.production_per_turn * WEALTH_GOLD_PER_PRODUCTION
+    }
+    
+    // Original capitals can't be razed
+    pub fn can_be_razed(&self) -> bool {
+        !self.is_original_capital
+    }
+    
+    // Called when the city changes hands. Retaking one of our own cities is a liberation,
+    // not a conquest, so it cooperates straight away.
+    pub fn begin_occupation(&mut self, new_civilization_id: u32) {
+        self.is_razing = false;
+        self.resistance_turns = if new_civilization_id == self.original_civilization_id {
+            0
+        } else {
+            self.population * RESISTANCE_TURNS_PER_POPULATION
+        };
+    }
+    
+    // The player's annex-or-raze choice for a city they conquered
+    pub fn decide_capture(&mut self, raze: bool) {
+        self.awaiting_capture_decision = false;
+        if raze && self.can_be_razed() {
+            self.is_razing = true;
+            self.resistance_turns = 0;
+            self.current_production = None;
+            self.production_queue.clear();
+            println!("{} will be razed", self.name);
+        } else {
+            println!("{} has been annexed and resists for {} turns", self.name, self.resistance_turns);
+        }
+    }
+    
+    // Turn of a city in resistance or being razed. Razing takes a citizen away; the
+    // city is removed by destroy_razed_cities once nobody is left.
+    fn occupied_turn(&mut self) {
+        self.hit_points = (self.hit_points + CITY_HEAL_PER_TURN).min(CITY_MAX_HIT_POINTS);
+        self.worked_tiles = vec![self.hex_coord];
+        self.food_per_turn = 0.0;
+        self.production_per_turn = 0.0;
+        self.science_per_turn = 0.0;
+        self.gold_per_turn = 0.0;
+        self.culture_per_turn = 0.0;
+        self.trade_gold_per_turn = 0.0;
+        self.maintenance_per_turn = 0.0;
+        
+        if self.is_razing {
+            self.population = self.population.saturating_sub(1);
+            self.specialists.truncate(self.population as usize);
+        } else {
+            self.resistance_turns -= 1;
+        }
+    }
+    
+    // Largest size the city can grow to with its current buildings
+    pub fn max_population(&self) -> u32 {
+        if self.buildings.contains(&Building::Aqueduct) {
+            u32::MAX
+        } else {
+            MAX_POPULATION_WITHOUT_AQUEDUCT
+        }
+    }
+    
+    // Claim the single best unowned tile bordering the territory
+    pub fn expand_territory(
+        &mut self,
+        city_entity: Entity,
+        tiles: &TileLookup,
+        territory: &mut TerritoryMap,
+        events: &mut EventWriter<TerritoryExpandedEvent>,
+    ) {
+        self.culture_stored -= self.culture_needed_for_expansion;
+        
+        let owned: HashSet<HexCoord> = self.territory_tiles.iter().copied().collect();
+        let mut candidates: Vec<(HexCoord, f32)> = owned
+            .iter()
+            .flat_map(|coord| coord.neighbors())
+            .filter(|coord| !owned.contains(coord) && !territory.is_owned(*coord))
+            .collect::<HashSet<HexCoord>>()
+            .into_iter()
+            .filter(|coord| coord.distance(self.hex_coord) <= MAX_TERRITORY_RADIUS)
+            .filter_map(|coord| tiles.get(coord).map(|tile| (coord, self.expansion_score(coord, tile))))
+            .collect();
+        
+        // Highest score first; ties broken by coordinate so expansion is stable
+        candidates.sort_by(|a, b| {
+            b.1.total_cmp(&a.1).then((a.0.q, a.0.r).cmp(&(b.0.q, b.0.r)))
+        });
+        
+        let Some(&(new_tile, _)) = candidates.first() else {
+            self.culture_stored = 0.0;
+            return; // Hemmed in by neighbours or the map edge
+        };
+        
+        territory.claim(new_tile, city_entity, self.civilization_id);
+        self.territory_tiles.push(new_tile);
+        self.territory_radius = self.territory_radius.max(new_tile.distance(self.hex_coord) as u32);
+        self.culture_needed_for_expansion = self.culture_needed_for_expansion();
+        
+        events.write(TerritoryExpandedEvent { city_entity, tile: new_tile });
+    }
+    
+    // How attractive a tile is to claim: yields, resources, then closeness
+    fn expansion_score(&self, coord: HexCoord, tile: &MapTile) -> f32 {
+        let resource = ResourceType::from_u8(tile.resource);
+        let mut score = Self::tile_score(tile, CityFocus::Default);
+        if resource.is_luxury() {
+            score += 3.0;
+        } else if resource.is_strategic() {
+            score += 2.5;
+        } else if resource != ResourceType::None {
+            score += 1.5;
+        }
+        score - coord.distance(self.hex_coord) as f32 * 1.5
+    }
+    
+    // Work the city center plus the best `population` tiles this city owns and no other
+    // city works. Re-run whenever population, territory or tile yields change. Tiles the
+    // player picked by hand stay worked while the city still holds them; citizens they
+    // haven't placed, new ones or ones whose tile was lost, go where the governor would put them.
+    pub fn assign_worked_tiles(
+        &mut self,
+        city_entity: Entity,
+        tiles: &TileLookup,
+        territory: &TerritoryMap,
+        taken: &HashSet<HexCoord>,
+    ) {
+        let mut candidates: Vec<(HexCoord, f32)> = self.territory_tiles
+            .iter()
+            .filter(|coord| **coord != self.hex_coord && !taken.contains(*coord))
+            .filter(|coord| territory.owner(**coord).is_some_and(|owner| owner.city == city_entity))
+            .filter_map(|coord| tiles.get(*coord).map(|tile| (*coord, Self::tile_score(tile, self.focus))))
+            .collect();
+        
+        // Highest score first; ties broken by coordinate so assignment is stable
+        candidates.sort_by(|a, b| {
+            b.1.total_cmp(&a.1).then((a.0.q, a.0.r).cmp(&(b.0.q, b.0.r)))
+        });
+        
+        self.clamp_specialists();
+        if !self.specialists_locked && !self.tiles_locked {
+            self.assign_specialists(&candidates, tiles);
+        }
+        
+        let tile_workers = (self.population as usize).saturating_sub(self.specialists.len());
+        if !self.tiles_locked {
+            self.worked_tiles = std::iter::once(self.hex_coord)
+                .chain(candidates.into_iter().take(tile_workers).map(|(coord, _)| coord))
+                .collect();
+            return;
+        }
+        
+        self.idle_citizens = self.idle_citizens.min(tile_workers as u32);
+        let placed = tile_workers - self.idle_citizens as usize;
+        let kept: Vec<HexCoord> = self.worked_tiles
+            .iter()
+            .copied()
+            .filter(|coord| candidates.iter().any(|(candidate, _)| candidate == coord))
+            .take(placed)
+            .collect();
+        let filled: Vec<HexCoord> = candidates
+            .into_iter()
+            .map(|(coord, _)| coord)
+            .filter(|coord| !kept.contains(coord))
+            .take(placed - kept.len())
+            .collect();
+        self.worked_tiles = std::iter::once(self.hex_coord).chain(kept).chain(filled).collect();
+    }
+    
+    /// The tiles the governor would work under the current focus, to set beside the ones
+    /// the player picked
+    pub fn governor_tiles(
+        &self,
+        city_entity: Entity,
+        tiles: &TileLookup,
+        territory: &TerritoryMap,
+        taken: &HashSet<HexCoord>,
+    ) -> Vec<HexCoord> {
+        let mut governed = self.clone();
+        governed.release_tiles();
+        governed.assign_worked_tiles(city_entity, tiles, territory, taken);
+        governed.worked_tiles
+    }
+    
+    // Take the citizen off a worked tile, leaving them idle until they're placed again.
+    // From then on the player picks the tiles.
+    pub fn unwork_tile(&mut self, coord: HexCoord) -> bool {
+        if coord == self.hex_coord {
+            return false;
+        }
+        let Some(index) = self.worked_tiles.iter().position(|worked| *worked == coord) else { return false };
+        self.worked_tiles.remove(index);
+        self.idle_citizens += 1;
+        self.tiles_locked = true;
+        true
+    }
+    
+    // Put an idle citizen to work on one of the city's tiles; false if nobody is idle
+    pub fn work_tile(&mut self, coord: HexCoord) -> bool {
+        if self.idle_citizens == 0 || self.worked_tiles.contains(&coord) || !self.territory_tiles.contains(&coord) {
+            return false;
+        }
+        self.worked_tiles.push(coord);
+        self.idle_citizens -= 1;
+        self.tiles_locked = true;
+        true
+    }
+    
+    // Hand the tiles back to the governor
+    pub fn release_tiles(&mut self) {
+        self.tiles_locked = false;
+        self.idle_citizens = 0;
+    }
+    
+    // Governor: give citizens specialist jobs where those beat the tiles still free,
+    // then hand jobs back to tiles while the city would go hungry
+    fn assign_specialists(&mut self, ranked_tiles: &[(HexCoord, f32)], tiles: &TileLookup) {
+        self.specialists.clear();
+        let mut open_slots: Vec<SpecialistType> = SpecialistType::ALL
+            .into_iter()
+            .flat_map(|specialist| std::iter::repeat_n(specialist, self.specialist_slots(specialist) as usize))
+            .collect();
+        open_slots.sort_by(|a, b| b.score(self.focus).total_cmp(&a.score(self.focus)));
+        
+        let mut next_tile = 0;
+        for _ in 0..self.population {
+            let tile_score = ranked_tiles.get(next_tile).map(|(_, score)| *score);
+            match open_slots.first() {
+                Some(specialist) if tile_score.is_none_or(|score| specialist.score(self.focus) > score) => {
+                    self.specialists.push(open_slots.remove(0));
+                }
+                _ => next_tile += 1,
+            }
+        }
+        
+        let food_of = |count: usize| -> f32 {
+            std::iter::once(self.hex_coord)
+                .chain(ranked_tiles.iter().take(count).map(|(coord, _)| *coord))
+                .filter_map(|coord| tiles.get(coord))
+                .map(|tile| Self::get_tile_yields(tile).0)
+                .sum::<f32>()
+                - self.population as f32 * FOOD_PER_CITIZEN
+        };
+        while !self.specialists.is_empty() && food_of(next_tile) < 0.0 && next_tile < ranked_tiles.len() {
+            self.specialists.pop();
+            next_tile += 1;
+        }
+    }
+    
+    // Specialist slots the city's buildings provide for `specialist`
+    pub fn specialist_slots(&self, specialist: SpecialistType) -> u32 {
+        self.buildings
+            .iter()
+            .filter_map(|building| building.specialist_slots())
+            .filter(|(slot_type, _)| *slot_type == specialist)
+            .map(|(_, slots)| slots)
+            .sum()
+    }
+    
+    pub fn specialist_count(&self, specialist: SpecialistType) -> u32 {
+        self.specialists.iter().filter(|assigned| **assigned == specialist).count() as u32
+    }
+    
+    // Move a citizen from a tile into a specialist slot; false if none is free
+    pub fn add_specialist(&mut self, specialist: SpecialistType) -> bool {
+        if self.specialist_count(specialist) >= self.specialist_slots(specialist)
+            || self.specialists.len() >= self.population as usize
+        {
+            return false;
+        }
+        self.specialists.push(specialist);
+        self.specialists_locked = true;
+        true
+    }
+    
+    pub fn remove_specialist(&mut self, specialist: SpecialistType) {
+        if let Some(index) = self.specialists.iter().position(|assigned| *assigned == specialist) {
+            self.specialists.remove(index);
+            self.specialists_locked = true;
+        }
+    }
+    
+    // Drop specialists whose slots or citizens are gone
+    fn clamp_specialists(&mut self) {
+        for specialist in SpecialistType::ALL {
+            while self.specialist_count(specialist) > self.specialist_slots(specialist) {
+                if let Some(index) = self.specialists.iter().position(|assigned| *assigned == specialist) {
+                    self.specialists.remove(index);
+                }
+            }
+        }
+        self.specialists.truncate(self.population as usize);
+    }
+    
+    // How much a citizen working this tile is worth under the given focus
+    pub fn tile_score(tile: &MapTile, focus: CityFocus) -> f32 {
+        let (food, production, science) = Self::get_tile_yields(tile);
+        let gold = Self::get_tile_gold_yield(tile);
+        let (food_weight, production_weight, gold_weight, science_weight) = focus.weights();
+        food * food_weight + production * production_weight + gold * gold_weight + science * science_weight
+    }
+    
+    fn complete_production(
+        &mut self,
+        city_entity: Entity,
+        _civ_manager: &mut CivilizationManager,
+        events: &mut CityEventWriters,
+    ) {
+        if let Some(item) = self.current_production.take() {
+            // Surplus hammers carry over, but never finish more than the next item
+            let overflow = (self.production_progress - self.required_production(&item)).max(0.0);
+            self.place_item(item, city_entity, events);
+            
+            self.production_progress = overflow;
+            
+            // Start next item in queue if available
+            if !self.production_queue.is_empty() {
+                let next = self.production_queue.remove(0);
+                self.set_production(next);
+            }
+        }
+    }
+    
+    // Completes the current item outright, as a Great Engineer does; returns what was finished
+    pub fn finish_production(
+        &mut self,
+        city_entity: Entity,
+        civ_manager: &mut CivilizationManager,
+        events: &mut CityEventWriters,
+    ) -> Option<ProductionItem> {
+        let item = self.current_production.clone().filter(|item| *item != ProductionItem::Wealth)?;
+        self.production_progress = self.production_progress.max(self.required_production(&item));
+        self.complete_production(city_entity, civ_manager, events);
+        Some(item)
+    }
+    
+    // Put a finished item into the world
+    fn place_item(&mut self, item: ProductionItem, city_entity: Entity, events: &mut CityEventWriters) {
+        match item {
             ProductionItem::Building(building) => {
-                !self.buildings.contains(building) && self.meets_building_requirements(building)
+                if !self.buildings.contains(&building) {
+                    self.buildings.push(building);
+                }
+                events.production_completed.write(ProductionCompletedEvent { city_entity, item });
+            }
+            ProductionItem::Unit(unit_type) => {
+                // Spawned by spawn_produced_units, which has world access
+                events.units_produced.write(UnitProduced { city_entity, unit_type });
+                events.production_completed.write(ProductionCompletedEvent { city_entity, item });
+            }
+            ProductionItem::Wonder(wonder) => {
+                // Claimed in the WonderRegistry by wonder_system, which settles races
+                // and announces the winner
+                self.wonders.push(wonder);
             }
-            ProductionItem::Unit(_) => true, // Can always build units if you have resources
-            ProductionItem::Wonder(wonder) => self.meets_wonder_requirements(wonder),
+            ProductionItem::Wealth => {} // Never finished
         }
     }
     
-    fn meets_building_requirements(&self, building: &Building) -> bool {
-        match building {
-            Building::Harbor | Building::Lighthouse => {
-                // Requires coastal city - would need tile query to check
-                true // Simplified
+    // Gold needed to finish `item` right now, counting progress if it's the current item.
+    // None for things gold can't buy.
+    pub fn purchase_cost(&self, item: &ProductionItem) -> Option<f32> {
+        if matches!(item, ProductionItem::Wonder(_) | ProductionItem::Wealth) {
+            return None;
+        }
+        let progress = if self.current_production.as_ref() == Some(item) { self.production_progress } else { 0.0 };
+        Some((self.required_production(item) - progress).max(0.0) * GOLD_PER_PRODUCTION)
+    }
+    
+    // Finish `item` immediately for gold from the civ's treasury
+    pub fn buy_with_gold(
+        &mut self,
+        item: ProductionItem,
+        city_entity: Entity,
+        civ_manager: &mut CivilizationManager,
+        events: &mut CityEventWriters,
+    ) -> Result<f32, &'static str> {
+        let Some(cost) = self.purchase_cost(&item) else {
+            return Err("wonders can't be bought");
+        };
+        if let Some(resource) = self.missing_resource(&item, civ_manager) {
+            println!("Can't buy {} without {}", item.get_name(), resource.get_name());
+            return Err("a strategic resource is missing");
+        }
+        if let ProductionItem::Unit(unit_type) = &item
+            && self.unit_requirement_failure(unit_type).is_some()
+        {
+            return Err("no water next to the city to launch it");
+        }
+        
+        let Some(civ) = civ_manager.get_civilization_mut(self.civilization_id) else {
+            return Err("no owning civilization");
+        };
+        if civ.gold < cost {
+            return Err("not enough gold");
+        }
+        civ.gold -= cost;
+        
+        if self.current_production.as_ref() == Some(&item) {
+            self.current_production = None;
+            self.production_progress = 0.0;
+            if !self.production_queue.is_empty() {
+                let next = self.production_queue.remove(0);
+                self.set_production(next);
+            }
+        }
+        self.place_item(item, city_entity, events);
+        Ok(cost)
+    }
+    
+    // Swap a queued item with its neighbour; `up` moves it toward the front
+    pub fn move_queued(&mut self, index: usize, up: bool) {
+        let Some(target) = (if up { index.checked_sub(1) } else { index.checked_add(1) }) else { return };
+        if index < self.production_queue.len() && target < self.production_queue.len() {
+            self.production_queue.swap(index, target);
+        }
+    }
+    
+    pub fn remove_queued(&mut self, index: usize) {
+        if index < self.production_queue.len() {
+            self.production_queue.remove(index);
+        }
+    }
+    
+    pub fn can_build(&self, item: &ProductionItem, wonders: &WonderRegistry, civ_manager: &CivilizationManager) -> bool {
+        if self.missing_tech(item, civ_manager).is_some() {
+            return false;
+        }
+        
+        // Buildings and wonders only once, including ones already underway
+        let already_planned = self.current_production.as_ref() == Some(item)
+            || self.production_queue.contains(item);
+        if already_planned && !matches!(item, ProductionItem::Unit(_)) {
+            return false;
+        }
+        
+        match item {
+            ProductionItem::Building(building) => {
+                !self.buildings.contains(building) && self.building_requirement_failure(building).is_none()
+            }
+            ProductionItem::Unit(unit_type) => {
+                self.missing_resource(item, civ_manager).is_none() && self.unit_requirement_failure(unit_type).is_none()
+            }
+            ProductionItem::Wonder(wonder) => !wonders.is_built(*wonder),
+            ProductionItem::Wealth => true,
+        }
+    }
+    
+    // The technology the owner still needs before this city can produce `item`
+    pub fn missing_tech(&self, item: &ProductionItem, civ_manager: &CivilizationManager) -> Option<Technology> {
+        let tech = item.required_tech()?;
+        let known = civ_manager
+            .get_civilization(self.civilization_id)
+            .is_some_and(|civ| civ.has_technology(tech));
+        if known { None } else { Some(tech) }
+    }
+    
+    // The strategic resource the owner lacks for `item`, if it needs one
+    pub fn missing_resource(&self, item: &ProductionItem, civ_manager: &CivilizationManager) -> Option<ResourceType> {
+        let ProductionItem::Unit(unit_type) = item else { return None };
+        let resource = unit_type.required_resource()?;
+        let available = civ_manager
+            .get_civilization(self.civilization_id)
+            .is_some_and(|civ| civ.has_resource(resource));
+        if available { None } else { Some(resource) }
+    }
+    
+    // Start building `item` now; progress on the previous item is discarded, while
+    // overflow from a finished item is kept up to the new item's cost
+    pub fn set_production(&mut self, item: ProductionItem) {
+        let building = self.current_production.as_ref().is_some_and(|current| *current != ProductionItem::Wealth);
+        self.production_progress = if building {
+            0.0
+        } else {
+            self.production_progress.min(self.required_production(&item))
+        };
+        self.current_production = Some(item);
+    }
+    
+    pub fn queue_production(&mut self, item: ProductionItem) {
+        if matches!(self.current_production, None | Some(ProductionItem::Wealth)) {
+            self.set_production(item);
+        } else {
+            self.production_queue.push(item);
+        }
+    }
+    
+    // Drop the current item without refund and move on to the next queued one
+    pub fn cancel_production(&mut self) {
+        self.current_production = None;
+        self.production_progress = 0.0;
+        if !self.production_queue.is_empty() {
+            self.current_production = Some(self.production_queue.remove(0));
+        }
+    }
+    
+    // Net happiness from buildings, luxuries and the capital, minus population, the
+    // empire's captured cities and its war weariness. Below zero the city stops growing.
+    pub fn calculate_happiness(&mut self, tiles: &TileLookup, captured_cities_in_empire: usize, war_weariness: f32) {
+        let luxuries: HashSet<u8> = self.territory_tiles
+            .iter()
+            .filter_map(|coord| tiles.get(*coord))
+            .filter(|tile| ResourceType::from_u8(tile.resource).is_luxury())
+            .map(|tile| tile.resource)
+            .collect();
+        
+        let mut happiness = BASE_HAPPINESS + luxuries.len() as f32 * LUXURY_HAPPINESS;
+        for building in &self.buildings {
+            happiness += building.get_happiness();
+        }
+        if self.is_capital {
+            happiness += CAPITAL_HAPPINESS;
+        }
+        happiness -= self.population as f32 * POPULATION_UNHAPPINESS;
+        happiness -= captured_cities_in_empire as f32 * CAPTURED_CITY_UNHAPPINESS;
+        happiness -= war_weariness * WAR_WEARINESS_UNHAPPINESS;
+        
+        self.happiness = happiness;
+    }
+    
+    // Net health: fresh water and buildings against crowding. Below zero growth slows.
+    pub fn calculate_health(&mut self, tiles: &TileLookup) {
+        let center_has_river = tiles.get(self.hex_coord).is_some_and(|tile| tile.has_river);
+        
+        let mut health = BASE_HEALTH;
+        if center_has_river || has_freshwater_nearby(self.hex_coord, |coord| tiles.get(coord)) {
+            health += FRESHWATER_HEALTH;
+        }
+        for building in &self.buildings {
+            health += building.get_health();
+        }
+        health -= self.population as f32 * POPULATION_UNHEALTHINESS;
+        
+        self.health = health;
+    }
+    
+    // Share of production lost to unhappiness
+    pub fn unhappiness_production_penalty(&self) -> f32 {
+        if self.happiness >= 0.0 {
+            return 0.0;
+        }
+        (-self.happiness * UNHAPPY_PRODUCTION_PENALTY).min(MAX_UNHAPPY_PRODUCTION_PENALTY)
+    }
+    
+    pub fn is_in_unrest(&self) -> bool {
+        self.happiness <= UNREST_THRESHOLD
+    }
+    
+    pub fn happiness_status(&self) -> &'static str {
+        if self.is_in_unrest() {
+            "Unrest"
+        } else if self.happiness < 0.0 {
+            "Unhappy"
+        } else {
+            "Content"
+        }
+    }
+    
+    // Strength of the city itself once its garrison is gone, scaled down by damage
+    pub fn combat_strength(&self) -> u32 {
+        let strength = self.defense_strength + self.population as f32 * 0.5;
+        let health_ratio = self.hit_points as f32 / CITY_MAX_HIT_POINTS as f32;
+        ((strength * health_ratio).round() as u32).max(1)
+    }
+    
+    // Defense from the city itself, its best defender (`garrison`), buildings and the site
+    pub fn calculate_defense(&self, garrison: u32, tiles: &TileLookup) -> CityDefense {
+        let site = tiles.get(self.hex_coord);
+        let on_hills = site.is_some_and(|tile| tile.elevation_raw > 0.3);
+        let by_river = std::iter::once(self.hex_coord)
+            .chain(self.hex_coord.neighbors())
+            .any(|coord| tiles.get(coord).is_some_and(|tile| tile.has_river));
+        
+        let mut terrain = 0.0;
+        if on_hills {
+            terrain += CITY_HILLS_DEFENSE;
+        }
+        if by_river {
+            terrain += CITY_RIVER_DEFENSE;
+        }
+        
+        CityDefense {
+            base: CITY_BASE_DEFENSE,
+            garrison: garrison as f32,
+            buildings: self.buildings.iter().map(|building| building.get_defense()).sum(),
+            terrain,
+        }
+    }
+    
+    // Defense multiplier for units garrisoned in the city
+    pub fn garrison_defense_modifier(&self) -> f32 {
+        if self.buildings.contains(&Building::Walls) { 1.5 } else { 1.25 }
+    }
+    
+    // Apply battle damage; `floor` keeps the city above zero for attackers that can't capture
+    pub fn take_damage(&mut self, damage: u32, floor: u32) {
+        self.hit_points = self.hit_points.saturating_sub(damage).max(floor);
+    }
+    
+    // Gold left over once buildings are paid for
+    pub fn net_gold_per_turn(&self) -> f32 {
+        self.gold_per_turn - self.maintenance_per_turn
+    }
+    
+    // Bankruptcy: sell the building that costs least to run (then least to build),
+    // skipping ones granted by wonders. Returns the building and the gold it fetched.
+    pub fn sell_cheapest_building(&mut self, wonders: &WonderRegistry) -> Option<(Building, f32)> {
+        let free_buildings = wonders.free_buildings(self.civilization_id);
+        let (index, building) = self.buildings
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(|(_, building)| !free_buildings.contains(building))
+            .min_by(|(_, a), (_, b)| {
+                a.get_maintenance().total_cmp(&b.get_maintenance()).then(
+                    ProductionItem::Building(*a).get_required_production(self.speed)
+                        .total_cmp(&ProductionItem::Building(*b).get_required_production(self.speed)),
+                )
+            })?;
+        
+        self.buildings.remove(index);
+        let sale_price = ProductionItem::Building(building).get_required_production(self.speed) * BUILDING_SALE_FRACTION;
+        Some((building, sale_price))
+    }
+    
+    // How many trade routes the city can run at once
+    pub fn max_trade_routes(&self) -> usize {
+        let mut routes = 1;
+        if self.has_trait(CivTrait::Commercial) {
+            routes += COMMERCIAL_EXTRA_TRADE_ROUTES;
+        }
+        if self.buildings.contains(&Building::Marketplace) {
+            routes += 1;
+        }
+        if self.buildings.contains(&Building::Lighthouse) && !self.is_blockaded {
+            routes += 2;
+        }
+        routes
+    }
+    
+    // Gold per turn a route to `destination` brings in. Bigger and farther destinations pay
+    // more, foreign ones most. Distance is straight-line until roads and harbors exist.
+    pub fn trade_route_gold(&self, destination: &City) -> f32 {
+        let distance = self.hex_coord.distance(destination.hex_coord) as f32;
+        let mut gold = 1.0 + destination.population as f32 * 0.5 + distance * 0.2;
+        if self.has_trait(CivTrait::Commercial) {
+            gold += COMMERCIAL_ROUTE_GOLD;
+        }
+        if destination.civilization_id != self.civilization_id {
+            gold * 1.5
+        } else {
+            gold
+        }
+    }
+    
+    // Whether a new route to `destination` could be opened right now
+    pub fn can_trade_with(
+        &self,
+        city_entity: Entity,
+        destination_entity: Entity,
+        destination: &City,
+        civ_manager: &CivilizationManager,
+    ) -> bool {
+        destination_entity != city_entity
+            && !self.trade_routes.contains(&destination_entity)
+            && self.trade_routes.len() < self.max_trade_routes()
+            && self.hex_coord.distance(destination.hex_coord) <= TRADE_ROUTE_RANGE
+            && !are_enemies(self.civilization_id, destination.civilization_id, civ_manager)
+    }
+    
+    // Combined percentage bonuses of every building in the city
+    pub fn building_modifiers(&self) -> BuildingModifiers {
+        let mut modifiers = BuildingModifiers::default();
+        for building in &self.buildings {
+            modifiers.add(building.get_modifiers());
+        }
+        modifiers
+    }
+    
+    // Production this city needs to finish `item`, after the owner's difficulty and trait discounts on units
+    pub fn required_production(&self, item: &ProductionItem) -> f32 {
+        match item {
+            ProductionItem::Unit(unit_type) => {
+                let mut cost = item.get_required_production(self.speed) * self.unit_cost_multiplier;
+                if *unit_type == UnitType::Settler && self.has_trait(CivTrait::Expansionist) {
+                    cost *= EXPANSIONIST_SETTLER_COST;
+                }
+                if unit_type.get_stats().can_attack && self.has_trait(CivTrait::Militaristic) {
+                    cost *= MILITARISTIC_UNIT_COST;
+                }
+                cost
+            }
+            _ => item.get_required_production(self.speed),
+        }
+    }
+    
+    // Production per turn applied to `item`, including bonuses that only apply to some items
+    pub fn production_rate_for(&self, item: &ProductionItem) -> f32 {
+        match item {
+            ProductionItem::Building(_) => {
+                let mut bonus = self.building_modifiers().building_production;
+                if self.has_trait(CivTrait::Industrious) {
+                    bonus += INDUSTRIOUS_BUILDING_PRODUCTION;
+                }
+                self.production_per_turn * (1.0 + bonus)
             }
-            Building::Aqueduct => self.population >= 4,
-            _ => true,
+            _ => self.production_per_turn,
+        }
+    }
+    
+    // Turns until `remaining` production on `item` is done, None if it never will be
+    pub fn turns_for(&self, item: &ProductionItem, remaining: f32) -> Option<u32> {
+        let rate = self.production_rate_for(item);
+        if rate <= 0.0 {
+            return None;
+        }
+        Some((remaining.max(0.0) / rate).ceil() as u32)
+    }
+    
+    // Why `building` can't be built here, or None if its requirements are met
+    pub fn building_requirement_failure(&self, building: &Building) -> Option<&'static str> {
+        match building {
+            Building::Harbor | Building::Lighthouse if !self.is_coastal => Some("requires a coastal city"),
+            Building::Aqueduct if self.population < 4 => Some("requires population 4"),
+            _ => None,
         }
     }
     
-    fn meets_wonder_requirements(&self, _wonder: &Wonder) -> bool {
-        // Would check for required technologies, resources, etc.
-        true // Simplified
+    // Why `unit_type` can't be built here: ships need a coastal city to launch from
+    pub fn unit_requirement_failure(&self, unit_type: &UnitType) -> Option<&'static str> {
+        if unit_type.get_stats().naval_unit && !self.is_coastal {
+            Some("requires a coastal city")
+        } else {
+            None
+        }
     }
 }
 
 impl Building {
+    pub const ALL: [Building; 10] = [
+        Building::Granary, Building::Barracks, Building::Library, Building::Marketplace,
+        Building::Temple, Building::Walls, Building::Aqueduct, Building::Workshop,
+        Building::Harbor, Building::Lighthouse,
+    ];
+    
+    pub fn required_tech(&self) -> Option<Technology> {
+        match self {
+            Building::Granary => Some(Technology::Pottery),
+            Building::Barracks => None,
+            Building::Library => Some(Technology::Writing),
+            Building::Marketplace => Some(Technology::Currency),
+            Building::Temple => None,
+            Building::Walls => Some(Technology::Masonry),
+            Building::Aqueduct => Some(Technology::Construction),
+            Building::Workshop => Some(Technology::TheWheel),
+            Building::Harbor => Some(Technology::Pottery),
+            Building::Lighthouse => Some(Technology::Masonry),
+        }
+    }
+    
     pub fn get_yields(&self) -> (f32, f32, f32, f32, f32) {
         // Returns (food, production, science, gold, culture)
+        rules().building(*self).yields
+    }
+    
+    pub fn get_happiness(&self) -> f32 {
+        rules().building(*self).happiness
+    }
+    
+    pub fn specialist_slots(&self) -> Option<(SpecialistType, u32)> {
+        match self {
+            Building::Library => Some((SpecialistType::Scientist, 2)),
+            Building::Marketplace => Some((SpecialistType::Merchant, 2)),
+            Building::Temple => Some((SpecialistType::Artist, 1)),
+            _ => None,
+        }
+    }
+    
+    // Gold per turn to keep the building running
+    pub fn get_maintenance(&self) -> f32 {
+        rules().building(*self).maintenance
+    }
+    
+    // Added to the city's defense strength
+    pub fn get_defense(&self) -> f32 {
+        rules().building(*self).defense
+    }
+    
+    pub fn get_health(&self) -> f32 {
+        rules().building(*self).health
+    }
+    
+    pub fn get_modifiers(&self) -> BuildingModifiers {
+        rules().building(*self).modifiers
+    }
+    
+    // Built-in numbers, used when no rules file overrides them
+    pub fn default_rules(&self) -> BuildingRules {
+        let rules = |cost: f32, maintenance: f32, yields: (f32, f32, f32, f32, f32)| BuildingRules {
+            cost,
+            maintenance,
+            yields,
+            ..Default::default()
+        };
         match self {
-            Building::Granary => (2.0, 0.0, 0.0, 0.0, 0.0),
-            Building::Barracks => (0.0, 0.0, 0.0, 0.0, 0.0),
-            Building::Library => (0.0, 0.0, 2.0, 0.0, 0.0),
-            Building::Marketplace => (0.0, 0.0, 0.0, 2.0, 0.0),
-            Building::Temple => (0.0, 0.0, 0.0, 0.0, 2.0),
-            Building::Walls => (0.0, 0.0, 0.0, 0.0, 0.0),
-            Building::Aqueduct => (0.0, 0.0, 0.0, 0.0, 0.0),
-            Building::Workshop => (0.0, 1.0, 0.0, 0.0, 0.0),
-            Building::Harbor => (1.0, 0.0, 0.0, 2.0, 0.0),
-            Building::Lighthouse => (1.0, 0.0, 0.0, 0.0, 0.0),
+            Building::Granary => BuildingRules {
+                modifiers: BuildingModifiers { food_storage: 0.25, ..Default::default() },
+                ..rules(60.0, 1.0, (2.0, 0.0, 0.0, 0.0, 0.0))
+            },
+            Building::Barracks => BuildingRules { defense: 1.0, ..rules(60.0, 1.0, (0.0, 0.0, 0.0, 0.0, 0.0)) },
+            Building::Library => BuildingRules {
+                modifiers: BuildingModifiers { science: 0.25, ..Default::default() },
+                ..rules(90.0, 1.0, (0.0, 0.0, 2.0, 0.0, 0.0))
+            },
+            Building::Marketplace => BuildingRules {
+                modifiers: BuildingModifiers { gold: 0.25, ..Default::default() },
+                ..rules(100.0, 0.5, (0.0, 0.0, 0.0, 2.0, 0.0))
+            },
+            Building::Temple => BuildingRules { happiness: 1.0, ..rules(80.0, 1.0, (0.0, 0.0, 0.0, 0.0, 2.0)) },
+            Building::Walls => BuildingRules { defense: 5.0, ..rules(100.0, 1.0, (0.0, 0.0, 0.0, 0.0, 0.0)) },
+            Building::Aqueduct => BuildingRules { health: 2.0, ..rules(120.0, 2.0, (0.0, 0.0, 0.0, 0.0, 0.0)) },
+            Building::Workshop => BuildingRules {
+                modifiers: BuildingModifiers { building_production: 0.25, ..Default::default() },
+                ..rules(120.0, 1.5, (0.0, 1.0, 0.0, 0.0, 0.0))
+            },
+            Building::Harbor => rules(100.0, 1.5, (1.0, 0.0, 0.0, 0.0, 0.0)),     // Gold comes per water tile
+            Building::Lighthouse => rules(80.0, 1.0, (0.0, 0.0, 0.0, 0.0, 0.0)), // Food comes per water tile
         }
     }
     
@@ -442,102 +1898,1442 @@ impl Building {
     }
 }
 
-impl ProductionItem {
-    pub fn get_required_production(&self) -> f32 {
+impl Wonder {
+    pub const ALL: [Wonder; 4] = [Wonder::Pyramids, Wonder::Stonehenge, Wonder::Colossus, Wonder::GreatLibrary];
+    
+    pub fn get_yields(&self) -> (f32, f32, f32, f32, f32) {
+        // Returns (food, production, science, gold, culture)
+        rules().wonder(*self).yields
+    }
+    
+    // Built-in numbers, used when no rules file overrides them
+    pub fn default_rules(&self) -> WonderRules {
         match self {
-            ProductionItem::Building(building) => match building {
-                Building::Granary => 60.0,
-                Building::Barracks => 60.0,
-                Building::Library => 90.0,
-                Building::Marketplace => 100.0,
-                Building::Temple => 80.0,
-                Building::Walls => 100.0,
-                Building::Aqueduct => 120.0,
-                Building::Workshop => 120.0,
-                Building::Harbor => 100.0,
-                Building::Lighthouse => 80.0,
-            },
-            ProductionItem::Unit(unit) => match unit {
-                UnitType::Warrior => 15.0,
-                UnitType::Archer => 25.0,
-                UnitType::Spearman => 35.0,
-                UnitType::Settler => 100.0,
-                UnitType::Worker => 60.0,
-                UnitType::Scout => 15.0,
-                UnitType::Galley => 40.0,
-                UnitType::Trireme => 60.0,
-            },
-            ProductionItem::Wonder(wonder) => match wonder {
-                Wonder::Pyramids => 400.0,
-                Wonder::Stonehenge => 300.0,
-                Wonder::Colossus => 350.0,
-                Wonder::GreatLibrary => 400.0,
-            },
+            Wonder::Pyramids => WonderRules { cost: 400.0, yields: (0.0, 0.0, 0.0, 0.0, 2.0) },
+            Wonder::Stonehenge => WonderRules { cost: 300.0, yields: (0.0, 0.0, 0.0, 0.0, 4.0) },
+            Wonder::Colossus => WonderRules { cost: 350.0, yields: (0.0, 0.0, 0.0, 3.0, 0.0) },
+            Wonder::GreatLibrary => WonderRules { cost: 400.0, yields: (0.0, 0.0, 4.0, 0.0, 0.0) },
         }
     }
     
-    pub fn get_name(&self) -> String {
+    pub fn free_building(&self) -> Option<Building> {
         match self {
-            ProductionItem::Building(building) => building.get_name().to_string(),
-            ProductionItem::Unit(unit) => format!("{:?}", unit),
-            ProductionItem::Wonder(wonder) => format!("{:?}", wonder),
+            Wonder::Pyramids => Some(Building::Granary),
+            Wonder::GreatLibrary => Some(Building::Library),
+            _ => None,
         }
     }
-}
+    
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Wonder::Pyramids => "Pyramids",
+            Wonder::Stonehenge => "Stonehenge",
+            Wonder::Colossus => "Colossus",
+            Wonder::GreatLibrary => "Great Library",
+        }
+    }
+}
+
+impl ProductionItem {
+    // Every item a city could produce, in display order
+    pub fn all() -> Vec<ProductionItem> {
+        let units = UnitType::ALL.into_iter().filter(|unit_type| !unit_type.is_great_person());
+        
+        units.map(ProductionItem::Unit)
+            .chain(Building::ALL.into_iter().map(ProductionItem::Building))
+            .chain(Wonder::ALL.into_iter().map(ProductionItem::Wonder))
+            .chain(std::iter::once(ProductionItem::Wealth))
+            .collect()
+    }
+    
+    // Technology the owning civilization must know to produce this
+    pub fn required_tech(&self) -> Option<Technology> {
+        match self {
+            ProductionItem::Building(building) => building.required_tech(),
+            ProductionItem::Unit(unit_type) => unit_type.required_tech(),
+            ProductionItem::Wonder(wonder) => match wonder {
+                Wonder::Pyramids => Some(Technology::Masonry),
+                Wonder::Stonehenge => None,
+                Wonder::Colossus => Some(Technology::Currency),
+                Wonder::GreatLibrary => Some(Technology::Writing),
+            },
+            ProductionItem::Wealth => None,
+        }
+    }
+    
+    // Base cost from the rules, scaled by the game speed
+    pub fn get_required_production(&self, speed: GameSpeed) -> f32 {
+        let cost = match self {
+            ProductionItem::Building(building) => rules().building(*building).cost,
+            ProductionItem::Unit(unit) => unit.get_stats().production_cost as f32, // Great People are never built
+            ProductionItem::Wonder(wonder) => rules().wonder(*wonder).cost,
+            ProductionItem::Wealth => 0.0,
+        };
+        cost * speed.multiplier()
+    }
+    
+    pub fn get_name(&self) -> String {
+        match self {
+            ProductionItem::Building(building) => building.get_name().to_string(),
+            ProductionItem::Unit(unit) => format!("{:?}", unit),
+            ProductionItem::Wonder(wonder) => wonder.get_name().to_string(),
+            ProductionItem::Wealth => "Wealth".to_string(),
+        }
+    }
+}
 
 // System for processing city turns
 pub fn process_city_turns(
-    mut city_query: Query<&mut City>,
-    tile_query: Query<&MapTile>,
+    mut city_query: Query<(Entity, &mut City)>,
+    tiles: TileLookup,
+    mut territory: ResMut<TerritoryMap>,
+    wonders: Res<WonderRegistry>,
     mut civ_manager: ResMut<CivilizationManager>,
+    mut events: CityEventWriters,
+    mut turn_events: EventReader<TurnStartedEvent>,
 ) {
-    for mut city in city_query.iter_mut() {
+    // Growth, production and income accrue once, when the owner's turn starts;
+    // tiles and yields are refreshed every frame so the panels stay current
+    let turns_started: HashSet<u32> = turn_events.read().map(|event| event.civilization_id).collect();
+    
+    // Which city works each tile, so no two cities share one
+    let mut worked_by: HashMap<HexCoord, Entity> = HashMap::new();
+    let mut city_sites: Vec<(u32, HexCoord)> = Vec::new();
+    let mut captured_cities: HashMap<u32, usize> = HashMap::new();
+    let mut trade_income: HashMap<Entity, f32> = HashMap::new();
+    for (city_entity, city) in city_query.iter() {
+        let income = city.trade_routes
+            .iter()
+            .filter_map(|destination| city_query.get(*destination).ok())
+            .map(|(_, destination)| city.trade_route_gold(destination))
+            .sum();
+        trade_income.insert(city_entity, income);
+        
+        for &coord in &city.worked_tiles {
+            worked_by.insert(coord, city_entity);
+        }
+        city_sites.push((city.civilization_id, city.hex_coord));
+        if city.civilization_id != city.original_civilization_id {
+            *captured_cities.entry(city.civilization_id).or_default() += 1;
+        }
+    }
+    
+    for (city_entity, mut city) in city_query.iter_mut() {
         // Only process cities for the current civilization's turn
         if civ_manager.is_current_turn(city.civilization_id) {
-            city.calculate_yields(&tile_query, &civ_manager);
-            city.process_turn(&mut civ_manager);
+            let turn_started = turns_started.contains(&city.civilization_id);
+            if city.is_occupied() {
+                if turn_started {
+                    city.occupied_turn();
+                }
+                worked_by.retain(|_, owner| *owner != city_entity);
+                continue;
+            }
+            
+            let taken: HashSet<HexCoord> = worked_by
+                .iter()
+                .filter(|(_, owner)| **owner != city_entity)
+                .map(|(coord, _)| *coord)
+                .collect();
+            
+            let is_ai = civ_manager.get_civilization(city.civilization_id).is_some_and(|civ| civ.is_ai);
+            if is_ai {
+                city.focus = choose_ai_focus(&city, &city_sites);
+            }
+            
+            city.assign_worked_tiles(city_entity, &tiles, &territory, &taken);
+            worked_by.retain(|_, owner| *owner != city_entity);
+            for &coord in &city.worked_tiles {
+                worked_by.insert(coord, city_entity);
+            }
+            
+            let captured = captured_cities.get(&city.civilization_id).copied().unwrap_or(0);
+            let war_weariness = civ_manager.diplomacy.total_war_weariness(city.civilization_id);
+            city.calculate_happiness(&tiles, captured, war_weariness);
+            city.calculate_health(&tiles);
+            city.trade_gold_per_turn = trade_income.get(&city_entity).copied().unwrap_or(0.0);
+            city.calculate_yields(&tiles, &civ_manager, &wonders);
+            if !turn_started {
+                continue;
+            }
+            city.process_turn(city_entity, &mut civ_manager, &mut events);
+            
+            // An empty treasury forces buildings onto the market
+            let bankrupt = civ_manager.get_civilization(city.civilization_id).is_some_and(|civ| civ.gold < 0.0);
+            if bankrupt && let Some((building, sale_price)) = city.sell_cheapest_building(&wonders) {
+                if let Some(civ) = civ_manager.get_civilization_mut(city.civilization_id) {
+                    civ.gold += sale_price;
+                }
+                println!("WARNING: Treasury empty! {} sold its {} for {:.0} gold", city.name, building.get_name(), sale_price);
+            }
+            
+            if city.culture_stored >= city.culture_needed_for_expansion {
+                city.expand_territory(city_entity, &tiles, &mut territory, &mut events.territory_expanded);
+            }
         }
     }
 }
 
+// Cities within this many tiles of a foreign city count as frontier cities for the AI
+const FRONTIER_DISTANCE: i32 = 8;
+
+// AI focus heuristic: young cities grow, frontier cities arm, everything else stays balanced
+fn choose_ai_focus(city: &City, city_sites: &[(u32, HexCoord)]) -> CityFocus {
+    if city.population <= 2 {
+        return CityFocus::Food;
+    }
+    
+    let is_frontier = city_sites.iter().any(|(civ_id, coord)| {
+        *civ_id != city.civilization_id && city.hex_coord.distance(*coord) <= FRONTIER_DISTANCE
+    });
+    if is_frontier {
+        CityFocus::Production
+    } else {
+        CityFocus::Default
+    }
+}
+
+// System that turns finished unit production into units on the map
+pub fn spawn_produced_units(
+    mut commands: Commands,
+    mut unit_events: EventReader<UnitProduced>,
+    mut city_query: Query<&mut City>,
+    tiles: TileLookup,
+    mut civ_manager: ResMut<CivilizationManager>,
+) {
+    for event in unit_events.read() {
+        let Ok(mut city) = city_query.get_mut(event.city_entity) else { continue };
+        
+        // Land units appear in the city; ships need water next to it
+        let probe = Unit::new(event.unit_type, city.civilization_id, city.hex_coord);
+        let spawn_pos = if probe.naval_unit {
+            city.hex_coord.neighbors().into_iter().find(|coord| probe.can_move_to(*coord, &tiles))
+        } else {
+            Some(city.hex_coord)
+        };
+        
+        match spawn_pos {
+            Some(spawn_pos) => {
+                spawn_unit(&mut commands, event.unit_type, city.civilization_id, spawn_pos, &mut civ_manager);
+            }
+            None => {
+                // Hold the finished item until the city can actually launch it
+                println!("City {} is not coastal and cannot launch its {}; production blocked",
+                         city.name, event.unit_type.get_name());
+                let item = ProductionItem::Unit(event.unit_type);
+                city.production_progress = city.required_production(&item);
+                if let Some(next) = city.current_production.replace(item) {
+                    city.production_queue.insert(0, next);
+                }
+            }
+        }
+    }
+}
+
+// Great People appear in the capital once a civilization has enough points of one type
+pub fn spawn_great_people(
+    mut commands: Commands,
+    city_query: Query<&City>,
+    mut civ_manager: ResMut<CivilizationManager>,
+) {
+    let civ_ids: Vec<u32> = civ_manager.civilizations.keys().copied().collect();
+    for civ_id in civ_ids {
+        let Some(capital) = city_query.iter().find(|city| city.civilization_id == civ_id && city.is_capital) else { continue };
+        let Some(kind) = civ_manager.get_civilization_mut(civ_id).and_then(|civ| civ.take_great_person()) else { continue };
+        
+        let unit_type = kind.unit_type();
+        spawn_unit(&mut commands, unit_type, civ_id, capital.hex_coord, &mut civ_manager);
+        println!("A {} has been born in {}!", unit_type.get_name(), capital.name);
+    }
+}
+
 // System for spawning city markers (visual representation)
 pub fn spawn_city_markers(
     mut commands: Commands,
-    cities_query: Query<&City, Added<City>>,
+    cities_query: Query<(Entity, &City), Added<City>>,
     civ_manager: Res<CivilizationManager>,
+    fonts: Res<GameFonts>,
 ) {
-    for city in cities_query.iter() {
+    for (city_entity, city) in cities_query.iter() {
         let world_pos = city.hex_coord.to_world_pos(super::map::HEX_SIZE);
+        let color = civ_manager.civ_color(city.civilization_id);
         
-        // Get civilization color
+        commands
+            .spawn((
+                CityMarker { city_entity },
+                Cullable,
+                Transform::from_translation(Vec3::new(world_pos.x, world_pos.y, 2.0)), // Above tiles
+                Visibility::default(),
+            ))
+            .with_children(|marker| {
+                marker.spawn((CitySymbol, Text2d::new(city_symbol(city)), TextColor(color), fonts.symbol(24.0)));
+                // Name below the symbol
+                marker.spawn((
+                    CityNameLabel,
+                    Text2d::new(city.name.clone()),
+                    TextColor(color),
+                    TextFont {
+                        font_size: 12.0,
+                        ..default()
+                    },
+                    Transform::from_translation(Vec3::new(0.0, -20.0, 0.0)),
+                ));
+            });
+    }
+}
+
+// The dot on the map, with a warning mark while the city is in unrest
+fn city_symbol(city: &City) -> &'static str {
+    if city.is_in_unrest() { "●!" } else { "●" }
+}
+
+// System that keeps each city's symbol and label in step with the city: its name, its
+// owner's color after a capture, and unrest
+#[allow(clippy::type_complexity)]
+pub fn update_city_markers(
+    city_query: Query<&City, Changed<City>>,
+    civ_manager: Res<CivilizationManager>,
+    marker_query: Query<(&CityMarker, &Children)>,
+    mut symbol_query: Query<(&mut Text2d, &mut TextColor), (With<CitySymbol>, Without<CityNameLabel>)>,
+    mut label_query: Query<(&mut Text2d, &mut TextColor), With<CityNameLabel>>,
+) {
+    if city_query.is_empty() {
+        return;
+    }
+    
+    for (marker, children) in marker_query.iter() {
+        let Ok(city) = city_query.get(marker.city_entity) else { continue };
+        let color = civ_manager.civ_color(city.civilization_id);
+        for child in children.iter() {
+            let (mut text, mut text_color, value) = match (symbol_query.get_mut(child), label_query.get_mut(child)) {
+                (Ok((text, text_color)), _) => (text, text_color, city_symbol(city)),
+                (_, Ok((text, text_color))) => (text, text_color, city.name.as_str()),
+                _ => continue,
+            };
+            if text.0 != value {
+                text.0 = value.to_string();
+            }
+            if text_color.0 != color {
+                text_color.0 = color;
+            }
+        }
+    }
+}
+
+// Debug option: echo every city announcement to the console
+pub fn log_city_events(
+    settings: Res<GameSettings>,
+    city_query: Query<&City>,
+    mut events: CityEventReaders,
+) {
+    let notifications = events.read_notifications(&city_query);
+    if settings.log_city_events {
+        for notification in notifications {
+            println!("{}", notification.text);
+        }
+    }
+}
+
+// System that opens the rename prompt for the selected city (R). With a unit selected,
+// R renames the unit instead.
+pub fn start_city_rename_system(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    input_map: Res<InputMap>,
+    city_selection: Res<CitySelection>,
+    unit_selection: Res<UnitSelection>,
+    city_query: Query<&City>,
+    mut text_entry: ResMut<TextEntry>,
+) {
+    if !input_map.just_pressed(Action::Rename, &keyboard) || unit_selection.selected_unit.is_some() || text_entry.is_active() {
+        return;
+    }
+    let Some(city_entity) = city_selection.selected_city else { return };
+    let Ok(city) = city_query.get(city_entity) else { return };
+    
+    begin_city_rename(&mut text_entry, city_entity, city);
+}
+
+/// Opens the text entry for a new name, starting from the current one
+pub fn begin_city_rename(text_entry: &mut TextEntry, city_entity: Entity, city: &City) {
+    text_entry.begin(
+        TextEntryTarget::CityName(city_entity),
+        format!("Rename {}", city.name),
+        city.name.clone(),
+    );
+}
+
+// System that applies confirmed city names; update_city_markers relabels the map
+pub fn apply_city_rename_system(
+    mut submitted: EventReader<TextEntrySubmitted>,
+    mut city_query: Query<(Entity, &mut City)>,
+) {
+    for event in submitted.read() {
+        let TextEntryTarget::CityName(city_entity) = event.target else { continue };
+        let Ok((_, city)) = city_query.get(city_entity) else { continue };
+        if city.name == event.text {
+            continue;
+        }
+        
+        // Duplicate names are allowed, but usually a typo
+        let civilization_id = city.civilization_id;
+        let duplicate = city_query
+            .iter()
+            .any(|(entity, other)| entity != city_entity && other.civilization_id == civilization_id && other.name == event.text);
+        if duplicate {
+            println!("Warning: another of your cities is already called {}", event.text);
+        }
+        
+        let Ok((_, mut city)) = city_query.get_mut(city_entity) else { continue };
+        println!("{} is now known as {}", city.name, event.text);
+        city.name = event.text.clone();
+    }
+}
+
+// System that hands captured cities over to their new owner
+#[allow(clippy::too_many_arguments)]
+pub fn city_capture_system(
+    mut capture_events: EventReader<CityCaptured>,
+    mut city_query: Query<&mut City>,
+    mut civ_manager: ResMut<CivilizationManager>,
+    mut city_selection: ResMut<CitySelection>,
+    mut territory: ResMut<TerritoryMap>,
+    mut wonders: ResMut<WonderRegistry>,
+    mut notifications: EventWriter<GameNotification>,
+) {
+    for event in capture_events.read() {
+        // Close the production panel of a city we just lost
+        if city_selection.selected_city == Some(event.city_entity) {
+            city_selection.selected_city = None;
+        }
+        
+        let Ok(mut city) = city_query.get_mut(event.city_entity) else { continue };
+        let old_civilization_id = city.civilization_id;
+        if old_civilization_id == event.new_civilization_id {
+            continue;
+        }
+        
+        if let Some(civ) = civ_manager.get_civilization_mut(old_civilization_id) {
+            civ.remove_city(event.city_entity);
+        }
+        let captor = match civ_manager.get_civilization_mut(event.new_civilization_id) {
+            Some(civ) => {
+                civ.add_city(event.city_entity);
+                civ.name.clone()
+            }
+            None if event.new_civilization_id == BARBARIAN_CIV_ID => "The Barbarians".to_string(),
+            None => "Unknown".to_string(),
+        };
+        
+        // A fallen capital is news for everyone, any other capture only for the two sides
+        let military = |text: String| GameNotification::new(NotificationCategory::Military, text).event_at(MapEvent::CityCaptured, city.hex_coord);
+        if city.is_original_capital && event.new_civilization_id != city.original_civilization_id {
+            notifications.write(military(format!("The capital {} has fallen to {}!", city.name, captor)));
+        } else {
+            notifications.write(military(format!("{} has captured {}!", captor, city.name)).for_civ(event.new_civilization_id));
+            notifications.write(military(format!("{} has been captured by {}!", city.name, captor)).for_civ(old_civilization_id));
+        }
+        
+        territory.transfer_city(event.city_entity, event.new_civilization_id);
+        wonders.transfer_city(event.city_entity, event.new_civilization_id);
+        
+        // A captured city keeps its buildings but loses its plans, capital status and hand-picked tiles
+        city.civilization_id = event.new_civilization_id;
+        city.is_capital = false;
+        city.release_tiles();
+        city.current_production = None;
+        city.production_queue.clear();
+        city.production_progress = 0.0;
+        city.hit_points = CITY_MAX_HIT_POINTS / 4;
+        city.begin_occupation(event.new_civilization_id);
+        
+        // Humans decide the fate of their conquests in the city panel; AI conquerors annex
+        let player_conquest = civ_manager.get_civilization(event.new_civilization_id).is_some_and(|civ| civ.is_player)
+            && event.new_civilization_id != city.original_civilization_id;
+        city.awaiting_capture_decision = player_conquest;
+        if player_conquest {
+            city_selection.selected_city = Some(event.city_entity);
+        }
+
+    }
+}
+
+// System that removes razed cities once their last citizen is gone, freeing their territory
+pub fn destroy_razed_cities(
+    mut commands: Commands,
+    city_query: Query<(Entity, &City)>,
+    mut territory: ResMut<TerritoryMap>,
+    mut wonders: ResMut<WonderRegistry>,
+    mut civ_manager: ResMut<CivilizationManager>,
+    mut destroyed_events: EventWriter<CityDestroyedEvent>,
+) {
+    for (city_entity, city) in city_query.iter() {
+        if !city.is_razing || city.population > 0 {
+            continue;
+        }
+        
+        territory.release_city(city_entity);
+        wonders.destroy_city(city_entity);
+        let was_last_city = match civ_manager.get_civilization_mut(city.civilization_id) {
+            Some(civ) => {
+                civ.remove_city(city_entity);
+                civ.cities.is_empty()
+            }
+            None => true,
+        };
+        
+        commands.entity(city_entity).despawn();
+        destroyed_events.write(CityDestroyedEvent {
+            city_entity,
+            civilization_id: city.civilization_id,
+            name: city.name.clone(),
+            was_last_city,
+        });
+    }
+}
+
+// System that clears the map markers and selection of destroyed cities. The symbol and
+// label go with their marker.
+pub fn despawn_destroyed_city_markers(
+    mut commands: Commands,
+    mut destroyed_events: EventReader<CityDestroyedEvent>,
+    marker_query: Query<(Entity, &CityMarker)>,
+    mut city_selection: ResMut<CitySelection>,
+) {
+    for event in destroyed_events.read() {
+        for (entity, _) in marker_query.iter().filter(|(_, marker)| marker.city_entity == event.city_entity) {
+            commands.entity(entity).despawn();
+        }
+        
+        if city_selection.selected_city == Some(event.city_entity) {
+            city_selection.selected_city = None;
+        }
+    }
+}
+
+// System that keeps city defense in step with the garrison, buildings and terrain.
+// Only writes when something changed, so Changed<City> stays meaningful.
+pub fn update_city_defense(
+    mut city_query: Query<&mut City>,
+    unit_query: Query<&Unit>,
+    tiles: TileLookup,
+) {
+    for mut city in city_query.iter_mut() {
+        let garrison = unit_query
+            .iter()
+            .filter(|unit| unit.hex_coord == city.hex_coord && unit.civilization_id == city.civilization_id && !unit.naval_unit)
+            .map(|unit| unit.get_combat_strength(false))
+            .max()
+            .unwrap_or(0);
+        
+        let defense = city.calculate_defense(garrison, &tiles);
+        if city.defense_breakdown != defense {
+            city.defense_breakdown = defense;
+            city.defense_strength = defense.total();
+        }
+    }
+}
+
+// System that finishes setting up newly founded cities: notes whether they sit on the
+// coast and registers their starting borders in the TerritoryMap. Tiles someone else
+// already owns stay theirs, except the new city's own center.
+pub fn register_new_cities(
+    mut city_query: Query<(Entity, &mut City)>,
+    tiles: TileLookup,
+    mut territory: ResMut<TerritoryMap>,
+) {
+    let mut seized: Vec<(Entity, HexCoord)> = Vec::new();
+    
+    for (city_entity, mut city) in city_query.iter_mut() {
+        if !city.is_added() {
+            continue;
+        }
+        
+        let center = city.hex_coord;
+        let civilization_id = city.civilization_id;
+        city.is_coastal = tiles.get(center).is_some_and(|tile| tile.is_coastal);
+        
+        if let Some(previous) = territory.seize(center, city_entity, civilization_id) {
+            seized.push((previous.city, center));
+        }
+        city.territory_tiles.retain(|coord| *coord == center || territory.claim(*coord, city_entity, civilization_id));
+    }
+    
+    for (previous_city, coord) in seized {
+        if let Ok((_, mut city)) = city_query.get_mut(previous_city) {
+            city.territory_tiles.retain(|tile| *tile != coord);
+            city.worked_tiles.retain(|tile| *tile != coord);
+        }
+    }
+}
+
+// System that keeps the set of city centers in the TileIndex, for movement costs
+pub fn index_city_sites(
+    city_query: Query<&City>,
+    added_cities: Query<(), Added<City>>,
+    mut removed_cities: RemovedComponents<City>,
+    mut tile_index: ResMut<TileIndex>,
+) {
+    let removed = removed_cities.read().count() > 0;
+    if added_cities.is_empty() && !removed {
+        return;
+    }
+    tile_index.city_sites = city_query.iter().map(|city| city.hex_coord).collect();
+}
+
+// System that settles completed wonders: the first city to finish claims the wonder, any
+// other city that finished it or was still building it gets its production back as gold.
+// Also hands out the free buildings wonders grant to every city of their owner.
+pub fn wonder_system(
+    mut city_query: Query<(Entity, &mut City)>,
+    mut wonders: ResMut<WonderRegistry>,
+    mut civ_manager: ResMut<CivilizationManager>,
+    mut completed_events: EventWriter<ProductionCompletedEvent>,
+    mut notifications: EventWriter<GameNotification>,
+) {
+    for (city_entity, mut city) in city_query.iter_mut() {
+        let unclaimed: Vec<Wonder> = city.wonders
+            .iter()
+            .copied()
+            .filter(|wonder| wonders.site(*wonder).is_none_or(|site| site.city != city_entity))
+            .collect();
+        
+        for wonder in unclaimed {
+            if wonders.is_built(wonder) {
+                // Lost the race this turn
+                city.wonders.retain(|built| *built != wonder);
+                let refund = ProductionItem::Wonder(wonder).get_required_production(city.speed);
+                if let Some(civ) = civ_manager.get_civilization_mut(city.civilization_id) {
+                    civ.gold += refund;
+                }
+                let text = format!("{} finished the {} too late; {:.0} production became gold", city.name, wonder.get_name(), refund);
+                notifications.write(GameNotification::new(NotificationCategory::City, text).at(city.hex_coord).for_civ(city.civilization_id));
+                continue;
+            }
+            
+            wonders.record(wonder, city_entity, city.civilization_id);
+            completed_events.write(ProductionCompletedEvent { city_entity, item: ProductionItem::Wonder(wonder) });
+            let civ_name = civ_manager.known_civ_name(city.civilization_id);
+            let text = format!("World news: {} completed the {} in {}!", civ_name, wonder.get_name(), city.name);
+            notifications.write(GameNotification::new(NotificationCategory::City, text).at(city.hex_coord));
+        }
+    }
+    
+    for (_, mut city) in city_query.iter_mut() {
+        // Drop wonders someone else already finished, refunding work in progress
+        if let Some(ProductionItem::Wonder(wonder)) = city.current_production.clone()
+            && wonders.is_built(wonder)
+        {
+            let refund = city.production_progress;
+            if let Some(civ) = civ_manager.get_civilization_mut(city.civilization_id) {
+                civ.gold += refund;
+            }
+            let text = format!("{} lost the race for the {}; {:.0} production became gold", city.name, wonder.get_name(), refund);
+            notifications.write(GameNotification::new(NotificationCategory::City, text).at(city.hex_coord).for_civ(city.civilization_id));
+            city.production_progress = 0.0;
+            city.current_production = None;
+        }
+        city.production_queue.retain(|item| !matches!(item, ProductionItem::Wonder(wonder) if wonders.is_built(*wonder)));
+        if city.current_production.is_none() && !city.production_queue.is_empty() {
+            city.current_production = Some(city.production_queue.remove(0));
+        }
+        
+        for building in wonders.free_buildings(city.civilization_id) {
+            if !city.buildings.contains(&building) {
+                city.buildings.push(building);
+            }
+        }
+    }
+}
+
+// System that severs trade routes that can no longer run: the destination is gone or out
+// of range, its owner is now at war with us, an enemy unit sits on the route, or the
+// route goes by sea from or to a blockaded city.
+pub fn trade_route_maintenance_system(
+    mut city_query: Query<(Entity, &mut City)>,
+    unit_query: Query<&Unit>,
+    tiles: TileLookup,
+    civ_manager: Res<CivilizationManager>,
+) {
+    let sites: HashMap<Entity, (HexCoord, u32, String, bool)> = city_query
+        .iter()
+        .map(|(entity, city)| (entity, (city.hex_coord, city.civilization_id, city.name.clone(), city.is_blockaded)))
+        .collect();
+    
+    for (_, mut city) in city_query.iter_mut() {
+        if city.trade_routes.is_empty() {
+            continue;
+        }
+        
+        let origin = city.hex_coord;
+        let civilization_id = city.civilization_id;
+        let origin_blockaded = city.is_blockaded;
+        let mut severed = Vec::new();
+        let still_running: Vec<Entity> = city.trade_routes
+            .iter()
+            .copied()
+            .filter(|destination| {
+                let Some((coord, destination_civ, name, destination_blockaded)) = sites.get(destination) else {
+                    return false;
+                };
+                let pillaged = origin.line_to(*coord).into_iter().any(|hex| {
+                    unit_query.iter().any(|unit| {
+                        unit.hex_coord == hex && are_enemies(civilization_id, unit.civilization_id, &civ_manager)
+                    })
+                });
+                let by_sea = || {
+                    origin.line_to(*coord).into_iter().any(|hex| {
+                        tiles.get(hex).is_some_and(|tile| matches!(TerrainType::from_u8(tile.terrain), TerrainType::Ocean | TerrainType::Lake))
+                    })
+                };
+                let blockaded = (origin_blockaded || *destination_blockaded) && by_sea();
+                let running = !pillaged
+                    && !blockaded
+                    && origin.distance(*coord) <= TRADE_ROUTE_RANGE
+                    && !are_enemies(civilization_id, *destination_civ, &civ_manager);
+                if !running {
+                    severed.push(name.clone());
+                }
+                running
+            })
+            .take(city.max_trade_routes())
+            .collect();
+        
+        if still_running.len() != city.trade_routes.len() {
+            for name in severed {
+                println!("Trade route from {} to {} has been severed!", city.name, name);
+            }
+            city.trade_routes = still_running;
+        }
+    }
+}
+
+// Marks a coastal city blockaded while an enemy ship lies next to it. Several ships count
+// no more than one, and sinking or chasing off the last lifts the blockade at once.
+pub fn blockade_system(
+    mut city_query: Query<(Entity, &mut City)>,
+    unit_query: Query<&Unit>,
+    civ_manager: Res<CivilizationManager>,
+    mut blockades: EventWriter<BlockadeStartedEvent>,
+) {
+    for (city_entity, mut city) in city_query.iter_mut() {
+        let blockader = unit_query.iter().find(|unit| {
+            city.is_coastal
+                && unit.naval_unit
+                && unit.hex_coord.distance(city.hex_coord) == 1
+                && are_enemies(city.civilization_id, unit.civilization_id, &civ_manager)
+        });
+        if blockader.is_some() == city.is_blockaded {
+            continue;
+        }
+        city.is_blockaded = blockader.is_some();
+        match blockader {
+            Some(ship) => {
+                println!("{} is blockaded by a {} ship!", city.name, civ_manager.civ_name(ship.civilization_id));
+                blockades.write(BlockadeStartedEvent { city_entity });
+            }
+            None => println!("The blockade of {} has been lifted", city.name),
+        }
+    }
+}
+
+// System that draws trade routes as faint lines in their owner's color
+pub fn draw_trade_routes(
+    mut gizmos: Gizmos,
+    city_query: Query<&City>,
+    civ_manager: Res<CivilizationManager>,
+) {
+    for city in city_query.iter() {
         let color = civ_manager.get_civilization(city.civilization_id)
             .map(|civ| civ.color)
-            .unwrap_or(Color::WHITE);
+            .unwrap_or(Color::WHITE)
+            .with_alpha(0.35);
+        let start = city.hex_coord.to_world_pos(super::map::HEX_SIZE);
         
-        // Create city marker
-        commands.spawn((
-            CityMarker {
-                civilization_id: city.civilization_id,
-                city_name: city.name.clone(),
-            },
-            Text2d::new("●"), // Circle symbol for city
-            TextColor(color),
-            TextFont {
-                font_size: 24.0,
-                ..default()
-            },
-            Transform::from_translation(Vec3::new(world_pos.x, world_pos.y, 2.0)), // Above tiles
+        for destination in &city.trade_routes {
+            if let Ok(destination) = city_query.get(*destination) {
+                gizmos.line_2d(start, destination.hex_coord.to_world_pos(super::map::HEX_SIZE), color);
+            }
+        }
+    }
+}
+
+// System that outlines every civilization's territory in its color, along the edges
+// between the tiles it owns and those it doesn't
+pub fn draw_territory_borders(
+    mut gizmos: Gizmos,
+    territory: Res<TerritoryMap>,
+    civ_manager: Res<CivilizationManager>,
+) {
+    for (coord, neighbor, civilization_id) in territory.border_edges() {
+        let (start, end) = coord.edge_toward(neighbor, super::map::HEX_SIZE);
+        gizmos.line_2d(start, end, civ_manager.civ_color(civilization_id).with_alpha(0.8));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::civilization::{
+        Civilization, Difficulty, DifficultyModifiers, SCIENTIFIC_RESEARCH_COST, SEAFARING_NAVAL_MOVEMENT,
+        SPIRITUAL_PRESSURE_BONUS, create_default_civilizations,
+    };
+    use crate::game::game_initialization::{EndTurnEvent, turn_system};
+    use crate::game::culture::pressure_on;
+    use crate::game::test_support::{area, game_app, run};
+    
+    const CENTER: HexCoord = HexCoord { q: 0, r: 0 };
+    
+    // An app on a patch of `terrain` around CENTER, holding `civs` (ids from 1, the first
+    // played by a human) and no cities yet
+    fn city_app(terrain: TerrainType, civs: Vec<Civilization>) -> App {
+        let mut app = game_app(area(CENTER, 4, terrain));
+        let mut manager = CivilizationManager::default();
+        for civ in civs {
+            manager.add_civilization(civ);
+        }
+        app.insert_resource(manager);
+        app
+    }
+    
+    // The Roman Empire on its own: Military, so no trait touches food, science or buildings
+    fn romans() -> Vec<Civilization> {
+        create_default_civilizations().into_iter().take(1).collect()
+    }
+    
+    // A lone human civilization with exactly `traits`
+    fn civ_with(traits: &[CivTrait]) -> Vec<Civilization> {
+        let mut civs = romans();
+        civs[0].traits = traits.to_vec();
+        civs
+    }
+    
+    // Founds a city for `civ_id` and hands it its starting borders
+    fn found(app: &mut App, name: &str, coord: HexCoord, civ_id: u32) -> Entity {
+        let turn = app.world().resource::<CivilizationManager>().turn_number;
+        let city = app.world_mut().spawn(City::new(name.to_string(), coord, civ_id, turn, true)).id();
+        if let Some(civ) = app.world_mut().resource_mut::<CivilizationManager>().get_civilization_mut(civ_id) {
+            civ.add_city(city);
+        }
+        run(app, register_new_cities);
+        city
+    }
+    
+    fn city(app: &App, entity: Entity) -> City {
+        app.world().get::<City>(entity).expect("city should exist").clone()
+    }
+    
+    fn end_turn(app: &mut App) {
+        app.world_mut().send_event(EndTurnEvent);
+        app.update();
+    }
+    
+    // A city gains its food_per_turn once per end-turn and nothing on the frames between
+    #[test]
+    fn city_accrues_food_once_per_turn() {
+        let mut app = city_app(TerrainType::TemperateGrassland, romans());
+        let rome = found(&mut app, "Rome", CENTER, 1);
+        app.add_systems(Update, (turn_system, process_city_turns.after(turn_system)));
+        
+        app.update();
+        let before = city(&app, rome);
+        assert_eq!(before.food_stored, 0.0);
+        assert!(before.food_per_turn > 0.0);
+        
+        for turn in 1..=2 {
+            end_turn(&mut app);
+            for _ in 0..3 {
+                app.update();
+            }
+            let after = city(&app, rome);
+            assert_eq!(after.population, 1);
+            assert_eq!(after.food_stored, before.food_per_turn * turn as f32, "after {} end-turns", turn);
+        }
+    }
+    
+    // Starting a game opens the first civilization's turn, so its capital accrues on turn 1
+    // like every other civilization's does when their turn comes round
+    #[test]
+    fn opening_civilization_is_processed_on_the_first_turn() {
+        use crate::game::game_initialization::initialize_game;
+        use crate::game::map::spawn_world;
+        use crate::game::world_gen::{WorldGenerator, WorldType};
+        
+        let mut app = game_app(Vec::new());
+        app.world_mut().resource_mut::<GameSettings>().opponents = 2;
+        let config = WorldType::Continents.config();
+        let mut generator = WorldGenerator::with_seed(30, config.clone(), 11);
+        let world_tiles = generator.generate();
+        let sea_level = generator.sea_level;
+        run(&mut app, move |mut commands: Commands, mut index: ResMut<TileIndex>| {
+            spawn_world(&mut commands, &mut index, world_tiles.clone(), sea_level, config.clone());
+        });
+        run(&mut app, initialize_game);
+        app.add_systems(Update, (register_new_cities, process_city_turns.after(register_new_cities)));
+        app.update();
+        
+        let cities: Vec<City> = app.world_mut().query::<&City>().iter(app.world()).cloned().collect();
+        let manager = app.world().resource::<CivilizationManager>();
+        let opening = manager.current_turn_civ;
+        assert_eq!(cities.len(), 3);
+        for city in cities {
+            let culture = manager.get_civilization(city.civilization_id).unwrap().culture;
+            if city.civilization_id == opening {
+                assert_eq!(culture, city.culture_per_turn, "{} wasn't processed on turn 1", city.name);
+            } else {
+                assert_eq!(culture, 0.0, "{} was processed out of turn", city.name);
+            }
+        }
+    }
+    
+    // A Warrior put into production comes out as a live unit in the city once its cost is
+    // paid, and not a turn earlier
+    #[test]
+    fn queued_warrior_becomes_a_unit() {
+        let mut app = city_app(TerrainType::TemperateDeciduousForest, romans());
+        let rome = found(&mut app, "Rome", CENTER, 1);
+        app.add_systems(Update, (
+            turn_system,
+            process_city_turns.after(turn_system),
+            spawn_produced_units.after(process_city_turns),
         ));
+        let warrior = ProductionItem::Unit(UnitType::Warrior);
+        app.world_mut().get_mut::<City>(rome).unwrap().queue_production(warrior.clone());
+        app.update();
+        
+        let rome_now = city(&app, rome);
+        let turns = rome_now.turns_for(&warrior, rome_now.required_production(&warrior)).expect("Rome makes production");
+        assert!(turns > 1);
+        let warriors = |app: &mut App| -> Vec<(Entity, Unit)> {
+            app.world_mut()
+                .query::<(Entity, &Unit)>()
+                .iter(app.world())
+                .filter(|(_, unit)| unit.unit_type == UnitType::Warrior)
+                .map(|(entity, unit)| (entity, unit.clone()))
+                .collect()
+        };
+        for _ in 1..turns {
+            end_turn(&mut app);
+        }
+        assert!(warriors(&mut app).is_empty());
         
-        // Add city name text below the marker
-        commands.spawn((
-            Text2d::new(city.name.clone()),
-            TextColor(Color::WHITE),
-            TextFont {
-                font_size: 12.0,
-                ..default()
+        end_turn(&mut app);
+        let produced = warriors(&mut app);
+        assert_eq!(produced.len(), 1);
+        let (entity, unit) = &produced[0];
+        assert_eq!((unit.civilization_id, unit.hex_coord), (1, CENTER));
+        assert!(app.world().resource::<CivilizationManager>().get_civilization(1).unwrap().units.contains(entity));
+        assert_eq!(city(&app, rome).current_production, None);
+    }
+    
+    // Grassland and forest side by side: a size 3 city on Food focus works the grassland and
+    // grows sooner, on Production focus works the forest and builds sooner
+    #[test]
+    fn focus_changes_growth_and_build_times() {
+        let forest: Vec<HexCoord> = CENTER.neighbors()[..3].to_vec();
+        let outcome = |focus: CityFocus| -> (u32, u32, Vec<HexCoord>) {
+            let mut app = city_app(TerrainType::TemperateGrassland, romans());
+            let mut tiles = app.world_mut().query::<&mut MapTile>();
+            for mut tile in tiles.iter_mut(app.world_mut()) {
+                if forest.contains(&tile.hex_coord) {
+                    tile.terrain = TerrainType::TemperateConiferForest as u8;
+                }
+            }
+            let rome = found(&mut app, "Rome", CENTER, 1);
+            let mut founded = app.world_mut().get_mut::<City>(rome).unwrap();
+            founded.population = 3;
+            founded.focus = focus;
+            run(&mut app, process_city_turns);
+            
+            let rome = city(&app, rome);
+            let warrior = ProductionItem::Unit(UnitType::Warrior);
+            let turns_to_grow = (rome.food_needed_for_growth / rome.food_per_turn).ceil() as u32;
+            let turns_to_build = rome.turns_for(&warrior, rome.required_production(&warrior)).unwrap();
+            (turns_to_grow, turns_to_build, rome.worked_tiles[1..].to_vec())
+        };
+        
+        let (food_growth, food_build, food_tiles) = outcome(CityFocus::Food);
+        let (production_growth, production_build, production_tiles) = outcome(CityFocus::Production);
+        assert!(food_tiles.iter().all(|coord| !forest.contains(coord)));
+        assert!(production_tiles.iter().all(|coord| forest.contains(coord)));
+        assert!(food_growth < production_growth, "food focus grows in {} turns, production in {}", food_growth, production_growth);
+        assert!(production_build < food_build, "production focus builds in {} turns, food in {}", production_build, food_build);
+    }
+    
+    // Flat yields from tiles and buildings are summed first and the building percentages
+    // scale the total: a Library's +2 science is boosted by its own +25%
+    #[test]
+    fn building_percentages_apply_after_flat_yields() {
+        let mut app = city_app(TerrainType::TemperateRainforest, civ_with(&[]));
+        let rome = found(&mut app, "Rome", CENTER, 1);
+        let mut founded = app.world_mut().get_mut::<City>(rome).unwrap();
+        founded.buildings = vec![Building::Library, Building::Marketplace];
+        founded.set_production(ProductionItem::Unit(UnitType::Warrior)); // Not Wealth, which adds gold
+        run(&mut app, process_city_turns);
+        
+        let rome = city(&app, rome);
+        assert_eq!(rome.worked_tiles.len(), 2);
+        // Two rainforest tiles at 2 science each, plus the Library's 2, then +25%
+        assert_eq!(rome.science_per_turn, (2.0 + 2.0 + 2.0) * 1.25);
+        // The center's 1 gold floor plus the Marketplace's 2, then +25%
+        assert_eq!(rome.gold_per_turn, (1.0 + 2.0) * 1.25);
+    }
+    
+    // Difficulty and traits come last, multiplying what the buildings made rather than
+    // adding to their percentage
+    #[test]
+    fn traits_and_difficulty_apply_after_building_percentages() {
+        let mut civs = civ_with(&[CivTrait::Spiritual]);
+        civs[0].set_player(false);
+        let mut app = city_app(TerrainType::TemperateRainforest, civs);
+        app.world_mut().resource_mut::<CivilizationManager>().difficulty = Difficulty::King;
+        let rome = found(&mut app, "Rome", CENTER, 1);
+        app.world_mut().get_mut::<City>(rome).unwrap().buildings = vec![Building::Library, Building::Temple];
+        run(&mut app, process_city_turns);
+        
+        let rome = city(&app, rome);
+        assert_eq!(rome.science_per_turn, (2.0 + 2.0 + 2.0) * 1.25 * 1.2);
+        // Base culture and the Temple's, then Spiritual, then King's bonus for the AI
+        assert_eq!(rome.culture_per_turn, (1.0 + 2.0) * (1.0 + SPIRITUAL_CULTURE_BONUS) * 1.2);
+    }
+    
+    // The Workshop's +1 production counts for everything, its +25% only toward buildings;
+    // Industrious adds its share to the Workshop's rather than multiplying it
+    #[test]
+    fn workshop_bonus_only_speeds_buildings() {
+        for (traits, building_bonus) in [(vec![], 0.25), (vec![CivTrait::Industrious], 0.25 + INDUSTRIOUS_BUILDING_PRODUCTION)] {
+            let mut app = city_app(TerrainType::TemperateConiferForest, civ_with(&traits));
+            let rome = found(&mut app, "Rome", CENTER, 1);
+            app.world_mut().get_mut::<City>(rome).unwrap().buildings = vec![Building::Workshop];
+            run(&mut app, process_city_turns);
+            
+            let rome = city(&app, rome);
+            // Two forest tiles at 2 production each, plus the Workshop's 1
+            assert_eq!(rome.production_per_turn, 5.0);
+            assert_eq!(rome.production_rate_for(&ProductionItem::Building(Building::Library)), 5.0 * (1.0 + building_bonus));
+            assert_eq!(rome.production_rate_for(&ProductionItem::Wonder(Wonder::Stonehenge)), 5.0);
+            assert_eq!(rome.production_rate_for(&ProductionItem::Unit(UnitType::Warrior)), 5.0);
+        }
+    }
+    
+    // Food past the growth threshold carries into the next citizen rather than being lost
+    #[test]
+    fn growth_carries_surplus_food_over() {
+        let mut rome = City::new("Rome".to_string(), CENTER, 1, 1, true);
+        assert_eq!(rome.food_needed_for_growth, 12.0);
+        rome.food_stored = 15.0;
+        rome.grow_population();
+        assert_eq!(rome.population, 2);
+        assert_eq!(rome.food_needed_for_growth, 14.0);
+        assert_eq!(rome.food_stored, 3.0);
+    }
+    
+    // A Granary keeps a quarter of the spent threshold on top of the surplus
+    #[test]
+    fn granary_keeps_food_on_growth() {
+        let mut rome = City::new("Rome".to_string(), CENTER, 1, 1, true);
+        rome.buildings.push(Building::Granary);
+        rome.food_stored = 15.0;
+        rome.grow_population();
+        assert_eq!(rome.population, 2);
+        assert_eq!(rome.food_stored, 3.0 + 12.0 * 0.25);
+    }
+    
+    // However much food is stored, a city grows one citizen per turn; the rest waits,
+    // capped at a full bar, for the turns after
+    #[test]
+    fn growth_is_capped_at_one_citizen_per_turn() {
+        let mut rome = City::new("Rome".to_string(), CENTER, 1, 1, true);
+        rome.buildings.push(Building::Granary);
+        rome.food_stored = 50.0;
+        rome.grow_population();
+        assert_eq!(rome.population, 2);
+        assert_eq!(rome.food_stored, rome.food_needed_for_growth);
+        
+        rome.grow_population();
+        assert_eq!(rome.population, 3);
+        assert!(rome.food_stored < rome.food_needed_for_growth);
+    }
+    
+    // A city stops growing the moment its happiness drops below zero, however much food it
+    // has, and grows again once something brings it back to zero
+    #[test]
+    fn growth_halts_and_resumes_at_the_happiness_tipping_point() {
+        let mut app = city_app(TerrainType::TemperateGrassland, romans());
+        let town = found(&mut app, "Antium", CENTER, 1);
+        app.add_systems(Update, (turn_system, process_city_turns.after(turn_system)));
+        let mut founded = app.world_mut().get_mut::<City>(town).unwrap();
+        founded.is_capital = false;
+        founded.population = 4;
+        founded.food_stored = 17.0;
+        app.update();
+        
+        // 4 content citizens against 4 base happiness: exactly content, so it grows
+        assert_eq!(city(&app, town).happiness, 0.0);
+        end_turn(&mut app);
+        assert_eq!(city(&app, town).population, 5);
+        
+        // The fifth citizen tips it into unhappiness and growth stops on a full bar
+        let threshold = city(&app, town).food_needed_for_growth;
+        app.world_mut().get_mut::<City>(town).unwrap().food_stored = threshold;
+        for _ in 0..2 {
+            end_turn(&mut app);
+            let halted = city(&app, town);
+            assert_eq!(halted.happiness, -1.0);
+            assert_eq!(halted.population, 5);
+            assert!(halted.food_stored >= halted.food_needed_for_growth);
+        }
+        
+        // A Temple brings it back to zero and the stored food grows the city at once
+        app.world_mut().get_mut::<City>(town).unwrap().buildings.push(Building::Temple);
+        end_turn(&mut app);
+        let resumed = city(&app, town);
+        assert_eq!(resumed.happiness, 0.0);
+        assert_eq!(resumed.population, 6);
+    }
+    
+    // A city out of food loses a citizen a turn, with a warning each time, but never
+    // drops below population 1: there it just goes hungry
+    #[test]
+    fn starvation_stops_at_population_one() {
+        let mut app = city_app(TerrainType::HotDesert, romans());
+        app.add_event::<BlockadeStartedEvent>();
+        let oasis = found(&mut app, "Siwa", CENTER, 1);
+        app.add_systems(Update, (turn_system, process_city_turns.after(turn_system)));
+        app.world_mut().get_mut::<City>(oasis).unwrap().population = 3;
+        app.update();
+        // Each run is a fresh reader, so clear what it saw before the next turn
+        let warnings = |app: &mut App| -> Vec<String> {
+            let notifications = run(app, |city_query: Query<&City>, mut events: CityEventReaders| events.read_notifications(&city_query));
+            app.world_mut().resource_mut::<Events<CityStarvingEvent>>().clear();
+            notifications
+                .into_iter()
+                .filter(|notification| notification.category == NotificationCategory::Warning)
+                .map(|notification| notification.text)
+                .collect()
+        };
+        
+        let mut populations = Vec::new();
+        for _ in 0..4 {
+            if city(&app, oasis).population == 1 {
+                // Even with the larder in deficit, population 1 holds
+                app.world_mut().get_mut::<City>(oasis).unwrap().food_stored = -5.0;
+            }
+            end_turn(&mut app);
+            populations.push(city(&app, oasis).population);
+        }
+        assert_eq!(populations, vec![2, 1, 1, 1]);
+        assert!(city(&app, oasis).food_stored >= 0.0);
+        
+        let mut app = city_app(TerrainType::HotDesert, romans());
+        app.add_event::<BlockadeStartedEvent>();
+        let oasis = found(&mut app, "Siwa", CENTER, 1);
+        app.add_systems(Update, (turn_system, process_city_turns.after(turn_system)));
+        app.world_mut().get_mut::<City>(oasis).unwrap().population = 2;
+        app.update();
+        warnings(&mut app);
+        end_turn(&mut app);
+        assert_eq!(warnings(&mut app), vec!["Siwa is starving and has shrunk to population 1!".to_string()]);
+        app.world_mut().get_mut::<City>(oasis).unwrap().food_stored = -5.0;
+        end_turn(&mut app);
+        assert_eq!(warnings(&mut app), vec!["Siwa is starving!".to_string()]);
+        assert_eq!(city(&app, oasis).population, 1);
+    }
+    
+    // A center on bare desert still feeds its first citizen and turns out a little hammer
+    // and coin, while the desert tile it works alongside yields nothing
+    #[test]
+    fn desert_center_gets_the_floor_yields() {
+        let mut app = city_app(TerrainType::HotDesert, romans());
+        let oasis = found(&mut app, "Siwa", CENTER, 1);
+        app.world_mut().get_mut::<City>(oasis).unwrap().set_production(ProductionItem::Unit(UnitType::Warrior));
+        app.add_systems(Update, process_city_turns);
+        app.update();
+        
+        let oasis = city(&app, oasis);
+        assert_eq!(oasis.worked_tiles.len(), 2);
+        assert_eq!(oasis.food_per_turn + oasis.population as f32 * FOOD_PER_CITIZEN, CENTER_MIN_FOOD);
+        assert_eq!(oasis.production_per_turn, CENTER_MIN_PRODUCTION);
+        assert_eq!(oasis.gold_per_turn, CENTER_MIN_GOLD);
+    }
+    
+    // A bankrupt city sells what costs least to run first, then what was cheapest to build
+    #[test]
+    fn bankruptcy_sells_the_cheapest_building_to_run_first() {
+        let mut rome = City::new("Rome".to_string(), CENTER, 1, 1, true);
+        rome.buildings = vec![Building::Aqueduct, Building::Walls, Building::Library, Building::Granary, Building::Marketplace];
+        let wonders = WonderRegistry::default();
+        
+        let mut sold = Vec::new();
+        while let Some((building, price)) = rome.sell_cheapest_building(&wonders) {
+            assert_eq!(price, ProductionItem::Building(building).get_required_production(rome.speed) * BUILDING_SALE_FRACTION);
+            sold.push(building);
+        }
+        assert_eq!(sold, vec![Building::Marketplace, Building::Granary, Building::Library, Building::Walls, Building::Aqueduct]);
+        assert!(rome.buildings.is_empty());
+    }
+    
+    // Buildings the owner's wonders hand out are never sold, but a rival's wonders protect nothing
+    #[test]
+    fn bankruptcy_never_sells_wonder_granted_buildings() {
+        let mut wonders = WonderRegistry::default();
+        wonders.record(Wonder::Pyramids, Entity::PLACEHOLDER, 1);
+        wonders.record(Wonder::GreatLibrary, Entity::PLACEHOLDER, 2);
+        let mut rome = City::new("Rome".to_string(), CENTER, 1, 1, true);
+        rome.buildings = vec![Building::Granary, Building::Library, Building::Walls];
+        
+        let mut sold = Vec::new();
+        while let Some((building, _)) = rome.sell_cheapest_building(&wonders) {
+            sold.push(building);
+        }
+        assert_eq!(sold, vec![Building::Library, Building::Walls]);
+        assert_eq!(rome.buildings, vec![Building::Granary]);
+    }
+    
+    // Rival cities growing their borders into the same ground split it between them: every
+    // tile ends up with exactly one owner, the one the territory map records
+    #[test]
+    fn rival_borders_never_overlap() {
+        let mut app = game_app(area(CENTER, 8, TerrainType::TemperateGrassland));
+        let mut manager = CivilizationManager::default();
+        for civ in create_default_civilizations().into_iter().take(2) {
+            manager.add_civilization(civ);
+        }
+        app.insert_resource(manager);
+        let rome = found(&mut app, "Rome", CENTER, 1);
+        let athens = found(&mut app, "Athens", HexCoord { q: 3, r: 0 }, 2);
+        
+        run(&mut app, |mut cities: Query<(Entity, &mut City)>, tiles: TileLookup, mut territory: ResMut<TerritoryMap>, mut events: EventWriter<TerritoryExpandedEvent>| {
+            for _ in 0..20 {
+                for (entity, mut city) in &mut cities {
+                    city.culture_stored = city.culture_needed_for_expansion;
+                    city.expand_territory(entity, &tiles, &mut territory, &mut events);
+                }
+            }
+        });
+        
+        let territory = app.world().resource::<TerritoryMap>().clone();
+        let rome_tiles: HashSet<HexCoord> = city(&app, rome).territory_tiles.into_iter().collect();
+        let athens_tiles: HashSet<HexCoord> = city(&app, athens).territory_tiles.into_iter().collect();
+        assert_eq!(rome_tiles.len(), city(&app, rome).territory_tiles.len());
+        assert_eq!(athens_tiles.len(), city(&app, athens).territory_tiles.len());
+        assert!(rome_tiles.len() > 7 && athens_tiles.len() > 7, "both cities should have grown");
+        assert!(rome_tiles.is_disjoint(&athens_tiles));
+        for (entity, tiles, civilization_id) in [(rome, &rome_tiles, 1), (athens, &athens_tiles, 2)] {
+            for coord in tiles {
+                assert_eq!(territory.owner(*coord), Some(TileOwner { city: entity, civilization_id }));
+            }
+        }
+    }
+    
+    // Borders run along the edges of the tiles each civilization owns: between rivals on
+    // both sides, never between two tiles of one civilization, and around a tile held well
+    // past a city's reach
+    #[test]
+    fn borders_follow_tile_ownership() {
+        let mut app = game_app(area(CENTER, 8, TerrainType::TemperateGrassland));
+        let mut manager = CivilizationManager::default();
+        for civ in create_default_civilizations().into_iter().take(2) {
+            manager.add_civilization(civ);
+        }
+        app.insert_resource(manager);
+        let rome = found(&mut app, "Rome", CENTER, 1);
+        found(&mut app, "Athens", HexCoord { q: 3, r: 0 }, 2);
+        let outpost = HexCoord { q: 0, r: 6 };
+        app.world_mut().resource_mut::<TerritoryMap>().seize(outpost, rome, 1);
+        
+        let territory = app.world().resource::<TerritoryMap>().clone();
+        let edges: Vec<(HexCoord, HexCoord, u32)> = territory.border_edges().collect();
+        for (inside, outside, civilization_id) in &edges {
+            assert_eq!(territory.owner(*inside).map(|owner| owner.civilization_id), Some(*civilization_id));
+            assert_ne!(territory.owner(*outside).map(|owner| owner.civilization_id), Some(*civilization_id));
+        }
+        let around_outpost = edges.iter().filter(|(inside, _, _)| *inside == outpost).count();
+        assert_eq!(around_outpost, 6);
+        
+        // Rome's and Athens' first rings meet between them, and each side draws its own edge
+        let (west, east) = (HexCoord { q: 1, r: 0 }, HexCoord { q: 2, r: 0 });
+        assert!(edges.contains(&(west, east, 1)) && edges.contains(&(east, west, 2)));
+        assert!(!edges.iter().any(|(inside, outside, _)| *inside == CENTER || *outside == CENTER));
+    }
+    
+    // The game speed scales what a Warrior costs, so the same forest city takes longer to
+    // raise one the slower the game: 15 production at 4 a turn
+    #[test]
+    fn warrior_build_time_follows_game_speed() {
+        for (speed, expected_turns) in [(GameSpeed::Quick, 3), (GameSpeed::Standard, 4), (GameSpeed::Epic, 6)] {
+            let mut app = city_app(TerrainType::TemperateConiferForest, civ_with(&[]));
+            app.world_mut().resource_mut::<CivilizationManager>().speed = speed;
+            let rome = found(&mut app, "Rome", CENTER, 1);
+            app.add_systems(Update, (
+                turn_system,
+                process_city_turns.after(turn_system),
+                spawn_produced_units.after(process_city_turns),
+            ));
+            app.world_mut().get_mut::<City>(rome).unwrap().queue_production(ProductionItem::Unit(UnitType::Warrior));
+            app.update();
+            assert_eq!(city(&app, rome).production_per_turn, 4.0);
+            
+            let mut turns = 0;
+            while app.world_mut().query::<&Unit>().iter(app.world()).next().is_none() {
+                end_turn(&mut app);
+                turns += 1;
+                assert!(turns <= 10, "no Warrior after 10 turns at {:?}", speed);
+            }
+            assert_eq!(turns, expected_turns, "at {:?}", speed);
+        }
+    }
+    
+    // Each difficulty level scales a city's yields and unit costs by exactly its modifiers
+    // against Prince, which changes nothing for anyone
+    #[test]
+    fn difficulty_scales_yields_against_a_neutral_prince() {
+        // Gross food, production, science, gold, culture and a Warrior's cost
+        let outcome = |difficulty: Difficulty, is_player: bool| -> [f32; 6] {
+            let mut civs = civ_with(&[]);
+            civs[0].set_player(is_player);
+            let mut app = city_app(TerrainType::TemperateDeciduousForest, civs);
+            app.world_mut().resource_mut::<CivilizationManager>().difficulty = difficulty;
+            let rome = found(&mut app, "Rome", CENTER, 1);
+            app.world_mut().get_mut::<City>(rome).unwrap().set_production(ProductionItem::Unit(UnitType::Warrior));
+            run(&mut app, process_city_turns);
+            
+            let rome = city(&app, rome);
+            [
+                rome.food_per_turn + rome.population as f32 * FOOD_PER_CITIZEN,
+                rome.production_per_turn,
+                rome.science_per_turn,
+                rome.gold_per_turn,
+                rome.culture_per_turn,
+                rome.required_production(&ProductionItem::Unit(UnitType::Warrior)),
+            ]
+        };
+        
+        assert_eq!(Difficulty::Prince.modifiers(true), DifficultyModifiers::NEUTRAL);
+        assert_eq!(Difficulty::Prince.modifiers(false), DifficultyModifiers::NEUTRAL);
+        let neutral = outcome(Difficulty::Prince, true);
+        assert_eq!(outcome(Difficulty::Prince, false), neutral);
+        
+        let scaled = |food: f32, production: f32, science: f32, gold: f32, culture: f32, unit_cost: f32| {
+            let [f, p, s, g, c, u] = neutral;
+            [f * food, p * production, s * science, g * gold, c * culture, u * unit_cost]
+        };
+        // Settler and Chieftain only help the player, and only with production
+        assert_eq!(outcome(Difficulty::Settler, true), scaled(1.0, 1.5, 1.0, 1.0, 1.0, 1.0));
+        assert_eq!(outcome(Difficulty::Settler, false), neutral);
+        assert_eq!(outcome(Difficulty::Chieftain, true), scaled(1.0, 1.25, 1.0, 1.0, 1.0, 1.0));
+        assert_eq!(outcome(Difficulty::Chieftain, false), neutral);
+        // King helps the AI with everything and discounts its units
+        assert_eq!(outcome(Difficulty::King, false), scaled(1.2, 1.2, 1.2, 1.2, 1.2, 0.8));
+        assert_eq!(outcome(Difficulty::King, true), neutral);
+    }
+    
+    // Every number a trait can touch, for a forest city with a Library and a domestic
+    // trade partner, its owner, and a Galley it launches. Whether the owner could cross
+    // oceans with Sailing counts 1 for yes.
+    fn trait_profile(traits: &[CivTrait]) -> Vec<f32> {
+        let mut app = city_app(TerrainType::TemperateDeciduousForest, civ_with(traits));
+        let rome = found(&mut app, "Rome", CENTER, 1);
+        let mut founded = app.world_mut().get_mut::<City>(rome).unwrap();
+        founded.buildings = vec![Building::Library];
+        founded.set_production(ProductionItem::Unit(UnitType::Warrior));
+        run(&mut app, process_city_turns);
+        let galley = run(&mut app, |mut commands: Commands, mut manager: ResMut<CivilizationManager>| {
+            spawn_unit(&mut commands, UnitType::Galley, 1, CENTER, &mut manager)
+        });
+        app.update();
+        
+        let rome = city(&app, rome);
+        let mut partner = City::new("Antium".to_string(), HexCoord { q: 3, r: 0 }, 1, 1, false);
+        partner.population = 3;
+        let manager = app.world().resource::<CivilizationManager>();
+        let owner = manager.get_civilization(1).unwrap();
+        vec![
+            rome.required_production(&ProductionItem::Unit(UnitType::Settler)),
+            rome.required_production(&ProductionItem::Unit(UnitType::Warrior)),
+            rome.required_production(&ProductionItem::Unit(UnitType::Worker)),
+            rome.production_rate_for(&ProductionItem::Building(Building::Granary)),
+            rome.production_rate_for(&ProductionItem::Unit(UnitType::Warrior)),
+            rome.culture_per_turn,
+            rome.culture_needed_for_expansion,
+            rome.maintenance_per_turn,
+            rome.max_trade_routes() as f32,
+            rome.trade_route_gold(&partner),
+            pressure_on(&rome, HexCoord { q: 2, r: 0 }),
+            owner.research_cost(Technology::Pottery, manager.speed),
+            owner.unit_maintenance(UnitType::Warrior),
+            app.world().get::<Unit>(galley).unwrap().max_movement_points as f32,
+            {
+                let mut sailors = owner.clone();
+                sailors.technologies.push(Technology::Sailing);
+                sailors.can_cross_oceans() as u8 as f32
             },
-            Transform::from_translation(Vec3::new(world_pos.x, world_pos.y - 20.0, 2.0)),
-        ));
+            rome.food_per_turn,
+            rome.science_per_turn,
+            rome.gold_per_turn,
+        ]
+    }
+    
+    // Each trait moves exactly the numbers it advertises, by exactly its constant, and leaves
+    // the rest of a traitless civilization's numbers alone. Militaristic's combat bonus is
+    // checked with the rest of combat.
+    #[test]
+    fn each_trait_changes_only_its_own_numbers() {
+        const SETTLER: usize = 0;
+        const WARRIOR: usize = 1;
+        const BUILDING_RATE: usize = 3;
+        const CULTURE: usize = 5;
+        const BORDER_COST: usize = 6;
+        const BUILDING_UPKEEP: usize = 7;
+        const ROUTES: usize = 8;
+        const ROUTE_GOLD: usize = 9;
+        const PRESSURE: usize = 10;
+        const RESEARCH: usize = 11;
+        const UNIT_UPKEEP: usize = 12;
+        const GALLEY_MOVES: usize = 13;
+        const OCEANS_WITH_SAILING: usize = 14;
+        
+        let base = trait_profile(&[]);
+        let changed = |changes: &[(usize, f32)]| {
+            let mut expected = base.clone();
+            for &(index, value) in changes {
+                expected[index] = value;
+            }
+            expected
+        };
+        let cases = [
+            (CivTrait::Expansionist, changed(&[
+                (SETTLER, base[SETTLER] * EXPANSIONIST_SETTLER_COST),
+                (BORDER_COST, base[BORDER_COST] * EXPANSIONIST_BORDER_COST),
+            ])),
+            (CivTrait::Industrious, changed(&[
+                (BUILDING_RATE, base[BUILDING_RATE] * (1.0 + INDUSTRIOUS_BUILDING_PRODUCTION)),
+            ])),
+            (CivTrait::Commercial, changed(&[
+                (ROUTES, base[ROUTES] + COMMERCIAL_EXTRA_TRADE_ROUTES as f32),
+                (ROUTE_GOLD, base[ROUTE_GOLD] + COMMERCIAL_ROUTE_GOLD),
+            ])),
+            (CivTrait::Militaristic, changed(&[
+                (WARRIOR, base[WARRIOR] * MILITARISTIC_UNIT_COST),
+            ])),
+            (CivTrait::Scientific, changed(&[
+                (RESEARCH, base[RESEARCH] * SCIENTIFIC_RESEARCH_COST),
+            ])),
+            (CivTrait::Seafaring, changed(&[
+                (GALLEY_MOVES, base[GALLEY_MOVES] + SEAFARING_NAVAL_MOVEMENT as f32),
+                (OCEANS_WITH_SAILING, 1.0),
+            ])),
+            (CivTrait::Spiritual, changed(&[
+                (CULTURE, base[CULTURE] * (1.0 + SPIRITUAL_CULTURE_BONUS)),
+                (BORDER_COST, base[BORDER_COST] * SPIRITUAL_BORDER_COST),
+                (PRESSURE, base[PRESSURE] * (1.0 + SPIRITUAL_CULTURE_BONUS) * (1.0 + SPIRITUAL_PRESSURE_BONUS)),
+            ])),
+            (CivTrait::Organized, changed(&[
+                (BUILDING_UPKEEP, base[BUILDING_UPKEEP] * ORGANIZED_MAINTENANCE),
+                (UNIT_UPKEEP, base[UNIT_UPKEEP] * ORGANIZED_MAINTENANCE),
+            ])),
+        ];
+        for (civ_trait, expected) in cases {
+            assert_eq!(trait_profile(&[civ_trait]), expected, "{:?}", civ_trait);
+        }
     }
-}
\ No newline at end of file
+}
@@ -1,10 +1,20 @@
 use bevy::prelude::*;
 use super::hex::HexCoord;
-use super::map::MapTile;
-use super::units::{Unit, UnitSelection};
-use super::cities::City;
+use super::map::{TileLookup, create_hexagon_mesh, evaluate_tile_suitability, HEX_SIZE};
+use super::units::{Unit, UnitOrder, UnitSelection};
+use super::cities::{City, CityEventWriters, CityFoundedEvent, UnitType};
 use super::civilization::CivilizationManager;
-use super::game_initialization::GameState;
+use super::game_initialization::{GameState, has_freshwater_nearby};
+use super::input::CursorState;
+use super::input_map::{Action, InputMap};
+use super::notifications::{GameNotification, NotificationCategory};
+use super::unit_actions::{ActionSite, UnitAction, UnitActionEvent, action_requested};
+
+// Cities must be at least this many tiles apart
+pub const MIN_CITY_DISTANCE: i32 = 3;
+
+// Settlement preview warns when a site is this close to another city or foreign territory
+const SETTLEMENT_WARNING_DISTANCE: i32 = 4;
 
 #[derive(Resource)]
 pub struct CityFoundingState {
@@ -24,73 +34,90 @@ impl Default for CityFoundingState {
 // System for handling city founding
 pub fn city_founding_system(
     mut commands: Commands,
-    keyboard: Res<ButtonInput<KeyCode>>,
+    mut action_events: EventReader<UnitActionEvent>,
     mut unit_query: Query<(Entity, &mut Unit)>,
     city_query: Query<&City>,
-    tile_query: Query<&MapTile>,
+    tiles: TileLookup,
     unit_selection: Res<UnitSelection>,
     mut civ_manager: ResMut<CivilizationManager>,
     game_state: Res<GameState>,
-    _founding_state: ResMut<CityFoundingState>,
+    mut founded_events: EventWriter<CityFoundedEvent>,
+    mut notifications: EventWriter<GameNotification>,
 ) {
-    if !game_state.is_initialized {
-        return;
-    }
-    
-    // Check for 'F' key to found city
-    if keyboard.just_pressed(KeyCode::KeyF) {
+    // Found City, from F or the action bar
+    if action_requested(&mut action_events, UnitAction::FoundCity) {
         if let Some(selected_unit_entity) = unit_selection.selected_unit {
             if let Ok((unit_entity, unit)) = unit_query.get_mut(selected_unit_entity) {
-                if unit.can_found_cities && unit.movement_points > 0 {
-                    // Check if location is valid for city founding
-                    if can_found_city_at(unit.hex_coord, &city_query, &tile_query) {
-                        // Generate a city name
-                        let city_name = generate_city_name(unit.civilization_id, &civ_manager, &city_query);
-                        
-                        // Create the city
-                        let city = City::new(
-                            city_name.clone(),
-                            unit.hex_coord,
-                            unit.civilization_id,
-                            game_state.game_turn,
-                            false, // Not a capital (first city is marked as capital during init)
-                        );
-                        
-                        let city_entity = commands.spawn(city).id();
-                        
-                        // Add city to civilization
-                        if let Some(civ) = civ_manager.get_civilization_mut(unit.civilization_id) {
-                            civ.add_city(city_entity);
-                        }
-                        
-                        // Remove the settler unit (they become the city)
-                        if let Some(civ) = civ_manager.get_civilization_mut(unit.civilization_id) {
-                            civ.remove_unit(unit_entity);
-                        }
-                        commands.entity(unit_entity).despawn();
-                        
-                        println!("Founded city {} at ({}, {})", city_name, unit.hex_coord.q, unit.hex_coord.r);
-                    } else {
-                        println!("Cannot found city here! Cities must be at least 3 tiles apart and on suitable land.");
+                let site = ActionSite::new(&unit, city_query.iter(), &tiles, &civ_manager);
+                match UnitAction::FoundCity.check(&unit, &site) {
+                    Some(Ok(())) => {
+                        found_city(&mut commands, unit_entity, &unit, city_query.iter(), game_state.game_turn, &mut civ_manager, &mut founded_events);
+                    }
+                    Some(Err(reason)) => {
+                        let text = format!("Cannot found city here! {}.", reason);
+                        notifications.write(GameNotification::new(NotificationCategory::Warning, text).at(unit.hex_coord));
+                    }
+                    None => {
+                        notifications.write(GameNotification::new(NotificationCategory::Warning, "Selected unit cannot found cities!"));
                     }
-                } else {
-                    println!("Selected unit cannot found cities or has no movement points!");
                 }
             }
         } else {
-            println!("No unit selected! Select a settler to found a city.");
+            notifications.write(GameNotification::new(NotificationCategory::Warning, "No unit selected! Select a settler to found a city."));
         }
     }
 }
 
-fn can_found_city_at(coord: HexCoord, city_query: &Query<&City>, tile_query: &Query<&MapTile>) -> bool {
+// Turns a settler into a new city on its hex; the caller checks the site with can_found_city_at
+pub fn found_city<'a>(
+    commands: &mut Commands,
+    unit_entity: Entity,
+    unit: &Unit,
+    cities: impl Iterator<Item = &'a City>,
+    turn: u32,
+    civ_manager: &mut CivilizationManager,
+    founded_events: &mut EventWriter<CityFoundedEvent>,
+) -> Entity {
+    // Generate a city name
+    let city_name = generate_city_name(unit.civilization_id, civ_manager, cities);
+    
+    // Create the city
+    let city = City::new(
+        city_name,
+        unit.hex_coord,
+        unit.civilization_id,
+        turn,
+        false, // Not a capital (first city is marked as capital during init)
+    );
+    
+    let city_entity = commands.spawn(city).id();
+    
+    // Add city to civilization
+    if let Some(civ) = civ_manager.get_civilization_mut(unit.civilization_id) {
+        civ.add_city(city_entity);
+    }
+    founded_events.write(CityFoundedEvent {
+        city_entity,
+        civilization_id: unit.civilization_id,
+        hex_coord: unit.hex_coord,
+    });
+    
+    // Remove the settler unit (they become the city)
+    if let Some(civ) = civ_manager.get_civilization_mut(unit.civilization_id) {
+        civ.remove_unit(unit_entity);
+    }
+    commands.entity(unit_entity).despawn();
+    city_entity
+}
+
+pub fn can_found_city_at<'a>(coord: HexCoord, cities: impl Iterator<Item = &'a City> + Clone, tiles: &TileLookup) -> bool {
     // Check if there's already a city here
-    if city_query.iter().any(|city| city.hex_coord == coord) {
+    if cities.clone().any(|city| city.hex_coord == coord) {
         return false;
     }
     
     // Check if the tile is suitable (must be land)
-    if let Some(tile) = tile_query.iter().find(|t| t.hex_coord == coord) {
+    if let Some(tile) = tiles.get(coord) {
         let biome = super::world_gen::BiomeType::from_u8(tile.biome);
         if matches!(biome, super::world_gen::BiomeType::Ocean | super::world_gen::BiomeType::Lake) {
             return false;
@@ -99,10 +126,9 @@ fn can_found_city_at(coord: HexCoord, city_query: &Query<&City>, tile_query: &Qu
         return false; // Tile doesn't exist
     }
     
-    // Check minimum distance from other cities (at least 3 tiles)
-    let min_distance = 3;
-    for city in city_query.iter() {
-        if hex_distance(coord, city.hex_coord) < min_distance {
+    // Check minimum distance from other cities
+    for city in cities {
+        if coord.distance(city.hex_coord) < MIN_CITY_DISTANCE {
             return false;
         }
     }
@@ -110,9 +136,9 @@ fn can_found_city_at(coord: HexCoord, city_query: &Query<&City>, tile_query: &Qu
     true
 }
 
-fn generate_city_name(civ_id: u32, civ_manager: &CivilizationManager, city_query: &Query<&City>) -> String {
+fn generate_city_name<'a>(civ_id: u32, civ_manager: &CivilizationManager, cities: impl Iterator<Item = &'a City>) -> String {
     // Get existing city names for this civilization
-    let existing_names: std::collections::HashSet<String> = city_query.iter()
+    let existing_names: std::collections::HashSet<String> = cities
         .filter(|city| city.civilization_id == civ_id)
         .map(|city| city.name.clone())
         .collect();
@@ -184,22 +210,21 @@ fn get_city_names_for_civilization(civ_name: &str) -> Vec<&'static str> {
 
 // System for handling worker actions (building improvements)
 pub fn worker_actions_system(
-    keyboard: Res<ButtonInput<KeyCode>>,
+    mut action_events: EventReader<UnitActionEvent>,
     mut unit_query: Query<&mut Unit>,
     unit_selection: Res<UnitSelection>,
-    game_state: Res<GameState>,
+    mut notifications: EventWriter<GameNotification>,
 ) {
-    if !game_state.is_initialized {
-        return;
-    }
-    
-    // Check for 'B' key to build improvement
-    if keyboard.just_pressed(KeyCode::KeyB) {
+    // Build, from B or the action bar
+    if action_requested(&mut action_events, UnitAction::BuildImprovement) {
         if let Some(selected_unit_entity) = unit_selection.selected_unit {
             if let Ok(mut unit) = unit_query.get_mut(selected_unit_entity) {
                 if unit.can_build_improvements && unit.movement_points > 0 {
                     // For now, just consume movement points and show message
                     // In a full implementation, this would start an improvement construction
+                    if matches!(unit.order, UnitOrder::Automate(_)) {
+                        unit.clear_order(); // Told what to do, so no longer automated
+                    }
                     unit.movement_points = 0;
                     unit.has_moved = true;
                     
@@ -207,72 +232,134 @@ pub fn worker_actions_system(
                              unit.hex_coord.q, unit.hex_coord.r);
                     println!("(Improvement system not fully implemented yet)");
                 } else {
-                    println!("Selected unit cannot build improvements or has no movement points!");
+                    notifications.write(GameNotification::new(NotificationCategory::Warning, "Selected unit cannot build improvements or has no movement points!"));
                 }
             }
         } else {
-            println!("No unit selected! Select a worker to build improvements.");
+            notifications.write(GameNotification::new(NotificationCategory::Warning, "No unit selected! Select a worker to build improvements."));
         }
     }
 }
 
-// System for skipping unit turns
-pub fn skip_unit_system(
-    keyboard: Res<ButtonInput<KeyCode>>,
-    mut unit_query: Query<&mut Unit>,
-    unit_selection: Res<UnitSelection>,
-    game_state: Res<GameState>,
+// A Great Scientist's lump of science once there's no technology left to hand over
+const GREAT_SCIENTIST_SCIENCE: f32 = 150.0;
+// Gold a Great Merchant brings home from a trade mission into foreign territory
+const GREAT_MERCHANT_GOLD: f32 = 200.0;
+
+// System for a Great Person's one-time ability (U or the action bar); the unit is used up
+#[allow(clippy::too_many_arguments)]
+pub fn great_person_ability_system(
+    mut commands: Commands,
+    mut action_events: EventReader<UnitActionEvent>,
+    unit_query: Query<&Unit>,
+    mut unit_selection: ResMut<UnitSelection>,
+    mut city_query: Query<(Entity, &mut City)>,
+    mut civ_manager: ResMut<CivilizationManager>,
+    mut city_events: CityEventWriters,
+    mut notifications: EventWriter<GameNotification>,
 ) {
-    if !game_state.is_initialized {
+    if !action_requested(&mut action_events, UnitAction::UseAbility) {
         return;
     }
+    let Some(unit_entity) = unit_selection.selected_unit else { return };
+    let Ok(unit) = unit_query.get(unit_entity) else { return };
+    let civ_id = unit.civilization_id;
     
-    // Check for 'S' key to skip unit turn
-    if keyboard.just_pressed(KeyCode::KeyS) {
-        if let Some(selected_unit_entity) = unit_selection.selected_unit {
-            if let Ok(mut unit) = unit_query.get_mut(selected_unit_entity) {
-                unit.movement_points = 0;
-                unit.has_moved = true;
-                println!("Skipped turn for {} at ({}, {})", 
-                         unit.unit_type.get_name(), unit.hex_coord.q, unit.hex_coord.r);
+    let notify = |category: NotificationCategory, text: String| GameNotification::new(category, text).at(unit.hex_coord).for_civ(civ_id);
+    let (used, notification) = match unit.unit_type {
+        UnitType::GreatScientist => {
+            let Some(civ) = civ_manager.get_civilization_mut(civ_id) else { return };
+            let text = match civ.next_free_technology() {
+                Some(tech) => {
+                    civ.grant_technology(tech);
+                    format!("Great Scientist discovered {}!", tech.get_name())
+                }
+                None => {
+                    civ.science_points += GREAT_SCIENTIST_SCIENCE;
+                    format!("Great Scientist contributed {:.0} science", GREAT_SCIENTIST_SCIENCE)
+                }
+            };
+            (true, notify(NotificationCategory::Research, text))
+        }
+        UnitType::GreatEngineer => {
+            let own_city = city_query
+                .iter_mut()
+                .find(|(_, city)| city.hex_coord == unit.hex_coord && city.civilization_id == civ_id);
+            match own_city {
+                Some((city_entity, mut city)) => match city.finish_production(city_entity, &mut civ_manager, &mut city_events) {
+                    Some(item) => (true, notify(NotificationCategory::City, format!("Great Engineer finished the {} in {}", item.get_name(), city.name))),
+                    None => (false, notify(NotificationCategory::Warning, format!("{} isn't producing anything for the Great Engineer to finish", city.name))),
+                },
+                None => (false, notify(NotificationCategory::Warning, "A Great Engineer must be in one of your cities to finish its production".to_string())),
+            }
+        }
+        UnitType::GreatMerchant => {
+            let in_foreign_territory = city_query
+                .iter()
+                .any(|(_, city)| city.civilization_id != civ_id && city.territory_tiles.contains(&unit.hex_coord));
+            if in_foreign_territory {
+                if let Some(civ) = civ_manager.get_civilization_mut(civ_id) {
+                    civ.gold += GREAT_MERCHANT_GOLD;
+                }
+                (true, notify(NotificationCategory::City, format!("Great Merchant's trade mission earned {:.0} gold", GREAT_MERCHANT_GOLD)))
+            } else {
+                (false, notify(NotificationCategory::Warning, "A Great Merchant must be in foreign territory to start a trade mission".to_string()))
             }
         }
+        _ => return,
+    };
+    notifications.write(notification);
+    
+    if used {
+        if let Some(civ) = civ_manager.get_civilization_mut(civ_id) {
+            civ.remove_unit(unit_entity);
+        }
+        commands.entity(unit_entity).despawn();
+        unit_selection.selected_unit = None;
     }
 }
 
-// System for fortifying units
-pub fn fortify_system(
-    keyboard: Res<ButtonInput<KeyCode>>,
+// System for skipping unit turns
+pub fn skip_unit_system(
+    mut action_events: EventReader<UnitActionEvent>,
     mut unit_query: Query<&mut Unit>,
     unit_selection: Res<UnitSelection>,
-    game_state: Res<GameState>,
 ) {
-    if !game_state.is_initialized {
-        return;
-    }
-    
-    // Check for 'Shift+F' to fortify (different from found city)
-    if keyboard.just_pressed(KeyCode::KeyF) && 
-       (keyboard.pressed(KeyCode::ShiftLeft) || keyboard.pressed(KeyCode::ShiftRight)) {
-        if let Some(selected_unit_entity) = unit_selection.selected_unit {
-            if let Ok(mut unit) = unit_query.get_mut(selected_unit_entity) {
-                if unit.can_attack { // Only military units can fortify
-                    unit.fortify();
-                    println!("Unit fortified at ({}, {}). Defense bonus will increase each turn.", 
-                             unit.hex_coord.q, unit.hex_coord.r);
-                } else {
-                    println!("Only military units can fortify!");
-                }
-            }
+    // Skip, from S or the action bar
+    if action_requested(&mut action_events, UnitAction::Skip)
+        && let Some(selected_unit_entity) = unit_selection.selected_unit
+        && let Ok(mut unit) = unit_query.get_mut(selected_unit_entity)
+    {
+        if matches!(unit.order, UnitOrder::Automate(_)) {
+            unit.clear_order();
         }
+        unit.movement_points = 0;
+        unit.has_moved = true;
+        println!("Skipped turn for {} at ({}, {})", 
+                 unit.unit_type.get_name(), unit.hex_coord.q, unit.hex_coord.r);
     }
 }
 
-fn hex_distance(a: HexCoord, b: HexCoord) -> i32 {
-    let dq = (a.q - b.q).abs();
-    let dr = (a.r - b.r).abs();
-    let ds = (-(a.q + a.r) + (b.q + b.r)).abs();
-    (dq.max(dr)).max(ds)
+// System for fortifying units
+pub fn fortify_system(
+    mut action_events: EventReader<UnitActionEvent>,
+    mut unit_query: Query<&mut Unit>,
+    unit_selection: Res<UnitSelection>,
+    mut notifications: EventWriter<GameNotification>,
+) {
+    // Fortify, from Shift+F or the action bar
+    if action_requested(&mut action_events, UnitAction::Fortify)
+        && let Some(selected_unit_entity) = unit_selection.selected_unit
+        && let Ok(mut unit) = unit_query.get_mut(selected_unit_entity)
+    {
+        if unit.can_attack { // Only military units can fortify
+            unit.fortify();
+            println!("Unit fortified at ({}, {}). Defense bonus will increase each turn.", 
+                     unit.hex_coord.q, unit.hex_coord.r);
+        } else {
+            notifications.write(GameNotification::new(NotificationCategory::Warning, "Only military units can fortify!"));
+        }
+    }
 }
 
 // Helper function to check if a player has units that can still move
@@ -292,43 +379,243 @@ pub fn player_has_active_units(
 }
 
 // System to auto-advance turn when player has no more moves
+#[allow(clippy::too_many_arguments)]
 pub fn auto_turn_advance_system(
     unit_query: Query<&Unit>,
     civ_manager: Res<CivilizationManager>,
     game_state: Res<GameState>,
     keyboard: Res<ButtonInput<KeyCode>>,
+    input_map: Res<InputMap>,
+    mut notifications: EventWriter<GameNotification>,
 ) {
-    if !game_state.is_initialized {
-        return;
-    }
-    
     // Only check during player turns
     if let super::game_initialization::GamePhase::PlayerTurn = game_state.current_phase {
-        // Check if player pressed 'N' for next unit with moves
-        if keyboard.just_pressed(KeyCode::KeyN) {
-            if let Some(player_civ) = civ_manager.get_player_civilization() {
-                let active_units: Vec<_> = unit_query.iter()
-                    .filter(|unit| {
-                        unit.civilization_id == player_civ.id && 
-                        unit.movement_points > 0 && 
-                        !unit.has_moved
-                    })
-                    .collect();
-                
-                if active_units.is_empty() {
-                    println!("No units with remaining movement. Press SPACE to end turn.");
-                } else {
-                    println!("You have {} units that can still move:", active_units.len());
-                    for (i, unit) in active_units.iter().enumerate() {
-                        println!("  {}. {} at ({}, {}) - {} movement points", 
-                                 i + 1,
-                                 unit.unit_type.get_name(),
-                                 unit.hex_coord.q, 
-                                 unit.hex_coord.r,
-                                 unit.movement_points);
-                    }
+        // Check if player pressed Next Unit (N) for next unit with moves
+        if input_map.just_pressed(Action::NextUnit, &keyboard)
+            && let Some(player_civ) = civ_manager.get_player_civilization()
+        {
+            let active_units: Vec<_> = unit_query.iter()
+                .filter(|unit| {
+                    unit.civilization_id == player_civ.id && 
+                    unit.movement_points > 0 && 
+                    !unit.has_moved
+                })
+                .collect();
+            
+            if active_units.is_empty() {
+                notifications.write(GameNotification::new(NotificationCategory::System, "No units with remaining movement. Press SPACE to end turn."));
+            } else {
+                println!("You have {} units that can still move:", active_units.len());
+                for (i, unit) in active_units.iter().enumerate() {
+                    println!("  {}. {} at ({}, {}) - {} movement points", 
+                             i + 1,
+                             unit.unit_type.get_name(),
+                             unit.hex_coord.q, 
+                             unit.hex_coord.r,
+                             unit.movement_points);
                 }
             }
         }
     }
-}
\ No newline at end of file
+}
+#[derive(Component)]
+pub struct SettlementPreviewOverlay;
+
+#[derive(Component)]
+pub struct SettlementPreviewText;
+
+/// Founding preview for the hovered tile while a city-founding unit is selected
+#[derive(Resource)]
+pub struct SettlementPreview {
+    pub text: Option<String>,
+    // Settler, hovered hex and city count the overlay was built for
+    shown_for: Option<(Entity, HexCoord, usize)>,
+    overlays: Vec<Entity>,
+    hex_mesh: Handle<Mesh>,
+    legal_material: Handle<ColorMaterial>,
+    illegal_material: Handle<ColorMaterial>,
+    claimed_material: Handle<ColorMaterial>,
+}
+
+impl FromWorld for SettlementPreview {
+    fn from_world(world: &mut World) -> Self {
+        let hex_mesh = world.resource_mut::<Assets<Mesh>>().add(create_hexagon_mesh(HEX_SIZE));
+        let mut materials = world.resource_mut::<Assets<ColorMaterial>>();
+        
+        Self {
+            text: None,
+            shown_for: None,
+            overlays: Vec::new(),
+            hex_mesh,
+            legal_material: materials.add(Color::srgba(0.2, 1.0, 0.3, 0.35)),
+            illegal_material: materials.add(Color::srgba(1.0, 0.2, 0.2, 0.35)),
+            claimed_material: materials.add(Color::srgba(1.0, 0.6, 0.1, 0.45)),
+        }
+    }
+}
+
+impl SettlementPreview {
+    // Forgets the preview once the game it was shown in is gone (the overlays go with it)
+    pub fn clear(&mut self) {
+        self.text = None;
+        self.shown_for = None;
+        self.overlays.clear();
+    }
+}
+
+pub fn reset_settlement_preview(mut preview: ResMut<SettlementPreview>) {
+    preview.clear();
+}
+
+// System to overlay the territory, yields and warnings of founding a city on the hovered tile
+pub fn settlement_preview_system(
+    mut commands: Commands,
+    unit_query: Query<&Unit>,
+    city_query: Query<&City>,
+    tiles: TileLookup,
+    unit_selection: Res<UnitSelection>,
+    cursor: Res<CursorState>,
+    mut preview: ResMut<SettlementPreview>,
+) {
+    let settler = unit_selection
+        .selected_unit
+        .filter(|entity| unit_query.get(*entity).is_ok_and(|unit| unit.can_found_cities));
+    
+    let hovered = cursor.hex.filter(|_| !cursor.over_ui).filter(|coord| tiles.get(*coord).is_some());
+    
+    let current = settler.zip(hovered).map(|(entity, coord)| (entity, coord, city_query.iter().len()));
+    if current == preview.shown_for {
+        return;
+    }
+    preview.shown_for = current;
+    
+    for overlay in preview.overlays.drain(..) {
+        commands.entity(overlay).despawn();
+    }
+    preview.text = None;
+    
+    let Some((settler_entity, site, _)) = current else { return };
+    let Ok(unit) = unit_query.get(settler_entity) else { return };
+    let Some(site_tile) = tiles.get(site) else { return };
+    
+    let legal = can_found_city_at(site, city_query.iter(), &tiles);
+    let territory = City::calculate_initial_territory(site);
+    
+    // Tiles already inside another civilization's borders
+    let foreign_tiles: std::collections::HashSet<HexCoord> = city_query
+        .iter()
+        .filter(|city| city.civilization_id != unit.civilization_id)
+        .flat_map(|city| city.territory_tiles.iter().copied())
+        .collect();
+    
+    let mut overlays = Vec::new();
+    for &coord in &territory {
+        let material = if foreign_tiles.contains(&coord) {
+            preview.claimed_material.clone()
+        } else if legal {
+            preview.legal_material.clone()
+        } else {
+            preview.illegal_material.clone()
+        };
+        
+        let world_pos = coord.to_world_pos(HEX_SIZE);
+        overlays.push(commands.spawn((
+            SettlementPreviewOverlay,
+            Mesh2d(preview.hex_mesh.clone()),
+            MeshMaterial2d(material),
+            Transform::from_translation(Vec3::new(world_pos.x, world_pos.y, 0.5)),
+        )).id());
+    }
+    preview.overlays = overlays;
+    
+    // Projected yields: the center tile is worked from the start, the ring is what the city can grow into
+    let (center_food, center_production, center_science) = City::get_tile_yields(site_tile);
+    let center_gold = City::get_tile_gold_yield(site_tile);
+    
+    let (mut food, mut production, mut science, mut gold) = (0.0, 0.0, 0.0, 0.0);
+    for tile in territory.iter().filter_map(|coord| tiles.get(*coord)) {
+        let (tile_food, tile_production, tile_science) = City::get_tile_yields(tile);
+        food += tile_food;
+        production += tile_production;
+        science += tile_science;
+        gold += City::get_tile_gold_yield(tile);
+    }
+    
+    let fresh_water = site_tile.has_river || has_freshwater_nearby(site, |coord| tiles.get(coord));
+    let suitability = evaluate_tile_suitability(site_tile);
+    
+    let mut text = format!(
+        "=== FOUND CITY ({}, {}) ===\n{}\nCenter: {:.0}F {:.0}P {:.0}S {:.0}G\nTerritory: {:.0}F {:.0}P {:.0}S {:.0}G\nFresh water: {}\nSettlement: {:.0}% | Defense: {:.0}%",
+        site.q,
+        site.r,
+        if legal { "Can found city here" } else { "Cannot found city here" },
+        center_food, center_production, center_science, center_gold,
+        food, production, science, gold,
+        if fresh_water { "Yes" } else { "No" },
+        suitability.settlement * 100.0,
+        suitability.defensibility * 100.0,
+    );
+    
+    if let Some(nearest) = city_query
+        .iter()
+        .min_by_key(|city| site.distance(city.hex_coord))
+        .filter(|city| site.distance(city.hex_coord) <= SETTLEMENT_WARNING_DISTANCE)
+    {
+        text.push_str(&format!(
+            "\n! {} tiles from {}",
+            site.distance(nearest.hex_coord),
+            nearest.name
+        ));
+    }
+    
+    if foreign_tiles
+        .iter()
+        .any(|coord| site.distance(*coord) <= SETTLEMENT_WARNING_DISTANCE)
+    {
+        text.push_str("\n! Near foreign territory");
+    }
+    
+    preview.text = Some(text);
+}
+
+// System to show the settlement preview text next to the cursor
+pub fn settlement_preview_text_system(
+    preview: Res<SettlementPreview>,
+    cursor: Res<CursorState>,
+    mut text_query: Query<(&mut Text, &mut Node, &mut Visibility), With<SettlementPreviewText>>,
+    ui_scale: Res<UiScale>,
+) {
+    let Ok((mut text, mut node, mut visibility)) = text_query.single_mut() else { return };
+    
+    // Node offsets are in interface pixels, not the window's
+    match (&preview.text, cursor.screen_pos.map(|pos| pos / ui_scale.0)) {
+        (Some(preview_text), Some(screen_pos)) => {
+            if **text != *preview_text {
+                **text = preview_text.clone();
+            }
+            node.left = Val::Px(screen_pos.x + 18.0);
+            node.top = Val::Px(screen_pos.y + 18.0);
+            *visibility = Visibility::Visible;
+        }
+        _ => *visibility = Visibility::Hidden,
+    }
+}
+
+pub fn setup_settlement_preview_ui(mut commands: Commands) {
+    commands.spawn((
+        SettlementPreviewText,
+        Text::new(""),
+        TextFont {
+            font_size: 12.0,
+            ..default()
+        },
+        TextColor(Color::srgb(1.0, 1.0, 0.85)),
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.75)),
+        Node {
+            position_type: PositionType::Absolute,
+            padding: UiRect::all(Val::Px(4.0)),
+            ..default()
+        },
+        Visibility::Hidden,
+    ));
+}
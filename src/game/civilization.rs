@@ -1,12 +1,42 @@
 use bevy::prelude::*;
-use std::collections::HashMap;
+use bevy::ecs::entity::{EntityMapper, MapEntities};
+use rand::Rng;
+use rand::seq::IndexedRandom;
+use std::collections::{HashMap, HashSet};
+use super::cities::{GreatPersonType, UnitType};
+use super::diplomacy::DiplomacyState;
+use super::resources::ResourceType;
+use super::rules::rules;
+use super::stable_id::as_stable_ids;
+use serde::{Deserialize, Serialize};
 
-#[derive(Resource)]
+// Great People points needed for the first Great Person, and how much more each later one costs
+const GREAT_PERSON_BASE_THRESHOLD: f32 = 100.0;
+const GREAT_PERSON_THRESHOLD_STEP: f32 = 100.0;
+
+/// Owner id of barbarian units and camps. The barbarians aren't a civilization:
+/// they have no entry in the manager and no diplomacy, only a phase at the start of each turn.
+pub const BARBARIAN_CIV_ID: u32 = 0;
+pub const BARBARIAN_COLOR: Color = Color::srgb(0.7, 0.15, 0.1);
+
+// How civilizations the player hasn't met are shown
+pub const UNKNOWN_PLAYER: &str = "Unknown Player";
+
+#[derive(Resource, Clone, Serialize, Deserialize)]
 pub struct CivilizationManager {
     pub civilizations: HashMap<u32, Civilization>,
     pub next_civ_id: u32,
     pub current_turn_civ: u32,
     pub turn_number: u32,
+    pub diplomacy: DiplomacyState,
+    pub difficulty: Difficulty, // Copied from GameSettings when the game starts
+    pub barbarians: bool,       // Likewise; adds the barbarian phase to the turn rotation
+    #[serde(default)]
+    pub speed: GameSpeed,       // Likewise; scales what everything costs
+    #[serde(default)]
+    pub active_player: u32,     // The human whose turn it is, or who played last; see get_player_civilization
+    #[serde(default)]
+    pub observed: Option<u32>,  // With no humans, the civilization the panels follow
 }
 
 impl Default for CivilizationManager {
@@ -16,11 +46,26 @@ impl Default for CivilizationManager {
             next_civ_id: 1, // Start at 1, 0 is reserved for neutral/barbarian
             current_turn_civ: 1,
             turn_number: 1,
+            diplomacy: DiplomacyState::default(),
+            difficulty: Difficulty::default(),
+            barbarians: false,
+            speed: GameSpeed::default(),
+            active_player: 0,
+            observed: None,
+        }
+    }
+}
+
+impl MapEntities for CivilizationManager {
+    fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+        for civ in self.civilizations.values_mut() {
+            civ.cities.map_entities(entity_mapper);
+            civ.units.map_entities(entity_mapper);
         }
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Civilization {
     pub id: u32,
     pub name: String,
@@ -30,16 +75,25 @@ pub struct Civilization {
     pub is_ai: bool,
     pub civ_type: CivilizationType,
     pub traits: Vec<CivTrait>,
+    #[serde(with = "as_stable_ids")]
     pub cities: Vec<Entity>,
+    #[serde(with = "as_stable_ids")]
     pub units: Vec<Entity>,
     pub technologies: Vec<Technology>,
     pub culture: f32,
     pub science_points: f32,
     pub gold: f32,
     pub military_strength: f32,
+    pub great_people_points: HashMap<GreatPersonType, f32>,
+    pub great_people_born: u32,     // Raises the threshold for the next one
+    pub current_research: Option<(Technology, f32)>, // Target and science put into it so far
+    pub research_queue: Vec<Technology>,             // Researched in order once the current target is done
+    pub eliminated: bool,                            // Lost its last city; out of the game
+    pub met_civilizations: HashSet<u32>,             // Civilizations it has made contact with
+    pub strategic_resources: HashMap<ResourceType, u32>, // Strategic resource tiles in its territory
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub enum CivilizationType {
     Agricultural,  // Bonus to food and growth
     Commercial,    // Bonus to trade and gold
@@ -49,19 +103,174 @@ pub enum CivilizationType {
     Maritime,      // Bonus to naval units and coastal settlements
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub enum CivTrait {
-    Expansionist,      // Faster settler production, movement bonus
-    Industrious,       // Production bonus to buildings and units
-    Commercial,        // Extra trade routes, gold bonus
-    Militaristic,      // Combat bonuses, cheaper military units
-    Scientific,        // Research bonus, starts with extra tech
-    Seafaring,         // Naval bonuses, can cross oceans earlier
-    Spiritual,         // Culture bonus, faster border expansion
-    Organized,         // Maintenance cost reduction, efficiency bonus
+    Expansionist,      // Cheaper settlers, faster border growth
+    Industrious,       // More production toward buildings
+    Commercial,        // An extra trade route per city, more gold per route
+    Militaristic,      // Stronger and cheaper military units
+    Scientific,        // Cheaper technologies
+    Seafaring,         // Faster ships, and oceans crossed from Sailing on
+    Spiritual,         // More culture, faster border growth, stronger pull on rival borders
+    Organized,         // Cheaper unit and building upkeep
+}
+
+// Trait effects. Each is applied where a comment names its trait:
+// City::required_production, City::production_rate_for, City::calculate_yields,
+// City::max_trade_routes, City::trade_route_gold, Civilization::research_cost,
+// Civilization::unit_maintenance, Civilization::can_cross_oceans, combat::target_preview,
+// units::spawn_unit and culture::culture_pressure_system.
+pub const EXPANSIONIST_SETTLER_COST: f32 = 0.75;
+pub const EXPANSIONIST_BORDER_COST: f32 = 0.75;
+pub const INDUSTRIOUS_BUILDING_PRODUCTION: f32 = 0.25;
+pub const MILITARISTIC_COMBAT_BONUS: f32 = 0.1;
+pub const MILITARISTIC_UNIT_COST: f32 = 0.8;
+pub const SEAFARING_NAVAL_MOVEMENT: u32 = 1;
+pub const SEAFARING_OCEAN_TECH: Technology = Technology::Sailing;
+pub const SCIENTIFIC_RESEARCH_COST: f32 = 0.85;
+pub const SPIRITUAL_CULTURE_BONUS: f32 = 0.25;
+pub const SPIRITUAL_BORDER_COST: f32 = 0.75;
+pub const SPIRITUAL_PRESSURE_BONUS: f32 = 0.5;
+pub const ORGANIZED_MAINTENANCE: f32 = 0.75;
+pub const COMMERCIAL_EXTRA_TRADE_ROUTES: usize = 1;
+pub const COMMERCIAL_ROUTE_GOLD: f32 = 1.0;
+
+/// How much help the game gives the player or the AI. Prince plays it straight;
+/// below it the player gets a head start, above it the AI does.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum Difficulty {
+    Settler,
+    Chieftain,
+    #[default]
+    Prince,
+    King,
 }
 
+/// Bonuses one civilization gets from the difficulty level
 #[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DifficultyModifiers {
+    pub yield_bonus: f32,         // Added share of every city yield
+    pub production_bonus: f32,    // Added share of city production, on top of yield_bonus
+    pub unit_cost: f32,           // Multiplier on the production units cost
+    pub starting_gold: f32,       // Gold in the treasury on turn 1
+    pub extra_starting_unit: bool, // A second Warrior at the start
+}
+
+impl DifficultyModifiers {
+    pub const NEUTRAL: Self = Self {
+        yield_bonus: 0.0,
+        production_bonus: 0.0,
+        unit_cost: 1.0,
+        starting_gold: 0.0,
+        extra_starting_unit: false,
+    };
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [Difficulty::Settler, Difficulty::Chieftain, Difficulty::Prince, Difficulty::King];
+    
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Difficulty::Settler => "Settler",
+            Difficulty::Chieftain => "Chieftain",
+            Difficulty::Prince => "Prince",
+            Difficulty::King => "King",
+        }
+    }
+    
+    // Case-insensitive lookup by name, for the command line
+    pub fn from_name(name: &str) -> Option<Difficulty> {
+        Self::ALL.into_iter().find(|difficulty| difficulty.get_name().eq_ignore_ascii_case(name))
+    }
+    
+    // What the level gives the player (is_player) or each AI civilization
+    pub fn modifiers(&self, is_player: bool) -> DifficultyModifiers {
+        match (self, is_player) {
+            (Difficulty::Settler, true) => DifficultyModifiers {
+                production_bonus: 0.5,
+                starting_gold: 100.0,
+                ..DifficultyModifiers::NEUTRAL
+            },
+            (Difficulty::Chieftain, true) => DifficultyModifiers {
+                production_bonus: 0.25,
+                starting_gold: 50.0,
+                ..DifficultyModifiers::NEUTRAL
+            },
+            (Difficulty::King, false) => DifficultyModifiers {
+                yield_bonus: 0.2,
+                unit_cost: 0.8,
+                extra_starting_unit: true,
+                ..DifficultyModifiers::NEUTRAL
+            },
+            _ => DifficultyModifiers::NEUTRAL,
+        }
+    }
+}
+
+/// How long a game takes. Costs and thresholds are scaled where they're looked up,
+/// so the stored values, and saves, are the same at every speed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum GameSpeed {
+    Quick,
+    #[default]
+    Standard,
+    Epic,
+}
+
+impl GameSpeed {
+    pub const ALL: [GameSpeed; 3] = [GameSpeed::Quick, GameSpeed::Standard, GameSpeed::Epic];
+    
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            GameSpeed::Quick => "Quick",
+            GameSpeed::Standard => "Standard",
+            GameSpeed::Epic => "Epic",
+        }
+    }
+    
+    // Case-insensitive lookup by name, for the command line
+    pub fn from_name(name: &str) -> Option<GameSpeed> {
+        Self::ALL.into_iter().find(|speed| speed.get_name().eq_ignore_ascii_case(name))
+    }
+    
+    // Applied to production costs, tech costs, food for growth and culture for borders
+    pub fn multiplier(&self) -> f32 {
+        match self {
+            GameSpeed::Quick => 0.67,
+            GameSpeed::Standard => 1.0,
+            GameSpeed::Epic => 1.5,
+        }
+    }
+}
+
+/// Ages of the tech tree. A civilization is in the latest era it has a technology from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Era {
+    Ancient,
+    Classical,
+    Medieval,
+}
+
+impl Era {
+    pub fn get_name(self) -> &'static str {
+        match self {
+            Era::Ancient => "Ancient",
+            Era::Classical => "Classical",
+            Era::Medieval => "Medieval",
+        }
+    }
+    
+    // Cost of the era's first technologies; deeper ones cost more
+    fn base_cost(self) -> f32 {
+        match self {
+            Era::Ancient => 25.0,
+            Era::Classical => 60.0,
+            Era::Medieval => 120.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Technology {
     // Ancient Era
     Agriculture,
@@ -72,6 +281,9 @@ pub enum Technology {
     Writing,
     Archery,
     Masonry,
+    Sailing,
+    BronzeWorking,
+    Calendar,
     
     // Classical Era
     Mathematics,
@@ -79,8 +291,157 @@ pub enum Technology {
     Ironworking,
     Construction,
     HorsebackRiding,
+    Philosophy,
+    Literature,
+    
+    // Medieval Era
+    Engineering,
+    Feudalism,
+    Theology,
+    Chivalry,
+    Astronomy,
+    CivilService,
+    Machinery,
+}
+
+// The naval technology that opens the oceans to ships and embarking land units
+pub const OCEAN_TECH: Technology = Technology::Astronomy;
+
+impl Technology {
+    pub const ALL: [Technology; 25] = [
+        Technology::Agriculture,
+        Technology::AnimalHusbandry,
+        Technology::Mining,
+        Technology::Pottery,
+        Technology::TheWheel,
+        Technology::Writing,
+        Technology::Archery,
+        Technology::Masonry,
+        Technology::Sailing,
+        Technology::BronzeWorking,
+        Technology::Calendar,
+        Technology::Mathematics,
+        Technology::Currency,
+        Technology::Ironworking,
+        Technology::Construction,
+        Technology::HorsebackRiding,
+        Technology::Philosophy,
+        Technology::Literature,
+        Technology::Engineering,
+        Technology::Feudalism,
+        Technology::Theology,
+        Technology::Chivalry,
+        Technology::Astronomy,
+        Technology::CivilService,
+        Technology::Machinery,
+    ];
+    
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Technology::Agriculture => "Agriculture",
+            Technology::AnimalHusbandry => "Animal Husbandry",
+            Technology::Mining => "Mining",
+            Technology::Pottery => "Pottery",
+            Technology::TheWheel => "The Wheel",
+            Technology::Writing => "Writing",
+            Technology::Archery => "Archery",
+            Technology::Masonry => "Masonry",
+            Technology::Sailing => "Sailing",
+            Technology::BronzeWorking => "Bronze Working",
+            Technology::Calendar => "Calendar",
+            Technology::Mathematics => "Mathematics",
+            Technology::Currency => "Currency",
+            Technology::Ironworking => "Ironworking",
+            Technology::Construction => "Construction",
+            Technology::HorsebackRiding => "Horseback Riding",
+            Technology::Philosophy => "Philosophy",
+            Technology::Literature => "Literature",
+            Technology::Engineering => "Engineering",
+            Technology::Feudalism => "Feudalism",
+            Technology::Theology => "Theology",
+            Technology::Chivalry => "Chivalry",
+            Technology::Astronomy => "Astronomy",
+            Technology::CivilService => "Civil Service",
+            Technology::Machinery => "Machinery",
+        }
+    }
+    
+    pub fn era(&self) -> Era {
+        match self {
+            Technology::Agriculture
+            | Technology::AnimalHusbandry
+            | Technology::Mining
+            | Technology::Pottery
+            | Technology::TheWheel
+            | Technology::Writing
+            | Technology::Archery
+            | Technology::Masonry
+            | Technology::Sailing
+            | Technology::BronzeWorking
+            | Technology::Calendar => Era::Ancient,
+            Technology::Mathematics
+            | Technology::Currency
+            | Technology::Ironworking
+            | Technology::Construction
+            | Technology::HorsebackRiding
+            | Technology::Philosophy
+            | Technology::Literature => Era::Classical,
+            Technology::Engineering
+            | Technology::Feudalism
+            | Technology::Theology
+            | Technology::Chivalry
+            | Technology::Astronomy
+            | Technology::CivilService
+            | Technology::Machinery => Era::Medieval,
+        }
+    }
+    
+    // Science needed to research the technology: the era's base cost, plus a fifth
+    // of it for every step of prerequisites leading here
+    pub fn cost(&self) -> f32 {
+        rules().tech_cost(*self)
+    }
+    
+    // Built-in cost, used when no rules file overrides it. Counted in fifths so the
+    // result is exact and matches the whole numbers in techs.ron.
+    pub fn default_cost(&self) -> f32 {
+        self.era().base_cost() * (5 + self.depth()) as f32 / 5.0
+    }
+    
+    // Technologies that must be known before this one can be researched
+    pub fn prerequisites(&self) -> &'static [Technology] {
+        match self {
+            Technology::Agriculture | Technology::Mining | Technology::Archery => &[],
+            Technology::AnimalHusbandry | Technology::Pottery => &[Technology::Agriculture],
+            Technology::Masonry | Technology::BronzeWorking => &[Technology::Mining],
+            Technology::TheWheel | Technology::HorsebackRiding => &[Technology::AnimalHusbandry],
+            Technology::Writing | Technology::Sailing | Technology::Calendar => &[Technology::Pottery],
+            Technology::Mathematics | Technology::Literature => &[Technology::Writing],
+            Technology::Currency => &[Technology::BronzeWorking, Technology::Writing],
+            Technology::Ironworking => &[Technology::BronzeWorking],
+            Technology::Construction => &[Technology::Masonry, Technology::Mathematics],
+            Technology::Philosophy => &[Technology::Writing, Technology::Calendar],
+            Technology::Engineering => &[Technology::Construction, Technology::TheWheel],
+            Technology::Feudalism => &[Technology::Ironworking, Technology::Construction],
+            Technology::Theology => &[Technology::Philosophy],
+            Technology::Chivalry => &[Technology::Feudalism, Technology::HorsebackRiding],
+            Technology::Astronomy => &[Technology::Mathematics, Technology::Calendar],
+            Technology::CivilService => &[Technology::Currency, Technology::Philosophy],
+            Technology::Machinery => &[Technology::Engineering],
+        }
+    }
     
-    // More can be added as needed
+    // Length of the longest prerequisite chain leading to this technology
+    pub fn depth(&self) -> usize {
+        self.prerequisites().iter().map(|prereq| prereq.depth() + 1).max().unwrap_or(0)
+    }
+}
+
+/// Sent when a civilization finishes researching a technology
+#[derive(Event)]
+pub struct TechResearchedEvent {
+    pub civilization_id: u32,
+    pub technology: Technology,
 }
 
 impl Civilization {
@@ -104,6 +465,13 @@ impl Civilization {
             science_points: 0.0,
             gold: 50.0, // Starting gold
             military_strength: 0.0,
+            great_people_points: HashMap::new(),
+            great_people_born: 0,
+            current_research: None,
+            research_queue: Vec::new(),
+            eliminated: false,
+            met_civilizations: HashSet::new(),
+            strategic_resources: HashMap::new(),
         }
     }
     
@@ -133,10 +501,159 @@ impl Civilization {
         techs
     }
     
+    // The latest era the civilization has a technology from
+    pub fn current_era(&self) -> Era {
+        self.technologies.iter().map(|tech| tech.era()).max().unwrap_or(Era::Ancient)
+    }
+    
     pub fn has_technology(&self, tech: Technology) -> bool {
         self.technologies.contains(&tech)
     }
     
+    // Points needed for the next Great Person of any type; each one born raises it
+    pub fn great_person_threshold(&self) -> f32 {
+        GREAT_PERSON_BASE_THRESHOLD + GREAT_PERSON_THRESHOLD_STEP * self.great_people_born as f32
+    }
+    
+    // Spends the points of the first type past the threshold, if any
+    pub fn take_great_person(&mut self) -> Option<GreatPersonType> {
+        let threshold = self.great_person_threshold();
+        let kind = GreatPersonType::ALL
+            .into_iter()
+            .find(|kind| self.great_people_points.get(kind).is_some_and(|&points| points >= threshold))?;
+        
+        if let Some(points) = self.great_people_points.get_mut(&kind) {
+            *points -= threshold;
+        }
+        self.great_people_born += 1;
+        Some(kind)
+    }
+    
+    // What a Great Scientist hands over: the current research target, else the cheapest available
+    pub fn next_free_technology(&self) -> Option<Technology> {
+        self.current_research
+            .map(|(tech, _)| tech)
+            .or_else(|| self.available_research().into_iter().min_by(|a, b| a.cost().total_cmp(&b.cost())))
+    }
+    
+    pub fn can_research(&self, tech: Technology) -> bool {
+        !self.has_technology(tech) && tech.prerequisites().iter().all(|&prereq| self.has_technology(prereq))
+    }
+    
+    // Technologies whose prerequisites are all known, in tech-tree order
+    pub fn available_research(&self) -> Vec<Technology> {
+        Technology::ALL.into_iter().filter(|&tech| self.can_research(tech)).collect()
+    }
+    
+    // Switch research; science already put into the old target goes back into the pool
+    pub fn set_research(&mut self, tech: Technology) -> bool {
+        if !self.can_research(tech) {
+            return false;
+        }
+        if let Some((_, progress)) = self.current_research.take() {
+            self.science_points += progress;
+        }
+        self.current_research = Some((tech, 0.0));
+        true
+    }
+    
+    // Unknown technologies needed to reach `target`, prerequisites first, ending with `target`
+    pub fn research_path(&self, target: Technology) -> Vec<Technology> {
+        fn visit(civ: &Civilization, tech: Technology, path: &mut Vec<Technology>) {
+            if civ.has_technology(tech) || path.contains(&tech) {
+                return;
+            }
+            for &prereq in tech.prerequisites() {
+                visit(civ, prereq, path);
+            }
+            path.push(tech);
+        }
+        
+        let mut path = Vec::new();
+        visit(self, target, &mut path);
+        path
+    }
+    
+    // Research toward `target`, queueing whatever prerequisites it still needs
+    pub fn queue_research(&mut self, target: Technology) -> bool {
+        let mut path = self.research_path(target);
+        if path.is_empty() {
+            return false;
+        }
+        let first = path.remove(0);
+        if self.current_research.is_none_or(|(tech, _)| tech != first) && !self.set_research(first) {
+            return false;
+        }
+        self.research_queue = path;
+        true
+    }
+    
+    // Learn a technology outright, refunding any research already put into it
+    pub fn grant_technology(&mut self, tech: Technology) {
+        if self.has_technology(tech) {
+            return;
+        }
+        if self.current_research.is_some_and(|(target, _)| target == tech)
+            && let Some((_, progress)) = self.current_research.take()
+        {
+            self.science_points += progress;
+        }
+        self.technologies.push(tech);
+        self.research_queue.retain(|&queued| queued != tech);
+        if self.current_research.is_none() {
+            self.start_next_queued();
+        }
+    }
+    
+    // Move on to the next queued technology that is still open
+    fn start_next_queued(&mut self) {
+        while !self.research_queue.is_empty() {
+            let next = self.research_queue.remove(0);
+            if self.set_research(next) {
+                break;
+            }
+        }
+    }
+    
+    // Moves pooled science into the current target; returns the technology if it completed.
+    // Science beyond the cost stays in the pool for the next target.
+    pub fn advance_research(&mut self, speed: GameSpeed) -> Option<Technology> {
+        let cost = self.research_cost(self.current_research?.0, speed);
+        let (tech, progress) = self.current_research.as_mut()?;
+        let tech = *tech;
+        *progress += self.science_points;
+        self.science_points = 0.0;
+        
+        if *progress < cost {
+            return None;
+        }
+        self.science_points = *progress - cost;
+        self.current_research = None;
+        self.technologies.push(tech);
+        self.start_next_queued();
+        Some(tech)
+    }
+    
+    // AI heuristic: the cheapest available technology, favouring ones matching the civ's type
+    pub fn choose_ai_research(&self) -> Option<Technology> {
+        let favoured = self.favoured_technologies();
+        self.available_research().into_iter().min_by(|a, b| {
+            let score = |tech: &Technology| if favoured.contains(tech) { tech.cost() * 0.5 } else { tech.cost() };
+            score(a).total_cmp(&score(b))
+        })
+    }
+    
+    fn favoured_technologies(&self) -> &'static [Technology] {
+        match self.civ_type {
+            CivilizationType::Agricultural => &[Technology::Pottery, Technology::AnimalHusbandry],
+            CivilizationType::Commercial => &[Technology::Currency, Technology::Writing, Technology::CivilService],
+            CivilizationType::Military => &[Technology::Archery, Technology::BronzeWorking, Technology::Ironworking, Technology::Feudalism],
+            CivilizationType::Scientific => &[Technology::Writing, Technology::Mathematics, Technology::Philosophy, Technology::Astronomy],
+            CivilizationType::Cultural => &[Technology::Masonry, Technology::Calendar, Technology::Theology],
+            CivilizationType::Maritime => &[Technology::Pottery, Technology::Sailing, Technology::Astronomy],
+        }
+    }
+    
     pub fn add_city(&mut self, city_entity: Entity) {
         self.cities.push(city_entity);
     }
@@ -153,20 +670,88 @@ impl Civilization {
         self.units.retain(|&e| e != unit_entity);
     }
     
-    pub fn get_trait_bonus(&self, trait_type: CivTrait) -> f32 {
-        if self.traits.contains(&trait_type) {
-            match trait_type {
-                CivTrait::Expansionist => 1.5,      // 50% bonus
-                CivTrait::Industrious => 1.25,      // 25% bonus
-                CivTrait::Commercial => 1.3,        // 30% bonus
-                CivTrait::Militaristic => 1.2,      // 20% bonus
-                CivTrait::Scientific => 1.4,        // 40% bonus
-                CivTrait::Seafaring => 1.3,         // 30% bonus
-                CivTrait::Spiritual => 1.35,        // 35% bonus
-                CivTrait::Organized => 0.8,         // 20% cost reduction (multiplicative)
-            }
+    // Adjective for the civilization's people, e.g. "Roman" for "Roman Empire"
+    // Hands the civilization to the player, or to the AI
+    pub fn set_player(&mut self, is_player: bool) {
+        self.is_player = is_player;
+        self.is_ai = !is_player;
+    }
+    
+    // Case-insensitive match against any part of the name, so "greek" finds the Greek City-States
+    pub fn matches_name(&self, query: &str) -> bool {
+        !query.is_empty() && self.name.to_lowercase().contains(&query.to_lowercase())
+    }
+    
+    // One line for the civilization list: name, leader, type, traits and color
+    pub fn describe(&self) -> String {
+        let traits: Vec<String> = self.traits.iter().map(|civ_trait| format!("{:?}", civ_trait)).collect();
+        format!(
+            "{} ({}) - {:?}; traits: {}; color {}",
+            self.name,
+            self.leader_name,
+            self.civ_type,
+            traits.join(", "),
+            Srgba::from(self.color).to_hex()
+        )
+    }
+    
+    pub fn adjective(&self) -> &str {
+        self.name.split_whitespace().next().unwrap_or(&self.name)
+    }
+    
+    // Whether this civilization knows `civ_id`; everyone knows themselves
+    pub fn has_met(&self, civ_id: u32) -> bool {
+        civ_id == self.id || self.met_civilizations.contains(&civ_id)
+    }
+    
+    pub fn has_resource(&self, resource: ResourceType) -> bool {
+        self.strategic_resources.get(&resource).is_some_and(|count| *count > 0)
+    }
+    
+    // The civilization's strategic resources, e.g. "Iron x2, Horses"
+    pub fn describe_strategic_resources(&self) -> String {
+        let mut resources: Vec<(ResourceType, u32)> = self.strategic_resources.iter().map(|(r, c)| (*r, *c)).collect();
+        if resources.is_empty() {
+            return "none".to_string();
+        }
+        resources.sort_by_key(|(resource, _)| *resource as u8);
+        resources
+            .iter()
+            .map(|(resource, count)| match count {
+                1 => resource.get_name().to_string(),
+                _ => format!("{} x{}", resource.get_name(), count),
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+    
+    pub fn has_trait(&self, trait_type: CivTrait) -> bool {
+        self.traits.contains(&trait_type)
+    }
+    
+    // Whether its ships may leave the coast for the open ocean and its land units embark:
+    // from OCEAN_TECH, or SEAFARING_OCEAN_TECH for a Seafaring civilization
+    pub fn can_cross_oceans(&self) -> bool {
+        let tech = if self.has_trait(CivTrait::Seafaring) { SEAFARING_OCEAN_TECH } else { OCEAN_TECH };
+        self.has_technology(tech)
+    }
+    
+    // Science needed for `tech` at the game's speed (Scientific researches for less)
+    pub fn research_cost(&self, tech: Technology, speed: GameSpeed) -> f32 {
+        let cost = tech.cost() * speed.multiplier();
+        if self.has_trait(CivTrait::Scientific) {
+            cost * SCIENTIFIC_RESEARCH_COST
         } else {
-            1.0
+            cost
+        }
+    }
+    
+    // Gold per turn to keep a unit of `unit_type` (Organized pays less)
+    pub fn unit_maintenance(&self, unit_type: UnitType) -> f32 {
+        if self.has_trait(CivTrait::Organized) {
+            unit_type.get_maintenance() * ORGANIZED_MAINTENANCE
+        } else {
+            unit_type.get_maintenance()
         }
     }
 }
@@ -184,37 +769,259 @@ impl CivilizationManager {
         self.civilizations.get(&id)
     }
     
+    // Difficulty bonuses for a civilization; neutral for unknown ids
+    pub fn difficulty_modifiers(&self, civ_id: u32) -> DifficultyModifiers {
+        self.get_civilization(civ_id)
+            .map_or(DifficultyModifiers::NEUTRAL, |civ| self.difficulty.modifiers(civ.is_player))
+    }
+    
     pub fn get_civilization_mut(&mut self, id: u32) -> Option<&mut Civilization> {
         self.civilizations.get_mut(&id)
     }
     
+    // Display name of whoever owns `civ_id`, barbarians included
+    pub fn civ_name(&self, civ_id: u32) -> &str {
+        match self.get_civilization(civ_id) {
+            Some(civ) => &civ.name,
+            None if civ_id == BARBARIAN_CIV_ID => "Barbarians",
+            None => "Unknown",
+        }
+    }
+    
+    // Color of whoever owns `civ_id`, barbarians included
+    pub fn civ_color(&self, civ_id: u32) -> Color {
+        match self.get_civilization(civ_id) {
+            Some(civ) => civ.color,
+            None if civ_id == BARBARIAN_CIV_ID => BARBARIAN_COLOR,
+            None => Color::srgb(0.5, 0.5, 0.5),
+        }
+    }
+    
+    // Whether the player has met `civ_id`. Barbarians are always recognizable.
+    pub fn player_knows(&self, civ_id: u32) -> bool {
+        civ_id == BARBARIAN_CIV_ID || self.get_player_civilization().is_none_or(|player| player.has_met(civ_id))
+    }
+    
+    // civ_name as the player sees it: civilizations they haven't met stay anonymous
+    pub fn known_civ_name(&self, civ_id: u32) -> &str {
+        if self.player_knows(civ_id) { self.civ_name(civ_id) } else { UNKNOWN_PLAYER }
+    }
+    
+    // Records that two civilizations have made contact; returns true the first time
+    pub fn meet(&mut self, civ1: u32, civ2: u32) -> bool {
+        if civ1 == civ2 || !self.civilizations.contains_key(&civ1) || !self.civilizations.contains_key(&civ2) {
+            return false;
+        }
+        let mut first_contact = false;
+        for (civ_id, other_id) in [(civ1, civ2), (civ2, civ1)] {
+            if let Some(civ) = self.get_civilization_mut(civ_id) {
+                first_contact |= civ.met_civilizations.insert(other_id);
+            }
+        }
+        first_contact
+    }
+    
+    // Id of the human the game is shown to: whoever's turn it is, or the last human to
+    // play while the AI moves. Falls back to the first human if none has played yet.
+    fn player_id(&self) -> Option<u32> {
+        match self.civilizations.get(&self.active_player) {
+            Some(civ) if civ.is_player => Some(civ.id),
+            _ => self.civilizations.values().filter(|civ| civ.is_player).map(|civ| civ.id).min(),
+        }
+    }
+    
+    // The human the game is shown to. In hotseat games that changes with each human's turn.
     pub fn get_player_civilization(&self) -> Option<&Civilization> {
-        self.civilizations.values().find(|civ| civ.is_player)
+        self.civilizations.get(&self.player_id()?)
     }
     
     pub fn get_player_civilization_mut(&mut self) -> Option<&mut Civilization> {
-        self.civilizations.values_mut().find(|civ| civ.is_player)
+        let id = self.player_id()?;
+        self.civilizations.get_mut(&id)
+    }
+    
+    // The civilization the panels show: the player's, or in an observer game the one
+    // being watched
+    pub fn get_viewed_civilization(&self) -> Option<&Civilization> {
+        self.get_player_civilization().or_else(|| self.civilizations.get(&self.observed?))
+    }
+    
+    // Every civilization is run by the AI and the game plays itself
+    pub fn is_observer_game(&self) -> bool {
+        !self.civilizations.is_empty() && !self.civilizations.values().any(|civ| civ.is_player)
+    }
+    
+    // More than one human still in the game, taking turns at the same screen
+    pub fn is_hotseat(&self) -> bool {
+        self.civilizations.values().filter(|civ| civ.is_player && !civ.eliminated).count() > 1
+    }
+    
+    // Every human is out of the game; false if there were none to begin with
+    pub fn humans_eliminated(&self) -> bool {
+        let mut humans = self.civilizations.values().filter(|civ| civ.is_player).peekable();
+        humans.peek().is_some() && humans.all(|civ| civ.eliminated)
+    }
+    
+    // Civilizations still in the game, in turn order
+    pub fn turn_order(&self) -> Vec<u32> {
+        let mut civ_ids: Vec<u32> = self.civilizations
+            .values()
+            .filter(|civ| !civ.eliminated)
+            .map(|civ| civ.id)
+            .collect();
+        civ_ids.sort_unstable();
+        civ_ids
     }
     
     pub fn next_turn(&mut self) {
-        // Advance to next civilization's turn
-        let civ_ids: Vec<u32> = self.civilizations.keys().cloned().collect();
-        if let Some(current_index) = civ_ids.iter().position(|&id| id == self.current_turn_civ) {
-            let next_index = (current_index + 1) % civ_ids.len();
-            self.current_turn_civ = civ_ids[next_index];
-            
-            // If we're back to the first civ, increment turn number
-            if next_index == 0 {
+        // Advance to the next civilization by id rather than by position, so this
+        // still works when the current civ has just been eliminated
+        let civ_ids = self.turn_order();
+        let Some(&first) = civ_ids.first() else { return };
+        match civ_ids.iter().find(|&&id| id > self.current_turn_civ) {
+            Some(&next) => self.current_turn_civ = next,
+            None => {
+                // Wrapped around: a new turn, opened by the barbarians when they're in the game
+                self.current_turn_civ = if self.barbarians { BARBARIAN_CIV_ID } else { first };
                 self.turn_number += 1;
             }
         }
     }
     
+    // Moves gold between treasuries; the payer can't hand over more than it has
+    pub fn transfer_gold(&mut self, payer: u32, payee: u32, amount: f32) {
+        let Some(from) = self.get_civilization_mut(payer) else { return };
+        let paid = amount.min(from.gold.max(0.0));
+        from.gold -= paid;
+        if let Some(to) = self.get_civilization_mut(payee) {
+            to.gold += paid;
+        }
+    }
+    
     pub fn is_current_turn(&self, civ_id: u32) -> bool {
         self.current_turn_civ == civ_id
     }
 }
 
+// Feeds pooled science into each civilization's research and picks new targets for the AI.
+// The pool only grows when cities are processed, so this amounts to once per turn.
+pub fn research_system(
+    mut civ_manager: ResMut<CivilizationManager>,
+    mut researched_events: EventWriter<TechResearchedEvent>,
+) {
+    // Look before borrowing mutably so idle frames don't mark the manager changed
+    let pending: Vec<u32> = civ_manager.civilizations
+        .values()
+        .filter(|civ| match civ.current_research {
+            Some(_) => civ.science_points > 0.0,
+            None => civ.is_ai && !civ.available_research().is_empty(),
+        })
+        .map(|civ| civ.id)
+        .collect();
+    
+    let speed = civ_manager.speed;
+    for civ_id in pending {
+        let Some(civ) = civ_manager.get_civilization_mut(civ_id) else { continue };
+        loop {
+            if civ.is_ai && civ.current_research.is_none() && let Some(tech) = civ.choose_ai_research() {
+                civ.set_research(tech);
+            }
+            let Some(tech) = civ.advance_research(speed) else { break };
+            println!("{} researched {}", civ.name, tech.get_name());
+            researched_events.write(TechResearchedEvent { civilization_id: civ_id, technology: tech });
+        }
+    }
+}
+
+// Building blocks for generated civilizations
+const GENERATED_NAME_ROOTS: [&str; 15] = [
+    "Vel", "Kar", "Ost", "Dun", "Ash", "Mor", "Tal", "Ser", "Bri", "Hal", "Zan", "Eld", "Thar", "Quor", "Lys",
+];
+const GENERATED_NAME_ENDINGS: [&str; 6] = ["ian", "an", "ese", "ic", "ish", "ite"];
+const GENERATED_POLITIES: [&str; 8] = [
+    "Kingdom", "Empire", "Confederacy", "Republic", "Dominion", "Clans", "League", "Khanate",
+];
+const GENERATED_LEADER_NAMES: [&str; 16] = [
+    "Aric", "Belen", "Corvin", "Daria", "Esk", "Fenna", "Goran", "Hessa",
+    "Iskar", "Jora", "Kethel", "Lorne", "Maren", "Nyra", "Orrin", "Talia",
+];
+const GENERATED_LEADER_EPITHETS: [&str; 8] = [
+    "the Bold", "the Wise", "the Builder", "the Far-Seeing", "the Stern", "the Generous", "Ironhand", "the Young",
+];
+const GENERATED_CIV_TYPES: [CivilizationType; 6] = [
+    CivilizationType::Agricultural,
+    CivilizationType::Commercial,
+    CivilizationType::Military,
+    CivilizationType::Scientific,
+    CivilizationType::Cultural,
+    CivilizationType::Maritime,
+];
+// Hues tried for each generated color; the one farthest from every color in use wins
+const GENERATED_HUE_CANDIDATES: usize = 36;
+// Generated colors stay at least this many degrees of hue from the others while there's room
+const MIN_CIV_HUE_DISTANCE: f32 = 12.0;
+
+// Degrees between two hues around the color wheel
+fn hue_distance(a: Color, b: Color) -> f32 {
+    let difference = (Hsla::from(a).hue - Hsla::from(b).hue).abs() % 360.0;
+    difference.min(360.0 - difference)
+}
+
+// `count` AI civilizations with made-up names and leaders, a random type, and a color
+// as far in hue as possible from the existing civilizations' and the barbarians'
+pub fn generate_civilizations(count: usize, existing: &[Civilization]) -> Vec<Civilization> {
+    let mut rng = rand::rng();
+    let mut names: Vec<String> = existing.iter().map(|civ| civ.name.clone()).collect();
+    let mut colors: Vec<Color> = existing.iter().map(|civ| civ.color).collect();
+    colors.push(BARBARIAN_COLOR);
+    
+    let mut generated = Vec::with_capacity(count);
+    while generated.len() < count {
+        let root = GENERATED_NAME_ROOTS.choose(&mut rng).copied().unwrap_or("Nor");
+        let ending = GENERATED_NAME_ENDINGS.choose(&mut rng).copied().unwrap_or("ian");
+        let polity = GENERATED_POLITIES.choose(&mut rng).copied().unwrap_or("Kingdom");
+        let name = format!("{}{} {}", root, ending, polity);
+        // Every root/ending pair reads as a different people; don't reuse one
+        let adjective = format!("{}{} ", root, ending);
+        if names.iter().any(|existing| existing.starts_with(&adjective)) {
+            continue;
+        }
+        
+        let leader = format!(
+            "{} {}",
+            GENERATED_LEADER_NAMES.choose(&mut rng).copied().unwrap_or("Aric"),
+            GENERATED_LEADER_EPITHETS.choose(&mut rng).copied().unwrap_or("the Bold"),
+        );
+        let civ_type = GENERATED_CIV_TYPES.choose(&mut rng).copied().unwrap_or(CivilizationType::Agricultural);
+        let offset = rng.random_range(0.0..360.0);
+        let (hue, nearest) = (0..GENERATED_HUE_CANDIDATES)
+            .map(|step| {
+                let hue = (offset + step as f32 * 360.0 / GENERATED_HUE_CANDIDATES as f32) % 360.0;
+                let candidate = Color::hsl(hue, 0.7, 0.5);
+                let nearest = colors.iter().map(|used| hue_distance(candidate, *used)).fold(f32::MAX, f32::min);
+                (hue, nearest)
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap_or((offset, 0.0));
+        // Once the wheel is crowded, a pale shade keeps it apart from its neighbours
+        let lightness = if nearest < MIN_CIV_HUE_DISTANCE { 0.78 } else { 0.5 };
+        let color = Color::hsl(hue, 0.7, lightness);
+        
+        names.push(name.clone());
+        colors.push(color);
+        generated.push(Civilization::new(0, name, leader, color, civ_type, false));
+    }
+    generated
+}
+
+// Prints the civilizations a game can be played as, for --list-civs
+pub fn print_civilization_list() {
+    println!("Civilizations (choose one with --civ <name>):");
+    for civ in create_default_civilizations() {
+        println!("  {}", civ.describe());
+    }
+}
+
 // Predefined civilizations for easy setup
 pub fn create_default_civilizations() -> Vec<Civilization> {
     vec![
@@ -267,4 +1074,189 @@ pub fn create_default_civilizations() -> Vec<Civilization> {
             false,
         ),
     ]
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::test_support::{game_app, run};
+    
+    // A manager holding the six default civilizations, ids 1 to 6
+    fn manager() -> CivilizationManager {
+        let mut manager = CivilizationManager::default();
+        for civ in create_default_civilizations() {
+            manager.add_civilization(civ);
+        }
+        manager
+    }
+    
+    // Eliminating the civilization whose turn it is hands the turn to the next one still in
+    // the game, and eliminating the last in the order wraps to a new turn
+    #[test]
+    fn next_turn_skips_the_civilization_eliminated_on_its_own_turn() {
+        let mut manager = manager();
+        manager.current_turn_civ = 3;
+        manager.get_civilization_mut(3).unwrap().eliminated = true;
+        manager.get_civilization_mut(4).unwrap().eliminated = true;
+        manager.next_turn();
+        assert_eq!(manager.current_turn_civ, 5);
+        assert_eq!(manager.turn_number, 1);
+        
+        manager.current_turn_civ = 6;
+        manager.get_civilization_mut(6).unwrap().eliminated = true;
+        manager.next_turn();
+        assert_eq!(manager.current_turn_civ, 1);
+        assert_eq!(manager.turn_number, 2);
+        assert_eq!(manager.turn_order(), vec![1, 2, 5]);
+    }
+    
+    // With barbarians in the game the wrap goes to their phase first, and from there
+    // to the first civilization still alive
+    #[test]
+    fn next_turn_wraps_to_the_barbarians() {
+        let mut manager = manager();
+        manager.barbarians = true;
+        manager.get_civilization_mut(1).unwrap().eliminated = true;
+        manager.current_turn_civ = 6;
+        manager.get_civilization_mut(6).unwrap().eliminated = true;
+        manager.next_turn();
+        assert_eq!(manager.current_turn_civ, BARBARIAN_CIV_ID);
+        assert_eq!(manager.turn_number, 2);
+        
+        manager.next_turn();
+        assert_eq!(manager.current_turn_civ, 2);
+        assert_eq!(manager.turn_number, 2);
+    }
+    
+    // The Roman Empire: Military, with Agriculture and Archery, no research trait
+    fn roman() -> Civilization {
+        create_default_civilizations().remove(0)
+    }
+    
+    // Research can only target technologies whose prerequisites are all known; aiming
+    // further ahead queues the missing ones first
+    #[test]
+    fn research_needs_the_prerequisites() {
+        let mut civ = roman();
+        assert!(!civ.set_research(Technology::Writing));
+        assert!(civ.current_research.is_none());
+        assert!(!civ.set_research(Technology::Agriculture));
+        
+        assert!(civ.queue_research(Technology::Writing));
+        assert_eq!(civ.current_research, Some((Technology::Pottery, 0.0)));
+        assert_eq!(civ.research_queue, vec![Technology::Writing]);
+    }
+    
+    // Science is paid into the target until it costs out; the excess stays in the pool
+    // and goes into the next queued technology
+    #[test]
+    fn research_spends_exactly_the_cost() {
+        let mut civ = roman();
+        civ.queue_research(Technology::Writing);
+        let cost = civ.research_cost(Technology::Pottery, GameSpeed::Standard);
+        assert_eq!(cost, 30.0);
+        
+        civ.science_points = 20.0;
+        assert_eq!(civ.advance_research(GameSpeed::Standard), None);
+        assert_eq!(civ.current_research, Some((Technology::Pottery, 20.0)));
+        assert_eq!(civ.science_points, 0.0);
+        
+        civ.science_points = 15.0;
+        assert_eq!(civ.advance_research(GameSpeed::Standard), Some(Technology::Pottery));
+        assert!(civ.has_technology(Technology::Pottery));
+        assert_eq!(civ.science_points, 5.0);
+        assert_eq!(civ.current_research, Some((Technology::Writing, 0.0)));
+    }
+    
+    // research_system carries a turn's science through several cheap technologies at
+    // once, announcing each
+    #[test]
+    fn research_system_announces_each_technology() {
+        let mut app = game_app(Vec::new());
+        let mut manager = manager();
+        let civ = manager.get_civilization_mut(1).unwrap();
+        civ.queue_research(Technology::Writing);
+        civ.science_points = 70.0;
+        app.insert_resource(manager);
+        
+        run(&mut app, research_system);
+        let civ = app.world().resource::<CivilizationManager>().get_civilization(1).unwrap().clone();
+        assert!(civ.has_technology(Technology::Pottery) && civ.has_technology(Technology::Writing));
+        assert_eq!(civ.science_points, 5.0);
+        assert!(civ.current_research.is_none());
+        let events = app.world().resource::<Events<TechResearchedEvent>>();
+        let researched: Vec<Technology> = events.iter_current_update_events().filter(|event| event.civilization_id == 1).map(|event| event.technology).collect();
+        assert_eq!(researched, vec![Technology::Pottery, Technology::Writing]);
+    }
+    
+    // The prerequisites form a DAG: walking them from any technology never comes back
+    // to a technology still on the path
+    #[test]
+    fn technology_prerequisites_have_no_cycles() {
+        fn visit(tech: Technology, path: &mut Vec<Technology>, done: &mut HashSet<Technology>) {
+            assert!(!path.contains(&tech), "prerequisite cycle: {:?} -> {:?}", path, tech);
+            if done.contains(&tech) {
+                return;
+            }
+            path.push(tech);
+            for &prereq in tech.prerequisites() {
+                visit(prereq, path, done);
+            }
+            path.pop();
+            done.insert(tech);
+        }
+        
+        let mut done = HashSet::new();
+        for tech in Technology::ALL {
+            visit(tech, &mut Vec::new(), &mut done);
+        }
+        assert_eq!(done.len(), Technology::ALL.len());
+    }
+    
+    // Whatever a civilization starts with, researching whatever is available eventually
+    // reaches every technology, and no technology needs one from a later era
+    #[test]
+    fn every_technology_is_reachable_from_the_start() {
+        for start in create_default_civilizations() {
+            let mut civ = start.clone();
+            while let Some(&tech) = civ.available_research().first() {
+                civ.technologies.push(tech);
+            }
+            let missing: Vec<Technology> = Technology::ALL.into_iter().filter(|tech| !civ.has_technology(*tech)).collect();
+            assert!(missing.is_empty(), "{} can never research {:?}", start.name, missing);
+        }
+        for tech in Technology::ALL {
+            for prereq in tech.prerequisites() {
+                assert!(prereq.era() <= tech.era(), "{:?} needs {:?} from a later era", tech, prereq);
+                assert!(prereq.cost() < tech.cost(), "{:?} costs no more than its prerequisite {:?}", tech, prereq);
+            }
+        }
+    }
+    
+    // However many civilizations a full game needs, each generated one can be told apart from
+    // every other civilization and the barbarians: a different hue or a different shade
+    #[test]
+    fn generated_civilizations_have_distinct_colors_and_names() {
+        use crate::game::game_initialization::MAX_OPPONENTS;
+        
+        let defaults = create_default_civilizations();
+        let count = MAX_OPPONENTS + 1 - defaults.len();
+        for _ in 0..50 {
+            let generated = generate_civilizations(count, &defaults);
+            assert_eq!(generated.len(), count);
+            let others: Vec<(String, Color)> = defaults
+                .iter()
+                .map(|civ| (civ.name.clone(), civ.color))
+                .chain([("Barbarians".to_string(), BARBARIAN_COLOR)])
+                .collect();
+            for (index, civ) in generated.iter().enumerate() {
+                let earlier = generated[..index].iter().map(|civ| (civ.name.clone(), civ.color));
+                for (name, color) in others.iter().cloned().chain(earlier) {
+                    assert_ne!(civ.name, name);
+                    let apart = hue_distance(civ.color, color) >= MIN_CIV_HUE_DISTANCE
+                        || Hsla::from(civ.color).lightness != Hsla::from(color).lightness;
+                    assert!(apart, "{} ({:?}) looks like {} ({:?})", civ.name, civ.color, name, color);
+                }
+            }
+        }
+    }
+}
@@ -1,11 +1,22 @@
 use bevy::prelude::*;
+use bevy::ecs::system::SystemParam;
 use super::hex::HexCoord;
-use super::units::{Unit, UnitSelection};
-use super::civilization::CivilizationManager;
-use super::game_initialization::GameState;
-use super::map::MapTile;
+use super::units::{Unit, UnitCaptured, UnitSelection};
+use super::cities::{City, CityCaptured};
+use super::input::{ClickIntent, InputMode};
+use super::combat_log::{CombatLog, CombatLogEntry, CombatOutcome};
+use super::diplomacy::war_weariness_combat_modifier;
+use super::rng::GameRng;
+use super::game_initialization::GameSettings;
+use super::civilization::{BARBARIAN_CIV_ID, CivTrait, CivilizationManager, MILITARISTIC_COMBAT_BONUS};
+use super::map::{MapTile, TileLookup};
 use super::world_gen::BiomeType;
+use super::notifications::{GameNotification, NotificationCategory};
+use super::unit_actions::{UnitAction, UnitActionEvent, action_requested};
 use rand::Rng;
+use std::collections::HashSet;
+
+const CANNOT_ATTACK: &str = "Unit cannot attack (no movement, already attacked, or non-combat unit)!";
 
 #[derive(Resource)]
 pub struct CombatState {
@@ -20,14 +31,66 @@ impl Default for CombatState {
     }
 }
 
+/// What an attack is aimed at: a unit, a city with no garrison left, or a city being
+/// shelled from range over the heads of its garrison
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CombatTarget {
+    Unit(Entity),
+    City(Entity),
+    Bombard(Entity),
+}
+
+// Each friendly military unit next to the defender adds this to the attacker's strength,
+// up to MAX_FLANKING_BONUS
+pub const FLANKING_BONUS_PER_UNIT: f32 = 0.1;
+pub const MAX_FLANKING_BONUS: f32 = 0.3;
+
+// Siege engines hit cities this much harder, and units outside a city this much softer
+pub const SIEGE_CITY_MULTIPLIER: f32 = 2.5;
+pub const SIEGE_FIELD_PENALTY: f32 = 0.5;
+
+// Share of a normal assault's damage a bombardment does
+const BOMBARD_DAMAGE_FACTOR: f32 = 0.5;
+
 #[derive(Clone, Debug)]
 pub struct CombatPreview {
     pub attacker_entity: Entity,
-    pub defender_entity: Entity,
+    pub defender: CombatTarget,
     pub attacker_strength: u32,
     pub defender_strength: u32,
     pub attacker_win_chance: f32,
     pub terrain_modifier: f32,
+    pub flanking_units: u32, // Hexes around the defender held by the attacker's other military units
+    pub withdraw_chance: f32, // Odds the defender escapes an attack that would destroy it
+    pub siege_modifier: f32, // Already in attacker_strength
+}
+
+/// A unit lost `damage` health in a fight; `destroyed` if it didn't survive
+#[derive(Event)]
+pub struct UnitDamagedEvent {
+    pub unit: Entity,
+    pub damage: u32,
+    pub destroyed: bool,
+    pub sides: (u32, u32), // The attacking and defending civilizations
+}
+
+// Whether a fight between these civilizations gets its damage numbers, flashes and camera
+// alerts. With GameSettings::quick_combat_ai, fights no human takes part in are only
+// logged; the outcome is rolled the same either way.
+pub fn fight_shown_in_full(settings: &GameSettings, civ_manager: &CivilizationManager, sides: (u32, u32)) -> bool {
+    let human = |civ_id: u32| civ_manager.get_civilization(civ_id).is_some_and(|civ| civ.is_player);
+    !settings.quick_combat_ai || human(sides.0) || human(sides.1)
+}
+
+/// What fights roll against and where they're recorded: city and civilian captures for
+/// city_capture_system and unit_capture_system, hits for the map effects, everything for the log
+#[derive(SystemParam)]
+pub struct CombatRecorders<'w> {
+    pub capture_events: EventWriter<'w, CityCaptured>,
+    pub unit_captures: EventWriter<'w, UnitCaptured>,
+    pub hits: EventWriter<'w, UnitDamagedEvent>,
+    pub log: ResMut<'w, CombatLog>,
+    pub rng: ResMut<'w, GameRng>,
 }
 
 #[derive(Component)]
@@ -38,66 +101,72 @@ pub struct CombatResult {
     pub experience_gained: u32,
 }
 
-// System for handling combat initiation
+// System for handling combat initiation from the keyboard and the action bar: A enters
+// targeting, Enter fights the previewed attack, Escape cancels. Clicks on the map are
+// attack_click_system's.
+#[allow(clippy::too_many_arguments)]
 pub fn combat_system(
     mut commands: Commands,
     keyboard: Res<ButtonInput<KeyCode>>,
-    windows: Query<&Window>,
-    camera_query: Query<(&Camera, &GlobalTransform)>,
-    mouse_input: Res<ButtonInput<MouseButton>>,
+    mut action_events: EventReader<UnitActionEvent>,
+    mut input_mode: ResMut<InputMode>,
     mut unit_query: Query<(Entity, &mut Unit)>,
-    tile_query: Query<&MapTile>,
+    mut city_query: Query<(Entity, &mut City)>,
+    tiles: TileLookup,
     unit_selection: Res<UnitSelection>,
     mut combat_state: ResMut<CombatState>,
     civ_manager: Res<CivilizationManager>,
-    game_state: Res<GameState>,
+    mut recorders: CombatRecorders,
+    mut notifications: EventWriter<GameNotification>,
 ) {
-    if !game_state.is_initialized {
-        return;
-    }
+    let warning = |text: String| GameNotification::new(NotificationCategory::Warning, text);
     
-    // Handle attack command with 'A' key
-    if keyboard.just_pressed(KeyCode::KeyA) {
+    // Attack, from A or the action bar
+    if action_requested(&mut action_events, UnitAction::Attack) {
         if let Some(selected_unit_entity) = unit_selection.selected_unit {
             if let Ok((_, unit)) = unit_query.get(selected_unit_entity) {
-                if unit.can_attack && unit.movement_points > 0 && !unit.has_attacked {
-                    println!("Attack mode activated. Click on an enemy unit to attack.");
-                    println!("Available targets within range will be highlighted.");
+                if unit.must_set_up() {
+                    notifications.write(warning(format!("{} must set up first: siege engines can't attack on a turn they moved.", unit.unit_type.get_name())));
+                } else if unit.can_attack && unit.movement_points > 0 && !unit.has_attacked {
+                    *input_mode = InputMode::AttackTargeting;
+                    println!("Click an enemy unit or city to attack (Esc to cancel).");
                     
                     // Show available attack targets
-                    show_attack_targets(selected_unit_entity, &unit_query, &civ_manager);
+                    if show_attack_targets(selected_unit_entity, &unit_query, &civ_manager) == 0 {
+                        notifications.write(warning("No enemy units within attack range!".to_string()));
+                    }
                 } else {
-                    println!("Unit cannot attack (no movement, already attacked, or non-combat unit)!");
+                    notifications.write(warning(CANNOT_ATTACK.to_string()));
                 }
             }
         } else {
-            println!("No unit selected! Select a military unit to attack.");
+            notifications.write(warning("No unit selected! Select a military unit to attack.".to_string()));
         }
     }
     
-    // Handle combat target selection with mouse click
-    if mouse_input.just_pressed(MouseButton::Left) {
-        handle_combat_targeting(
-            &mut commands,
-            &windows,
-            &camera_query,
-            &mut unit_query,
-            &tile_query,
-            &unit_selection,
-            &mut combat_state,
-            &civ_manager,
-        );
+    // Targeting only lasts as long as the selected unit is still able to attack
+    if *input_mode == InputMode::AttackTargeting {
+        let still_able = unit_selection.selected_unit
+            .and_then(|entity| unit_query.get(entity).ok())
+            .is_some_and(|(_, unit)| unit.can_attack && unit.movement_points > 0 && !unit.has_attacked);
+        if !still_able {
+            *input_mode = InputMode::Normal;
+        }
     }
     
     // Handle combat confirmation with 'Enter'
-    if keyboard.just_pressed(KeyCode::Enter) {
-        if let Some(preview) = combat_state.combat_preview.take() {
-            execute_combat(&mut commands, &mut unit_query, &tile_query, preview, &civ_manager);
-        }
+    if keyboard.just_pressed(KeyCode::Enter)
+        && let Some(preview) = combat_state.combat_preview.take()
+    {
+        resolve_attack(&mut commands, &mut unit_query, &mut city_query, &tiles, preview, &civ_manager, &mut recorders);
     }
     
-    // Cancel combat preview with 'Escape'
+    // Cancel targeting or the combat preview with 'Escape'
     if keyboard.just_pressed(KeyCode::Escape) {
+        if *input_mode == InputMode::AttackTargeting {
+            *input_mode = InputMode::Normal;
+            println!("Attack cancelled.");
+        }
         if combat_state.combat_preview.is_some() {
             combat_state.combat_preview = None;
             println!("Combat cancelled.");
@@ -105,14 +174,75 @@ pub fn combat_system(
     }
 }
 
+// Picks attack targets from map clicks: a left click in targeting mode, or a right click
+// on an enemy. Runs after combat_system, so a unit that can no longer attack has already
+// left targeting, and before unit_order_system, which takes the right-clicks left over.
+#[allow(clippy::too_many_arguments)]
+pub fn attack_click_system(
+    mut click_intent: ResMut<ClickIntent>,
+    mut input_mode: ResMut<InputMode>,
+    unit_query: Query<(Entity, &mut Unit)>,
+    city_query: Query<(Entity, &mut City)>,
+    tile_query: Query<&MapTile>,
+    unit_selection: Res<UnitSelection>,
+    mut combat_state: ResMut<CombatState>,
+    civ_manager: Res<CivilizationManager>,
+    mut notifications: EventWriter<GameNotification>,
+) {
+    let warning = |text: String| GameNotification::new(NotificationCategory::Warning, text);
+    
+    // In targeting mode a left click picks the target; a miss keeps the mode so the player can try again
+    if let Some(clicked_hex) = click_intent.take_target() {
+        match handle_combat_targeting(
+            clicked_hex,
+            &unit_query,
+            &city_query,
+            &tile_query,
+            &unit_selection,
+            &mut combat_state,
+            &civ_manager,
+        ) {
+            Ok(true) => *input_mode = InputMode::Normal,
+            Ok(false) => {
+                notifications.write(warning("No enemy there. Click an enemy unit or city, or press Esc to cancel.".to_string()));
+            }
+            Err(reason) => {
+                *input_mode = InputMode::Normal;
+                notifications.write(warning(reason).at(clicked_hex));
+            }
+        }
+    }
+    
+    // Right-clicking an enemy unit or city is an attack order; anything else is left for unit_order_system
+    if let Some(clicked_hex) = click_intent.order {
+        let targeted = handle_combat_targeting(
+            clicked_hex,
+            &unit_query,
+            &city_query,
+            &tile_query,
+            &unit_selection,
+            &mut combat_state,
+            &civ_manager,
+        );
+        if targeted != Ok(false) {
+            click_intent.take_order();
+            *input_mode = InputMode::Normal;
+        }
+        if let Err(reason) = targeted {
+            notifications.write(warning(reason).at(clicked_hex));
+        }
+    }
+}
+
+// Lists the enemy units the attacker can reach on the console; returns how many there are
 fn show_attack_targets(
     attacker_entity: Entity,
     unit_query: &Query<(Entity, &mut Unit)>,
     civ_manager: &CivilizationManager,
-) {
+) -> usize {
+    let mut targets_found = 0;
     if let Ok((_, attacker)) = unit_query.get(attacker_entity) {
         let attack_range = get_attack_range(&attacker);
-        let mut targets_found = 0;
         
         for (target_entity, target_unit) in unit_query.iter() {
             if target_entity == attacker_entity {
@@ -121,7 +251,7 @@ fn show_attack_targets(
             
             // Check if target is enemy
             if are_enemies(attacker.civilization_id, target_unit.civilization_id, civ_manager) {
-                let distance = hex_distance(attacker.hex_coord, target_unit.hex_coord);
+                let distance = attacker.hex_coord.distance(target_unit.hex_coord);
                 
                 if distance <= attack_range {
                     targets_found += 1;
@@ -139,205 +269,706 @@ fn show_attack_targets(
                 }
             }
         }
-        
-        if targets_found == 0 {
-            println!("No enemy units within attack range!");
-        }
     }
+    targets_found
 }
 
+// Ok(true) if the click put an enemy unit or city up for attack, Ok(false) if there's no
+// enemy there, and the reason if there is one but the selected unit can't attack it.
+// Units on the hex are fought first; the city itself only once its garrison is gone.
 fn handle_combat_targeting(
-    commands: &mut Commands,
-    windows: &Query<&Window>,
-    camera_query: &Query<(&Camera, &GlobalTransform)>,
-    unit_query: &mut Query<(Entity, &mut Unit)>,
+    clicked_hex: HexCoord,
+    unit_query: &Query<(Entity, &mut Unit)>,
+    city_query: &Query<(Entity, &mut City)>,
     tile_query: &Query<&MapTile>,
     unit_selection: &Res<UnitSelection>,
     combat_state: &mut ResMut<CombatState>,
     civ_manager: &Res<CivilizationManager>,
-) {
-    let Ok(window) = windows.single() else { return };
-    let Ok((camera, camera_transform)) = camera_query.single() else { return };
+) -> Result<bool, String> {
+    let Some(selected_entity) = unit_selection.selected_unit else { return Ok(false) };
+    let Ok((attacker_entity, attacker)) = unit_query.get(selected_entity) else { return Ok(false) };
     
-    if let Some(world_position) = window
-        .cursor_position()
-        .and_then(|cursor| camera.viewport_to_world_2d(camera_transform, cursor).ok())
-    {
-        let clicked_hex = HexCoord::from_world_pos(world_position, super::map::HEX_SIZE);
-        
-        // Check if there's a unit at the clicked position
-        if let Some(selected_entity) = unit_selection.selected_unit {
-            if let Ok((attacker_entity, attacker)) = unit_query.get(selected_entity) {
-                if !attacker.can_attack || attacker.movement_points == 0 || attacker.has_attacked {
-                    return;
-                }
-                
-                // Find target unit at clicked location
-                for (target_entity, target_unit) in unit_query.iter() {
-                    if target_unit.hex_coord == clicked_hex && target_entity != attacker_entity {
-                        // Check if target is enemy
-                        if are_enemies(attacker.civilization_id, target_unit.civilization_id, civ_manager) {
-                            let distance = hex_distance(attacker.hex_coord, target_unit.hex_coord);
-                            let attack_range = get_attack_range(&attacker);
-                            
-                            if distance <= attack_range {
-                                // Create combat preview
-                                let preview = create_combat_preview(
-                                    attacker_entity,
-                                    target_entity,
-                                    &attacker,
-                                    &target_unit,
-                                    tile_query,
-                                );
-                                
-                                display_combat_preview(&preview, civ_manager);
-                                combat_state.combat_preview = Some(preview);
-                                return;
-                            } else {
-                                println!("Target is out of range! (Distance: {}, Range: {})", distance, attack_range);
-                            }
-                        } else {
-                            println!("Cannot attack allied units!");
-                        }
-                        break;
-                    }
-                }
+    let enemy_city = city_query
+        .iter()
+        .find(|(_, city)| city.hex_coord == clicked_hex && are_enemies(attacker.civilization_id, city.civilization_id, civ_manager));
+    if can_bombard(attacker) && let Some((_, city)) = enemy_city && let Some(reason) = bombard_blocked(attacker, city, tile_query) {
+        return Err(format!("Can't bombard {}: {}", city.name, reason));
+    }
+    
+    // Find enemy target unit at clicked location, falling back to an enemy city
+    let Some(preview) = target_preview(attacker_entity, clicked_hex, unit_query, city_query, tile_query, civ_manager) else { return Ok(false) };
+    
+    if !attacker.can_attack || attacker.movement_points == 0 || attacker.has_attacked {
+        return Err(CANNOT_ATTACK.to_string());
+    }
+    if attacker.must_set_up() {
+        return Err(format!("{} must set up first: siege engines can't attack on a turn they moved.", attacker.unit_type.get_name()));
+    }
+    
+    let distance = attacker.hex_coord.distance(clicked_hex);
+    let attack_range = get_attack_range(attacker);
+    
+    if distance > attack_range {
+        return Err(format!("Target is out of range! (Distance: {}, Range: {})", distance, attack_range));
+    }
+    // Shown by the combat panel until confirmed or cancelled
+    combat_state.combat_preview = Some(preview);
+    Ok(true)
+}
+
+// Odds of `attacker_entity` attacking whatever is hostile at `target`, ignoring range
+// and whether it may still attack. Units on the hex are fought first; the city only
+// once its garrison is gone. Ranged units bombard an enemy city instead, if they can see it.
+pub fn target_preview(
+    attacker_entity: Entity,
+    target: HexCoord,
+    unit_query: &Query<(Entity, &mut Unit)>,
+    city_query: &Query<(Entity, &mut City)>,
+    tile_query: &Query<&MapTile>,
+    civ_manager: &CivilizationManager,
+) -> Option<CombatPreview> {
+    let Ok((_, attacker)) = unit_query.get(attacker_entity) else { return None };
+    if can_bombard(attacker) {
+        let enemy_city = city_query
+            .iter()
+            .find(|(_, city)| city.hex_coord == target && are_enemies(attacker.civilization_id, city.civilization_id, civ_manager));
+        if let Some((city_entity, city)) = enemy_city {
+            if bombard_blocked(attacker, city, tile_query).is_some() {
+                return None;
             }
+            return Some(create_bombard_preview(attacker_entity, city_entity, attacker, city, civ_manager));
         }
     }
+    
+    if let Some((target_entity, target_unit)) = strongest_defender(attacker.civilization_id, target, unit_query, civ_manager) {
+        // Garrisoned units get the city's defenses on top of the terrain
+        let garrison_modifier = city_query
+            .iter()
+            .find(|(_, city)| city.hex_coord == target && city.civilization_id == target_unit.civilization_id)
+            .map(|(_, city)| city.garrison_defense_modifier());
+        return create_combat_preview(
+            attacker_entity,
+            target_entity,
+            unit_query,
+            garrison_modifier,
+            tile_query,
+            civ_manager,
+        );
+    }
+    
+    city_query
+        .iter()
+        .find(|(_, city)| city.hex_coord == target && are_enemies(attacker.civilization_id, city.civilization_id, civ_manager))
+        .map(|(city_entity, city)| create_city_combat_preview(attacker_entity, city_entity, attacker, city, civ_manager))
 }
 
-fn create_combat_preview(
+// The enemy unit that defends `target` against `attacker_civ`: military units before
+// civilians, then the highest defensive strength after fortification and wounds. Everyone
+// on a hex shares its terrain, so that doesn't change the pick.
+pub fn strongest_defender<'a>(
+    attacker_civ: u32,
+    target: HexCoord,
+    unit_query: &'a Query<(Entity, &mut Unit)>,
+    civ_manager: &CivilizationManager,
+) -> Option<(Entity, &'a Unit)> {
+    unit_query
+        .iter()
+        .filter(|(_, unit)| {
+            unit.hex_coord == target && !unit.is_dead() && are_enemies(attacker_civ, unit.civilization_id, civ_manager)
+        })
+        .max_by_key(|(_, unit)| (unit.can_attack, unit.get_combat_strength(false)))
+}
+
+// Fights out a previewed attack
+pub fn resolve_attack(
+    commands: &mut Commands,
+    unit_query: &mut Query<(Entity, &mut Unit)>,
+    city_query: &mut Query<(Entity, &mut City)>,
+    tiles: &TileLookup,
+    preview: CombatPreview,
+    civ_manager: &CivilizationManager,
+    recorders: &mut CombatRecorders,
+) {
+    match preview.defender {
+        CombatTarget::Unit(defender_entity) => {
+            let attacker_entity = preview.attacker_entity;
+            let retreat = withdrawal_hex(defender_entity, unit_query, city_query, tiles, &mut recorders.rng);
+            if let Some(vacated) = execute_combat(commands, unit_query, defender_entity, preview, retreat, civ_manager, recorders) {
+                advance_after_victory(attacker_entity, vacated, unit_query, city_query, civ_manager, recorders);
+            }
+        }
+        CombatTarget::City(city_entity) => {
+            execute_city_combat(unit_query, city_query, city_entity, preview, civ_manager, recorders);
+        }
+        CombatTarget::Bombard(city_entity) => {
+            execute_bombard(unit_query, city_query, city_entity, preview, civ_manager, &mut recorders.log);
+        }
+    }
+}
+
+// Multiplier on a civilization's combat strength from its traits and war weariness
+fn civ_combat_modifier(civilization_id: u32, civ_manager: &CivilizationManager) -> f32 {
+    let militaristic = civ_manager
+        .get_civilization(civilization_id)
+        .is_some_and(|civ| civ.has_trait(CivTrait::Militaristic));
+    let weariness = war_weariness_combat_modifier(civ_manager.diplomacy.total_war_weariness(civilization_id));
+    if militaristic { (1.0 + MILITARISTIC_COMBAT_BONUS) * weariness } else { weariness }
+}
+
+// Multiplier on a siege engine's strength against a city or its garrison, or against a
+// unit in the open; 1.0 for every other attacker
+pub fn siege_modifier(attacker: &Unit, against_city: bool) -> f32 {
+    match (attacker.unit_type.is_siege(), against_city) {
+        (false, _) => 1.0,
+        (true, true) => SIEGE_CITY_MULTIPLIER,
+        (true, false) => SIEGE_FIELD_PENALTY,
+    }
+}
+
+// Multiplier on the attacker's strength for `units` flanking the defender
+pub fn flanking_multiplier(units: u32) -> f32 {
+    1.0 + (units as f32 * FLANKING_BONUS_PER_UNIT).min(MAX_FLANKING_BONUS)
+}
+
+// How many hexes next to `defender` hold a military unit of the attacker's civilization
+// other than the attacker. Civilians don't count, and only units fighting in the
+// defender's element do: ships don't flank land units or the other way round.
+fn count_flanking_units(
     attacker_entity: Entity,
-    defender_entity: Entity,
     attacker: &Unit,
     defender: &Unit,
+    unit_query: &Query<(Entity, &mut Unit)>,
+) -> u32 {
+    let flank_positions: HashSet<HexCoord> = unit_query
+        .iter()
+        .filter(|(entity, unit)| {
+            *entity != attacker_entity
+                && unit.civilization_id == attacker.civilization_id
+                && unit.can_attack
+                && unit.naval_unit == defender.naval_unit
+        })
+        .map(|(_, unit)| unit.hex_coord)
+        .collect();
+    defender.hex_coord.neighbors().iter().filter(|coord| flank_positions.contains(coord)).count() as u32
+}
+
+fn create_combat_preview(
+    attacker_entity: Entity,
+    defender_entity: Entity,
+    unit_query: &Query<(Entity, &mut Unit)>,
+    garrison_modifier: Option<f32>,
     tile_query: &Query<&MapTile>,
-) -> CombatPreview {
-    let attacker_strength = attacker.get_combat_strength(true);
+    civ_manager: &CivilizationManager,
+) -> Option<CombatPreview> {
+    let Ok([(_, attacker), (_, defender)]) = unit_query.get_many([attacker_entity, defender_entity]) else { return None };
+    let siege_modifier = siege_modifier(attacker, garrison_modifier.is_some());
+    let attacker_strength = ((attacker.get_combat_strength(true) as f32 * siege_modifier) as u32).max(1);
     let mut defender_strength = defender.get_combat_strength(false);
     
-    // Apply terrain defensive bonuses
-    let terrain_modifier = get_terrain_defensive_bonus(defender.hex_coord, tile_query);
+    // Apply terrain and city defensive bonuses
+    let terrain_bonus = get_terrain_defensive_bonus(defender.hex_coord, tile_query);
+    let terrain_modifier = terrain_bonus * garrison_modifier.unwrap_or(1.0);
     defender_strength = (defender_strength as f32 * terrain_modifier) as u32;
     
-    // Calculate win probability (simplified)
-    let total_strength = attacker_strength + defender_strength;
-    let attacker_win_chance = if total_strength > 0 {
-        attacker_strength as f32 / total_strength as f32
+    // Calculate win probability (simplified); trait and flanking bonuses only tip the odds
+    let flanking_units = count_flanking_units(attacker_entity, attacker, defender, unit_query);
+    let attacker_power = attacker_strength as f32
+        * civ_combat_modifier(attacker.civilization_id, civ_manager)
+        * flanking_multiplier(flanking_units);
+    let defender_power = defender_strength as f32 * civ_combat_modifier(defender.civilization_id, civ_manager);
+    let total_power = attacker_power + defender_power;
+    let attacker_win_chance = if total_power > 0.0 {
+        attacker_power / total_power
     } else {
         0.5
     };
     
-    CombatPreview {
+    Some(CombatPreview {
         attacker_entity,
-        defender_entity,
+        defender: CombatTarget::Unit(defender_entity),
         attacker_strength,
         defender_strength,
         attacker_win_chance,
         terrain_modifier,
+        flanking_units,
+        withdraw_chance: withdraw_chance(attacker, defender, terrain_bonus),
+        siege_modifier,
+    })
+}
+
+fn create_city_combat_preview(
+    attacker_entity: Entity,
+    city_entity: Entity,
+    attacker: &Unit,
+    city: &City,
+    civ_manager: &CivilizationManager,
+) -> CombatPreview {
+    let attacker_strength = attacker.get_combat_strength(true);
+    let defender_strength = city.combat_strength();
+    let attacker_power = attacker_strength as f32 * civ_combat_modifier(attacker.civilization_id, civ_manager);
+    let defender_power = defender_strength as f32 * civ_combat_modifier(city.civilization_id, civ_manager);
+    
+    CombatPreview {
+        attacker_entity,
+        defender: CombatTarget::City(city_entity),
+        attacker_strength,
+        defender_strength,
+        attacker_win_chance: attacker_power / (attacker_power + defender_power).max(1.0),
+        terrain_modifier: 1.0,
+        flanking_units: 0,
+        withdraw_chance: 0.0,
+        siege_modifier: 1.0,
+    }
+}
+
+// A bombardment can't be resisted; it simply does nothing once the city is down to 1 HP
+fn create_bombard_preview(
+    attacker_entity: Entity,
+    city_entity: Entity,
+    attacker: &Unit,
+    city: &City,
+    civ_manager: &CivilizationManager,
+) -> CombatPreview {
+    let siege_modifier = siege_modifier(attacker, true);
+    let attacker_power = attacker.get_combat_strength(true) as f32
+        * civ_combat_modifier(attacker.civilization_id, civ_manager)
+        * siege_modifier;
+    CombatPreview {
+        attacker_entity,
+        defender: CombatTarget::Bombard(city_entity),
+        attacker_strength: attacker_power.round() as u32,
+        defender_strength: city.combat_strength(),
+        attacker_win_chance: if city.hit_points > 1 { 1.0 } else { 0.0 },
+        terrain_modifier: 1.0,
+        flanking_units: 0,
+        withdraw_chance: 0.0,
+        siege_modifier,
     }
 }
 
-fn display_combat_preview(preview: &CombatPreview, _civ_manager: &CivilizationManager) {
-    println!("=== COMBAT PREVIEW ===");
-    println!("Attacker Strength: {}", preview.attacker_strength);
-    println!("Defender Strength: {} (terrain bonus: {:.1}x)", 
-             preview.defender_strength, preview.terrain_modifier);
-    println!("Attacker Win Chance: {:.1}%", preview.attacker_win_chance * 100.0);
-    println!("Press ENTER to attack, ESC to cancel");
+// Odds that `defender` slips away from a melee attack that would destroy it. Only units
+// faster than the attacker get the chance, and rough ground (`terrain_bonus`) hinders the escape.
+fn withdraw_chance(attacker: &Unit, defender: &Unit, terrain_bonus: f32) -> f32 {
+    let defender_stats = defender.unit_type.get_stats();
+    let faster = defender_stats.movement > attacker.unit_type.get_stats().movement;
+    if get_attack_range(attacker) != 1 || !faster {
+        return 0.0;
+    }
+    defender_stats.withdraw_chance / terrain_bonus.max(1.0)
+}
+
+// A random hex next to the defender it could withdraw into: passable for it, empty of
+// other units and not an enemy city. None if it's surrounded.
+fn withdrawal_hex(
+    defender_entity: Entity,
+    unit_query: &Query<(Entity, &mut Unit)>,
+    city_query: &Query<(Entity, &mut City)>,
+    tiles: &TileLookup,
+    rng: &mut GameRng,
+) -> Option<HexCoord> {
+    let Ok((_, defender)) = unit_query.get(defender_entity) else { return None };
+    let occupied: HashSet<HexCoord> = unit_query
+        .iter()
+        .filter(|(entity, _)| *entity != defender_entity)
+        .map(|(_, unit)| unit.hex_coord)
+        .chain(
+            city_query
+                .iter()
+                .filter(|(_, city)| city.civilization_id != defender.civilization_id)
+                .map(|(_, city)| city.hex_coord),
+        )
+        .collect();
+    let open: Vec<HexCoord> = defender
+        .hex_coord
+        .neighbors()
+        .into_iter()
+        .filter(|coord| !occupied.contains(coord) && defender.can_move_to(*coord, tiles))
+        .collect();
+    if open.is_empty() {
+        return None;
+    }
+    Some(open[rng.random_range(0..open.len())])
+}
+
+// Ranged units (Archers and Triremes) can shell cities from a distance
+pub fn can_bombard(unit: &Unit) -> bool {
+    unit.can_attack && get_attack_range(unit) > 1
+}
+
+// Why `attacker` can't bombard `city` from where it stands, if it can't. Ships only reach
+// coastal cities, and forests or mountains on every hex in between block the shot.
+fn bombard_blocked(attacker: &Unit, city: &City, tile_query: &Query<&MapTile>) -> Option<&'static str> {
+    if attacker.naval_unit && !city.is_coastal {
+        return Some("ships can only bombard coastal cities");
+    }
+    if !has_line_of_sight(attacker.hex_coord, city.hex_coord, tile_query) {
+        return Some("no line of sight");
+    }
+    None
+}
+
+// Neighbours always see each other; two hexes apart, at least one of the hexes between
+// them must be open ground or water
+fn has_line_of_sight(from: HexCoord, to: HexCoord, tile_query: &Query<&MapTile>) -> bool {
+    if from.distance(to) <= 1 {
+        return true;
+    }
+    let between: Vec<HexCoord> = from
+        .neighbors()
+        .into_iter()
+        .filter(|coord| coord.distance(to) < from.distance(to))
+        .collect();
+    between.iter().any(|coord| {
+        tile_query
+            .iter()
+            .find(|tile| tile.hex_coord == *coord)
+            .is_none_or(|tile| !blocks_sight(BiomeType::from_u8(tile.biome)))
+    })
+}
+
+fn blocks_sight(biome: BiomeType) -> bool {
+    matches!(
+        biome,
+        BiomeType::TropicalRainforest
+            | BiomeType::TemperateDeciduousForest
+            | BiomeType::AlpineTundra
+            | BiomeType::MontaneForest
+    )
+}
+
+// Only land melee units can walk in and take a city
+pub fn can_capture_cities(unit: &Unit) -> bool {
+    !unit.naval_unit && get_attack_range(unit) == 1
 }
 
 fn execute_combat(
     commands: &mut Commands,
     unit_query: &mut Query<(Entity, &mut Unit)>,
-    _tile_query: &Query<&MapTile>,
+    defender_entity: Entity,
     preview: CombatPreview,
-    _civ_manager: &CivilizationManager,
-) {
+    retreat: Option<HexCoord>,
+    civ_manager: &CivilizationManager,
+    recorders: &mut CombatRecorders,
+) -> Option<HexCoord> {
     // We need to handle the borrowing more carefully
     let mut attacker_data = None;
     let mut defender_data = None;
     
     // First, get immutable references to calculate combat
     {
-        let Ok((_, attacker)) = unit_query.get(preview.attacker_entity) else { return };
-        let Ok((_, defender)) = unit_query.get(preview.defender_entity) else { return };
+        let Ok((_, attacker)) = unit_query.get(preview.attacker_entity) else { return None };
+        let Ok((_, defender)) = unit_query.get(defender_entity) else { return None };
         
-        let mut rng = rand::rng();
-        let roll = rng.random::<f32>();
+        let roll = recorders.rng.random::<f32>();
         
         let attacker_wins = roll < preview.attacker_win_chance;
+        let attacker_label = attacker.label(civ_manager);
+        let defender_label = defender.label(civ_manager);
         
         println!("=== COMBAT RESULT ===");
         println!("Roll: {:.3}, Win threshold: {:.3}", roll, preview.attacker_win_chance);
         
+        let (damage_on_win, damage_on_loss) = predicted_damage(&preview);
         if attacker_wins {
-            let damage = calculate_damage(preview.attacker_strength, preview.defender_strength, true);
+            let damage = damage_on_win;
             defender_data = Some((damage, false)); // (damage, is_killed)
             attacker_data = Some((0, false)); // Attacker takes no damage when winning
-            println!("Attacker wins! Defender takes {} damage.", damage);
+            println!("{} defeats {}! {} takes {} damage.", attacker_label, defender_label, defender_label, damage);
         } else {
-            let damage = calculate_damage(preview.defender_strength, preview.attacker_strength, false);
+            let damage = damage_on_loss;
             attacker_data = Some((damage, false));
             defender_data = Some((0, false)); // Defender takes no damage when winning
-            println!("Defender wins! Attacker takes {} damage.", damage);
+            println!("{} holds against {}! {} takes {} damage.", defender_label, attacker_label, attacker_label, damage);
         }
     }
     
     // Now apply the changes with mutable access
+    let mut attacker_promoted = false;
     if let Some((damage, _)) = attacker_data {
         if let Ok((_, mut attacker)) = unit_query.get_mut(preview.attacker_entity) {
             attacker.has_attacked = true;
             attacker.movement_points = attacker.movement_points.saturating_sub(1);
             attacker.take_damage(damage);
-            attacker.gain_experience(1);
+            attacker_promoted |= attacker.gain_experience(1);
             
             if !attacker.is_dead() {
-                attacker.gain_experience(if damage == 0 { 3 } else { 1 }); // Extra for winning
+                attacker.battles_survived += 1;
+                attacker_promoted |= attacker.gain_experience(if damage == 0 { 3 } else { 1 }); // Extra for winning
             }
         }
     }
     
+    let mut defender_promoted = false;
+    let mut battle_hex = None;
+    let mut withdrew = false;
     if let Some((damage, _)) = defender_data {
-        if let Ok((_, mut defender)) = unit_query.get_mut(preview.defender_entity) {
+        if let Ok((_, mut defender)) = unit_query.get_mut(defender_entity) {
+            battle_hex = Some(defender.hex_coord);
             defender.take_damage(damage);
-            defender.gain_experience(1);
+            defender_promoted |= defender.gain_experience(1);
             
-            if !defender.is_dead() {
-                defender.gain_experience(if damage == 0 { 2 } else { 1 }); // Extra for winning
+            // A faster defender may get away instead of dying, if it has somewhere to go
+            if defender.is_dead() && let Some(retreat) = retreat && recorders.rng.random::<f32>() < preview.withdraw_chance {
+                defender.withdraw_to(retreat);
+                withdrew = true;
+                println!("{} withdraws to ({}, {})!", defender.label(civ_manager), retreat.q, retreat.r);
+            } else if !defender.is_dead() {
+                defender.battles_survived += 1;
+                defender_promoted |= defender.gain_experience(if damage == 0 { 2 } else { 1 }); // Extra for winning
             }
         }
     }
     
+    let Ok([(_, attacker), (_, defender)]) = unit_query.get_many([preview.attacker_entity, defender_entity]) else { return None };
+    let battle_hex = battle_hex.unwrap_or(defender.hex_coord);
+    let outcome = match (attacker.is_dead(), defender.is_dead(), attacker_data) {
+        (true, _, _) => CombatOutcome::AttackerDestroyed,
+        (_, true, _) => CombatOutcome::DefenderDestroyed,
+        _ if withdrew => CombatOutcome::DefenderWithdrew,
+        (_, _, Some((0, _))) => CombatOutcome::AttackerWon,
+        _ => CombatOutcome::DefenderHeld,
+    };
+    let damage_to_attacker = attacker_data.map_or(0, |(damage, _)| damage);
+    let damage_to_defender = defender_data.map_or(0, |(damage, _)| damage);
+    let sides = (attacker.civilization_id, defender.civilization_id);
+    for (unit, damage, destroyed) in [
+        (preview.attacker_entity, damage_to_attacker, attacker.is_dead()),
+        (defender_entity, damage_to_defender, defender.is_dead()),
+    ] {
+        if damage > 0 {
+            recorders.hits.write(UnitDamagedEvent { unit, damage, destroyed, sides });
+        }
+    }
+    let log = &mut recorders.log;
+    log.push(CombatLogEntry {
+        turn: civ_manager.turn_number,
+        location: battle_hex,
+        attacker: attacker.label(civ_manager),
+        attacker_civ: attacker.civilization_id,
+        defender: defender.label(civ_manager),
+        defender_civ: Some(defender.civilization_id),
+        damage_to_attacker,
+        damage_to_defender,
+        outcome,
+    });
+    if attacker_promoted && !attacker.is_dead() {
+        log_promotion(log, attacker, civ_manager);
+    }
+    if defender_promoted && !defender.is_dead() {
+        log_promotion(log, defender, civ_manager);
+    }
+    
     // Handle unit destruction
-    if let Ok((_, attacker)) = unit_query.get(preview.attacker_entity) {
-        if attacker.is_dead() {
-            println!("Attacker unit destroyed!");
-            commands.entity(preview.attacker_entity).despawn();
+    if attacker.is_dead() {
+        println!("{} destroyed!", attacker.label(civ_manager));
+        commands.entity(preview.attacker_entity).despawn();
+    }
+    if defender.is_dead() {
+        println!("{} destroyed!", defender.label(civ_manager));
+        commands.entity(defender_entity).despawn();
+    }
+    
+    // A surviving melee attacker may follow up into the emptied hex, if it could stand there
+    let advances = (defender.is_dead() || withdrew)
+        && !attacker.is_dead()
+        && get_attack_range(attacker) == 1
+        && attacker.movement_type == defender.movement_type;
+    advances.then_some(battle_hex)
+}
+
+// Moves a melee attacker into the hex of the unit it just beat, unless another military
+// unit still holds it; enemy civilians left there are captured. An enemy city there blocks
+// the advance while it has HP left; at 0 HP a unit that can capture marches in and takes it.
+fn advance_after_victory(
+    attacker_entity: Entity,
+    vacated: HexCoord,
+    unit_query: &mut Query<(Entity, &mut Unit)>,
+    city_query: &mut Query<(Entity, &mut City)>,
+    civ_manager: &CivilizationManager,
+    recorders: &mut CombatRecorders,
+) {
+    let Ok((_, attacker)) = unit_query.get(attacker_entity) else { return };
+    let attacker_civ = attacker.civilization_id;
+    let mut civilians = Vec::new();
+    for (entity, unit) in unit_query.iter() {
+        if entity == attacker_entity || unit.hex_coord != vacated || unit.is_dead() {
+            continue;
+        }
+        if unit.can_attack || !are_enemies(attacker_civ, unit.civilization_id, civ_manager) {
+            return;
         }
+        civilians.push(entity);
     }
+    let Ok((_, mut attacker)) = unit_query.get_mut(attacker_entity) else { return };
+    let city = city_query.iter().find(|(_, city)| city.hex_coord == vacated);
+    if let Some((city_entity, city)) = city {
+        if city.civilization_id == attacker.civilization_id || city.hit_points > 0 || !can_capture_cities(&attacker) {
+            return;
+        }
+        recorders.capture_events.write(CityCaptured {
+            city_entity,
+            new_civilization_id: attacker.civilization_id,
+        });
+    }
+    for unit_entity in civilians {
+        recorders.unit_captures.write(UnitCaptured {
+            unit_entity,
+            new_civilization_id: attacker_civ,
+        });
+    }
+    attacker.advance_into(vacated);
+    println!("{} advances into ({}, {})", attacker.label(civ_manager), vacated.q, vacated.r);
+}
+
+fn log_promotion(log: &mut CombatLog, unit: &Unit, civ_manager: &CivilizationManager) {
+    log.push(CombatLogEntry {
+        turn: civ_manager.turn_number,
+        location: unit.hex_coord,
+        attacker: unit.label(civ_manager),
+        attacker_civ: unit.civilization_id,
+        defender: String::new(),
+        defender_civ: None,
+        damage_to_attacker: 0,
+        damage_to_defender: 0,
+        outcome: CombatOutcome::Promoted,
+    });
+}
+
+// Assault on an ungarrisoned city; a land melee win at 0 HP captures it
+fn execute_city_combat(
+    unit_query: &mut Query<(Entity, &mut Unit)>,
+    city_query: &mut Query<(Entity, &mut City)>,
+    city_entity: Entity,
+    preview: CombatPreview,
+    civ_manager: &CivilizationManager,
+    recorders: &mut CombatRecorders,
+) {
+    let Ok((_, mut attacker)) = unit_query.get_mut(preview.attacker_entity) else { return };
+    let Ok((_, mut city)) = city_query.get_mut(city_entity) else { return };
+    
+    let roll = recorders.rng.random::<f32>();
+    let attacker_wins = roll < preview.attacker_win_chance;
+    let attacker_label = attacker.label(civ_manager);
+    let can_capture = can_capture_cities(&attacker);
     
-    if let Ok((_, defender)) = unit_query.get(preview.defender_entity) {
-        if defender.is_dead() {
-            println!("Defender unit destroyed!");
-            commands.entity(preview.defender_entity).despawn();
+    println!("=== CITY ASSAULT RESULT ===");
+    println!("Roll: {:.3}, Win threshold: {:.3}", roll, preview.attacker_win_chance);
+    
+    attacker.has_attacked = true;
+    attacker.movement_points = attacker.movement_points.saturating_sub(1);
+    
+    let mut entry = CombatLogEntry {
+        turn: civ_manager.turn_number,
+        location: city.hex_coord,
+        attacker: attacker_label.clone(),
+        attacker_civ: attacker.civilization_id,
+        defender: city.name.clone(),
+        defender_civ: Some(city.civilization_id),
+        damage_to_attacker: 0,
+        damage_to_defender: 0,
+        outcome: CombatOutcome::AttackerWon,
+    };
+    let promoted;
+    if attacker_wins {
+        let (damage, _) = predicted_damage(&preview);
+        let before = city.hit_points;
+        city.take_damage(damage, if can_capture { 0 } else { 1 });
+        entry.damage_to_defender = before.saturating_sub(city.hit_points);
+        attacker.battles_survived += 1;
+        promoted = attacker.gain_experience(3);
+        println!("{} breaches the defenses of {}! The city takes {} damage ({}/{} HP).",
+                 attacker_label, city.name, damage, city.hit_points, super::cities::CITY_MAX_HIT_POINTS);
+        
+        if city.hit_points == 0 {
+            // March in and take the city
+            attacker.hex_coord = city.hex_coord;
+            attacker.movement_points = 0;
+            attacker.is_fortified = false;
+            attacker.fortification_turns = 0;
+            attacker.clear_order();
+            recorders.capture_events.write(CityCaptured {
+                city_entity,
+                new_civilization_id: attacker.civilization_id,
+            });
+            entry.outcome = CombatOutcome::CityCaptured;
         }
+    } else {
+        // A dead attacker is removed by cleanup_dead_units_system
+        let (_, damage) = predicted_damage(&preview);
+        attacker.take_damage(damage);
+        promoted = attacker.gain_experience(1);
+        println!("{} repels {}! {} takes {} damage.", city.name, attacker_label, attacker_label, damage);
+        recorders.hits.write(UnitDamagedEvent {
+            unit: preview.attacker_entity,
+            damage,
+            destroyed: attacker.is_dead(),
+            sides: (attacker.civilization_id, city.civilization_id),
+        });
+        entry.damage_to_attacker = damage;
+        entry.outcome = if attacker.is_dead() { CombatOutcome::AttackerDestroyed } else { CombatOutcome::DefenderHeld };
+        
+        if !attacker.is_dead() {
+            attacker.battles_survived += 1;
+        }
+    }
+    recorders.log.push(entry);
+    if promoted && !attacker.is_dead() {
+        log_promotion(&mut recorders.log, &attacker, civ_manager);
+    }
+}
+
+// Shelling a city from range: damages the city alone, never takes it below 1 HP and
+// draws no return fire. The garrison isn't hit; the city's HP shields it.
+fn execute_bombard(
+    unit_query: &mut Query<(Entity, &mut Unit)>,
+    city_query: &mut Query<(Entity, &mut City)>,
+    city_entity: Entity,
+    preview: CombatPreview,
+    civ_manager: &CivilizationManager,
+    log: &mut CombatLog,
+) {
+    let Ok((_, mut attacker)) = unit_query.get_mut(preview.attacker_entity) else { return };
+    let Ok((_, mut city)) = city_query.get_mut(city_entity) else { return };
+    
+    attacker.has_attacked = true;
+    attacker.movement_points = attacker.movement_points.saturating_sub(1);
+    let promoted = attacker.gain_experience(1);
+    
+    let (damage, _) = predicted_damage(&preview);
+    let before = city.hit_points;
+    city.take_damage(damage, 1);
+    println!("=== BOMBARDMENT RESULT ===");
+    println!("{} bombards {} for {} damage ({}/{} HP).",
+             attacker.label(civ_manager), city.name, before.saturating_sub(city.hit_points), city.hit_points, super::cities::CITY_MAX_HIT_POINTS);
+    log.push(CombatLogEntry {
+        turn: civ_manager.turn_number,
+        location: city.hex_coord,
+        attacker: attacker.label(civ_manager),
+        attacker_civ: attacker.civilization_id,
+        defender: city.name.clone(),
+        defender_civ: Some(city.civilization_id),
+        damage_to_attacker: 0,
+        damage_to_defender: before.saturating_sub(city.hit_points),
+        outcome: CombatOutcome::Bombarded,
+    });
+    if promoted {
+        log_promotion(log, &attacker, civ_manager);
     }
 }
 
-fn get_attack_range(unit: &Unit) -> i32 {
+pub fn get_attack_range(unit: &Unit) -> i32 {
     match unit.unit_type {
         super::cities::UnitType::Archer => 2, // Archers can attack from range
         super::cities::UnitType::Trireme => 2, // Naval units have range
+        super::cities::UnitType::Catapult => 2, // Siege engines lob stones over the walls
         _ => 1, // Most units are melee
     }
 }
 
-fn are_enemies(civ1: u32, civ2: u32, _civ_manager: &CivilizationManager) -> bool {
-    // For now, all civilizations are enemies except with themselves
-    // In a full game, you'd have a diplomacy system
+// Civilizations are enemies only while at war; barbarians are hostile to everyone
+pub fn are_enemies(civ1: u32, civ2: u32, civ_manager: &CivilizationManager) -> bool {
     civ1 != civ2
+        && (civ1 == BARBARIAN_CIV_ID || civ2 == BARBARIAN_CIV_ID || civ_manager.diplomacy.at_war(civ1, civ2))
 }
 
 fn get_terrain_defensive_bonus(coord: HexCoord, tile_query: &Query<&MapTile>) -> f32 {
@@ -376,6 +1007,16 @@ fn get_terrain_defensive_bonus(coord: HexCoord, tile_query: &Query<&MapTile>) ->
     }
 }
 
+// Damage the defender takes if the attack succeeds, and the attacker takes if it fails.
+// A bombardment always lands and draws no return fire.
+pub fn predicted_damage(preview: &CombatPreview) -> (u32, u32) {
+    let on_win = calculate_damage(preview.attacker_strength, preview.defender_strength, true);
+    match preview.defender {
+        CombatTarget::Bombard(_) => (((on_win as f32 * BOMBARD_DAMAGE_FACTOR).round() as u32).max(1), 0),
+        _ => (on_win, calculate_damage(preview.defender_strength, preview.attacker_strength, false)),
+    }
+}
+
 fn calculate_damage(winner_strength: u32, loser_strength: u32, attacker_won: bool) -> u32 {
     let strength_ratio = winner_strength as f32 / loser_strength.max(1) as f32;
     
@@ -390,13 +1031,6 @@ fn calculate_damage(winner_strength: u32, loser_strength: u32, attacker_won: boo
     (100.0 * damage_percent) as u32
 }
 
-fn hex_distance(a: HexCoord, b: HexCoord) -> i32 {
-    let dq = (a.q - b.q).abs();
-    let dr = (a.r - b.r).abs();
-    let ds = (-(a.q + a.r) + (b.q + b.r)).abs();
-    (dq.max(dr)).max(ds)
-}
-
 // System to clean up dead units from civilization lists
 pub fn cleanup_dead_units_system(
     mut commands: Commands,
@@ -420,4 +1054,324 @@ pub fn cleanup_dead_units_system(
         // Despawn entity
         commands.entity(entity).despawn();
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::civilization::create_default_civilizations;
+    use crate::game::cities::UnitType;
+    use crate::game::diplomacy::WarStrength;
+    use crate::game::map::TerrainType;
+    use crate::game::rng::GameRng;
+    use crate::game::test_support::{TEST_SEED, area, game_app, run};
+    
+    const ORIGIN: HexCoord = HexCoord { q: 0, r: 0 };
+    
+    // Open grassland around ORIGIN and two traitless civilizations, 1 (human) and 2, at war
+    fn battle_app() -> App {
+        let mut app = game_app(area(ORIGIN, 5, TerrainType::TemperateGrassland));
+        let mut manager = CivilizationManager::default();
+        for mut civ in create_default_civilizations().into_iter().take(2) {
+            civ.traits.clear();
+            manager.add_civilization(civ);
+        }
+        manager.diplomacy.declare_war((1, WarStrength::default()), (2, WarStrength::default()), 0);
+        app.insert_resource(manager).init_resource::<CombatLog>();
+        app
+    }
+    
+    fn place(app: &mut App, unit_type: UnitType, civ_id: u32, coord: HexCoord) -> Entity {
+        let entity = app.world_mut().spawn(Unit::new(unit_type, civ_id, coord)).id();
+        if let Some(civ) = app.world_mut().resource_mut::<CivilizationManager>().get_civilization_mut(civ_id) {
+            civ.add_unit(entity);
+        }
+        entity
+    }
+    
+    fn preview(app: &mut App, attacker: Entity, target: HexCoord) -> Option<CombatPreview> {
+        run(app, move |units: Query<(Entity, &mut Unit)>, cities: Query<(Entity, &mut City)>, tiles: Query<&MapTile>, manager: Res<CivilizationManager>| {
+            target_preview(attacker, target, &units, &cities, &tiles, &manager)
+        })
+    }
+    
+    // Fights out `attacker`'s attack on `target` the way combat_system does, returning what
+    // the log recorded
+    fn attack(app: &mut App, attacker: Entity, target: HexCoord) -> Vec<CombatLogEntry> {
+        let seen = app.world().resource::<CombatLog>().recorded();
+        run(app, move |
+            mut commands: Commands,
+            mut units: Query<(Entity, &mut Unit)>,
+            mut cities: Query<(Entity, &mut City)>,
+            tiles: TileLookup,
+            tile_query: Query<&MapTile>,
+            manager: Res<CivilizationManager>,
+            mut recorders: CombatRecorders,
+        | {
+            let preview = target_preview(attacker, target, &units, &cities, &tile_query, &manager).expect("something to attack");
+            resolve_attack(&mut commands, &mut units, &mut cities, &tiles, preview, &manager, &mut recorders);
+        });
+        app.world().resource::<CombatLog>().recorded_since(seen).cloned().collect()
+    }
+    
+    // Militaristic tips the odds by its bonus on whichever side it fights, without
+    // changing the strengths shown
+    #[test]
+    fn militaristic_civilizations_fight_harder() {
+        let target = HexCoord { q: 1, r: 0 };
+        let odds = |militaristic: Option<u32>| {
+            let mut app = battle_app();
+            if let Some(civ_id) = militaristic {
+                let mut manager = app.world_mut().resource_mut::<CivilizationManager>();
+                manager.get_civilization_mut(civ_id).unwrap().traits = vec![CivTrait::Militaristic];
+            }
+            let attacker = place(&mut app, UnitType::Spearman, 1, ORIGIN);
+            place(&mut app, UnitType::Spearman, 2, target);
+            preview(&mut app, attacker, target).expect("an enemy Spearman to attack")
+        };
+        
+        let plain = odds(None);
+        let (attack, defense) = (plain.attacker_strength as f32, plain.defender_strength as f32);
+        assert_eq!(plain.attacker_win_chance, attack / (attack + defense));
+        let bonus = 1.0 + MILITARISTIC_COMBAT_BONUS;
+        for (civ_id, expected) in [
+            (1, attack * bonus / (attack * bonus + defense)),
+            (2, attack / (attack + defense * bonus)),
+        ] {
+            let trained = odds(Some(civ_id));
+            assert_eq!((trained.attacker_strength, trained.defender_strength), (plain.attacker_strength, plain.defender_strength));
+            assert_eq!(trained.attacker_win_chance, expected, "Militaristic civilization {}", civ_id);
+        }
+    }
+    
+    #[test]
+    fn flanking_adds_a_tenth_per_unit_up_to_three() {
+        let multipliers: Vec<f32> = (0..=5).map(flanking_multiplier).collect();
+        assert_eq!(multipliers, vec![1.0, 1.1, 1.2, 1.0 + MAX_FLANKING_BONUS, 1.0 + MAX_FLANKING_BONUS, 1.0 + MAX_FLANKING_BONUS]);
+        assert!((flanking_multiplier(3) - 1.3).abs() < 1e-6);
+    }
+    
+    // A Warrior at ORIGIN attacked from the west with the attacker's other units on the
+    // other five sides. Only military units fighting on land count: three Warriors flank
+    // it, the Settler, the Galley and the defender's own ally don't.
+    #[test]
+    fn only_military_units_in_the_defenders_element_flank() {
+        let mut app = battle_app();
+        let [east, north_east, north_west, west, south_west, south_east] = ORIGIN.neighbors();
+        let attacker = place(&mut app, UnitType::Warrior, 1, west);
+        place(&mut app, UnitType::Warrior, 2, ORIGIN);
+        for flank in [east, north_east, north_west] {
+            place(&mut app, UnitType::Warrior, 1, flank);
+        }
+        place(&mut app, UnitType::Settler, 1, south_west);
+        place(&mut app, UnitType::Galley, 1, south_east);
+        place(&mut app, UnitType::Spearman, 2, south_east);
+        
+        let flanked = preview(&mut app, attacker, ORIGIN).expect("an enemy Warrior to attack");
+        assert_eq!(flanked.flanking_units, 3);
+        let (attack, defense) = (flanked.attacker_strength as f32, flanked.defender_strength as f32);
+        let flanking = flanking_multiplier(3);
+        assert_eq!(flanked.attacker_win_chance, attack * flanking / (attack * flanking + defense));
+        
+        // A fourth flanker is past the cap and changes nothing
+        place(&mut app, UnitType::Archer, 1, south_west);
+        let crowded = preview(&mut app, attacker, ORIGIN).unwrap();
+        assert_eq!(crowded.flanking_units, 4);
+        assert_eq!(crowded.attacker_win_chance, flanked.attacker_win_chance);
+    }
+    
+    // Ships flank ships: land units beside a defending Galley don't help the attacking one
+    #[test]
+    fn land_units_do_not_flank_ships() {
+        let mut app = battle_app();
+        let [east, north_east, _, west, _, _] = ORIGIN.neighbors();
+        let attacker = place(&mut app, UnitType::Galley, 1, west);
+        place(&mut app, UnitType::Galley, 2, ORIGIN);
+        place(&mut app, UnitType::Warrior, 1, east);
+        assert_eq!(preview(&mut app, attacker, ORIGIN).unwrap().flanking_units, 0);
+        
+        place(&mut app, UnitType::Galley, 1, north_east);
+        assert_eq!(preview(&mut app, attacker, ORIGIN).unwrap().flanking_units, 1);
+    }
+    
+    fn defender_at(app: &mut App, target: HexCoord) -> Option<Entity> {
+        run(app, move |units: Query<(Entity, &mut Unit)>, manager: Res<CivilizationManager>| {
+            strongest_defender(1, target, &units, &manager).map(|(entity, _)| entity)
+        })
+    }
+    
+    // A Settler escorted by a Warrior is behind it, even once the Warrior is badly hurt
+    #[test]
+    fn military_units_defend_before_civilians() {
+        let mut app = battle_app();
+        let warrior = place(&mut app, UnitType::Warrior, 2, ORIGIN);
+        place(&mut app, UnitType::Settler, 2, ORIGIN);
+        assert_eq!(defender_at(&mut app, ORIGIN), Some(warrior));
+        
+        app.world_mut().get_mut::<Unit>(warrior).unwrap().health = 1;
+        assert_eq!(defender_at(&mut app, ORIGIN), Some(warrior));
+        let attacker = place(&mut app, UnitType::Warrior, 1, HexCoord { q: 1, r: 0 });
+        assert_eq!(preview(&mut app, attacker, ORIGIN).unwrap().defender, CombatTarget::Unit(warrior));
+    }
+    
+    // Between military units the one with the most defense left after its wounds holds
+    // the hex, and the attacker's own units and the dead are never picked
+    #[test]
+    fn the_strongest_military_unit_defends() {
+        let mut app = battle_app();
+        let archer = place(&mut app, UnitType::Archer, 2, ORIGIN);
+        let spearman = place(&mut app, UnitType::Spearman, 2, ORIGIN);
+        place(&mut app, UnitType::Spearman, 1, ORIGIN);
+        assert_eq!(defender_at(&mut app, ORIGIN), Some(spearman));
+        
+        // Half dead, the Spearman is weaker than the fresh Archer
+        app.world_mut().get_mut::<Unit>(spearman).unwrap().health = 50;
+        assert_eq!(defender_at(&mut app, ORIGIN), Some(archer));
+        
+        // Killed, the Archer is out of the running
+        app.world_mut().get_mut::<Unit>(archer).unwrap().health = 0;
+        assert_eq!(defender_at(&mut app, ORIGIN), Some(spearman));
+        
+        // Nobody defends a hex holding only the attacker's own units
+        place(&mut app, UnitType::Warrior, 1, HexCoord { q: 2, r: 0 });
+        assert_eq!(defender_at(&mut app, HexCoord { q: 2, r: 0 }), None);
+    }
+    
+    // Type, owner, position, health and experience of a unit after the fighting
+    type Survivor = (UnitType, u32, HexCoord, u32, u32);
+    
+    // Three Horsemen charging three Spearmen in a row, and where everyone ended up
+    fn three_battles(seed: u64) -> (Vec<CombatLogEntry>, Vec<Survivor>) {
+        let mut app = battle_app();
+        app.insert_resource(GameRng::new(seed));
+        let mut log = Vec::new();
+        for row in 0..3 {
+            let target = HexCoord { q: 1, r: row };
+            let horseman = place(&mut app, UnitType::Horseman, 1, HexCoord { q: 0, r: row });
+            place(&mut app, UnitType::Spearman, 2, target);
+            log.extend(attack(&mut app, horseman, target));
+        }
+        let mut units: Vec<(Entity, Unit)> = app.world_mut()
+            .query::<(Entity, &Unit)>()
+            .iter(app.world())
+            .map(|(entity, unit)| (entity, unit.clone()))
+            .collect();
+        units.sort_by_key(|(entity, _)| *entity);
+        let units = units
+            .into_iter()
+            .map(|(_, unit)| (unit.unit_type, unit.civilization_id, unit.hex_coord, unit.health, unit.combat_experience))
+            .collect();
+        (log, units)
+    }
+    
+    // The same seed and the same orders fight the same battles, run after run
+    #[test]
+    fn seeded_battles_repeat_exactly() {
+        let first = three_battles(TEST_SEED);
+        assert_eq!(first.0.iter().filter(|entry| entry.outcome != CombatOutcome::Promoted).count(), 3);
+        for _ in 0..2 {
+            assert_eq!(three_battles(TEST_SEED), first);
+        }
+        let other_seeds: Vec<_> = (1..=5).map(|seed| three_battles(TEST_SEED + seed)).collect();
+        assert!(other_seeds.iter().any(|run| *run != first), "the seed should matter");
+    }
+    
+    // Over many fights the attacker wins about as often as the preview says it will
+    #[test]
+    fn preview_odds_match_the_rolls() {
+        const FIGHTS: usize = 2000;
+        let mut app = battle_app();
+        let target = HexCoord { q: 1, r: 0 };
+        let mut odds = None;
+        let mut wins = 0;
+        for _ in 0..FIGHTS {
+            let horseman = place(&mut app, UnitType::Horseman, 1, ORIGIN);
+            let warrior = place(&mut app, UnitType::Warrior, 2, target);
+            let chance = preview(&mut app, horseman, target).unwrap().attacker_win_chance;
+            assert_eq!(*odds.get_or_insert(chance), chance);
+            let battle = attack(&mut app, horseman, target);
+            if matches!(battle[0].outcome, CombatOutcome::AttackerWon | CombatOutcome::DefenderDestroyed) {
+                wins += 1;
+            }
+            for unit in [horseman, warrior] {
+                if let Ok(entity) = app.world_mut().get_entity_mut(unit) {
+                    entity.despawn();
+                }
+            }
+        }
+        
+        let expected = odds.unwrap();
+        let observed = wins as f32 / FIGHTS as f32;
+        assert!(expected > 0.05 && expected < 0.95 && expected != 0.5, "a matchup worth measuring, at {}", expected);
+        assert!((observed - expected).abs() < 0.03, "won {} of fights, preview said {}", observed, expected);
+    }
+    
+    // A Catapult that moved this turn can't open fire until the next, however it's asked to
+    #[test]
+    fn catapults_must_set_up_before_attacking() {
+        let mut app = battle_app();
+        app.init_resource::<ButtonInput<KeyCode>>()
+            .init_resource::<ClickIntent>()
+            .init_resource::<InputMode>()
+            .init_resource::<UnitSelection>()
+            .add_event::<UnitActionEvent>()
+            .add_systems(Update, (combat_system, attack_click_system.after(combat_system)));
+        let target = HexCoord { q: 2, r: 0 };
+        let catapult = place(&mut app, UnitType::Catapult, 1, ORIGIN);
+        place(&mut app, UnitType::Warrior, 2, target);
+        app.world_mut().resource_mut::<UnitSelection>().selected_unit = Some(catapult);
+        app.world_mut().get_mut::<Unit>(catapult).unwrap().has_moved = true;
+        
+        let warnings = |app: &mut App| -> Vec<String> {
+            let mut notifications = app.world_mut().resource_mut::<Events<GameNotification>>();
+            notifications.drain().map(|notification| notification.text).collect()
+        };
+        let set_up = "Catapult must set up first: siege engines can't attack on a turn they moved.".to_string();
+        
+        app.world_mut().resource_mut::<ClickIntent>().order = Some(target);
+        app.update();
+        assert!(app.world().resource::<CombatState>().combat_preview.is_none());
+        assert_eq!(warnings(&mut app), vec![set_up.clone()]);
+        
+        app.world_mut().send_event(UnitActionEvent { action: UnitAction::Attack });
+        app.update();
+        assert_eq!(*app.world().resource::<InputMode>(), InputMode::Normal);
+        assert_eq!(warnings(&mut app), vec![set_up]);
+        
+        // Set up by the next turn, it fires
+        app.world_mut().get_mut::<Unit>(catapult).unwrap().has_moved = false;
+        app.world_mut().resource_mut::<ClickIntent>().order = Some(target);
+        app.update();
+        let preview = app.world().resource::<CombatState>().combat_preview.clone().expect("the attack to be previewed");
+        assert_eq!(preview.attacker_entity, catapult);
+        assert!(warnings(&mut app).is_empty());
+    }
+    
+    // A Catapult shells a city at SIEGE_CITY_MULTIPLIER times its strength and fights units
+    // in the open at SIEGE_FIELD_PENALTY; other attackers get neither
+    #[test]
+    fn catapults_hit_cities_harder_and_units_softer() {
+        let mut app = battle_app();
+        let catapult = place(&mut app, UnitType::Catapult, 1, ORIGIN);
+        let archer = place(&mut app, UnitType::Archer, 1, ORIGIN);
+        let attack = UnitType::Catapult.get_stats().attack as f32;
+        
+        let field = HexCoord { q: 2, r: 0 };
+        place(&mut app, UnitType::Warrior, 2, field);
+        let in_the_open = preview(&mut app, catapult, field).unwrap();
+        assert_eq!(in_the_open.siege_modifier, SIEGE_FIELD_PENALTY);
+        assert_eq!(in_the_open.attacker_strength, ((attack * SIEGE_FIELD_PENALTY) as u32).max(1));
+        assert_eq!(preview(&mut app, archer, field).unwrap().siege_modifier, 1.0);
+        
+        let walls = HexCoord { q: 0, r: 2 };
+        let city = app.world_mut().spawn(City::new("Athens".to_string(), walls, 2, 1, true)).id();
+        let shelling = preview(&mut app, catapult, walls).unwrap();
+        assert_eq!(shelling.defender, CombatTarget::Bombard(city));
+        assert_eq!(shelling.siege_modifier, SIEGE_CITY_MULTIPLIER);
+        assert_eq!(shelling.attacker_strength, (attack * SIEGE_CITY_MULTIPLIER).round() as u32);
+        
+        let archery = preview(&mut app, archer, walls).unwrap();
+        assert_eq!(archery.siege_modifier, 1.0);
+        assert_eq!(archery.attacker_strength, UnitType::Archer.get_stats().attack);
+        assert!(predicted_damage(&shelling).0 > predicted_damage(&archery).0);
+    }
+}
@@ -0,0 +1,113 @@
+use bevy::prelude::*;
+use std::collections::VecDeque;
+use super::hex::HexCoord;
+use serde::{Deserialize, Serialize};
+
+// The log forgets its oldest entries past this many
+pub const MAX_COMBAT_LOG_ENTRIES: usize = 500;
+
+/// How a logged event turned out
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum CombatOutcome {
+    AttackerWon,
+    DefenderHeld,
+    AttackerDestroyed,
+    DefenderDestroyed,
+    DefenderWithdrew,
+    Bombarded,
+    CityCaptured,
+    Promoted,
+    CampCleared { gold: f32 },
+}
+
+/// One line of military history. Events with a single side (promotions, camp clearings)
+/// leave `defender` empty and `defender_civ` None.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CombatLogEntry {
+    pub turn: u32,
+    pub location: HexCoord,
+    pub attacker: String,
+    pub attacker_civ: u32,
+    pub defender: String,
+    pub defender_civ: Option<u32>,
+    pub damage_to_attacker: u32,
+    pub damage_to_defender: u32,
+    pub outcome: CombatOutcome,
+}
+
+impl CombatLogEntry {
+    // What happened, after the attacker's name and before the defender's
+    pub fn verb(&self) -> &'static str {
+        match self.outcome {
+            CombatOutcome::AttackerWon => " defeated ",
+            CombatOutcome::DefenderHeld => " was repelled by ",
+            CombatOutcome::AttackerDestroyed => " died attacking ",
+            CombatOutcome::DefenderDestroyed => " destroyed ",
+            CombatOutcome::DefenderWithdrew => " drove off ",
+            CombatOutcome::Bombarded => " bombarded ",
+            CombatOutcome::CityCaptured => " captured ",
+            CombatOutcome::Promoted => " was promoted",
+            CombatOutcome::CampCleared { .. } => " cleared a barbarian camp",
+        }
+    }
+    
+    // Damage and spoils, after the defender's name
+    pub fn details(&self) -> String {
+        let location = format!(" at ({}, {})", self.location.q, self.location.r);
+        match self.outcome {
+            CombatOutcome::Promoted => String::new(),
+            CombatOutcome::CampCleared { gold } => format!("{} (+{:.0} gold)", location, gold),
+            _ => format!("{} ({} dealt, {} taken)", location, self.damage_to_defender, self.damage_to_attacker),
+        }
+    }
+}
+
+/// Every battle, capture, promotion and camp clearing, oldest first, capped at
+/// MAX_COMBAT_LOG_ENTRIES
+#[derive(Resource, Default)]
+pub struct CombatLog {
+    entries: VecDeque<CombatLogEntry>,
+    recorded: usize, // Entries ever pushed, including those since forgotten
+}
+
+impl CombatLog {
+    pub fn push(&mut self, entry: CombatLogEntry) {
+        self.recorded += 1;
+        self.entries.push_back(entry);
+        while self.entries.len() > MAX_COMBAT_LOG_ENTRIES {
+            self.entries.pop_front();
+        }
+    }
+    
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    
+    pub fn newest_first(&self) -> impl Iterator<Item = &CombatLogEntry> {
+        self.entries.iter().rev()
+    }
+    
+    pub fn oldest_first(&self) -> impl Iterator<Item = &CombatLogEntry> {
+        self.entries.iter()
+    }
+    
+    // Swaps in the history of a loaded game. The running count carries on from this
+    // session, so the loaded entries aren't seen as new by recorded_since.
+    pub fn restore(&mut self, entries: Vec<CombatLogEntry>) {
+        self.entries = entries.into();
+    }
+    
+    pub fn recorded(&self) -> usize {
+        self.recorded
+    }
+    
+    // Entries pushed since `recorded()` returned `seen`, oldest first
+    pub fn recorded_since(&self, seen: usize) -> impl Iterator<Item = &CombatLogEntry> {
+        let new = self.recorded.saturating_sub(seen).min(self.entries.len());
+        self.entries.iter().skip(self.entries.len() - new)
+    }
+}
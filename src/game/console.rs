@@ -0,0 +1,465 @@
+use bevy::prelude::*;
+use bevy::ecs::system::SystemParam;
+use std::str::FromStr;
+use super::cities::UnitType;
+use super::civilization::{CivilizationManager, Technology};
+use super::game_initialization::{AppState, GameSettings, GameState, TurnStartedEvent, advance_turn};
+use super::hex::HexCoord;
+use super::input::{TextEntry, TextEntrySubmitted, TextEntryTarget};
+use super::input_map::{Action, InputMap};
+use super::map::TileIndex;
+use super::units::{Unit, UnitSelection, spawn_unit};
+
+// Lines of scrollback the console keeps, and how many of them it shows
+const MAX_SCROLLBACK: usize = 200;
+const LINES_SHOWN: usize = 14;
+
+/// Every command the console knows, with how to use it
+const COMMANDS: [&str; 11] = [
+    "help - list the commands",
+    "reveal - meet every civilization",
+    "gold <amount> - add gold to your treasury",
+    "tech all | tech <name> - learn every technology, or one",
+    "spawn <unit> <q> <r> - give yourself a unit",
+    "teleport <q> <r> - move the selected unit",
+    "kill - destroy the selected unit",
+    "end_turn - hand the turn to the next civilization",
+    "regen [seed=<n>] - start over on a new world",
+    "free_camera - let the camera leave the map, or keep it over the map again",
+    "omniscient - show every civilization's exact figures, or only what you know again",
+];
+
+/// A parsed console command
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConsoleCommand {
+    Help,
+    Reveal,
+    Gold(f32),
+    Tech(Option<Technology>), // None learns them all
+    Spawn(UnitType, HexCoord),
+    Teleport(HexCoord),
+    Kill,
+    EndTurn,
+    Regen(Option<u64>), // None picks a world seed at random
+    FreeCamera,
+    Omniscient,
+}
+
+// Names compare without spaces, underscores or case, so "great_scientist" finds Great Scientist
+fn names_match(name: &str, query: &str) -> bool {
+    let squash = |text: &str| text.replace([' ', '_'], "").to_lowercase();
+    squash(name) == squash(query)
+}
+
+fn number<T: FromStr>(arg: Option<&str>, what: &str) -> Result<T, String> {
+    let arg = arg.ok_or_else(|| format!("expected {}", what))?;
+    arg.parse().map_err(|_| format!("'{}' is not a valid {}", arg, what))
+}
+
+fn coord<'a>(args: &mut impl Iterator<Item = &'a str>) -> Result<HexCoord, String> {
+    Ok(HexCoord::new(number(args.next(), "q coordinate")?, number(args.next(), "r coordinate")?))
+}
+
+impl ConsoleCommand {
+    pub fn parse(line: &str) -> Result<ConsoleCommand, String> {
+        let mut args = line.split_whitespace();
+        let name = args.next().unwrap_or_default().to_lowercase();
+        let command = match name.as_str() {
+            "help" => ConsoleCommand::Help,
+            "reveal" => ConsoleCommand::Reveal,
+            "gold" => ConsoleCommand::Gold(number(args.next(), "amount")?),
+            "tech" => {
+                let query = args.by_ref().collect::<Vec<_>>().join(" ");
+                if query.is_empty() {
+                    return Err("expected 'all' or a technology".to_string());
+                }
+                if query.eq_ignore_ascii_case("all") {
+                    ConsoleCommand::Tech(None)
+                } else {
+                    let tech = Technology::ALL.into_iter().find(|tech| names_match(tech.get_name(), &query));
+                    ConsoleCommand::Tech(Some(tech.ok_or_else(|| format!("no technology called '{}'", query))?))
+                }
+            }
+            "spawn" => {
+                let query = args.next().ok_or("expected a unit")?;
+                let unit_type = UnitType::ALL
+                    .into_iter()
+                    .find(|unit_type| names_match(unit_type.get_name(), query))
+                    .ok_or_else(|| format!("no unit called '{}'", query))?;
+                ConsoleCommand::Spawn(unit_type, coord(&mut args)?)
+            }
+            "teleport" => ConsoleCommand::Teleport(coord(&mut args)?),
+            "kill" => ConsoleCommand::Kill,
+            "end_turn" => ConsoleCommand::EndTurn,
+            "free_camera" => ConsoleCommand::FreeCamera,
+            "omniscient" => ConsoleCommand::Omniscient,
+            "regen" => match args.next() {
+                None => ConsoleCommand::Regen(None),
+                Some(arg) => ConsoleCommand::Regen(Some(number(arg.strip_prefix("seed="), "seed=<n>")?)),
+            },
+            _ => return Err(format!("unknown command '{}'; try help", name)),
+        };
+        match args.next() {
+            Some(extra) => Err(format!("unexpected '{}' after {}", extra, name)),
+            None => Ok(command),
+        }
+    }
+}
+
+/// The console's scrollback. It's open while the text entry is taking a console command.
+#[derive(Resource, Default)]
+pub struct DevConsole {
+    lines: Vec<String>,
+}
+
+impl DevConsole {
+    fn push(&mut self, line: String) {
+        self.lines.push(line);
+        if self.lines.len() > MAX_SCROLLBACK {
+            self.lines.remove(0);
+        }
+    }
+}
+
+/// Asks for a new world once the game has been left: `regen` goes through the setup
+/// screen so the old game is cleared away first. The seed is for that one world; the
+/// game settings keep their own.
+#[derive(Resource)]
+pub struct ConsoleRegen(pub(super) Option<u64>);
+
+/// What console commands act on
+#[derive(SystemParam)]
+pub struct ConsoleWorld<'w, 's> {
+    commands: Commands<'w, 's>,
+    civ_manager: ResMut<'w, CivilizationManager>,
+    game_state: ResMut<'w, GameState>,
+    unit_selection: Res<'w, UnitSelection>,
+    unit_query: Query<'w, 's, &'static mut Unit>,
+    tile_index: Res<'w, TileIndex>, // Not TileLookup, which reads the settings changed here
+    turn_events: EventWriter<'w, TurnStartedEvent>,
+    next_state: ResMut<'w, NextState<AppState>>,
+    settings: ResMut<'w, GameSettings>,
+}
+
+impl ConsoleWorld<'_, '_> {
+    // Carries out a command, returning what to print. Gold, technologies and units go to
+    // the civilization on screen.
+    fn run(&mut self, command: ConsoleCommand) -> Result<String, String> {
+        let civ_id = self.civ_manager.get_viewed_civilization().map(|civ| civ.id).ok_or("nobody to act for")?;
+        match command {
+            ConsoleCommand::Help => Ok(COMMANDS.join("\n")),
+            ConsoleCommand::Reveal => {
+                // There is no fog of war; what's hidden is who the other civilizations are
+                let others: Vec<u32> = self.civ_manager.civilizations.keys().copied().filter(|id| *id != civ_id).collect();
+                let met = others.iter().filter(|other| self.civ_manager.meet(civ_id, **other)).count();
+                Ok(format!("Met {} new civilization(s)", met))
+            }
+            ConsoleCommand::Gold(amount) => {
+                let civ = self.civ_manager.get_civilization_mut(civ_id).ok_or("nobody to act for")?;
+                civ.gold += amount;
+                Ok(format!("Treasury: {:.0} gold", civ.gold))
+            }
+            ConsoleCommand::Tech(tech) => {
+                let civ = self.civ_manager.get_civilization_mut(civ_id).ok_or("nobody to act for")?;
+                let known = civ.technologies.len();
+                match tech {
+                    Some(tech) => civ.grant_technology(tech),
+                    None => Technology::ALL.into_iter().for_each(|tech| civ.grant_technology(tech)),
+                }
+                Ok(format!("Learned {} technologies", civ.technologies.len() - known))
+            }
+            ConsoleCommand::Spawn(unit_type, at) => {
+                self.on_map(at)?;
+                spawn_unit(&mut self.commands, unit_type, civ_id, at, &mut self.civ_manager);
+                Ok(format!("Spawned a {} at ({}, {})", unit_type.get_name(), at.q, at.r))
+            }
+            ConsoleCommand::Teleport(at) => {
+                self.on_map(at)?;
+                let mut unit = self.selected_unit()?;
+                unit.hex_coord = at;
+                Ok(format!("{} moved to ({}, {})", unit.unit_type.get_name(), at.q, at.r))
+            }
+            ConsoleCommand::Kill => {
+                // Left to cleanup_dead_units_system like any other loss
+                let mut unit = self.selected_unit()?;
+                unit.health = 0;
+                Ok(format!("{} destroyed", unit.unit_type.get_name()))
+            }
+            ConsoleCommand::EndTurn => {
+                advance_turn(&mut self.game_state, &mut self.civ_manager, &mut self.turn_events);
+                Ok(format!("Turn {}: {} to move", self.civ_manager.turn_number, self.civ_manager.civ_name(self.civ_manager.current_turn_civ)))
+            }
+            ConsoleCommand::Regen(seed) => {
+                self.commands.insert_resource(ConsoleRegen(seed));
+                self.next_state.set(AppState::Setup);
+                Ok(match seed {
+                    Some(seed) => format!("Generating a new world from seed {}", seed),
+                    None => "Generating a new world".to_string(),
+                })
+            }
+            ConsoleCommand::FreeCamera => {
+                self.settings.free_camera = !self.settings.free_camera;
+                Ok(if self.settings.free_camera { "The camera is free" } else { "The camera stays over the map" }.to_string())
+            }
+            ConsoleCommand::Omniscient => {
+                self.settings.omniscient = !self.settings.omniscient;
+                Ok(if self.settings.omniscient { "Showing every civilization's figures" } else { "Showing only what you know" }.to_string())
+            }
+        }
+    }
+    
+    fn on_map(&self, at: HexCoord) -> Result<(), String> {
+        if self.tile_index.tiles.contains_key(&at) {
+            Ok(())
+        } else {
+            Err(format!("({}, {}) is off the map", at.q, at.r))
+        }
+    }
+    
+    fn selected_unit(&mut self) -> Result<Mut<'_, Unit>, String> {
+        let entity = self.unit_selection.selected_unit.ok_or("no unit selected")?;
+        self.unit_query.get_mut(entity).map_err(|_| "no unit selected".to_string())
+    }
+}
+
+// Backquote opens the console when it's enabled (debug builds, or --enable-console);
+// the text entry closes it again on Backquote or Escape
+pub fn console_toggle_system(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    input_map: Res<InputMap>,
+    settings: Res<GameSettings>,
+    mut text_entry: ResMut<TextEntry>,
+) {
+    if settings.console && !text_entry.is_active() && input_map.just_pressed(Action::ToggleConsole, &keyboard) {
+        text_entry.begin(TextEntryTarget::Console, String::new(), String::new());
+    }
+}
+
+// Runs each submitted command into the scrollback and keeps the console open for the next
+pub fn console_command_system(
+    mut submitted: EventReader<TextEntrySubmitted>,
+    mut console: ResMut<DevConsole>,
+    mut text_entry: ResMut<TextEntry>,
+    mut world: ConsoleWorld,
+) {
+    for event in submitted.read() {
+        if event.target != TextEntryTarget::Console {
+            continue;
+        }
+        console.push(format!("> {}", event.text));
+        match ConsoleCommand::parse(&event.text).and_then(|command| world.run(command)) {
+            Ok(reply) => reply.lines().for_each(|line| console.push(line.to_string())),
+            Err(error) => console.push(format!("Error: {}", error)),
+        }
+        text_entry.begin(TextEntryTarget::Console, String::new(), String::new());
+    }
+}
+
+// Once `regen` has left the game for the setup screen, starts a new one. World generation
+// picks up the asked-for seed and clears the request.
+pub fn console_regen_system(mut next_state: ResMut<NextState<AppState>>) {
+    next_state.set(AppState::Loading);
+}
+
+#[derive(Component)]
+pub struct ConsoleText;
+
+pub fn setup_console_ui(mut commands: Commands) {
+    // Developer console (top, across the screen)
+    commands.spawn((
+        ConsoleText,
+        Text::new(""),
+        TextFont {
+            font_size: 13.0,
+            ..default()
+        },
+        TextColor(Color::srgb(0.7, 1.0, 0.7)),
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.9)),
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(10.0),
+            right: Val::Px(10.0),
+            top: Val::Px(10.0),
+            padding: UiRect::all(Val::Px(8.0)),
+            display: Display::None,
+            ..default()
+        },
+        GlobalZIndex(10),
+    ));
+}
+
+pub fn console_display_system(
+    console: Res<DevConsole>,
+    text_entry: Res<TextEntry>,
+    mut text_query: Query<(&mut Text, &mut Node), With<ConsoleText>>,
+) {
+    if !console.is_changed() && !text_entry.is_changed() {
+        return;
+    }
+    let Ok((mut text, mut node)) = text_query.single_mut() else { return };
+    
+    if text_entry.target != Some(TextEntryTarget::Console) {
+        node.display = Display::None;
+        return;
+    }
+    node.display = Display::Flex;
+    let shown = &console.lines[console.lines.len().saturating_sub(LINES_SHOWN)..];
+    let mut lines: Vec<String> = shown.to_vec();
+    lines.push(format!("> {}_", text_entry.buffer));
+    **text = lines.join("\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::civilization::create_default_civilizations;
+    use crate::game::map::TerrainType;
+    use crate::game::test_support::{area, game_app};
+    
+    const ORIGIN: HexCoord = HexCoord { q: 0, r: 0 };
+    
+    #[test]
+    fn parses_every_command() {
+        let cases = [
+            ("help", ConsoleCommand::Help),
+            ("reveal", ConsoleCommand::Reveal),
+            ("gold 250", ConsoleCommand::Gold(250.0)),
+            ("gold -12.5", ConsoleCommand::Gold(-12.5)),
+            ("tech all", ConsoleCommand::Tech(None)),
+            ("tech ALL", ConsoleCommand::Tech(None)),
+            ("tech pottery", ConsoleCommand::Tech(Some(Technology::Pottery))),
+            ("tech Animal Husbandry", ConsoleCommand::Tech(Some(Technology::AnimalHusbandry))),
+            ("tech animal_husbandry", ConsoleCommand::Tech(Some(Technology::AnimalHusbandry))),
+            ("spawn warrior 3 -2", ConsoleCommand::Spawn(UnitType::Warrior, HexCoord::new(3, -2))),
+            ("spawn great_scientist 0 0", ConsoleCommand::Spawn(UnitType::GreatScientist, ORIGIN)),
+            ("teleport -4 7", ConsoleCommand::Teleport(HexCoord::new(-4, 7))),
+            ("kill", ConsoleCommand::Kill),
+            ("end_turn", ConsoleCommand::EndTurn),
+            ("regen", ConsoleCommand::Regen(None)),
+            ("regen seed=42", ConsoleCommand::Regen(Some(42))),
+            ("free_camera", ConsoleCommand::FreeCamera),
+            ("omniscient", ConsoleCommand::Omniscient),
+            ("  GOLD   5  ", ConsoleCommand::Gold(5.0)),
+        ];
+        for (line, expected) in cases {
+            assert_eq!(ConsoleCommand::parse(line), Ok(expected), "{:?}", line);
+        }
+    }
+    
+    #[test]
+    fn rejects_malformed_commands() {
+        let cases = [
+            ("", "unknown command ''; try help"),
+            ("fly", "unknown command 'fly'; try help"),
+            ("gold", "expected amount"),
+            ("gold lots", "'lots' is not a valid amount"),
+            ("tech", "expected 'all' or a technology"),
+            ("tech alchemy", "no technology called 'alchemy'"),
+            ("spawn", "expected a unit"),
+            ("spawn dragon 0 0", "no unit called 'dragon'"),
+            ("spawn warrior 1", "expected r coordinate"),
+            ("teleport 1 x", "'x' is not a valid r coordinate"),
+            ("regen 42", "expected seed=<n>"),
+            ("regen seed=abc", "'abc' is not a valid seed=<n>"),
+            ("kill everyone", "unexpected 'everyone' after kill"),
+            ("gold 5 5", "unexpected '5' after gold"),
+        ];
+        for (line, expected) in cases {
+            assert_eq!(ConsoleCommand::parse(line), Err(expected.to_string()), "{:?}", line);
+        }
+    }
+    
+    // The six default civilizations, the first played by a human, on a patch of grassland
+    // with the console open
+    fn console_app() -> App {
+        let mut app = game_app(area(ORIGIN, 2, TerrainType::TemperateGrassland));
+        let mut manager = CivilizationManager::default();
+        for civ in create_default_civilizations() {
+            manager.add_civilization(civ);
+        }
+        manager.current_turn_civ = 1;
+        app.insert_resource(manager)
+            .init_resource::<DevConsole>()
+            .init_resource::<TextEntry>()
+            .init_resource::<UnitSelection>()
+            .init_resource::<NextState<AppState>>()
+            .add_event::<TextEntrySubmitted>()
+            .add_systems(Update, console_command_system);
+        app
+    }
+    
+    // Types `line` into the console and returns what it printed
+    fn submit(app: &mut App, line: &str) -> Vec<String> {
+        let before = app.world().resource::<DevConsole>().lines.len();
+        app.world_mut().send_event(TextEntrySubmitted { target: TextEntryTarget::Console, text: line.to_string() });
+        app.update();
+        assert_eq!(app.world().resource::<TextEntry>().target, Some(TextEntryTarget::Console), "the console stays open");
+        app.world().resource::<DevConsole>().lines[before..].to_vec()
+    }
+    
+    fn player(app: &App) -> &crate::game::civilization::Civilization {
+        app.world().resource::<CivilizationManager>().get_civilization(1).unwrap()
+    }
+    
+    #[test]
+    fn commands_act_for_the_player() {
+        let mut app = console_app();
+        
+        let gold = player(&app).gold;
+        assert_eq!(submit(&mut app, "gold 50"), vec!["> gold 50".to_string(), format!("Treasury: {:.0} gold", gold + 50.0)]);
+        assert_eq!(player(&app).gold, gold + 50.0);
+        
+        submit(&mut app, "tech pottery");
+        assert!(player(&app).technologies.contains(&Technology::Pottery));
+        submit(&mut app, "tech all");
+        assert_eq!(player(&app).technologies.len(), Technology::ALL.len());
+        
+        assert_eq!(submit(&mut app, "reveal")[1], "Met 5 new civilization(s)");
+        assert!((2..=6).all(|other| player(&app).has_met(other)));
+        assert_eq!(submit(&mut app, "reveal")[1], "Met 0 new civilization(s)");
+        
+        assert_eq!(submit(&mut app, "end_turn")[1], format!("Turn 1: {} to move", app.world().resource::<CivilizationManager>().civ_name(2)));
+        assert_eq!(app.world().resource::<CivilizationManager>().current_turn_civ, 2);
+    }
+    
+    #[test]
+    fn spawned_units_can_be_moved_and_killed() {
+        let mut app = console_app();
+        assert_eq!(submit(&mut app, "spawn scout 1 0")[1], "Spawned a Scout at (1, 0)");
+        let scout = *player(&app).units.last().expect("the Scout joins the player's units");
+        let unit = app.world().get::<Unit>(scout).unwrap();
+        assert_eq!((unit.unit_type, unit.civilization_id, unit.hex_coord), (UnitType::Scout, 1, HexCoord::new(1, 0)));
+        
+        assert_eq!(submit(&mut app, "teleport 0 2")[1], "Error: no unit selected");
+        app.world_mut().resource_mut::<UnitSelection>().selected_unit = Some(scout);
+        assert_eq!(submit(&mut app, "teleport 9 9")[1], "Error: (9, 9) is off the map");
+        assert_eq!(submit(&mut app, "teleport 0 2")[1], "Scout moved to (0, 2)");
+        assert_eq!(app.world().get::<Unit>(scout).unwrap().hex_coord, HexCoord::new(0, 2));
+        
+        assert_eq!(submit(&mut app, "kill")[1], "Scout destroyed");
+        assert!(app.world().get::<Unit>(scout).unwrap().is_dead());
+        
+        assert_eq!(submit(&mut app, "spawn warrior 5 5")[1], "Error: (5, 5) is off the map");
+        assert_eq!(submit(&mut app, "dance")[1], "Error: unknown command 'dance'; try help");
+    }
+    
+    // `regen` leaves for the setup screen carrying the seed, and the toggles flip back and forth
+    #[test]
+    fn regen_and_toggles() {
+        let mut app = console_app();
+        submit(&mut app, "regen seed=9");
+        assert_eq!(app.world().resource::<ConsoleRegen>().0, Some(9));
+        assert_eq!(app.world().resource::<GameSettings>().world_seed, None, "the seed is for the one world");
+        assert!(matches!(app.world().resource::<NextState<AppState>>(), NextState::Pending(AppState::Setup)));
+        
+        let free_camera = app.world().resource::<GameSettings>().free_camera;
+        submit(&mut app, "free_camera");
+        assert_eq!(app.world().resource::<GameSettings>().free_camera, !free_camera);
+        submit(&mut app, "free_camera");
+        assert_eq!(app.world().resource::<GameSettings>().free_camera, free_camera);
+        
+        let omniscient = app.world().resource::<GameSettings>().omniscient;
+        submit(&mut app, "omniscient");
+        assert_eq!(app.world().resource::<GameSettings>().omniscient, !omniscient);
+    }
+}
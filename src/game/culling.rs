@@ -1,13 +1,18 @@
 use bevy::prelude::*;
-use super::map::{MapTile, HEX_SIZE};
+use super::map::HEX_SIZE;
 
+/// Hidden by viewport culling while it's out of view: map tiles, grid lines and the unit
+/// and city markers drawn over them
 #[derive(Component)]
-pub struct Culled;
+pub struct Cullable;
+
+type CullableQuery<'w, 's, F> = Query<'w, 's, (&'static Transform, &'static mut Visibility), F>;
+type MovedCullables = (With<Cullable>, Or<(Changed<Transform>, Added<Cullable>)>);
 
 #[derive(Resource)]
 pub struct ViewportCulling {
     pub enabled: bool,
-    pub padding: f32, // Extra tiles to render beyond viewport
+    pub padding: f32, // Extra distance to render beyond viewport, at normal zoom
 }
 
 impl Default for ViewportCulling {
@@ -19,47 +24,41 @@ impl Default for ViewportCulling {
     }
 }
 
+// The part of the world the camera shows, grown by the padding. Both grow with the camera's
+// scale: zoomed out, a pixel covers more of the world.
+fn visible_world_rect(camera_transform: &Transform, window: &Window, padding: f32) -> Rect {
+    let scale = camera_transform.scale.truncate();
+    let half_size = window.size() / 2.0 * scale + Vec2::splat(padding) * scale;
+    Rect::from_center_half_size(camera_transform.translation.truncate(), half_size)
+}
+
+// Hides whatever is outside the view. Flipping Visibility leaves the entities where they
+// are, where a marker component would move tens of thousands of them between archetypes on
+// every pan. Everything is gone through only when the view has changed; otherwise just
+// what has moved or newly appeared, such as a unit stepping onto the screen.
 pub fn viewport_culling_system(
-    mut commands: Commands,
-    camera_query: Query<&Transform, (With<Camera>, Without<MapTile>)>,
+    camera_query: Query<&Transform, (With<Camera>, Without<Cullable>)>,
     windows: Query<&Window>,
-    tile_query: Query<(Entity, &Transform, &MapTile), Without<Culled>>,
-    culled_query: Query<(Entity, &Transform, &MapTile), With<Culled>>,
+    mut cullables: ParamSet<(CullableQuery<With<Cullable>>, CullableQuery<MovedCullables>)>,
     culling_settings: Res<ViewportCulling>,
+    mut last_view: Local<Option<Rect>>,
 ) {
-    if !culling_settings.enabled {
-        return;
-    }
-
-    let Ok(camera_transform) = camera_query.single() else { return };
-    let Ok(window) = windows.single() else { return };
-
-    // Calculate viewport bounds in world space
-    let camera_pos = camera_transform.translation.truncate();
-    let half_width = window.width() / 2.0;
-    let half_height = window.height() / 2.0;
-    
-    let viewport_min = camera_pos - Vec2::new(half_width, half_height) - Vec2::splat(culling_settings.padding);
-    let viewport_max = camera_pos + Vec2::new(half_width, half_height) + Vec2::splat(culling_settings.padding);
-
-    // Cull tiles that are outside viewport
-    for (entity, transform, _) in tile_query.iter() {
-        let tile_pos = transform.translation.truncate();
-        
-        if tile_pos.x < viewport_min.x || tile_pos.x > viewport_max.x ||
-           tile_pos.y < viewport_min.y || tile_pos.y > viewport_max.y {
-            commands.entity(entity).insert(Culled);
-        }
-    }
-
-    // Un-cull tiles that are back in viewport
-    for (entity, transform, _) in culled_query.iter() {
-        let tile_pos = transform.translation.truncate();
-        
-        if tile_pos.x >= viewport_min.x && tile_pos.x <= viewport_max.x &&
-           tile_pos.y >= viewport_min.y && tile_pos.y <= viewport_max.y {
-            commands.entity(entity).remove::<Culled>();
-        }
+    let view = if culling_settings.enabled {
+        let Ok(camera_transform) = camera_query.single() else { return };
+        let Ok(window) = windows.single() else { return };
+        Some(visible_world_rect(camera_transform, window, culling_settings.padding))
+    } else {
+        None
+    };
+    let cull = |(transform, mut visibility): (&Transform, Mut<Visibility>)| {
+        let on_screen = view.is_none_or(|view| view.contains(transform.translation.truncate()));
+        visibility.set_if_neq(if on_screen { Visibility::Inherited } else { Visibility::Hidden });
+    };
+    if *last_view != view || culling_settings.is_changed() {
+        *last_view = view;
+        cullables.p0().iter_mut().for_each(cull);
+    } else {
+        cullables.p1().iter_mut().for_each(cull);
     }
 }
 
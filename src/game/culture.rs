@@ -0,0 +1,256 @@
+use bevy::prelude::*;
+use bevy::ecs::entity::{EntityMapper, MapEntities};
+use std::collections::{HashMap, HashSet};
+use super::cities::{City, TerritoryMap};
+use super::civilization::{CivTrait, CivilizationManager, SPIRITUAL_PRESSURE_BONUS};
+use super::game_initialization::TurnStartedEvent;
+use super::hex::HexCoord;
+use super::stable_id::as_stable_id;
+use super::units::Unit;
+use serde::{Deserialize, Serialize};
+
+// Cities push culture onto tiles this far away, weakening with distance
+pub const PRESSURE_RADIUS: i32 = 3;
+// A rival needs this much more pressure on a tile than its owner to start winning it over...
+pub const PRESSURE_DOMINANCE: f32 = 2.0;
+// ...and at least this much in absolute terms, so two quiet cities never trade tiles
+pub const MIN_FLIP_PRESSURE: f32 = 3.0;
+// Full turns the rival has to keep the upper hand before the tile changes hands
+pub const PRESSURE_FLIP_TURNS: u32 = 10;
+
+/// A border tile changed hands because a rival's culture overwhelmed it
+#[derive(Event)]
+pub struct TileFlippedEvent {
+    pub tile: HexCoord,
+    pub city_entity: Entity, // The city that gained it
+    pub civilization_id: u32,
+    pub previous_civilization_id: u32,
+}
+
+/// A rival that has been out-pressuring a tile's owner
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PressureRecord {
+    pub civilization_id: u32,
+    #[serde(with = "as_stable_id")]
+    pub city: Entity, // The rival city pushing hardest
+    pub turns: u32,   // Consecutive full turns it has dominated
+}
+
+/// Culture pressure on contested border tiles, updated once per full turn
+#[derive(Resource, Clone, Default, Serialize, Deserialize)]
+pub struct CulturePressure {
+    pub tiles: HashMap<HexCoord, PressureRecord>,
+    last_turn: u32,
+}
+
+impl CulturePressure {
+    pub fn record(&self, coord: HexCoord) -> Option<PressureRecord> {
+        self.tiles.get(&coord).copied()
+    }
+}
+
+impl MapEntities for CulturePressure {
+    fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+        for record in self.tiles.values_mut() {
+            record.city = entity_mapper.get_mapped(record.city);
+        }
+    }
+}
+
+// How hard a city pushes on a tile: its culture, boosted for Spiritual owners,
+// divided by one more than the distance
+pub fn pressure_on(city: &City, coord: HexCoord) -> f32 {
+    let distance = city.hex_coord.distance(coord);
+    if distance > PRESSURE_RADIUS || city.is_occupied() {
+        return 0.0;
+    }
+    let mut strength = city.culture_per_turn;
+    // Spiritual: culture carries further into rival borders
+    if city.has_trait(CivTrait::Spiritual) {
+        strength *= 1.0 + SPIRITUAL_PRESSURE_BONUS;
+    }
+    strength / (1 + distance) as f32
+}
+
+// The civilization most likely to win a tile from `owner`: the rival with the most total
+// pressure, if it dominates the owner's own, along with its strongest city there
+pub fn dominant_rival<'a>(
+    coord: HexCoord,
+    owner: u32,
+    cities: impl Iterator<Item = (Entity, &'a City)>,
+) -> Option<(u32, Entity)> {
+    let mut totals: HashMap<u32, f32> = HashMap::new();
+    let mut strongest: HashMap<u32, (Entity, f32)> = HashMap::new();
+    for (city_entity, city) in cities {
+        let pressure = pressure_on(city, coord);
+        if pressure <= 0.0 {
+            continue;
+        }
+        *totals.entry(city.civilization_id).or_default() += pressure;
+        let best = strongest.entry(city.civilization_id).or_insert((city_entity, pressure));
+        if pressure > best.1 {
+            *best = (city_entity, pressure);
+        }
+    }
+    
+    let own = totals.get(&owner).copied().unwrap_or(0.0);
+    let (rival, pressure) = totals
+        .iter()
+        .filter(|(civ_id, _)| **civ_id != owner)
+        .max_by(|a, b| a.1.total_cmp(b.1).then(b.0.cmp(a.0)))?;
+    if *pressure < MIN_FLIP_PRESSURE || *pressure < own * PRESSURE_DOMINANCE {
+        return None;
+    }
+    strongest.get(rival).map(|(city_entity, _)| (*rival, *city_entity))
+}
+
+// System that lets strong culture pull border tiles away from weaker neighbours. Once per
+// full turn every owned tile touching another civilization's territory is weighed; a rival
+// that dominates it for PRESSURE_FLIP_TURNS turns in a row takes it. City centers never
+// flip, and a unit of the owner standing on a tile holds it for as long as it stays.
+pub fn culture_pressure_system(
+    mut turn_events: EventReader<TurnStartedEvent>,
+    civ_manager: Res<CivilizationManager>,
+    mut pressure: ResMut<CulturePressure>,
+    mut territory: ResMut<TerritoryMap>,
+    mut city_query: Query<(Entity, &mut City)>,
+    unit_query: Query<&Unit>,
+    mut flip_events: EventWriter<TileFlippedEvent>,
+) {
+    if turn_events.read().count() == 0 || civ_manager.turn_number <= pressure.last_turn {
+        return;
+    }
+    pressure.last_turn = civ_manager.turn_number;
+    
+    let centers: HashSet<HexCoord> = city_query.iter().map(|(_, city)| city.hex_coord).collect();
+    let garrisons: HashSet<(HexCoord, u32)> = unit_query
+        .iter()
+        .map(|unit| (unit.hex_coord, unit.civilization_id))
+        .collect();
+    let border_tiles: Vec<(HexCoord, Entity, u32)> = city_query
+        .iter()
+        .flat_map(|(city_entity, city)| {
+            city.territory_tiles.iter().map(move |coord| (*coord, city_entity, city.civilization_id))
+        })
+        .filter(|(coord, _, owner)| {
+            !centers.contains(coord)
+                && coord.neighbors().iter().any(|neighbor| {
+                    territory.owner(*neighbor).is_some_and(|other| other.civilization_id != *owner)
+                })
+        })
+        .collect();
+    
+    let mut records = HashMap::new();
+    let mut flips: Vec<(HexCoord, Entity, u32, PressureRecord)> = Vec::new();
+    for (coord, owner_city, owner) in border_tiles {
+        let Some((rival, rival_city)) = dominant_rival(coord, owner, city_query.iter()) else { continue };
+        let turns = match pressure.record(coord) {
+            Some(record) if record.civilization_id == rival => record.turns + 1,
+            _ => 1,
+        };
+        let record = PressureRecord { civilization_id: rival, city: rival_city, turns };
+        if turns >= PRESSURE_FLIP_TURNS && !garrisons.contains(&(coord, owner)) {
+            flips.push((coord, owner_city, owner, record));
+        } else {
+            records.insert(coord, record);
+        }
+    }
+    pressure.tiles = records;
+    
+    for (coord, owner_city, owner, record) in flips {
+        if !city_query.contains(record.city) {
+            continue;
+        }
+        if let Ok((_, mut city)) = city_query.get_mut(owner_city) {
+            city.territory_tiles.retain(|tile| *tile != coord);
+            city.worked_tiles.retain(|tile| *tile != coord);
+        }
+        let Ok((_, mut city)) = city_query.get_mut(record.city) else { continue };
+        territory.seize(coord, record.city, record.civilization_id);
+        city.territory_tiles.push(coord);
+        city.territory_radius = city.territory_radius.max(coord.distance(city.hex_coord) as u32);
+        flip_events.write(TileFlippedEvent {
+            tile: coord,
+            city_entity: record.city,
+            civilization_id: record.civilization_id,
+            previous_civilization_id: owner,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::cities::{TerritoryExpandedEvent, register_new_cities};
+    use crate::game::civilization::create_default_civilizations;
+    use crate::game::map::{TerrainType, TileLookup};
+    use crate::game::test_support::{area, game_app, run};
+    
+    const ROME: HexCoord = HexCoord { q: 0, r: 0 };
+    const ATHENS: HexCoord = HexCoord { q: 5, r: 0 };
+    
+    // Rome (civilization 1) and Athens (civilization 2) on open grassland, both without
+    // traits, with borders grown until they meet
+    fn border_app() -> (App, Entity, Entity) {
+        let mut app = game_app(area(ROME, 9, TerrainType::TemperateGrassland));
+        let mut manager = CivilizationManager::default();
+        for mut civ in create_default_civilizations().into_iter().take(2) {
+            civ.traits.clear();
+            manager.add_civilization(civ);
+        }
+        app.insert_resource(manager)
+            .init_resource::<CulturePressure>()
+            .add_event::<TileFlippedEvent>()
+            .add_systems(Update, culture_pressure_system);
+        let rome = app.world_mut().spawn(City::new("Rome".to_string(), ROME, 1, 1, true)).id();
+        let athens = app.world_mut().spawn(City::new("Athens".to_string(), ATHENS, 2, 1, false)).id();
+        run(&mut app, register_new_cities);
+        run(&mut app, |mut cities: Query<(Entity, &mut City)>, tiles: TileLookup, mut territory: ResMut<TerritoryMap>, mut events: EventWriter<TerritoryExpandedEvent>| {
+            for _ in 0..20 {
+                for (entity, mut city) in &mut cities {
+                    city.culture_stored = city.culture_needed_for_expansion;
+                    city.expand_territory(entity, &tiles, &mut territory, &mut events);
+                }
+            }
+        });
+        (app, rome, athens)
+    }
+    
+    fn territory(app: &App, city: Entity) -> HashSet<HexCoord> {
+        app.world().get::<City>(city).unwrap().territory_tiles.iter().copied().collect()
+    }
+    
+    // Starts `turns` full turns, one frame each
+    fn play_turns(app: &mut App, turns: u32) {
+        for _ in 0..turns {
+            let mut manager = app.world_mut().resource_mut::<CivilizationManager>();
+            manager.turn_number += 1;
+            let civilization_id = manager.current_turn_civ;
+            app.world_mut().send_event(TurnStartedEvent { civilization_id });
+            app.update();
+        }
+    }
+    
+    // A small city alone against a capital stacked with wonders never takes a tile from
+    // it, however long it pushes, while the capital does wear the small city's borders down
+    #[test]
+    fn a_low_culture_city_cannot_flip_a_wonder_capitals_tiles() {
+        let (mut app, rome, athens) = border_app();
+        app.world_mut().get_mut::<City>(rome).unwrap().culture_per_turn = 30.0;
+        app.world_mut().get_mut::<City>(athens).unwrap().culture_per_turn = 2.0;
+        let (rome_before, athens_before) = (territory(&app, rome), territory(&app, athens));
+        let touching = rome_before.iter().any(|coord| coord.neighbors().iter().any(|neighbor| athens_before.contains(neighbor)));
+        assert!(touching, "the borders should meet");
+        
+        // Athens pushes 1 at most, even on a tile next to it: well short of MIN_FLIP_PRESSURE
+        let small = app.world().get::<City>(athens).unwrap().clone();
+        assert_eq!(pressure_on(&small, HexCoord { q: 4, r: 0 }), 1.0);
+        
+        play_turns(&mut app, PRESSURE_FLIP_TURNS * 3);
+        let rome_after = territory(&app, rome);
+        assert!(rome_before.is_subset(&rome_after), "Rome lost {:?}", rome_before.difference(&rome_after).collect::<Vec<_>>());
+        let pressure = app.world().resource::<CulturePressure>();
+        assert!(pressure.tiles.values().all(|record| record.civilization_id == 1));
+        assert!(territory(&app, athens).len() < athens_before.len(), "Rome's culture should take Athens' border tiles");
+    }
+}
@@ -0,0 +1,550 @@
+use std::collections::HashMap;
+use bevy::prelude::*;
+use super::cities::{City, TerritoryMap};
+use super::civilization::CivilizationManager;
+use super::combat_log::{CombatLog, CombatOutcome};
+use super::game_initialization::TurnStartedEvent;
+use super::hex::HexCoord;
+use super::units::Unit;
+use serde::{Deserialize, Serialize};
+
+// Turns after a peace treaty before either side may declare war again
+pub const TRUCE_TURNS: u32 = 10;
+// How long per-turn gold in a deal keeps flowing
+pub const DEAL_PAYMENT_TURNS: u32 = 20;
+// Enemy military units this close to a city put it under threat
+const THREAT_DISTANCE: i32 = 3;
+// Units and cities this close make contact. Adjacent until fog of war gives units a sight range.
+const CONTACT_DISTANCE: i32 = 1;
+
+// War weariness, which each civilization builds up against each opponent
+pub const MAX_WAR_WEARINESS: f32 = 100.0;
+const WEARINESS_PER_WAR_TURN: f32 = 1.0;
+const WEARINESS_PER_UNIT_LOST: f32 = 3.0;
+const WEARINESS_PER_CITY_LOST: f32 = 10.0;
+const WEARINESS_PER_HOME_BATTLE: f32 = 2.0;     // Any battle inside the civilization's own borders
+const WEARINESS_PEACE_DECAY: f32 = 3.0;         // Shed each turn while not at war
+const MAX_WEARINESS_COMBAT_PENALTY: f32 = 0.1;  // Share of unit strength lost at MAX_WAR_WEARINESS
+const PEACE_VALUE_PER_WEARINESS: f32 = 0.6;     // What peace is worth to the AI per point against the other side
+
+/// How two civilizations stand with each other
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Relation {
+    Peace,
+    War(WarRecord),
+    Truce { until_turn: u32 }, // Peace that can't be broken before this turn
+}
+
+/// Each side's strength when a war began, so a losing side can be recognized later
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WarRecord {
+    pub started_turn: u32,
+    pub strength_at_start: [(u32, WarStrength); 2],
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub struct WarStrength {
+    pub cities: usize,
+    pub military: f32,
+}
+
+impl WarStrength {
+    // Cities held and total unit combat strength of one civilization
+    pub fn measure<'a>(civ_id: u32, units: impl Iterator<Item = &'a Unit>, cities: impl Iterator<Item = &'a City>) -> Self {
+        WarStrength {
+            cities: cities.filter(|city| city.civilization_id == civ_id).count(),
+            military: units
+                .filter(|unit| unit.civilization_id == civ_id)
+                .map(|unit| unit.get_combat_strength(true) as f32)
+                .sum(),
+        }
+    }
+}
+
+impl WarRecord {
+    pub fn strength_at_start(&self, civ_id: u32) -> Option<WarStrength> {
+        self.strength_at_start
+            .iter()
+            .find(|(id, _)| *id == civ_id)
+            .map(|(_, strength)| *strength)
+    }
+    
+    // A side is losing once it has fewer cities or less military than when the war began
+    pub fn is_losing(&self, civ_id: u32, current: WarStrength) -> bool {
+        self.strength_at_start(civ_id)
+            .is_some_and(|start| current.cities < start.cities || current.military < start.military)
+    }
+}
+
+/// Two civilizations met for the first time
+#[derive(Event)]
+pub struct FirstContactEvent {
+    pub civilization_id: u32,
+    pub other_id: u32,
+}
+
+/// One civilization declared war on another
+#[derive(Event)]
+pub struct WarDeclaredEvent {
+    pub civilization_id: u32,
+    pub other_id: u32,
+}
+
+/// Gold one side of a deal hands over
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum DealGold {
+    #[default]
+    None,
+    Lump(f32),
+    PerTurn(f32), // Paid at the start of each of the payer's turns for DEAL_PAYMENT_TURNS
+}
+
+impl DealGold {
+    // What the gold is worth up front; future payments are discounted a little
+    fn value(self) -> f32 {
+        match self {
+            DealGold::None => 0.0,
+            DealGold::Lump(amount) => amount,
+            DealGold::PerTurn(amount) => amount * DEAL_PAYMENT_TURNS as f32 * 0.8,
+        }
+    }
+    
+    pub fn describe(self) -> String {
+        match self {
+            DealGold::None => "nothing".to_string(),
+            DealGold::Lump(amount) => format!("{:.0} gold", amount),
+            DealGold::PerTurn(amount) => format!("{:.0} gold/turn for {} turns", amount, DEAL_PAYMENT_TURNS),
+        }
+    }
+}
+
+/// Terms one civilization offers another: optionally peace, plus gold in either direction
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DealProposal {
+    pub proposer: u32,
+    pub recipient: u32,
+    pub peace: bool,
+    pub proposer_gold: DealGold,  // Paid by the proposer
+    pub recipient_gold: DealGold, // Paid by the recipient
+}
+
+/// What the recipient knows when weighing a deal
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DealContext {
+    pub at_war: bool,
+    pub own_military: f32,
+    pub other_military: f32,
+    pub losing: bool,             // Fewer cities or less military than when the war began
+    pub threatened_cities: usize, // Own cities with enemy military nearby
+    pub war_weariness: f32,       // Against the other side
+    pub gold: f32,
+}
+
+impl DealContext {
+    // The situation as `civ_id` sees it when dealing with `other_id`
+    pub fn gather(civ_id: u32, other_id: u32, unit_query: &Query<&Unit>, city_query: &Query<&City>, civ_manager: &CivilizationManager) -> Self {
+        let own = WarStrength::measure(civ_id, unit_query.iter(), city_query.iter());
+        let other = WarStrength::measure(other_id, unit_query.iter(), city_query.iter());
+        let relation = civ_manager.diplomacy.relation(civ_id, other_id);
+        let losing = matches!(relation, Relation::War(record) if record.is_losing(civ_id, own));
+        
+        let threatened_cities = city_query
+            .iter()
+            .filter(|city| city.civilization_id == civ_id)
+            .filter(|city| {
+                unit_query.iter().any(|unit| {
+                    unit.civilization_id == other_id && unit.can_attack && unit.hex_coord.distance(city.hex_coord) <= THREAT_DISTANCE
+                })
+            })
+            .count();
+        
+        DealContext {
+            at_war: matches!(relation, Relation::War(_)),
+            own_military: own.military,
+            other_military: other.military,
+            losing,
+            threatened_cities,
+            war_weariness: civ_manager.diplomacy.war_weariness(civ_id, other_id),
+            gold: civ_manager.get_civilization(civ_id).map_or(0.0, |civ| civ.gold),
+        }
+    }
+}
+
+// How much the recipient gains from a deal; it accepts anything worth zero or more.
+// A stronger side wants to keep fighting, while losses, threatened cities and war
+// weariness make peace worth more.
+pub fn evaluate_deal(deal: &DealProposal, context: &DealContext) -> f32 {
+    // Can't hand over a lump it doesn't have
+    if let DealGold::Lump(amount) = deal.recipient_gold && amount > context.gold {
+        return f32::NEG_INFINITY;
+    }
+    
+    let mut value = deal.proposer_gold.value() - deal.recipient_gold.value();
+    if deal.peace && context.at_war {
+        let strength_ratio = context.own_military / context.other_military.max(1.0);
+        value -= (strength_ratio - 1.0) * 50.0;
+        if context.losing {
+            value += 40.0;
+        }
+        value += context.threatened_cities as f32 * 15.0;
+        value += context.war_weariness * PEACE_VALUE_PER_WEARINESS;
+    }
+    value
+}
+
+/// Per-turn gold one civilization owes another under a deal
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GoldPayment {
+    pub payer: u32,
+    pub payee: u32,
+    pub amount: f32,
+    pub turns_left: u32,
+}
+
+/// Pairwise relations between civilizations. Pairs that never met are at peace.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct DiplomacyState {
+    relations: HashMap<(u32, u32), Relation>,
+    pub payments: Vec<GoldPayment>,
+    // How tired each civilization is of fighting each opponent, keyed (civ, opponent).
+    // Unlike relations this isn't symmetric; pairs with none are left out.
+    weariness: HashMap<(u32, u32), f32>,
+}
+
+impl DiplomacyState {
+    // Relations are symmetric, so each pair is stored once, lowest id first
+    fn key(civ1: u32, civ2: u32) -> (u32, u32) {
+        (civ1.min(civ2), civ1.max(civ2))
+    }
+    
+    // Gold `civ_id` receives and pays each turn under its deals
+    pub fn deal_gold(&self, civ_id: u32) -> (f32, f32) {
+        let received = self.payments.iter().filter(|payment| payment.payee == civ_id).map(|payment| payment.amount).sum();
+        let paid = self.payments.iter().filter(|payment| payment.payer == civ_id).map(|payment| payment.amount).sum();
+        (received, paid)
+    }
+    
+    pub fn relation(&self, civ1: u32, civ2: u32) -> Relation {
+        self.relations.get(&Self::key(civ1, civ2)).copied().unwrap_or(Relation::Peace)
+    }
+    
+    pub fn at_war(&self, civ1: u32, civ2: u32) -> bool {
+        matches!(self.relation(civ1, civ2), Relation::War(_))
+    }
+    
+    // Every war being fought, as (the side that declared it, the other side)
+    pub fn wars(&self) -> Vec<(u32, u32)> {
+        self.relations
+            .values()
+            .filter_map(|relation| match relation {
+                Relation::War(record) => Some((record.strength_at_start[0].0, record.strength_at_start[1].0)),
+                _ => None,
+            })
+            .collect()
+    }
+    
+    // War can be declared from peace, or from a truce once it has run out
+    pub fn can_declare_war(&self, civ1: u32, civ2: u32, turn: u32) -> bool {
+        match self.relation(civ1, civ2) {
+            Relation::Peace => civ1 != civ2,
+            Relation::Truce { until_turn } => turn >= until_turn,
+            Relation::War(_) => false,
+        }
+    }
+    
+    pub fn declare_war(&mut self, attacker: (u32, WarStrength), defender: (u32, WarStrength), turn: u32) -> bool {
+        if !self.can_declare_war(attacker.0, defender.0, turn) {
+            return false;
+        }
+        let record = WarRecord {
+            started_turn: turn,
+            strength_at_start: [attacker, defender],
+        };
+        self.relations.insert(Self::key(attacker.0, defender.0), Relation::War(record));
+        // War voids whatever either side still owed the other
+        let pair = Self::key(attacker.0, defender.0);
+        self.payments.retain(|payment| Self::key(payment.payer, payment.payee) != pair);
+        true
+    }
+    
+    pub fn make_peace(&mut self, civ1: u32, civ2: u32, turn: u32) {
+        self.relations.insert(Self::key(civ1, civ2), Relation::Truce { until_turn: turn + TRUCE_TURNS });
+    }
+    
+    pub fn war_weariness(&self, civ_id: u32, opponent: u32) -> f32 {
+        self.weariness.get(&(civ_id, opponent)).copied().unwrap_or(0.0)
+    }
+    
+    // Weariness from all of a civilization's wars together, which is what its people feel
+    pub fn total_war_weariness(&self, civ_id: u32) -> f32 {
+        self.weariness
+            .iter()
+            .filter(|((civ, _), _)| *civ == civ_id)
+            .map(|(_, weariness)| *weariness)
+            .sum::<f32>()
+            .min(MAX_WAR_WEARINESS)
+    }
+    
+    // Negative amounts let weariness fade; it never drops below zero or passes the cap
+    pub fn add_war_weariness(&mut self, civ_id: u32, opponent: u32, amount: f32) {
+        let weariness = (self.war_weariness(civ_id, opponent) + amount).clamp(0.0, MAX_WAR_WEARINESS);
+        if weariness > 0.0 {
+            self.weariness.insert((civ_id, opponent), weariness);
+        } else {
+            self.weariness.remove(&(civ_id, opponent));
+        }
+    }
+}
+
+// Multiplier on a civilization's unit strength from its total war weariness
+pub fn war_weariness_combat_modifier(weariness: f32) -> f32 {
+    1.0 - weariness / MAX_WAR_WEARINESS * MAX_WEARINESS_COMBAT_PENALTY
+}
+
+// Carries out an accepted deal: peace starts a truce, lumps change hands now and
+// per-turn gold is paid by deal_payment_system
+pub fn apply_deal(deal: &DealProposal, civ_manager: &mut CivilizationManager) {
+    let turn = civ_manager.turn_number;
+    if deal.peace && civ_manager.diplomacy.at_war(deal.proposer, deal.recipient) {
+        civ_manager.diplomacy.make_peace(deal.proposer, deal.recipient, turn);
+    }
+    
+    for (payer, payee, gold) in [
+        (deal.proposer, deal.recipient, deal.proposer_gold),
+        (deal.recipient, deal.proposer, deal.recipient_gold),
+    ] {
+        match gold {
+            DealGold::None => {}
+            DealGold::Lump(amount) => civ_manager.transfer_gold(payer, payee, amount),
+            DealGold::PerTurn(amount) => civ_manager.diplomacy.payments.push(GoldPayment {
+                payer,
+                payee,
+                amount,
+                turns_left: DEAL_PAYMENT_TURNS,
+            }),
+        }
+    }
+}
+
+// Pays out per-turn deal gold when the paying civilization's turn starts
+pub fn deal_payment_system(
+    mut turn_events: EventReader<TurnStartedEvent>,
+    mut civ_manager: ResMut<CivilizationManager>,
+) {
+    for event in turn_events.read() {
+        let due: Vec<GoldPayment> = civ_manager.diplomacy.payments
+            .iter()
+            .filter(|payment| payment.payer == event.civilization_id)
+            .copied()
+            .collect();
+        if due.is_empty() {
+            continue;
+        }
+        
+        for payment in due {
+            civ_manager.transfer_gold(payment.payer, payment.payee, payment.amount);
+        }
+        for payment in civ_manager.diplomacy.payments.iter_mut().filter(|payment| payment.payer == event.civilization_id) {
+            payment.turns_left -= 1;
+        }
+        civ_manager.diplomacy.payments.retain(|payment| payment.turns_left > 0);
+    }
+}
+
+// Wars wear on the civilizations fighting them: each turn at war, each unit or city lost
+// and each battle inside their own borders adds weariness against that opponent, and
+// it fades again once they're no longer at war. Battles are read from the combat log.
+pub fn war_weariness_system(
+    mut turn_events: EventReader<TurnStartedEvent>,
+    combat_log: Res<CombatLog>,
+    territory: Res<TerritoryMap>,
+    mut civ_manager: ResMut<CivilizationManager>,
+    mut log_seen: Local<usize>,
+) {
+    let diplomacy = &mut civ_manager.diplomacy;
+    for entry in combat_log.recorded_since(*log_seen) {
+        let (attacker, Some(defender)) = (entry.attacker_civ, entry.defender_civ) else { continue };
+        if !diplomacy.at_war(attacker, defender) {
+            continue;
+        }
+        match entry.outcome {
+            CombatOutcome::AttackerDestroyed => diplomacy.add_war_weariness(attacker, defender, WEARINESS_PER_UNIT_LOST),
+            CombatOutcome::DefenderDestroyed => diplomacy.add_war_weariness(defender, attacker, WEARINESS_PER_UNIT_LOST),
+            CombatOutcome::CityCaptured => diplomacy.add_war_weariness(defender, attacker, WEARINESS_PER_CITY_LOST),
+            _ => {}
+        }
+        match territory.owner(entry.location).map(|owner| owner.civilization_id) {
+            Some(home) if home == attacker => diplomacy.add_war_weariness(attacker, defender, WEARINESS_PER_HOME_BATTLE),
+            Some(home) if home == defender => diplomacy.add_war_weariness(defender, attacker, WEARINESS_PER_HOME_BATTLE),
+            _ => {}
+        }
+    }
+    *log_seen = combat_log.recorded();
+    
+    for event in turn_events.read() {
+        let civ_id = event.civilization_id;
+        let opponents: Vec<u32> = civ_manager.civilizations.keys().copied().filter(|id| *id != civ_id).collect();
+        for opponent in opponents {
+            let change = if civ_manager.diplomacy.at_war(civ_id, opponent) { WEARINESS_PER_WAR_TURN } else { -WEARINESS_PEACE_DECAY };
+            civ_manager.diplomacy.add_war_weariness(civ_id, opponent, change);
+        }
+    }
+}
+
+// Civilizations meet when any of their units or cities come within CONTACT_DISTANCE
+pub fn first_contact_system(
+    mut civ_manager: ResMut<CivilizationManager>,
+    unit_query: Query<&Unit>,
+    city_query: Query<&City>,
+    mut contact_events: EventWriter<FirstContactEvent>,
+) {
+    let presence: Vec<(u32, HexCoord)> = unit_query
+        .iter()
+        .map(|unit| (unit.civilization_id, unit.hex_coord))
+        .chain(city_query.iter().map(|city| (city.civilization_id, city.hex_coord)))
+        .filter(|(civ_id, _)| civ_manager.get_civilization(*civ_id).is_some())
+        .collect();
+    
+    let mut contacts = Vec::new();
+    for (index, &(civ1, coord1)) in presence.iter().enumerate() {
+        for &(civ2, coord2) in &presence[index + 1..] {
+            if civ1 != civ2 && coord1.distance(coord2) <= CONTACT_DISTANCE && !contacts.contains(&(civ1.min(civ2), civ1.max(civ2))) {
+                contacts.push((civ1.min(civ2), civ1.max(civ2)));
+            }
+        }
+    }
+    for (civ1, civ2) in contacts {
+        if civ_manager.meet(civ1, civ2) {
+            println!("{} and {} have made contact", civ_manager.civ_name(civ1), civ_manager.civ_name(civ2));
+            contact_events.write(FirstContactEvent { civilization_id: civ1, other_id: civ2 });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::cities::UnitType;
+    use crate::game::civilization::{UNKNOWN_PLAYER, create_default_civilizations};
+    use crate::game::test_support::{game_app, run};
+    
+    // Civilization 2 weighing an offer from civilization 1
+    fn offer(peace: bool, proposer_gold: DealGold, recipient_gold: DealGold) -> DealProposal {
+        DealProposal { proposer: 1, recipient: 2, peace, proposer_gold, recipient_gold }
+    }
+    
+    // At war with an even opponent, holding its own, nothing threatened and 100 gold
+    fn even_war() -> DealContext {
+        DealContext {
+            at_war: true,
+            own_military: 30.0,
+            other_military: 30.0,
+            losing: false,
+            threatened_cities: 0,
+            war_weariness: 0.0,
+            gold: 100.0,
+        }
+    }
+    
+    // A side three times as strong as its enemy won't pay for peace, or even take it free
+    #[test]
+    fn a_much_stronger_side_refuses_peace() {
+        let dominant = DealContext { own_military: 90.0, ..even_war() };
+        assert!(evaluate_deal(&offer(true, DealGold::None, DealGold::None), &dominant) < 0.0);
+        assert!(evaluate_deal(&offer(true, DealGold::None, DealGold::Lump(50.0)), &dominant) < 0.0);
+        assert!(evaluate_deal(&offer(true, DealGold::None, DealGold::PerTurn(2.0)), &dominant) < 0.0);
+        // It can be bought off, at a price
+        assert!(evaluate_deal(&offer(true, DealGold::Lump(100.0), DealGold::None), &dominant) >= 0.0);
+    }
+    
+    // Losing, threatened and war-weary, the weaker side takes peace and pays for it
+    #[test]
+    fn a_beaten_side_pays_for_peace() {
+        let beaten = DealContext {
+            own_military: 10.0,
+            losing: true,
+            threatened_cities: 2,
+            war_weariness: 50.0,
+            ..even_war()
+        };
+        assert!(evaluate_deal(&offer(true, DealGold::None, DealGold::None), &beaten) > 0.0);
+        assert!(evaluate_deal(&offer(true, DealGold::None, DealGold::Lump(50.0)), &beaten) > 0.0);
+        // Each hardship adds to what peace is worth
+        let base = evaluate_deal(&offer(true, DealGold::None, DealGold::None), &DealContext { own_military: 10.0, ..even_war() });
+        for hardship in [
+            DealContext { own_military: 10.0, losing: true, ..even_war() },
+            DealContext { own_military: 10.0, threatened_cities: 1, ..even_war() },
+            DealContext { own_military: 10.0, war_weariness: 10.0, ..even_war() },
+        ] {
+            assert!(evaluate_deal(&offer(true, DealGold::None, DealGold::None), &hardship) > base, "{:?}", hardship);
+        }
+    }
+    
+    // Between even sides peace alone is neutral, so the gold decides
+    #[test]
+    fn even_sides_weigh_only_the_gold() {
+        let even = even_war();
+        assert_eq!(evaluate_deal(&offer(true, DealGold::None, DealGold::None), &even), 0.0);
+        assert_eq!(evaluate_deal(&offer(true, DealGold::Lump(30.0), DealGold::None), &even), 30.0);
+        assert_eq!(evaluate_deal(&offer(true, DealGold::None, DealGold::Lump(30.0)), &even), -30.0);
+        // Gold spread over turns is worth a little less than the same sum now
+        let per_turn = evaluate_deal(&offer(false, DealGold::PerTurn(5.0), DealGold::None), &even);
+        assert!(per_turn > 0.0 && per_turn < 5.0 * DEAL_PAYMENT_TURNS as f32);
+    }
+    
+    // Peace offered to a civilization that isn't at war is worth nothing by itself
+    #[test]
+    fn peace_is_worthless_when_not_at_war() {
+        let calm = DealContext { at_war: false, own_military: 10.0, threatened_cities: 3, ..even_war() };
+        assert_eq!(evaluate_deal(&offer(true, DealGold::None, DealGold::None), &calm), 0.0);
+        assert_eq!(evaluate_deal(&offer(false, DealGold::Lump(20.0), DealGold::None), &calm), 20.0);
+    }
+    
+    // No deal can ask for a lump of gold the recipient doesn't have
+    #[test]
+    fn a_lump_beyond_the_treasury_is_refused() {
+        let beaten = DealContext { own_military: 1.0, losing: true, threatened_cities: 5, ..even_war() };
+        assert_eq!(evaluate_deal(&offer(true, DealGold::None, DealGold::Lump(101.0)), &beaten), f32::NEG_INFINITY);
+        assert!(evaluate_deal(&offer(true, DealGold::None, DealGold::Lump(100.0)), &beaten) > 0.0);
+    }
+    
+    // Contact is mutual: whoever stumbles on whom, both sides learn of each other at once,
+    // it's announced once, and civilizations out of reach stay strangers
+    #[test]
+    fn first_contact_is_recorded_on_both_sides() {
+        let mut app = game_app(Vec::new());
+        app.add_event::<FirstContactEvent>();
+        let mut manager = CivilizationManager::default();
+        for civ in create_default_civilizations().into_iter().take(3) {
+            manager.add_civilization(civ);
+        }
+        app.insert_resource(manager);
+        app.world_mut().spawn(Unit::new(UnitType::Warrior, 1, HexCoord { q: 0, r: 0 }));
+        app.world_mut().spawn(Unit::new(UnitType::Scout, 2, HexCoord { q: 1, r: 0 }));
+        let stranger = app.world_mut().spawn(Unit::new(UnitType::Scout, 3, HexCoord { q: 4, r: 0 })).id();
+        
+        let contacts = |app: &mut App| -> Vec<(u32, u32)> {
+            run(app, first_contact_system);
+            let mut events = app.world_mut().resource_mut::<Events<FirstContactEvent>>();
+            events.drain().map(|event| (event.civilization_id, event.other_id)).collect()
+        };
+        assert_eq!(contacts(&mut app), vec![(1, 2)]);
+        assert_eq!(contacts(&mut app), vec![]);
+        
+        let manager = app.world().resource::<CivilizationManager>();
+        let met = |civ_id: u32, other_id: u32| manager.get_civilization(civ_id).unwrap().has_met(other_id);
+        assert!(met(1, 2) && met(2, 1));
+        assert!(!met(1, 3) && !met(3, 1) && !met(2, 3) && !met(3, 2));
+        assert_eq!(manager.known_civ_name(2), manager.civ_name(2));
+        assert_eq!(manager.known_civ_name(3), UNKNOWN_PLAYER);
+        
+        // Two other civilizations meeting doesn't introduce the player to either
+        app.world_mut().get_mut::<Unit>(stranger).unwrap().hex_coord = HexCoord { q: 2, r: 0 };
+        assert_eq!(contacts(&mut app), vec![(2, 3)]);
+        let manager = app.world().resource::<CivilizationManager>();
+        let met = |civ_id: u32, other_id: u32| manager.get_civilization(civ_id).unwrap().has_met(other_id);
+        assert!(met(2, 3) && met(3, 2));
+        assert!(!met(1, 3) && !met(3, 1));
+        assert_eq!(manager.known_civ_name(3), UNKNOWN_PLAYER);
+    }
+}
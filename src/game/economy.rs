@@ -0,0 +1,192 @@
+use bevy::prelude::*;
+use super::cities::City;
+use super::civilization::{Civilization, CivilizationManager};
+use super::game_initialization::TurnStartedEvent;
+use super::units::Unit;
+
+// Unit upkeep each city pays for out of its own pocket
+pub const FREE_UNIT_UPKEEP_PER_CITY: f32 = 2.0;
+
+/// A civilization's gold per turn, split the way the status panel shows it
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Finances {
+    pub income: f32,
+    pub building_upkeep: f32,
+    pub unit_upkeep: f32, // After the free support from cities
+}
+
+impl Finances {
+    pub fn expenses(&self) -> f32 {
+        self.building_upkeep + self.unit_upkeep
+    }
+    
+    pub fn net(&self) -> f32 {
+        self.income - self.expenses()
+    }
+}
+
+/// A step the treasury took to cover a deficit, for the notification panel
+#[derive(Event)]
+pub struct DeficitEvent {
+    pub civilization_id: u32,
+    pub message: String,
+}
+
+// What `civ` earns and pays each turn
+pub fn civ_finances<'a>(
+    civ: &Civilization,
+    cities: impl Iterator<Item = &'a City>,
+    units: impl Iterator<Item = &'a Unit>,
+) -> Finances {
+    let mut finances = Finances::default();
+    let mut city_count = 0;
+    for city in cities.filter(|city| city.civilization_id == civ.id) {
+        finances.income += city.gold_per_turn;
+        finances.building_upkeep += city.maintenance_per_turn;
+        city_count += 1;
+    }
+    let unit_upkeep: f32 = units
+        .filter(|unit| unit.civilization_id == civ.id)
+        .map(|unit| civ.unit_maintenance(unit.unit_type))
+        .sum();
+    finances.unit_upkeep = (unit_upkeep - city_count as f32 * FREE_UNIT_UPKEEP_PER_CITY).max(0.0);
+    finances
+}
+
+// Charges unit upkeep at the start of a civilization's turn (cities have already paid
+// for their buildings). If that empties the treasury, this turn's research is diverted
+// to cover it, and then the costliest units are disbanded one by one until it's paid.
+pub fn unit_upkeep_system(
+    mut commands: Commands,
+    mut civ_manager: ResMut<CivilizationManager>,
+    unit_query: Query<(Entity, &Unit)>,
+    city_query: Query<&City>,
+    mut turn_events: EventReader<TurnStartedEvent>,
+    mut deficit_events: EventWriter<DeficitEvent>,
+) {
+    for event in turn_events.read() {
+        let civ_id = event.civilization_id;
+        let Some(civ) = civ_manager.get_civilization_mut(civ_id) else { continue };
+        let finances = civ_finances(civ, city_query.iter(), unit_query.iter().map(|(_, unit)| unit));
+        civ.gold -= finances.unit_upkeep;
+        if civ.gold >= 0.0 {
+            continue;
+        }
+        
+        let mut announce = |message: String| {
+            println!("{}", message);
+            deficit_events.write(DeficitEvent { civilization_id: civ_id, message });
+        };
+        
+        // First, divert this turn's research into the treasury
+        let science: f32 = city_query
+            .iter()
+            .filter(|city| city.civilization_id == civ_id)
+            .map(|city| city.science_per_turn)
+            .sum();
+        let diverted = (-civ.gold).min(science).min(civ.science_points).max(0.0);
+        if diverted > 0.0 {
+            civ.science_points -= diverted;
+            civ.gold += diverted;
+            announce(format!("Deficit: research cut by {:.0}% to pay upkeep", diverted / science * 100.0));
+        }
+        
+        // Then disband units, costliest upkeep (then costliest to build) first. Each one
+        // refunds this turn's upkeep, but never more than was actually charged.
+        let mut units: Vec<(Entity, &Unit)> = unit_query
+            .iter()
+            .filter(|(_, unit)| unit.civilization_id == civ_id && civ.unit_maintenance(unit.unit_type) > 0.0)
+            .collect();
+        units.sort_by(|(_, a), (_, b)| {
+            civ.unit_maintenance(b.unit_type).total_cmp(&civ.unit_maintenance(a.unit_type))
+                .then(b.production_cost.cmp(&a.production_cost))
+        });
+        let mut charged = finances.unit_upkeep;
+        for (entity, unit) in units {
+            if civ.gold >= 0.0 || charged <= 0.0 {
+                break;
+            }
+            let refund = civ.unit_maintenance(unit.unit_type).min(charged);
+            charged -= refund;
+            civ.gold += refund;
+            civ.remove_unit(entity);
+            commands.entity(entity).despawn();
+            announce(format!("Deficit: a {} was disbanded, the treasury can't pay its upkeep", unit.unit_type.get_name()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::cities::UnitType;
+    use crate::game::civilization::create_default_civilizations;
+    use crate::game::hex::HexCoord;
+    use crate::game::test_support::game_app;
+    
+    const ARMY: [UnitType; 5] = [UnitType::Scout, UnitType::Warrior, UnitType::Catapult, UnitType::Spearman, UnitType::Trireme];
+    
+    // A broke, traitless civilization 1 with one city making `science` a turn, that much
+    // research under way, and ARMY to pay for: 5.5 gold of upkeep, 3.5 after the city's support
+    fn broke_app(science: f32) -> App {
+        let mut app = game_app(Vec::new());
+        app.add_event::<DeficitEvent>().add_systems(Update, unit_upkeep_system);
+        let mut civ = create_default_civilizations().remove(0);
+        civ.traits.clear();
+        civ.gold = 0.0;
+        civ.science_points = science;
+        let mut manager = CivilizationManager::default();
+        manager.add_civilization(civ);
+        
+        let mut city = City::new("Rome".to_string(), HexCoord::new(0, 0), 1, 1, true);
+        city.science_per_turn = science;
+        app.world_mut().spawn(city);
+        for unit_type in ARMY {
+            let entity = app.world_mut().spawn(Unit::new(unit_type, 1, HexCoord::new(0, 0))).id();
+            manager.get_civilization_mut(1).unwrap().add_unit(entity);
+        }
+        app.insert_resource(manager);
+        app
+    }
+    
+    // Starts civilization 1's turn and returns the deficit messages
+    fn start_turn(app: &mut App) -> Vec<String> {
+        app.world_mut().send_event(TurnStartedEvent { civilization_id: 1 });
+        app.update();
+        app.world_mut().resource_mut::<Events<DeficitEvent>>().drain().map(|event| event.message).collect()
+    }
+    
+    fn survivors(app: &mut App) -> Vec<UnitType> {
+        let mut units: Vec<UnitType> = app.world_mut().query::<&Unit>().iter(app.world()).map(|unit| unit.unit_type).collect();
+        units.sort_by_key(|unit_type| ARMY.iter().position(|army| army == unit_type));
+        units
+    }
+    
+    // Research is cut first; only what it can't cover costs units, the costliest to keep
+    // (then to build) first, and no more of them than it takes to balance the books
+    #[test]
+    fn deficit_cuts_research_then_disbands_the_costliest_units() {
+        let mut app = broke_app(1.0);
+        assert_eq!(start_turn(&mut app), vec![
+            "Deficit: research cut by 100% to pay upkeep".to_string(),
+            "Deficit: a Trireme was disbanded, the treasury can't pay its upkeep".to_string(),
+            "Deficit: a Catapult was disbanded, the treasury can't pay its upkeep".to_string(),
+        ]);
+        assert_eq!(survivors(&mut app), vec![UnitType::Scout, UnitType::Warrior, UnitType::Spearman]);
+        let civ = app.world().resource::<CivilizationManager>().get_civilization(1).unwrap();
+        assert_eq!(civ.science_points, 0.0);
+        assert_eq!(civ.gold, 0.5);
+        assert_eq!(civ.units.len(), 3);
+    }
+    
+    // Research that covers the whole bill spares every unit
+    #[test]
+    fn research_covering_the_deficit_saves_the_units() {
+        let mut app = broke_app(5.0);
+        assert_eq!(start_turn(&mut app), vec!["Deficit: research cut by 70% to pay upkeep".to_string()]);
+        assert_eq!(survivors(&mut app), ARMY.to_vec());
+        let civ = app.world().resource::<CivilizationManager>().get_civilization(1).unwrap();
+        assert_eq!(civ.science_points, 1.5);
+        assert_eq!(civ.gold, 0.0);
+    }
+}
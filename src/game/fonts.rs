@@ -0,0 +1,47 @@
+use bevy::prelude::*;
+
+// Built into the binary, so every glyph the game draws is there on any machine. The
+// text font is monospaced to keep the reports' columns lined up; the symbol font has
+// the wider coverage the map markers need.
+const TEXT_FONT: &[u8] = include_bytes!("../../assets/fonts/DejaVuSansMono.ttf");
+const SYMBOL_FONT: &[u8] = include_bytes!("../../assets/fonts/DejaVuSans.ttf");
+
+/// The bundled symbol font. The text font takes the place of Bevy's default, so panels
+/// get it without asking.
+#[derive(Resource, Clone, Default)]
+pub struct GameFonts {
+    pub symbols: Handle<Font>,
+}
+
+impl GameFonts {
+    // A TextFont for map markers and other symbols
+    pub fn symbol(&self, font_size: f32) -> TextFont {
+        TextFont {
+            font: self.symbols.clone(),
+            font_size,
+            ..default()
+        }
+    }
+}
+
+fn parse_font(name: &str, bytes: &[u8]) -> Option<Font> {
+    match Font::try_from_bytes(bytes.to_vec()) {
+        Ok(font) => Some(font),
+        Err(error) => {
+            println!("Warning: bundled font {} is unreadable: {}", name, error);
+            None
+        }
+    }
+}
+
+// Installs the bundled fonts; a font that fails to parse leaves Bevy's in its place
+pub fn load_fonts(mut commands: Commands, mut fonts: ResMut<Assets<Font>>) {
+    if let Some(text) = parse_font("DejaVuSansMono.ttf", TEXT_FONT) {
+        fonts.insert(&Handle::<Font>::default(), text);
+    }
+    let symbols = match parse_font("DejaVuSans.ttf", SYMBOL_FONT) {
+        Some(font) => fonts.add(font),
+        None => Handle::default(),
+    };
+    commands.insert_resource(GameFonts { symbols });
+}
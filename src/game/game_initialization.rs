@@ -1,24 +1,286 @@
 use bevy::prelude::*;
 use super::hex::HexCoord;
-use super::map::MapTile;
-use super::world_gen::BiomeType;
-use super::civilization::{CivilizationManager, create_default_civilizations};
-use super::cities::{City, UnitType};
-use super::units::{Unit, spawn_unit, spawn_city};
+use super::map::{MapTile, TileIndex, TileLookup, WorldInfo, WorldOrigin};
+use super::world_gen::{BiomeType, WorldType};
+use rand::seq::SliceRandom;
+use super::civilization::{BARBARIAN_CIV_ID, Civilization, CivilizationManager, Difficulty, GameSpeed, create_default_civilizations, generate_civilizations};
+use super::cities::{City, CitySelection, TerritoryMap, UnitType, WonderRegistry};
+use super::units::{MoveHistory, Unit, UnitSelection, spawn_unit, spawn_city};
+use super::rules::DEFAULT_RULES_DIR;
+use super::ai::{AI_TURN_DELAY, AiTurnTimer};
+use super::camera_zoom::CameraTween;
+use super::city_founding::CityFoundingState;
+use super::combat::CombatState;
+use super::combat_log::CombatLog;
+use super::culture::CulturePressure;
+use super::input::{InputMode, TextEntry};
+use super::input_map::{Action, InputMap};
+use super::rng::GameRng;
+use super::save::LoadMenu;
+use super::stable_id::StableIds;
+use super::statistics::StatsHistory;
+use super::replay::ReplayLog;
+use super::victory::GameResult;
+use super::notifications::{GameNotification, NotificationCategory};
+use std::path::PathBuf;
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
 
-#[derive(Resource)]
+#[derive(Resource, Clone, Serialize, Deserialize)]
 pub struct GameState {
     pub is_initialized: bool,
     pub game_turn: u32,
     pub current_phase: GamePhase,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+/// Which screen the app is on. Gameplay only runs InGame; Loading covers world
+/// generation (or reading a save) and setting up the civilizations.
+#[derive(States, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum AppState {
+    #[default]
+    MainMenu,
+    Setup,
+    Loading,
+    InGame,
+    Paused,
+    Handover,    // Hotseat: the screen is hidden until the next human takes over
+    Replay,      // Watching the finished game play back, from the results screen
+    GameOver,
+}
+
+/// Present while a game exists, from Loading until the player leaves for the menus.
+/// The map and the in-game UI are spawned on entering it and torn down on leaving.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct InSession;
+
+impl ComputedStates for InSession {
+    type SourceStates = AppState;
+    
+    fn compute(state: AppState) -> Option<Self> {
+        match state {
+            AppState::Loading | AppState::InGame | AppState::Paused | AppState::Handover | AppState::GameOver | AppState::Replay => Some(InSession),
+            AppState::MainMenu | AppState::Setup => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub enum GamePhase {
     Initialization,
     PlayerTurn,
     AITurn(u32), // Civilization ID
+    BarbarianTurn, // Barbarian units move and camps spawn raiders, before the first civilization
     EndTurn,
+    GameOver,    // Someone has won; only the results screen responds
+}
+
+/// Sent once when a civilization's turn begins. Per-turn upkeep (city growth and
+/// production, unit movement refresh) runs off this rather than every frame.
+#[derive(Event)]
+pub struct TurnStartedEvent {
+    pub civilization_id: u32,
+}
+
+/// Gameplay rule toggles
+#[derive(Resource)]
+pub struct GameSettings {
+    pub impassable_peaks: bool, // false restores the old rule where every land tile is enterable
+    pub keyboard_unit_movement: bool, // Numpad / Alt+letters step the selected unit
+    pub log_city_events: bool, // Echo city growth, borders, production and founding to the console
+    pub turn_limit: u32, // The game ends in a score victory once this turn is over; 0 means no limit
+    pub difficulty: Difficulty,
+    pub speed: GameSpeed, // Scales production, research, growth and border costs
+    pub barbarians: bool, // Camps spawn raiders that attack everyone
+    pub player_civ: Option<String>, // Name (or part of one) of the civilization to play; None keeps the default
+    pub opponents: usize, // How many AI civilizations join, MIN_OPPONENTS..=MAX_OPPONENTS
+    pub rules_dir: PathBuf, // Where units.ron, buildings.ron and techs.ron are read from
+    pub seed: Option<u64>, // Seeds the combat rolls; None picks one at random
+    pub world_seed: Option<u64>, // Seeds world generation; None picks one at random
+    pub autosave_interval: u32, // Autosave at the start of every this many player turns; 0 turns it off
+    pub world_type: WorldType, // Shape of the world new games generate
+    pub require_orders: bool, // Ending the turn takes a second press while units still need orders
+    pub humans: usize, // Civilizations played by people taking turns at this screen, up to MAX_HUMANS; 0 to watch the AI play
+    pub turn_delay: f32, // Shortest time an AI turn is shown for, in seconds; 0 plays them as fast as they go
+    pub headless: bool, // Run an AI-only game with no window and print how each civilization fared
+    pub stats_out: Option<PathBuf>, // Where a headless run also writes its report, as JSON
+    pub console: bool, // Backquote opens the developer console; on in debug builds
+    pub turn_report: bool, // Sum up what happened since a human's last turn when their next one starts
+    pub turn_time_limit: Option<Duration>, // How long a human's turn may take before it ends on its own; None for no limit
+    pub edge_pan: bool, // Resting the cursor at a window edge pans the camera
+    pub edge_pan_margin: f32, // How close to the edge, in pixels, the cursor pans
+    pub camera_map_overlap: f32, // Fraction of the view, across and down, that always stays over the map
+    pub free_camera: bool, // Debugging: the camera may leave the map behind
+    pub omniscient: bool, // Debugging: the stats readouts show every civilization's exact figures
+    pub log_reports: bool, // Also print the turn summary and debug report to stdout, for headless runs
+    pub auto_production: bool, // Idle cities of human players pick their next build as the AI would, instead of prompting
+    pub quick_movement_ai: bool, // AI units act one a frame; off, one a step so their moves can be followed
+    pub quick_combat_ai: bool, // Fights no human takes part in are logged without damage numbers or camera alerts
+}
+
+// Range of AI civilizations a game can be set up with; past the five curated
+// rivals, the rest are generated
+pub const MIN_OPPONENTS: usize = 2;
+pub const MAX_OPPONENTS: usize = 11;
+const DEFAULT_OPPONENTS: usize = 5;
+const DEFAULT_AUTOSAVE_INTERVAL: u32 = 5;
+const DEFAULT_EDGE_PAN_MARGIN: f32 = 20.0;
+const DEFAULT_CAMERA_MAP_OVERLAP: f32 = 0.5;
+pub const MAX_HUMANS: usize = 4;
+// Turn limits the setup screen offers; 0 plays on until someone wins outright
+pub const TURN_LIMITS: [u32; 6] = [0, 100, 200, 300, 400, 500];
+// Time limits for human turns the setup screen offers
+pub const TURN_TIME_LIMITS: [Option<Duration>; 5] = [
+    None,
+    Some(Duration::from_secs(30)),
+    Some(Duration::from_secs(60)),
+    Some(Duration::from_secs(120)),
+    Some(Duration::from_secs(300)),
+];
+// Seconds left on a timed turn when the countdown turns to a warning
+const TURN_TIMER_WARNING_SECONDS: f32 = 10.0;
+
+impl Default for GameSettings {
+    fn default() -> Self {
+        Self {
+            impassable_peaks: true,
+            keyboard_unit_movement: true,
+            log_city_events: false,
+            turn_limit: 300,
+            difficulty: Difficulty::Prince,
+            speed: GameSpeed::Standard,
+            barbarians: true,
+            player_civ: None,
+            opponents: DEFAULT_OPPONENTS,
+            rules_dir: PathBuf::from(DEFAULT_RULES_DIR),
+            seed: None,
+            world_seed: None,
+            autosave_interval: DEFAULT_AUTOSAVE_INTERVAL,
+            world_type: WorldType::default(),
+            require_orders: true,
+            humans: 1,
+            turn_delay: AI_TURN_DELAY,
+            headless: false,
+            stats_out: None,
+            console: cfg!(debug_assertions),
+            turn_report: true,
+            turn_time_limit: None,
+            edge_pan: true,
+            edge_pan_margin: DEFAULT_EDGE_PAN_MARGIN,
+            camera_map_overlap: DEFAULT_CAMERA_MAP_OVERLAP,
+            free_camera: false,
+            omniscient: false,
+            log_reports: false,
+            auto_production: false,
+            quick_movement_ai: true,
+            quick_combat_ai: true,
+        }
+    }
+}
+
+impl GameSettings {
+    // These settings (the defaults, or the player's saved choices), overridden on the command line
+    // by `--difficulty <level>`, `--speed <name>`, `--no-barbarians`, `--civ <name>`, `--opponents <count>`,
+    // `--civs <total>`, `--rules <dir>`, `--seed <n>`, `--world-seed <n>`,
+    // `--autosave <turns>`, `--no-autosave`, `--world <type>`, `--no-require-orders`,
+    // `--humans <count>`, `--observe` (no humans), `--turn-delay <seconds>`, `--turns <n>`,
+    // `--headless`, `--stats-out <file>`, `--enable-console`, `--no-turn-report`,
+    // `--turn-timer <seconds>`, `--no-edge-pan`, `--edge-pan-margin <pixels>`, `--camera-overlap <fraction>`,
+    // `--free-camera`, `--omniscient`, `--log-reports` and `--auto-production`
+    pub fn with_args(self, args: impl Iterator<Item = String>) -> Self {
+        let mut settings = self;
+        let mut args = args.peekable();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--difficulty" => match args.peek().and_then(|name| Difficulty::from_name(name)) {
+                    Some(difficulty) => settings.difficulty = difficulty,
+                    None => println!("Unknown difficulty; expected Settler, Chieftain, Prince or King"),
+                },
+                "--speed" => match args.peek().and_then(|name| GameSpeed::from_name(name)) {
+                    Some(speed) => settings.speed = speed,
+                    None => println!("Unknown game speed; expected Quick, Standard or Epic"),
+                },
+                "--no-barbarians" => settings.barbarians = false,
+                "--civ" => settings.player_civ = args.peek().cloned(),
+                "--opponents" => match args.peek().and_then(|count| count.parse::<usize>().ok()) {
+                    Some(count) => settings.opponents = count.clamp(MIN_OPPONENTS, MAX_OPPONENTS),
+                    None => println!("--opponents expects a number from {} to {}", MIN_OPPONENTS, MAX_OPPONENTS),
+                },
+                "--civs" => match args.peek().and_then(|count| count.parse::<usize>().ok()) {
+                    Some(total) => settings.opponents = total.saturating_sub(1).clamp(MIN_OPPONENTS, MAX_OPPONENTS),
+                    None => println!("--civs expects a number from {} to {}", MIN_OPPONENTS + 1, MAX_OPPONENTS + 1),
+                },
+                "--rules" => match args.peek() {
+                    Some(dir) => settings.rules_dir = PathBuf::from(dir),
+                    None => println!("--rules expects a directory"),
+                },
+                "--seed" => match args.peek().and_then(|seed| seed.parse::<u64>().ok()) {
+                    Some(seed) => settings.seed = Some(seed),
+                    None => println!("--seed expects a number"),
+                },
+                "--world-seed" => match args.peek().and_then(|seed| seed.parse::<u64>().ok()) {
+                    Some(seed) => settings.world_seed = Some(seed),
+                    None => println!("--world-seed expects a number"),
+                },
+                "--autosave" => match args.peek().and_then(|turns| turns.parse::<u32>().ok()) {
+                    Some(turns) => settings.autosave_interval = turns,
+                    None => println!("--autosave expects a number of turns (0 turns it off)"),
+                },
+                "--no-autosave" => settings.autosave_interval = 0,
+                "--world" => match args.peek().and_then(|name| WorldType::from_name(name)) {
+                    Some(world_type) => settings.world_type = world_type,
+                    None => println!("Unknown world type; expected one of {}", WorldType::ALL.map(|world_type| world_type.get_name()).join(", ")),
+                },
+                "--no-require-orders" => settings.require_orders = false,
+                "--humans" => match args.peek().and_then(|count| count.parse::<usize>().ok()) {
+                    Some(count) => settings.humans = count.min(MAX_HUMANS),
+                    None => println!("--humans expects a number from 0 to {}", MAX_HUMANS),
+                },
+                "--observe" => settings.humans = 0,
+                "--turn-delay" => match args.peek().and_then(|seconds| seconds.parse::<f32>().ok()) {
+                    Some(seconds) => settings.turn_delay = seconds.max(0.0),
+                    None => println!("--turn-delay expects a number of seconds"),
+                },
+                "--turns" => match args.peek().and_then(|turns| turns.parse::<u32>().ok()) {
+                    Some(turns) => settings.turn_limit = turns,
+                    None => println!("--turns expects a number of turns"),
+                },
+                "--headless" => settings.headless = true,
+                "--stats-out" => match args.peek() {
+                    Some(path) => settings.stats_out = Some(PathBuf::from(path)),
+                    None => println!("--stats-out expects a file"),
+                },
+                "--enable-console" => settings.console = true,
+                "--no-turn-report" => settings.turn_report = false,
+                "--turn-timer" => match args.peek().and_then(|seconds| seconds.parse::<f32>().ok()) {
+                    Some(seconds) if seconds > 0.0 => settings.turn_time_limit = Some(Duration::from_secs_f32(seconds)),
+                    Some(_) => settings.turn_time_limit = None,
+                    None => println!("--turn-timer expects a number of seconds"),
+                },
+                "--no-edge-pan" => settings.edge_pan = false,
+                "--edge-pan-margin" => match args.peek().and_then(|pixels| pixels.parse::<f32>().ok()) {
+                    Some(pixels) => settings.edge_pan_margin = pixels.max(1.0),
+                    None => println!("--edge-pan-margin expects a number of pixels"),
+                },
+                "--camera-overlap" => match args.peek().and_then(|fraction| fraction.parse::<f32>().ok()) {
+                    Some(fraction) => settings.camera_map_overlap = fraction.clamp(0.0, 1.0),
+                    None => println!("--camera-overlap expects a fraction from 0 to 1"),
+                },
+                "--free-camera" => settings.free_camera = true,
+                "--omniscient" => settings.omniscient = true,
+                "--log-reports" => settings.log_reports = true,
+                "--auto-production" => settings.auto_production = true,
+                _ => {}
+            }
+        }
+        // Nobody is there to play or watch: the AI takes every civilization and its turns
+        // go by as fast as they can, with nothing saved along the way
+        if settings.headless {
+            settings.humans = 0;
+            settings.turn_delay = 0.0;
+            settings.autosave_interval = 0;
+        }
+        settings
+    }
 }
 
 impl Default for GameState {
@@ -31,13 +293,54 @@ impl Default for GameState {
     }
 }
 
+// The civilizations in this game: the one named in the settings (or the default) as the
+// player, plus a random pick of the others as opponents, in their usual order. If more
+// opponents are wanted than there are curated civilizations, generated ones fill in.
+// In hotseat games the first few opponents picked are played by people as well; with no
+// humans at all, the AI plays the player's civilization too.
+fn choose_civilizations(mut civilizations: Vec<Civilization>, settings: &GameSettings) -> Vec<Civilization> {
+    let chosen = settings.player_civ.as_deref().and_then(|query| {
+        let found = civilizations.iter().position(|civ| civ.matches_name(query));
+        if found.is_none() {
+            println!("No civilization called '{}'; run with --list-civs to see them", query);
+        }
+        found
+    });
+    let player_index = chosen
+        .or_else(|| civilizations.iter().position(|civ| civ.is_player))
+        .unwrap_or(0);
+    
+    let mut opponents: Vec<usize> = (0..civilizations.len()).filter(|&index| index != player_index).collect();
+    opponents.shuffle(&mut rand::rng());
+    opponents.truncate(settings.opponents);
+    
+    let other_humans = &opponents[..settings.humans.saturating_sub(1).min(opponents.len())];
+    for (index, civ) in civilizations.iter_mut().enumerate() {
+        civ.set_player(settings.humans > 0 && (index == player_index || other_humans.contains(&index)));
+    }
+    let missing = settings.opponents.saturating_sub(opponents.len());
+    let mut chosen: Vec<Civilization> = civilizations
+        .into_iter()
+        .enumerate()
+        .filter(|(index, _)| *index == player_index || opponents.contains(index))
+        .map(|(_, civ)| civ)
+        .collect();
+    let generated = generate_civilizations(missing, &chosen);
+    chosen.extend(generated);
+    chosen
+}
+
 // System to initialize the game once the world is generated
+#[allow(clippy::too_many_arguments)]
 pub fn initialize_game(
     mut commands: Commands,
     mut game_state: ResMut<GameState>,
     mut civ_manager: ResMut<CivilizationManager>,
     tile_query: Query<&MapTile>,
-    world_info: Option<Res<super::map::WorldInfo>>,
+    tiles: TileLookup,
+    world_info: Option<Res<WorldInfo>>,
+    settings: Res<GameSettings>,
+    mut turn_events: EventWriter<TurnStartedEvent>,
 ) {
     if game_state.is_initialized {
         return;
@@ -49,9 +352,13 @@ pub fn initialize_game(
     }
     
     println!("=== INITIALIZING CIVILIZATION GAME ===");
+    civ_manager.difficulty = settings.difficulty;
+    civ_manager.barbarians = settings.barbarians;
+    civ_manager.speed = settings.speed;
+    println!("Difficulty: {}, speed: {}", settings.difficulty.get_name(), settings.speed.get_name());
     
     // Create civilizations
-    let civilizations = create_default_civilizations();
+    let civilizations = choose_civilizations(create_default_civilizations(), &settings);
     let mut civ_ids = Vec::new();
     
     for civ in civilizations {
@@ -60,7 +367,7 @@ pub fn initialize_game(
     }
     
     // Find suitable starting positions for each civilization
-    let starting_positions = find_starting_positions(&tile_query, civ_ids.len());
+    let starting_positions = find_starting_positions(&tile_query, &tiles, civ_ids.len());
     
     if starting_positions.len() < civ_ids.len() {
         println!("Warning: Could only find {} starting positions for {} civilizations", 
@@ -70,32 +377,163 @@ pub fn initialize_game(
     // Spawn starting cities and units for each civilization
     for (i, &civ_id) in civ_ids.iter().enumerate() {
         if let Some(&start_pos) = starting_positions.get(i) {
-            spawn_civilization_start(&mut commands, civ_id, start_pos, &mut civ_manager);
+            spawn_civilization_start(&mut commands, civ_id, start_pos, &tiles, &mut civ_manager);
         }
     }
     
     // Set up turn order
-    if !civ_ids.is_empty() {
-        civ_manager.current_turn_civ = civ_ids[0];
+    if let Some(&first) = civ_manager.turn_order().first() {
+        civ_manager.current_turn_civ = first;
     }
     
     game_state.is_initialized = true;
-    game_state.current_phase = GamePhase::PlayerTurn;
+    game_state.current_phase = current_phase(&civ_manager).unwrap_or(GamePhase::PlayerTurn);
+    // The opening civilization's turn starts here; every later one in advance_turn
+    turn_events.write(TurnStartedEvent { civilization_id: civ_manager.current_turn_civ });
     
     println!("Game initialized with {} civilizations", civ_ids.len());
     print_game_status(&civ_manager);
 }
 
-fn find_starting_positions(tile_query: &Query<&MapTile>, num_civs: usize) -> Vec<HexCoord> {
-    let mut candidates = Vec::new();
+// Moves on to the game once the civilizations are set up, or a save has been restored.
+// A hotseat game opening on a human's turn first hands the screen to them.
+pub fn finish_loading(game_state: Res<GameState>, civ_manager: Res<CivilizationManager>, mut next_state: ResMut<NextState<AppState>>) {
+    if !game_state.is_initialized {
+        return;
+    }
+    let human_to_move = game_state.current_phase == GamePhase::PlayerTurn;
+    next_state.set(if civ_manager.is_hotseat() && human_to_move { AppState::Handover } else { AppState::InGame });
+}
+
+// Top-level entities a game spawns: cities and units, plus everything with a place in
+// the world or on screen bar the camera
+type SessionEntities = (Or<(With<Transform>, With<City>, With<Unit>)>, Without<Camera>, Without<ChildOf>);
+
+// Ends a game on its way back to the menus. Everything but the camera was spawned for
+// it (the map, cities, units and their markers, the HUD and every panel), so all of it
+// goes, and its resources are put back the way a fresh launch has them.
+pub fn end_session(
+    mut commands: Commands,
+    session_entities: Query<Entity, SessionEntities>,
+    settings: Res<GameSettings>,
+    mut combat_log: ResMut<CombatLog>,
+) {
+    for entity in session_entities.iter() {
+        commands.entity(entity).despawn();
+    }
+    
+    commands.insert_resource(GameState::default());
+    commands.insert_resource(CivilizationManager::default());
+    commands.insert_resource(TileIndex::default());
+    commands.insert_resource(UnitSelection::default());
+    commands.insert_resource(CitySelection::default());
+    commands.insert_resource(CombatState::default());
+    commands.insert_resource(CityFoundingState::default());
+    commands.insert_resource(InputMode::default());
+    commands.insert_resource(TextEntry::default());
+    commands.insert_resource(TerritoryMap::default());
+    commands.insert_resource(WonderRegistry::default());
+    commands.insert_resource(CulturePressure::default());
+    commands.insert_resource(StatsHistory::default());
+    commands.insert_resource(ReplayLog::default());
+    commands.insert_resource(AiTurnTimer::new(settings.turn_delay));
+    commands.insert_resource(EndTurnGuard::default());
+    commands.insert_resource(TurnTimer::default());
+    commands.insert_resource(MoveHistory::default());
+    commands.insert_resource(StableIds::default());
+    commands.insert_resource(LoadMenu::default());
+    commands.insert_resource(GameRng::new(settings.seed.unwrap_or_else(rand::random)));
+    commands.remove_resource::<WorldInfo>();
+    commands.remove_resource::<WorldOrigin>();
+    commands.remove_resource::<GameResult>();
+    // Emptied rather than replaced, so its running count (which war weariness keeps
+    // its place by) doesn't go back to zero
+    combat_log.restore(Vec::new());
+    commands.insert_resource(TeardownCheck { frames_left: TEARDOWN_SETTLE_FRAMES });
+}
+
+// Frames after a game ends before its meshes and materials are expected to be freed;
+// assets go a frame or two after the last handle to them is dropped
+const TEARDOWN_SETTLE_FRAMES: u32 = 3;
+
+/// How many meshes and materials there were before the first game, which every
+/// teardown should bring the counts back down to
+#[derive(Resource)]
+pub struct AssetBaseline {
+    meshes: usize,
+    materials: usize,
+}
+
+/// Set when a game is torn down, until the teardown has been checked
+#[derive(Resource)]
+pub struct TeardownCheck {
+    frames_left: u32,
+}
+
+pub fn record_asset_baseline(
+    mut commands: Commands,
+    baseline: Option<Res<AssetBaseline>>,
+    meshes: Option<Res<Assets<Mesh>>>,
+    materials: Option<Res<Assets<ColorMaterial>>>,
+) {
+    if baseline.is_none() {
+        commands.insert_resource(AssetBaseline {
+            meshes: meshes.map_or(0, |meshes| meshes.len()),
+            materials: materials.map_or(0, |materials| materials.len()),
+        });
+    }
+}
+
+// Debug builds: checks that a game torn down on the way back to the menus left nothing
+// behind. No tile, unit or city may survive it, and once the freed assets have gone the
+// mesh and material counts must be back where they were before the first game.
+#[allow(clippy::too_many_arguments)]
+pub fn check_session_teardown(
+    mut commands: Commands,
+    mut check: ResMut<TeardownCheck>,
+    tile_query: Query<(), With<MapTile>>,
+    unit_query: Query<(), With<Unit>>,
+    city_query: Query<(), With<City>>,
+    baseline: Option<Res<AssetBaseline>>,
+    meshes: Option<Res<Assets<Mesh>>>,
+    materials: Option<Res<Assets<ColorMaterial>>>,
+) {
+    if check.frames_left == TEARDOWN_SETTLE_FRAMES {
+        debug_assert!(tile_query.is_empty(), "{} map tiles outlived their game", tile_query.iter().count());
+        debug_assert!(unit_query.is_empty(), "{} units outlived their game", unit_query.iter().count());
+        debug_assert!(city_query.is_empty(), "{} cities outlived their game", city_query.iter().count());
+    }
+    if check.frames_left > 0 {
+        check.frames_left -= 1;
+        return;
+    }
+    commands.remove_resource::<TeardownCheck>();
+    let Some(baseline) = baseline else { return };
+    if let Some(meshes) = meshes {
+        debug_assert!(meshes.len() <= baseline.meshes, "{} meshes left after the game, {} before it", meshes.len(), baseline.meshes);
+    }
+    if let Some(materials) = materials {
+        debug_assert!(
+            materials.len() <= baseline.materials,
+            "{} materials left after the game, {} before it",
+            materials.len(),
+            baseline.materials
+        );
+    }
+}
+
+// Neighbors are found through the tile index, and only tiles that pass the cheap checks on
+// their own biome, soil and climate have their surroundings looked at
+fn find_starting_positions(tile_query: &Query<&MapTile>, tiles: &TileLookup, num_civs: usize) -> Vec<HexCoord> {
     let mut positions = Vec::new();
+    let tile_at = |coord: HexCoord| tiles.get(coord);
     
     // First pass: find all suitable starting tiles
-    for tile in tile_query.iter() {
-        if is_good_starting_position(tile, tile_query) {
-            candidates.push((tile.hex_coord, rate_starting_position(tile, tile_query)));
-        }
-    }
+    let mut candidates: Vec<(HexCoord, f32)> = tile_query
+        .iter()
+        .filter(|tile| is_good_starting_position(tile, tile_at))
+        .map(|tile| (tile.hex_coord, rate_starting_position(tile, tile_at)))
+        .collect();
     
     // Sort by quality (best first)
     candidates.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
@@ -140,7 +578,7 @@ fn find_starting_positions(tile_query: &Query<&MapTile>, num_civs: usize) -> Vec
     positions
 }
 
-fn is_good_starting_position(tile: &MapTile, tile_query: &Query<&MapTile>) -> bool {
+fn is_good_starting_position<'a>(tile: &MapTile, tile_at: impl Fn(HexCoord) -> Option<&'a MapTile>) -> bool {
     // Must be on land
     let biome = BiomeType::from_u8(tile.biome);
     if matches!(biome, BiomeType::Ocean | BiomeType::Lake) {
@@ -155,15 +593,20 @@ fn is_good_starting_position(tile: &MapTile, tile_query: &Query<&MapTile>) -> bo
         _ => {}
     }
     
-    // Check for basic necessities in the immediate area
-    let has_freshwater = tile.has_river || has_freshwater_nearby(tile.hex_coord, tile_query);
+    // Check the tile itself before looking around it
     let has_decent_fertility = tile.soil_fertility > 0.3;
     let not_too_harsh = tile.temperature > 0.2 && tile.precipitation > 0.15;
+    if !has_decent_fertility || !not_too_harsh {
+        return false;
+    }
     
-    has_freshwater && has_decent_fertility && not_too_harsh
+    // Then for fresh water in the immediate area
+    tile.has_river || has_freshwater_nearby(tile.hex_coord, tile_at)
 }
 
-fn rate_starting_position(tile: &MapTile, tile_query: &Query<&MapTile>) -> f32 {
+// How good a site is for a city; also used by the AI to pick where its settlers go.
+// `tile_at` looks up a tile by coordinate.
+pub fn rate_starting_position<'a>(tile: &MapTile, tile_at: impl Fn(HexCoord) -> Option<&'a MapTile> + Copy) -> f32 {
     let mut score = 0.0;
     
     // Base fertility score
@@ -180,7 +623,7 @@ fn rate_starting_position(tile: &MapTile, tile_query: &Query<&MapTile>) -> f32 {
     // Freshwater bonus
     if tile.has_river {
         score += 15.0;
-    } else if has_freshwater_nearby(tile.hex_coord, tile_query) {
+    } else if has_freshwater_nearby(tile.hex_coord, tile_at) {
         score += 10.0;
     }
     
@@ -199,7 +642,7 @@ fn rate_starting_position(tile: &MapTile, tile_query: &Query<&MapTile>) -> f32 {
     score += tile.trade_value * 5.0;
     
     // Nearby tile diversity and quality
-    let nearby_score = rate_nearby_tiles(tile.hex_coord, tile_query);
+    let nearby_score = rate_nearby_tiles(tile.hex_coord, tile_at);
     score += nearby_score;
     
     // Biome preference
@@ -218,61 +661,54 @@ fn rate_starting_position(tile: &MapTile, tile_query: &Query<&MapTile>) -> f32 {
     score
 }
 
-fn has_freshwater_nearby(center: HexCoord, tile_query: &Query<&MapTile>) -> bool {
-    for neighbor in center.neighbors() {
-        if let Some(tile) = tile_query.iter().find(|t| t.hex_coord == neighbor) {
-            if tile.has_river || matches!(BiomeType::from_u8(tile.biome), BiomeType::Lake) {
-                return true;
-            }
-        }
-    }
-    false
+// Fresh water on a neighboring tile; `tile_at` looks up a tile by coordinate
+pub fn has_freshwater_nearby<'a>(center: HexCoord, tile_at: impl Fn(HexCoord) -> Option<&'a MapTile>) -> bool {
+    center
+        .neighbors()
+        .into_iter()
+        .filter_map(tile_at)
+        .any(|tile| tile.has_river || matches!(BiomeType::from_u8(tile.biome), BiomeType::Lake))
 }
 
-fn rate_nearby_tiles(center: HexCoord, tile_query: &Query<&MapTile>) -> f32 {
+fn rate_nearby_tiles<'a>(center: HexCoord, tile_at: impl Fn(HexCoord) -> Option<&'a MapTile>) -> f32 {
     let mut score = 0.0;
-    let mut _tile_count = 0;
     
     // Check tiles within 2 hex radius
-    for tile in tile_query.iter() {
+    for tile in (1..=2).flat_map(|radius| center.ring(radius)).filter_map(&tile_at) {
         let distance = hex_distance(center, tile.hex_coord);
-        if distance <= 2 && distance > 0 {
-            _tile_count += 1;
-            
-            // Distance weight (closer tiles matter more)
-            let weight = match distance {
-                1 => 1.0,
-                2 => 0.5,
-                _ => 0.0,
-            };
-            
-            // Land tile bonus
-            let biome = BiomeType::from_u8(tile.biome);
-            if !matches!(biome, BiomeType::Ocean) {
-                score += 2.0 * weight;
+        // Distance weight (closer tiles matter more)
+        let weight = match distance {
+            1 => 1.0,
+            2 => 0.5,
+            _ => 0.0,
+        };
+        
+        // Land tile bonus
+        let biome = BiomeType::from_u8(tile.biome);
+        if !matches!(biome, BiomeType::Ocean) {
+            score += 2.0 * weight;
+        }
+        
+        // Fertility
+        score += tile.soil_fertility * 3.0 * weight;
+        
+        // Resources
+        if tile.resource != 0 {
+            score += 2.0 * weight;
+        }
+        
+        // Terrain diversity bonus
+        match biome {
+            BiomeType::TemperateDeciduousForest | BiomeType::TaigaBorealForest => {
+                score += 1.0 * weight; // Production potential
             }
-            
-            // Fertility
-            score += tile.soil_fertility * 3.0 * weight;
-            
-            // Resources
-            if tile.resource != 0 {
-                score += 2.0 * weight;
+            BiomeType::TemperateGrassland | BiomeType::TropicalGrasslandSavanna => {
+                score += 1.5 * weight; // Food potential
             }
-            
-            // Terrain diversity bonus
-            match biome {
-                BiomeType::TemperateDeciduousForest | BiomeType::TaigaBorealForest => {
-                    score += 1.0 * weight; // Production potential
-                }
-                BiomeType::TemperateGrassland | BiomeType::TropicalGrasslandSavanna => {
-                    score += 1.5 * weight; // Food potential
-                }
-                BiomeType::AlpineTundra | BiomeType::MontaneForest => {
-                    score += 0.5 * weight; // Mining potential
-                }
-                _ => {}
+            BiomeType::AlpineTundra | BiomeType::MontaneForest => {
+                score += 0.5 * weight; // Mining potential
             }
+            _ => {}
         }
     }
     
@@ -283,6 +719,7 @@ fn spawn_civilization_start(
     commands: &mut Commands,
     civ_id: u32,
     start_pos: HexCoord,
+    tiles: &TileLookup,
     civ_manager: &mut CivilizationManager,
 ) {
     let civ_name = civ_manager.get_civilization(civ_id)
@@ -303,20 +740,43 @@ fn spawn_civilization_start(
         civ_manager,
     );
     
-    // Spawn starting units around the capital
+    // Spawn starting units around the capital, one per hex, each on a tile it can occupy
     let starting_units = get_starting_units_for_civilization(civ_id, civ_manager);
+    let mut occupied = std::collections::HashSet::new();
     
-    let mut spawn_positions = vec![start_pos];
-    
-    // Add neighboring positions for additional units
-    let neighbors = start_pos.neighbors();
-    spawn_positions.extend_from_slice(&neighbors);
-    
-    for (i, unit_type) in starting_units.iter().enumerate() {
-        if let Some(&spawn_pos) = spawn_positions.get(i) {
-            spawn_unit(commands, *unit_type, civ_id, spawn_pos, civ_manager);
+    for unit_type in starting_units {
+        match find_unit_spawn_position(unit_type, civ_id, start_pos, tiles, &occupied) {
+            Some(spawn_pos) => {
+                occupied.insert(spawn_pos);
+                spawn_unit(commands, unit_type, civ_id, spawn_pos, civ_manager);
+            }
+            None => {
+                println!("No valid tile near {} for starting {}; skipped", civ_name, unit_type.get_name());
+            }
         }
     }
+    
+    let starting_gold = civ_manager.difficulty_modifiers(civ_id).starting_gold;
+    if let Some(civ) = civ_manager.get_civilization_mut(civ_id) {
+        civ.gold += starting_gold;
+    }
+}
+
+// First free tile near `center` the unit can legally stand on. Land units look up to
+// two rings out; naval units only use water adjacent to the city.
+fn find_unit_spawn_position(
+    unit_type: UnitType,
+    civ_id: u32,
+    center: HexCoord,
+    tiles: &TileLookup,
+    occupied: &std::collections::HashSet<HexCoord>,
+) -> Option<HexCoord> {
+    let unit = Unit::new(unit_type, civ_id, center);
+    let max_radius = if unit.naval_unit { 1 } else { 2 };
+    
+    (0..=max_radius)
+        .flat_map(|radius| center.ring(radius))
+        .find(|coord| !occupied.contains(coord) && unit.can_move_to(*coord, tiles))
 }
 
 fn get_starting_units_for_civilization(
@@ -351,6 +811,10 @@ fn get_starting_units_for_civilization(
         }
     }
     
+    if civ_manager.difficulty_modifiers(civ_id).extra_starting_unit {
+        units.push(UnitType::Warrior);
+    }
+    
     units
 }
 
@@ -376,129 +840,239 @@ fn print_game_status(civ_manager: &CivilizationManager) {
     }
 }
 
+// Seconds in which a second press ends the turn even though units still need orders
+const FORCE_END_TURN_SECONDS: f32 = 3.0;
+
+/// Holds back ending the turn while the player's units still need orders, when
+/// GameSettings::require_orders is on. A second press soon after goes through.
+#[derive(Resource, Default)]
+pub struct EndTurnGuard {
+    pub hold: bool, // The end-turn press of this frame was held back
+    force_until: Option<f32>, // A press before this time ends the turn anyway
+}
+
+/// The human whose turn it is asked to end it, with its key or the End Turn button
+#[derive(Event)]
+pub struct EndTurnEvent;
+
+// Turns the End Turn keys into end-turn requests
+pub fn end_turn_hotkey_system(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    input_map: Res<InputMap>,
+    mut end_turn_events: EventWriter<EndTurnEvent>,
+) {
+    if input_map.just_pressed(Action::EndTurn, &keyboard) {
+        end_turn_events.write(EndTurnEvent);
+    }
+}
+
+// The civilization's units still waiting for orders, oldest first. The end-turn guard
+// holds the turn for these and the End Turn button counts them.
+pub fn units_needing_orders<'a>(civ_id: u32, units: impl Iterator<Item = (Entity, &'a Unit)>) -> Vec<(Entity, &'a Unit)> {
+    let mut waiting: Vec<(Entity, &Unit)> = units
+        .filter(|(_, unit)| unit.civilization_id == civ_id && unit.needs_orders())
+        .collect();
+    waiting.sort_by_key(|(entity, _)| *entity);
+    waiting
+}
+
+// System that stops the player ending the turn with units still waiting for orders:
+// it selects and centers the first of them instead and says how many there are
+#[allow(clippy::too_many_arguments)]
+pub fn end_turn_guard_system(
+    mut end_turn_events: EventReader<EndTurnEvent>,
+    settings: Res<GameSettings>,
+    civ_manager: Res<CivilizationManager>,
+    unit_query: Query<(Entity, &Unit)>,
+    mut unit_selection: ResMut<UnitSelection>,
+    mut camera_tween: ResMut<CameraTween>,
+    mut notifications: EventWriter<GameNotification>,
+    mut guard: ResMut<EndTurnGuard>,
+    time: Res<Time>,
+) {
+    if guard.hold {
+        guard.hold = false;
+    }
+    if end_turn_events.read().count() == 0 || !settings.require_orders {
+        return;
+    }
+    let Some(player) = civ_manager.get_player_civilization() else { return };
+    if civ_manager.current_turn_civ != player.id {
+        return;
+    }
+    let now = time.elapsed_secs();
+    if guard.force_until.take().is_some_and(|until| now <= until) {
+        return;
+    }
+    
+    let waiting = units_needing_orders(player.id, unit_query.iter());
+    let Some(&(first, unit)) = waiting.first() else { return };
+    
+    unit_selection.selected_unit = Some(first);
+    camera_tween.center_on(unit.hex_coord);
+    guard.hold = true;
+    guard.force_until = Some(now + FORCE_END_TURN_SECONDS);
+    
+    let count = if waiting.len() == 1 { "1 unit needs".to_string() } else { format!("{} units need", waiting.len()) };
+    let text = format!("{} orders - press SPACE again to force end turn", count);
+    notifications.write(GameNotification::new(NotificationCategory::Warning, text).at(unit.hex_coord).for_civ(player.id));
+}
+
 // System for advancing turns
 pub fn turn_system(
-    keyboard: Res<ButtonInput<KeyCode>>,
+    mut end_turn_events: EventReader<EndTurnEvent>,
+    guard: Res<EndTurnGuard>,
+    mut game_state: ResMut<GameState>,
+    mut civ_manager: ResMut<CivilizationManager>,
+    mut turn_events: EventWriter<TurnStartedEvent>,
+) {
+    // Handle turn advancement; an observer game advances on its own
+    if end_turn_events.read().count() > 0 && !guard.hold && !civ_manager.is_observer_game() {
+        advance_turn(&mut game_state, &mut civ_manager, &mut turn_events);
+    }
+}
+
+/// The countdown on a human's turn when turns are timed
+#[derive(Resource, Default)]
+pub struct TurnTimer {
+    timer: Option<Timer>,
+    turn: (u32, u32), // The turn number and civilization the countdown belongs to
+}
+
+impl TurnTimer {
+    // Seconds left on the running countdown, if there is one
+    pub fn remaining_secs(&self) -> Option<f32> {
+        self.timer.as_ref().map(|timer| timer.remaining_secs())
+    }
+}
+
+// Counts down a human's turn when turns are timed, and ends it when time runs out: units
+// still waiting for orders fortify, or skip their turn if they can't. The countdown starts
+// afresh each human turn, stops while a choice is open (a combat preview, a city's
+// production, a name being typed) and is never kept for the AI.
+#[allow(clippy::too_many_arguments)]
+pub fn turn_timer_system(
+    time: Res<Time>,
+    settings: Res<GameSettings>,
+    mut turn_timer: ResMut<TurnTimer>,
     mut game_state: ResMut<GameState>,
     mut civ_manager: ResMut<CivilizationManager>,
-    mut city_query: Query<&mut City>,
+    mut turn_events: EventWriter<TurnStartedEvent>,
     mut unit_query: Query<&mut Unit>,
+    combat_state: Res<CombatState>,
+    city_selection: Res<CitySelection>,
+    text_entry: Res<TextEntry>,
+    mut notifications: EventWriter<GameNotification>,
 ) {
-    if !game_state.is_initialized {
+    let human = civ_manager.get_civilization(civ_manager.current_turn_civ).is_some_and(|civ| civ.is_player);
+    let (Some(limit), true) = (settings.turn_time_limit, human && game_state.current_phase == GamePhase::PlayerTurn) else {
+        turn_timer.timer = None;
+        return;
+    };
+    let turn = (civ_manager.turn_number, civ_manager.current_turn_civ);
+    if turn_timer.turn != turn || turn_timer.timer.is_none() {
+        turn_timer.turn = turn;
+        turn_timer.timer = Some(Timer::new(limit, TimerMode::Once));
+    }
+    let modal_open = combat_state.combat_preview.is_some() || city_selection.selected_city.is_some() || text_entry.is_active();
+    let Some(timer) = turn_timer.timer.as_mut() else { return };
+    if modal_open || !timer.tick(time.delta()).finished() {
         return;
     }
     
-    // Handle turn advancement
-    if keyboard.just_pressed(KeyCode::Space) || 
-       keyboard.just_pressed(KeyCode::Enter) {
-        advance_turn(&mut game_state, &mut civ_manager, &mut city_query, &mut unit_query);
+    let player = civ_manager.current_turn_civ;
+    for mut unit in unit_query.iter_mut().filter(|unit| unit.civilization_id == player && unit.needs_orders()) {
+        if unit.can_attack {
+            unit.fortify();
+        } else {
+            unit.movement_points = 0;
+            unit.has_moved = true;
+        }
+    }
+    notifications.write(GameNotification::new(NotificationCategory::Warning, "Time is up - your turn has ended").for_civ(player));
+    turn_timer.timer = None;
+    advance_turn(&mut game_state, &mut civ_manager, &mut turn_events);
+}
+
+// Phase for whoever's turn it is, None if the current id is unknown
+fn current_phase(civ_manager: &CivilizationManager) -> Option<GamePhase> {
+    match civ_manager.get_civilization(civ_manager.current_turn_civ) {
+        Some(civ) if civ.is_player => Some(GamePhase::PlayerTurn),
+        Some(civ) => Some(GamePhase::AITurn(civ.id)),
+        None if civ_manager.current_turn_civ == BARBARIAN_CIV_ID => Some(GamePhase::BarbarianTurn),
+        None => None,
     }
 }
 
-fn advance_turn(
+// Hands the turn to the next civilization. Its cities and units are processed by
+// the systems reading TurnStartedEvent, so each civ is processed once per turn.
+pub fn advance_turn(
     game_state: &mut ResMut<GameState>,
     civ_manager: &mut ResMut<CivilizationManager>,
-    city_query: &mut Query<&mut City>,
-    unit_query: &mut Query<&mut Unit>,
+    turn_events: &mut EventWriter<TurnStartedEvent>,
 ) {
     println!("Advancing turn...");
     
-    // Process current civilization's end-of-turn activities
-    let current_civ_id = civ_manager.current_turn_civ;
-    
-    // Process cities for the current civilization
-    for mut city in city_query.iter_mut() {
-        if city.civilization_id == current_civ_id {
-            city.process_turn(civ_manager);
-        }
-    }
-    
-    // Process units for the current civilization
-    for mut unit in unit_query.iter_mut() {
-        if unit.civilization_id == current_civ_id {
-            unit.start_turn();
-        }
-    }
-    
-    // Advance to next civilization
+    // Advance to next civilization; a human taking over is shown the game from now on
     civ_manager.next_turn();
+    if civ_manager.get_civilization(civ_manager.current_turn_civ).is_some_and(|civ| civ.is_player) {
+        civ_manager.active_player = civ_manager.current_turn_civ;
+    }
+    turn_events.write(TurnStartedEvent { civilization_id: civ_manager.current_turn_civ });
     
     // Update game state
-    if civ_manager.current_turn_civ == 1 { // Back to first civ = new turn
-        game_state.game_turn = civ_manager.turn_number;
-    }
+    game_state.game_turn = civ_manager.turn_number;
     
     // Determine current phase
-    if let Some(current_civ) = civ_manager.get_civilization(civ_manager.current_turn_civ) {
-        game_state.current_phase = if current_civ.is_player {
-            GamePhase::PlayerTurn
-        } else {
-            GamePhase::AITurn(current_civ.id)
-        };
+    if let Some(phase) = current_phase(civ_manager) {
+        game_state.current_phase = phase;
     }
     
     println!("Now: Turn {}, Civilization {} ({})",
              game_state.game_turn,
              civ_manager.current_turn_civ,
-             civ_manager.get_civilization(civ_manager.current_turn_civ)
-                 .map(|c| c.name.as_str())
-                 .unwrap_or("Unknown"));
-}
-
-// System for AI turns (simplified - just advances automatically for now)
-pub fn ai_turn_system(
-    mut game_state: ResMut<GameState>,
-    mut civ_manager: ResMut<CivilizationManager>,
-    mut city_query: Query<&mut City>,
-    mut unit_query: Query<&mut Unit>,
-    time: Res<Time>,
-) {
-    if !game_state.is_initialized {
-        return;
-    }
-    
-    // For now, AI turns advance automatically after a short delay
-    if let GamePhase::AITurn(_civ_id) = game_state.current_phase {
-        // In a real implementation, this would be replaced with AI logic
-        // For now, just auto-advance after 1 second
-        static mut AI_TIMER: f32 = 0.0;
-        unsafe {
-            AI_TIMER += time.delta_secs();
-            if AI_TIMER >= 1.0 {
-                AI_TIMER = 0.0;
-                advance_turn(&mut game_state, &mut civ_manager, &mut city_query, &mut unit_query);
-            }
-        }
-    }
+             civ_manager.civ_name(civ_manager.current_turn_civ));
 }
 
-// Helper system to display current turn info
+// Helper system to display current turn info, with the countdown on a timed turn
 pub fn display_turn_info(
     game_state: Res<GameState>,
     civ_manager: Res<CivilizationManager>,
-    mut turn_info_query: Query<&mut Text, With<TurnInfoText>>,
+    turn_timer: Res<TurnTimer>,
+    input_map: Res<InputMap>,
+    mut turn_info_query: Query<(&mut Text, &mut TextColor), With<TurnInfoText>>,
 ) {
-    if !game_state.is_initialized {
-        return;
-    }
-    
-    if let Ok(mut text) = turn_info_query.single_mut() {
-        let current_civ_name = civ_manager.get_civilization(civ_manager.current_turn_civ)
-            .map(|c| c.name.as_str())
-            .unwrap_or("Unknown");
+    if let Ok((mut text, mut color)) = turn_info_query.single_mut() {
+        let current_civ_name = civ_manager.civ_name(civ_manager.current_turn_civ);
         
         let phase_text = match game_state.current_phase {
             GamePhase::PlayerTurn => "Your Turn",
             GamePhase::AITurn(_) => "AI Turn",
+            GamePhase::BarbarianTurn => "Barbarian Turn",
+            GamePhase::GameOver => "Game Over",
             _ => "Processing",
         };
         
+        let hint = if civ_manager.is_observer_game() {
+            "Observing - SPACE pauses".to_string()
+        } else {
+            format!("Press {} to end turn", input_map.label(Action::EndTurn))
+        };
+        let remaining = turn_timer.remaining_secs();
+        let countdown = remaining.map(|seconds| format!(" - {}:{:02}", seconds.ceil() as u32 / 60, seconds.ceil() as u32 % 60)).unwrap_or_default();
         **text = format!(
-            "Turn {}: {} ({})\nPress SPACE/ENTER to end turn",
+            "Turn {}: {} ({}){}\n{}",
             game_state.game_turn,
             current_civ_name,
-            phase_text
+            phase_text,
+            countdown,
+            hint
         );
+        let warning = remaining.is_some_and(|seconds| seconds < TURN_TIMER_WARNING_SECONDS);
+        let new_color = if warning { Color::srgb(1.0, 0.35, 0.3) } else { Color::srgb(1.0, 1.0, 0.8) };
+        if color.0 != new_color {
+            color.0 = new_color;
+        }
     }
 }
 
@@ -507,7 +1081,7 @@ pub struct TurnInfoText;
 
 // Function to add turn info UI to the setup
 pub fn setup_turn_info_ui(mut commands: Commands) {
-    // Turn info display (top center)
+    // Turn info display (top center, below the header bar)
     commands.spawn((
         TurnInfoText,
         Text::new("Game Initializing..."),
@@ -519,11 +1093,49 @@ pub fn setup_turn_info_ui(mut commands: Commands) {
         TextColor(Color::srgb(1.0, 1.0, 0.8)),
         Node {
             position_type: PositionType::Absolute,
-            top: Val::Px(10.0),
-            left: Val::Percent(50.0),
-            margin: UiRect::left(Val::Px(-150.0)), // Center the 300px wide text
+            top: Val::Px(30.0),
+            left: Val::Px(0.0),
+            right: Val::Px(0.0),
+            margin: UiRect::horizontal(Val::Auto),
             width: Val::Px(300.0),
+            max_width: Val::Percent(96.0),
             ..default()
         },
     ));
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::map::spawn_world;
+    use crate::game::test_support::{game_app, run};
+    use crate::game::world_gen::WorldGenerator;
+    
+    // Every starting unit of every civilization placed on a generated map stands on a
+    // tile it can occupy, one unit to a tile
+    #[test]
+    fn starting_units_stand_on_legal_tiles() {
+        for (world_type, seed) in [(WorldType::Archipelago, 3), (WorldType::Continents, 11)] {
+            let mut app = game_app(Vec::new());
+            app.world_mut().resource_mut::<GameSettings>().opponents = 5;
+            let config = world_type.config();
+            let mut generator = WorldGenerator::with_seed(30, config.clone(), seed);
+            let world_tiles = generator.generate();
+            let sea_level = generator.sea_level;
+            run(&mut app, move |mut commands: Commands, mut index: ResMut<TileIndex>| {
+                spawn_world(&mut commands, &mut index, world_tiles.clone(), sea_level, config.clone());
+            });
+            run(&mut app, initialize_game);
+            
+            let placed = run(&mut app, |units: Query<&Unit>, tiles: TileLookup| {
+                units.iter().map(|unit| (unit.civilization_id, unit.hex_coord, unit.can_move_to(unit.hex_coord, &tiles))).collect::<Vec<_>>()
+            });
+            assert!(!placed.is_empty(), "no units spawned on {:?}", world_type);
+            for (civ_id, coord, legal) in &placed {
+                assert!(legal, "civ {} has a unit stuck at {:?} on {:?}", civ_id, coord, world_type);
+                let sharing = placed.iter().filter(|(other, at, _)| other == civ_id && at == coord).count();
+                assert_eq!(sharing, 1, "civ {} stacked units at {:?}", civ_id, coord);
+            }
+        }
+    }
+}
@@ -1,5 +1,7 @@
 use bevy::prelude::*;
+use super::culling::Cullable;
 use super::hex::HexCoord;
+use super::input_map::{Action, InputMap};
 use super::map::HEX_SIZE;
 use std::f32::consts::PI;
 
@@ -23,6 +25,11 @@ impl Default for GridSettings {
 #[derive(Component)]
 pub struct GridLine;
 
+// Parent of every grid line; the grid is shown or hidden as a whole through it, leaving
+// each line's own visibility to culling
+#[derive(Component)]
+pub struct GridRoot;
+
 #[derive(Resource)]
 pub struct GridAssets {
     pub mesh: Handle<Mesh>,
@@ -47,6 +54,13 @@ pub fn setup_grid_lines(
         material: material_handle.clone(),
     });
 
+    let visibility = if grid_settings.show_grid {
+        Visibility::Visible
+    } else {
+        Visibility::Hidden
+    };
+    let root = commands.spawn((GridRoot, Transform::default(), visibility)).id();
+
     // Generate grid lines for the same area as the map
     let map_radius = crate::game::map::MAP_RADIUS;
     let mut grid_lines_created = 0;
@@ -56,18 +70,13 @@ pub fn setup_grid_lines(
             let hex_coord = HexCoord::new(q, r);
             let world_pos = hex_coord.to_world_pos(HEX_SIZE);
 
-            let visibility = if grid_settings.show_grid {
-                Visibility::Visible
-            } else {
-                Visibility::Hidden
-            };
-
             commands.spawn((
                 GridLine,
+                Cullable,
                 Mesh2d(mesh_handle.clone()),
                 MeshMaterial2d(material_handle.clone()),
                 Transform::from_translation(Vec3::new(world_pos.x, world_pos.y, 0.5)), // Above tiles
-                visibility,
+                ChildOf(root),
             ));
             
             grid_lines_created += 1;
@@ -79,26 +88,36 @@ pub fn setup_grid_lines(
 
 pub fn toggle_grid_system(
     keyboard: Res<ButtonInput<KeyCode>>,
+    input_map: Res<InputMap>,
     mut grid_settings: ResMut<GridSettings>,
-    mut grid_query: Query<&mut Visibility, With<GridLine>>,
+    mut grid_query: Query<&mut Visibility, With<GridRoot>>,
 ) {
-    if keyboard.just_pressed(KeyCode::KeyG) {
+    if input_map.just_pressed(Action::ToggleGrid, &keyboard) {
         grid_settings.show_grid = !grid_settings.show_grid;
-        
-        let visibility = if grid_settings.show_grid {
-            Visibility::Visible
-        } else {
-            Visibility::Hidden
-        };
-        
-        for mut vis in grid_query.iter_mut() {
-            *vis = visibility;
-        }
+        show_grid_lines(&grid_settings, &mut grid_query);
         
         println!("Grid lines: {}", if grid_settings.show_grid { "ON" } else { "OFF" });
     }
 }
 
+// The grid's mesh and material go with the game they were made for
+pub fn release_grid_assets(mut commands: Commands) {
+    commands.remove_resource::<GridAssets>();
+}
+
+// Shows or hides the grid lines to match the settings
+pub fn show_grid_lines(grid_settings: &GridSettings, grid_query: &mut Query<&mut Visibility, With<GridRoot>>) {
+    let visibility = if grid_settings.show_grid {
+        Visibility::Visible
+    } else {
+        Visibility::Hidden
+    };
+    
+    for mut vis in grid_query.iter_mut() {
+        *vis = visibility;
+    }
+}
+
 fn create_hex_outline_mesh(size: f32) -> Mesh {
     let mut vertices = Vec::new();
     let mut indices = Vec::new();
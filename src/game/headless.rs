@@ -0,0 +1,151 @@
+use bevy::prelude::*;
+use serde::Serialize;
+use super::civilization::CivilizationManager;
+use super::game_initialization::{AppState, GameSettings};
+use super::reports::log_reports_system;
+use super::rng::GameRng;
+use super::statistics::{Demographic, StatsHistory, stats_history_system};
+use super::victory::GameResult;
+
+// Turns between the standings a headless run prints as it goes
+const REPORT_INTERVAL: u32 = 10;
+
+/// One civilization at one sampled turn
+#[derive(Serialize)]
+struct TurnStats {
+    turn: u32,
+    cities: u32,
+    population: u32,
+    techs: usize,
+    military: f32,
+    score: f32,
+}
+
+#[derive(Serialize)]
+struct CivReport {
+    id: u32,
+    name: String,
+    eliminated: bool,
+    turns: Vec<TurnStats>, // Every REPORT_INTERVAL turns, and the last turn played
+}
+
+/// What a headless run writes to `--stats-out`
+#[derive(Serialize)]
+struct HeadlessReport {
+    seed: u64,
+    final_turn: u32,
+    winner: Option<String>,
+    victory: Option<String>,
+    civilizations: Vec<CivReport>,
+}
+
+// The sample StatsHistory took at the end of `turn`, if it has one
+fn turn_stats(history: &StatsHistory, civ_id: u32, turn: u32) -> Option<TurnStats> {
+    let index = history.turns.iter().position(|sampled| *sampled == turn)?;
+    let civ = history.civs.get(&civ_id)?;
+    let demographics = civ.demographics.get(index)?;
+    Some(TurnStats {
+        turn,
+        cities: demographics.get(Demographic::Cities) as u32,
+        population: demographics.get(Demographic::Population) as u32,
+        techs: civ.techs.get(index).copied().unwrap_or(0),
+        military: demographics.get(Demographic::Military),
+        score: civ.score.get(index).copied().unwrap_or(0.0),
+    })
+}
+
+// Every civilization, eliminated ones included, in turn order
+fn every_civ(civ_manager: &CivilizationManager) -> Vec<u32> {
+    let mut civ_ids: Vec<u32> = civ_manager.civilizations.keys().copied().collect();
+    civ_ids.sort_unstable();
+    civ_ids
+}
+
+fn print_standings(history: &StatsHistory, civ_manager: &CivilizationManager, turn: u32) {
+    println!("=== TURN {} ===", turn);
+    println!("{:<24} {:>6} {:>6} {:>6} {:>9} {:>7}", "Civilization", "Cities", "Pop", "Techs", "Military", "Score");
+    for civ_id in every_civ(civ_manager) {
+        let Some(stats) = turn_stats(history, civ_id, turn) else { continue };
+        println!(
+            "{:<24} {:>6} {:>6} {:>6} {:>9.0} {:>7.0}",
+            civ_manager.civ_name(civ_id),
+            stats.cities,
+            stats.population,
+            stats.techs,
+            stats.military,
+            stats.score,
+        );
+    }
+}
+
+// Prints every civilization's standing each time another REPORT_INTERVAL turns are sampled
+pub fn headless_progress_system(history: Res<StatsHistory>, civ_manager: Res<CivilizationManager>) {
+    if !history.is_changed() {
+        return;
+    }
+    if let Some(turn) = history.last_turn()
+        && turn.is_multiple_of(REPORT_INTERVAL)
+    {
+        print_standings(&history, &civ_manager, turn);
+    }
+}
+
+// Once the game is decided: prints the final standings and the winner, writes the report
+// if one was asked for, and quits
+pub fn finish_headless_run(
+    history: Res<StatsHistory>,
+    civ_manager: Res<CivilizationManager>,
+    result: Option<Res<GameResult>>,
+    settings: Res<GameSettings>,
+    rng: Res<GameRng>,
+    mut exit: EventWriter<AppExit>,
+) {
+    let final_turn = history.last_turn().unwrap_or(0);
+    if !final_turn.is_multiple_of(REPORT_INTERVAL) {
+        print_standings(&history, &civ_manager, final_turn);
+    }
+    let winner = result.as_ref().map(|result| civ_manager.civ_name(result.winner).to_string());
+    let victory = result.as_ref().map(|result| result.victory.get_name().to_string());
+    if let (Some(winner), Some(victory)) = (&winner, &victory) {
+        println!("=== {} wins: {} (seed {}) ===", winner, victory, rng.seed());
+    }
+    
+    if let Some(path) = &settings.stats_out {
+        let civilizations = every_civ(&civ_manager)
+            .into_iter()
+            .map(|civ_id| CivReport {
+                id: civ_id,
+                name: civ_manager.civ_name(civ_id).to_string(),
+                eliminated: civ_manager.get_civilization(civ_id).is_some_and(|civ| civ.eliminated),
+                turns: history
+                    .turns
+                    .iter()
+                    .filter(|turn| turn.is_multiple_of(REPORT_INTERVAL) || **turn == final_turn)
+                    .filter_map(|turn| turn_stats(&history, civ_id, *turn))
+                    .collect(),
+            })
+            .collect();
+        let report = HeadlessReport { seed: rng.seed(), final_turn, winner, victory, civilizations };
+        let written = serde_json::to_string_pretty(&report)
+            .map_err(|error| error.to_string())
+            .and_then(|json| std::fs::write(path, json).map_err(|error| error.to_string()));
+        match written {
+            Ok(()) => println!("Wrote the report to {}", path.display()),
+            Err(error) => println!("Could not write the report to {}: {}", path.display(), error),
+        }
+    }
+    exit.write(AppExit::Success);
+}
+
+/// Runs a game with nobody watching: reports the standings as it goes and quits once
+/// the game is decided
+pub struct HeadlessPlugin;
+
+impl Plugin for HeadlessPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Update, headless_progress_system.after(stats_history_system).run_if(in_state(AppState::InGame)))
+            .add_systems(Update, log_reports_system.run_if(in_state(AppState::InGame)))
+            .add_systems(OnEnter(AppState::GameOver), finish_headless_run);
+    }
+}
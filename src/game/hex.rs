@@ -1,7 +1,8 @@
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
 /// Axial coordinates for hex grid (q, r)
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Component)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Component, Serialize, Deserialize)]
 pub struct HexCoord {
     pub q: i32,
     pub r: i32,
@@ -19,6 +20,15 @@ impl HexCoord {
         Vec2::new(x, y)
     }
 
+    /// Endpoints of the edge this hex shares with `neighbor`, in world space
+    pub fn edge_toward(self, neighbor: HexCoord, hex_size: f32) -> (Vec2, Vec2) {
+        let center = self.to_world_pos(hex_size);
+        let toward = (neighbor.to_world_pos(hex_size) - center).normalize();
+        let midpoint = center + toward * hex_size * 3.0_f32.sqrt() / 2.0;
+        let along = toward.perp() * hex_size / 2.0;
+        (midpoint - along, midpoint + along)
+    }
+
     /// Convert world position to hex coordinates
     pub fn from_world_pos(world_pos: Vec2, hex_size: f32) -> Self {
         // Convert to fractional axial coordinates
@@ -62,4 +72,51 @@ impl HexCoord {
         }
         neighbors
     }
-}
\ No newline at end of file
+
+    /// All coordinates exactly `radius` steps away, walking around the ring
+    pub fn ring(self, radius: i32) -> Vec<HexCoord> {
+        if radius <= 0 {
+            return vec![self];
+        }
+        
+        let mut ring = Vec::with_capacity(6 * radius as usize);
+        // Start at the south-west corner and walk each of the six sides
+        let mut current = HexCoord::new(self.q - radius, self.r + radius);
+        let directions = [
+            (1, 0), (1, -1), (0, -1), (-1, 0), (-1, 1), (0, 1)
+        ];
+        for (dq, dr) in directions {
+            for _ in 0..radius {
+                ring.push(current);
+                current = HexCoord::new(current.q + dq, current.r + dr);
+            }
+        }
+        ring
+    }
+
+    /// Hex distance (number of steps) between two coordinates
+    pub fn distance(self, other: HexCoord) -> i32 {
+        let dq = (self.q - other.q).abs();
+        let dr = (self.r - other.r).abs();
+        let ds = (-(self.q + self.r) + (other.q + other.r)).abs();
+        dq.max(dr).max(ds)
+    }
+
+    /// Hexes on the straight line to `other`, both ends included
+    pub fn line_to(self, other: HexCoord) -> Vec<HexCoord> {
+        let steps = self.distance(other);
+        if steps == 0 {
+            return vec![self];
+        }
+        
+        // Nudge off exact hex edges so ties round consistently
+        let (q0, r0) = (self.q as f32 + 1e-6, self.r as f32 + 1e-6);
+        let (q1, r1) = (other.q as f32 + 1e-6, other.r as f32 + 1e-6);
+        (0..=steps)
+            .map(|step| {
+                let t = step as f32 / steps as f32;
+                Self::round_hex(q0 + (q1 - q0) * t, r0 + (r1 - r0) * t)
+            })
+            .collect()
+    }
+}
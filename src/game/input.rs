@@ -0,0 +1,491 @@
+use bevy::prelude::*;
+use bevy::input::ButtonState;
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::window::{PrimaryWindow, SystemCursorIcon};
+use bevy::winit::cursor::CursorIcon;
+use super::hex::HexCoord;
+use super::map::HEX_SIZE;
+use super::units::{Unit, UnitSelection};
+use super::game_initialization::GameSettings;
+
+// Keys that step the selected unit one hex, with the axial offset each one moves.
+// Hexes are pointy-top, so the six neighbors lie E, W, NE, NW, SE and SW.
+const NUMPAD_UNIT_MOVES: [(KeyCode, (i32, i32)); 6] = [
+    (KeyCode::Numpad6, (1, 0)),   // East
+    (KeyCode::Numpad4, (-1, 0)),  // West
+    (KeyCode::Numpad9, (0, 1)),   // North-east
+    (KeyCode::Numpad7, (-1, 1)),  // North-west
+    (KeyCode::Numpad3, (1, -1)),  // South-east
+    (KeyCode::Numpad1, (0, -1)),  // South-west
+];
+
+// Letter layout around S, only active while Alt is held so it doesn't fight the letter hotkeys
+const LETTER_UNIT_MOVES: [(KeyCode, (i32, i32)); 6] = [
+    (KeyCode::KeyD, (1, 0)),
+    (KeyCode::KeyA, (-1, 0)),
+    (KeyCode::KeyE, (0, 1)),
+    (KeyCode::KeyQ, (-1, 1)),
+    (KeyCode::KeyC, (1, -1)),
+    (KeyCode::KeyZ, (0, -1)),
+];
+
+/// Mouse clicks resolved to hexes once per frame. Gameplay systems `take` the intent
+/// they handle, so a single click is never consumed by two systems.
+///
+/// Left click selects (or picks an attack target in `InputMode::AttackTargeting`, where
+/// to go in `InputMode::MoveTargeting`, or a tile to work in `InputMode::CityManagement`),
+/// right click issues an order for the current selection.
+#[derive(Resource, Default)]
+pub struct ClickIntent {
+    pub select: Option<HexCoord>,
+    pub order: Option<HexCoord>,
+    pub target: Option<HexCoord>,
+    pub tile: Option<HexCoord>,
+}
+
+impl ClickIntent {
+    pub fn take_select(&mut self) -> Option<HexCoord> {
+        self.select.take()
+    }
+
+    pub fn take_order(&mut self) -> Option<HexCoord> {
+        self.order.take()
+    }
+
+    pub fn take_target(&mut self) -> Option<HexCoord> {
+        self.target.take()
+    }
+
+    pub fn take_tile(&mut self) -> Option<HexCoord> {
+        self.tile.take()
+    }
+
+    // Files a click on `clicked_hex` under the intent the input mode gives it. While
+    // targeting, a left click never reaches unit selection. A move is a one-off right
+    // click. The city screen gives no orders at all.
+    fn route(&mut self, clicked_hex: HexCoord, left: bool, right: bool, input_mode: &mut InputMode) {
+        if let InputMode::CityManagement(_) = *input_mode {
+            if left {
+                self.tile = Some(clicked_hex);
+            }
+            return;
+        }
+        if left && *input_mode == InputMode::AttackTargeting {
+            self.target = Some(clicked_hex);
+        } else if left && *input_mode == InputMode::MoveTargeting {
+            self.order = Some(clicked_hex);
+            *input_mode = InputMode::Normal;
+        } else if left {
+            self.select = Some(clicked_hex);
+        }
+        if right {
+            self.order = Some(clicked_hex);
+        }
+    }
+}
+
+/// What a left click on the map means right now
+#[derive(Resource, Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputMode {
+    #[default]
+    Normal,
+    // Entered with A: the next left click on an enemy picks it as the attack target
+    AttackTargeting,
+    // Entered from the Move button: the next left click orders the selected unit there
+    MoveTargeting,
+    // The city screen: the camera stays on the city and left clicks move its citizens
+    // between tiles
+    CityManagement(Entity),
+}
+
+/// Where the mouse cursor is this frame, in screen space, in the world and as a hex
+#[derive(Resource, Default)]
+pub struct CursorState {
+    pub screen_pos: Option<Vec2>,
+    pub world_pos: Option<Vec2>,
+    pub hex: Option<HexCoord>,
+    pub over_ui: bool,
+}
+
+/// True while the modifier for letter-key unit movement is held; other systems
+/// bound to those letters should ignore them meanwhile
+pub fn unit_move_modifier_held(keyboard: &ButtonInput<KeyCode>) -> bool {
+    keyboard.any_pressed([KeyCode::AltLeft, KeyCode::AltRight])
+}
+
+/// True while Ctrl is held for the save, load and undo shortcuts; the camera ignores its
+/// letter keys meanwhile
+pub fn command_modifier_held(keyboard: &ButtonInput<KeyCode>) -> bool {
+    keyboard.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight])
+}
+
+/// True while the cursor is over an interactive UI node, so map input should be ignored
+pub fn cursor_over_ui(interactions: &Query<&Interaction>) -> bool {
+    interactions.iter().any(|interaction| *interaction != Interaction::None)
+}
+
+/// Run condition: true while the cursor is over a UI node. Map systems driven by the
+/// mouse run with `.run_if(not(ui_wants_input))`, leaving the pointer to the panels.
+pub fn ui_wants_input(cursor: Res<CursorState>) -> bool {
+    cursor.over_ui
+}
+
+// Runs in PreUpdate after UI focus so every Update system sees this frame's clicks
+pub fn mouse_intent_system(
+    windows: Query<&Window>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    interactions: Query<&Interaction>,
+    mut input_mode: ResMut<InputMode>,
+    mut click_intent: ResMut<ClickIntent>,
+    mut cursor: ResMut<CursorState>,
+) {
+    // Unconsumed intents never carry over to the next frame
+    click_intent.select = None;
+    click_intent.order = None;
+    click_intent.target = None;
+    click_intent.tile = None;
+
+    let Ok(window) = windows.single() else { return };
+    let Ok((camera, camera_transform)) = camera_query.single() else { return };
+
+    cursor.screen_pos = window.cursor_position();
+    cursor.world_pos = cursor
+        .screen_pos
+        .and_then(|screen_pos| camera.viewport_to_world_2d(camera_transform, screen_pos).ok());
+    cursor.hex = cursor.world_pos.map(|world_pos| HexCoord::from_world_pos(world_pos, HEX_SIZE));
+    cursor.over_ui = cursor_over_ui(&interactions);
+
+    let left = mouse_input.just_pressed(MouseButton::Left);
+    let right = mouse_input.just_pressed(MouseButton::Right);
+    if (!left && !right) || cursor.over_ui {
+        return;
+    }
+
+    let Some(clicked_hex) = cursor.hex else { return };
+    click_intent.route(clicked_hex, left, right, &mut input_mode);
+}
+
+// A crosshair over the map while an attack target is being picked, and the move cursor
+// while a destination is
+pub fn attack_cursor_system(
+    mut commands: Commands,
+    input_mode: Res<InputMode>,
+    window_query: Query<Entity, With<PrimaryWindow>>,
+) {
+    if !input_mode.is_changed() {
+        return;
+    }
+    let Ok(window) = window_query.single() else { return };
+    
+    let icon = match *input_mode {
+        InputMode::Normal => SystemCursorIcon::Default,
+        InputMode::AttackTargeting => SystemCursorIcon::Crosshair,
+        InputMode::MoveTargeting => SystemCursorIcon::Move,
+        InputMode::CityManagement(_) => SystemCursorIcon::Pointer,
+    };
+    commands.entity(window).insert(CursorIcon::from(icon));
+}
+
+// Turns numpad (or Alt + Q/E/A/D/Z/C) presses into a right-click order on the
+// neighboring hex, so keyboard moves and attacks follow exactly the same rules as clicks
+pub fn keyboard_unit_move_system(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    settings: Res<GameSettings>,
+    unit_selection: Res<UnitSelection>,
+    unit_query: Query<&Unit>,
+    mut click_intent: ResMut<ClickIntent>,
+) {
+    if !settings.keyboard_unit_movement {
+        return;
+    }
+    let Some(unit) = unit_selection.selected_unit.and_then(|entity| unit_query.get(entity).ok()) else { return };
+    
+    let letters_active = unit_move_modifier_held(&keyboard);
+    let step = NUMPAD_UNIT_MOVES
+        .iter()
+        .chain(LETTER_UNIT_MOVES.iter().filter(|_| letters_active))
+        .find(|(key, _)| keyboard.just_pressed(*key))
+        .map(|(_, offset)| *offset);
+    
+    if let Some((dq, dr)) = step {
+        click_intent.order = Some(HexCoord::new(unit.hex_coord.q + dq, unit.hex_coord.r + dr));
+    }
+}
+
+/// What a line of typed text is for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextEntryTarget {
+    CityName(Entity),
+    Console, // A developer console command
+}
+
+impl TextEntryTarget {
+    fn max_length(&self) -> usize {
+        match self {
+            TextEntryTarget::CityName(_) => MAX_TEXT_ENTRY_LENGTH,
+            TextEntryTarget::Console => MAX_CONSOLE_LINE_LENGTH,
+        }
+    }
+}
+
+/// A line of text being typed by the player. While a target is set every key press goes
+/// into the buffer, and other systems see no keyboard input at all.
+#[derive(Resource, Default)]
+pub struct TextEntry {
+    pub target: Option<TextEntryTarget>,
+    pub prompt: String,
+    pub buffer: String,
+}
+
+impl TextEntry {
+    pub fn is_active(&self) -> bool {
+        self.target.is_some()
+    }
+
+    pub fn begin(&mut self, target: TextEntryTarget, prompt: String, initial: String) {
+        self.target = Some(target);
+        self.prompt = prompt;
+        self.buffer = initial;
+    }
+
+    fn finish(&mut self) {
+        self.target = None;
+        self.prompt.clear();
+        self.buffer.clear();
+    }
+}
+
+/// Sent when the player confirms a text entry with Enter
+#[derive(Event)]
+pub struct TextEntrySubmitted {
+    pub target: TextEntryTarget,
+    pub text: String,
+}
+
+#[derive(Component)]
+pub struct TextEntryText;
+
+const MAX_TEXT_ENTRY_LENGTH: usize = 32;
+const MAX_CONSOLE_LINE_LENGTH: usize = 80;
+
+// Runs in PreUpdate before the other keyboard systems. Enter submits the entry,
+// Escape cancels it (as does the backquote that opens the console); either way the key
+// never reaches the rest of the game.
+pub fn text_entry_system(
+    mut key_events: EventReader<KeyboardInput>,
+    mut keyboard: ResMut<ButtonInput<KeyCode>>,
+    mut text_entry: ResMut<TextEntry>,
+    mut submitted: EventWriter<TextEntrySubmitted>,
+) {
+    // Always drain, so the key that opened the entry isn't typed into it
+    let presses: Vec<Key> = key_events
+        .read()
+        .filter(|event| event.state == ButtonState::Pressed)
+        .map(|event| event.logical_key.clone())
+        .collect();
+    let Some(target) = text_entry.target else { return };
+    
+    for key in presses {
+        match key {
+            Key::Enter => {
+                let text = text_entry.buffer.trim().to_string();
+                if !text.is_empty() {
+                    submitted.write(TextEntrySubmitted { target, text });
+                }
+                text_entry.finish();
+                break;
+            }
+            Key::Escape => {
+                text_entry.finish();
+                break;
+            }
+            Key::Character(characters) if target == TextEntryTarget::Console && characters == "`" => {
+                text_entry.finish();
+                break;
+            }
+            Key::Backspace => {
+                text_entry.buffer.pop();
+            }
+            Key::Space => text_entry.buffer.push(' '),
+            Key::Character(characters) => {
+                for character in characters.chars().filter(|c| !c.is_control()) {
+                    if text_entry.buffer.chars().count() < target.max_length() {
+                        text_entry.buffer.push(character);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    
+    keyboard.reset_all();
+}
+
+pub fn text_entry_display_system(
+    text_entry: Res<TextEntry>,
+    mut text_query: Query<(&mut Text, &mut Visibility), With<TextEntryText>>,
+) {
+    if !text_entry.is_changed() {
+        return;
+    }
+    let Ok((mut text, mut visibility)) = text_query.single_mut() else { return };
+    
+    // The console shows its own entry line
+    if text_entry.is_active() && text_entry.target != Some(TextEntryTarget::Console) {
+        **text = format!("{}: {}_\n(Enter to confirm, Esc to cancel)", text_entry.prompt, text_entry.buffer);
+        *visibility = Visibility::Visible;
+    } else {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+pub fn setup_text_entry_ui(mut commands: Commands) {
+    commands.spawn((
+        TextEntryText,
+        Text::new(""),
+        TextFont {
+            font_size: 16.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.85)),
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Percent(40.0),
+            top: Val::Px(60.0),
+            padding: UiRect::all(Val::Px(8.0)),
+            ..default()
+        },
+        Visibility::Hidden,
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::schedule::ScheduleConfigs;
+    use bevy::ecs::system::ScheduleSystem;
+    use crate::game::cities::{CitySelection, UnitType};
+    use crate::game::civilization::{CivilizationManager, create_default_civilizations};
+    use crate::game::combat::{CombatState, attack_click_system};
+    use crate::game::combat_log::CombatLog;
+    use crate::game::diplomacy::WarStrength;
+    use crate::game::map::TerrainType;
+    use crate::game::test_support::{area, game_app};
+    use crate::game::unit_actions::UnitActionEvent;
+    use crate::game::units::unit_selection_system;
+    
+    const ORIGIN: HexCoord = HexCoord { q: 0, r: 0 };
+    const ENEMY: HexCoord = HexCoord { q: 1, r: 0 };
+    
+    // The player's Warrior on ORIGIN, selected, next to an enemy Warrior, with unit
+    // selection and combat running in the order `systems` gives them
+    fn targeting_app(systems: ScheduleConfigs<ScheduleSystem>) -> (App, Entity) {
+        let mut app = game_app(area(ORIGIN, 3, TerrainType::TemperateGrassland));
+        let mut manager = CivilizationManager::default();
+        for civ in create_default_civilizations().into_iter().take(2) {
+            manager.add_civilization(civ);
+        }
+        manager.diplomacy.declare_war((1, WarStrength::default()), (2, WarStrength::default()), 0);
+        app.insert_resource(manager)
+            .init_resource::<ButtonInput<KeyCode>>()
+            .init_resource::<ClickIntent>()
+            .init_resource::<InputMode>()
+            .init_resource::<UnitSelection>()
+            .init_resource::<CitySelection>()
+            .init_resource::<CombatLog>()
+            .add_event::<UnitActionEvent>()
+            .add_systems(Update, systems);
+        let warrior = app.world_mut().spawn(Unit::new(UnitType::Warrior, 1, ORIGIN)).id();
+        app.world_mut().spawn(Unit::new(UnitType::Warrior, 2, ENEMY));
+        app.world_mut().resource_mut::<UnitSelection>().selected_unit = Some(warrior);
+        (app, warrior)
+    }
+    
+    fn both_orders() -> [ScheduleConfigs<ScheduleSystem>; 2] {
+        [
+            (unit_selection_system, attack_click_system).chain(),
+            (attack_click_system, unit_selection_system).chain(),
+        ]
+    }
+    
+    // A left click on `hex` this frame, routed as mouse_intent_system routes it
+    fn left_click(app: &mut App, hex: HexCoord) {
+        let world = app.world_mut();
+        let mut input_mode = *world.resource::<InputMode>();
+        world.resource_mut::<ClickIntent>().route(hex, true, false, &mut input_mode);
+        *world.resource_mut::<InputMode>() = input_mode;
+        app.update();
+    }
+    
+    // While targeting, a left click picks the attack target and never reaches unit
+    // selection, whichever of the two systems runs first
+    #[test]
+    fn targeting_clicks_skip_selection_in_either_order() {
+        for (index, systems) in both_orders().into_iter().enumerate() {
+            let (mut app, warrior) = targeting_app(systems);
+            *app.world_mut().resource_mut::<InputMode>() = InputMode::AttackTargeting;
+            
+            // A miss keeps the mode and the selection
+            left_click(&mut app, HexCoord { q: -1, r: 0 });
+            assert_eq!(*app.world().resource::<InputMode>(), InputMode::AttackTargeting, "order {}", index);
+            assert_eq!(app.world().resource::<UnitSelection>().selected_unit, Some(warrior), "order {}", index);
+            assert!(app.world().resource::<CombatState>().combat_preview.is_none(), "order {}", index);
+            
+            left_click(&mut app, ENEMY);
+            let preview = app.world().resource::<CombatState>().combat_preview.clone();
+            assert_eq!(preview.map(|preview| preview.attacker_entity), Some(warrior), "order {}", index);
+            assert_eq!(*app.world().resource::<InputMode>(), InputMode::Normal, "order {}", index);
+            assert_eq!(app.world().resource::<UnitSelection>().selected_unit, Some(warrior), "order {}", index);
+        }
+    }
+    
+    // Outside targeting the same click is a selection: the enemy hex has nothing of the
+    // player's, so the selection clears and no attack is previewed
+    #[test]
+    fn normal_clicks_select_in_either_order() {
+        for (index, systems) in both_orders().into_iter().enumerate() {
+            let (mut app, _) = targeting_app(systems);
+            left_click(&mut app, ENEMY);
+            assert_eq!(app.world().resource::<UnitSelection>().selected_unit, None, "order {}", index);
+            assert!(app.world().resource::<CombatState>().combat_preview.is_none(), "order {}", index);
+        }
+    }
+    
+    #[test]
+    fn clicks_follow_the_input_mode() {
+        let routed = |mode: InputMode, left: bool, right: bool| {
+            let (mut intent, mut mode) = (ClickIntent::default(), mode);
+            intent.route(ENEMY, left, right, &mut mode);
+            ((intent.select, intent.order, intent.target, intent.tile), mode)
+        };
+        let city = Entity::from_raw(1);
+        assert_eq!(routed(InputMode::Normal, true, false), ((Some(ENEMY), None, None, None), InputMode::Normal));
+        assert_eq!(routed(InputMode::Normal, false, true), ((None, Some(ENEMY), None, None), InputMode::Normal));
+        assert_eq!(routed(InputMode::AttackTargeting, true, false), ((None, None, Some(ENEMY), None), InputMode::AttackTargeting));
+        assert_eq!(routed(InputMode::MoveTargeting, true, false), ((None, Some(ENEMY), None, None), InputMode::Normal));
+        assert_eq!(routed(InputMode::CityManagement(city), true, true), ((None, None, None, Some(ENEMY)), InputMode::CityManagement(city)));
+    }
+    
+    // Selection and attack clicks don't run while the cursor is over a panel: a right
+    // click on the enemy waits until the cursor is back on the map
+    #[test]
+    fn map_clicks_wait_while_the_cursor_is_over_the_ui() {
+        let gated = (unit_selection_system, attack_click_system).chain().run_if(not(ui_wants_input));
+        let (mut app, warrior) = targeting_app(gated);
+        app.init_resource::<CursorState>();
+        app.world_mut().resource_mut::<CursorState>().over_ui = true;
+        
+        app.world_mut().resource_mut::<ClickIntent>().order = Some(ENEMY);
+        app.update();
+        assert_eq!(app.world().resource::<ClickIntent>().order, Some(ENEMY));
+        assert!(app.world().resource::<CombatState>().combat_preview.is_none());
+        
+        app.world_mut().resource_mut::<CursorState>().over_ui = false;
+        app.update();
+        assert_eq!(app.world().resource::<ClickIntent>().order, None);
+        let preview = app.world().resource::<CombatState>().combat_preview.clone();
+        assert_eq!(preview.map(|preview| preview.attacker_entity), Some(warrior));
+    }
+}
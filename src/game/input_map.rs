@@ -0,0 +1,440 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Everything the player can do from the keyboard that they can also rebind. Dialog keys
+/// (Escape, Enter in a prompt), the numpad unit steps and the menus stay fixed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Action {
+    EndTurn,
+    NextUnit,
+    CityScreen,
+    Attack,
+    FoundCity,
+    Fortify,
+    BuildImprovement,
+    UseAbility,
+    Upgrade,
+    Skip,
+    Sentry,
+    Automate,
+    Disband,
+    Rename,
+    Undo,
+    PanUp,
+    PanDown,
+    PanLeft,
+    PanRight,
+    CenterOnCapital,
+    CenterOnSelection,
+    ToggleGrid,
+    ToggleElevation,
+    ElevationFainter,
+    ElevationStronger,
+    CycleInfoMode,
+    ToggleHelp,
+    OpenGuide,
+    ToggleGameStatus,
+    ToggleUnitStatus,
+    ToggleDebugInfo,
+    ToggleTurnSummary,
+    ToggleDemographics,
+    ToggleCombatEffects,
+    ToggleUnitList,
+    ToggleEmpireOverview,
+    ToggleResources,
+    ToggleDiplomacy,
+    ToggleGlyphSheet,
+    ToggleTechTree,
+    ToggleCombatLog,
+    ToggleMessages,
+    ToggleTurnReport,
+    ToggleConsole,
+    QuickSave,
+    LoadGame,
+}
+
+impl Action {
+    pub const ALL: [Action; 46] = [
+        Action::EndTurn,
+        Action::NextUnit,
+        Action::CityScreen,
+        Action::Attack,
+        Action::FoundCity,
+        Action::Fortify,
+        Action::BuildImprovement,
+        Action::UseAbility,
+        Action::Upgrade,
+        Action::Skip,
+        Action::Sentry,
+        Action::Automate,
+        Action::Disband,
+        Action::Rename,
+        Action::Undo,
+        Action::PanUp,
+        Action::PanDown,
+        Action::PanLeft,
+        Action::PanRight,
+        Action::CenterOnCapital,
+        Action::CenterOnSelection,
+        Action::ToggleGrid,
+        Action::ToggleElevation,
+        Action::ElevationFainter,
+        Action::ElevationStronger,
+        Action::CycleInfoMode,
+        Action::ToggleHelp,
+        Action::OpenGuide,
+        Action::ToggleGameStatus,
+        Action::ToggleUnitStatus,
+        Action::ToggleDebugInfo,
+        Action::ToggleTurnSummary,
+        Action::ToggleDemographics,
+        Action::ToggleCombatEffects,
+        Action::ToggleUnitList,
+        Action::ToggleEmpireOverview,
+        Action::ToggleResources,
+        Action::ToggleDiplomacy,
+        Action::ToggleGlyphSheet,
+        Action::ToggleTechTree,
+        Action::ToggleCombatLog,
+        Action::ToggleMessages,
+        Action::ToggleTurnReport,
+        Action::ToggleConsole,
+        Action::QuickSave,
+        Action::LoadGame,
+    ];
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Action::EndTurn => "End Turn",
+            Action::NextUnit => "Next Unit",
+            Action::CityScreen => "City Screen",
+            Action::Attack => "Attack",
+            Action::FoundCity => "Found City",
+            Action::Fortify => "Fortify",
+            Action::BuildImprovement => "Build Improvement",
+            Action::UseAbility => "Use Ability",
+            Action::Upgrade => "Upgrade",
+            Action::Skip => "Skip Turn",
+            Action::Sentry => "Sentry",
+            Action::Automate => "Automate Worker",
+            Action::Disband => "Disband",
+            Action::Rename => "Rename Unit or City",
+            Action::Undo => "Undo Last Move",
+            Action::PanUp => "Camera Up",
+            Action::PanDown => "Camera Down",
+            Action::PanLeft => "Camera Left",
+            Action::PanRight => "Camera Right",
+            Action::CenterOnCapital => "Center on Capital",
+            Action::CenterOnSelection => "Center on Selection",
+            Action::ToggleGrid => "Toggle Grid",
+            Action::ToggleElevation => "Toggle Elevation",
+            Action::ElevationFainter => "Elevation Fainter",
+            Action::ElevationStronger => "Elevation Stronger",
+            Action::CycleInfoMode => "Info Modes",
+            Action::ToggleHelp => "Toggle Help",
+            Action::OpenGuide => "Game Guide",
+            Action::ToggleGameStatus => "Game Status",
+            Action::ToggleUnitStatus => "Unit Status",
+            Action::ToggleDebugInfo => "Debug Info",
+            Action::ToggleTurnSummary => "Turn Summary",
+            Action::ToggleDemographics => "Demographics",
+            Action::ToggleCombatEffects => "Combat Effects",
+            Action::ToggleUnitList => "Units List",
+            Action::ToggleEmpireOverview => "Empire Overview",
+            Action::ToggleResources => "Resources",
+            Action::ToggleDiplomacy => "Diplomacy",
+            Action::ToggleGlyphSheet => "Glyph Sheet",
+            Action::ToggleTechTree => "Tech Tree",
+            Action::ToggleCombatLog => "Combat Log",
+            Action::ToggleMessages => "Message History",
+            Action::ToggleTurnReport => "Turn Report",
+            Action::ToggleConsole => "Console",
+            Action::QuickSave => "Save Game",
+            Action::LoadGame => "Load Game",
+        }
+    }
+
+    // Whether the action is checked while its key is held rather than on the press
+    fn is_held(&self) -> bool {
+        matches!(self, Action::PanUp | Action::PanDown | Action::PanLeft | Action::PanRight)
+    }
+}
+
+// The keys a chord can be written with, and the names they're saved under. Escape and
+// the modifiers themselves are left out: Escape always backs out of things, and the
+// modifiers only ever qualify another key.
+const KEY_NAMES: [(KeyCode, &str); 74] = [
+    (KeyCode::KeyA, "A"), (KeyCode::KeyB, "B"), (KeyCode::KeyC, "C"), (KeyCode::KeyD, "D"),
+    (KeyCode::KeyE, "E"), (KeyCode::KeyF, "F"), (KeyCode::KeyG, "G"), (KeyCode::KeyH, "H"),
+    (KeyCode::KeyI, "I"), (KeyCode::KeyJ, "J"), (KeyCode::KeyK, "K"), (KeyCode::KeyL, "L"),
+    (KeyCode::KeyM, "M"), (KeyCode::KeyN, "N"), (KeyCode::KeyO, "O"), (KeyCode::KeyP, "P"),
+    (KeyCode::KeyQ, "Q"), (KeyCode::KeyR, "R"), (KeyCode::KeyS, "S"), (KeyCode::KeyT, "T"),
+    (KeyCode::KeyU, "U"), (KeyCode::KeyV, "V"), (KeyCode::KeyW, "W"), (KeyCode::KeyX, "X"),
+    (KeyCode::KeyY, "Y"), (KeyCode::KeyZ, "Z"),
+    (KeyCode::Digit0, "0"), (KeyCode::Digit1, "1"), (KeyCode::Digit2, "2"), (KeyCode::Digit3, "3"),
+    (KeyCode::Digit4, "4"), (KeyCode::Digit5, "5"), (KeyCode::Digit6, "6"), (KeyCode::Digit7, "7"),
+    (KeyCode::Digit8, "8"), (KeyCode::Digit9, "9"),
+    (KeyCode::F1, "F1"), (KeyCode::F2, "F2"), (KeyCode::F3, "F3"), (KeyCode::F4, "F4"),
+    (KeyCode::F5, "F5"), (KeyCode::F6, "F6"), (KeyCode::F7, "F7"), (KeyCode::F8, "F8"),
+    (KeyCode::F9, "F9"), (KeyCode::F10, "F10"), (KeyCode::F11, "F11"), (KeyCode::F12, "F12"),
+    (KeyCode::Space, "Space"), (KeyCode::Enter, "Enter"), (KeyCode::NumpadEnter, "NumpadEnter"),
+    (KeyCode::Tab, "Tab"), (KeyCode::Backspace, "Backspace"), (KeyCode::Delete, "Delete"),
+    (KeyCode::Insert, "Insert"), (KeyCode::Home, "Home"), (KeyCode::End, "End"),
+    (KeyCode::PageUp, "PageUp"), (KeyCode::PageDown, "PageDown"),
+    (KeyCode::ArrowUp, "Up"), (KeyCode::ArrowDown, "Down"),
+    (KeyCode::ArrowLeft, "Left"), (KeyCode::ArrowRight, "Right"),
+    (KeyCode::BracketLeft, "["), (KeyCode::BracketRight, "]"), (KeyCode::Backquote, "`"),
+    (KeyCode::Minus, "-"), (KeyCode::Equal, "="), (KeyCode::Comma, ","), (KeyCode::Period, "."),
+    (KeyCode::Slash, "/"), (KeyCode::Semicolon, ";"), (KeyCode::Quote, "'"), (KeyCode::Backslash, "\\"),
+];
+
+/// A key together with the modifiers that must be held with it, written "Shift+F",
+/// "Ctrl+Z" or just "G" in the settings file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct KeyChord {
+    pub key: KeyCode,
+    pub shift: bool,
+    pub ctrl: bool,
+    pub alt: bool,
+}
+
+impl KeyChord {
+    pub const fn key(key: KeyCode) -> Self {
+        Self { key, shift: false, ctrl: false, alt: false }
+    }
+
+    pub const fn shift(key: KeyCode) -> Self {
+        Self { key, shift: true, ctrl: false, alt: false }
+    }
+
+    pub const fn ctrl(key: KeyCode) -> Self {
+        Self { key, shift: false, ctrl: true, alt: false }
+    }
+
+    // Whether `key` can be bound at all
+    pub fn is_bindable(key: KeyCode) -> bool {
+        KEY_NAMES.iter().any(|(code, _)| *code == key)
+    }
+
+    // The chord made by pressing `key` with whatever modifiers are held right now
+    pub fn from_keyboard(key: KeyCode, keyboard: &ButtonInput<KeyCode>) -> Self {
+        let held = Modifiers::read(keyboard);
+        Self { key, shift: held.shift, ctrl: held.ctrl, alt: held.alt }
+    }
+
+    // Pressed this frame with exactly these modifiers, so Shift+F doesn't also found a city
+    fn just_pressed(&self, keyboard: &ButtonInput<KeyCode>, held: Modifiers) -> bool {
+        keyboard.just_pressed(self.key) && held == Modifiers { shift: self.shift, ctrl: self.ctrl, alt: self.alt }
+    }
+
+    // Held down, along with at least these modifiers
+    fn pressed(&self, keyboard: &ButtonInput<KeyCode>, held: Modifiers) -> bool {
+        keyboard.pressed(self.key) && (!self.shift || held.shift) && (!self.ctrl || held.ctrl) && (!self.alt || held.alt)
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.ctrl {
+            write!(f, "Ctrl+")?;
+        }
+        if self.alt {
+            write!(f, "Alt+")?;
+        }
+        if self.shift {
+            write!(f, "Shift+")?;
+        }
+        let name = KEY_NAMES.iter().find(|(code, _)| *code == self.key).map_or("?", |(_, name)| name);
+        write!(f, "{}", name)
+    }
+}
+
+impl From<KeyChord> for String {
+    fn from(chord: KeyChord) -> Self {
+        chord.to_string()
+    }
+}
+
+impl TryFrom<String> for KeyChord {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        let mut chord = KeyChord::key(KeyCode::Escape);
+        let parts: Vec<&str> = text.split('+').collect();
+        let Some((key_name, modifiers)) = parts.split_last() else {
+            return Err(format!("empty key binding \"{}\"", text));
+        };
+        for modifier in modifiers {
+            match modifier.trim().to_ascii_lowercase().as_str() {
+                "shift" => chord.shift = true,
+                "ctrl" | "control" => chord.ctrl = true,
+                "alt" => chord.alt = true,
+                other => return Err(format!("unknown modifier \"{}\" in \"{}\"", other, text)),
+            }
+        }
+        let key_name = key_name.trim();
+        let Some((key, _)) = KEY_NAMES.iter().find(|(_, name)| name.eq_ignore_ascii_case(key_name)) else {
+            return Err(format!("unknown key \"{}\" in \"{}\"", key_name, text));
+        };
+        chord.key = *key;
+        Ok(chord)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Modifiers {
+    shift: bool,
+    ctrl: bool,
+    alt: bool,
+}
+
+impl Modifiers {
+    fn read(keyboard: &ButtonInput<KeyCode>) -> Self {
+        Self {
+            shift: keyboard.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]),
+            ctrl: super::input::command_modifier_held(keyboard),
+            alt: super::input::unit_move_modifier_held(keyboard),
+        }
+    }
+}
+
+/// Which keys do what. Saved with the user settings as action names mapped to lists of
+/// chords; an action the file doesn't mention keeps its default keys.
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "BTreeMap<Action, Vec<KeyChord>>", into = "BTreeMap<Action, Vec<KeyChord>>")]
+pub struct InputMap {
+    bindings: BTreeMap<Action, Vec<KeyChord>>,
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        let bindings = Action::ALL.iter().map(|action| (*action, default_keys(*action))).collect();
+        Self { bindings }
+    }
+}
+
+// The out-of-the-box keys. The camera pans on the arrows alone, since W, A, S and D
+// are unit keys; no two actions share a chord.
+fn default_keys(action: Action) -> Vec<KeyChord> {
+    use KeyChord as K;
+    match action {
+        Action::EndTurn => vec![K::key(KeyCode::Space)],
+        Action::NextUnit => vec![K::key(KeyCode::KeyN)],
+        Action::CityScreen => vec![K::key(KeyCode::Enter)],
+        Action::Attack => vec![K::key(KeyCode::KeyA)],
+        Action::FoundCity => vec![K::key(KeyCode::KeyF)],
+        Action::Fortify => vec![K::shift(KeyCode::KeyF)],
+        Action::BuildImprovement => vec![K::key(KeyCode::KeyB)],
+        Action::UseAbility => vec![K::key(KeyCode::KeyU)],
+        Action::Upgrade => vec![K::shift(KeyCode::KeyU)],
+        Action::Skip => vec![K::key(KeyCode::KeyS)],
+        Action::Sentry => vec![K::shift(KeyCode::KeyS)],
+        Action::Automate => vec![K::shift(KeyCode::KeyA)],
+        Action::Disband => vec![K::key(KeyCode::Delete)],
+        Action::Rename => vec![K::key(KeyCode::KeyR)],
+        Action::Undo => vec![K::ctrl(KeyCode::KeyZ)],
+        Action::PanUp => vec![K::key(KeyCode::ArrowUp)],
+        Action::PanDown => vec![K::key(KeyCode::ArrowDown)],
+        Action::PanLeft => vec![K::key(KeyCode::ArrowLeft)],
+        Action::PanRight => vec![K::key(KeyCode::ArrowRight)],
+        Action::CenterOnCapital => vec![K::key(KeyCode::Home)],
+        Action::CenterOnSelection => vec![K::key(KeyCode::KeyC)],
+        Action::ToggleGrid => vec![K::key(KeyCode::KeyG)],
+        Action::ToggleElevation => vec![K::key(KeyCode::KeyE)],
+        Action::ElevationFainter => vec![K::key(KeyCode::BracketLeft)],
+        Action::ElevationStronger => vec![K::key(KeyCode::BracketRight)],
+        Action::CycleInfoMode => vec![K::key(KeyCode::Tab)],
+        Action::ToggleHelp => vec![K::key(KeyCode::KeyH)],
+        Action::OpenGuide => vec![K::shift(KeyCode::Slash)],
+        Action::ToggleGameStatus => vec![K::key(KeyCode::F1)],
+        Action::ToggleUnitStatus => vec![K::key(KeyCode::F2)],
+        Action::ToggleDebugInfo => vec![K::key(KeyCode::F3)],
+        Action::ToggleTurnSummary => vec![K::key(KeyCode::F4)],
+        Action::ToggleDemographics => vec![K::key(KeyCode::F5)],
+        Action::ToggleCombatEffects => vec![K::key(KeyCode::F6)],
+        Action::ToggleUnitList => vec![K::key(KeyCode::F7)],
+        Action::ToggleEmpireOverview => vec![K::key(KeyCode::F8)],
+        Action::ToggleResources => vec![K::key(KeyCode::F11)],
+        Action::ToggleDiplomacy => vec![K::key(KeyCode::F9)],
+        Action::ToggleGlyphSheet => vec![K::key(KeyCode::F10)],
+        Action::ToggleTechTree => vec![K::key(KeyCode::KeyT)],
+        Action::ToggleCombatLog => vec![K::key(KeyCode::KeyL)],
+        Action::ToggleMessages => vec![K::key(KeyCode::KeyM)],
+        Action::ToggleTurnReport => vec![K::key(KeyCode::KeyO)],
+        Action::ToggleConsole => vec![K::key(KeyCode::Backquote)],
+        Action::QuickSave => vec![K::ctrl(KeyCode::KeyS)],
+        Action::LoadGame => vec![K::ctrl(KeyCode::KeyL)],
+    }
+}
+
+impl From<BTreeMap<Action, Vec<KeyChord>>> for InputMap {
+    fn from(saved: BTreeMap<Action, Vec<KeyChord>>) -> Self {
+        let mut map = InputMap::default();
+        map.bindings.extend(saved);
+        map
+    }
+}
+
+impl From<InputMap> for BTreeMap<Action, Vec<KeyChord>> {
+    fn from(map: InputMap) -> Self {
+        map.bindings
+    }
+}
+
+impl InputMap {
+    // Pressed this frame, with exactly the chord's modifiers held
+    pub fn just_pressed(&self, action: Action, keyboard: &ButtonInput<KeyCode>) -> bool {
+        let held = Modifiers::read(keyboard);
+        self.keys(action).iter().any(|chord| chord.just_pressed(keyboard, held))
+    }
+
+    // Held down; extra modifiers don't matter, so Shift can still speed up the camera
+    pub fn pressed(&self, action: Action, keyboard: &ButtonInput<KeyCode>) -> bool {
+        let held = Modifiers::read(keyboard);
+        self.keys(action).iter().any(|chord| chord.pressed(keyboard, held))
+    }
+
+    pub fn keys(&self, action: Action) -> &[KeyChord] {
+        self.bindings.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    // The keys written out for help text and buttons, e.g. "Space / NumpadEnter"
+    pub fn label(&self, action: Action) -> String {
+        let keys = self.keys(action);
+        if keys.is_empty() {
+            return "Unbound".to_string();
+        }
+        keys.iter().map(KeyChord::to_string).collect::<Vec<_>>().join(" / ")
+    }
+
+    // Clears the action's keys for the rest of the frame, so nothing else reads the press
+    pub fn consume(&self, action: Action, keyboard: &mut ButtonInput<KeyCode>) {
+        for chord in self.keys(action) {
+            keyboard.clear_just_pressed(chord.key);
+        }
+    }
+
+    // Makes `chord` the only key for `action`
+    pub fn rebind(&mut self, action: Action, chord: KeyChord) {
+        self.bindings.insert(action, vec![chord]);
+    }
+
+    pub fn reset(&mut self, action: Action) {
+        self.bindings.insert(action, default_keys(action));
+    }
+
+    // The other actions that one of `action`'s keys would also trigger. The held camera
+    // keys only clash among themselves, since they're read alongside everything else.
+    pub fn conflicts(&self, action: Action) -> Vec<Action> {
+        let keys = self.keys(action);
+        Action::ALL
+            .iter()
+            .copied()
+            .filter(|other| *other != action && other.is_held() == action.is_held())
+            .filter(|other| self.keys(*other).iter().any(|chord| keys.contains(chord)))
+            .collect()
+    }
+}
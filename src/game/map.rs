@@ -1,13 +1,24 @@
 use bevy::prelude::*;
+use bevy::ecs::system::SystemParam;
+use bevy::tasks::{AsyncComputeTaskPool, Task, futures::check_ready};
 use super::hex::HexCoord;
+use super::console::ConsoleRegen;
+use super::culling::Cullable;
+use super::game_initialization::GameSettings;
+use super::input_map::{Action, InputMap};
+use super::resources::ResourceType;
 use super::world_gen::{WorldGenerator, BiomeType, WorldGenConfig, WorldTile, StrategicFeature};
 use std::f32::consts::PI;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use serde::{Deserialize, Serialize};
 
 pub const HEX_SIZE: f32 = 30.0;
 pub const MAP_RADIUS: i32 = 100;
+/// How far elevation shading can be turned up, and how much each key press changes it
+pub const MAX_ELEVATION_INTENSITY: f32 = 0.5;
+pub const ELEVATION_INTENSITY_STEP: f32 = 0.05;
 
-#[derive(Component)]
+#[derive(Component, Clone, Serialize, Deserialize)]
 pub struct MapTile {
     pub hex_coord: HexCoord,
     pub terrain: u8,
@@ -32,6 +43,34 @@ pub struct MapTile {
     pub naval_access: f32,          // 0.0 to 1.0
 }
 
+// Rebuilds generator output from a spawned tile, for recoloring and for loaded games.
+// River edges and drainage aren't kept on tiles, so they come back as defaults.
+impl From<&MapTile> for WorldTile {
+    fn from(tile: &MapTile) -> Self {
+        WorldTile {
+            hex_coord: tile.hex_coord,
+            elevation: tile.elevation_raw,
+            terrain: tile.terrain,
+            biome: tile.biome,
+            has_river: tile.has_river,
+            river_flow: tile.river_flow,
+            river_edges: [false; 6],
+            is_coastal: tile.is_coastal,
+            resource: tile.resource,
+            temperature: tile.temperature,
+            precipitation: tile.precipitation,
+            drainage: 0.5,
+            geology: tile.geology,
+            soil_fertility: tile.soil_fertility,
+            strategic_feature: tile.strategic_feature,
+            defensibility: tile.defensibility,
+            trade_value: tile.trade_value,
+            flood_risk: tile.flood_risk,
+            naval_access: tile.naval_access,
+        }
+    }
+}
+
 // Keep the old TerrainType for compatibility, but map it to BiomeType
 #[derive(Clone, Copy, PartialEq, Debug)]
 pub enum TerrainType {
@@ -189,7 +228,10 @@ pub struct TerrainAssets {
     pub sea_level: f32,
 }
 
-#[derive(Clone)]
+/// How the terrain is shaded. As a resource it holds the player's choice, which each
+/// game's terrain starts from.
+#[derive(Resource, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct VisualConfig {
     pub elevation_shading: bool,
     pub elevation_intensity: f32,
@@ -219,81 +261,144 @@ pub struct WorldInfo {
     pub biome_counts: HashMap<u8, usize>,
 }
 
-pub fn setup_map(
-    mut commands: Commands, 
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
-) {
-    setup_map_with_config(commands, meshes, materials, WorldGenConfig::default());
+/// Lookup from hex coordinate to the tile entity, so gameplay code doesn't have to
+/// scan every tile to find one
+#[derive(Resource, Default)]
+pub struct TileIndex {
+    pub tiles: HashMap<HexCoord, Entity>,
+    pub city_sites: HashSet<HexCoord>, // Kept by index_city_sites
+    pub resources: HashMap<ResourceType, Vec<HexCoord>>, // Set by spawn_world
 }
 
-pub fn setup_map_with_config(
-    mut commands: Commands, 
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
-    config: WorldGenConfig,
-) {
-    println!("=== GENERATING REALISTIC WORLD ===");
-    println!("World Type: {} continents, {:.0}% land target", 
-             config.continent_count, 
-             config.target_land_percentage * 100.0);
-    
-    let hex_mesh = create_hexagon_mesh(HEX_SIZE);
-    let mesh_handle = meshes.add(hex_mesh);
-    
-    // Generate the world using our configurable system
-    let mut world_gen = WorldGenerator::with_config(MAP_RADIUS, config.clone());
-    let world_tiles = world_gen.generate();
-    
-    // Calculate elevation range for shading
-    let min_elevation = world_tiles.iter().map(|t| t.elevation).fold(f32::INFINITY, f32::min);
-    let max_elevation = world_tiles.iter().map(|t| t.elevation).fold(f32::NEG_INFINITY, f32::max);
-    
-    println!("Elevation range: {:.3} to {:.3}", min_elevation, max_elevation);
-    
-    let visual_config = VisualConfig::default();
+impl TileIndex {
+    // Where a resource lies, in the order the world was spawned
+    pub fn resource_sites(&self, resource: ResourceType) -> &[HexCoord] {
+        self.resources.get(&resource).map_or(&[], Vec::as_slice)
+    }
+}
+
+// Height above sea level at which alpine terrain becomes an impassable peak
+pub const PEAK_ELEVATION: f32 = 0.75;
+
+/// System param bundling the tile index with the tile query for O(1) tile access
+#[derive(SystemParam)]
+pub struct TileLookup<'w, 's> {
+    index: Res<'w, TileIndex>,
+    tiles: Query<'w, 's, &'static MapTile>,
+    world_info: Option<Res<'w, WorldInfo>>,
+    settings: Res<'w, GameSettings>,
+}
+
+impl TileLookup<'_, '_> {
+    pub fn get(&self, coord: HexCoord) -> Option<&MapTile> {
+        let entity = self.index.tiles.get(&coord)?;
+        self.tiles.get(*entity).ok()
+    }
     
-    // Create enhanced materials for each tile with shading applied
-    let mut biome_materials = HashMap::new();
-    let mut enhanced_materials = HashMap::new();
-    let mut hover_materials = HashMap::new();
+    pub fn is_city_site(&self, coord: HexCoord) -> bool {
+        self.index.city_sites.contains(&coord)
+    }
     
-    // First pass: create base materials for each biome
-    for biome_id in 0..=62u8 {
-        let biome_type = BiomeType::from_u8(biome_id);
-        let base_color = biome_type.color();
-        let material_handle = materials.add(ColorMaterial::from(base_color));
-        biome_materials.insert(biome_id, material_handle);
+    // Ocean with no land beside it, which only ocean-going units may enter
+    pub fn is_open_ocean(&self, coord: HexCoord) -> bool {
+        let is_water = |tile: &MapTile| matches!(TerrainType::from_u8(tile.terrain), TerrainType::Ocean | TerrainType::Lake | TerrainType::River);
+        self.get(coord).is_some_and(|tile| TerrainType::from_u8(tile.terrain) == TerrainType::Ocean)
+            && coord.neighbors().into_iter().filter_map(|neighbor| self.get(neighbor)).all(is_water)
     }
     
-    // Second pass: create enhanced materials for each tile
-    for world_tile in &world_tiles {
-        let enhanced_color = calculate_enhanced_color(
-            world_tile,
-            &visual_config,
-            min_elevation,
-            max_elevation,
-            world_gen.sea_level,
-        );
-        let enhanced_material = materials.add(ColorMaterial::from(enhanced_color));
-        enhanced_materials.insert(world_tile.hex_coord, enhanced_material);
+    // True peaks can't be crossed on foot, except through a mountain pass
+    pub fn is_impassable_peak(&self, tile: &MapTile) -> bool {
+        if !self.settings.impassable_peaks {
+            return false;
+        }
+        let sea_level = self.world_info.as_ref().map_or(0.0, |info| info.sea_level);
         
-        // Create hover version (brighter)
-        let hover_color = brighten_color(enhanced_color, 0.3);
-        let hover_material = materials.add(ColorMaterial::from(hover_color));
-        hover_materials.insert(world_tile.hex_coord, hover_material);
+        BiomeType::from_u8(tile.biome) == BiomeType::AlpineTundra
+            && tile.elevation_raw - sea_level > PEAK_ELEVATION
+            && StrategicFeature::from_u8(tile.strategic_feature) != StrategicFeature::MountainPass
+    }
+}
+
+// System to register newly spawned tiles in the tile index
+pub fn index_new_tiles(
+    mut tile_index: ResMut<TileIndex>,
+    new_tiles: Query<(Entity, &MapTile), Added<MapTile>>,
+) {
+    for (entity, tile) in new_tiles.iter() {
+        tile_index.tiles.insert(tile.hex_coord, entity);
     }
+}
+
+// A generated world's tiles, sea level, config and what it was generated from
+type GeneratedWorld = (Vec<WorldTile>, f32, WorldGenConfig, WorldOrigin);
+
+/// The world being generated in the background while a new game loads
+#[derive(Resource, Default)]
+pub struct WorldGenTask(Option<Task<GeneratedWorld>>);
+
+/// The seed and size a generated world came from. Generating again from them, with the
+/// config in WorldInfo, gives the same map; a replay checks its map that way.
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WorldOrigin {
+    pub seed: u64,
+    pub radius: i32,
+}
+
+// Starts generating the world of the chosen type off the main thread, so the loading
+// screen keeps drawing. A console `regen` seeds this world alone.
+pub fn start_world_generation(
+    mut commands: Commands,
+    mut task: ResMut<WorldGenTask>,
+    settings: Res<GameSettings>,
+    regen: Option<Res<ConsoleRegen>>,
+) {
+    let config = settings.world_type.config();
+    let world_seed = match regen {
+        Some(regen) => {
+            commands.remove_resource::<ConsoleRegen>();
+            regen.0
+        }
+        None => settings.world_seed,
+    };
+    let seed = world_seed.unwrap_or_else(rand::random);
+    println!("=== GENERATING REALISTIC WORLD ===");
+    println!("World seed: {}", seed);
+    println!("World Type: {} ({} continents, {:.0}% land target)", 
+             settings.world_type.get_name(),
+             config.continent_count, 
+             config.target_land_percentage * 100.0);
     
-    commands.insert_resource(TerrainAssets {
-        hex_mesh: mesh_handle.clone(),
-        materials: biome_materials.clone(),
-        enhanced_materials: enhanced_materials.clone(),
-        hover_materials: hover_materials.clone(),
-        visual_config: visual_config.clone(),
-        elevation_range: (min_elevation, max_elevation),
-        sea_level: world_gen.sea_level,
-    });
+    task.0 = Some(AsyncComputeTaskPool::get().spawn(async move {
+        let mut world_gen = WorldGenerator::with_seed(MAP_RADIUS, config.clone(), seed);
+        let world_tiles = world_gen.generate();
+        (world_tiles, world_gen.sea_level, config, WorldOrigin { seed, radius: MAP_RADIUS })
+    }));
+}
+
+// Spawns the world once generation has finished
+pub fn finish_world_generation(
+    mut commands: Commands, 
+    mut task: ResMut<WorldGenTask>,
+    mut tile_index: ResMut<TileIndex>,
+) {
+    let Some(running) = task.0.as_mut() else { return };
+    let Some((world_tiles, sea_level, config, origin)) = check_ready(running) else { return };
+    task.0 = None;
+    
+    spawn_world(&mut commands, &mut tile_index, world_tiles, sea_level, config);
+    commands.insert_resource(origin);
+}
 
+// Spawns the tiles of a generated or loaded world and records its statistics in
+// WorldInfo. The new tile entities and the resources' sites go in the tile index. Their
+// meshes and materials come from add_tile_visuals, so a world without a window has none.
+pub fn spawn_world(
+    commands: &mut Commands,
+    tile_index: &mut TileIndex,
+    world_tiles: Vec<WorldTile>,
+    sea_level: f32,
+    config: WorldGenConfig,
+) {
     // Track statistics
     let mut tiles_created = 0;
     let mut rivers_created = 0;
@@ -301,25 +406,24 @@ pub fn setup_map_with_config(
     let mut total_land_tiles = 0;
     let mut total_ocean_tiles = 0;
     let mut biome_counts = HashMap::new();
+    let mut tile_entities = HashMap::new();
+    let mut resources: HashMap<ResourceType, Vec<HexCoord>> = HashMap::new();
     
     // Create map tiles from world generation
     for world_tile in world_tiles {
         let world_pos = world_tile.hex_coord.to_world_pos(HEX_SIZE);
         let elevation_u8 = ((world_tile.elevation + 1.0) * 127.5).clamp(0.0, 255.0) as u8;
-        
-        // Use enhanced material with shading
-        let material_handle = enhanced_materials[&world_tile.hex_coord].clone();
 
         // Calculate water distance (simplified)
-        let water_distance = if world_tile.elevation <= world_gen.sea_level {
+        let water_distance = if world_tile.elevation <= sea_level {
             0
         } else if world_tile.is_coastal {
             1
         } else {
-            ((world_tile.elevation - world_gen.sea_level) * 10.0) as u8
+            ((world_tile.elevation - sea_level) * 10.0) as u8
         };
 
-        commands.spawn((
+        let tile_entity = commands.spawn((
             MapTile {
                 hex_coord: world_tile.hex_coord,
                 terrain: world_tile.biome, // Use biome as terrain for compatibility
@@ -341,17 +445,19 @@ pub fn setup_map_with_config(
                 flood_risk: world_tile.flood_risk,
                 naval_access: world_tile.naval_access,
             },
-            Mesh2d(mesh_handle.clone()),
-            MeshMaterial2d(material_handle),
             Transform::from_translation(Vec3::new(world_pos.x, world_pos.y, 0.0)),
-        ));
+        )).id();
+        tile_entities.insert(world_tile.hex_coord, tile_entity);
+        if world_tile.resource != ResourceType::None as u8 {
+            resources.entry(ResourceType::from_u8(world_tile.resource)).or_default().push(world_tile.hex_coord);
+        }
         
         // Update statistics
         tiles_created += 1;
         if world_tile.has_river { rivers_created += 1; }
         if world_tile.is_coastal { coastal_tiles += 1; }
         
-        if world_tile.elevation <= world_gen.sea_level {
+        if world_tile.elevation <= sea_level {
             total_ocean_tiles += 1;
         } else {
             total_land_tiles += 1;
@@ -362,7 +468,7 @@ pub fn setup_map_with_config(
     
     // Store world information for reference
     commands.insert_resource(WorldInfo {
-        sea_level: world_gen.sea_level,
+        sea_level,
         config: config.clone(),
         total_land_tiles,
         total_ocean_tiles,
@@ -375,7 +481,7 @@ pub fn setup_map_with_config(
     println!("Coastal tiles: {}", coastal_tiles);
     println!("Land/Ocean ratio: {:.1}% land", 
              (total_land_tiles as f32 / tiles_created as f32) * 100.0);
-    println!("Sea level: {:.3}", world_gen.sea_level);
+    println!("Sea level: {:.3}", sea_level);
     
     // Print biome distribution
     println!("=== BIOME DISTRIBUTION ===");
@@ -387,9 +493,88 @@ pub fn setup_map_with_config(
         let percentage = (**count as f32 / tiles_created as f32) * 100.0;
         println!("{:?}: {} tiles ({:.1}%)", biome_type, count, percentage);
     }
+    
+    tile_index.tiles = tile_entities;
+    tile_index.resources = resources;
 }
 
-fn create_hexagon_mesh(size: f32) -> Mesh {
+// Gives the tiles of a newly spawned or loaded world their hexagon and shaded material,
+// building the terrain materials for the whole map
+pub fn add_tile_visuals(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    world_info: Option<Res<WorldInfo>>,
+    preferred_visuals: Res<VisualConfig>,
+    new_tiles: Query<(), Added<MapTile>>,
+    tile_query: Query<(Entity, &MapTile)>,
+) {
+    if new_tiles.is_empty() {
+        return;
+    }
+    let Some(world_info) = world_info else { return };
+    let sea_level = world_info.sea_level;
+    let mesh_handle = meshes.add(create_hexagon_mesh(HEX_SIZE));
+    
+    // Calculate elevation range for shading
+    let min_elevation = tile_query.iter().map(|(_, t)| t.elevation_raw).fold(f32::INFINITY, f32::min);
+    let max_elevation = tile_query.iter().map(|(_, t)| t.elevation_raw).fold(f32::NEG_INFINITY, f32::max);
+    
+    println!("Elevation range: {:.3} to {:.3}", min_elevation, max_elevation);
+    
+    let visual_config = preferred_visuals.clone();
+    
+    // Create enhanced materials for each tile with shading applied
+    let mut biome_materials = HashMap::new();
+    let mut enhanced_materials = HashMap::new();
+    let mut hover_materials = HashMap::new();
+    
+    // First pass: create base materials for each biome
+    for biome_id in 0..=62u8 {
+        let biome_type = BiomeType::from_u8(biome_id);
+        let base_color = biome_type.color();
+        let material_handle = materials.add(ColorMaterial::from(base_color));
+        biome_materials.insert(biome_id, material_handle);
+    }
+    
+    // Second pass: create enhanced materials for each tile
+    for (entity, tile) in tile_query.iter() {
+        let enhanced_color = calculate_enhanced_color(
+            &WorldTile::from(tile),
+            &visual_config,
+            min_elevation,
+            max_elevation,
+            sea_level,
+        );
+        let enhanced_material = materials.add(ColorMaterial::from(enhanced_color));
+        enhanced_materials.insert(tile.hex_coord, enhanced_material.clone());
+        
+        // Create hover version (brighter)
+        let hover_color = brighten_color(enhanced_color, 0.3);
+        let hover_material = materials.add(ColorMaterial::from(hover_color));
+        hover_materials.insert(tile.hex_coord, hover_material);
+        
+        commands.entity(entity).insert((Mesh2d(mesh_handle.clone()), MeshMaterial2d(enhanced_material), Cullable));
+    }
+    
+    commands.insert_resource(TerrainAssets {
+        hex_mesh: mesh_handle,
+        materials: biome_materials,
+        enhanced_materials,
+        hover_materials,
+        visual_config,
+        elevation_range: (min_elevation, max_elevation),
+        sea_level,
+    });
+}
+
+// The map's meshes and materials go with the game they were made for; the next game's
+// tiles make their own
+pub fn release_terrain_assets(mut commands: Commands) {
+    commands.remove_resource::<TerrainAssets>();
+}
+
+pub fn create_hexagon_mesh(size: f32) -> Mesh {
     let mut vertices = Vec::new();
     let mut indices = Vec::new();
     
@@ -643,57 +828,34 @@ fn apply_river_highlighting(base_color: Color, tile: &WorldTile) -> Color {
 // Visual configuration toggle functions
 pub fn toggle_elevation_shading(
     keyboard: Res<ButtonInput<KeyCode>>,
-    mut terrain_assets: ResMut<TerrainAssets>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
-    tile_query: Query<(Entity, &MapTile)>,
-    mut tile_materials: Query<&mut MeshMaterial2d<ColorMaterial>>,
+    input_map: Res<InputMap>,
+    mut shading: TileShading,
 ) {
-    if keyboard.just_pressed(KeyCode::KeyE) {
-        terrain_assets.visual_config.elevation_shading = !terrain_assets.visual_config.elevation_shading;
+    if input_map.just_pressed(Action::ToggleElevation, &keyboard) {
+        shading.update(|visuals| visuals.elevation_shading = !visuals.elevation_shading);
         println!("Elevation shading: {}", 
-                if terrain_assets.visual_config.elevation_shading { "ON" } else { "OFF" });
-        
-        // Regenerate all materials
-        update_all_tile_materials(
-            &mut terrain_assets,
-            &mut materials,
-            &tile_query,
-            &mut tile_materials,
-        );
+                if shading.config().elevation_shading { "ON" } else { "OFF" });
     }
 }
 
 pub fn adjust_elevation_intensity(
     keyboard: Res<ButtonInput<KeyCode>>,
-    mut terrain_assets: ResMut<TerrainAssets>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
-    tile_query: Query<(Entity, &MapTile)>,
-    mut tile_materials: Query<&mut MeshMaterial2d<ColorMaterial>>,
+    input_map: Res<InputMap>,
+    mut shading: TileShading,
 ) {
-    let mut changed = false;
+    let mut step = 0.0;
     
-    if keyboard.just_pressed(KeyCode::BracketLeft) {
-        terrain_assets.visual_config.elevation_intensity = 
-            (terrain_assets.visual_config.elevation_intensity - 0.05).max(0.0); // Smaller steps
-        changed = true;
+    if input_map.just_pressed(Action::ElevationFainter, &keyboard) {
+        step -= ELEVATION_INTENSITY_STEP;
     }
     
-    if keyboard.just_pressed(KeyCode::BracketRight) {
-        terrain_assets.visual_config.elevation_intensity = 
-            (terrain_assets.visual_config.elevation_intensity + 0.05).min(0.5); // Smaller max
-        changed = true;
+    if input_map.just_pressed(Action::ElevationStronger, &keyboard) {
+        step += ELEVATION_INTENSITY_STEP;
     }
     
-    if changed {
-        println!("Elevation intensity: {:.2}", terrain_assets.visual_config.elevation_intensity);
-        
-        // Regenerate all materials
-        update_all_tile_materials(
-            &mut terrain_assets,
-            &mut materials,
-            &tile_query,
-            &mut tile_materials,
-        );
+    if step != 0.0 {
+        shading.update(|visuals| visuals.elevation_intensity = (visuals.elevation_intensity + step).clamp(0.0, MAX_ELEVATION_INTENSITY));
+        println!("Elevation intensity: {:.2}", shading.config().elevation_intensity);
     }
 }
 
@@ -709,27 +871,7 @@ fn update_all_tile_materials(
     
     // Create new enhanced materials for each tile
     for (entity, tile) in tile_query.iter() {
-        let world_tile = WorldTile {
-            hex_coord: tile.hex_coord,
-            elevation: tile.elevation_raw,
-            terrain: tile.terrain,
-            biome: tile.biome,
-            has_river: tile.has_river,
-            river_flow: tile.river_flow,
-            river_edges: [false; 6], // Simplified for this update
-            is_coastal: tile.is_coastal,
-            resource: tile.resource,
-            temperature: tile.temperature,
-            precipitation: tile.precipitation,
-            drainage: 0.5, // Default value
-            geology: tile.geology,
-            soil_fertility: tile.soil_fertility,
-            strategic_feature: tile.strategic_feature,
-            defensibility: tile.defensibility,
-            trade_value: tile.trade_value,
-            flood_risk: tile.flood_risk,
-            naval_access: tile.naval_access,
-        };
+        let world_tile = WorldTile::from(tile);
         
         let enhanced_color = calculate_enhanced_color(
             &world_tile,
@@ -754,43 +896,31 @@ fn update_all_tile_materials(
     }
 }
 
-// Convenience functions for different world types
-pub fn setup_pangaea_world(
-    commands: Commands, 
-    meshes: ResMut<Assets<Mesh>>,
-    materials: ResMut<Assets<ColorMaterial>>,
-) {
-    setup_map_with_config(commands, meshes, materials, WorldGenConfig::pangaea());
+/// The terrain's visual config along with what it takes to recolour the map after a change.
+/// Outside a game there's no map to recolour, and the config new games start from is
+/// changed instead.
+#[derive(SystemParam)]
+pub struct TileShading<'w, 's> {
+    pub terrain_assets: Option<ResMut<'w, TerrainAssets>>,
+    visual_config: ResMut<'w, VisualConfig>,
+    materials: ResMut<'w, Assets<ColorMaterial>>,
+    tile_query: Query<'w, 's, (Entity, &'static MapTile)>,
+    tile_materials: Query<'w, 's, &'static mut MeshMaterial2d<ColorMaterial>>,
 }
 
-pub fn setup_archipelago_world(
-    commands: Commands, 
-    meshes: ResMut<Assets<Mesh>>,
-    materials: ResMut<Assets<ColorMaterial>>,
-) {
-    setup_map_with_config(commands, meshes, materials, WorldGenConfig::archipelago_world());
-}
-
-pub fn setup_fragmented_world(
-    commands: Commands, 
-    meshes: ResMut<Assets<Mesh>>,
-    materials: ResMut<Assets<ColorMaterial>>,
-) {
-    setup_map_with_config(commands, meshes, materials, WorldGenConfig::fragmented_continents());
-}
-
-pub fn setup_dual_supercontinents(
-    commands: Commands, 
-    meshes: ResMut<Assets<Mesh>>,
-    materials: ResMut<Assets<ColorMaterial>>,
-) {
-    setup_map_with_config(commands, meshes, materials, WorldGenConfig::dual_supercontinents());
+impl TileShading<'_, '_> {
+    pub fn config(&self) -> &VisualConfig {
+        self.terrain_assets.as_ref().map_or(&*self.visual_config, |terrain_assets| &terrain_assets.visual_config)
+    }
+    
+    // Changes the visual config and regenerates every tile's material to match. The
+    // hotkeys and the settings menu both come through here.
+    pub fn update(&mut self, change: impl FnOnce(&mut VisualConfig)) {
+        let Some(terrain_assets) = &mut self.terrain_assets else {
+            change(&mut self.visual_config);
+            return;
+        };
+        change(&mut terrain_assets.visual_config);
+        update_all_tile_materials(terrain_assets, &mut self.materials, &self.tile_query, &mut self.tile_materials);
+    }
 }
-
-pub fn setup_mediterranean_world(
-    commands: Commands, 
-    meshes: ResMut<Assets<Mesh>>,
-    materials: ResMut<Assets<ColorMaterial>>,
-) {
-    setup_map_with_config(commands, meshes, materials, WorldGenConfig::mediterranean_world());
-}
\ No newline at end of file
@@ -10,7 +10,32 @@ pub mod camera_zoom;
 pub mod civilization;
 pub mod game_initialization;
 pub mod combat;
+pub mod diplomacy;
+pub mod victory;
 pub mod city_founding;
+pub mod input;
+pub mod ai;
+pub mod barbarians;
+pub mod economy;
+pub mod statistics;
+pub mod rules;
+pub mod culture;
+pub mod combat_log;
+pub mod rng;
+pub mod save;
+pub mod stable_id;
+pub mod notifications;
+pub mod replay;
+pub mod headless;
+pub mod console;
+pub mod reports;
+pub mod unit_actions;
+pub mod automation;
+pub mod fonts;
+pub mod input_map;
+pub mod plugin;
+#[cfg(test)]
+pub mod test_support;
 
 pub use hex::*;
 pub use map::*;
@@ -0,0 +1,307 @@
+use bevy::prelude::*;
+use bevy::ecs::system::SystemParam;
+use serde::{Deserialize, Serialize};
+use super::hex::HexCoord;
+use super::cities::{City, CityEventReaders};
+use super::civilization::{Civilization, CivilizationManager, TechResearchedEvent};
+use super::combat::fight_shown_in_full;
+use super::combat_log::{CombatLog, CombatLogEntry, CombatOutcome};
+use super::victory::CivilizationEliminatedEvent;
+use super::diplomacy::{FirstContactEvent, WarDeclaredEvent};
+use super::culture::TileFlippedEvent;
+use super::barbarians::{BarbarianCampClearedEvent, BarbarianSpawnedEvent};
+use super::economy::DeficitEvent;
+use super::game_initialization::{GameSettings, TurnStartedEvent};
+use super::save::SaveNotice;
+
+/// What a notification is about; the feed colors it by this
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NotificationCategory {
+    City,
+    Research,
+    Military,
+    Diplomacy,
+    Warning,
+    System,
+}
+
+// How close to the player's borders a new raider has to turn up for them to hear of it
+const BARBARIAN_ALERT_RADIUS: i32 = 3;
+
+/// Things that happen at a place on the map, which the player can choose to be taken to
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum MapEvent {
+    Combat,
+    CityCaptured,
+    BorderFlip,
+    BarbarianSpawn,
+    CampCleared,
+}
+
+impl MapEvent {
+    pub const ALL: [MapEvent; 5] = [
+        MapEvent::Combat,
+        MapEvent::CityCaptured,
+        MapEvent::BorderFlip,
+        MapEvent::BarbarianSpawn,
+        MapEvent::CampCleared,
+    ];
+    
+    pub fn get_name(self) -> &'static str {
+        match self {
+            MapEvent::Combat => "Combat",
+            MapEvent::CityCaptured => "City captured",
+            MapEvent::BorderFlip => "Tile won or lost",
+            MapEvent::BarbarianSpawn => "Barbarians nearby",
+            MapEvent::CampCleared => "Camp cleared",
+        }
+    }
+}
+
+/// A message for the player: news from their empire, the outcome of a fight, or why
+/// an order didn't go through. `location` is where the camera goes when it's clicked;
+/// `civ` is whose news it is, None for everyone's. `event` marks the ones the player's
+/// alert settings apply to.
+#[derive(Event, Clone, Debug)]
+pub struct GameNotification {
+    pub category: NotificationCategory,
+    pub text: String,
+    pub location: Option<HexCoord>,
+    pub civ: Option<u32>,
+    pub event: Option<MapEvent>,
+}
+
+impl GameNotification {
+    pub fn new(category: NotificationCategory, text: impl Into<String>) -> Self {
+        Self {
+            category,
+            text: text.into(),
+            location: None,
+            civ: None,
+            event: None,
+        }
+    }
+    
+    pub fn at(mut self, location: HexCoord) -> Self {
+        self.location = Some(location);
+        self
+    }
+    
+    pub fn for_civ(mut self, civ: u32) -> Self {
+        self.civ = Some(civ);
+        self
+    }
+    
+    // Where it happened, and what kind of event it was there
+    pub fn event_at(self, event: MapEvent, location: HexCoord) -> Self {
+        Self { event: Some(event), ..self.at(location) }
+    }
+    
+    // Whether the player, if they still have a civilization, gets to see this
+    pub fn concerns(&self, player_civ: Option<u32>) -> bool {
+        self.civ.is_none() || self.civ == player_civ
+    }
+}
+
+/// Readers for announcements that don't come from a city
+#[derive(SystemParam)]
+pub struct WorldEventReaders<'w, 's> {
+    researched: EventReader<'w, 's, TechResearchedEvent>,
+    eliminated: EventReader<'w, 's, CivilizationEliminatedEvent>,
+    camps_cleared: EventReader<'w, 's, BarbarianCampClearedEvent>,
+    spawns: EventReader<'w, 's, BarbarianSpawnedEvent>,
+    deficits: EventReader<'w, 's, DeficitEvent>,
+    contacts: EventReader<'w, 's, FirstContactEvent>,
+    wars: EventReader<'w, 's, WarDeclaredEvent>,
+    flips: EventReader<'w, 's, TileFlippedEvent>,
+    turns: EventReader<'w, 's, TurnStartedEvent>,
+    saves: EventReader<'w, 's, SaveNotice>,
+}
+
+// System that turns the game's own events into notifications for the players: news from
+// their cities and research, the fights they were in, contacts, wars and falls of
+// civilizations. Each human's news is addressed to them, whoever is playing right now.
+#[allow(clippy::too_many_arguments)]
+pub fn relay_notifications_system(
+    city_query: Query<&City>,
+    civ_manager: Res<CivilizationManager>,
+    settings: Res<GameSettings>,
+    combat_log: Res<CombatLog>,
+    mut events: CityEventReaders,
+    mut world_events: WorldEventReaders,
+    mut notifications: EventWriter<GameNotification>,
+    mut log_seen: Local<usize>,
+) {
+    notifications.write_batch(events.read_notifications(&city_query));
+    notifications.write_batch(world_events.researched.read().map(|event| {
+        GameNotification::new(NotificationCategory::Research, format!("Researched {}", event.technology.get_name()))
+            .for_civ(event.civilization_id)
+    }));
+    notifications.write_batch(world_events.deficits.read().map(|event| {
+        GameNotification::new(NotificationCategory::Warning, event.message.clone()).for_civ(event.civilization_id)
+    }));
+    notifications.write_batch(world_events.camps_cleared.read().map(|event| {
+        let text = format!("Cleared a barbarian camp at ({}, {}): +{:.0} gold", event.hex_coord.q, event.hex_coord.r, event.gold);
+        GameNotification::new(NotificationCategory::Military, text)
+            .event_at(MapEvent::CampCleared, event.hex_coord)
+            .for_civ(event.civilization_id)
+    }));
+    notifications.write_batch(world_events.saves.read().map(|notice| {
+        GameNotification::new(NotificationCategory::System, notice.message.clone())
+    }));
+    // Both sides hear of a war, including a human who isn't the one playing right now
+    for event in world_events.wars.read() {
+        let declared = format!("You have declared war on the {}!", civ_manager.civ_name(event.other_id));
+        let received = format!("The {} have declared war on you!", civ_manager.civ_name(event.civilization_id));
+        notifications.write(GameNotification::new(NotificationCategory::Diplomacy, declared).for_civ(event.civilization_id));
+        notifications.write(GameNotification::new(NotificationCategory::Diplomacy, received).for_civ(event.other_id));
+    }
+    
+    // Whose news the rest is: every human, so in hotseat each hears what happened to them
+    // while another was playing, or the civilization being watched in an observer game
+    let mut recipients: Vec<&Civilization> = civ_manager.civilizations.values().filter(|civ| civ.is_player).collect();
+    if recipients.is_empty() {
+        recipients.extend(civ_manager.get_viewed_civilization());
+    }
+    recipients.sort_by_key(|civ| civ.id);
+    let turns: Vec<u32> = world_events.turns.read().map(|event| event.civilization_id).collect();
+    let contacts: Vec<&FirstContactEvent> = world_events.contacts.read().collect();
+    let flips: Vec<&TileFlippedEvent> = world_events.flips.read().collect();
+    let spawns: Vec<&BarbarianSpawnedEvent> = world_events.spawns.read().collect();
+    let eliminated: Vec<&CivilizationEliminatedEvent> = world_events.eliminated.read().collect();
+    let fights: Vec<&CombatLogEntry> = combat_log.recorded_since(*log_seen).collect();
+    *log_seen = combat_log.recorded();
+    
+    for player_civ in recipients {
+        let player = player_civ.id;
+        
+        if turns.contains(&player) && !civ_manager.is_observer_game() {
+            let text = format!("Turn {}: your move", civ_manager.turn_number);
+            notifications.write(GameNotification::new(NotificationCategory::System, text).for_civ(player));
+        }
+        
+        // Fights the player's units or cities took part in, and their promotions
+        for entry in &fights {
+            // Camp clearings come with their own event, announced above
+            if matches!(entry.outcome, CombatOutcome::CampCleared { .. }) {
+                continue;
+            }
+            if entry.attacker_civ != player && entry.defender_civ != Some(player) {
+                continue;
+            }
+            let text = format!("{}{}{}{}", entry.attacker, entry.verb(), entry.defender, entry.details());
+            let notification = GameNotification::new(NotificationCategory::Military, text).for_civ(player);
+            // Watching AIs fight, the fight is listed but never jumps the camera
+            let sides = (entry.attacker_civ, entry.defender_civ.unwrap_or(entry.attacker_civ));
+            notifications.write(if fight_shown_in_full(&settings, &civ_manager, sides) {
+                notification.event_at(MapEvent::Combat, entry.location)
+            } else {
+                notification.at(entry.location)
+            });
+        }
+        
+        for event in &contacts {
+            let other_id = if event.civilization_id == player {
+                event.other_id
+            } else if event.other_id == player {
+                event.civilization_id
+            } else {
+                continue;
+            };
+            let text = format!("You have met the {}!", civ_manager.civ_name(other_id));
+            notifications.write(GameNotification::new(NotificationCategory::Diplomacy, text).for_civ(player));
+        }
+        for event in &flips {
+            let (q, r) = (event.tile.q, event.tile.r);
+            let text = if event.civilization_id == player {
+                let city = city_query.get(event.city_entity).map_or("Your culture", |city| city.name.as_str());
+                format!("{} has won tile ({}, {}) from the {}", city, q, r, civ_manager.known_civ_name(event.previous_civilization_id))
+            } else if event.previous_civilization_id == player {
+                format!("Tile ({}, {}) has defected to the {}", q, r, civ_manager.known_civ_name(event.civilization_id))
+            } else {
+                continue;
+            };
+            notifications.write(GameNotification::new(NotificationCategory::City, text).event_at(MapEvent::BorderFlip, event.tile).for_civ(player));
+        }
+        // Raiders turning up near the player's borders
+        for event in &spawns {
+            let near_border = city_query
+                .iter()
+                .filter(|city| city.civilization_id == player)
+                .flat_map(|city| city.territory_tiles.iter())
+                .any(|tile| tile.distance(event.hex_coord) <= BARBARIAN_ALERT_RADIUS);
+            if near_border {
+                let text = format!("Barbarians have appeared at ({}, {}), near your borders", event.hex_coord.q, event.hex_coord.r);
+                let notification = GameNotification::new(NotificationCategory::Warning, text).event_at(MapEvent::BarbarianSpawn, event.hex_coord);
+                notifications.write(notification.for_civ(player));
+            }
+        }
+        // Everyone hears about a civilization's fall
+        for event in &eliminated {
+            let text = if event.civilization_id == player {
+                "Your civilization has been destroyed!".to_string()
+            } else if !player_civ.has_met(event.civilization_id) {
+                "An unknown civilization has been destroyed!".to_string()
+            } else {
+                format!("The {} have been destroyed!", event.name)
+            };
+            notifications.write(GameNotification::new(NotificationCategory::Diplomacy, text).for_civ(player));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::cities::BlockadeStartedEvent;
+    use crate::game::civilization::create_default_civilizations;
+    use crate::game::test_support::game_app;
+    
+    // A hotseat game where civilization 1 is playing, 2 is the other human and 3 the AI,
+    // with a fight between 2 and 3 just logged
+    fn hotseat_fight() -> Vec<GameNotification> {
+        let mut app = game_app(Vec::new());
+        app.init_resource::<CombatLog>()
+            .add_event::<BlockadeStartedEvent>()
+            .add_event::<CivilizationEliminatedEvent>()
+            .add_event::<BarbarianCampClearedEvent>()
+            .add_event::<BarbarianSpawnedEvent>()
+            .add_event::<DeficitEvent>()
+            .add_event::<FirstContactEvent>()
+            .add_event::<TileFlippedEvent>()
+            .add_event::<SaveNotice>()
+            .add_systems(Update, relay_notifications_system);
+        let mut manager = CivilizationManager::default();
+        for mut civ in create_default_civilizations().into_iter().take(3) {
+            civ.set_player(civ.id != 3);
+            manager.add_civilization(civ);
+        }
+        manager.current_turn_civ = 1;
+        manager.active_player = 1;
+        app.insert_resource(manager);
+        app.world_mut().resource_mut::<CombatLog>().push(CombatLogEntry {
+            turn: 1,
+            location: HexCoord::new(0, 0),
+            attacker: "Warrior".to_string(),
+            attacker_civ: 3,
+            defender: "Archer".to_string(),
+            defender_civ: Some(2),
+            damage_to_attacker: 0,
+            damage_to_defender: 3,
+            outcome: CombatOutcome::DefenderHeld,
+        });
+        
+        app.update();
+        app.world_mut().resource_mut::<Events<GameNotification>>().drain().collect()
+    }
+    
+    // The human who isn't playing still hears of the fight their unit was in, addressed to
+    // them so their turn report keeps it for their handover; the one playing doesn't
+    #[test]
+    fn waiting_hotseat_players_hear_their_own_news() {
+        let notifications = hotseat_fight();
+        let fights_for = |civ| notifications.iter().filter(|notification| notification.category == NotificationCategory::Military && notification.civ == Some(civ)).count();
+        assert_eq!(fights_for(2), 1);
+        assert_eq!(fights_for(1), 0);
+    }
+}
@@ -0,0 +1,318 @@
+use bevy::prelude::*;
+use crate::game::*;
+use crate::game::camera_zoom::CameraTween;
+use crate::game::map::{WorldGenTask, start_world_generation, finish_world_generation};
+use crate::game::input_map::InputMap;
+use crate::game::civilization::{CivilizationManager, TechResearchedEvent, research_system};
+use crate::game::statistics::{StatsHistory, stats_history_system};
+use crate::game::stable_id::StableIds;
+use crate::game::save::{AutosaveTask, LoadMenu, PendingLoad, SaveNotice, autosave_system, save_game_system, load_game_system, load_pending_game, clear_pending_load};
+use crate::game::culture::{CulturePressure, TileFlippedEvent, culture_pressure_system};
+use crate::game::diplomacy::{FirstContactEvent, WarDeclaredEvent, deal_payment_system, first_contact_system, war_weariness_system};
+use crate::game::economy::{DeficitEvent, unit_upkeep_system};
+use crate::game::victory::{CivilizationEliminatedEvent, elimination_system, victory_check_system};
+use crate::game::automation::worker_automation_system;
+use crate::game::units::{UnitSelection, ocean_access_system, unit_selection_system, rename_unit_system, unit_order_system, goto_order_system, start_unit_turns, UnitCaptured, unit_capture_system, MoveHistory, move_history_upkeep_system, undo_move_system};
+use crate::game::input::{ClickIntent, CursorState, InputMode, ui_wants_input, TextEntry, TextEntrySubmitted, keyboard_unit_move_system, text_entry_system};
+use crate::game::cities::{CityCaptured, index_city_sites, update_city_defense, CityDestroyedEvent, destroy_razed_cities, CityGrewEvent, CityStarvingEvent, TerritoryExpandedEvent, ProductionCompletedEvent, CityFoundedEvent, log_city_events, CitySelection, TerritoryMap, WonderRegistry, wonder_system, trade_route_maintenance_system, blockade_system, BlockadeStartedEvent, start_city_rename_system, apply_city_rename_system, UnitProduced, city_capture_system, register_new_cities, process_city_turns, spawn_produced_units, spawn_great_people};
+use crate::game::ai::{ai_turn_system, ai_production_system, auto_production_system};
+use crate::game::barbarians::{BarbarianCampClearedEvent, BarbarianSpawnedEvent, place_barbarian_camps, barbarian_spawn_system, barbarian_turn_system, barbarian_camp_clearing_system};
+use crate::game::game_initialization::{AppState, InSession, GameState, TurnStartedEvent, EndTurnEvent, EndTurnGuard, TurnTimer, initialize_game, finish_loading, end_session, TeardownCheck, record_asset_baseline, check_session_teardown, end_turn_hotkey_system, end_turn_guard_system, turn_system, turn_timer_system};
+use crate::game::city_founding::{CityFoundingState, city_founding_system, worker_actions_system, great_person_ability_system, skip_unit_system, fortify_system, auto_turn_advance_system};
+use crate::game::unit_actions::{UnitActionEvent, unit_action_hotkey_system, unit_action_system, sentry_wake_system};
+use crate::game::combat::{CombatState, UnitDamagedEvent, combat_system, attack_click_system, cleanup_dead_units_system};
+use crate::game::combat_log::CombatLog;
+use crate::game::replay::{ReplayLog, replay_record_system};
+use crate::game::notifications::{GameNotification, relay_notifications_system};
+
+/// The game itself: its state, the turn pipeline, the AI and everything the player's
+/// orders set off. Needs no window, so a headless run uses it alone.
+pub struct GameLogicPlugin;
+
+impl Plugin for GameLogicPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(CivilizationManager::default())
+            .insert_resource(UnitSelection::default())
+            .insert_resource(ClickIntent::default())
+            .insert_resource(CursorState::default())
+            .insert_resource(InputMode::default())
+            .init_resource::<TextEntry>()
+            .init_resource::<StableIds>()
+            .init_resource::<InputMap>()
+            .insert_resource(TileIndex::default())
+            .insert_resource(GameState::default())
+            .insert_resource(CityFoundingState::default())
+            .insert_resource(CombatState::default())
+            .init_resource::<StatsHistory>()
+            .init_resource::<CulturePressure>()
+            .init_resource::<CombatLog>()
+            .init_resource::<AutosaveTask>()
+            .init_resource::<LoadMenu>()
+            .init_resource::<EndTurnGuard>()
+            .init_resource::<TurnTimer>()
+            .init_resource::<CameraTween>()
+            .init_resource::<MoveHistory>()
+            .init_resource::<ReplayLog>()
+            .add_event::<TurnStartedEvent>()
+            .add_event::<TechResearchedEvent>()
+            .add_event::<CivilizationEliminatedEvent>()
+            .add_event::<BarbarianCampClearedEvent>()
+            .add_event::<BarbarianSpawnedEvent>()
+            .add_event::<DeficitEvent>()
+            .add_event::<TileFlippedEvent>()
+            .add_event::<FirstContactEvent>()
+            .add_event::<WarDeclaredEvent>()
+            .add_event::<UnitProduced>()
+            .add_event::<CityCaptured>()
+            .add_event::<BlockadeStartedEvent>()
+            .add_event::<UnitDamagedEvent>()
+            .add_event::<UnitCaptured>()
+            .add_event::<CityGrewEvent>()
+            .add_event::<CityStarvingEvent>()
+            .add_event::<TerritoryExpandedEvent>()
+            .add_event::<ProductionCompletedEvent>()
+            .add_event::<CityFoundedEvent>()
+            .add_event::<CityDestroyedEvent>()
+            .add_event::<TextEntrySubmitted>()
+            .add_event::<SaveNotice>()
+            .add_event::<GameNotification>()
+            .add_event::<UnitActionEvent>()
+            .add_event::<EndTurnEvent>()
+            .insert_resource(CitySelection::default())
+            .init_resource::<TerritoryMap>()
+            .init_resource::<WonderRegistry>()
+            .init_resource::<WorldGenTask>()
+            .init_state::<AppState>()
+            .add_computed_state::<InSession>()
+            .enable_state_scoped_entities::<AppState>()
+            .add_systems(OnExit(InSession), end_session)
+            .add_systems(OnEnter(AppState::Loading), (
+                record_asset_baseline,
+                start_world_generation.run_if(not(resource_exists::<PendingLoad>)),
+                load_pending_game.run_if(resource_exists::<PendingLoad>),
+            ))
+            .add_systems(OnExit(AppState::Loading), clear_pending_load)
+            .add_systems(PreUpdate, (
+                text_entry_system.after(bevy::input::InputSystem),
+                keyboard_unit_move_system.after(text_entry_system),
+                save_game_system.after(text_entry_system),
+                load_game_system.after(text_entry_system),
+                // After saving, which keeps Ctrl+S from also skipping the selected unit
+                unit_action_hotkey_system.after(save_game_system),
+                end_turn_hotkey_system.after(text_entry_system),
+            ).run_if(in_state(AppState::InGame)))
+            .add_systems(PreUpdate, index_new_tiles)
+            .add_systems(Update, (
+                // Loading a new game, each step waiting on the one before; a loaded save
+                // arrives already initialized and goes straight through
+                finish_world_generation,
+                initialize_game,
+                place_barbarian_camps.run_if(not(resource_exists::<PendingLoad>)),
+                finish_loading,
+            ).chain().run_if(in_state(AppState::Loading)))
+            .add_systems(Update, (
+                // Core game systems (Group 1)
+                turn_system.after(end_turn_guard_system),
+                turn_timer_system.before(turn_system),
+                register_new_cities,
+                index_city_sites,
+                process_city_turns.after(register_new_cities).after(turn_system).after(ai_turn_system),
+                spawn_produced_units.after(process_city_turns).after(turn_system).after(ai_turn_system),
+                spawn_great_people.after(process_city_turns).after(turn_system).after(ai_turn_system),
+                (research_system.after(process_city_turns), ocean_access_system.after(research_system)),
+                unit_upkeep_system.after(process_city_turns).before(research_system),
+                deal_payment_system.after(turn_system).after(ai_turn_system),
+                wonder_system.after(process_city_turns).after(turn_system).after(ai_turn_system),
+                trade_route_maintenance_system.before(process_city_turns),
+                destroy_razed_cities.after(process_city_turns).after(turn_system).after(ai_turn_system),
+                start_unit_turns.after(turn_system).after(ai_turn_system),
+                (goto_order_system, worker_automation_system).after(start_unit_turns),
+                sentry_wake_system.after(start_unit_turns),
+                cleanup_dead_units_system,
+                elimination_system.after(city_capture_system).after(destroy_razed_cities),
+                victory_check_system.after(elimination_system).after(turn_system).after(ai_turn_system),
+                move_history_upkeep_system,
+            ).run_if(in_state(AppState::InGame)))
+            .add_systems(Update, (
+                // Player actions (Group 2)
+                city_founding_system,
+                worker_actions_system,
+                great_person_ability_system,
+                skip_unit_system,
+                fortify_system,
+                // After combat_system, and so after basic_input: Escape leaves move mode
+                // without also opening the pause menu
+                unit_action_system.after(combat_system),
+                auto_turn_advance_system,
+                (
+                    combat_system,
+                    // Map clicks: none reach the map while the cursor is over a panel
+                    attack_click_system.after(combat_system).run_if(not(ui_wants_input)),
+                ),
+                unit_order_system.after(attack_click_system),
+                city_capture_system.after(combat_system),
+                unit_capture_system.after(combat_system),
+                update_city_defense.before(combat_system),
+                rename_unit_system,
+                start_city_rename_system,
+                apply_city_rename_system,
+                unit_selection_system.run_if(not(ui_wants_input)),
+                undo_move_system.before(unit_order_system),
+                first_contact_system,
+                end_turn_guard_system,
+                log_city_events.after(city_founding_system),
+            ).run_if(in_state(AppState::InGame)))
+            .add_systems(Update, (
+                // AI and barbarians (Group 7)
+                ai_turn_system.after(turn_system),
+                ai_production_system.after(ai_turn_system).before(process_city_turns),
+                auto_production_system.after(turn_system).after(ai_turn_system).after(process_city_turns),
+                barbarian_turn_system.after(turn_system).after(ai_turn_system),
+                barbarian_spawn_system.after(barbarian_turn_system).after(turn_system).after(ai_turn_system),
+                barbarian_camp_clearing_system,
+            ).run_if(in_state(AppState::InGame)))
+            .add_systems(Update, (
+                // Statistics, strategic resources, culture, blockades and war weariness (Group 8)
+                strategic_resource_system.before(process_city_turns),
+                blockade_system.before(trade_route_maintenance_system).before(process_city_turns),
+                stats_history_system.after(turn_system).after(ai_turn_system),
+                culture_pressure_system.after(process_city_turns).after(turn_system).after(ai_turn_system),
+                war_weariness_system.after(combat_system).after(turn_system).after(ai_turn_system).before(process_city_turns),
+            ).run_if(in_state(AppState::InGame)))
+            .add_systems(Update, relay_notifications_system.after(city_founding_system).after(attack_click_system).run_if(in_state(InSession)))
+            .add_systems(Update, check_session_teardown.run_if(resource_exists::<TeardownCheck>.and(not(in_state(InSession)))))
+            // After the frame's gameplay, so an autosave sees the turn's upkeep finished
+            .add_systems(Last, (autosave_system, replay_record_system).run_if(in_state(AppState::InGame)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::input::InputPlugin;
+    use bevy::state::app::StatesPlugin;
+    use bevy::time::TimeUpdateStrategy;
+    use rand::RngCore;
+    use std::time::Duration;
+    use crate::game::ai::AiTurnTimer;
+    use crate::game::cities::UnitType;
+    use crate::game::combat_log::CombatLogEntry;
+    use crate::game::diplomacy::WarStrength;
+    use crate::game::game_initialization::{GameSettings, advance_turn};
+    use crate::game::headless::HeadlessPlugin;
+    use crate::game::map::spawn_world;
+    use crate::game::rng::GameRng;
+    use crate::game::stable_id::StableId;
+    use crate::game::test_support::{TEST_SEED, run};
+    use crate::game::units::Unit;
+    use crate::game::world_gen::{WorldGenerator, WorldType};
+    
+    const ROUNDS: u32 = 5;
+    // Frames each civilization's units get to act before its turn is handed on
+    const FRAMES_PER_TURN: usize = 3;
+    
+    // The game a headless run plays, on a generated map, in play with its first turn begun
+    fn headless_game(settings: GameSettings, map_radius: i32) -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin, InputPlugin))
+            .insert_resource(AiTurnTimer::new(settings.turn_delay))
+            .insert_resource(GameRng::new(TEST_SEED))
+            .insert_resource(settings)
+            .add_plugins((GameLogicPlugin, HeadlessPlugin));
+        
+        let config = WorldType::Continents.config();
+        let mut generator = WorldGenerator::with_seed(map_radius, config.clone(), 11);
+        let world_tiles = generator.generate();
+        let sea_level = generator.sea_level;
+        run(&mut app, move |mut commands: Commands, mut index: ResMut<TileIndex>| {
+            spawn_world(&mut commands, &mut index, world_tiles.clone(), sea_level, config.clone());
+        });
+        run(&mut app, initialize_game);
+        app.world_mut().resource_mut::<NextState<AppState>>().set(AppState::InGame);
+        app.update();
+        assert_eq!(*app.world().resource::<State<AppState>>().get(), AppState::InGame);
+        app
+    }
+    
+    // The game as a headless run builds it plays several rounds with every civilization's
+    // turn handed on by advance_turn
+    #[test]
+    fn headless_game_plays_several_rounds() {
+        // Long enough that the AI never ends a turn itself
+        let mut app = headless_game(GameSettings { headless: true, humans: 0, autosave_interval: 0, turn_delay: 3600.0, ..default() }, 30);
+        
+        let civ_turns = app.world().resource::<CivilizationManager>().civilizations.len() as u32 * ROUNDS;
+        for _ in 0..civ_turns {
+            for _ in 0..FRAMES_PER_TURN {
+                app.update();
+            }
+            run(&mut app, |mut game_state: ResMut<GameState>, mut civ_manager: ResMut<CivilizationManager>, mut turn_events: EventWriter<TurnStartedEvent>| {
+                advance_turn(&mut game_state, &mut civ_manager, &mut turn_events);
+            });
+        }
+        app.update();
+        
+        assert_eq!(app.world().resource::<GameState>().game_turn, 1 + ROUNDS);
+        assert_eq!(*app.world().resource::<State<AppState>>().get(), AppState::InGame);
+    }
+    
+    // Rounds of the all-out war below; on its small map the armies meet well inside them
+    const WAR_ROUNDS: u32 = 20;
+    const FRAME: Duration = Duration::from_millis(100);
+    const MAX_FRAMES: usize = 20_000;
+    
+    // Where a game has got to: every unit by its ID, the cities, the fights and the next
+    // combat roll
+    type Outcome = (Vec<(StableId, UnitType, u32, HexCoord, u32)>, Vec<(String, u32, HexCoord, u32)>, Vec<CombatLogEntry>, u64);
+    
+    // A seeded game with the AI playing everyone and everyone at war, played for WAR_ROUNDS
+    fn play_war(quick_movement_ai: bool) -> Outcome {
+        let settings = GameSettings { headless: true, humans: 0, autosave_interval: 0, turn_delay: 0.0, seed: Some(TEST_SEED), quick_movement_ai, ..default() };
+        let mut app = headless_game(settings, 20);
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(FRAME));
+        {
+            let mut manager = app.world_mut().resource_mut::<CivilizationManager>();
+            let civ_ids: Vec<u32> = manager.civilizations.keys().copied().collect();
+            for &a in &civ_ids {
+                for &b in civ_ids.iter().filter(|b| **b > a) {
+                    manager.diplomacy.declare_war((a, WarStrength::default()), (b, WarStrength::default()), 0);
+                }
+            }
+        }
+        
+        for _ in 0..MAX_FRAMES {
+            if app.world().resource::<GameState>().game_turn > WAR_ROUNDS {
+                break;
+            }
+            app.update();
+        }
+        assert!(app.world().resource::<GameState>().game_turn > WAR_ROUNDS, "the AI should have played {} rounds", WAR_ROUNDS);
+        
+        let mut units: Vec<_> = app.world_mut()
+            .query::<(&StableId, &Unit)>()
+            .iter(app.world())
+            .map(|(id, unit)| (*id, unit.unit_type, unit.civilization_id, unit.hex_coord, unit.health))
+            .collect();
+        units.sort_by_key(|unit| unit.0);
+        let mut cities: Vec<_> = app.world_mut()
+            .query::<&City>()
+            .iter(app.world())
+            .map(|city| (city.name.clone(), city.civilization_id, city.hex_coord, city.population))
+            .collect();
+        cities.sort_by(|a, b| a.0.cmp(&b.0));
+        let fights = app.world().resource::<CombatLog>().recorded_since(0).cloned().collect();
+        let next_roll = app.world_mut().resource_mut::<GameRng>().next_u64();
+        (units, cities, fights, next_roll)
+    }
+    
+    // Quick AI movement only changes how fast AI turns go by: the same seed plays the
+    // same game, fight for fight and roll for roll, with it on or off
+    #[test]
+    fn quick_ai_movement_plays_the_same_game() {
+        let quick = play_war(true);
+        assert!(!quick.2.is_empty(), "the armies should have fought");
+        assert_eq!(play_war(true), quick, "a seeded game should play the same every time");
+        assert_eq!(play_war(false), quick);
+    }
+}
@@ -0,0 +1,410 @@
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use super::cities::{City, UnitType, Wonder};
+use super::civilization::{CivilizationManager, Technology};
+use super::combat_log::{CombatLog, CombatLogEntry};
+use super::game_initialization::TurnStartedEvent;
+use super::hex::HexCoord;
+use super::map::{MapTile, WorldInfo, WorldOrigin};
+use super::units::Unit;
+use super::world_gen::{WorldGenConfig, WorldGenerator};
+
+/// Where a recorded game's map came from, and a checksum of the map it was played on.
+/// Playback generates the world again from the seed and config and checks it against
+/// the checksum before anything plays.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ReplayWorld {
+    pub origin: WorldOrigin,
+    pub config: WorldGenConfig,
+    pub terrain: u64,
+}
+
+impl ReplayWorld {
+    // Generates the world afresh and sums up its terrain as the recorder does the live map's.
+    // Takes as long as generating the world did, so playback does it off the main thread.
+    pub fn regenerate(&self) -> u64 {
+        let mut generator = WorldGenerator::with_seed(self.origin.radius, self.config.clone(), self.origin.seed);
+        terrain_checksum(generator.generate().iter().map(|tile| (tile.hex_coord, tile.biome, tile.resource)))
+    }
+}
+
+/// One consequential thing that happened, in the order it happened
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ReplayAction {
+    // Where the recording begins: the turn, the cities standing, how many technologies
+    // each civilization knows and, for a generated world, what it was generated from
+    Start {
+        turn: u32,
+        cities: Vec<(HexCoord, u32, String)>,
+        techs: Vec<(u32, usize)>,
+        #[serde(default)]
+        world: Option<ReplayWorld>,
+    },
+    TurnStarted { turn: u32, civ: u32 },
+    UnitMoved { civ: u32, unit: UnitType, from: HexCoord, to: HexCoord },
+    Combat(CombatLogEntry),
+    CityFounded { civ: u32, name: String, at: HexCoord },
+    CityCaptured { civ: u32, previous_civ: u32, name: String, at: HexCoord },
+    CityDestroyed { civ: u32, name: String, at: HexCoord },
+    TechResearched { civ: u32, tech: Technology },
+    WarDeclared { civ: u32, other: u32 },
+    PeaceMade { civ: u32, other: u32 },
+    WonderBuilt { civ: u32, wonder: Wonder, city: String, at: HexCoord },
+    // A full turn went round; `checksum` sums up the live game's map, cities and
+    // technologies at that point
+    TurnEnded { turn: u32, checksum: u64 },
+}
+
+impl ReplayAction {
+    // Where the camera should look while this plays
+    pub fn location(&self) -> Option<HexCoord> {
+        match self {
+            ReplayAction::UnitMoved { to, .. } => Some(*to),
+            ReplayAction::Combat(entry) => Some(entry.location),
+            ReplayAction::CityFounded { at, .. }
+            | ReplayAction::CityCaptured { at, .. }
+            | ReplayAction::CityDestroyed { at, .. }
+            | ReplayAction::WonderBuilt { at, .. } => Some(*at),
+            _ => None,
+        }
+    }
+    
+    // Whether this is one of the turning points a game's recap lists: cities changing hands,
+    // wars and wonders
+    pub fn is_major(&self) -> bool {
+        matches!(
+            self,
+            ReplayAction::CityFounded { .. }
+                | ReplayAction::CityCaptured { .. }
+                | ReplayAction::CityDestroyed { .. }
+                | ReplayAction::WarDeclared { .. }
+                | ReplayAction::PeaceMade { .. }
+                | ReplayAction::WonderBuilt { .. }
+        )
+    }
+}
+
+// What the recorder saw last frame, so it can tell what changed. Not saved: after a
+// load it's taken afresh from the restored game.
+#[derive(Clone, Default)]
+struct Tracked {
+    turn: u32,
+    cities: HashMap<Entity, (u32, HexCoord, String)>,
+    units: HashMap<Entity, HexCoord>,
+    techs: HashMap<u32, usize>,
+    wars: HashSet<(u32, u32)>,
+    wonders: HashSet<Wonder>,
+    log_seen: usize,
+}
+
+/// Everything that happened this game, recorded as it happened and saved with it
+#[derive(Resource, Clone, Default, Serialize, Deserialize)]
+pub struct ReplayLog {
+    pub actions: Vec<ReplayAction>,
+    #[serde(skip)]
+    tracked: Option<Tracked>,
+}
+
+/// The part of the game a replay rebuilds: who holds which city and how many
+/// technologies each civilization knows, from its actions, and the terrain of the map
+/// generated again from its seed
+#[derive(Clone, Default)]
+pub struct ReplayState {
+    pub cities: HashMap<HexCoord, (u32, String)>,
+    pub techs: HashMap<u32, usize>,
+    pub terrain: Option<u64>, // Set once the world is generated again; None for a world with no seed
+}
+
+impl ReplayState {
+    pub fn apply(&mut self, action: &ReplayAction) {
+        match action {
+            ReplayAction::Start { cities, techs, .. } => {
+                self.cities = cities.iter().map(|(at, civ, name)| (*at, (*civ, name.clone()))).collect();
+                self.techs = techs.iter().copied().collect();
+            }
+            ReplayAction::CityFounded { civ, name, at } | ReplayAction::CityCaptured { civ, name, at, .. } => {
+                self.cities.insert(*at, (*civ, name.clone()));
+            }
+            ReplayAction::CityDestroyed { at, .. } => {
+                self.cities.remove(at);
+            }
+            ReplayAction::TechResearched { civ, .. } => *self.techs.entry(*civ).or_default() += 1,
+            _ => {}
+        }
+    }
+    
+    pub fn checksum(&self) -> u64 {
+        checksum(
+            self.cities.iter().map(|(at, (civ, _))| (*at, *civ)),
+            self.techs.iter().map(|(civ, count)| (*civ, *count)),
+            self.terrain,
+        )
+    }
+}
+
+// FNV-1a, fed whole values so the checksums stay the same from one build to the next
+struct Fnv(u64);
+
+impl Fnv {
+    fn new() -> Self {
+        Fnv(0xcbf2_9ce4_8422_2325)
+    }
+    
+    fn feed(&mut self, value: i64) {
+        for byte in value.to_le_bytes() {
+            self.0 ^= byte as u64;
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+}
+
+// Sums up the sorted cities and tech counts, and the terrain checksum if the world has
+// one, so it doesn't depend on map order
+fn checksum(cities: impl Iterator<Item = (HexCoord, u32)>, techs: impl Iterator<Item = (u32, usize)>, terrain: Option<u64>) -> u64 {
+    let mut cities: Vec<_> = cities.map(|(at, civ)| (at.q, at.r, civ)).collect();
+    cities.sort();
+    let mut techs: Vec<_> = techs.filter(|(_, count)| *count > 0).collect();
+    techs.sort();
+    
+    let mut hash = Fnv::new();
+    for (q, r, civ) in cities {
+        hash.feed(q as i64);
+        hash.feed(r as i64);
+        hash.feed(civ as i64);
+    }
+    for (civ, count) in techs {
+        hash.feed(civ as i64);
+        hash.feed(count as i64);
+    }
+    if let Some(terrain) = terrain {
+        hash.feed(terrain as i64);
+    }
+    hash.0
+}
+
+// Sums up every tile's terrain and resource in coordinate order
+pub fn terrain_checksum(tiles: impl Iterator<Item = (HexCoord, u8, u8)>) -> u64 {
+    let mut tiles: Vec<_> = tiles.map(|(at, terrain, resource)| (at.q, at.r, terrain, resource)).collect();
+    tiles.sort();
+    
+    let mut hash = Fnv::new();
+    for (q, r, terrain, resource) in tiles {
+        hash.feed(q as i64);
+        hash.feed(r as i64);
+        hash.feed(terrain as i64);
+        hash.feed(resource as i64);
+    }
+    hash.0
+}
+
+/// The map being played on, as the recorder sums it up
+#[derive(SystemParam)]
+pub struct LiveMap<'w, 's> {
+    info: Option<Res<'w, WorldInfo>>,
+    origin: Option<Res<'w, WorldOrigin>>,
+    tiles: Query<'w, 's, &'static MapTile>,
+}
+
+impl LiveMap<'_, '_> {
+    // The terrain of every tile on the map, for a world that can be generated again
+    fn terrain(&self) -> Option<u64> {
+        self.origin.as_ref()?;
+        Some(terrain_checksum(self.tiles.iter().map(|tile| (tile.hex_coord, tile.terrain, tile.resource))))
+    }
+    
+    fn world(&self) -> Option<ReplayWorld> {
+        Some(ReplayWorld { origin: *self.origin.as_deref()?, config: self.info.as_ref()?.config.clone(), terrain: self.terrain()? })
+    }
+}
+
+// Appends what changed since last frame to the replay log: units that moved, fights,
+// cities founded, taken or destroyed, technologies, wars and peace, wonders and turn boundaries. Runs in Last,
+// after the frame's gameplay.
+pub fn replay_record_system(
+    mut log: ResMut<ReplayLog>,
+    civ_manager: Res<CivilizationManager>,
+    combat_log: Res<CombatLog>,
+    city_query: Query<(Entity, &City)>,
+    unit_query: Query<(Entity, &Unit)>,
+    mut turn_events: EventReader<TurnStartedEvent>,
+    live_map: LiveMap,
+) {
+    let cities: HashMap<Entity, (u32, HexCoord, String)> = city_query
+        .iter()
+        .map(|(entity, city)| (entity, (city.civilization_id, city.hex_coord, city.name.clone())))
+        .collect();
+    let units: HashMap<Entity, HexCoord> = unit_query.iter().map(|(entity, unit)| (entity, unit.hex_coord)).collect();
+    let techs: HashMap<u32, usize> = civ_manager.civilizations.values().map(|civ| (civ.id, civ.technologies.len())).collect();
+    let wars: HashSet<(u32, u32)> = civ_manager.diplomacy.wars().into_iter().collect();
+    let wonders: HashSet<Wonder> = city_query.iter().flat_map(|(_, city)| city.wonders.iter().copied()).collect();
+    
+    // A new game, or one just loaded: start watching from here
+    let Some(mut tracked) = log.tracked.take() else {
+        if log.actions.is_empty() {
+            log.actions.push(ReplayAction::Start {
+                turn: civ_manager.turn_number,
+                cities: cities.values().map(|(civ, at, name)| (*at, *civ, name.clone())).collect(),
+                techs: techs.iter().map(|(civ, count)| (*civ, *count)).collect(),
+                world: live_map.world(),
+            });
+        }
+        log.tracked = Some(Tracked { turn: civ_manager.turn_number, cities, units, techs, wars, wonders, log_seen: combat_log.recorded() });
+        turn_events.clear();
+        return;
+    };
+    
+    let mut actions = Vec::new();
+    for (entity, unit) in unit_query.iter() {
+        if let Some(from) = tracked.units.get(&entity)
+            && *from != unit.hex_coord
+        {
+            actions.push(ReplayAction::UnitMoved { civ: unit.civilization_id, unit: unit.unit_type, from: *from, to: unit.hex_coord });
+        }
+    }
+    actions.extend(combat_log.recorded_since(tracked.log_seen).cloned().map(ReplayAction::Combat));
+    for (entity, (civ, at, name)) in &tracked.cities {
+        if !cities.contains_key(entity) {
+            actions.push(ReplayAction::CityDestroyed { civ: *civ, name: name.clone(), at: *at });
+        }
+    }
+    for (entity, (civ, at, name)) in &cities {
+        match tracked.cities.get(entity) {
+            None => actions.push(ReplayAction::CityFounded { civ: *civ, name: name.clone(), at: *at }),
+            Some((previous_civ, _, _)) if previous_civ != civ => {
+                actions.push(ReplayAction::CityCaptured { civ: *civ, previous_civ: *previous_civ, name: name.clone(), at: *at });
+            }
+            _ => {}
+        }
+    }
+    // Researched, traded for or handed over by a Great Scientist alike
+    for civ in civ_manager.civilizations.values() {
+        let known = tracked.techs.get(&civ.id).copied().unwrap_or(0);
+        for tech in civ.technologies.iter().skip(known) {
+            actions.push(ReplayAction::TechResearched { civ: civ.id, tech: *tech });
+        }
+    }
+    for (civ, other) in wars.difference(&tracked.wars) {
+        actions.push(ReplayAction::WarDeclared { civ: *civ, other: *other });
+    }
+    for (civ, other) in tracked.wars.difference(&wars) {
+        actions.push(ReplayAction::PeaceMade { civ: *civ, other: *other });
+    }
+    for (_, city) in city_query.iter() {
+        for wonder in city.wonders.iter().filter(|wonder| !tracked.wonders.contains(wonder)) {
+            actions.push(ReplayAction::WonderBuilt { civ: city.civilization_id, wonder: *wonder, city: city.name.clone(), at: city.hex_coord });
+        }
+    }
+    // The turn boundary covers everything above; the new turn's starts come after it
+    if civ_manager.turn_number > tracked.turn {
+        let checksum = checksum(
+            cities.values().map(|(civ, at, _)| (*at, *civ)),
+            techs.iter().map(|(civ, count)| (*civ, *count)),
+            live_map.terrain(),
+        );
+        actions.push(ReplayAction::TurnEnded { turn: tracked.turn, checksum });
+        tracked.turn = civ_manager.turn_number;
+    }
+    for event in turn_events.read() {
+        actions.push(ReplayAction::TurnStarted { turn: civ_manager.turn_number, civ: event.civilization_id });
+    }
+    
+    tracked.cities = cities;
+    tracked.units = units;
+    tracked.techs = techs;
+    tracked.wars = wars;
+    tracked.wonders = wonders;
+    tracked.log_seen = combat_log.recorded();
+    log.actions.extend(actions);
+    log.tracked = Some(tracked);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::civilization::create_default_civilizations;
+    use crate::game::map::{TerrainType, TileIndex, spawn_world};
+    use crate::game::test_support::{game_app, run};
+    use crate::game::world_gen::WorldType;
+    
+    const ORIGIN: WorldOrigin = WorldOrigin { seed: 5, radius: 30 };
+    
+    // A generated world with one civilization holding one city, recording from turn 1
+    fn recorded_game() -> App {
+        let mut app = game_app(Vec::new());
+        app.init_resource::<CombatLog>().init_resource::<ReplayLog>();
+        let config = WorldType::Continents.config();
+        let mut generator = WorldGenerator::with_seed(ORIGIN.radius, config.clone(), ORIGIN.seed);
+        let world_tiles = generator.generate();
+        let sea_level = generator.sea_level;
+        run(&mut app, move |mut commands: Commands, mut index: ResMut<TileIndex>| {
+            spawn_world(&mut commands, &mut index, world_tiles.clone(), sea_level, config.clone());
+            commands.insert_resource(ORIGIN);
+        });
+        
+        let mut manager = CivilizationManager::default();
+        manager.add_civilization(create_default_civilizations().remove(0));
+        manager.turn_number = 1;
+        app.insert_resource(manager);
+        app.world_mut().spawn(City::new("Rome".to_string(), HexCoord::new(0, 0), 1, 1, true));
+        run(&mut app, replay_record_system);
+        app
+    }
+    
+    fn end_turn(app: &mut App) {
+        app.world_mut().resource_mut::<CivilizationManager>().turn_number += 1;
+        run(app, replay_record_system);
+    }
+    
+    // Plays the log against the world generated again from its seed, as the viewer does;
+    // whether each turn's checksum matched
+    fn play_back(log: &ReplayLog) -> Vec<bool> {
+        let mut state = ReplayState::default();
+        let mut matched = Vec::new();
+        for action in &log.actions {
+            if let ReplayAction::Start { world: Some(world), .. } = action {
+                state.terrain = Some(world.regenerate());
+            }
+            state.apply(action);
+            if let ReplayAction::TurnEnded { checksum, .. } = action {
+                matched.push(state.checksum() == *checksum);
+            }
+        }
+        matched
+    }
+    
+    // The recording knows which world it was played on, generating it again gives the same
+    // map, and every turn checks out
+    #[test]
+    fn replay_generates_the_recorded_world_again() {
+        let mut app = recorded_game();
+        end_turn(&mut app);
+        end_turn(&mut app);
+        
+        let log = app.world().resource::<ReplayLog>();
+        let Some(ReplayAction::Start { world: Some(world), .. }) = log.actions.first() else {
+            panic!("the recording doesn't start with its world: {:?}", log.actions.first());
+        };
+        assert_eq!(world.origin, ORIGIN);
+        assert_eq!(world.regenerate(), world.terrain);
+        assert_eq!(play_back(log), vec![true, true]);
+        
+        let other_seed = ReplayWorld { origin: WorldOrigin { seed: 6, ..ORIGIN }, ..world.clone() };
+        assert_ne!(other_seed.regenerate(), world.terrain);
+    }
+    
+    // The checksum is taken from the live game, not from the log: a map that stops
+    // matching its seed partway through shows up at the next turn boundary
+    #[test]
+    fn a_live_map_that_drifts_from_its_seed_desyncs() {
+        let mut app = recorded_game();
+        end_turn(&mut app);
+        let mut tiles = app.world_mut().query::<&mut MapTile>();
+        let mut changed = tiles.iter_mut(app.world_mut()).next().unwrap();
+        changed.terrain = if changed.terrain == TerrainType::HotDesert as u8 { TerrainType::Ocean } else { TerrainType::HotDesert } as u8;
+        end_turn(&mut app);
+        
+        assert_eq!(play_back(app.world().resource::<ReplayLog>()), vec![true, false]);
+    }
+}
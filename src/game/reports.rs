@@ -0,0 +1,193 @@
+use bevy::prelude::*;
+use bevy::ecs::entity::Entities;
+use bevy::ecs::system::SystemParam;
+use std::collections::HashMap;
+use super::cities::{City, UnitType};
+use super::civilization::{CivilizationManager, UNKNOWN_PLAYER};
+use super::culling::Cullable;
+use super::game_initialization::{GameSettings, GameState, TurnStartedEvent};
+use super::map::{MapTile, WorldInfo};
+use super::rng::GameRng;
+use super::statistics::{MilitaryComparison, military_strength, shows_everything};
+use super::units::Unit;
+use super::world_gen::BiomeType;
+
+// Biomes the debug report lists, most common first
+const BIOMES_LISTED: usize = 8;
+
+/// What the turn summary (F4) is drawn from: the civilization whose turn it is
+#[derive(SystemParam)]
+pub struct TurnSummarySources<'w, 's> {
+    unit_query: Query<'w, 's, &'static Unit>,
+    city_query: Query<'w, 's, &'static City>,
+    civ_manager: Res<'w, CivilizationManager>,
+}
+
+impl TurnSummarySources<'_, '_> {
+    pub fn lines(&self) -> Vec<String> {
+        let Some(civ) = self.civ_manager.get_civilization(self.civ_manager.current_turn_civ) else {
+            return vec!["No civilization is taking its turn".to_string()];
+        };
+        let mut lines = vec![format!("=== TURN SUMMARY: {} (turn {}) ===", civ.name, self.civ_manager.turn_number)];
+        
+        // Units by type, in the order they're listed everywhere else
+        let units: Vec<&Unit> = self.unit_query.iter().filter(|unit| unit.civilization_id == civ.id).collect();
+        let can_move = units.iter().filter(|unit| unit.movement_points > 0 && !unit.has_moved).count();
+        lines.push(format!("{:<18} {:>5}", "Units", units.len()));
+        for unit_type in UnitType::ALL {
+            let count = units.iter().filter(|unit| unit.unit_type == unit_type).count();
+            if count > 0 {
+                lines.push(format!("  {:<16} {:>5}", unit_type.get_name(), count));
+            }
+        }
+        lines.push(format!("  {:<16} {:>5}", "Can still move", can_move));
+        
+        let cities: Vec<&City> = self.city_query.iter().filter(|city| city.civilization_id == civ.id).collect();
+        let population: u32 = cities.iter().map(|city| city.population).sum();
+        lines.push(format!("{:<18} {:>5}", "Cities", cities.len()));
+        lines.push(format!("{:<18} {:>5}", "Population", population));
+        
+        lines.push(String::new());
+        lines.push(format!("{:<12} {:>8} {:>8} {:>8} {:>8} {:>8}", "", "Food", "Prod", "Science", "Gold", "Culture"));
+        let sum = |yield_of: fn(&City) -> f32| cities.iter().map(|city| yield_of(city)).sum::<f32>();
+        lines.push(format!(
+            "{:<12} {:>8.1} {:>8.1} {:>8.1} {:>8.1} {:>8.1}",
+            "Per turn",
+            sum(|city| city.food_per_turn),
+            sum(|city| city.production_per_turn),
+            sum(|city| city.science_per_turn),
+            sum(City::net_gold_per_turn),
+            sum(|city| city.culture_per_turn),
+        ));
+        lines.push(format!("{:<12} {:>8} {:>8} {:>8.0} {:>8.0}", "Accumulated", "", "", civ.science_points, civ.gold));
+        lines
+    }
+}
+
+/// What the debug report (F3) is drawn from
+#[derive(SystemParam)]
+pub struct DebugSources<'w, 's> {
+    tile_query: Query<'w, 's, (&'static MapTile, Option<&'static Visibility>)>,
+    marker_query: Query<'w, 's, &'static Visibility, (With<Cullable>, Without<MapTile>)>,
+    unit_query: Query<'w, 's, &'static Unit>,
+    city_query: Query<'w, 's, (), With<City>>,
+    world_info: Option<Res<'w, WorldInfo>>,
+    civ_manager: Res<'w, CivilizationManager>,
+    settings: Res<'w, GameSettings>,
+    game_state: Res<'w, GameState>,
+    game_rng: Res<'w, GameRng>,
+    time: Res<'w, Time>,
+    entities: &'w Entities,
+}
+
+impl DebugSources<'_, '_> {
+    pub fn lines(&self) -> Vec<String> {
+        // Zoomed all the way out, nearly every tile should be on screen. Without a window
+        // nothing is culled.
+        let culled = |visibility: Option<&Visibility>| visibility == Some(&Visibility::Hidden);
+        let visible = || self.tile_query.iter().filter(|(_, visibility)| !culled(*visibility)).map(|(tile, _)| tile);
+        let visible_tiles = visible().count();
+        let total_tiles = self.tile_query.iter().count();
+        let culled_tiles = total_tiles - visible_tiles;
+        let culled_markers = self.marker_query.iter().filter(|visibility| culled(Some(*visibility))).count();
+        let shown_markers = self.marker_query.iter().count() - culled_markers;
+        let frame_seconds = self.time.delta_secs();
+        // A player only gets their own pieces and figures; the totals are for observers
+        // and --omniscient
+        let exact = shows_everything(&self.settings, &self.civ_manager);
+        let player = self.civ_manager.get_player_civilization().filter(|_| !exact);
+        let pieces = match player {
+            Some(player) => format!("yours: {} units, {} cities", player.units.len(), player.cities.len()),
+            None => format!("{} units, {} cities", self.unit_query.iter().count(), self.city_query.iter().count()),
+        };
+        
+        let mut lines = vec![
+            "=== DEBUG INFO ===".to_string(),
+            format!("{:<16} {} ({:?})", "Game turn", self.game_state.game_turn, self.game_state.current_phase),
+            format!("{:<16} {} (replay with --seed {})", "Combat seed", self.game_rng.seed(), self.game_rng.seed()),
+            format!("{:<16} {:.1} ms ({:.0} fps)", "Frame time", frame_seconds * 1000.0, 1.0 / frame_seconds.max(0.0001)),
+            format!("{:<16} {}", "Entities", self.entities.len()),
+            format!("{:<16} {}", "Pieces", pieces),
+            String::new(),
+            format!("{:<16} {:>7} {:>7} {:>7}", "", "Total", "Shown", "Culled"),
+            format!("{:<16} {:>7} {:>7} {:>7}", "Tiles", total_tiles, visible_tiles, culled_tiles),
+            format!("{:<16} {:>7} {:>7} {:>7}", "Markers", shown_markers + culled_markers, shown_markers, culled_markers),
+            format!("{:<16} {:>6.1}%", "Culling ratio", culled_tiles as f32 / total_tiles.max(1) as f32 * 100.0),
+        ];
+        
+        // What's on screen
+        let count = visible_tiles.max(1) as f32;
+        lines.push(String::new());
+        lines.push(format!(
+            "Shown: {} rivers, {} coastal, {} resources",
+            visible().filter(|tile| tile.has_river).count(),
+            visible().filter(|tile| tile.is_coastal).count(),
+            visible().filter(|tile| tile.resource != 0).count(),
+        ));
+        lines.push(format!(
+            "Averages: temperature {:.2}, precipitation {:.2}, soil fertility {:.2}",
+            visible().map(|tile| tile.temperature).sum::<f32>() / count,
+            visible().map(|tile| tile.precipitation).sum::<f32>() / count,
+            visible().map(|tile| tile.soil_fertility).sum::<f32>() / count,
+        ));
+        if self.world_info.is_some() {
+            let mut biome_counts: HashMap<u8, usize> = HashMap::new();
+            for tile in visible() {
+                *biome_counts.entry(tile.biome).or_insert(0) += 1;
+            }
+            let mut biomes: Vec<(u8, usize)> = biome_counts.into_iter().collect();
+            biomes.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+            for (biome, tiles) in biomes.into_iter().take(BIOMES_LISTED) {
+                let name = format!("{:?}", BiomeType::from_u8(biome));
+                lines.push(format!("  {:<28} {:>6} {:>6.1}%", name, tiles, tiles as f32 / count * 100.0));
+            }
+        }
+        
+        lines.push(String::new());
+        lines.push(format!("{:<4} {:<24} {:>6} {:>6} {:>7} {:>8}", "Civ", "Name", "Cities", "Units", "Gold", "Science"));
+        let mut civ_ids: Vec<u32> = self.civ_manager.civilizations.keys().copied().collect();
+        civ_ids.sort_unstable();
+        let player_military = player.map_or(0.0, |player| military_strength(player.id, self.unit_query.iter()));
+        for civ_id in civ_ids {
+            let Some(civ) = self.civ_manager.get_civilization(civ_id) else { continue };
+            if !self.civ_manager.player_knows(civ_id) {
+                lines.push(format!("{:<4} {}", civ_id, UNKNOWN_PLAYER));
+                continue;
+            }
+            // Their cities are on the map; their treasury and research aren't, and their
+            // army is only sized up against the player's
+            if player.is_some_and(|player| player.id != civ_id) {
+                let army = MilitaryComparison::between(military_strength(civ_id, self.unit_query.iter()), player_military);
+                lines.push(format!(
+                    "{:<4} {:<24} {:>6} {:>6} {:>7} {:>8}  army {}",
+                    civ_id, civ.name, civ.cities.len(), "?", "?", "?", army.get_name(),
+                ));
+                continue;
+            }
+            lines.push(format!(
+                "{:<4} {:<24} {:>6} {:>6} {:>7.0} {:>8.0}",
+                civ_id, civ.name, civ.cities.len(), civ.units.len(), civ.gold, civ.science_points,
+            ));
+        }
+        lines
+    }
+}
+
+// With --log-reports and nobody watching, prints each civilization's summary as its turn
+// begins and the debug report once a round
+pub fn log_reports_system(
+    mut turn_events: EventReader<TurnStartedEvent>,
+    settings: Res<GameSettings>,
+    summary: TurnSummarySources,
+    debug: DebugSources,
+    mut last_round: Local<Option<u32>>,
+) {
+    if turn_events.read().count() == 0 || !settings.log_reports {
+        return;
+    }
+    println!("{}", summary.lines().join("\n"));
+    if *last_round != Some(debug.game_state.game_turn) {
+        *last_round = Some(debug.game_state.game_turn);
+        println!("{}", debug.lines().join("\n"));
+    }
+}
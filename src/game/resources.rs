@@ -1,9 +1,13 @@
 use bevy::prelude::*;
-use super::hex::HexCoord;
+use std::collections::HashMap;
+use super::cities::City;
+use super::fonts::GameFonts;
+use super::civilization::CivilizationManager;
+use super::map::TileLookup;
 use super::world_gen::BiomeType;
-use noise::{NoiseFn, Perlin};
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Copy, PartialEq, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
 pub enum ResourceType {
     None = 0,
     Gold = 1,
@@ -69,6 +73,28 @@ impl ResourceType {
         }
     }
 
+    pub fn get_name(self) -> &'static str {
+        match self {
+            ResourceType::None => "Nothing",
+            ResourceType::Gold => "Gold",
+            ResourceType::Iron => "Iron",
+            ResourceType::Wheat => "Wheat",
+            ResourceType::Fish => "Fish",
+            ResourceType::Stone => "Stone",
+            ResourceType::Wood => "Wood",
+            ResourceType::Oil => "Oil",
+            ResourceType::Horses => "Horses",
+            ResourceType::Gems => "Gems",
+            ResourceType::Copper => "Copper",
+            ResourceType::Coal => "Coal",
+            ResourceType::Cattle => "Cattle",
+            ResourceType::Spices => "Spices",
+            ResourceType::Silk => "Silk",
+            ResourceType::Wine => "Wine",
+            ResourceType::Salt => "Salt",
+        }
+    }
+
     pub fn color(self) -> Color {
         match self {
             ResourceType::None => Color::WHITE,
@@ -91,6 +117,61 @@ impl ResourceType {
         }
     }
 
+    /// Food, production and gold the resource adds to the tile it's on
+    pub fn bonus_yields(self) -> (f32, f32, f32) {
+        match self {
+            ResourceType::Wheat | ResourceType::Fish => (2.0, 0.0, 0.0),
+            ResourceType::Wood => (0.0, 2.0, 0.0),
+            ResourceType::Iron | ResourceType::Stone => (0.0, 1.0, 0.0),
+            ResourceType::Gold => (0.0, 0.0, 3.0),
+            ResourceType::Gems => (0.0, 0.0, 2.0),
+            ResourceType::Spices | ResourceType::Silk => (0.0, 0.0, 1.0),
+            _ => (0.0, 0.0, 0.0),
+        }
+    }
+
+    // What the resource does, in a line: its tile bonus and whether it's a luxury or strategic
+    pub fn effect_summary(self) -> String {
+        let (food, production, gold) = self.bonus_yields();
+        let mut parts: Vec<String> = [(food, "food"), (production, "production"), (gold, "gold")]
+            .iter()
+            .filter(|(amount, _)| *amount > 0.0)
+            .map(|(amount, name)| format!("+{} {}", amount, name))
+            .collect();
+        if self.is_luxury() {
+            parts.push("luxury: makes nearby cities happier".to_string());
+        }
+        if self.is_strategic() {
+            parts.push("strategic: some units need it".to_string());
+        }
+        if parts.is_empty() {
+            "No effect on its own".to_string()
+        } else {
+            parts.join(", ")
+        }
+    }
+
+    /// Luxuries make nearby cities happier
+    pub fn is_luxury(self) -> bool {
+        matches!(
+            self,
+            ResourceType::Gold | ResourceType::Gems | ResourceType::Spices | ResourceType::Silk | ResourceType::Wine
+        )
+    }
+
+    /// Strategic resources matter for military units; some can't be built without one
+    pub fn is_strategic(self) -> bool {
+        matches!(
+            self,
+            ResourceType::Iron
+                | ResourceType::Horses
+                | ResourceType::Wood
+                | ResourceType::Copper
+                | ResourceType::Coal
+                | ResourceType::Oil
+        )
+    }
+
     /// Get resources that can appear on specific biome types
     pub fn for_biome(biome: u8) -> Vec<ResourceType> {
         match BiomeType::from_u8(biome) {
@@ -138,25 +219,54 @@ impl ResourceType {
     }
 }
 
-pub fn generate_resource(hex_coord: HexCoord, biome: u8) -> u8 {
-    let resource_noise = Perlin::new(789);
-    
-    // Lower chance of resources (about 15% of tiles)
-    let resource_chance = resource_noise.get([
-        hex_coord.q as f64 * 0.3,
-        hex_coord.r as f64 * 0.3,
-    ]) as f32;
-    
-    if resource_chance > 0.7 {
-        let possible_resources = ResourceType::for_biome(biome);
-        if !possible_resources.is_empty() {
-            // Use coordinate hash to pick consistent resource
-            let index = ((hex_coord.q.abs() + hex_coord.r.abs() * 3) as usize) % possible_resources.len();
-            return possible_resources[index] as u8;
+// Strategic resource tiles inside each civilization's territory. Tile improvements don't
+// exist yet, so owning the tile is enough to count as having it.
+pub fn count_strategic_resources<'a>(
+    cities: impl Iterator<Item = &'a City>,
+    tiles: &TileLookup,
+) -> HashMap<u32, HashMap<ResourceType, u32>> {
+    let mut counts: HashMap<u32, HashMap<ResourceType, u32>> = HashMap::new();
+    for city in cities {
+        for coord in &city.territory_tiles {
+            let Some(tile) = tiles.get(*coord) else { continue };
+            let resource = ResourceType::from_u8(tile.resource);
+            if resource.is_strategic() {
+                *counts.entry(city.civilization_id).or_default().entry(resource).or_default() += 1;
+            }
         }
     }
+    counts
+}
+
+// Keeps every civilization's strategic stockpile in step with its territory. Recounted
+// whenever a city changes (borders grow, it's captured) or one disappears.
+pub fn strategic_resource_system(
+    mut civ_manager: ResMut<CivilizationManager>,
+    city_query: Query<&City>,
+    changed_cities: Query<(), Changed<City>>,
+    mut removed_cities: RemovedComponents<City>,
+    tiles: TileLookup,
+) {
+    let cities_removed = removed_cities.read().count() > 0;
+    if changed_cities.is_empty() && !cities_removed {
+        return;
+    }
     
-    ResourceType::None as u8
+    let mut counts = count_strategic_resources(city_query.iter(), &tiles);
+    let changed: Vec<u32> = civ_manager
+        .civilizations
+        .values()
+        .filter(|civ| counts.get(&civ.id).map_or(!civ.strategic_resources.is_empty(), |stockpile| *stockpile != civ.strategic_resources))
+        .map(|civ| civ.id)
+        .collect();
+    for civ_id in changed {
+        let Some(civ) = civ_manager.get_civilization_mut(civ_id) else { continue };
+        let stockpile = counts.remove(&civ_id).unwrap_or_default();
+        for resource in civ.strategic_resources.keys().filter(|resource| !stockpile.contains_key(resource)) {
+            println!("{} has lost access to {}", civ.name, resource.get_name());
+        }
+        civ.strategic_resources = stockpile;
+    }
 }
 
 #[derive(Component)]
@@ -170,6 +280,7 @@ pub struct RiverMarker;
 pub fn spawn_resource_markers(
     mut commands: Commands,
     tiles_query: Query<(Entity, &crate::game::map::MapTile, &Transform), Added<crate::game::map::MapTile>>,
+    fonts: Res<GameFonts>,
 ) {
     for (tile_entity, tile, _transform) in tiles_query.iter() {
         let mut children = Vec::new();
@@ -182,10 +293,7 @@ pub fn spawn_resource_markers(
                 ResourceMarker { resource_type },
                 Text2d::new(resource_type.symbol()),
                 TextColor(resource_type.color()),
-                TextFont {
-                    font_size: 20.0,
-                    ..default()
-                },
+                fonts.symbol(20.0),
                 Transform::from_translation(Vec3::new(8.0, 8.0, 1.0)), // Top-right corner
             )).id();
             
@@ -198,10 +306,7 @@ pub fn spawn_resource_markers(
                 RiverMarker,
                 Text2d::new("≈"), // Wave symbol for river
                 TextColor(Color::srgb(0.3, 0.6, 1.0)),
-                TextFont {
-                    font_size: 16.0,
-                    ..default()
-                },
+                fonts.symbol(16.0),
                 Transform::from_translation(Vec3::new(-8.0, -8.0, 1.0)), // Bottom-left corner
             )).id();
             
@@ -0,0 +1,259 @@
+use bevy::prelude::*;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize, Serializer};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::path::Path;
+use std::sync::OnceLock;
+use super::cities::{Building, BuildingModifiers, UnitType, Wonder};
+use super::civilization::Technology;
+use super::units::UnitStats;
+
+pub const DEFAULT_RULES_DIR: &str = "rules";
+const UNITS_FILE: &str = "units.ron";
+const BUILDINGS_FILE: &str = "buildings.ron";
+const TECHS_FILE: &str = "techs.ron";
+const SCORE_FILE: &str = "score.ron";
+
+// The rules the game runs with, installed once at startup. Lookups before that see the built-in defaults.
+static RULES: OnceLock<GameRules> = OnceLock::new();
+
+/// Costs and effects of one building
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct BuildingRules {
+    pub cost: f32,
+    pub maintenance: f32,
+    pub yields: (f32, f32, f32, f32, f32), // Food, production, science, gold, culture
+    #[serde(default)]
+    pub happiness: f32,
+    #[serde(default)]
+    pub health: f32,
+    #[serde(default)]
+    pub defense: f32,
+    #[serde(default)]
+    pub modifiers: BuildingModifiers,
+}
+
+/// Cost and yields of one wonder
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct WonderRules {
+    pub cost: f32,
+    pub yields: (f32, f32, f32, f32, f32), // Food, production, science, gold, culture
+}
+
+/// Points a civilization's score gets for each citizen, tile of territory, technology,
+/// wonder and piece of gold
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScoreWeights {
+    pub citizen: f32,
+    pub tile: f32,
+    pub tech: f32,
+    pub wonder: f32,
+    pub gold: f32,
+}
+
+impl Default for ScoreWeights {
+    fn default() -> Self {
+        Self {
+            citizen: 2.0,
+            tile: 1.0,
+            tech: 5.0,
+            wonder: 20.0,
+            gold: 0.1,
+        }
+    }
+}
+
+/// Every balance number that used to be a match arm: unit stats, building and wonder
+/// costs and effects, tech costs and score weights. The enums stay the keys; the numbers come from
+/// RON files in the rules directory, falling back to the built-in values. Not a
+/// resource: unit, building and tech methods look them up through `rules()`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameRules {
+    pub units: HashMap<UnitType, UnitStats>,
+    pub buildings: HashMap<Building, BuildingRules>,
+    pub wonders: HashMap<Wonder, WonderRules>,
+    pub tech_costs: HashMap<Technology, f32>,
+    pub score: ScoreWeights,
+}
+
+// What buildings.ron holds
+#[derive(Deserialize)]
+struct BuildingsFile {
+    buildings: HashMap<Building, BuildingRules>,
+    wonders: HashMap<Wonder, WonderRules>,
+}
+
+// Serializes a map in the keys' usual order so written files are stable and readable
+struct Ordered<'a, K, V>(&'a [K], &'a HashMap<K, V>);
+
+impl<K: Serialize + Eq + Hash, V: Serialize> Serialize for Ordered<'_, K, V> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.0.iter().filter_map(|key| self.1.get(key).map(|value| (key, value))))
+    }
+}
+
+#[derive(Serialize)]
+struct OrderedBuildings<'a> {
+    buildings: Ordered<'a, Building, BuildingRules>,
+    wonders: Ordered<'a, Wonder, WonderRules>,
+}
+
+impl Default for GameRules {
+    fn default() -> Self {
+        Self {
+            units: UnitType::ALL.iter().map(|unit| (*unit, unit.default_stats())).collect(),
+            buildings: Building::ALL.iter().map(|building| (*building, building.default_rules())).collect(),
+            wonders: Wonder::ALL.iter().map(|wonder| (*wonder, wonder.default_rules())).collect(),
+            tech_costs: Technology::ALL.iter().map(|tech| (*tech, tech.default_cost())).collect(),
+            score: ScoreWeights::default(),
+        }
+    }
+}
+
+// Reads one rules file. A missing file is fine (None); a broken one is reported with
+// its line and column.
+fn read_file<T: DeserializeOwned>(dir: &Path, name: &str, errors: &mut Vec<String>) -> Option<T> {
+    let path = dir.join(name);
+    let text = std::fs::read_to_string(&path).ok()?;
+    match ron::from_str(&text) {
+        Ok(value) => Some(value),
+        Err(error) => {
+            errors.push(format!("{}:{}: {}", path.display(), error.position, error.code));
+            None
+        }
+    }
+}
+
+fn to_ron<T: Serialize>(value: &T) -> std::io::Result<String> {
+    ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default()).map_err(std::io::Error::other)
+}
+
+// Replaces the defaults with the file's entries, reporting any key the file leaves out
+fn merge<K: Copy + Eq + Hash + std::fmt::Debug, V>(
+    defaults: &mut HashMap<K, V>,
+    loaded: HashMap<K, V>,
+    keys: &[K],
+    file: &Path,
+    errors: &mut Vec<String>,
+) {
+    for key in keys {
+        if !loaded.contains_key(key) {
+            errors.push(format!("{}: no entry for {:?}; using the built-in one", file.display(), key));
+        }
+    }
+    defaults.extend(loaded);
+}
+
+impl GameRules {
+    // The rules in `dir`, with built-in values for anything missing. Problems are
+    // printed rather than fatal so a broken mod still starts.
+    pub fn load(dir: &Path) -> Self {
+        let mut rules = GameRules::default();
+        if !dir.is_dir() {
+            return rules;
+        }
+        
+        let mut errors = Vec::new();
+        if let Some(units) = read_file::<HashMap<UnitType, UnitStats>>(dir, UNITS_FILE, &mut errors) {
+            merge(&mut rules.units, units, &UnitType::ALL, &dir.join(UNITS_FILE), &mut errors);
+        }
+        if let Some(file) = read_file::<BuildingsFile>(dir, BUILDINGS_FILE, &mut errors) {
+            merge(&mut rules.buildings, file.buildings, &Building::ALL, &dir.join(BUILDINGS_FILE), &mut errors);
+            merge(&mut rules.wonders, file.wonders, &Wonder::ALL, &dir.join(BUILDINGS_FILE), &mut errors);
+        }
+        if let Some(tech_costs) = read_file::<HashMap<Technology, f32>>(dir, TECHS_FILE, &mut errors) {
+            merge(&mut rules.tech_costs, tech_costs, &Technology::ALL, &dir.join(TECHS_FILE), &mut errors);
+        }
+        if let Some(score) = read_file::<ScoreWeights>(dir, SCORE_FILE, &mut errors) {
+            rules.score = score;
+        }
+        
+        for error in &errors {
+            println!("Rules: {}", error);
+        }
+        println!("Loaded game rules from {}", dir.display());
+        rules
+    }
+    
+    // Writes these rules out as RON files, a starting point for a mod
+    pub fn write(&self, dir: &Path) -> std::io::Result<()> {
+        std::fs::create_dir_all(dir)?;
+        std::fs::write(dir.join(UNITS_FILE), to_ron(&Ordered(&UnitType::ALL, &self.units))?)?;
+        let buildings = OrderedBuildings {
+            buildings: Ordered(&Building::ALL, &self.buildings),
+            wonders: Ordered(&Wonder::ALL, &self.wonders),
+        };
+        std::fs::write(dir.join(BUILDINGS_FILE), to_ron(&buildings)?)?;
+        std::fs::write(dir.join(TECHS_FILE), to_ron(&Ordered(&Technology::ALL, &self.tech_costs))?)?;
+        std::fs::write(dir.join(SCORE_FILE), to_ron(&self.score)?)?;
+        Ok(())
+    }
+    
+    pub fn unit(&self, unit_type: UnitType) -> UnitStats {
+        self.units.get(&unit_type).cloned().unwrap_or_else(|| unit_type.default_stats())
+    }
+    
+    pub fn building(&self, building: Building) -> BuildingRules {
+        self.buildings.get(&building).cloned().unwrap_or_else(|| building.default_rules())
+    }
+    
+    pub fn wonder(&self, wonder: Wonder) -> WonderRules {
+        self.wonders.get(&wonder).cloned().unwrap_or_else(|| wonder.default_rules())
+    }
+    
+    pub fn tech_cost(&self, tech: Technology) -> f32 {
+        self.tech_costs.get(&tech).copied().unwrap_or_else(|| tech.default_cost())
+    }
+}
+
+// Makes `rules` the ones every lookup uses. Only the first call counts.
+pub fn install_rules(rules: GameRules) {
+    if RULES.set(rules).is_err() {
+        println!("Game rules were already installed; keeping the first set");
+    }
+}
+
+pub fn rules() -> &'static GameRules {
+    RULES.get_or_init(GameRules::default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    // The shipped rules directory describes exactly the built-in game, so editing one
+    // without the other shows up here
+    #[test]
+    fn shipped_rules_match_the_defaults() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join(DEFAULT_RULES_DIR);
+        // Every file parses and lists every key, so nothing quietly falls back to a default
+        let mut errors = Vec::new();
+        let units = read_file::<HashMap<UnitType, UnitStats>>(&dir, UNITS_FILE, &mut errors).expect("units.ron");
+        let buildings = read_file::<BuildingsFile>(&dir, BUILDINGS_FILE, &mut errors).expect("buildings.ron");
+        let techs = read_file::<HashMap<Technology, f32>>(&dir, TECHS_FILE, &mut errors).expect("techs.ron");
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert!(UnitType::ALL.iter().all(|unit| units.contains_key(unit)));
+        assert!(Building::ALL.iter().all(|building| buildings.buildings.contains_key(building)));
+        assert!(Wonder::ALL.iter().all(|wonder| buildings.wonders.contains_key(wonder)));
+        assert!(Technology::ALL.iter().all(|tech| techs.contains_key(tech)));
+        
+        let shipped = GameRules::load(&dir);
+        let defaults = GameRules::default();
+        for unit in UnitType::ALL {
+            assert_eq!(shipped.unit(unit), defaults.unit(unit), "{:?} differs", unit);
+        }
+        for building in Building::ALL {
+            assert_eq!(shipped.building(building), defaults.building(building), "{:?} differs", building);
+        }
+        for wonder in Wonder::ALL {
+            assert_eq!(shipped.wonder(wonder), defaults.wonder(wonder), "{:?} differs", wonder);
+        }
+        for tech in Technology::ALL {
+            assert_eq!(shipped.tech_cost(tech), defaults.tech_cost(tech), "{:?} differs", tech);
+        }
+        assert_eq!(shipped.score, defaults.score);
+        assert_eq!(shipped, defaults);
+    }
+}
@@ -0,0 +1,647 @@
+use bevy::prelude::*;
+use bevy::ecs::entity::{EntityHashMap, MapEntities};
+use bevy::ecs::system::SystemParam;
+use bevy::tasks::{IoTaskPool, Task, futures::check_ready};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use super::barbarians::{BarbarianCamp, spawn_camp};
+use super::cities::{City, CityMarker, CitySelection, TerritoryMap, WonderRegistry};
+use super::civilization::CivilizationManager;
+use super::combat::CombatState;
+use super::combat_log::{CombatLog, CombatLogEntry};
+use super::culture::CulturePressure;
+use super::game_initialization::{AppState, GamePhase, GameSettings, GameState, TurnStartedEvent};
+use super::input::InputMode;
+use super::input_map::{Action, InputMap};
+use super::map::{MapTile, TileIndex, WorldInfo, WorldOrigin, spawn_world};
+use super::replay::ReplayLog;
+use super::rng::GameRng;
+use super::stable_id::{StableId, StableIds, reading_ids, writing_ids};
+use super::statistics::StatsHistory;
+use super::units::{Unit, UnitSelection};
+use super::world_gen::{WorldGenConfig, WorldTile};
+
+pub const SAVE_DIR: &str = "saves";
+pub const QUICKSAVE_FILE: &str = "quicksave.ron";
+// Autosaves rotate through this many files, autosave_1 being the newest
+pub const AUTOSAVE_SLOTS: usize = 3;
+// Folder for this game under the platform's data directory
+const APP_DIR: &str = "civ_game";
+
+// Bumped whenever the file layout changes; older files are refused rather than misread
+const SAVE_VERSION: u32 = 2;
+
+/// The world as it was generated: every tile plus the settings that shaped it
+#[derive(Serialize, Deserialize)]
+pub struct SavedWorld {
+    pub sea_level: f32,
+    pub config: WorldGenConfig,
+    pub tiles: Vec<MapTile>,
+    #[serde(default)]
+    pub origin: Option<WorldOrigin>, // None for a world from an older save
+}
+
+/// Everything needed to pick a game back up. Cities and units are stored under their
+/// StableId, and references to them elsewhere (civilization rosters, territory, wonders,
+/// trade routes, culture pressure) are written as the same IDs and pointed at the new
+/// entities on load.
+#[derive(Serialize, Deserialize)]
+pub struct SaveGame {
+    pub version: u32,
+    pub seed: u64,
+    pub game_state: GameState,
+    pub civ_manager: CivilizationManager,
+    pub world: SavedWorld,
+    pub cities: Vec<(StableId, City)>,
+    pub units: Vec<(StableId, Unit)>,
+    pub camps: Vec<BarbarianCamp>,
+    pub territory: TerritoryMap,
+    pub wonders: WonderRegistry,
+    pub culture_pressure: CulturePressure,
+    pub stats_history: StatsHistory,
+    pub combat_log: Vec<CombatLogEntry>,
+    #[serde(default)]
+    pub replay: ReplayLog,
+    // The entity each StableId is referred to by in this save: the live ones when it's
+    // captured, stand-ins when it's read from a file
+    #[serde(skip)]
+    ids: EntityHashMap<StableId>,
+}
+
+// Just enough of a save to list it
+#[derive(Deserialize)]
+struct SaveHeader {
+    version: u32,
+    game_state: GameState,
+}
+
+// How much of a file holds its header; the map and everything else come after
+const HEADER_BYTES: u64 = 4096;
+
+// Reads the header from the top of a save without parsing the whole map: SaveGame
+// writes version, seed and game_state first, so everything before civ_manager is a
+// struct that only needs closing.
+fn read_header(path: &Path) -> Option<SaveHeader> {
+    let mut top = Vec::new();
+    std::fs::File::open(path).ok()?.take(HEADER_BYTES).read_to_end(&mut top).ok()?;
+    let top = String::from_utf8_lossy(&top);
+    let end = top.find("civ_manager:")?;
+    let header: SaveHeader = ron::from_str(&format!("{})", &top[..end])).ok()?;
+    (header.version == SAVE_VERSION).then_some(header)
+}
+
+impl SaveGame {
+    fn to_ron(&self) -> std::io::Result<String> {
+        // Keep each tile, city and unit on one line; fully pretty output runs to a
+        // line per field
+        let config = ron::ser::PrettyConfig::default().depth_limit(3);
+        writing_ids(&self.ids, || ron::ser::to_string_pretty(self, config)).map_err(std::io::Error::other)
+    }
+    
+    fn from_ron(text: &str) -> ron::error::SpannedResult<Self> {
+        let (save, ids) = reading_ids(|| ron::from_str::<SaveGame>(text));
+        save.map(|save| SaveGame { ids, ..save })
+    }
+    
+    pub fn write(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, self.to_ron()?)
+    }
+    
+    // Reads a save file, reporting a broken one with its line and column
+    pub fn read(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|error| format!("{}: {}", path.display(), error))?;
+        let save = SaveGame::from_ron(&text).map_err(|error| format!("{}:{}: {}", path.display(), error.position, error.code))?;
+        if save.version != SAVE_VERSION {
+            return Err(format!("{}: save version {}, expected {}", path.display(), save.version, SAVE_VERSION));
+        }
+        Ok(save)
+    }
+}
+
+// This game's folder in the platform's per-user config directory, or the working
+// directory where there is none
+pub fn config_dir() -> PathBuf {
+    let config_dir = if cfg!(windows) {
+        std::env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        std::env::var_os("HOME").map(|home| PathBuf::from(home).join("Library/Application Support"))
+    } else {
+        std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+    };
+    match config_dir {
+        Some(dir) => dir.join(APP_DIR),
+        None => PathBuf::new(),
+    }
+}
+
+// Saves live in the platform's per-user data directory, or in ./saves where there is none
+pub fn save_dir() -> PathBuf {
+    let data_dir = if cfg!(windows) {
+        std::env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        std::env::var_os("HOME").map(|home| PathBuf::from(home).join("Library/Application Support"))
+    } else {
+        std::env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+    };
+    match data_dir {
+        Some(dir) => dir.join(APP_DIR).join(SAVE_DIR),
+        None => PathBuf::from(SAVE_DIR),
+    }
+}
+
+fn quicksave_path() -> PathBuf {
+    save_dir().join(QUICKSAVE_FILE)
+}
+
+fn autosave_path(slot: usize) -> PathBuf {
+    save_dir().join(format!("autosave_{}.ron", slot))
+}
+
+// Writes a new autosave into slot 1, moving the older ones down a slot and dropping the
+// last. The new file is written aside first, so a failed write loses no autosave.
+fn write_autosave(save: SaveGame) -> std::io::Result<PathBuf> {
+    let dir = save_dir();
+    std::fs::create_dir_all(&dir)?;
+    let pending = dir.join("autosave.tmp");
+    std::fs::write(&pending, save.to_ron()?)?;
+    for slot in (1..AUTOSAVE_SLOTS).rev() {
+        let older = autosave_path(slot);
+        if older.exists() {
+            std::fs::rename(&older, autosave_path(slot + 1))?;
+        }
+    }
+    let newest = autosave_path(1);
+    std::fs::rename(&pending, &newest)?;
+    Ok(newest)
+}
+
+/// A save or load finished or failed; shown with the player's notifications
+#[derive(Event)]
+pub struct SaveNotice {
+    pub message: String,
+}
+
+/// The state a save is taken from
+#[derive(SystemParam)]
+pub struct SaveSources<'w, 's> {
+    game_state: Res<'w, GameState>,
+    civ_manager: Res<'w, CivilizationManager>,
+    world_info: Res<'w, WorldInfo>,
+    world_origin: Option<Res<'w, WorldOrigin>>,
+    rng: Res<'w, GameRng>,
+    territory: Res<'w, TerritoryMap>,
+    wonders: Res<'w, WonderRegistry>,
+    culture_pressure: Res<'w, CulturePressure>,
+    stats_history: Res<'w, StatsHistory>,
+    combat_log: Res<'w, CombatLog>,
+    replay: Res<'w, ReplayLog>,
+    tiles: Query<'w, 's, &'static MapTile>,
+    cities: Query<'w, 's, (Entity, &'static StableId, &'static City)>,
+    units: Query<'w, 's, (Entity, &'static StableId, &'static Unit)>,
+    camps: Query<'w, 's, &'static BarbarianCamp>,
+}
+
+impl SaveSources<'_, '_> {
+    fn capture(&self) -> SaveGame {
+        // In ID order, so the same game always writes the same file
+        let mut cities: Vec<_> = self.cities.iter().map(|(_, id, city)| (*id, city.clone())).collect();
+        cities.sort_by_key(|(id, _)| *id);
+        let mut units: Vec<_> = self.units.iter().map(|(_, id, unit)| (*id, unit.clone())).collect();
+        units.sort_by_key(|(id, _)| *id);
+        let ids = self.cities
+            .iter()
+            .map(|(entity, id, _)| (entity, *id))
+            .chain(self.units.iter().map(|(entity, id, _)| (entity, *id)))
+            .collect();
+        SaveGame {
+            version: SAVE_VERSION,
+            seed: self.rng.seed(),
+            game_state: self.game_state.clone(),
+            civ_manager: self.civ_manager.clone(),
+            world: SavedWorld {
+                sea_level: self.world_info.sea_level,
+                config: self.world_info.config.clone(),
+                tiles: self.tiles.iter().cloned().collect(),
+                origin: self.world_origin.as_deref().copied(),
+            },
+            cities,
+            units,
+            camps: self.camps.iter().cloned().collect(),
+            territory: self.territory.clone(),
+            wonders: self.wonders.clone(),
+            culture_pressure: self.culture_pressure.clone(),
+            stats_history: self.stats_history.clone(),
+            combat_log: self.combat_log.oldest_first().cloned().collect(),
+            replay: self.replay.clone(),
+            ids,
+        }
+    }
+    
+    // Writes the quicksave, returning what to tell the player. Only on the player's own
+    // turn, so no AI turn is caught halfway through.
+    pub fn quicksave(&self) -> String {
+        if self.game_state.current_phase != GamePhase::PlayerTurn {
+            return "Games can only be saved during your turn".to_string();
+        }
+        
+        let path = quicksave_path();
+        match self.capture().write(&path) {
+            Ok(()) => {
+                println!("Game saved to {}", path.display());
+                format!("Game saved (turn {})", self.game_state.game_turn)
+            }
+            Err(error) => format!("Could not save the game to {}: {}", path.display(), error),
+        }
+    }
+}
+
+// Ctrl+S writes the quicksave
+pub fn save_game_system(
+    mut keyboard: ResMut<ButtonInput<KeyCode>>,
+    input_map: Res<InputMap>,
+    sources: SaveSources,
+    mut notices: EventWriter<SaveNotice>,
+) {
+    if !input_map.just_pressed(Action::QuickSave, &keyboard) {
+        return;
+    }
+    // Not also read by whatever else shares the key
+    input_map.consume(Action::QuickSave, &mut keyboard);
+    
+    notices.write(SaveNotice { message: sources.quicksave() });
+}
+
+/// The autosave being written in the background, if any
+#[derive(Resource, Default)]
+pub struct AutosaveTask(Option<Task<std::io::Result<PathBuf>>>);
+
+// Autosaves every `autosave_interval` turns once the player's turn has begun. Runs in
+// Last and waits a frame after TurnStartedEvent, so the turn's upkeep is in the save.
+// The state is copied here; turning it into RON and writing it happen off the main
+// thread so a large map doesn't stall a frame.
+pub fn autosave_system(
+    mut turn_events: EventReader<TurnStartedEvent>,
+    settings: Res<GameSettings>,
+    sources: SaveSources,
+    mut task: ResMut<AutosaveTask>,
+    mut notices: EventWriter<SaveNotice>,
+    mut due: Local<bool>,
+) {
+    if let Some(running) = task.0.as_mut()
+        && let Some(result) = check_ready(running)
+    {
+        task.0 = None;
+        match result {
+            Ok(path) => println!("Autosaved to {}", path.display()),
+            Err(error) => {
+                notices.write(SaveNotice { message: format!("Autosave failed: {}", error) });
+            }
+        }
+    }
+    
+    if *due {
+        *due = false;
+        if sources.game_state.current_phase == GamePhase::PlayerTurn && task.0.is_none() {
+            let save = sources.capture();
+            task.0 = Some(IoTaskPool::get().spawn(async move { write_autosave(save) }));
+        }
+    }
+    
+    let player_id = sources.civ_manager.get_player_civilization().map(|civ| civ.id);
+    let player_turn_started = turn_events.read().any(|event| Some(event.civilization_id) == player_id);
+    let interval = settings.autosave_interval;
+    if player_turn_started && interval > 0 && sources.game_state.game_turn.is_multiple_of(interval) {
+        *due = true;
+    }
+}
+
+// Everything a load throws away: the map, cities, units and camps, and the city markers
+// (unit markers and banners clear themselves once their owner is gone)
+type WorldEntities = Or<(With<MapTile>, With<City>, With<Unit>, With<BarbarianCamp>, With<CityMarker>)>;
+
+/// The world a load replaces: resources to overwrite and the entities to clear away
+#[derive(SystemParam)]
+pub struct LoadTargets<'w, 's> {
+    commands: Commands<'w, 's>,
+    game_state: ResMut<'w, GameState>,
+    civ_manager: ResMut<'w, CivilizationManager>,
+    rng: ResMut<'w, GameRng>,
+    tile_index: ResMut<'w, TileIndex>,
+    territory: ResMut<'w, TerritoryMap>,
+    wonders: ResMut<'w, WonderRegistry>,
+    culture_pressure: ResMut<'w, CulturePressure>,
+    stats_history: ResMut<'w, StatsHistory>,
+    combat_log: ResMut<'w, CombatLog>,
+    replay: ResMut<'w, ReplayLog>,
+    stable_ids: ResMut<'w, StableIds>,
+    unit_selection: ResMut<'w, UnitSelection>,
+    city_selection: ResMut<'w, CitySelection>,
+    combat_state: ResMut<'w, CombatState>,
+    input_mode: ResMut<'w, InputMode>,
+    world_entities: Query<'w, 's, Entity, WorldEntities>,
+}
+
+impl LoadTargets<'_, '_> {
+    fn restore(&mut self, save: SaveGame) {
+        for entity in self.world_entities.iter() {
+            self.commands.entity(entity).despawn();
+        }
+        
+        let world_tiles: Vec<WorldTile> = save.world.tiles.iter().map(WorldTile::from).collect();
+        spawn_world(&mut self.commands, &mut self.tile_index, world_tiles, save.world.sea_level, save.world.config);
+        match save.world.origin {
+            Some(origin) => self.commands.insert_resource(origin),
+            None => self.commands.remove_resource::<WorldOrigin>(),
+        }
+        
+        // Reserve the new entities first, so references between saved objects can be
+        // pointed at them before anything is inserted
+        let mut spawned = HashMap::<StableId, Entity>::new();
+        for id in save.cities.iter().map(|(id, _)| id).chain(save.units.iter().map(|(id, _)| id)) {
+            spawned.insert(*id, self.commands.spawn_empty().id());
+        }
+        let mut entity_map: EntityHashMap<Entity> = save.ids
+            .iter()
+            .filter_map(|(referred_to, id)| Some((*referred_to, *spawned.get(id)?)))
+            .collect();
+        self.stable_ids.resume_after(spawned.keys().copied());
+        
+        // Each keeps its StableId, so the next save names it the same way
+        for (id, mut city) in save.cities {
+            city.map_entities(&mut entity_map);
+            self.commands.entity(spawned[&id]).insert((id, city));
+        }
+        for (id, unit) in save.units {
+            self.commands.entity(spawned[&id]).insert((id, unit));
+        }
+        for camp in save.camps {
+            spawn_camp(&mut self.commands, camp);
+        }
+        
+        *self.civ_manager = save.civ_manager;
+        self.civ_manager.map_entities(&mut entity_map);
+        *self.territory = save.territory;
+        self.territory.map_entities(&mut entity_map);
+        *self.wonders = save.wonders;
+        self.wonders.map_entities(&mut entity_map);
+        *self.culture_pressure = save.culture_pressure;
+        self.culture_pressure.map_entities(&mut entity_map);
+        *self.stats_history = save.stats_history;
+        self.combat_log.restore(save.combat_log);
+        *self.replay = save.replay;
+        *self.game_state = save.game_state;
+        *self.rng = GameRng::new(save.seed);
+        
+        self.unit_selection.selected_unit = None;
+        self.city_selection.selected_city = None;
+        *self.combat_state = CombatState::default();
+        *self.input_mode = InputMode::Normal;
+    }
+}
+
+/// One save offered by the load menu
+pub struct SaveSlot {
+    pub name: String,
+    pub path: PathBuf,
+    pub turn: Option<u32>, // None if the file can't be read as a save of this version
+    pub age: Option<Duration>, // Since the file was written
+}
+
+/// The saves listed by Ctrl+L: the quicksave, then the autosaves newest first
+#[derive(Resource, Default)]
+pub struct LoadMenu {
+    pub open: bool,
+    pub slots: Vec<SaveSlot>,
+}
+
+// Number keys picking a save from the load menu, in slot order
+const SLOT_KEYS: [KeyCode; 1 + AUTOSAVE_SLOTS] = [KeyCode::Digit1, KeyCode::Digit2, KeyCode::Digit3, KeyCode::Digit4];
+
+pub fn list_saves() -> Vec<SaveSlot> {
+    let files = std::iter::once(("Quicksave".to_string(), quicksave_path()))
+        .chain((1..=AUTOSAVE_SLOTS).map(|slot| (format!("Autosave {}", slot), autosave_path(slot))));
+    files
+        .filter(|(_, path)| path.exists())
+        .map(|(name, path)| {
+            let turn = read_header(&path).map(|header| header.game_state.game_turn);
+            let age = std::fs::metadata(&path)
+                .and_then(|metadata| metadata.modified())
+                .ok()
+                .and_then(|modified| SystemTime::now().duration_since(modified).ok());
+            SaveSlot { name, path, turn, age }
+        })
+        .collect()
+}
+
+// Ctrl+L opens the load menu and a number key loads that save, replacing the current
+// game. Combat rolls start over from the saved seed; the generator's exact position
+// isn't kept. While the menu is open it takes the number keys and Escape.
+pub fn load_game_system(
+    mut keyboard: ResMut<ButtonInput<KeyCode>>,
+    input_map: Res<InputMap>,
+    mut menu: ResMut<LoadMenu>,
+    mut targets: LoadTargets,
+    mut notices: EventWriter<SaveNotice>,
+) {
+    let player_turn = targets.game_state.current_phase == GamePhase::PlayerTurn;
+    if input_map.just_pressed(Action::LoadGame, &keyboard) {
+        input_map.consume(Action::LoadGame, &mut keyboard);
+        if menu.open {
+            menu.open = false;
+        } else if !player_turn {
+            notices.write(SaveNotice { message: "Games can only be loaded during your turn".to_string() });
+        } else {
+            menu.slots = list_saves();
+            menu.open = true;
+        }
+        return;
+    }
+    if !menu.open {
+        return;
+    }
+    if !player_turn || keyboard.clear_just_pressed(KeyCode::Escape) {
+        menu.open = false;
+        return;
+    }
+    
+    let Some(index) = SLOT_KEYS.iter().position(|key| keyboard.clear_just_pressed(*key)) else { return };
+    let Some(slot) = menu.slots.get(index) else { return };
+    let message = match SaveGame::read(&slot.path) {
+        Ok(save) => {
+            let message = format!("Loaded {} (turn {})", slot.name, save.game_state.game_turn);
+            println!("Loaded {}", slot.path.display());
+            targets.restore(save);
+            message
+        }
+        Err(error) => format!("Could not load the game: {}", error),
+    };
+    notices.write(SaveNotice { message });
+    menu.open = false;
+}
+
+/// The save picked from the main menu, restored once the loading screen is up
+#[derive(Resource)]
+pub struct PendingLoad(pub PathBuf);
+
+// Restores the save picked from the main menu. A file that can't be read sends the
+// player back to the menu.
+pub fn load_pending_game(
+    pending: Res<PendingLoad>,
+    mut targets: LoadTargets,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    match SaveGame::read(&pending.0) {
+        Ok(save) => {
+            println!("Loaded {}", pending.0.display());
+            targets.restore(save);
+        }
+        Err(error) => {
+            println!("Could not load the game: {}", error);
+            next_state.set(AppState::MainMenu);
+        }
+    }
+}
+
+pub fn clear_pending_load(mut commands: Commands) {
+    commands.remove_resource::<PendingLoad>();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::cities::{UnitType, Wonder, register_new_cities, wonder_system};
+    use crate::game::civilization::create_default_civilizations;
+    use crate::game::culture::PressureRecord;
+    use crate::game::hex::HexCoord;
+    use crate::game::map::TerrainType;
+    use crate::game::test_support::{area, game_app, run};
+    
+    const ROME: HexCoord = HexCoord { q: 0, r: 0 };
+    const ATHENS: HexCoord = HexCoord { q: 4, r: 0 };
+    
+    // An app holding everything a save is taken from and a load restores, with nothing
+    // in the world yet
+    fn save_app() -> App {
+        let mut app = game_app(Vec::new());
+        app.init_resource::<CulturePressure>()
+            .init_resource::<StatsHistory>()
+            .init_resource::<CombatLog>()
+            .init_resource::<ReplayLog>()
+            .init_resource::<UnitSelection>()
+            .init_resource::<CitySelection>()
+            .init_resource::<InputMode>();
+        app
+    }
+    
+    fn capture(app: &mut App) -> SaveGame {
+        run(app, |sources: SaveSources| sources.capture())
+    }
+    
+    fn load(app: &mut App, save: SaveGame) {
+        let mut save = Some(save);
+        run(app, move |mut targets: LoadTargets| targets.restore(save.take().expect("loaded once")));
+    }
+    
+    // A small grassland map, spawned as world generation spawns it, with Rome and Athens
+    // at war, a unit each, a wonder, a trade route and a tile under culture pressure
+    fn small_game() -> App {
+        let mut app = save_app();
+        let tiles: Vec<WorldTile> = area(ROME, 6, TerrainType::TemperateGrassland)
+            .into_iter()
+            .map(|mut tile| {
+                tile.biome = tile.terrain;
+                WorldTile::from(&tile)
+            })
+            .collect();
+        run(&mut app, move |mut commands: Commands, mut tile_index: ResMut<TileIndex>| {
+            spawn_world(&mut commands, &mut tile_index, tiles.clone(), 0.0, WorldGenConfig::default());
+        });
+        
+        let mut manager = CivilizationManager::default();
+        for civ in create_default_civilizations().into_iter().take(2) {
+            manager.add_civilization(civ);
+        }
+        app.insert_resource(manager);
+        let mut spawn_city = |name: &str, coord: HexCoord, civ_id: u32| {
+            let city = app.world_mut().spawn(City::new(name.to_string(), coord, civ_id, 1, civ_id == 1)).id();
+            app.world_mut().resource_mut::<CivilizationManager>().get_civilization_mut(civ_id).unwrap().add_city(city);
+            city
+        };
+        let rome = spawn_city("Rome", ROME, 1);
+        let athens = spawn_city("Athens", ATHENS, 2);
+        run(&mut app, register_new_cities);
+        for (unit_type, civ_id, coord) in [(UnitType::Warrior, 1, ROME), (UnitType::Archer, 2, ATHENS)] {
+            let unit = app.world_mut().spawn(Unit::new(unit_type, civ_id, coord)).id();
+            app.world_mut().resource_mut::<CivilizationManager>().get_civilization_mut(civ_id).unwrap().add_unit(unit);
+        }
+        
+        let mut city = app.world_mut().get_mut::<City>(rome).unwrap();
+        city.wonders.push(Wonder::Pyramids);
+        city.trade_routes.push(athens);
+        run(&mut app, wonder_system);
+        app.world_mut().resource_mut::<CulturePressure>().tiles.insert(
+            HexCoord { q: 2, r: 0 },
+            PressureRecord { civilization_id: 1, city: rome, turns: 4 },
+        );
+        app
+    }
+    
+    // The file a save writes, read back as a RON value so that maps compare whatever
+    // order their entries were written in
+    fn written(save: &SaveGame) -> ron::Value {
+        ron::from_str(&save.to_ron().expect("save should serialize")).expect("save should parse")
+    }
+    
+    // Every city and unit by StableId, with the entity it has in this run
+    fn stable_ids(app: &mut App) -> Vec<(StableId, Entity)> {
+        let mut ids: Vec<_> = app.world_mut()
+            .query_filtered::<(&StableId, Entity), Or<(With<City>, With<Unit>)>>()
+            .iter(app.world())
+            .map(|(id, entity)| (*id, entity))
+            .collect();
+        ids.sort();
+        ids
+    }
+    
+    // Saving, reading the file into a fresh game and saving again writes the same game.
+    // The loaded cities and units are new entities but keep their StableIds, and the
+    // rosters, territory, wonders, trade routes and culture pressure have to follow them
+    // for the second file to name them the same way.
+    #[test]
+    fn save_load_save_writes_the_same_game() {
+        let mut original = small_game();
+        let first = capture(&mut original);
+        assert!(first.wonders.site(Wonder::Pyramids).is_some());
+        assert!(first.territory.owner(HexCoord { q: 1, r: 0 }).is_some());
+        
+        let mut loaded = save_app();
+        // Taken up first, so no loaded entity can land on the one it was saved from
+        for _ in 0..first.world.tiles.len() * 2 {
+            loaded.world_mut().spawn_empty();
+        }
+        load(&mut loaded, SaveGame::from_ron(&first.to_ron().unwrap()).expect("save should read back"));
+        let second = capture(&mut loaded);
+        
+        let saved = stable_ids(&mut original);
+        let restored = stable_ids(&mut loaded);
+        assert_eq!(saved.len(), first.cities.len() + first.units.len());
+        assert_eq!(saved.iter().map(|(id, _)| *id).collect::<Vec<_>>(), restored.iter().map(|(id, _)| *id).collect::<Vec<_>>());
+        assert!(saved.iter().zip(&restored).all(|((_, before), (_, after))| before != after));
+        assert_eq!(written(&second), written(&first));
+        
+        // What comes next gets an ID of its own, not one the loaded game already uses
+        let recruit = loaded.world_mut().spawn(Unit::new(UnitType::Warrior, 1, ROME)).id();
+        let recruit_id = *loaded.world().get::<StableId>(recruit).expect("new units get an ID");
+        assert!(restored.iter().all(|(id, _)| *id < recruit_id));
+    }
+}
@@ -0,0 +1,117 @@
+use bevy::prelude::*;
+use bevy::ecs::component::HookContext;
+use bevy::ecs::entity::EntityHashMap;
+use bevy::ecs::world::DeferredWorld;
+use serde::de::{Deserializer, Error as _};
+use serde::ser::{Error as _, Serializer};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+/// A city's or unit's ID for the whole game. Entities only last as long as one run, so a
+/// save names its cities and units, and every reference to them, by this instead.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct StableId(pub u64);
+
+/// Hands each new city and unit the next StableId
+#[derive(Resource, Default)]
+pub struct StableIds {
+    next: u64,
+}
+
+impl StableIds {
+    fn issue(&mut self) -> StableId {
+        let id = StableId(self.next);
+        self.next += 1;
+        id
+    }
+
+    // After a load, carries on past every ID the loaded game uses
+    pub fn resume_after(&mut self, used: impl Iterator<Item = StableId>) {
+        self.next = used.map(|id| id.0 + 1).fold(self.next, u64::max);
+    }
+}
+
+// on_add hook for City and Unit: a new one gets the next StableId. One restored from a
+// save arrives with its own and keeps it.
+pub fn issue_stable_id(mut world: DeferredWorld, context: HookContext) {
+    if world.get::<StableId>(context.entity).is_some() {
+        return;
+    }
+    let Some(mut ids) = world.get_resource_mut::<StableIds>() else { return };
+    let id = ids.issue();
+    world.commands().entity(context.entity).insert(id);
+}
+
+thread_local! {
+    // While a save is written: the StableId each entity it refers to is written as
+    static WRITING: RefCell<Option<EntityHashMap<StableId>>> = const { RefCell::new(None) };
+    // While one is read: the stand-in entity given to each StableId it names
+    static READING: RefCell<Option<HashMap<StableId, Entity>>> = const { RefCell::new(None) };
+}
+
+// Runs `write` with the entity references it serializes written as the StableIds in `ids`
+pub fn writing_ids<T>(ids: &EntityHashMap<StableId>, write: impl FnOnce() -> T) -> T {
+    WRITING.with(|writing| *writing.borrow_mut() = Some(ids.clone()));
+    let written = write();
+    WRITING.with(|writing| *writing.borrow_mut() = None);
+    written
+}
+
+// Runs `read`, giving each StableId it deserializes a stand-in entity. Hands back the
+// stand-ins with their IDs, for the load to point at the entities it spawns.
+pub fn reading_ids<T>(read: impl FnOnce() -> T) -> (T, EntityHashMap<StableId>) {
+    READING.with(|reading| *reading.borrow_mut() = Some(HashMap::new()));
+    let read = read();
+    let stand_ins = READING.with(|reading| reading.borrow_mut().take()).unwrap_or_default();
+    (read, stand_ins.into_iter().map(|(id, entity)| (entity, id)).collect())
+}
+
+fn to_stable_id(entity: Entity) -> Option<StableId> {
+    WRITING.with(|writing| writing.borrow().as_ref()?.get(&entity).copied())
+}
+
+fn stand_in_for(id: StableId) -> Option<Entity> {
+    READING.with(|reading| {
+        let mut reading = reading.borrow_mut();
+        let stand_ins = reading.as_mut()?;
+        // Counting down from the top, well clear of the entities a game spawns
+        let next = Entity::from_raw(u32::MAX - 1 - stand_ins.len() as u32);
+        Some(*stand_ins.entry(id).or_insert(next))
+    })
+}
+
+/// `#[serde(with)]` for a saved reference to a city or unit: written as its StableId
+pub mod as_stable_id {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(entity: &Entity, serializer: S) -> Result<S::Ok, S::Error> {
+        let id = to_stable_id(*entity).ok_or_else(|| S::Error::custom(format!("{} is not a saved city or unit", entity)))?;
+        id.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Entity, D::Error> {
+        let id = StableId::deserialize(deserializer)?;
+        stand_in_for(id).ok_or_else(|| D::Error::custom("city and unit references can only be read as part of a save"))
+    }
+}
+
+/// `#[serde(with)]` for a list of saved references to cities or units
+pub mod as_stable_ids {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(entities: &[Entity], serializer: S) -> Result<S::Ok, S::Error> {
+        let ids = entities
+            .iter()
+            .map(|entity| to_stable_id(*entity).ok_or_else(|| S::Error::custom(format!("{} is not a saved city or unit", entity))))
+            .collect::<Result<Vec<_>, _>>()?;
+        ids.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Entity>, D::Error> {
+        Vec::<StableId>::deserialize(deserializer)?
+            .into_iter()
+            .map(|id| stand_in_for(id).ok_or_else(|| D::Error::custom("city and unit references can only be read as part of a save")))
+            .collect()
+    }
+}
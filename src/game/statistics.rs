@@ -0,0 +1,230 @@
+use bevy::prelude::*;
+use std::collections::HashMap;
+use super::cities::City;
+use super::civilization::{Civilization, CivilizationManager};
+use super::economy::{civ_finances, Finances};
+use super::game_initialization::{GameSettings, TurnStartedEvent};
+use super::units::Unit;
+use super::victory::compute_scores;
+use serde::{Deserialize, Serialize};
+
+/// One line of the demographics screen
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Demographic {
+    Population,
+    Land,
+    Military,
+    Science,
+    Gold,
+    Cities,
+}
+
+impl Demographic {
+    pub const ALL: [Demographic; 6] = [
+        Demographic::Population,
+        Demographic::Land,
+        Demographic::Military,
+        Demographic::Science,
+        Demographic::Gold,
+        Demographic::Cities,
+    ];
+    
+    pub fn get_name(self) -> &'static str {
+        match self {
+            Demographic::Population => "Population",
+            Demographic::Land => "Land (tiles)",
+            Demographic::Military => "Military Strength",
+            Demographic::Science => "Science per Turn",
+            Demographic::Gold => "Gold per Turn",
+            Demographic::Cities => "Cities",
+        }
+    }
+}
+
+// Within this factor of each other, two armies look about the same size
+const COMPARABLE_MILITARY_RATIO: f32 = 1.25;
+
+/// How another civilization's army looks beside the player's, judged from the units on
+/// the map. Without spies it's as close as the player can tell.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MilitaryComparison {
+    Weaker,
+    Comparable,
+    Stronger,
+}
+
+impl MilitaryComparison {
+    pub fn between(theirs: f32, yours: f32) -> Self {
+        if theirs > yours * COMPARABLE_MILITARY_RATIO {
+            MilitaryComparison::Stronger
+        } else if theirs * COMPARABLE_MILITARY_RATIO < yours {
+            MilitaryComparison::Weaker
+        } else {
+            MilitaryComparison::Comparable
+        }
+    }
+    
+    pub fn get_name(self) -> &'static str {
+        match self {
+            MilitaryComparison::Weaker => "weaker",
+            MilitaryComparison::Comparable => "comparable",
+            MilitaryComparison::Stronger => "stronger",
+        }
+    }
+}
+
+// Whether the readouts may give other civilizations' exact figures: with --omniscient, or
+// in an observer game, where there's no player to keep them from. Otherwise the player
+// gets their own numbers and what they could see on the map; there's no fog of war, so
+// every city and unit counts as seen.
+pub fn shows_everything(settings: &GameSettings, civ_manager: &CivilizationManager) -> bool {
+    settings.omniscient || civ_manager.is_observer_game()
+}
+
+// A civilization's army, from its units' attack strength
+pub fn military_strength<'a>(civ_id: u32, units: impl Iterator<Item = &'a Unit>) -> f32 {
+    units.filter(|unit| unit.civilization_id == civ_id).map(|unit| unit.get_combat_strength(true) as f32).sum()
+}
+
+/// A civilization's empire-wide figures, summed over its cities and units. The header bar
+/// shows them and the demographics are measured from them.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct EmpireTotals {
+    pub cities: usize,
+    pub population: u32,
+    pub land: usize,
+    pub military: f32,
+    pub science: f32,
+    pub culture: f32,
+    pub content_cities: usize, // Not unhappy
+    pub unrest_cities: usize,
+    pub finances: Finances,
+}
+
+impl EmpireTotals {
+    pub fn measure<'a>(
+        civ: &Civilization,
+        cities: impl Iterator<Item = &'a City> + Clone,
+        units: impl Iterator<Item = &'a Unit> + Clone,
+    ) -> Self {
+        let own_cities = cities.clone().filter(|city| city.civilization_id == civ.id);
+        EmpireTotals {
+            cities: own_cities.clone().count(),
+            population: own_cities.clone().map(|city| city.population).sum(),
+            land: own_cities.clone().map(|city| city.territory_tiles.len()).sum(),
+            military: military_strength(civ.id, units.clone()),
+            science: own_cities.clone().map(|city| city.science_per_turn).sum(),
+            culture: own_cities.clone().map(|city| city.culture_per_turn).sum(),
+            content_cities: own_cities.clone().filter(|city| city.happiness >= 0.0).count(),
+            unrest_cities: own_cities.clone().filter(|city| city.is_in_unrest()).count(),
+            finances: civ_finances(civ, cities, units),
+        }
+    }
+}
+
+/// Every demographic of one civilization, in Demographic::ALL order
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Demographics(pub [f32; Demographic::ALL.len()]);
+
+impl Demographics {
+    pub fn measure<'a>(
+        civ: &Civilization,
+        cities: impl Iterator<Item = &'a City> + Clone,
+        units: impl Iterator<Item = &'a Unit> + Clone,
+    ) -> Self {
+        let totals = EmpireTotals::measure(civ, cities, units);
+        Demographics(Demographic::ALL.map(|demographic| match demographic {
+            Demographic::Population => totals.population as f32,
+            Demographic::Land => totals.land as f32,
+            Demographic::Military => totals.military,
+            Demographic::Science => totals.science,
+            Demographic::Gold => totals.finances.net(),
+            Demographic::Cities => totals.cities as f32,
+        }))
+    }
+    
+    pub fn get(&self, demographic: Demographic) -> f32 {
+        let index = Demographic::ALL.iter().position(|d| *d == demographic).unwrap_or(0);
+        self.0[index]
+    }
+}
+
+/// One civilization at a glance: its demographics and score, measured the same way for
+/// the demographics screen, the relations strip and the history samples
+#[derive(Clone, Debug, PartialEq)]
+pub struct CivSummary {
+    pub civ_id: u32,
+    pub demographics: Demographics,
+    pub score: f32,
+}
+
+impl CivSummary {
+    /// Every civilization's summary, eliminated ones included, by id
+    pub fn measure_all(
+        civ_manager: &CivilizationManager,
+        city_query: &Query<&City>,
+        unit_query: &Query<&Unit>,
+    ) -> Vec<CivSummary> {
+        let scores = compute_scores(city_query, civ_manager);
+        let mut summaries: Vec<CivSummary> = civ_manager
+            .civilizations
+            .values()
+            .map(|civ| CivSummary {
+                civ_id: civ.id,
+                demographics: Demographics::measure(civ, city_query.iter(), unit_query.iter()),
+                score: scores.iter().find(|score| score.civ_id == civ.id).map_or(0.0, |score| score.score),
+            })
+            .collect();
+        summaries.sort_by_key(|summary| summary.civ_id);
+        summaries
+    }
+}
+
+/// One civilization's recorded past, one sample per completed turn
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct CivHistory {
+    pub demographics: Vec<Demographics>,
+    pub score: Vec<f32>,
+    #[serde(default)]
+    pub techs: Vec<usize>, // Technologies known
+}
+
+/// Samples taken at the end of every full turn, for the demographics graph
+#[derive(Resource, Clone, Default, Serialize, Deserialize)]
+pub struct StatsHistory {
+    pub turns: Vec<u32>, // Turn each sample closed
+    pub civs: HashMap<u32, CivHistory>,
+}
+
+impl StatsHistory {
+    pub fn last_turn(&self) -> Option<u32> {
+        self.turns.last().copied()
+    }
+}
+
+// Takes a sample for every civilization once a full turn has gone round, i.e. when the
+// turn number moves on. Eliminated civilizations are still sampled so the series line up.
+pub fn stats_history_system(
+    mut turn_events: EventReader<TurnStartedEvent>,
+    mut history: ResMut<StatsHistory>,
+    civ_manager: Res<CivilizationManager>,
+    city_query: Query<&City>,
+    unit_query: Query<&Unit>,
+) {
+    if turn_events.read().count() == 0 {
+        return;
+    }
+    let completed_turn = civ_manager.turn_number.saturating_sub(1);
+    if completed_turn == 0 || history.last_turn().is_some_and(|turn| turn >= completed_turn) {
+        return;
+    }
+    
+    history.turns.push(completed_turn);
+    for summary in CivSummary::measure_all(&civ_manager, &city_query, &unit_query) {
+        let techs = civ_manager.get_civilization(summary.civ_id).map_or(0, |civ| civ.technologies.len());
+        let civ_history = history.civs.entry(summary.civ_id).or_default();
+        civ_history.demographics.push(summary.demographics);
+        civ_history.score.push(summary.score);
+        civ_history.techs.push(techs);
+    }
+}
@@ -0,0 +1,91 @@
+// Fixtures for the tests: an app holding the resources and events the game systems
+// read, so a test only adds the systems it exercises
+use bevy::ecs::system::{IntoSystem, RunSystemOnce};
+use bevy::prelude::*;
+use super::cities::{CityCaptured, CityDestroyedEvent, CityFoundedEvent, CityGrewEvent, CityStarvingEvent, ProductionCompletedEvent, TerritoryExpandedEvent, TerritoryMap, UnitProduced, WonderRegistry};
+use super::civilization::{CivilizationManager, TechResearchedEvent};
+use super::combat::{CombatState, UnitDamagedEvent};
+use super::diplomacy::WarDeclaredEvent;
+use super::game_initialization::{EndTurnEvent, EndTurnGuard, GameSettings, GameState, TurnStartedEvent};
+use super::hex::HexCoord;
+use super::map::{MapTile, TerrainType, TileIndex};
+use super::notifications::GameNotification;
+use super::rng::GameRng;
+use super::stable_id::StableIds;
+use super::units::UnitCaptured;
+
+pub const TEST_SEED: u64 = 7;
+
+// An app with the game's resources and events and `tiles` spawned and indexed, but no
+// civilizations and no systems
+pub fn game_app(tiles: Vec<MapTile>) -> App {
+    let mut app = App::new();
+    app.insert_resource(GameSettings::default())
+        .insert_resource(CivilizationManager::default())
+        .insert_resource(GameState::default())
+        .insert_resource(GameRng::new(TEST_SEED))
+        .init_resource::<CombatState>()
+        .init_resource::<EndTurnGuard>()
+        .init_resource::<TerritoryMap>()
+        .init_resource::<WonderRegistry>()
+        .init_resource::<StableIds>()
+        .init_resource::<Time>()
+        .add_event::<TurnStartedEvent>()
+        .add_event::<EndTurnEvent>()
+        .add_event::<TechResearchedEvent>()
+        .add_event::<UnitProduced>()
+        .add_event::<UnitDamagedEvent>()
+        .add_event::<UnitCaptured>()
+        .add_event::<CityCaptured>()
+        .add_event::<CityGrewEvent>()
+        .add_event::<CityStarvingEvent>()
+        .add_event::<TerritoryExpandedEvent>()
+        .add_event::<ProductionCompletedEvent>()
+        .add_event::<CityFoundedEvent>()
+        .add_event::<CityDestroyedEvent>()
+        .add_event::<WarDeclaredEvent>()
+        .add_event::<GameNotification>();
+
+    let mut index = TileIndex::default();
+    for tile in tiles {
+        let coord = tile.hex_coord;
+        index.tiles.insert(coord, app.world_mut().spawn(tile).id());
+    }
+    app.insert_resource(index);
+    app
+}
+
+// Runs `system` once against the app's world and hands back what it returns
+pub fn run<T: 'static, M>(app: &mut App, system: impl IntoSystem<(), T, M>) -> T {
+    app.world_mut().run_system_once(system).expect("test system should run")
+}
+
+// A plain tile of `terrain`: no resource, river, coast or fertility to add to its yields
+pub fn tile(hex_coord: HexCoord, terrain: TerrainType) -> MapTile {
+    MapTile {
+        hex_coord,
+        terrain: terrain as u8,
+        biome: 0,
+        elevation: 0,
+        elevation_raw: 0.0,
+        resource: 0,
+        has_river: false,
+        river_flow: 0.0,
+        is_coastal: false,
+        water_distance: 0,
+        temperature: 0.5,
+        precipitation: 0.5,
+        soil_fertility: 0.0,
+        geology: 0,
+        strategic_feature: 0,
+        defensibility: 0.0,
+        trade_value: 0.0,
+        flood_risk: 0.0,
+        naval_access: 0.0,
+    }
+}
+
+// Every tile within `radius` of `center`, all of `terrain`
+pub fn area(center: HexCoord, radius: i32, terrain: TerrainType) -> Vec<MapTile> {
+    (0..=radius).flat_map(|ring| center.ring(ring)).map(|coord| tile(coord, terrain)).collect()
+}
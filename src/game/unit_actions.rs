@@ -0,0 +1,334 @@
+use bevy::prelude::*;
+use super::cities::{City, UnitType};
+use super::city_founding::{MIN_CITY_DISTANCE, can_found_city_at};
+use super::civilization::{Civilization, CivilizationManager};
+use super::game_initialization::TurnStartedEvent;
+use super::input::InputMode;
+use super::input_map::{Action, InputMap};
+use super::map::TileLookup;
+use super::notifications::{GameNotification, NotificationCategory};
+use super::units::{Unit, UnitOrder, UnitSelection, enemy_positions};
+
+// Gold an upgrade costs for each point of production the better unit costs over the old one,
+// and the least any upgrade costs
+const UPGRADE_GOLD_PER_PRODUCTION: f32 = 2.0;
+const MIN_UPGRADE_GOLD: f32 = 10.0;
+// A unit on sentry wakes when an enemy comes this close
+const SENTRY_RANGE: i32 = 2;
+
+/// Something the selected unit can be told to do, from its hotkey or its button
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnitAction {
+    Move,
+    Attack,
+    FoundCity,
+    BuildImprovement,
+    UseAbility, // A Great Person's one-time ability
+    Fortify,
+    Sentry,
+    Automate,
+    Skip,
+    Upgrade,
+    Disband,
+}
+
+/// Asks for the selected unit to carry out `action`. The hotkeys and the action bar both
+/// send these, so each action runs the same code whichever way it was asked for.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct UnitActionEvent {
+    pub action: UnitAction,
+}
+
+// Whether `action` was asked for since the last look; reads every pending request so
+// none is left over for the next frame
+pub fn action_requested(events: &mut EventReader<UnitActionEvent>, action: UnitAction) -> bool {
+    events.read().filter(|event| event.action == action).count() > 0
+}
+
+/// Where a unit stands, worked out once so every action can be checked against it
+pub struct ActionSite<'a> {
+    city_site: bool,         // A new city could be founded here
+    own_city: bool,          // In one of the unit's own cities
+    own_territory: bool,
+    foreign_territory: bool, // In another civilization's borders
+    civ: Option<&'a Civilization>,
+}
+
+impl<'a> ActionSite<'a> {
+    pub fn new<'c>(
+        unit: &Unit,
+        cities: impl Iterator<Item = &'c City> + Clone,
+        tiles: &TileLookup,
+        civ_manager: &'a CivilizationManager,
+    ) -> Self {
+        let holds = |own: bool| cities.clone().any(|city| (city.civilization_id == unit.civilization_id) == own && city.territory_tiles.contains(&unit.hex_coord));
+        Self {
+            city_site: can_found_city_at(unit.hex_coord, cities.clone(), tiles),
+            own_city: cities.clone().any(|city| city.hex_coord == unit.hex_coord && city.civilization_id == unit.civilization_id),
+            own_territory: holds(true),
+            foreign_territory: holds(false),
+            civ: civ_manager.get_civilization(unit.civilization_id),
+        }
+    }
+}
+
+// Gold it takes to turn a `from` into a `to`
+pub fn upgrade_cost(from: UnitType, to: UnitType) -> f32 {
+    let extra = to.get_stats().production_cost.saturating_sub(from.get_stats().production_cost);
+    (extra as f32 * UPGRADE_GOLD_PER_PRODUCTION).max(MIN_UPGRADE_GOLD)
+}
+
+impl UnitAction {
+    pub const ALL: [UnitAction; 11] = [
+        UnitAction::Move, UnitAction::Attack, UnitAction::FoundCity, UnitAction::BuildImprovement, UnitAction::UseAbility,
+        UnitAction::Fortify, UnitAction::Sentry, UnitAction::Automate, UnitAction::Skip, UnitAction::Upgrade, UnitAction::Disband,
+    ];
+    
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            UnitAction::Move => "Move",
+            UnitAction::Attack => "Attack",
+            UnitAction::FoundCity => "Found City",
+            UnitAction::BuildImprovement => "Build Improvement",
+            UnitAction::UseAbility => "Use Great Person",
+            UnitAction::Fortify => "Fortify",
+            UnitAction::Sentry => "Sentry",
+            UnitAction::Automate => "Automate",
+            UnitAction::Skip => "Skip",
+            UnitAction::Upgrade => "Upgrade",
+            UnitAction::Disband => "Disband",
+        }
+    }
+    
+    // One line on what the action does, for its button's tooltip
+    pub fn description(&self) -> &'static str {
+        match self {
+            UnitAction::Move => "Pick a hex for the unit to walk to",
+            UnitAction::Attack => "Pick an enemy in reach to fight",
+            UnitAction::FoundCity => "Settle a new city on this hex",
+            UnitAction::BuildImprovement => "Improve this tile for the city that works it",
+            UnitAction::UseAbility => "Spend the Great Person for a one-time boon",
+            UnitAction::Fortify => "Dig in for a defense bonus until given new orders",
+            UnitAction::Sentry => "Stand watch until an enemy comes near",
+            UnitAction::Automate => "Work the land near your cities each turn until given other orders",
+            UnitAction::Skip => "Leave the unit be for this turn",
+            UnitAction::Upgrade => "Pay gold to refit the unit as a newer kind",
+            UnitAction::Disband => "Dismiss the unit for good",
+        }
+    }
+    
+    // What `unit` doing this is called, naming a Great Person's particular ability
+    pub fn label(&self, unit: &Unit) -> &'static str {
+        match (self, unit.unit_type) {
+            (UnitAction::UseAbility, UnitType::GreatScientist) => "Discover Technology",
+            (UnitAction::UseAbility, UnitType::GreatEngineer) => "Finish Production",
+            (UnitAction::UseAbility, UnitType::GreatMerchant) => "Trade Mission",
+            _ => self.get_name(),
+        }
+    }
+    
+    // The rebindable key action that does the same as the button; Move is a right click
+    pub fn input_action(&self) -> Option<Action> {
+        match self {
+            UnitAction::Move => None,
+            UnitAction::Attack => Some(Action::Attack),
+            UnitAction::FoundCity => Some(Action::FoundCity),
+            UnitAction::BuildImprovement => Some(Action::BuildImprovement),
+            UnitAction::UseAbility => Some(Action::UseAbility),
+            UnitAction::Fortify => Some(Action::Fortify),
+            UnitAction::Sentry => Some(Action::Sentry),
+            UnitAction::Automate => Some(Action::Automate),
+            UnitAction::Skip => Some(Action::Skip),
+            UnitAction::Upgrade => Some(Action::Upgrade),
+            UnitAction::Disband => Some(Action::Disband),
+        }
+    }
+    
+    // The key (or click) that does the same as the button
+    pub fn binding(&self, input_map: &InputMap) -> String {
+        match self.input_action() {
+            Some(action) => input_map.label(action),
+            None => "Right Click".to_string(),
+        }
+    }
+    
+    // Whether `unit` can do this here and now: None if it's not something this kind of
+    // unit ever does, otherwise why it can't right now
+    pub fn check(&self, unit: &Unit, site: &ActionSite) -> Option<Result<(), String>> {
+        let fail = |reason: &str| Some(Err(reason.to_string()));
+        let no_movement = unit.movement_points == 0;
+        match self {
+            UnitAction::Move if no_movement => fail("No movement left this turn"),
+            UnitAction::Move => Some(Ok(())),
+            UnitAction::Attack if !unit.can_attack => None,
+            UnitAction::Attack if unit.has_attacked => fail("Already attacked this turn"),
+            UnitAction::Attack if no_movement => fail("No movement left this turn"),
+            UnitAction::Attack if unit.must_set_up() => fail("Siege engines can't attack on a turn they moved"),
+            UnitAction::Attack => Some(Ok(())),
+            UnitAction::FoundCity if !unit.can_found_cities => None,
+            UnitAction::FoundCity if no_movement => fail("No movement left this turn"),
+            UnitAction::FoundCity if !site.city_site => Some(Err(format!("Cities must be on land and at least {} tiles apart", MIN_CITY_DISTANCE))),
+            UnitAction::FoundCity => Some(Ok(())),
+            UnitAction::BuildImprovement if !unit.can_build_improvements => None,
+            UnitAction::BuildImprovement if no_movement => fail("No movement left this turn"),
+            UnitAction::BuildImprovement => Some(Ok(())),
+            UnitAction::UseAbility => match unit.unit_type {
+                UnitType::GreatScientist => Some(Ok(())),
+                UnitType::GreatEngineer if !site.own_city => fail("Must be in one of your cities"),
+                UnitType::GreatMerchant if !site.foreign_territory => fail("Must be in foreign territory"),
+                UnitType::GreatEngineer | UnitType::GreatMerchant => Some(Ok(())),
+                _ => None,
+            },
+            UnitAction::Fortify if !unit.can_attack => None,
+            UnitAction::Fortify if unit.is_fortified => fail("Already fortified"),
+            UnitAction::Fortify => Some(Ok(())),
+            UnitAction::Sentry if unit.order == UnitOrder::Sentry => fail("Already on sentry"),
+            UnitAction::Sentry => Some(Ok(())),
+            UnitAction::Automate if !unit.can_build_improvements => None,
+            UnitAction::Automate if matches!(unit.order, UnitOrder::Automate(_)) => fail("Already automated"),
+            UnitAction::Automate => Some(Ok(())),
+            UnitAction::Skip if no_movement => fail("No movement left this turn"),
+            UnitAction::Skip => Some(Ok(())),
+            UnitAction::Upgrade => {
+                let to = unit.unit_type.upgrades_to()?;
+                let civ = site.civ?;
+                let cost = upgrade_cost(unit.unit_type, to);
+                Some(if let Some(tech) = to.required_tech().filter(|tech| !civ.has_technology(*tech)) {
+                    Err(format!("Upgrading to {} needs {}", to.get_name(), tech.get_name()))
+                } else if let Some(resource) = to.required_resource().filter(|resource| !civ.has_resource(*resource)) {
+                    Err(format!("Upgrading to {} needs {:?}", to.get_name(), resource))
+                } else if !site.own_territory {
+                    Err("Must be in your own territory".to_string())
+                } else if no_movement {
+                    Err("No movement left this turn".to_string())
+                } else if civ.gold < cost {
+                    Err(format!("Upgrading to {} costs {:.0} gold", to.get_name(), cost))
+                } else {
+                    Ok(())
+                })
+            }
+            UnitAction::Disband => Some(Ok(())),
+        }
+    }
+}
+
+// Turns unit hotkeys into action requests. Chords match their modifiers exactly, so
+// Shift+F fortifies without also founding a city and Alt+A steps rather than attacks.
+pub fn unit_action_hotkey_system(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    input_map: Res<InputMap>,
+    mut action_events: EventWriter<UnitActionEvent>,
+) {
+    for action in UnitAction::ALL {
+        if let Some(key_action) = action.input_action()
+            && input_map.just_pressed(key_action, &keyboard)
+        {
+            action_events.write(UnitActionEvent { action });
+        }
+    }
+}
+
+// Move, Sentry, Automate, Upgrade and Disband; the other actions are carried out where
+// their rules live. Move mode lasts while the selected unit can still move, until Escape.
+#[allow(clippy::too_many_arguments)]
+pub fn unit_action_system(
+    mut commands: Commands,
+    mut action_events: EventReader<UnitActionEvent>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut unit_query: Query<&mut Unit>,
+    city_query: Query<&City>,
+    tiles: TileLookup,
+    mut unit_selection: ResMut<UnitSelection>,
+    mut civ_manager: ResMut<CivilizationManager>,
+    mut input_mode: ResMut<InputMode>,
+    mut notifications: EventWriter<GameNotification>,
+) {
+    let selected = unit_selection.selected_unit.and_then(|entity| unit_query.get(entity).ok().map(|unit| (entity, unit.clone())));
+    if *input_mode == InputMode::MoveTargeting
+        && (keyboard.just_pressed(KeyCode::Escape) || selected.as_ref().is_none_or(|(_, unit)| unit.movement_points == 0))
+    {
+        *input_mode = InputMode::Normal;
+    }
+    
+    let requested: Vec<UnitAction> = action_events
+        .read()
+        .map(|event| event.action)
+        .filter(|action| matches!(action, UnitAction::Move | UnitAction::Sentry | UnitAction::Automate | UnitAction::Upgrade | UnitAction::Disband))
+        .collect();
+    let Some((unit_entity, unit)) = selected else { return };
+    let warning = |text: String| GameNotification::new(NotificationCategory::Warning, text).at(unit.hex_coord);
+    
+    for action in requested {
+        let site = ActionSite::new(&unit, city_query.iter(), &tiles, &civ_manager);
+        match action.check(&unit, &site) {
+            Some(Ok(())) => {}
+            Some(Err(reason)) => {
+                notifications.write(warning(format!("{} can't {}: {}", unit.display_name(), action.label(&unit).to_lowercase(), reason)));
+                continue;
+            }
+            None => continue,
+        }
+        match action {
+            UnitAction::Move => {
+                *input_mode = InputMode::MoveTargeting;
+                println!("Click where {} should go (Esc to cancel).", unit.display_name());
+            }
+            UnitAction::Sentry => {
+                let Ok(mut unit) = unit_query.get_mut(unit_entity) else { continue };
+                unit.clear_order();
+                unit.order = UnitOrder::Sentry;
+            }
+            UnitAction::Automate => {
+                let Ok(mut unit) = unit_query.get_mut(unit_entity) else { continue };
+                unit.clear_order();
+                unit.order = UnitOrder::Automate(None);
+            }
+            UnitAction::Upgrade => {
+                let Some(to) = unit.unit_type.upgrades_to() else { continue };
+                let cost = upgrade_cost(unit.unit_type, to);
+                if let Some(civ) = civ_manager.get_civilization_mut(unit.civilization_id) {
+                    civ.gold -= cost;
+                }
+                let Ok(mut upgraded) = unit_query.get_mut(unit_entity) else { continue };
+                upgraded.upgrade_to(to);
+                let text = format!("{} upgraded to a {} for {:.0} gold", unit.display_name(), to.get_name(), cost);
+                notifications.write(GameNotification::new(NotificationCategory::Military, text).at(unit.hex_coord).for_civ(unit.civilization_id));
+                return;
+            }
+            UnitAction::Disband => {
+                if let Some(civ) = civ_manager.get_civilization_mut(unit.civilization_id) {
+                    civ.remove_unit(unit_entity);
+                }
+                commands.entity(unit_entity).despawn();
+                unit_selection.selected_unit = None;
+                println!("{} disbanded", unit.display_name());
+                return;
+            }
+            _ => {}
+        }
+    }
+}
+
+// Wakes a civilization's sentries as its turn begins if an enemy has come within SENTRY_RANGE
+pub fn sentry_wake_system(
+    mut turn_events: EventReader<TurnStartedEvent>,
+    mut unit_query: Query<&mut Unit>,
+    city_query: Query<&City>,
+    civ_manager: Res<CivilizationManager>,
+    mut notifications: EventWriter<GameNotification>,
+) {
+    for event in turn_events.read() {
+        let civ_id = event.civilization_id;
+        let enemies = enemy_positions(civ_id, unit_query.iter(), city_query.iter(), &civ_manager);
+        for mut unit in unit_query.iter_mut() {
+            if unit.civilization_id != civ_id || unit.order != UnitOrder::Sentry {
+                continue;
+            }
+            if enemies.iter().any(|enemy| enemy.distance(unit.hex_coord) <= SENTRY_RANGE) {
+                unit.clear_order();
+                let text = format!("{} on sentry spotted an enemy", unit.display_name());
+                notifications.write(GameNotification::new(NotificationCategory::Military, text).at(unit.hex_coord).for_civ(civ_id));
+            }
+        }
+    }
+}
@@ -1,10 +1,26 @@
 use bevy::prelude::*;
 use super::hex::HexCoord;
-use super::map::{MapTile, TerrainType};
-use super::civilization::{CivilizationManager, CivTrait};
-use super::cities::{UnitType, City};
+use super::culling::Cullable;
+use super::fonts::GameFonts;
+use super::map::{HEX_SIZE, TerrainType, TileLookup, create_hexagon_mesh};
+use super::civilization::{BARBARIAN_CIV_ID, BARBARIAN_COLOR, CivilizationManager, CivTrait, SEAFARING_NAVAL_MOVEMENT, TechResearchedEvent, Technology};
+use super::cities::{UnitType, City, CityFoundedEvent, CitySelection};
+use super::combat::{are_enemies, get_attack_range};
+use super::input::ClickIntent;
+use super::input_map::{Action, InputMap};
+use super::combat_log::CombatLog;
+use super::diplomacy::FirstContactEvent;
+use super::resources::ResourceType;
+use super::rules::rules;
+use serde::{Deserialize, Serialize};
+use super::game_initialization::TurnStartedEvent;
+use super::notifications::{GameNotification, NotificationCategory};
+use super::stable_id::issue_stable_id;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
 
-#[derive(Component)]
+#[derive(Component, Clone, Serialize, Deserialize)]
+#[component(on_add = issue_stable_id)]
 pub struct Unit {
     pub unit_type: UnitType,
     pub civilization_id: u32,
@@ -17,11 +33,15 @@ pub struct Unit {
     pub attack_strength: u32,
     pub defense_strength: u32,
     pub combat_experience: u32,
+    pub battles_survived: u32,
     
     // Movement
     pub movement_points: u32,
     pub max_movement_points: u32,
     pub movement_type: MovementType,
+    pub ignores_terrain_cost: bool,
+    #[serde(default)]
+    pub ocean_going: bool, // Ships may leave the coast and land units embark; kept by ocean_access_system
     
     // Special abilities
     pub can_found_cities: bool,
@@ -36,12 +56,16 @@ pub struct Unit {
     pub fortification_turns: u32,
     pub is_selected: bool,
     
+    // Orders
+    pub order: UnitOrder,
+    pub path: Vec<HexCoord>, // Remaining steps of a GoTo order, next step first
+    
     // Production info (for display)
     pub turns_to_build: u32,
     pub production_cost: u32,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub enum MovementType {
     Land,           // Normal land movement
     Naval,          // Water-only movement
@@ -49,10 +73,52 @@ pub enum MovementType {
     Air,            // Can fly over terrain (future use)
 }
 
+/// Standing order a unit keeps carrying out across turns
+#[derive(Clone, Copy, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub enum UnitOrder {
+    #[default]
+    Idle,
+    GoTo(HexCoord), // Walk towards the target, spending movement each turn
+    Sentry, // Stand watch until an enemy comes near
+    Automate(Option<HexCoord>), // A worker working its civilization's land on its own, and the tile it's headed for
+}
+
+// Battles a unit must survive to earn the veteran honorific
+pub const VETERAN_BATTLES: u32 = 5;
+
+// The health bar over a unit's marker, and where it and the status icons sit on it
+const CONDITION_BAR_WIDTH: f32 = 22.0;
+const CONDITION_BAR_HEIGHT: f32 = 3.0;
+const CONDITION_OFFSET: f32 = 14.0;
+const CONDITION_BAR_BACKGROUND: Color = Color::srgba(0.0, 0.0, 0.0, 0.7);
+const CONDITION_ICON_COLOR: Color = Color::srgb(1.0, 1.0, 0.85);
+// Bars and icons are hidden once the camera is zoomed out further than this
+const CONDITION_MAX_CAMERA_SCALE: f32 = 2.0;
+
+// Upper bound on A* node expansions so an unreachable target can't stall a frame
+const MAX_PATH_EXPANSIONS: usize = 20_000;
+
+/// A civilian taken when the last defender of its hex fell; handed over by `unit_capture_system`
+#[derive(Event)]
+pub struct UnitCaptured {
+    pub unit_entity: Entity,
+    pub new_civilization_id: u32,
+}
+
+/// The symbol drawn on the map for `unit`
 #[derive(Component)]
 pub struct UnitMarker {
-    pub civilization_id: u32,
-    pub unit_type: UnitType,
+    pub unit: Entity,
+}
+
+/// The health bar and status icons over a unit's marker. They're the marker's children,
+/// so they move and cull with it.
+#[derive(Component)]
+pub struct UnitCondition {
+    pub unit: Entity,
+    bar: Entity,
+    fill: Entity,
+    icons: Entity,
 }
 
 #[derive(Component)]
@@ -61,11 +127,115 @@ pub struct Selected;
 #[derive(Component)]
 pub struct MovementIndicator;
 
+// Parent of the pooled indicators, so they go with the game they were spawned for
+#[derive(Component)]
+pub struct MovementIndicatorRoot;
+
+// Indicators spawned up front; enough for a two-move unit on open ground. Units that
+// reach further add to the pool.
+const INDICATOR_POOL_SIZE: usize = 24;
+// Drawn a little inside the hex so the grid line still shows around it
+const INDICATOR_SCALE: f32 = 0.85;
+
+/// Translucent hexes marking where the selected unit can move and what it can attack.
+/// They're spawned hidden once a game and moved around rather than respawned.
+#[derive(Resource)]
+pub struct MovementIndicators {
+    root: Option<Entity>,
+    pool: Vec<Entity>,
+    shown: usize,
+    hex_mesh: Handle<Mesh>,
+    move_material: Handle<ColorMaterial>,
+    attack_material: Handle<ColorMaterial>,
+}
+
+impl FromWorld for MovementIndicators {
+    fn from_world(world: &mut World) -> Self {
+        let hex_mesh = world.resource_mut::<Assets<Mesh>>().add(create_hexagon_mesh(HEX_SIZE * INDICATOR_SCALE));
+        let mut materials = world.resource_mut::<Assets<ColorMaterial>>();
+        
+        Self {
+            root: None,
+            pool: Vec::new(),
+            shown: 0,
+            hex_mesh,
+            move_material: materials.add(Color::srgba(0.0, 1.0, 0.0, 0.25)),
+            attack_material: materials.add(Color::srgba(1.0, 0.2, 0.2, 0.4)),
+        }
+    }
+}
+
+impl MovementIndicators {
+    fn spawn_indicator(&self, commands: &mut Commands, root: Entity, world_pos: Vec2, material: Handle<ColorMaterial>, visibility: Visibility) -> Entity {
+        commands.spawn((
+            MovementIndicator,
+            Mesh2d(self.hex_mesh.clone()),
+            MeshMaterial2d(material),
+            Transform::from_translation(world_pos.extend(1.5)),
+            visibility,
+            ChildOf(root),
+        )).id()
+    }
+    
+    // Forgets the pool once the game it was spawned for is gone (the indicators go with it)
+    pub fn clear(&mut self) {
+        self.root = None;
+        self.pool.clear();
+        self.shown = 0;
+    }
+}
+
+pub fn setup_movement_indicators(mut commands: Commands, mut indicators: ResMut<MovementIndicators>) {
+    let root = commands.spawn((MovementIndicatorRoot, Transform::default(), Visibility::default())).id();
+    let pool = (0..INDICATOR_POOL_SIZE)
+        .map(|_| indicators.spawn_indicator(&mut commands, root, Vec2::ZERO, indicators.move_material.clone(), Visibility::Hidden))
+        .collect();
+    indicators.root = Some(root);
+    indicators.pool = pool;
+}
+
+pub fn reset_movement_indicators(mut indicators: ResMut<MovementIndicators>) {
+    indicators.clear();
+}
+
 #[derive(Resource)]
 pub struct UnitSelection {
     pub selected_unit: Option<Entity>,
     pub valid_moves: Vec<HexCoord>,
-    pub movement_indicators: Vec<Entity>,
+    pub attack_targets: Vec<HexCoord>,
+    // Unit, position and movement the indicators were last built for, while it's the
+    // unit's civilization's turn
+    shown_for: Option<(Entity, HexCoord, u32)>,
+}
+
+// Where a unit stood and what it had left before one of the player's moves
+#[derive(Clone)]
+struct MoveRecord {
+    unit: Entity,
+    from: HexCoord,
+    to: HexCoord,
+    movement_points: u32,
+    has_moved: bool,
+    is_fortified: bool,
+    fortification_turns: u32,
+    order: UnitOrder,
+    path: Vec<HexCoord>,
+    health: u32, // A unit hurt since can't take its move back
+}
+
+/// The player's moves this turn, most recent last, for Ctrl+Z. Anything that can't be
+/// taken back clears it: the end of the turn, any fight, a city being founded or a first
+/// contact. There's no fog of war, so moving reveals nothing that would need hiding again.
+#[derive(Resource, Default)]
+pub struct MoveHistory {
+    moves: Vec<MoveRecord>,
+    log_seen: usize, // CombatLog::recorded() as of the last check
+}
+
+impl MoveHistory {
+    pub fn clear(&mut self) {
+        self.moves.clear();
+    }
 }
 
 impl Default for UnitSelection {
@@ -73,11 +243,20 @@ impl Default for UnitSelection {
         Self {
             selected_unit: None,
             valid_moves: Vec::new(),
-            movement_indicators: Vec::new(),
+            attack_targets: Vec::new(),
+            shown_for: None,
         }
     }
 }
 
+impl UnitSelection {
+    // Rebuild the indicators next frame even if the selected unit hasn't changed,
+    // e.g. after a change of diplomacy makes other hexes targets or passable
+    pub fn refresh_indicators(&mut self) {
+        self.shown_for = None;
+    }
+}
+
 impl Unit {
     pub fn new(unit_type: UnitType, civilization_id: u32, hex_coord: HexCoord) -> Self {
         let stats = unit_type.get_stats();
@@ -86,15 +265,18 @@ impl Unit {
             unit_type,
             civilization_id,
             hex_coord,
-            name: format!("{:?}", unit_type), // Can be customized later
+            name: unit_type.get_name().to_string(), // Renamed with R
             health: stats.max_health,
             max_health: stats.max_health,
             attack_strength: stats.attack,
             defense_strength: stats.defense,
             combat_experience: 0,
+            battles_survived: 0,
             movement_points: stats.movement,
             max_movement_points: stats.movement,
             movement_type: stats.movement_type,
+            ignores_terrain_cost: stats.ignores_terrain_cost,
+            ocean_going: false,
             can_found_cities: stats.can_found_cities,
             can_build_improvements: stats.can_build_improvements,
             can_attack: stats.can_attack,
@@ -104,21 +286,24 @@ impl Unit {
             is_fortified: false,
             fortification_turns: 0,
             is_selected: false,
+            order: UnitOrder::Idle,
+            path: Vec::new(),
             turns_to_build: stats.build_time,
             production_cost: stats.production_cost,
         }
     }
     
-    pub fn can_move_to(&self, target: HexCoord, tile_query: &Query<&MapTile>) -> bool {
+    pub fn can_move_to(&self, target: HexCoord, tiles: &TileLookup) -> bool {
         // Check if unit can enter this tile type
-        if let Some(tile) = tile_query.iter().find(|t| t.hex_coord == target) {
+        if let Some(tile) = tiles.get(target) {
             let terrain = TerrainType::from_u8(tile.terrain);
             
+            let water = matches!(terrain, TerrainType::Ocean | TerrainType::Lake | TerrainType::River);
             match self.movement_type {
-                MovementType::Land => !matches!(terrain, 
-                    TerrainType::Ocean | TerrainType::Lake | TerrainType::River),
-                MovementType::Naval => matches!(terrain, 
-                    TerrainType::Ocean | TerrainType::Lake | TerrainType::River),
+                // Embarking onto water takes ocean access
+                MovementType::Land => if water { self.ocean_going } else { !tiles.is_impassable_peak(tile) },
+                // Ships hug the coast until their civilization opens the oceans
+                MovementType::Naval => water && (self.ocean_going || !tiles.is_open_ocean(target)),
                 MovementType::Amphibious => true, // Can go anywhere
                 MovementType::Air => true,        // Can fly over anything
             }
@@ -127,10 +312,20 @@ impl Unit {
         }
     }
     
-    pub fn get_movement_cost(&self, target: HexCoord, tile_query: &Query<&MapTile>) -> u32 {
-        if let Some(tile) = tile_query.iter().find(|t| t.hex_coord == target) {
+    pub fn get_movement_cost(&self, target: HexCoord, tiles: &TileLookup) -> u32 {
+        if let Some(tile) = tiles.get(target) {
             let terrain = TerrainType::from_u8(tile.terrain);
             
+            // Scouts move through rough terrain and across rivers at no extra cost
+            if self.ignores_terrain_cost {
+                return 1;
+            }
+            
+            // Founding a city clears its center: no forest, jungle or river crossing left
+            if tiles.is_city_site(target) {
+                return 1;
+            }
+            
             // Base movement cost by terrain
             let base_cost = match terrain {
                 TerrainType::TemperateGrassland | TerrainType::TropicalGrasslandSavanna => 1,
@@ -160,44 +355,108 @@ impl Unit {
         }
     }
     
-    pub fn calculate_valid_moves(&self, tile_query: &Query<&MapTile>) -> Vec<HexCoord> {
-        let mut valid_moves = Vec::new();
-        let mut visited = std::collections::HashSet::new();
-        let mut queue = std::collections::VecDeque::new();
+    // Hexes reachable this turn; `blocked` holds hexes the unit may not enter (enemy units)
+    pub fn calculate_valid_moves(&self, tiles: &TileLookup, blocked: &HashSet<HexCoord>) -> Vec<HexCoord> {
+        // Dijkstra on remaining movement so cheap detours aren't hidden behind expensive first steps
+        let mut best_remaining: HashMap<HexCoord, u32> = HashMap::new();
+        let mut queue = BinaryHeap::new();
         
-        // Start from current position
-        queue.push_back((self.hex_coord, self.movement_points));
-        visited.insert(self.hex_coord);
+        best_remaining.insert(self.hex_coord, self.movement_points);
+        queue.push((self.movement_points, self.hex_coord.q, self.hex_coord.r));
         
-        while let Some((current_coord, remaining_movement)) = queue.pop_front() {
+        while let Some((remaining_movement, q, r)) = queue.pop() {
+            let current_coord = HexCoord::new(q, r);
+            if best_remaining.get(&current_coord).is_some_and(|&best| best > remaining_movement) {
+                continue;
+            }
+            
             for neighbor in current_coord.neighbors() {
-                if visited.contains(&neighbor) {
+                if blocked.contains(&neighbor) || !self.can_move_to(neighbor, tiles) {
                     continue;
                 }
                 
-                if self.can_move_to(neighbor, tile_query) {
-                    let movement_cost = self.get_movement_cost(neighbor, tile_query);
-                    
-                    if movement_cost <= remaining_movement {
-                        valid_moves.push(neighbor);
-                        visited.insert(neighbor);
-                        
-                        // Continue exploring from this position
-                        let new_remaining = remaining_movement - movement_cost;
-                        if new_remaining > 0 {
-                            queue.push_back((neighbor, new_remaining));
-                        }
+                let movement_cost = self.get_movement_cost(neighbor, tiles);
+                if movement_cost > remaining_movement {
+                    continue;
+                }
+                
+                let new_remaining = remaining_movement - movement_cost;
+                if best_remaining.get(&neighbor).is_none_or(|&best| new_remaining > best) {
+                    best_remaining.insert(neighbor, new_remaining);
+                    queue.push((new_remaining, neighbor.q, neighbor.r));
+                }
+            }
+        }
+        
+        best_remaining.remove(&self.hex_coord);
+        let mut moves: Vec<HexCoord> = best_remaining.into_keys().collect();
+        moves.sort_unstable_by_key(|coord| (coord.q, coord.r)); // The same order every game
+        moves
+    }
+    
+    // A* path from the unit's position to `target`, excluding the start hex.
+    // Only uses steps the unit could ever afford in a single turn.
+    pub fn find_path(&self, target: HexCoord, tiles: &TileLookup, blocked: &HashSet<HexCoord>) -> Option<Vec<HexCoord>> {
+        if target == self.hex_coord || blocked.contains(&target) || !self.can_move_to(target, tiles) {
+            return None;
+        }
+        
+        let mut came_from: HashMap<HexCoord, HexCoord> = HashMap::new();
+        let mut cost_so_far: HashMap<HexCoord, u32> = HashMap::new();
+        let mut open = BinaryHeap::new();
+        
+        cost_so_far.insert(self.hex_coord, 0);
+        open.push(Reverse((self.hex_coord.distance(target) as u32, self.hex_coord.q, self.hex_coord.r)));
+        
+        let mut expansions = 0;
+        while let Some(Reverse((_, q, r))) = open.pop() {
+            let current = HexCoord::new(q, r);
+            if current == target {
+                let mut path = vec![current];
+                let mut step = current;
+                while let Some(&previous) = came_from.get(&step) {
+                    if previous == self.hex_coord {
+                        break;
                     }
+                    path.push(previous);
+                    step = previous;
+                }
+                path.reverse();
+                return Some(path);
+            }
+            
+            expansions += 1;
+            if expansions > MAX_PATH_EXPANSIONS {
+                return None;
+            }
+            
+            let current_cost = cost_so_far[&current];
+            for neighbor in current.neighbors() {
+                if blocked.contains(&neighbor) || !self.can_move_to(neighbor, tiles) {
+                    continue;
+                }
+                
+                let step_cost = self.get_movement_cost(neighbor, tiles);
+                if step_cost > self.max_movement_points {
+                    continue;
+                }
+                
+                let new_cost = current_cost + step_cost;
+                if cost_so_far.get(&neighbor).is_none_or(|&cost| new_cost < cost) {
+                    cost_so_far.insert(neighbor, new_cost);
+                    came_from.insert(neighbor, current);
+                    let priority = new_cost + neighbor.distance(target) as u32;
+                    open.push(Reverse((priority, neighbor.q, neighbor.r)));
                 }
             }
         }
         
-        valid_moves
+        None
     }
     
-    pub fn move_to(&mut self, target: HexCoord, tile_query: &Query<&MapTile>) -> bool {
-        if self.can_move_to(target, tile_query) {
-            let movement_cost = self.get_movement_cost(target, tile_query);
+    pub fn move_to(&mut self, target: HexCoord, tiles: &TileLookup) -> bool {
+        if self.can_move_to(target, tiles) {
+            let movement_cost = self.get_movement_cost(target, tiles);
             
             if movement_cost <= self.movement_points {
                 self.hex_coord = target;
@@ -214,12 +473,185 @@ impl Unit {
         false
     }
     
+    // Give the unit a GoTo order; returns false if no path exists
+    pub fn set_goto(&mut self, target: HexCoord, tiles: &TileLookup, blocked: &HashSet<HexCoord>) -> bool {
+        match self.find_path(target, tiles, blocked) {
+            Some(path) => {
+                self.order = UnitOrder::GoTo(target);
+                self.path = path;
+                true
+            }
+            None => false,
+        }
+    }
+    
+    pub fn clear_order(&mut self) {
+        self.order = UnitOrder::Idle;
+        self.path.clear();
+    }
+    
+    // Still has moves left and nothing to do with them: not fortified and not on its way anywhere
+    pub fn needs_orders(&self) -> bool {
+        self.movement_points > 0 && !self.is_fortified && self.order == UnitOrder::Idle
+    }
+    
+    // Siege engines that moved this turn have to set up before they can attack
+    pub fn must_set_up(&self) -> bool {
+        self.unit_type.is_siege() && self.has_moved
+    }
+    
+    // Step into a hex just won in battle, outside the normal movement rules; costs one move
+    pub fn advance_into(&mut self, target: HexCoord) {
+        self.hex_coord = target;
+        self.movement_points = self.movement_points.saturating_sub(1);
+        self.has_moved = true;
+        self.is_fortified = false;
+        self.fortification_turns = 0;
+        self.clear_order();
+    }
+    
+    // Slip out of a lost fight into `target` with a single hit point left
+    pub fn withdraw_to(&mut self, target: HexCoord) {
+        self.health = 1;
+        self.hex_coord = target;
+        self.is_fortified = false;
+        self.fortification_turns = 0;
+        self.clear_order();
+    }
+    
+    // Walk along the GoTo path as far as this turn's movement allows,
+    // re-pathing once if the way ahead has become blocked
+    pub fn advance_goto(&mut self, tiles: &TileLookup, blocked: &HashSet<HexCoord>) {
+        let UnitOrder::GoTo(target) = self.order else { return };
+        
+        match self.follow_path(target, tiles, blocked) {
+            Some(true) => self.clear_order(),
+            Some(false) => {} // Out of movement, continue next turn
+            None => {
+                println!("{} can't reach ({}, {}); order cancelled", self.name, target.q, target.r);
+                self.clear_order();
+            }
+        }
+    }
+    
+    // Walks the unit's path toward `target`, finding a new one once if the way is blocked.
+    // Some(true) once there, Some(false) if movement ran out on the way, None if there's
+    // no way there.
+    fn follow_path(&mut self, target: HexCoord, tiles: &TileLookup, blocked: &HashSet<HexCoord>) -> Option<bool> {
+        let mut repathed = false;
+        while let Some(&next) = self.path.first() {
+            if blocked.contains(&next) || !self.can_move_to(next, tiles) {
+                if repathed {
+                    return None;
+                }
+                self.path = self.find_path(target, tiles, blocked)?;
+                repathed = true;
+                continue;
+            }
+            
+            if !self.move_to(next, tiles) {
+                return Some(false);
+            }
+            self.path.remove(0);
+        }
+        Some(true)
+    }
+    
+    // A turn of automated work: walk to `task` and spend what's left of the turn working
+    // it, or wait where it is if there's nothing to do or no way there. Tile improvements
+    // aren't modelled yet, so as with the B order the work is only the turn spent.
+    pub fn work_automated(&mut self, task: Option<HexCoord>, tiles: &TileLookup, blocked: &HashSet<HexCoord>) {
+        if self.order != UnitOrder::Automate(task) {
+            self.path = match task {
+                Some(target) if target != self.hex_coord => self.find_path(target, tiles, blocked).unwrap_or_default(),
+                _ => Vec::new(),
+            };
+            self.order = UnitOrder::Automate(task);
+        }
+        
+        let arrived = match task {
+            Some(target) => self.follow_path(target, tiles, blocked),
+            None => Some(true),
+        };
+        match arrived {
+            Some(false) => return, // Out of movement, on its way again next turn
+            Some(true) if task.is_none_or(|target| target == self.hex_coord) => {}
+            // No way there; it looks again next turn
+            _ => {
+                self.path.clear();
+                self.order = UnitOrder::Automate(None);
+            }
+        }
+        self.movement_points = 0;
+        self.has_moved = true;
+    }
+    
+    pub fn has_custom_name(&self) -> bool {
+        self.name != self.unit_type.get_name()
+    }
+    
+    // Name including any honorific earned in battle
+    pub fn display_name(&self) -> String {
+        if self.battles_survived >= VETERAN_BATTLES {
+            format!("{} the Veteran", self.name)
+        } else {
+            self.name.clone()
+        }
+    }
+    
+    // Type plus name for panels, e.g. Warrior "Legio X Fretensis"
+    pub fn title(&self) -> String {
+        if self.has_custom_name() {
+            format!("{} \"{}\"", self.unit_type.get_name(), self.display_name())
+        } else {
+            self.display_name()
+        }
+    }
+    
+    // How the unit is referred to in messages: its own name, or e.g. "Egyptian Archer"
+    pub fn label(&self, civ_manager: &CivilizationManager) -> String {
+        if self.has_custom_name() {
+            return self.display_name();
+        }
+        
+        let adjective = match civ_manager.get_civilization(self.civilization_id) {
+            Some(civ) => civ.adjective(),
+            None if self.civilization_id == BARBARIAN_CIV_ID => "Barbarian",
+            None => "Unknown",
+        };
+        let label = format!("{} {}", adjective, self.unit_type.get_name());
+        if self.battles_survived >= VETERAN_BATTLES {
+            format!("{} the Veteran", label)
+        } else {
+            label
+        }
+    }
+    
     pub fn fortify(&mut self) {
         self.is_fortified = true;
         self.fortification_turns = 0;
         self.movement_points = 0; // Spend all movement to fortify
     }
     
+    // Swap in another type's stats, keeping the unit's name, experience, wounds and any
+    // movement bonus it was raised with; upgrading takes the rest of its turn
+    pub fn upgrade_to(&mut self, unit_type: UnitType) {
+        let bonus_movement = self.max_movement_points.saturating_sub(self.unit_type.get_stats().movement);
+        let health_fraction = self.health as f32 / self.max_health as f32;
+        let mut upgraded = Unit::new(unit_type, self.civilization_id, self.hex_coord);
+        if self.has_custom_name() {
+            upgraded.name = self.name.clone();
+        }
+        upgraded.max_movement_points += bonus_movement;
+        upgraded.movement_points = 0;
+        upgraded.has_moved = true;
+        upgraded.health = ((upgraded.max_health as f32 * health_fraction).round() as u32).max(1);
+        upgraded.combat_experience = self.combat_experience;
+        upgraded.battles_survived = self.battles_survived;
+        upgraded.is_selected = self.is_selected;
+        *self = upgraded;
+    }
+    
     pub fn get_combat_strength(&self, is_attacking: bool) -> u32 {
         let base_strength = if is_attacking {
             self.attack_strength
@@ -252,13 +684,16 @@ impl Unit {
         self.health = self.health.saturating_sub(damage);
     }
     
-    pub fn gain_experience(&mut self, amount: u32) {
+    // Returns true if the experience earned a promotion
+    pub fn gain_experience(&mut self, amount: u32) -> bool {
         self.combat_experience += amount;
         
         // Check for promotion (every 10 experience points)
         if self.combat_experience >= 10 && (self.combat_experience - amount) < 10 {
             self.promote();
+            return true;
         }
+        false
     }
     
     fn promote(&mut self) {
@@ -289,23 +724,86 @@ impl Unit {
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct UnitStats {
     pub max_health: u32,
     pub attack: u32,
     pub defense: u32,
     pub movement: u32,
     pub movement_type: MovementType,
+    pub ignores_terrain_cost: bool,
     pub can_found_cities: bool,
     pub can_build_improvements: bool,
     pub can_attack: bool,
     pub naval_unit: bool,
     pub build_time: u32,
     pub production_cost: u32,
+    pub maintenance: f32, // Gold per turn to keep the unit in the field, before a civilization's free support
+    // Chance of slipping away with 1 HP from a melee attack that would destroy the unit,
+    // when it is faster than the attacker
+    #[serde(default)]
+    pub withdraw_chance: f32,
 }
 
 impl UnitType {
+    // In the order cities list them
+    pub const ALL: [UnitType; 13] = [
+        UnitType::Warrior, UnitType::Archer, UnitType::Spearman, UnitType::Horseman, UnitType::Catapult, UnitType::Scout,
+        UnitType::Settler, UnitType::Worker, UnitType::Galley, UnitType::Trireme,
+        UnitType::GreatScientist, UnitType::GreatEngineer, UnitType::GreatMerchant,
+    ];
+    
+    pub fn is_great_person(&self) -> bool {
+        matches!(self, UnitType::GreatScientist | UnitType::GreatEngineer | UnitType::GreatMerchant)
+    }
+    
+    // Siege engines batter cities but fare badly against units in the field, and have to
+    // set up before they can shoot
+    pub fn is_siege(&self) -> bool {
+        matches!(self, UnitType::Catapult)
+    }
+    
+    pub fn required_tech(&self) -> Option<Technology> {
+        match self {
+            UnitType::Archer => Some(Technology::Archery),
+            UnitType::Spearman => Some(Technology::Mining),
+            UnitType::Horseman => Some(Technology::HorsebackRiding),
+            UnitType::Trireme => Some(Technology::Construction), // Hulls built to take a ram
+            UnitType::Catapult => Some(Technology::Mathematics),
+            UnitType::Warrior | UnitType::Settler | UnitType::Worker | UnitType::Scout | UnitType::Galley => None,
+            UnitType::GreatScientist | UnitType::GreatEngineer | UnitType::GreatMerchant => None,
+        }
+    }
+    
+    // Strategic resource the owner must control to build the unit
+    pub fn required_resource(&self) -> Option<ResourceType> {
+        match self {
+            UnitType::Spearman => Some(ResourceType::Iron),
+            UnitType::Horseman => Some(ResourceType::Horses),
+            UnitType::Trireme => Some(ResourceType::Wood), // Timber for the bigger hull
+            _ => None,
+        }
+    }
+    
+    // The unit this one can be upgraded into once its owner can build it
+    pub fn upgrades_to(&self) -> Option<UnitType> {
+        match self {
+            UnitType::Warrior => Some(UnitType::Spearman),
+            UnitType::Galley => Some(UnitType::Trireme),
+            _ => None,
+        }
+    }
+    
+    pub fn get_maintenance(&self) -> f32 {
+        rules().unit(*self).maintenance
+    }
+    
     pub fn get_stats(&self) -> UnitStats {
+        rules().unit(*self)
+    }
+    
+    // Built-in stats, used when no rules file overrides them
+    pub fn default_stats(&self) -> UnitStats {
         match self {
             UnitType::Warrior => UnitStats {
                 max_health: 100,
@@ -313,12 +811,15 @@ impl UnitType {
                 defense: 1,
                 movement: 1,
                 movement_type: MovementType::Land,
+                ignores_terrain_cost: false,
                 can_found_cities: false,
                 can_build_improvements: false,
                 can_attack: true,
                 naval_unit: false,
                 build_time: 3,
                 production_cost: 15,
+                maintenance: 1.0,
+                withdraw_chance: 0.5,
             },
             UnitType::Archer => UnitStats {
                 max_health: 60,
@@ -326,12 +827,15 @@ impl UnitType {
                 defense: 2,
                 movement: 1,
                 movement_type: MovementType::Land,
+                ignores_terrain_cost: false,
                 can_found_cities: false,
                 can_build_improvements: false,
                 can_attack: true,
                 naval_unit: false,
                 build_time: 4,
                 production_cost: 25,
+                maintenance: 1.0,
+                withdraw_chance: 0.5,
             },
             UnitType::Spearman => UnitStats {
                 max_health: 100,
@@ -339,12 +843,31 @@ impl UnitType {
                 defense: 3,
                 movement: 1,
                 movement_type: MovementType::Land,
+                ignores_terrain_cost: false,
                 can_found_cities: false,
                 can_build_improvements: false,
                 can_attack: true,
                 naval_unit: false,
                 build_time: 5,
                 production_cost: 35,
+                maintenance: 1.0,
+                withdraw_chance: 0.5,
+            },
+            UnitType::Horseman => UnitStats {
+                max_health: 100,
+                attack: 3,
+                defense: 1,
+                movement: 2,
+                movement_type: MovementType::Land,
+                ignores_terrain_cost: false,
+                can_found_cities: false,
+                can_build_improvements: false,
+                can_attack: true,
+                naval_unit: false,
+                build_time: 5,
+                production_cost: 30,
+                maintenance: 1.0,
+                withdraw_chance: 0.5,
             },
             UnitType::Settler => UnitStats {
                 max_health: 100,
@@ -352,12 +875,15 @@ impl UnitType {
                 defense: 1,
                 movement: 2,
                 movement_type: MovementType::Land,
+                ignores_terrain_cost: false,
                 can_found_cities: true,
                 can_build_improvements: false,
                 can_attack: false,
                 naval_unit: false,
                 build_time: 10,
                 production_cost: 100,
+                maintenance: 1.0,
+                withdraw_chance: 0.0,
             },
             UnitType::Worker => UnitStats {
                 max_health: 100,
@@ -365,12 +891,15 @@ impl UnitType {
                 defense: 1,
                 movement: 2,
                 movement_type: MovementType::Land,
+                ignores_terrain_cost: false,
                 can_found_cities: false,
                 can_build_improvements: true,
                 can_attack: false,
                 naval_unit: false,
                 build_time: 6,
                 production_cost: 60,
+                maintenance: 0.5,
+                withdraw_chance: 0.0,
             },
             UnitType::Scout => UnitStats {
                 max_health: 100,
@@ -378,12 +907,15 @@ impl UnitType {
                 defense: 1,
                 movement: 2,
                 movement_type: MovementType::Land,
+                ignores_terrain_cost: true,
                 can_found_cities: false,
                 can_build_improvements: false,
                 can_attack: true,
                 naval_unit: false,
                 build_time: 3,
                 production_cost: 15,
+                maintenance: 0.5,
+                withdraw_chance: 0.5,
             },
             UnitType::Galley => UnitStats {
                 max_health: 100,
@@ -391,12 +923,15 @@ impl UnitType {
                 defense: 1,
                 movement: 3,
                 movement_type: MovementType::Naval,
+                ignores_terrain_cost: false,
                 can_found_cities: false,
                 can_build_improvements: false,
                 can_attack: true,
                 naval_unit: true,
                 build_time: 5,
                 production_cost: 40,
+                maintenance: 1.5,
+                withdraw_chance: 0.5,
             },
             UnitType::Trireme => UnitStats {
                 max_health: 100,
@@ -404,12 +939,48 @@ impl UnitType {
                 defense: 1,
                 movement: 4,
                 movement_type: MovementType::Naval,
+                ignores_terrain_cost: false,
                 can_found_cities: false,
                 can_build_improvements: false,
                 can_attack: true,
                 naval_unit: true,
                 build_time: 7,
                 production_cost: 60,
+                maintenance: 1.5,
+                withdraw_chance: 0.5,
+            },
+            UnitType::Catapult => UnitStats {
+                max_health: 60,
+                attack: 3,
+                defense: 1,
+                movement: 1,
+                movement_type: MovementType::Land,
+                ignores_terrain_cost: false,
+                can_found_cities: false,
+                can_build_improvements: false,
+                can_attack: true,
+                naval_unit: false,
+                build_time: 5,
+                production_cost: 45,
+                maintenance: 1.5,
+                withdraw_chance: 0.0,
+            },
+            // Great People are born rather than built, and can't fight
+            UnitType::GreatScientist | UnitType::GreatEngineer | UnitType::GreatMerchant => UnitStats {
+                max_health: 100,
+                attack: 0,
+                defense: 0,
+                movement: 2,
+                movement_type: MovementType::Land,
+                ignores_terrain_cost: false,
+                can_found_cities: false,
+                can_build_improvements: false,
+                can_attack: false,
+                naval_unit: false,
+                build_time: 0,
+                production_cost: 0,
+                maintenance: 0.0,
+                withdraw_chance: 0.0,
             },
         }
     }
@@ -417,13 +988,18 @@ impl UnitType {
     pub fn get_symbol(&self) -> &'static str {
         match self {
             UnitType::Warrior => "⚔",
-            UnitType::Archer => "🏹",
-            UnitType::Spearman => "🗡",
-            UnitType::Settler => "🏠",
-            UnitType::Worker => "🔨",
-            UnitType::Scout => "👁",
-            UnitType::Galley => "⛵",
-            UnitType::Trireme => "🚢",
+            UnitType::Archer => "➶",
+            UnitType::Spearman => "↟",
+            UnitType::Horseman => "♞",
+            UnitType::Settler => "⌂",
+            UnitType::Worker => "⚒",
+            UnitType::Scout => "◉",
+            UnitType::Galley => "⚓",
+            UnitType::Trireme => "♆",
+            UnitType::Catapult => "☄",
+            UnitType::GreatScientist => "⚗",
+            UnitType::GreatEngineer => "⚙",
+            UnitType::GreatMerchant => "⚖",
         }
     }
     
@@ -432,129 +1008,476 @@ impl UnitType {
             UnitType::Warrior => "Warrior",
             UnitType::Archer => "Archer",
             UnitType::Spearman => "Spearman",
+            UnitType::Horseman => "Horseman",
             UnitType::Settler => "Settler",
             UnitType::Worker => "Worker",
             UnitType::Scout => "Scout",
             UnitType::Galley => "Galley",
             UnitType::Trireme => "Trireme",
+            UnitType::Catapult => "Catapult",
+            UnitType::GreatScientist => "Great Scientist",
+            UnitType::GreatEngineer => "Great Engineer",
+            UnitType::GreatMerchant => "Great Merchant",
         }
     }
 }
 
-// System for handling unit selection
+// System for left-click selection; clicking the selected unit's hex again cycles through its stack.
+// Clicking an own city without own units on it opens that city instead.
 pub fn unit_selection_system(
-    windows: Query<&Window>,
-    camera_query: Query<(&Camera, &GlobalTransform)>,
-    mouse_input: Res<ButtonInput<MouseButton>>,
-    mut unit_query: Query<(Entity, &mut Unit), With<Unit>>,
+    mut click_intent: ResMut<ClickIntent>,
+    unit_query: Query<(Entity, &Unit)>,
+    city_query: Query<(Entity, &City)>,
     mut unit_selection: ResMut<UnitSelection>,
-    tile_query: Query<&MapTile>,
-    mut commands: Commands,
+    mut city_selection: ResMut<CitySelection>,
     civ_manager: Res<CivilizationManager>,
 ) {
-    if !mouse_input.just_pressed(MouseButton::Left) {
+    let Some(clicked_hex) = click_intent.take_select() else { return };
+    
+    // Only select units belonging to the current player's civilization
+    let Some(player_civ) = civ_manager.get_player_civilization() else { return };
+    
+    let mut own_units: Vec<Entity> = unit_query
+        .iter()
+        .filter(|(_, unit)| unit.hex_coord == clicked_hex && unit.civilization_id == player_civ.id)
+        .map(|(entity, _)| entity)
+        .collect();
+    own_units.sort();
+    
+    let own_city = city_query
+        .iter()
+        .find(|(_, city)| city.hex_coord == clicked_hex && city.civilization_id == player_civ.id)
+        .map(|(entity, _)| entity);
+    
+    // Repeated clicks cycle through the units on the hex, then the city under them
+    let mut targets: Vec<(Option<Entity>, Option<Entity>)> = own_units
+        .iter()
+        .map(|&unit| (Some(unit), None))
+        .collect();
+    if own_city.is_some() {
+        targets.push((None, own_city));
+    }
+    
+    let current = (unit_selection.selected_unit, city_selection.selected_city);
+    let (next_unit, next_city) = match targets.iter().position(|target| *target == current) {
+        Some(index) => targets[(index + 1) % targets.len()],
+        None => targets.first().copied().unwrap_or((None, None)),
+    };
+    
+    // Clicking anywhere without an own unit or city clears the selection
+    unit_selection.selected_unit = next_unit;
+    city_selection.selected_city = next_city;
+}
+
+// System for right-click orders on the selected unit: move there, walking over
+// several turns with a GoTo order if needed. Runs after attack_click_system, which
+// takes right-clicks on enemy units. Moves made are recorded for undo.
+#[allow(clippy::too_many_arguments)]
+pub fn unit_order_system(
+    mut click_intent: ResMut<ClickIntent>,
+    mut unit_query: Query<(Entity, &mut Unit)>,
+    city_query: Query<&City>,
+    unit_selection: Res<UnitSelection>,
+    tiles: TileLookup,
+    civ_manager: Res<CivilizationManager>,
+    mut notifications: EventWriter<GameNotification>,
+    mut history: ResMut<MoveHistory>,
+) {
+    let Some(target) = click_intent.take_order() else { return };
+    let Some(selected_entity) = unit_selection.selected_unit else { return };
+    
+    let Ok((_, unit)) = unit_query.get(selected_entity) else { return };
+    let unit_positions = unit_query.iter().map(|(_, u)| (u.civilization_id, u.hex_coord));
+    let blocked = impassable_positions(unit, unit_positions, city_query.iter(), &civ_manager);
+    
+    // Ordered onto a civilization we're at peace with: say why nothing happens
+    if let Some(civ_id) = peaceful_owner(unit, target, &unit_query, &city_query, &civ_manager) {
+        let name = civ_manager.get_civilization(civ_id).map_or("Unknown", |civ| civ.name.as_str());
+        let text = format!("You are at peace with {}. Declare war from the Diplomacy panel (F9) to enter their lands or attack them.", name);
+        notifications.write(GameNotification::new(NotificationCategory::Diplomacy, text).at(target));
         return;
     }
     
-    let Ok(window) = windows.single() else { return };
-    let Ok((camera, camera_transform)) = camera_query.single() else { return };
+    let Ok((_, mut unit)) = unit_query.get_mut(selected_entity) else { return };
+    if target == unit.hex_coord {
+        unit.clear_order();
+        return;
+    }
     
-    if let Some(world_position) = window
-        .cursor_position()
-        .and_then(|cursor| camera.viewport_to_world_2d(camera_transform, cursor).ok())
-    {
-        let clicked_hex = HexCoord::from_world_pos(world_position, super::map::HEX_SIZE);
-        
-        // Check if we clicked on a unit
-        let mut clicked_unit = None;
-        for (entity, unit) in unit_query.iter() {
-            if unit.hex_coord == clicked_hex {
-                // Only select units belonging to the current player's civilization
-                if let Some(player_civ) = civ_manager.get_player_civilization() {
-                    if unit.civilization_id == player_civ.id {
-                        clicked_unit = Some(entity);
-                        break;
-                    }
-                }
-            }
+    let before = MoveRecord {
+        unit: selected_entity,
+        from: unit.hex_coord,
+        to: unit.hex_coord,
+        movement_points: unit.movement_points,
+        has_moved: unit.has_moved,
+        is_fortified: unit.is_fortified,
+        fortification_turns: unit.fortification_turns,
+        order: unit.order,
+        path: unit.path.clone(),
+        health: unit.health,
+    };
+    if unit.set_goto(target, &tiles, &blocked) {
+        unit.advance_goto(&tiles, &blocked);
+        if unit.hex_coord != before.from {
+            history.moves.push(MoveRecord { to: unit.hex_coord, ..before });
+        }
+    } else {
+        let text = format!("{} can't reach ({}, {})", unit.name, target.q, target.r);
+        notifications.write(GameNotification::new(NotificationCategory::Warning, text).at(target));
+    }
+}
+
+// System that forgets the moves that can no longer be taken back: at the end of the
+// turn and after any fight, founding or first contact
+pub fn move_history_upkeep_system(
+    mut history: ResMut<MoveHistory>,
+    combat_log: Res<CombatLog>,
+    mut turn_events: EventReader<TurnStartedEvent>,
+    mut founded_events: EventReader<CityFoundedEvent>,
+    mut contact_events: EventReader<FirstContactEvent>,
+) {
+    let fought = combat_log.recorded() != history.log_seen;
+    let turn_ended = turn_events.read().count() > 0;
+    let founded = founded_events.read().count() > 0;
+    let met = contact_events.read().count() > 0;
+    if fought {
+        history.log_seen = combat_log.recorded();
+    }
+    if (fought || turn_ended || founded || met) && !history.moves.is_empty() {
+        history.clear();
+    }
+}
+
+// System for taking back the player's last move with Ctrl+Z: the unit goes back to
+// where it stood with the movement, fortification and orders it had
+pub fn undo_move_system(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    input_map: Res<InputMap>,
+    mut history: ResMut<MoveHistory>,
+    mut unit_query: Query<&mut Unit>,
+    mut unit_selection: ResMut<UnitSelection>,
+    mut notifications: EventWriter<GameNotification>,
+) {
+    if !input_map.just_pressed(Action::Undo, &keyboard) {
+        return;
+    }
+    let Some(record) = history.moves.pop() else {
+        notifications.write(GameNotification::new(NotificationCategory::Warning, "No move to undo this turn"));
+        return;
+    };
+    
+    // A unit that has since been moved some other way, hurt or killed keeps where it is
+    let Ok(mut unit) = unit_query.get_mut(record.unit) else {
+        history.clear();
+        return;
+    };
+    if unit.hex_coord != record.to || unit.health != record.health {
+        history.clear();
+        let text = format!("{} can't take back its move", unit.name);
+        notifications.write(GameNotification::new(NotificationCategory::Warning, text).at(unit.hex_coord));
+        return;
+    }
+    
+    unit.hex_coord = record.from;
+    unit.movement_points = record.movement_points;
+    unit.has_moved = record.has_moved;
+    unit.is_fortified = record.is_fortified;
+    unit.fortification_turns = record.fortification_turns;
+    unit.order = record.order;
+    unit.path = record.path;
+    unit_selection.selected_unit = Some(record.unit);
+    let text = format!("{} moved back to ({}, {})", unit.name, record.from.q, record.from.r);
+    notifications.write(GameNotification::new(NotificationCategory::System, text).at(record.from));
+}
+
+// System for carrying out GoTo orders of the civilization whose turn it is
+pub fn goto_order_system(
+    mut unit_query: Query<&mut Unit>,
+    city_query: Query<&City>,
+    tiles: TileLookup,
+    civ_manager: Res<CivilizationManager>,
+) {
+    let unit_positions: Vec<(u32, HexCoord)> = unit_query
+        .iter()
+        .map(|unit| (unit.civilization_id, unit.hex_coord))
+        .collect();
+    
+    for mut unit in unit_query.iter_mut() {
+        if unit.order == UnitOrder::Idle
+            || unit.movement_points == 0
+            || !civ_manager.is_current_turn(unit.civilization_id)
+        {
+            continue;
         }
         
-        if let Some(unit_entity) = clicked_unit {
-            // Select the unit
-            select_unit(unit_entity, &mut unit_selection, &mut unit_query, &tile_query, &mut commands);
-        } else if let Some(selected_entity) = unit_selection.selected_unit {
-            // Try to move the selected unit
-            if let Ok((_, mut unit)) = unit_query.get_mut(selected_entity) {
-                if unit_selection.valid_moves.contains(&clicked_hex) {
-                    unit.move_to(clicked_hex, &tile_query);
-                    // Update the unit's visual position would happen in another system
-                }
-            }
-            
-            // Deselect after attempting move
-            deselect_unit(&mut unit_selection, &mut commands);
+        let blocked = impassable_positions(&unit, unit_positions.iter().copied(), city_query.iter(), &civ_manager);
+        unit.advance_goto(&tiles, &blocked);
+    }
+}
+
+// Hexes holding units or cities hostile to `civilization_id`
+pub fn enemy_positions<'a>(
+    civilization_id: u32,
+    units: impl Iterator<Item = &'a Unit>,
+    cities: impl Iterator<Item = &'a City>,
+    civ_manager: &CivilizationManager,
+) -> HashSet<HexCoord> {
+    let unit_positions = units.map(|unit| (unit.civilization_id, unit.hex_coord));
+    let city_positions = cities.map(|city| (city.civilization_id, city.hex_coord));
+    unit_positions
+        .chain(city_positions)
+        .filter(|(civ_id, _)| are_enemies(civilization_id, *civ_id, civ_manager))
+        .map(|(_, coord)| coord)
+        .collect()
+}
+
+// Hexes a unit can't enter: anything holding another civilization's units or cities,
+// and the territory of civilizations it's at peace with. A unit already inside such
+// territory (say, when peace was made) may still move within it to get out.
+pub fn impassable_positions<'a>(
+    unit: &Unit,
+    unit_positions: impl Iterator<Item = (u32, HexCoord)>,
+    cities: impl Iterator<Item = &'a City>,
+    civ_manager: &CivilizationManager,
+) -> HashSet<HexCoord> {
+    let civilization_id = unit.civilization_id;
+    let mut blocked: HashSet<HexCoord> = unit_positions
+        .filter(|(civ_id, _)| *civ_id != civilization_id)
+        .map(|(_, coord)| coord)
+        .collect();
+    
+    for city in cities.filter(|city| city.civilization_id != civilization_id) {
+        blocked.insert(city.hex_coord);
+        let closed = !are_enemies(civilization_id, city.civilization_id, civ_manager)
+            && !city.territory_tiles.contains(&unit.hex_coord);
+        if closed {
+            blocked.extend(city.territory_tiles.iter().copied());
         }
     }
+    blocked
 }
 
-fn select_unit(
-    unit_entity: Entity,
-    unit_selection: &mut ResMut<UnitSelection>,
-    unit_query: &mut Query<(Entity, &mut Unit), With<Unit>>,
-    tile_query: &Query<&MapTile>,
-    commands: &mut Commands,
+// The civilization at peace with the unit's owner whose unit, city or closed territory is at `coord`, if any
+fn peaceful_owner(
+    unit: &Unit,
+    coord: HexCoord,
+    unit_query: &Query<(Entity, &mut Unit)>,
+    city_query: &Query<&City>,
+    civ_manager: &CivilizationManager,
+) -> Option<u32> {
+    let civilization_id = unit.civilization_id;
+    let unit_owners = unit_query
+        .iter()
+        .filter(|(_, other)| other.hex_coord == coord)
+        .map(|(_, other)| other.civilization_id);
+    let city_owners = city_query
+        .iter()
+        .filter(|city| {
+            city.hex_coord == coord
+                || (city.territory_tiles.contains(&coord) && !city.territory_tiles.contains(&unit.hex_coord))
+        })
+        .map(|city| city.civilization_id);
+    
+    unit_owners
+        .chain(city_owners)
+        .find(|&civ_id| civ_id != civilization_id && !are_enemies(civilization_id, civ_id, civ_manager))
+}
+
+// System that keeps the movement and attack indicators in sync with the selected unit.
+// It's the one place they're put away: when the selection is cleared, the unit dies or
+// is disbanded, or its civilization's turn ends.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn update_selection_indicators(
+    mut commands: Commands,
+    mut unit_selection: ResMut<UnitSelection>,
+    mut indicators: ResMut<MovementIndicators>,
+    mut indicator_query: Query<(&mut Transform, &mut MeshMaterial2d<ColorMaterial>, &mut Visibility), With<MovementIndicator>>,
+    unit_query: Query<(Entity, &Unit)>,
+    city_query: Query<&City>,
+    tiles: TileLookup,
+    civ_manager: Res<CivilizationManager>,
 ) {
-    // Deselect previous unit
-    deselect_unit(unit_selection, commands);
-    
-    // Select new unit
-    unit_selection.selected_unit = Some(unit_entity);
-    
-    if let Ok((_, unit)) = unit_query.get(unit_entity) {
-        // Calculate valid moves
-        unit_selection.valid_moves = unit.calculate_valid_moves(tile_query);
-        
-        // Create movement indicators
-        let valid_moves_copy = unit_selection.valid_moves.clone();
-        for &move_coord in &valid_moves_copy {
-            let world_pos = move_coord.to_world_pos(super::map::HEX_SIZE);
-            let indicator = commands.spawn((
-                MovementIndicator,
-                Text2d::new("○"), // Circle outline for valid moves
-                TextColor(Color::srgb(0.0, 1.0, 0.0)), // Green
-                TextFont {
-                    font_size: 16.0,
-                    ..default()
-                },
-                Transform::from_translation(Vec3::new(world_pos.x, world_pos.y, 1.5)),
-            )).id();
-            
-            unit_selection.movement_indicators.push(indicator);
+    let selected = unit_selection
+        .selected_unit
+        .and_then(|entity| unit_query.get(entity).ok());
+    
+    // Selected unit died or was disbanded
+    if selected.is_none() {
+        unit_selection.selected_unit = None;
+    }
+    
+    let selected = selected.filter(|(_, unit)| civ_manager.is_current_turn(unit.civilization_id));
+    let current = selected.map(|(entity, unit)| (entity, unit.hex_coord, unit.movement_points));
+    if current == unit_selection.shown_for {
+        return;
+    }
+    unit_selection.shown_for = current;
+    
+    unit_selection.valid_moves.clear();
+    unit_selection.attack_targets.clear();
+    if let Some((_, unit)) = selected {
+        find_moves_and_targets(&mut unit_selection, unit, &unit_query, &city_query, &tiles, &civ_manager);
+    }
+    
+    let moves = unit_selection.valid_moves.iter().map(|coord| (*coord, indicators.move_material.clone()));
+    let attacks = unit_selection.attack_targets.iter().map(|coord| (*coord, indicators.attack_material.clone()));
+    let wanted: Vec<(HexCoord, Handle<ColorMaterial>)> = moves.chain(attacks).collect();
+    let Some(root) = indicators.root else { return };
+    
+    for (index, (coord, material)) in wanted.iter().enumerate() {
+        let world_pos = coord.to_world_pos(HEX_SIZE);
+        let Some(&entity) = indicators.pool.get(index) else {
+            let entity = indicators.spawn_indicator(&mut commands, root, world_pos, material.clone(), Visibility::Inherited);
+            indicators.pool.push(entity);
+            continue;
+        };
+        let Ok((mut transform, mut indicator_material, mut visibility)) = indicator_query.get_mut(entity) else { continue };
+        transform.translation = world_pos.extend(1.5);
+        indicator_material.0 = material.clone();
+        *visibility = Visibility::Inherited;
+    }
+    // Put away the ones the last selection used and this one doesn't
+    for &entity in indicators.pool.iter().take(indicators.shown).skip(wanted.len()) {
+        if let Ok((_, _, mut visibility)) = indicator_query.get_mut(entity) {
+            *visibility = Visibility::Hidden;
         }
     }
+    indicators.shown = wanted.len();
 }
 
-fn deselect_unit(
-    unit_selection: &mut ResMut<UnitSelection>,
-    commands: &mut Commands,
+// Where the unit can go and what it can attack from where it stands
+fn find_moves_and_targets(
+    unit_selection: &mut UnitSelection,
+    unit: &Unit,
+    unit_query: &Query<(Entity, &Unit)>,
+    city_query: &Query<&City>,
+    tiles: &TileLookup,
+    civ_manager: &CivilizationManager,
 ) {
-    unit_selection.selected_unit = None;
-    unit_selection.valid_moves.clear();
     
-    // Remove movement indicators
-    for indicator_entity in unit_selection.movement_indicators.drain(..) {
-        commands.entity(indicator_entity).despawn();
+    let unit_positions = unit_query.iter().map(|(_, u)| (u.civilization_id, u.hex_coord));
+    let blocked = impassable_positions(unit, unit_positions, city_query.iter(), civ_manager);
+    unit_selection.valid_moves = unit.calculate_valid_moves(tiles, &blocked);
+    
+    if unit.can_attack && unit.movement_points > 0 && !unit.has_attacked {
+        let attack_range = get_attack_range(unit);
+        let enemies = enemy_positions(unit.civilization_id, unit_query.iter().map(|(_, u)| u), city_query.iter(), civ_manager);
+        let mut targets: Vec<HexCoord> = enemies
+            .into_iter()
+            .filter(|coord| unit.hex_coord.distance(*coord) <= attack_range)
+            .collect();
+        targets.sort_by_key(|coord| (coord.q, coord.r));
+        unit_selection.attack_targets = targets;
+    }
+}
+
+// System for renaming the selected unit (R), cycling through its civilization's themed names
+pub fn rename_unit_system(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    input_map: Res<InputMap>,
+    mut unit_query: Query<(Entity, &mut Unit)>,
+    unit_selection: Res<UnitSelection>,
+    civ_manager: Res<CivilizationManager>,
+) {
+    if !input_map.just_pressed(Action::Rename, &keyboard) {
+        return;
+    }
+    let Some(selected_entity) = unit_selection.selected_unit else { return };
+    let Ok((_, unit)) = unit_query.get(selected_entity) else { return };
+    
+    let civ_id = unit.civilization_id;
+    let civ_name = civ_manager.get_civilization(civ_id)
+        .map(|c| c.name.as_str())
+        .unwrap_or("Unknown");
+    
+    let taken: HashSet<String> = unit_query
+        .iter()
+        .filter(|(entity, other)| *entity != selected_entity && other.civilization_id == civ_id)
+        .map(|(_, other)| other.name.clone())
+        .collect();
+    
+    // The plain type name closes the cycle so a unit can be un-named again
+    let mut names: Vec<String> = get_unit_names_for_civilization(civ_name)
+        .into_iter()
+        .map(str::to_string)
+        .collect();
+    names.push(unit.unit_type.get_name().to_string());
+    
+    let start = names.iter().position(|name| *name == unit.name).map_or(0, |index| index + 1);
+    let next_name = (0..names.len())
+        .map(|offset| &names[(start + offset) % names.len()])
+        .find(|name| !taken.contains(*name))
+        .cloned();
+    
+    if let (Some(next_name), Ok((_, mut unit))) = (next_name, unit_query.get_mut(selected_entity)) {
+        println!("{} is now known as {}", unit.display_name(), next_name);
+        unit.name = next_name;
+    }
+}
+
+fn get_unit_names_for_civilization(civ_name: &str) -> Vec<&'static str> {
+    match civ_name {
+        "Roman Empire" => vec![
+            "Legio I Italica", "Legio II Augusta", "Legio III Gallica", "Legio IV Scythica",
+            "Legio V Alaudae", "Legio VI Victrix", "Legio VII Claudia", "Legio VIII Augusta",
+            "Legio IX Hispana", "Legio X Fretensis", "Legio XII Fulminata", "Legio XIII Gemina"
+        ],
+        "Egyptian Kingdom" => vec![
+            "Host of Amun", "Host of Ra", "Host of Ptah", "Host of Set",
+            "Guard of Horus", "Nubian Bowmen", "Medjay", "Sons of Thebes"
+        ],
+        "Greek City-States" => vec![
+            "Sacred Band", "The Three Hundred", "Companions", "Hypaspists",
+            "Argyraspides", "Silver Shields", "Men of Marathon", "Athenian Hoplites"
+        ],
+        "Phoenician Traders" => vec![
+            "Sacred Band of Carthage", "Tyrian Guard", "Sons of Melqart", "Sidonian Marines",
+            "Hannibal's Veterans", "Numidian Riders", "Balearic Slingers", "Libyan Spears"
+        ],
+        "Celtic Tribes" => vec![
+            "Boii Warband", "Arverni Host", "Iceni Raiders", "Sons of Brennus",
+            "Gaesatae", "Fianna", "Red Branch", "Carnutes Guard"
+        ],
+        "Mesopotamian Empire" => vec![
+            "Guard of Marduk", "Host of Ishtar", "Chariots of Ur", "Lions of Babylon",
+            "Sargon's Own", "Spears of Akkad", "Bowmen of Assur", "Immortals of Nineveh"
+        ],
+        _ => vec![
+            "First Company", "Second Company", "Iron Guard", "Grey Wolves",
+            "Old Guard", "Vanguard", "Stormriders", "Free Company"
+        ]
     }
 }
 
 // System for starting unit turns
 pub fn start_unit_turns(
     mut unit_query: Query<&mut Unit>,
+    mut turn_events: EventReader<TurnStartedEvent>,
+) {
+    for event in turn_events.read() {
+        for mut unit in unit_query.iter_mut() {
+            if unit.civilization_id == event.civilization_id {
+                unit.start_turn();
+            }
+        }
+    }
+}
+
+// System that keeps each unit's ocean access in step with its civilization's: set as the
+// unit is raised, and for every unit a civilization has once it learns a technology
+pub fn ocean_access_system(
+    mut unit_query: Query<&mut Unit>,
+    mut researched: EventReader<TechResearchedEvent>,
     civ_manager: Res<CivilizationManager>,
 ) {
+    let learned: HashSet<u32> = researched.read().map(|event| event.civilization_id).collect();
     for mut unit in unit_query.iter_mut() {
-        if civ_manager.is_current_turn(unit.civilization_id) {
-            unit.start_turn();
+        if !unit.is_added() && !learned.contains(&unit.civilization_id) {
+            continue;
+        }
+        let ocean_going = civ_manager.get_civilization(unit.civilization_id).is_some_and(|civ| civ.can_cross_oceans());
+        if unit.ocean_going != ocean_going {
+            unit.ocean_going = ocean_going;
         }
     }
 }
@@ -562,51 +1485,185 @@ pub fn start_unit_turns(
 // System for spawning unit markers (visual representation)
 pub fn spawn_unit_markers(
     mut commands: Commands,
-    units_query: Query<&Unit, Added<Unit>>,
+    units_query: Query<(Entity, &Unit), Added<Unit>>,
     civ_manager: Res<CivilizationManager>,
+    fonts: Res<GameFonts>,
 ) {
-    for unit in units_query.iter() {
+    for (unit_entity, unit) in units_query.iter() {
         let world_pos = unit.hex_coord.to_world_pos(super::map::HEX_SIZE);
         
         // Get civilization color
         let color = civ_manager.get_civilization(unit.civilization_id)
             .map(|civ| civ.color)
-            .unwrap_or(Color::WHITE);
+            .unwrap_or(if unit.civilization_id == BARBARIAN_CIV_ID { BARBARIAN_COLOR } else { Color::WHITE });
         
         // Create unit marker
-        commands.spawn((
-            UnitMarker {
-                civilization_id: unit.civilization_id,
-                unit_type: unit.unit_type,
-            },
+        let marker = commands.spawn((
+            UnitMarker { unit: unit_entity },
+            Cullable,
             Text2d::new(unit.unit_type.get_symbol()),
             TextColor(color),
-            TextFont {
-                font_size: 20.0,
+            fonts.symbol(20.0),
+            Transform::from_translation(Vec3::new(world_pos.x, world_pos.y, 3.0)), // Above cities
+        )).id();
+        
+        // Its health bar above and status icons below, filled in by update_unit_conditions
+        let fill = commands.spawn((
+            Sprite {
+                custom_size: Some(Vec2::new(CONDITION_BAR_WIDTH, CONDITION_BAR_HEIGHT)),
+                anchor: bevy::sprite::Anchor::CenterLeft,
                 ..default()
             },
-            Transform::from_translation(Vec3::new(world_pos.x, world_pos.y, 3.0)), // Above cities
-        ));
+            Transform::from_xyz(-CONDITION_BAR_WIDTH / 2.0, 0.0, 0.1),
+        )).id();
+        let bar = commands.spawn((
+            Sprite::from_color(CONDITION_BAR_BACKGROUND, Vec2::new(CONDITION_BAR_WIDTH + 2.0, CONDITION_BAR_HEIGHT + 2.0)),
+            Transform::from_xyz(0.0, CONDITION_OFFSET, 0.1),
+            Visibility::Hidden,
+        )).add_child(fill).id();
+        let icons = commands.spawn((
+            Text2d::new(""),
+            TextColor(CONDITION_ICON_COLOR),
+            fonts.symbol(9.0),
+            Transform::from_xyz(0.0, -CONDITION_OFFSET, 0.1),
+        )).id();
+        let condition = commands.spawn((
+            UnitCondition { unit: unit_entity, bar, fill, icons },
+            Transform::default(),
+            Visibility::default(),
+        )).add_children(&[bar, icons]).id();
+        commands.entity(marker).add_child(condition);
+    }
+}
+
+// Green at full health through yellow to red near death
+fn health_color(fraction: f32) -> Color {
+    let (green, yellow, red) = (Srgba::rgb(0.2, 0.85, 0.2), Srgba::rgb(0.95, 0.85, 0.2), Srgba::rgb(0.9, 0.2, 0.15));
+    let color = if fraction > 0.5 {
+        yellow.mix(&green, (fraction - 0.5) * 2.0)
+    } else {
+        red.mix(&yellow, fraction * 2.0)
+    };
+    color.into()
+}
+
+// Refreshes the bars and icons of units that changed, and of the two units a change of
+// selection moves between. Bars are hidden at full health, except on the selected unit.
+pub fn update_unit_conditions(
+    unit_query: Query<Ref<Unit>>,
+    condition_query: Query<Ref<UnitCondition>>,
+    mut bar_query: Query<&mut Visibility>,
+    mut fill_query: Query<&mut Sprite>,
+    mut icon_query: Query<&mut Text2d>,
+    unit_selection: Res<UnitSelection>,
+    mut last_selected: Local<Option<Entity>>,
+) {
+    let selected = unit_selection.selected_unit;
+    let selection_moved = selected != *last_selected;
+    for condition in condition_query.iter() {
+        let Ok(unit) = unit_query.get(condition.unit) else { continue };
+        let is_selected = selected == Some(condition.unit);
+        let selection_touched = selection_moved && (is_selected || *last_selected == Some(condition.unit));
+        if !unit.is_changed() && !condition.is_added() && !selection_touched {
+            continue;
+        }
+        
+        let fraction = (unit.health as f32 / unit.max_health.max(1) as f32).clamp(0.0, 1.0);
+        if let Ok(mut visibility) = bar_query.get_mut(condition.bar) {
+            visibility.set_if_neq(if fraction < 1.0 || is_selected { Visibility::Inherited } else { Visibility::Hidden });
+        }
+        if let Ok(mut fill) = fill_query.get_mut(condition.fill) {
+            fill.custom_size = Some(Vec2::new(CONDITION_BAR_WIDTH * fraction, CONDITION_BAR_HEIGHT));
+            fill.color = health_color(fraction);
+        }
+        
+        let mut icons = Vec::new();
+        if unit.is_fortified {
+            icons.push("⬟");
+        }
+        if unit.order == UnitOrder::Sentry {
+            icons.push("zz");
+        }
+        if unit.battles_survived >= VETERAN_BATTLES {
+            icons.push("★");
+        }
+        if let Ok(mut text) = icon_query.get_mut(condition.icons) {
+            let icons = icons.join(" ");
+            if text.0 != icons {
+                text.0 = icons;
+            }
+        }
+    }
+    *last_selected = selected;
+}
+
+// Hides the bars and icons when the camera is zoomed far out, where they're only clutter
+pub fn unit_condition_zoom_system(
+    camera_query: Query<Ref<Transform>, With<Camera>>,
+    mut condition_query: Query<(Ref<UnitCondition>, &mut Visibility)>,
+) {
+    let Ok(camera_transform) = camera_query.single() else { return };
+    let visibility = if camera_transform.scale.x > CONDITION_MAX_CAMERA_SCALE { Visibility::Hidden } else { Visibility::Inherited };
+    for (condition, mut condition_visibility) in condition_query.iter_mut() {
+        if camera_transform.is_changed() || condition.is_added() {
+            condition_visibility.set_if_neq(visibility);
+        }
     }
 }
 
-// System for updating unit marker positions when units move
+// System for updating unit marker positions when units move, and their symbol when one is upgraded
 pub fn update_unit_marker_positions(
     unit_query: Query<&Unit, Changed<Unit>>,
-    mut marker_query: Query<(&UnitMarker, &mut Transform)>,
+    mut marker_query: Query<(&UnitMarker, &mut Transform, &mut Text2d)>,
 ) {
-    for unit in unit_query.iter() {
-        // Find the corresponding marker
-        for (marker, mut transform) in marker_query.iter_mut() {
-            if marker.civilization_id == unit.civilization_id && marker.unit_type == unit.unit_type {
-                let world_pos = unit.hex_coord.to_world_pos(super::map::HEX_SIZE);
-                transform.translation = Vec3::new(world_pos.x, world_pos.y, 3.0);
-                break; // Assume one marker per unit for now
+    for (marker, mut transform, mut symbol) in marker_query.iter_mut() {
+        if let Ok(unit) = unit_query.get(marker.unit) {
+            let world_pos = unit.hex_coord.to_world_pos(super::map::HEX_SIZE);
+            transform.translation = Vec3::new(world_pos.x, world_pos.y, 3.0);
+            if symbol.0 != unit.unit_type.get_symbol() {
+                symbol.0 = unit.unit_type.get_symbol().to_string();
             }
         }
     }
 }
 
+// Takes markers off the map once their unit is gone, however it died or was disbanded
+pub fn remove_orphaned_unit_markers(
+    mut commands: Commands,
+    marker_query: Query<(Entity, &UnitMarker)>,
+    unit_query: Query<(), With<Unit>>,
+) {
+    for (marker_entity, marker) in marker_query.iter() {
+        if !unit_query.contains(marker.unit) {
+            commands.entity(marker_entity).despawn();
+        }
+    }
+}
+
+// Hands captured civilians to their captor: Workers change sides as they are, Settlers
+// are put to work as Workers
+pub fn unit_capture_system(
+    mut commands: Commands,
+    mut capture_events: EventReader<UnitCaptured>,
+    unit_query: Query<&Unit>,
+    mut civ_manager: ResMut<CivilizationManager>,
+    mut notifications: EventWriter<GameNotification>,
+) {
+    for event in capture_events.read() {
+        let Ok(unit) = unit_query.get(event.unit_entity) else { continue };
+        if let Some(civ) = civ_manager.get_civilization_mut(unit.civilization_id) {
+            civ.remove_unit(event.unit_entity);
+        }
+        let (q, r) = (unit.hex_coord.q, unit.hex_coord.r);
+        let captor = civ_manager.civ_name(event.new_civilization_id);
+        let military = |text: String| GameNotification::new(NotificationCategory::Military, text).at(unit.hex_coord);
+        notifications.write(military(format!("Captured a {} at ({}, {})", unit.unit_type.get_name(), q, r)).for_civ(event.new_civilization_id));
+        notifications.write(military(format!("The {} captured our {} at ({}, {})", captor, unit.unit_type.get_name(), q, r)).for_civ(unit.civilization_id));
+        commands.entity(event.unit_entity).despawn();
+        spawn_unit(&mut commands, UnitType::Worker, event.new_civilization_id, unit.hex_coord, &mut civ_manager);
+    }
+}
+
 // Function to spawn a unit
 pub fn spawn_unit(
     commands: &mut Commands,
@@ -615,7 +1672,14 @@ pub fn spawn_unit(
     hex_coord: HexCoord,
     civ_manager: &mut CivilizationManager,
 ) -> Entity {
-    let unit = Unit::new(unit_type, civilization_id, hex_coord);
+    let mut unit = Unit::new(unit_type, civilization_id, hex_coord);
+    let seafaring = civ_manager
+        .get_civilization(civilization_id)
+        .is_some_and(|civ| civ.has_trait(CivTrait::Seafaring));
+    if seafaring && unit_type.get_stats().naval_unit {
+        unit.max_movement_points += SEAFARING_NAVAL_MOVEMENT;
+        unit.movement_points = unit.max_movement_points;
+    }
     let unit_entity = commands.spawn(unit).id();
     
     // Add unit to civilization
@@ -645,4 +1709,215 @@ pub fn spawn_city(
     }
     
     city_entity
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::civilization::{OCEAN_TECH, create_default_civilizations};
+    use crate::game::combat::{CombatRecorders, resolve_attack, target_preview};
+    use crate::game::diplomacy::WarStrength;
+    use crate::game::map::MapTile;
+    use crate::game::test_support::{area, game_app, run, tile};
+    
+    const ORIGIN: HexCoord = HexCoord { q: 0, r: 0 };
+    
+    // Grassland around ORIGIN, the human civilization 1 at war with civilization 2, and the
+    // player's order and undo systems running as they do in the game
+    fn units_app() -> App {
+        let mut app = game_app(area(ORIGIN, 4, TerrainType::TemperateGrassland));
+        let mut manager = CivilizationManager::default();
+        for civ in create_default_civilizations().into_iter().take(2) {
+            manager.add_civilization(civ);
+        }
+        manager.current_turn_civ = 1;
+        manager.diplomacy.declare_war((1, WarStrength::default()), (2, WarStrength::default()), 0);
+        app.insert_resource(manager)
+            .init_resource::<ButtonInput<KeyCode>>()
+            .init_resource::<InputMap>()
+            .init_resource::<ClickIntent>()
+            .init_resource::<UnitSelection>()
+            .init_resource::<MoveHistory>()
+            .init_resource::<CombatLog>()
+            .init_resource::<CitySelection>()
+            .add_event::<FirstContactEvent>()
+            .add_systems(Update, (
+                move_history_upkeep_system,
+                unit_order_system,
+                undo_move_system.before(unit_order_system),
+            ));
+        app
+    }
+    
+    fn place(app: &mut App, unit_type: UnitType, civ_id: u32, coord: HexCoord) -> Entity {
+        run(app, move |mut commands: Commands, mut manager: ResMut<CivilizationManager>| {
+            spawn_unit(&mut commands, unit_type, civ_id, coord, &mut manager)
+        })
+    }
+    
+    fn unit(app: &App, entity: Entity) -> Unit {
+        app.world().get::<Unit>(entity).expect("unit should exist").clone()
+    }
+    
+    // Right-clicks `target` with `entity` selected
+    fn order(app: &mut App, entity: Entity, target: HexCoord) {
+        app.world_mut().resource_mut::<UnitSelection>().selected_unit = Some(entity);
+        app.world_mut().resource_mut::<ClickIntent>().order = Some(target);
+        app.update();
+    }
+    
+    // Presses Ctrl+Z for one frame and returns the notifications it raised
+    fn undo(app: &mut App) -> Vec<String> {
+        let mut keyboard = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+        keyboard.press(KeyCode::ControlLeft);
+        keyboard.press(KeyCode::KeyZ);
+        app.update();
+        let mut keyboard = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+        keyboard.release_all();
+        keyboard.clear();
+        let mut notifications = app.world_mut().resource_mut::<Events<GameNotification>>();
+        notifications.drain().map(|notification| notification.text).collect()
+    }
+    
+    // `attacker` attacks `target` as the combat system would resolve it
+    fn fight(app: &mut App, attacker: Entity, target: HexCoord) {
+        run(app, move |
+            mut commands: Commands,
+            mut units: Query<(Entity, &mut Unit)>,
+            mut cities: Query<(Entity, &mut City)>,
+            tiles: TileLookup,
+            tile_query: Query<&MapTile>,
+            manager: Res<CivilizationManager>,
+            mut recorders: CombatRecorders,
+        | {
+            let preview = target_preview(attacker, target, &units, &cities, &tile_query, &manager).expect("an enemy to attack");
+            resolve_attack(&mut commands, &mut units, &mut cities, &tiles, preview, &manager, &mut recorders);
+        });
+    }
+    
+    // Undo puts the unit back exactly as it was: where it stood, its movement, its orders
+    #[test]
+    fn undo_restores_position_and_movement() {
+        let mut app = units_app();
+        let horseman = place(&mut app, UnitType::Horseman, 1, ORIGIN);
+        let before = unit(&app, horseman);
+        
+        order(&mut app, horseman, HexCoord { q: 1, r: 0 });
+        order(&mut app, horseman, HexCoord { q: 2, r: 0 });
+        let moved = unit(&app, horseman);
+        assert_eq!(moved.hex_coord, HexCoord { q: 2, r: 0 });
+        assert!(moved.movement_points < before.movement_points && moved.has_moved);
+        
+        undo(&mut app);
+        let halfway = unit(&app, horseman);
+        assert_eq!(halfway.hex_coord, HexCoord { q: 1, r: 0 });
+        assert!(halfway.movement_points > moved.movement_points);
+        
+        undo(&mut app);
+        let restored = unit(&app, horseman);
+        assert_eq!(restored.hex_coord, before.hex_coord);
+        assert_eq!(restored.movement_points, before.movement_points);
+        assert_eq!(restored.has_moved, before.has_moved);
+        assert_eq!(restored.order, before.order);
+        assert_eq!(restored.path, before.path);
+        assert_eq!(app.world().resource::<UnitSelection>().selected_unit, Some(horseman));
+        
+        assert_eq!(undo(&mut app), vec!["No move to undo this turn".to_string()]);
+        assert_eq!(unit(&app, horseman).hex_coord, before.hex_coord);
+    }
+    
+    // A move followed by a fight is final: the fight can't be taken back, so neither can
+    // the move that led to it
+    #[test]
+    fn moves_before_an_attack_cannot_be_undone() {
+        let mut app = units_app();
+        let horseman = place(&mut app, UnitType::Horseman, 1, ORIGIN);
+        let target = HexCoord { q: 2, r: 0 };
+        place(&mut app, UnitType::Warrior, 2, target);
+        order(&mut app, horseman, HexCoord { q: 1, r: 0 });
+        assert_eq!(unit(&app, horseman).hex_coord, HexCoord { q: 1, r: 0 });
+        
+        fight(&mut app, horseman, target);
+        app.update();
+        let after_attack = unit(&app, horseman);
+        
+        assert_eq!(undo(&mut app), vec!["No move to undo this turn".to_string()]);
+        let after_undo = unit(&app, horseman);
+        assert_eq!(after_undo.hex_coord, after_attack.hex_coord);
+        assert_eq!(after_undo.movement_points, after_attack.movement_points);
+        assert_ne!(after_undo.hex_coord, ORIGIN);
+    }
+    
+    // The player's selected unit, killed while the AI moves, leaves no move or attack
+    // hexes behind, then or once the player's turn comes back
+    #[test]
+    fn a_unit_killed_on_the_ai_turn_leaves_no_indicators() {
+        let mut app = units_app();
+        app.init_resource::<Assets<Mesh>>()
+            .init_resource::<Assets<ColorMaterial>>()
+            .init_resource::<MovementIndicators>()
+            .add_systems(Update, update_selection_indicators.after(unit_order_system));
+        run(&mut app, setup_movement_indicators);
+        let shown = |app: &mut App| {
+            app.world_mut()
+                .query_filtered::<&Visibility, With<MovementIndicator>>()
+                .iter(app.world())
+                .filter(|visibility| **visibility != Visibility::Hidden)
+                .count()
+        };
+        
+        let warrior = place(&mut app, UnitType::Warrior, 1, ORIGIN);
+        let horseman = place(&mut app, UnitType::Horseman, 2, HexCoord { q: 1, r: 0 });
+        app.world_mut().resource_mut::<UnitSelection>().selected_unit = Some(warrior);
+        app.update();
+        assert!(shown(&mut app) > 0);
+        
+        // Civilization 2's turn: its Horseman cuts the Warrior down before the next frame
+        app.world_mut().resource_mut::<CivilizationManager>().current_turn_civ = 2;
+        app.world_mut().get_mut::<Unit>(warrior).unwrap().health = 1;
+        fight(&mut app, horseman, ORIGIN);
+        assert!(app.world().get_entity(warrior).is_err(), "the Warrior should die");
+        app.update();
+        assert_eq!(shown(&mut app), 0);
+        assert_eq!(app.world().resource::<UnitSelection>().selected_unit, None);
+        
+        app.world_mut().resource_mut::<CivilizationManager>().current_turn_civ = 1;
+        app.update();
+        assert_eq!(shown(&mut app), 0);
+    }
+    
+    // Ships keep to the coast and land units stay ashore until their civilization can
+    // cross oceans: a Seafaring one from Sailing, anyone else from Astronomy
+    #[test]
+    fn oceans_open_with_sailing_for_seafarers_and_astronomy_for_the_rest() {
+        // An island of radius 1 in open sea; ring 2 is coastal water, ring 3 open ocean
+        let mut tiles = area(ORIGIN, 1, TerrainType::TemperateGrassland);
+        tiles.extend((2..=4).flat_map(|ring| ORIGIN.ring(ring)).map(|coord| tile(coord, TerrainType::Ocean)));
+        let mut app = game_app(tiles);
+        let mut manager = CivilizationManager::default();
+        for (mut civ, traits) in create_default_civilizations().into_iter().zip([vec![], vec![CivTrait::Seafaring]]) {
+            civ.traits = traits;
+            civ.technologies.push(Technology::Sailing);
+            manager.add_civilization(civ);
+        }
+        app.insert_resource(manager).add_systems(Update, ocean_access_system);
+        let (shore, coast, open_sea) = (HexCoord::new(1, 0), HexCoord::new(2, 0), HexCoord::new(3, 0));
+        let mut spawn = |unit_type, civ_id, at| app.world_mut().spawn(Unit::new(unit_type, civ_id, at)).id();
+        let units = [
+            (spawn(UnitType::Galley, 1, coast), open_sea),
+            (spawn(UnitType::Warrior, 1, shore), coast),
+            (spawn(UnitType::Galley, 2, coast), open_sea),
+            (spawn(UnitType::Warrior, 2, shore), coast),
+        ];
+        let can_go = |app: &mut App| {
+            app.update();
+            run(app, move |unit_query: Query<&Unit>, tiles: TileLookup| {
+                units.map(|(unit, target)| unit_query.get(unit).unwrap().can_move_to(target, &tiles))
+            })
+        };
+        assert_eq!(can_go(&mut app), [false, false, true, true]);
+        
+        app.world_mut().resource_mut::<CivilizationManager>().get_civilization_mut(1).unwrap().technologies.push(OCEAN_TECH);
+        app.world_mut().send_event(TechResearchedEvent { civilization_id: 1, technology: OCEAN_TECH });
+        assert_eq!(can_go(&mut app), [true, true, true, true]);
+    }
+}
@@ -0,0 +1,192 @@
+use bevy::prelude::*;
+use std::collections::HashSet;
+use super::cities::City;
+use super::civilization::CivilizationManager;
+use super::game_initialization::{AppState, GamePhase, GameSettings, GameState, TurnStartedEvent, advance_turn};
+use super::rules::rules;
+use super::units::Unit;
+
+/// How the game was decided
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum VictoryType {
+    Domination, // The last civilization still holding its original capital
+    Score,      // Highest score when the turn limit ran out
+    Defeat,     // The player was eliminated; the leading survivor takes the game
+}
+
+impl VictoryType {
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            VictoryType::Domination => "Domination Victory",
+            VictoryType::Score => "Score Victory",
+            VictoryType::Defeat => "Defeat",
+        }
+    }
+}
+
+/// A civilization lost its last city and is out of the game
+#[derive(Event)]
+pub struct CivilizationEliminatedEvent {
+    pub civilization_id: u32,
+    pub name: String,
+}
+
+/// One civilization's standing, as counted for a score victory
+#[derive(Clone, Debug, PartialEq)]
+pub struct CivScore {
+    pub civ_id: u32,
+    pub name: String,
+    pub eliminated: bool,
+    pub cities: usize,
+    pub population: u32,
+    pub territory: usize,
+    pub technologies: usize,
+    pub wonders: usize,
+    pub gold: f32,
+    pub score: f32,
+}
+
+/// Set when the game ends; the results screen shows it
+#[derive(Resource, Clone, Debug)]
+pub struct GameResult {
+    pub winner: u32,
+    pub victory: VictoryType,
+    pub final_turn: u32,
+    pub scores: Vec<CivScore>, // Highest score first
+}
+
+// Every civilization's score, best first, weighted as the rules say
+pub fn compute_scores(city_query: &Query<&City>, civ_manager: &CivilizationManager) -> Vec<CivScore> {
+    let weights = &rules().score;
+    let mut scores: Vec<CivScore> = civ_manager
+        .civilizations
+        .values()
+        .map(|civ| {
+            let cities: Vec<&City> = city_query.iter().filter(|city| city.civilization_id == civ.id).collect();
+            let population = cities.iter().map(|city| city.population).sum();
+            let territory = cities.iter().map(|city| city.territory_tiles.len()).sum();
+            let wonders = cities.iter().map(|city| city.wonders.len()).sum();
+            let technologies = civ.technologies.len();
+            
+            let score = population as f32 * weights.citizen
+                + territory as f32 * weights.tile
+                + technologies as f32 * weights.tech
+                + wonders as f32 * weights.wonder
+                + civ.gold.max(0.0) * weights.gold;
+            
+            CivScore {
+                civ_id: civ.id,
+                name: civ.name.clone(),
+                eliminated: civ.eliminated,
+                cities: cities.len(),
+                population,
+                territory,
+                technologies,
+                wonders,
+                gold: civ.gold,
+                score,
+            }
+        })
+        .collect();
+    scores.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.civ_id.cmp(&b.civ_id)));
+    scores
+}
+
+// Knocks out civilizations that have lost every city: their remaining units are
+// disbanded, they leave the turn rotation, and if it was their turn it passes on
+pub fn elimination_system(
+    mut commands: Commands,
+    mut game_state: ResMut<GameState>,
+    mut civ_manager: ResMut<CivilizationManager>,
+    unit_query: Query<(Entity, &Unit)>,
+    mut eliminated_events: EventWriter<CivilizationEliminatedEvent>,
+    mut turn_events: EventWriter<TurnStartedEvent>,
+) {
+    let mut newly_eliminated = Vec::new();
+    for civ in civ_manager.civilizations.values_mut() {
+        if civ.eliminated || !civ.cities.is_empty() {
+            continue;
+        }
+        civ.eliminated = true;
+        civ.units.clear();
+        civ.current_research = None;
+        civ.research_queue.clear();
+        for (entity, unit) in unit_query.iter() {
+            if unit.civilization_id == civ.id {
+                commands.entity(entity).despawn();
+            }
+        }
+        
+        println!("{} has been destroyed!", civ.name);
+        eliminated_events.write(CivilizationEliminatedEvent {
+            civilization_id: civ.id,
+            name: civ.name.clone(),
+        });
+        newly_eliminated.push(civ.id);
+    }
+    if newly_eliminated.is_empty() {
+        return;
+    }
+    // Deals with the fallen are void
+    civ_manager.diplomacy.payments.retain(|payment| {
+        !newly_eliminated.contains(&payment.payer) && !newly_eliminated.contains(&payment.payee)
+    });
+    
+    // Losing the last human ends the game instead
+    if newly_eliminated.contains(&civ_manager.current_turn_civ) && !civ_manager.humans_eliminated() {
+        advance_turn(&mut game_state, &mut civ_manager, &mut turn_events);
+    }
+}
+
+// Ends the game on a domination win, the elimination of every human player, or once the turn limit
+// has passed (a limit of 0 plays on until someone wins outright)
+pub fn victory_check_system(
+    mut commands: Commands,
+    mut game_state: ResMut<GameState>,
+    city_query: Query<&City>,
+    civ_manager: Res<CivilizationManager>,
+    settings: Res<GameSettings>,
+    mut turn_events: EventReader<TurnStartedEvent>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    let turn_started = turn_events.read().count() > 0;
+    
+    let survivors: Vec<u32> = civ_manager.civilizations.values().filter(|civ| !civ.eliminated).map(|civ| civ.id).collect();
+    // Civilizations that still hold the capital they started with
+    let capital_holders: HashSet<u32> = city_query
+        .iter()
+        .filter(|city| city.is_original_capital && city.civilization_id == city.original_civilization_id)
+        .map(|city| city.civilization_id)
+        .collect();
+    let player_eliminated = civ_manager.humans_eliminated();
+    
+    let leader = || {
+        compute_scores(&city_query, &civ_manager)
+            .into_iter()
+            .find(|score| !score.eliminated)
+            .map(|score| score.civ_id)
+    };
+    let outcome = if player_eliminated {
+        leader().map(|winner| (winner, VictoryType::Defeat))
+    } else if let [winner] = survivors[..] {
+        Some((winner, VictoryType::Domination))
+    } else if capital_holders.len() == 1 {
+        capital_holders.iter().next().map(|&winner| (winner, VictoryType::Domination))
+    } else if turn_started && settings.turn_limit > 0 && civ_manager.turn_number > settings.turn_limit {
+        leader().map(|winner| (winner, VictoryType::Score))
+    } else {
+        None
+    };
+    let Some((winner, victory)) = outcome else { return };
+    
+    let winner_name = civ_manager.get_civilization(winner).map_or("Unknown", |civ| civ.name.as_str());
+    println!("=== GAME OVER: {} - {} ===", victory.get_name(), winner_name);
+    game_state.current_phase = GamePhase::GameOver;
+    next_state.set(AppState::GameOver);
+    commands.insert_resource(GameResult {
+        winner,
+        victory,
+        final_turn: civ_manager.turn_number,
+        scores: compute_scores(&city_query, &civ_manager),
+    });
+}
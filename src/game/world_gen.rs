@@ -2,9 +2,11 @@ use bevy::prelude::*;
 use super::hex::HexCoord;
 use noise::{NoiseFn, Perlin, RidgedMulti};
 use std::collections::HashMap;
-use rand::Rng;
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct WorldGenConfig {
     // Continental Configuration
     pub continent_count: usize,           // 1-8 major landmasses
@@ -118,6 +120,57 @@ impl WorldGenConfig {
     }
 }
 
+/// The world shapes a new game can be set up with
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WorldType {
+    #[default]
+    Continents,
+    Pangaea,
+    Archipelago,
+    Fragmented,
+    DualSupercontinents,
+    Mediterranean,
+}
+
+impl WorldType {
+    pub const ALL: [WorldType; 6] = [
+        WorldType::Continents,
+        WorldType::Pangaea,
+        WorldType::Archipelago,
+        WorldType::Fragmented,
+        WorldType::DualSupercontinents,
+        WorldType::Mediterranean,
+    ];
+    
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            WorldType::Continents => "Continents",
+            WorldType::Pangaea => "Pangaea",
+            WorldType::Archipelago => "Archipelago",
+            WorldType::Fragmented => "Fragmented",
+            WorldType::DualSupercontinents => "Twin Continents",
+            WorldType::Mediterranean => "Mediterranean",
+        }
+    }
+    
+    // Case-insensitive lookup by name, for the command line; spaces are optional
+    pub fn from_name(name: &str) -> Option<WorldType> {
+        let wanted = name.replace(' ', "");
+        Self::ALL.into_iter().find(|world_type| world_type.get_name().replace(' ', "").eq_ignore_ascii_case(&wanted))
+    }
+    
+    pub fn config(&self) -> WorldGenConfig {
+        match self {
+            WorldType::Continents => WorldGenConfig::default(),
+            WorldType::Pangaea => WorldGenConfig::pangaea(),
+            WorldType::Archipelago => WorldGenConfig::archipelago_world(),
+            WorldType::Fragmented => WorldGenConfig::fragmented_continents(),
+            WorldType::DualSupercontinents => WorldGenConfig::dual_supercontinents(),
+            WorldType::Mediterranean => WorldGenConfig::mediterranean_world(),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct WorldTile {
     pub hex_coord: HexCoord,
@@ -343,14 +396,15 @@ pub struct WorldGenerator {
     pub config: WorldGenConfig,
     pub flow_directions: HashMap<HexCoord, (usize, HexCoord)>, // (direction, target)
     pub flow_accumulation: HashMap<HexCoord, f32>, // accumulated water flow
+    pub seed: u64, // The same seed and config generate the same world
 }
 
 impl WorldGenerator {
     pub fn new(map_radius: i32) -> Self {
-        Self::with_config(map_radius, WorldGenConfig::default())
+        Self::with_seed(map_radius, WorldGenConfig::default(), rand::random())
     }
     
-    pub fn with_config(map_radius: i32, config: WorldGenConfig) -> Self {
+    pub fn with_seed(map_radius: i32, config: WorldGenConfig, seed: u64) -> Self {
         Self {
             map_radius,
             tiles: HashMap::new(),
@@ -358,8 +412,15 @@ impl WorldGenerator {
             config,
             flow_directions: HashMap::new(),
             flow_accumulation: HashMap::new(),
+            seed,
         }
     }
+    
+    // Random numbers for one step of generation, drawn from the world seed so each step
+    // gets the same ones whatever the steps before it used
+    fn stage_rng(&self, stage: u64) -> StdRng {
+        StdRng::seed_from_u64(self.seed.wrapping_add(stage))
+    }
 
     pub fn generate(&mut self) -> Vec<WorldTile> {
         println!("=== REALISTIC WORLD GENERATION ===");
@@ -386,9 +447,11 @@ impl WorldGenerator {
         // Phase 4: Ecological Systems
         println!("Phase 4: Biome assignment...");
         self.assign_biomes();
+        let hydrology_started = std::time::Instant::now();
         self.generate_rivers();
         self.refine_river_network(); // Add more rivers in appropriate biomes
         self.place_lakes(); // After biomes are assigned for better threshold calculation
+        println!("Rivers and lakes took {:.0} ms", hydrology_started.elapsed().as_secs_f32() * 1000.0);
         self.calculate_soil_fertility();
         
         // Debug climate ranges
@@ -403,17 +466,28 @@ impl WorldGenerator {
         
         // Phase 5: Resource Distribution
         println!("Phase 5: Resource placement...");
+        let resources_started = std::time::Instant::now();
         self.place_geological_resources();
         self.place_biological_resources();
+        println!("Resource placement took {:.0} ms", resources_started.elapsed().as_secs_f32() * 1000.0);
         
         println!("World generation complete! {} tiles created", self.tiles.len());
         
-        self.tiles.values().cloned().collect()
+        self.sorted_coords().iter().map(|coord| self.tiles[coord].clone()).collect()
+    }
+    
+    // Every tile's coordinate in a fixed order. Passes whose outcome depends on the order
+    // they visit tiles in go through this rather than the map, so a seed always gives the
+    // same rivers and lakes.
+    fn sorted_coords(&self) -> Vec<HexCoord> {
+        let mut coords: Vec<HexCoord> = self.tiles.keys().copied().collect();
+        coords.sort_unstable_by_key(|coord| (coord.q, coord.r));
+        coords
     }
 
     fn generate_tectonic_structure(&mut self) {
         // Create the basic tectonic structure with continental and oceanic plates
-        let mut rng = rand::rng();
+        let mut rng = self.stage_rng(1);
         let plate_noise = RidgedMulti::<Perlin>::new(rng.random());
         
         // Generate continental centers based on configuration
@@ -496,7 +570,7 @@ impl WorldGenerator {
     }
     
     fn generate_continent_centers(&self) -> Vec<HexCoord> {
-        let mut rng = rand::rng();
+        let mut rng = self.stage_rng(2);
         let mut centers = Vec::new();
         
         // Determine spacing based on separation config
@@ -574,7 +648,7 @@ impl WorldGenerator {
     }
 
     fn generate_base_elevation(&mut self) {
-        let mut rng = rand::rng();
+        let mut rng = self.stage_rng(3);
         let mountain_noise = RidgedMulti::<Perlin>::new(rng.random());
         let hill_noise = Perlin::new(rng.random());
         let detail_noise = Perlin::new(rng.random());
@@ -681,7 +755,7 @@ impl WorldGenerator {
         // Apply variance if configured
         let variance = self.config.sea_level_variance;
         if variance > 0.0 {
-            let mut rng = rand::rng();
+            let mut rng = self.stage_rng(4);
             let adjustment = rng.random_range(-variance..variance);
             self.sea_level = base_sea_level + adjustment;
         } else {
@@ -763,8 +837,10 @@ impl WorldGenerator {
 
     fn find_river_sources(&self) -> Vec<HexCoord> {
         let mut sources = Vec::new();
+        let coords = self.sorted_coords();
         
-        for (coord, tile) in &self.tiles {
+        for coord in &coords {
+            let tile = &self.tiles[coord];
             // Only consider land tiles
             if tile.elevation <= self.sea_level {
                 continue;
@@ -848,7 +924,8 @@ impl WorldGenerator {
         if sources.len() < 10 {
             let mut backup_sources = Vec::new();
             
-            for (coord, tile) in &self.tiles {
+            for coord in &coords {
+                let tile = &self.tiles[coord];
                 if tile.elevation <= self.sea_level {
                     continue;
                 }
@@ -959,25 +1036,17 @@ impl WorldGenerator {
                 
                 // Much more lenient flow requirement
                 if avg_flow >= 1.0 { // Reduced from 2.0
-                    river_networks.push(river_path);
+                    river_networks.push((total_path_flow, river_path));
                 }
             }
         }
         
         // Sort networks by total flow (create major rivers first)
-        river_networks.sort_by(|a, b| {
-            let flow_a: f32 = a.iter()
-                .map(|coord| self.flow_accumulation.get(coord).copied().unwrap_or(1.0))
-                .sum();
-            let flow_b: f32 = b.iter()
-                .map(|coord| self.flow_accumulation.get(coord).copied().unwrap_or(1.0))
-                .sum();
-            flow_b.partial_cmp(&flow_a).unwrap()
-        });
+        river_networks.sort_by(|(flow_a, _), (flow_b, _)| flow_b.total_cmp(flow_a));
         
         // Create rivers along these networks
         let mut total_river_tiles = 0;
-        for (network_idx, river_path) in river_networks.iter().enumerate() {
+        for (network_idx, (_, river_path)) in river_networks.iter().enumerate() {
             // Allow more river networks
             if network_idx >= 100 { // Increased from 50
                 break;
@@ -1038,44 +1107,54 @@ impl WorldGenerator {
     }
 
     fn calculate_flow_accumulation(&mut self) {
-        // Calculate how much water flows through each tile
-        let coords: Vec<HexCoord> = self.tiles.keys().cloned().collect();
-        let mut flow_accumulation = HashMap::new();
+        // Calculate how much water flows through each tile. Tiles are worked on by their
+        // place in coordinate order, so the sums come out the same on every run.
+        let coords = self.sorted_coords();
+        let index: HashMap<HexCoord, usize> = coords.iter().enumerate().map(|(i, coord)| (*coord, i)).collect();
+        let mut flow = vec![0.0f32; coords.len()];
+        let mut has_flow = vec![false; coords.len()];
+        let mut elevations = vec![0.0f32; coords.len()];
+        let mut drainage_bonuses = vec![0.0f32; coords.len()];
+        let mut targets: Vec<Option<usize>> = vec![None; coords.len()];
+        let mut land = Vec::new();
         
         // Initialize: every land tile contributes based on precipitation
-        for coord in &coords {
+        for (i, coord) in coords.iter().enumerate() {
             let tile = &self.tiles[coord];
             if tile.elevation > self.sea_level {
                 // Base contribution is 1.0, modified by precipitation
                 let base_contribution = 1.0;
                 let precip_bonus = tile.precipitation * 2.0; // Precipitation adds significant flow
-                flow_accumulation.insert(*coord, base_contribution + precip_bonus);
+                flow[i] = base_contribution + precip_bonus;
+                has_flow[i] = true;
+                elevations[i] = tile.elevation;
+                drainage_bonuses[i] = tile.drainage * 0.3; // Bonus for geological drainage
+                targets[i] = self.flow_directions.get(coord).map(|(_, target)| index[target]);
+                land.push(i);
             }
         }
         
-        // Sort tiles by elevation (highest first) for proper flow calculation
-        let mut sorted_coords = coords.clone();
-        sorted_coords.retain(|coord| self.tiles[coord].elevation > self.sea_level);
-        sorted_coords.sort_by(|a, b| {
-            self.tiles[b].elevation.partial_cmp(&self.tiles[a].elevation).unwrap()
-        });
+        // Sort tiles by elevation (highest first) for proper flow calculation; the sort is
+        // stable, so level tiles keep their coordinate order
+        land.sort_by(|a, b| elevations[*b].total_cmp(&elevations[*a]));
         
         // Flow water from high to low elevation
-        for coord in sorted_coords {
-            if let Some((_, target)) = self.flow_directions.get(&coord) {
-                let source_flow = flow_accumulation.get(&coord).copied().unwrap_or(0.0);
-                
+        for i in land {
+            if let Some(target) = targets[i] {
                 // Add this tile's flow to the target tile
-                *flow_accumulation.entry(*target).or_insert(0.0) += source_flow;
-                
-                // Bonus for geological drainage
-                let tile = &self.tiles[&coord];
-                let drainage_bonus = tile.drainage * 0.3;
-                *flow_accumulation.entry(*target).or_insert(0.0) += drainage_bonus;
+                flow[target] += flow[i];
+                flow[target] += drainage_bonuses[i];
+                has_flow[target] = true;
             }
         }
         
-        self.flow_accumulation = flow_accumulation;
+        self.flow_accumulation = coords
+            .iter()
+            .zip(flow)
+            .zip(has_flow)
+            .filter(|(_, has_flow)| *has_flow)
+            .map(|((coord, flow), _)| (*coord, flow))
+            .collect();
         
         let max_flow = self.flow_accumulation.values().fold(0.0f32, |a, &b| a.max(b));
         let tiles_with_flow = self.flow_accumulation.len();
@@ -1144,7 +1223,7 @@ impl WorldGenerator {
     }
 
     fn simulate_temperature(&mut self) {
-        let mut rng = rand::rng();
+        let mut rng = self.stage_rng(5);
         let temp_noise = Perlin::new(rng.random());
         
         let coords: Vec<HexCoord> = self.tiles.keys().cloned().collect();
@@ -1181,7 +1260,7 @@ impl WorldGenerator {
     }
 
     fn simulate_precipitation(&mut self) {
-        let mut rng = rand::rng();
+        let mut rng = self.stage_rng(6);
         let precip_noise = Perlin::new(rng.random());
         
         let coords: Vec<HexCoord> = self.tiles.keys().cloned().collect();
@@ -1362,9 +1441,8 @@ impl WorldGenerator {
         let mut improvements = 0;
         
         // Add tributaries to major rivers
-        let main_river_coords: Vec<HexCoord> = self.tiles.iter()
-            .filter(|(_, tile)| tile.has_river && tile.river_flow > 0.5)
-            .map(|(coord, _)| *coord)
+        let main_river_coords: Vec<HexCoord> = self.sorted_coords().into_iter()
+            .filter(|coord| self.tiles[coord].has_river && self.tiles[coord].river_flow > 0.5)
             .collect();
         
         for river_coord in main_river_coords {
@@ -1406,8 +1484,9 @@ impl WorldGenerator {
     }
 
     fn place_lakes(&mut self) {
-        // Find natural depressions for lakes based on flow accumulation
-        let coords: Vec<HexCoord> = self.tiles.keys().cloned().collect();
+        // Find natural depressions for lakes based on flow accumulation, in a fixed order so
+        // equally good spots are settled the same way every time
+        let coords = self.sorted_coords();
         let mut lake_candidates = Vec::new();
         
         for coord in coords {
@@ -1505,28 +1584,16 @@ impl WorldGenerator {
     }
 
     fn place_geological_resources(&mut self) {
-        let coords: Vec<HexCoord> = self.tiles.keys().cloned().collect();
-        
-        for coord in coords {
-            let tile = &self.tiles[&coord];
-            let resource = self.generate_biome_resource(tile.hex_coord, tile.biome);
-            
-            // Update the tile
-            self.tiles.get_mut(&coord).unwrap().resource = resource;
+        // One noise field for the whole map rather than one per tile
+        let resource_noise = ResourceNoise::new();
+        for tile in self.tiles.values_mut() {
+            tile.resource = Self::generate_biome_resource(&resource_noise, tile.hex_coord, tile.biome);
         }
     }
 
-    fn generate_biome_resource(&self, hex_coord: HexCoord, biome: u8) -> u8 {
-        use noise::{NoiseFn, Perlin};
-        let resource_noise = Perlin::new(789);
-        
+    fn generate_biome_resource(resource_noise: &ResourceNoise, hex_coord: HexCoord, biome: u8) -> u8 {
         // Lower chance of resources (about 15% of tiles)
-        let resource_chance = resource_noise.get([
-            hex_coord.q as f64 * 0.3,
-            hex_coord.r as f64 * 0.3,
-        ]) as f32;
-        
-        if resource_chance > 0.7 {
+        if resource_noise.chance(hex_coord) > 0.7 {
             use super::resources::ResourceType;
             let possible_resources = match BiomeType::from_u8(biome) {
                 BiomeType::Ocean | BiomeType::Lake | BiomeType::River => {
@@ -1600,6 +1667,24 @@ impl WorldGenerator {
     }
 }
 
+// Where resources turn up. Seeded with a constant rather than the world seed, which keeps
+// every seed's resources where they've always been.
+struct ResourceNoise {
+    noise: Perlin,
+}
+
+impl ResourceNoise {
+    const SEED: u32 = 789;
+    
+    fn new() -> Self {
+        Self { noise: Perlin::new(Self::SEED) }
+    }
+    
+    fn chance(&self, hex_coord: HexCoord) -> f32 {
+        self.noise.get([hex_coord.q as f64 * 0.3, hex_coord.r as f64 * 0.3]) as f32
+    }
+}
+
 // Helper trait for direction finding
 trait HexDirection {
     fn direction_to(&self, other: HexCoord) -> Option<usize>;
@@ -1610,4 +1695,40 @@ impl HexDirection for HexCoord {
         let directions = self.neighbors();
         directions.iter().position(|&n| n == other)
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::resources::ResourceType;
+    
+    // The shared noise gives every tile the chance a fresh Perlin::new(789) per tile did
+    #[test]
+    fn resource_noise_matches_a_fresh_generator_per_tile() {
+        let noise = ResourceNoise::new();
+        for q in -20..=20 {
+            for r in -20..=20 {
+                let old = Perlin::new(789).get([q as f64 * 0.3, r as f64 * 0.3]) as f32;
+                assert_eq!(noise.chance(HexCoord::new(q, r)), old, "({}, {})", q, r);
+            }
+        }
+    }
+    
+    // Resources found at these hexes before the noise was shared, and still found there
+    #[test]
+    fn resources_stay_where_they_were() {
+        let noise = ResourceNoise::new();
+        let grassland = BiomeType::TemperateGrassland as u8;
+        for (q, r, biome, expected) in [
+            (0, 0, grassland, ResourceType::None),
+            (3, 3, grassland, ResourceType::None),
+            (1, -1, grassland, ResourceType::Horses),
+            (2, 8, grassland, ResourceType::Cattle),
+            (-12, -1, grassland, ResourceType::Wheat),
+            (5, -5, BiomeType::HotDesert as u8, ResourceType::Gems),
+            (2, 8, BiomeType::Ocean as u8, ResourceType::Fish),
+        ] {
+            let resource = WorldGenerator::generate_biome_resource(&noise, HexCoord::new(q, r), biome);
+            assert_eq!(resource, expected as u8, "({}, {})", q, r);
+        }
+    }
+}
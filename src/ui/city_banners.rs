@@ -0,0 +1,224 @@
+use bevy::prelude::*;
+use crate::game::cities::{CITY_MAX_HIT_POINTS, City, CitySelection, ProductionItem};
+use crate::game::civilization::CivilizationManager;
+use crate::game::map::HEX_SIZE;
+use crate::game::units::UnitSelection;
+use super::tooltips::TooltipSource;
+
+const BANNER_WIDTH: f32 = 120.0;
+const BANNER_HEIGHT: f32 = 50.0;
+// Banners are hidden once the camera is zoomed out further than this
+const BANNER_MAX_CAMERA_SCALE: f32 = 2.0;
+
+const FOOD_BAR_COLOR: Color = Color::srgb(0.3, 0.85, 0.3);
+const FOOD_BAR_BACKGROUND: Color = Color::srgba(0.0, 0.0, 0.0, 0.6);
+const HP_BAR_COLOR: Color = Color::srgb(0.9, 0.25, 0.2);
+
+/// Screen-space banner that follows a city around the map. Holds its own parts so
+/// they can be updated without walking the hierarchy.
+#[derive(Component)]
+pub struct CityBanner {
+    pub city_entity: Entity,
+    header: Entity,
+    food_fill: Entity,
+    hp_bar: Entity, // Only shown while the city is damaged
+    hp_fill: Entity,
+    production: Entity,
+}
+
+// Spawns a banner for every new city; the contents are filled in by update_city_banners
+pub fn spawn_city_banners(
+    mut commands: Commands,
+    city_query: Query<Entity, Added<City>>,
+) {
+    for city_entity in city_query.iter() {
+        let text = |font_size: f32| {
+            (
+                Text::new(""),
+                TextFont {
+                    font_size,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            )
+        };
+        
+        let header = commands.spawn(text(12.0)).id();
+        let food_fill = commands.spawn((
+            BackgroundColor(FOOD_BAR_COLOR),
+            Node {
+                width: Val::Percent(0.0),
+                height: Val::Percent(100.0),
+                ..default()
+            },
+        )).id();
+        let food_bar = commands.spawn((
+            BackgroundColor(FOOD_BAR_BACKGROUND),
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Px(4.0),
+                ..default()
+            },
+        )).add_child(food_fill).id();
+        let hp_fill = commands.spawn((
+            BackgroundColor(HP_BAR_COLOR),
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                ..default()
+            },
+        )).id();
+        let hp_bar = commands.spawn((
+            BackgroundColor(FOOD_BAR_BACKGROUND),
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Px(4.0),
+                display: Display::None,
+                ..default()
+            },
+        )).add_child(hp_fill).id();
+        let production = commands.spawn(text(10.0)).id();
+        
+        commands.spawn((
+            CityBanner { city_entity, header, food_fill, hp_bar, hp_fill, production },
+            Button,
+            TooltipSource::City(city_entity),
+            BackgroundColor(Color::srgba(0.2, 0.2, 0.2, 0.8)),
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Px(BANNER_WIDTH),
+                height: Val::Px(BANNER_HEIGHT),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                padding: UiRect::axes(Val::Px(4.0), Val::Px(2.0)),
+                row_gap: Val::Px(2.0),
+                ..default()
+            },
+            // Panels draw on top of banners
+            GlobalZIndex(-1),
+            Visibility::Hidden,
+        )).add_children(&[header, food_bar, hp_bar, production]);
+    }
+}
+
+// Refreshes banner contents when their city changes
+pub fn update_city_banners(
+    city_query: Query<Ref<City>>,
+    mut banner_query: Query<(Ref<CityBanner>, &mut BackgroundColor)>,
+    mut text_query: Query<&mut Text>,
+    mut node_query: Query<&mut Node, Without<CityBanner>>,
+    civ_manager: Res<CivilizationManager>,
+) {
+    for (banner, mut background) in banner_query.iter_mut() {
+        let Ok(city) = city_query.get(banner.city_entity) else { continue };
+        if !city.is_changed() && !banner.is_added() {
+            continue;
+        }
+        
+        let civ_color = civ_manager.get_civilization(city.civilization_id)
+            .map_or(Color::srgb(0.5, 0.5, 0.5), |civ| civ.color);
+        background.0 = civ_color.with_alpha(0.8);
+        
+        let star = if city.is_capital { "★ " } else { "" };
+        let blockade = if city.is_blockaded { " ⚓" } else { "" };
+        if let Ok(mut header) = text_query.get_mut(banner.header) {
+            **header = format!("{}{} {}{}", star, city.population, city.name, blockade);
+        }
+        
+        let growth = if city.food_needed_for_growth > 0.0 {
+            (city.food_stored / city.food_needed_for_growth).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        if let Ok(mut fill) = node_query.get_mut(banner.food_fill) {
+            fill.width = Val::Percent(growth * 100.0);
+        }
+        
+        let damaged = city.hit_points < CITY_MAX_HIT_POINTS;
+        if let Ok(mut bar) = node_query.get_mut(banner.hp_bar) {
+            bar.display = if damaged { Display::Flex } else { Display::None };
+        }
+        if let Ok(mut fill) = node_query.get_mut(banner.hp_fill) {
+            fill.width = Val::Percent(city.hit_points as f32 / CITY_MAX_HIT_POINTS as f32 * 100.0);
+        }
+        
+        if let Ok(mut production) = text_query.get_mut(banner.production) {
+            **production = production_label(&city);
+        }
+    }
+}
+
+// What the city is busy with, and the turns left on it
+pub(super) fn production_label(city: &City) -> String {
+    match &city.current_production {
+        _ if city.is_razing => "Razing".to_string(),
+        _ if city.resistance_turns > 0 => format!("Resistance ({})", city.resistance_turns),
+        Some(ProductionItem::Wealth) => "Wealth".to_string(),
+        Some(item) => {
+            let remaining = city.required_production(item) - city.production_progress;
+            match city.turns_for(item, remaining) {
+                Some(turns) => format!("{} ({})", item.get_name(), turns),
+                None => format!("{} (-)", item.get_name()),
+            }
+        }
+        None => "Idle".to_string(),
+    }
+}
+
+// Keeps banners over their cities on screen, hides them when zoomed far out,
+// and removes banners whose city is gone
+pub fn position_city_banners(
+    mut commands: Commands,
+    camera_query: Query<(&Camera, &GlobalTransform, &Transform)>,
+    city_query: Query<&City>,
+    mut banner_query: Query<(Entity, &CityBanner, &mut Node, &mut Visibility)>,
+    ui_scale: Res<UiScale>,
+) {
+    let Ok((camera, camera_global, camera_transform)) = camera_query.single() else { return };
+    let zoomed_out = camera_transform.scale.x > BANNER_MAX_CAMERA_SCALE;
+    
+    for (banner_entity, banner, mut node, mut visibility) in banner_query.iter_mut() {
+        let Ok(city) = city_query.get(banner.city_entity) else {
+            commands.entity(banner_entity).despawn();
+            continue;
+        };
+        
+        let anchor = city.hex_coord.to_world_pos(HEX_SIZE) + Vec2::new(0.0, HEX_SIZE * 0.8);
+        // Node offsets are in interface pixels, which the interface scale enlarges
+        let screen_pos = camera.world_to_viewport(camera_global, anchor.extend(0.0)).ok().map(|pos| pos / ui_scale.0);
+        
+        match screen_pos {
+            Some(screen_pos) if !zoomed_out => {
+                node.left = Val::Px(screen_pos.x - BANNER_WIDTH / 2.0);
+                node.top = Val::Px(screen_pos.y - BANNER_HEIGHT);
+                visibility.set_if_neq(Visibility::Visible);
+            }
+            _ => {
+                visibility.set_if_neq(Visibility::Hidden);
+            }
+        }
+    }
+}
+
+// Clicking one of the player's banners opens that city's panel
+pub fn city_banner_click_system(
+    banner_query: Query<(&Interaction, &CityBanner), Changed<Interaction>>,
+    city_query: Query<&City>,
+    civ_manager: Res<CivilizationManager>,
+    mut city_selection: ResMut<CitySelection>,
+    mut unit_selection: ResMut<UnitSelection>,
+) {
+    let Some(player_civ) = civ_manager.get_player_civilization() else { return };
+    
+    for (interaction, banner) in banner_query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let is_own = city_query.get(banner.city_entity).is_ok_and(|city| city.civilization_id == player_civ.id);
+        if is_own {
+            city_selection.selected_city = Some(banner.city_entity);
+            unit_selection.selected_unit = None;
+        }
+    }
+}
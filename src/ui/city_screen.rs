@@ -0,0 +1,277 @@
+use bevy::prelude::*;
+use std::collections::HashSet;
+use crate::game::cities::{City, CitySelection, TerritoryMap};
+use crate::game::camera_zoom::CameraTween;
+use crate::game::civilization::CivilizationManager;
+use crate::game::fonts::GameFonts;
+use crate::game::hex::HexCoord;
+use crate::game::input::{ClickIntent, InputMode};
+use crate::game::input_map::{Action, InputMap};
+use crate::game::map::{TileLookup, HEX_SIZE};
+use crate::game::notifications::{GameNotification, NotificationCategory};
+use crate::game::units::UnitSelection;
+
+const BORDER_COLOR: Color = Color::srgb(0.95, 0.95, 0.95);
+const WORKED_COLOR: Color = Color::srgb(1.0, 0.9, 0.2);
+const GHOST_COLOR: Color = Color::srgba(0.6, 0.85, 1.0, 0.45);
+const WORKED_LABEL_COLOR: Color = Color::srgb(1.0, 1.0, 0.6);
+const OPEN_LABEL_COLOR: Color = Color::srgba(0.8, 0.8, 0.8, 0.7);
+// How far in from a tile's edge the highlights are drawn, as a share of its size
+const WORKED_INSET: f32 = 0.85;
+const GHOST_INSET: f32 = 0.65;
+
+/// Line across the top of the screen while the city screen is open
+#[derive(Component)]
+pub struct CityScreenBanner;
+
+/// Yield numbers drawn over a tile of the managed city
+#[derive(Component)]
+pub struct CityTileLabel;
+
+pub fn setup_city_screen(mut commands: Commands) {
+    // City screen banner (top center, below the turn info)
+    commands.spawn((
+        CityScreenBanner,
+        Text::new(""),
+        TextLayout::new_with_justify(JustifyText::Center),
+        TextFont {
+            font_size: 13.0,
+            ..default()
+        },
+        TextColor(Color::srgb(1.0, 1.0, 0.8)),
+        BackgroundColor(Color::srgba(0.05, 0.05, 0.1, 0.85)),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(90.0),
+            left: Val::Px(0.0),
+            right: Val::Px(0.0),
+            margin: UiRect::horizontal(Val::Auto),
+            width: Val::Px(520.0),
+            max_width: Val::Percent(96.0),
+            padding: UiRect::all(Val::Px(4.0)),
+            display: Display::None,
+            ..default()
+        },
+    ));
+}
+
+// Whether the player may manage this city now
+fn manageable(city: &City, civ_manager: &CivilizationManager) -> bool {
+    civ_manager.get_player_civilization().is_some_and(|civ| civ.id == city.civilization_id) && !city.is_occupied()
+}
+
+// Runs in PreUpdate so the key that opens the city screen doesn't also end the turn, and
+// the Escape that closes it doesn't also pause. Choosing another of the player's cities
+// moves the screen there; losing the city or the selection closes it.
+#[allow(clippy::too_many_arguments)]
+pub fn city_screen_key_system(
+    mut keyboard: ResMut<ButtonInput<KeyCode>>,
+    input_map: Res<InputMap>,
+    mut input_mode: ResMut<InputMode>,
+    city_selection: Res<CitySelection>,
+    city_query: Query<&City>,
+    civ_manager: Res<CivilizationManager>,
+    mut unit_selection: ResMut<UnitSelection>,
+    mut camera_tween: ResMut<CameraTween>,
+) {
+    let selected = city_selection
+        .selected_city
+        .and_then(|entity| city_query.get(entity).ok().map(|city| (entity, city)))
+        .filter(|(_, city)| manageable(city, &civ_manager));
+    
+    if let InputMode::CityManagement(managed) = *input_mode {
+        let close_pressed = input_map.just_pressed(Action::CityScreen, &keyboard) || keyboard.just_pressed(KeyCode::Escape);
+        if close_pressed {
+            input_map.consume(Action::CityScreen, &mut keyboard);
+            keyboard.clear_just_pressed(KeyCode::Escape);
+        }
+        match selected {
+            _ if close_pressed => *input_mode = InputMode::Normal,
+            Some((entity, city)) if entity != managed => {
+                *input_mode = InputMode::CityManagement(entity);
+                camera_tween.center_on(city.hex_coord);
+            }
+            Some(_) => {}
+            None => *input_mode = InputMode::Normal,
+        }
+        return;
+    }
+    
+    if *input_mode != InputMode::Normal || !input_map.just_pressed(Action::CityScreen, &keyboard) {
+        return;
+    }
+    let Some((entity, city)) = selected else { return };
+    input_map.consume(Action::CityScreen, &mut keyboard);
+    *input_mode = InputMode::CityManagement(entity);
+    unit_selection.selected_unit = None;
+    camera_tween.center_on(city.hex_coord);
+}
+
+// A click on a worked tile frees its citizen; a click on an open tile of the city's own
+// territory puts an idle citizen there
+pub fn city_tile_click_system(
+    mut click_intent: ResMut<ClickIntent>,
+    input_mode: Res<InputMode>,
+    mut city_query: Query<(Entity, &mut City)>,
+    territory: Res<TerritoryMap>,
+    mut notifications: EventWriter<GameNotification>,
+) {
+    let InputMode::CityManagement(managed) = *input_mode else { return };
+    let Some(clicked) = click_intent.take_tile() else { return };
+    let warning = |text: String| GameNotification::new(NotificationCategory::Warning, text).at(clicked);
+    
+    let worked_elsewhere = city_query
+        .iter()
+        .find(|(entity, city)| *entity != managed && city.worked_tiles.contains(&clicked))
+        .map(|(_, city)| city.name.clone());
+    let Ok((_, mut city)) = city_query.get_mut(managed) else { return };
+    
+    if clicked == city.hex_coord {
+        notifications.write(warning(format!("The center of {} is always worked.", city.name)));
+    } else if city.worked_tiles.contains(&clicked) {
+        city.unwork_tile(clicked);
+    } else if territory.owner(clicked).is_none_or(|owner| owner.city != managed) {
+        notifications.write(warning(format!("That tile isn't part of {}'s territory.", city.name)));
+    } else if let Some(other) = worked_elsewhere {
+        notifications.write(warning(format!("{} already works that tile.", other)));
+    } else if !city.work_tile(clicked) {
+        notifications.write(warning(format!("Every citizen of {} is busy. Free a worked tile first.", city.name)));
+    }
+}
+
+// A whole number where the yield is one, otherwise one decimal
+fn amount(value: f32) -> String {
+    if value.fract().abs() < 0.05 { format!("{:.0}", value) } else { format!("{:.1}", value) }
+}
+
+// "2F 1P 1G", leaving out what the tile doesn't yield
+fn yield_label((food, production, science, gold): (f32, f32, f32, f32)) -> String {
+    [(food, "F"), (production, "P"), (science, "S"), (gold, "G")]
+        .into_iter()
+        .filter(|(value, _)| *value > 0.0)
+        .map(|(value, letter)| format!("{}{}", amount(value), letter))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// Keeps the banner and the tile yields in step with the managed city, rebuilding the
+// labels only when a tile's yields or worked state change
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn update_city_screen(
+    mut commands: Commands,
+    input_mode: Res<InputMode>,
+    input_map: Res<InputMap>,
+    city_query: Query<&City>,
+    tiles: TileLookup,
+    civ_manager: Res<CivilizationManager>,
+    fonts: Res<GameFonts>,
+    mut banner_query: Query<(&mut Node, &mut Text), With<CityScreenBanner>>,
+    label_query: Query<Entity, With<CityTileLabel>>,
+    mut shown_labels: Local<Vec<(HexCoord, String, bool)>>,
+) {
+    let managed = match *input_mode {
+        InputMode::CityManagement(entity) => city_query.get(entity).ok(),
+        _ => None,
+    };
+    
+    if let Ok((mut node, mut text)) = banner_query.single_mut() {
+        let display = if managed.is_some() { Display::Flex } else { Display::None };
+        if node.display != display {
+            node.display = display;
+        }
+        if let Some(city) = managed {
+            let on_tiles = city.worked_tiles.len().saturating_sub(1);
+            let banner = format!(
+                "=== {} (Pop {}) ===\n{} on tiles, {} specialists, {} idle. Focus: {}\n\
+                Click a worked tile to free its citizen, an open tile to put one there.\n\
+                Blue outlines are where the governor would work. {} / Esc - close",
+                city.name,
+                city.population,
+                on_tiles,
+                city.specialists.len(),
+                city.idle_citizens,
+                city.focus.get_name(),
+                input_map.label(Action::CityScreen)
+            );
+            if **text != banner {
+                **text = banner;
+            }
+        }
+    }
+    
+    let labels: Vec<(HexCoord, String, bool)> = managed.map_or_else(Vec::new, |city| {
+        city.territory_tiles
+            .iter()
+            .filter_map(|coord| tiles.get(*coord).map(|tile| (*coord, tile)))
+            .map(|(coord, tile)| {
+                let worked = city.worked_tiles.contains(&coord);
+                (coord, yield_label(city.tile_yields(coord, tile, &civ_manager)), worked)
+            })
+            .collect()
+    });
+    if *shown_labels == labels {
+        return;
+    }
+    for entity in label_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    for (coord, label, worked) in &labels {
+        let world_pos = coord.to_world_pos(HEX_SIZE);
+        commands.spawn((
+            CityTileLabel,
+            Text2d::new(label.clone()),
+            TextColor(if *worked { WORKED_LABEL_COLOR } else { OPEN_LABEL_COLOR }),
+            fonts.symbol(if *worked { 12.0 } else { 10.0 }),
+            Transform::from_translation(Vec3::new(world_pos.x, world_pos.y - HEX_SIZE * 0.45, 2.6)),
+        ));
+    }
+    *shown_labels = labels;
+}
+
+// Corners of a tile, shrunk toward its centre by `inset`
+fn hex_corners(coord: HexCoord, inset: f32) -> [Vec2; 7] {
+    let center = coord.to_world_pos(HEX_SIZE);
+    let mut corners = [Vec2::ZERO; 7];
+    for (index, corner) in corners.iter_mut().enumerate() {
+        let angle = (60.0 * index as f32 + 30.0).to_radians();
+        *corner = center + Vec2::new(angle.cos(), angle.sin()) * HEX_SIZE * inset;
+    }
+    corners
+}
+
+// Outlines the managed city's territory, highlights the tiles it works and marks where
+// the governor would put its citizens under the current focus
+pub fn draw_city_screen(
+    mut gizmos: Gizmos,
+    input_mode: Res<InputMode>,
+    city_query: Query<(Entity, &City)>,
+    tiles: TileLookup,
+    territory: Res<TerritoryMap>,
+) {
+    let InputMode::CityManagement(managed) = *input_mode else { return };
+    let Ok((_, city)) = city_query.get(managed) else { return };
+    
+    // Edges between a tile the territory map gives the city and a neighbor it doesn't
+    let owned = |coord: HexCoord| territory.owner(coord).is_some_and(|owner| owner.city == managed);
+    for coord in city.territory_tiles.iter().filter(|coord| owned(**coord)) {
+        for neighbor in coord.neighbors().into_iter().filter(|neighbor| !owned(*neighbor)) {
+            let (start, end) = coord.edge_toward(neighbor, HEX_SIZE);
+            gizmos.line_2d(start, end, BORDER_COLOR);
+        }
+    }
+    
+    for coord in city.worked_tiles.iter().filter(|coord| **coord != city.hex_coord) {
+        gizmos.linestrip_2d(hex_corners(*coord, WORKED_INSET), WORKED_COLOR);
+    }
+    
+    let taken: HashSet<HexCoord> = city_query
+        .iter()
+        .filter(|(entity, _)| *entity != managed)
+        .flat_map(|(_, other)| other.worked_tiles.iter().copied())
+        .collect();
+    for coord in city.governor_tiles(managed, &tiles, &territory, &taken) {
+        if coord != city.hex_coord {
+            gizmos.linestrip_2d(hex_corners(coord, GHOST_INSET), GHOST_COLOR);
+        }
+    }
+}
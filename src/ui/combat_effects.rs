@@ -0,0 +1,144 @@
+use bevy::prelude::*;
+use bevy::color::Mix;
+use crate::game::civilization::CivilizationManager;
+use crate::game::combat::{UnitDamagedEvent, fight_shown_in_full};
+use crate::game::game_initialization::GameSettings;
+use crate::game::units::UnitMarker;
+use super::game_panels::UIState;
+
+const POPUP_COLOR: Color = Color::srgb(1.0, 0.35, 0.3);
+const FLASH_COLOR: Color = Color::srgb(1.0, 0.1, 0.1);
+const POPUP_SECONDS: f32 = 1.0;
+const FLASH_SECONDS: f32 = 0.35;
+const DEATH_FADE_SECONDS: f32 = 0.6;
+// How far a damage number drifts up over its lifetime, in world units
+const POPUP_RISE: f32 = 30.0;
+
+/// A floating "-34" above a unit that was just hit
+#[derive(Component)]
+pub struct DamagePopup {
+    pub timer: Timer,
+}
+
+/// Tints a unit marker red, easing back to its own color
+#[derive(Component)]
+pub struct FlashEffect {
+    pub timer: Timer,
+    pub base_color: Color,
+}
+
+/// A copy of a destroyed unit's marker that shrinks and fades out in its place
+#[derive(Component)]
+pub struct DyingMarker {
+    pub timer: Timer,
+}
+
+// A unit marker's look, and its flash if it's already mid-flash
+type MarkerLook<'a> = (Entity, &'a UnitMarker, &'a Transform, &'a Text2d, &'a TextFont, &'a mut TextColor, Option<&'a mut FlashEffect>);
+
+// Damage numbers, hit flashes and death fades for every unit hurt in a fight, unless
+// they've been switched off with F6 or it's a fight between AIs played out quickly.
+// Runs before remove_orphaned_unit_markers so a dead unit's marker is still there to copy.
+pub fn spawn_combat_effects(
+    mut commands: Commands,
+    mut hits: EventReader<UnitDamagedEvent>,
+    ui_state: Res<UIState>,
+    settings: Res<GameSettings>,
+    civ_manager: Res<CivilizationManager>,
+    mut marker_query: Query<MarkerLook>,
+) {
+    for hit in hits.read() {
+        if !ui_state.show_combat_effects || !fight_shown_in_full(&settings, &civ_manager, hit.sides) {
+            continue;
+        }
+        let Some((marker_entity, _, transform, symbol, font, mut color, flash)) = marker_query
+            .iter_mut()
+            .find(|(_, marker, ..)| marker.unit == hit.unit) else { continue };
+        let position = transform.translation;
+        
+        commands.spawn((
+            DamagePopup { timer: Timer::from_seconds(POPUP_SECONDS, TimerMode::Once) },
+            Text2d::new(format!("-{}", hit.damage)),
+            TextColor(POPUP_COLOR),
+            TextFont {
+                font_size: 16.0,
+                ..default()
+            },
+            Transform::from_xyz(position.x, position.y + 14.0, 5.0),
+        ));
+        
+        if hit.destroyed {
+            // The marker itself goes with the unit; fade out a stand-in
+            commands.spawn((
+                DyingMarker { timer: Timer::from_seconds(DEATH_FADE_SECONDS, TimerMode::Once) },
+                symbol.clone(),
+                font.clone(),
+                TextColor(FLASH_COLOR),
+                *transform,
+            ));
+            continue;
+        }
+        
+        let timer = Timer::from_seconds(FLASH_SECONDS, TimerMode::Once);
+        match flash {
+            // Hit again mid-flash: start over, keeping the real color to return to
+            Some(mut flash) => flash.timer = timer,
+            None => {
+                commands.entity(marker_entity).insert(FlashEffect { timer, base_color: color.0 });
+            }
+        }
+        color.0 = FLASH_COLOR;
+    }
+}
+
+// Damage numbers drift upward and fade, then disappear
+pub fn animate_damage_popups(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut popup_query: Query<(Entity, &mut DamagePopup, &mut Transform, &mut TextColor)>,
+) {
+    for (entity, mut popup, mut transform, mut color) in popup_query.iter_mut() {
+        popup.timer.tick(time.delta());
+        if popup.timer.finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        transform.translation.y += POPUP_RISE * time.delta_secs() / POPUP_SECONDS;
+        color.0.set_alpha(1.0 - popup.timer.fraction());
+    }
+}
+
+// Flashed markers ease from red back to their civilization's color
+pub fn animate_flash_effects(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut flash_query: Query<(Entity, &mut FlashEffect, &mut TextColor)>,
+) {
+    for (entity, mut flash, mut color) in flash_query.iter_mut() {
+        flash.timer.tick(time.delta());
+        if flash.timer.finished() {
+            color.0 = flash.base_color;
+            commands.entity(entity).remove::<FlashEffect>();
+            continue;
+        }
+        color.0 = FLASH_COLOR.mix(&flash.base_color, flash.timer.fraction());
+    }
+}
+
+// Stand-ins for destroyed units shrink away to nothing
+pub fn animate_dying_markers(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut dying_query: Query<(Entity, &mut DyingMarker, &mut Transform, &mut TextColor)>,
+) {
+    for (entity, mut dying, mut transform, mut color) in dying_query.iter_mut() {
+        dying.timer.tick(time.delta());
+        if dying.timer.finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        let remaining = 1.0 - dying.timer.fraction();
+        transform.scale = Vec3::splat(remaining);
+        color.0.set_alpha(remaining);
+    }
+}
@@ -0,0 +1,126 @@
+use bevy::prelude::*;
+use crate::game::civilization::CivilizationManager;
+use crate::game::combat_log::{CombatLog, CombatLogEntry};
+use super::game_panels::UIState;
+
+const HEADER_COLOR: Color = Color::srgb(0.9, 0.9, 0.7);
+const TEXT_COLOR: Color = Color::srgb(0.8, 0.8, 0.8);
+// Entries shown at once; PageUp / PageDown move by this many
+const LOG_PAGE_SIZE: usize = 20;
+
+#[derive(Component)]
+pub struct CombatLogPanel;
+
+pub fn setup_combat_log_panel(mut commands: Commands) {
+    // Combat log (right side, toggled with L)
+    commands.spawn((
+        CombatLogPanel,
+        Interaction::default(),
+        BackgroundColor(Color::srgba(0.05, 0.05, 0.1, 0.9)),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(120.0),
+            right: Val::Px(10.0),
+            width: Val::Px(460.0),
+            flex_direction: FlexDirection::Column,
+            padding: UiRect::all(Val::Px(6.0)),
+            row_gap: Val::Px(2.0),
+            display: Display::None,
+            ..default()
+        },
+    ));
+}
+
+fn text(value: String, color: Color) -> impl Bundle {
+    (
+        Text::new(value),
+        TextFont {
+            font_size: 11.0,
+            ..default()
+        },
+        TextColor(color),
+    )
+}
+
+fn span(value: impl Into<String>, color: Color) -> impl Bundle {
+    (
+        TextSpan::new(value),
+        TextFont {
+            font_size: 11.0,
+            ..default()
+        },
+        TextColor(color),
+    )
+}
+
+// One entry as a line of text with each side's name in its civilization's color
+fn spawn_entry(parent: &mut ChildSpawnerCommands, entry: &CombatLogEntry, civ_manager: &CivilizationManager) {
+    parent
+        .spawn(text(format!("T{} ", entry.turn), TEXT_COLOR))
+        .with_children(|line| {
+            line.spawn(span(entry.attacker.clone(), civ_manager.civ_color(entry.attacker_civ)));
+            line.spawn(span(entry.verb(), TEXT_COLOR));
+            if let Some(defender_civ) = entry.defender_civ {
+                line.spawn(span(entry.defender.clone(), civ_manager.civ_color(defender_civ)));
+            }
+            line.spawn(span(entry.details(), TEXT_COLOR));
+        });
+}
+
+// Rebuilds the combat log when it's opened, when something new is logged and when the
+// player pages through it. Newest entries come first.
+pub fn update_combat_log_panel(
+    mut commands: Commands,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    ui_state: Res<UIState>,
+    combat_log: Res<CombatLog>,
+    mut panel_query: Query<(Entity, &mut Node), With<CombatLogPanel>>,
+    civ_manager: Res<CivilizationManager>,
+    mut first_shown: Local<usize>,
+) {
+    let Ok((panel_entity, mut panel_node)) = panel_query.single_mut() else { return };
+    // A new game's log starts on its first page
+    if panel_node.is_added() {
+        *first_shown = 0;
+    }
+    
+    let display = if ui_state.show_combat_log { Display::Flex } else { Display::None };
+    if panel_node.display != display {
+        panel_node.display = display;
+    }
+    if !ui_state.show_combat_log {
+        return;
+    }
+    
+    let last_page = combat_log.len().saturating_sub(1) / LOG_PAGE_SIZE * LOG_PAGE_SIZE;
+    let mut scrolled = false;
+    if keyboard.just_pressed(KeyCode::PageDown) && *first_shown < last_page {
+        *first_shown += LOG_PAGE_SIZE;
+        scrolled = true;
+    }
+    if keyboard.just_pressed(KeyCode::PageUp) && *first_shown > 0 {
+        *first_shown = first_shown.saturating_sub(LOG_PAGE_SIZE);
+        scrolled = true;
+    }
+    if !scrolled && !ui_state.is_changed() && !combat_log.is_changed() {
+        return;
+    }
+    *first_shown = (*first_shown).min(last_page);
+    
+    commands.entity(panel_entity).despawn_related::<Children>();
+    commands.entity(panel_entity).with_children(|panel| {
+        let header = format!(
+            "=== COMBAT LOG: {}-{} of {} (PgUp/PgDn to scroll, L to close) ===",
+            (*first_shown + 1).min(combat_log.len()),
+            (*first_shown + LOG_PAGE_SIZE).min(combat_log.len()),
+            combat_log.len(),
+        );
+        panel.spawn(text(header, HEADER_COLOR));
+        if combat_log.is_empty() {
+            panel.spawn(text("No battles fought yet".to_string(), TEXT_COLOR));
+        }
+        for entry in combat_log.newest_first().skip(*first_shown).take(LOG_PAGE_SIZE) {
+            spawn_entry(panel, entry, &civ_manager);
+        }
+    });
+}
@@ -0,0 +1,225 @@
+use bevy::prelude::*;
+use crate::game::cities::{CITY_MAX_HIT_POINTS, City};
+use crate::game::civilization::CivilizationManager;
+use crate::game::fonts::GameFonts;
+use crate::game::combat::{CombatPreview, CombatState, CombatTarget, can_capture_cities, flanking_multiplier, predicted_damage};
+use crate::game::hex::HexCoord;
+use crate::game::map::HEX_SIZE;
+use crate::game::units::Unit;
+
+const HEADER_COLOR: Color = Color::srgb(1.0, 0.85, 0.5);
+const TEXT_COLOR: Color = Color::srgb(0.85, 0.85, 0.85);
+const NOTE_COLOR: Color = Color::srgb(0.7, 0.7, 0.6);
+const ODDS_BAR_WIDTH: f32 = 300.0;
+const ODDS_GOOD_COLOR: Color = Color::srgb(0.3, 0.75, 0.3);
+const ODDS_BAD_COLOR: Color = Color::srgb(0.8, 0.3, 0.25);
+const ODDS_BACKGROUND: Color = Color::srgba(0.0, 0.0, 0.0, 0.6);
+
+#[derive(Component)]
+pub struct CombatPanel;
+
+/// Hex outline drawn over the defender while an attack is being considered
+#[derive(Component)]
+pub struct CombatTargetHighlight;
+
+pub fn setup_combat_panel(mut commands: Commands, fonts: Res<GameFonts>) {
+    // Combat preview (bottom center, shown while an attack awaits confirmation)
+    commands.spawn((
+        CombatPanel,
+        BackgroundColor(Color::srgba(0.1, 0.05, 0.05, 0.92)),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(60.0),
+            left: Val::Px(0.0),
+            right: Val::Px(0.0),
+            margin: UiRect::horizontal(Val::Auto),
+            width: Val::Px(330.0),
+            max_width: Val::Percent(96.0),
+            flex_direction: FlexDirection::Column,
+            padding: UiRect::all(Val::Px(8.0)),
+            row_gap: Val::Px(3.0),
+            display: Display::None,
+            ..default()
+        },
+    ));
+    
+    commands.spawn((
+        CombatTargetHighlight,
+        Text2d::new("⬡"),
+        TextColor(Color::srgba(1.0, 0.2, 0.2, 0.9)),
+        fonts.symbol(40.0),
+        Transform::from_xyz(0.0, 0.0, 2.5),
+        Visibility::Hidden,
+    ));
+}
+
+fn text(value: String, color: Color) -> impl Bundle {
+    (
+        Text::new(value),
+        TextFont {
+            font_size: 12.0,
+            ..default()
+        },
+        TextColor(color),
+    )
+}
+
+// Win probability as a filled bar with the percentage on top
+fn spawn_odds_bar(parent: &mut ChildSpawnerCommands, win_chance: f32) {
+    let color = if win_chance >= 0.5 { ODDS_GOOD_COLOR } else { ODDS_BAD_COLOR };
+    parent
+        .spawn((
+            BackgroundColor(ODDS_BACKGROUND),
+            Node {
+                width: Val::Px(ODDS_BAR_WIDTH),
+                height: Val::Px(16.0),
+                ..default()
+            },
+        ))
+        .with_children(|bar| {
+            bar.spawn((
+                BackgroundColor(color),
+                Node {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(win_chance * 100.0),
+                    height: Val::Percent(100.0),
+                    ..default()
+                },
+            ));
+            bar.spawn(text(format!(" Win chance: {:.0}%", win_chance * 100.0), Color::WHITE));
+        });
+}
+
+// What the panel says about one preview
+struct PanelContents {
+    title: &'static str,
+    defender_at: HexCoord,
+    lines: Vec<String>,
+    notes: Vec<String>,
+}
+
+// The panel for one preview, or None if a side has gone missing
+fn panel_contents(
+    preview: &CombatPreview,
+    unit_query: &Query<&Unit>,
+    city_query: &Query<&City>,
+    civ_manager: &CivilizationManager,
+) -> Option<PanelContents> {
+    let attacker = unit_query.get(preview.attacker_entity).ok()?;
+    let (win_damage, loss_damage) = predicted_damage(preview);
+    let mut notes = Vec::new();
+    
+    let (title, defender_line, defender_at) = match preview.defender {
+        CombatTarget::Unit(entity) => {
+            let defender = unit_query.get(entity).ok()?;
+            let base = (preview.defender_strength as f32 / preview.terrain_modifier.max(0.01)).round();
+            let line = format!(
+                "Defender: {} - strength {} ({:.0} x{:.2} terrain and fortifications)",
+                defender.label(civ_manager), preview.defender_strength, base, preview.terrain_modifier,
+            );
+            let stacked = unit_query.iter().filter(|unit| unit.hex_coord == defender.hex_coord && !unit.is_dead()).count().saturating_sub(1);
+            if stacked > 0 {
+                notes.push(format!(
+                    "{} other unit{} on the hex; the strongest defends, and civilians are captured once the last defender falls",
+                    stacked, if stacked == 1 { "" } else { "s" },
+                ));
+            }
+            if preview.withdraw_chance > 0.0 {
+                notes.push(format!(
+                    "The defender is faster: {:.0}% chance it withdraws with 1 HP instead of dying",
+                    preview.withdraw_chance * 100.0,
+                ));
+            }
+            ("ATTACK", line, defender.hex_coord)
+        }
+        CombatTarget::City(entity) | CombatTarget::Bombard(entity) => {
+            let city = city_query.get(entity).ok()?;
+            let bombard = matches!(preview.defender, CombatTarget::Bombard(_));
+            if bombard {
+                notes.push("Bombarding never takes a city below 1 HP; the garrison is unharmed".to_string());
+                if city.hit_points <= 1 {
+                    notes.push("The city's defenses are already down".to_string());
+                }
+            } else if !can_capture_cities(attacker) {
+                notes.push("Only land melee units can capture; this attack can't take the city below 1 HP".to_string());
+            }
+            let line = format!(
+                "City: {} ({}) - defense {}, {}/{} HP",
+                city.name, civ_manager.known_civ_name(city.civilization_id), preview.defender_strength,
+                city.hit_points, CITY_MAX_HIT_POINTS,
+            );
+            (if bombard { "BOMBARDMENT" } else { "CITY ASSAULT" }, line, city.hex_coord)
+        }
+    };
+    
+    let mut lines = vec![
+        format!("Attacker: {} - strength {}", attacker.label(civ_manager), preview.attacker_strength),
+    ];
+    if preview.siege_modifier != 1.0 {
+        let against = if preview.siege_modifier > 1.0 { "cities" } else { "units in the open" };
+        lines.push(format!("Siege engine: x{:.1} strength against {}", preview.siege_modifier, against));
+    }
+    if preview.flanking_units > 0 {
+        lines.push(format!(
+            "Flanking: +{:.0}% from {} adjacent unit{}",
+            (flanking_multiplier(preview.flanking_units) - 1.0) * 100.0,
+            preview.flanking_units,
+            if preview.flanking_units == 1 { "" } else { "s" },
+        ));
+    }
+    lines.extend([
+        defender_line,
+        format!("Defender takes: 0-{} damage", win_damage),
+    ]);
+    if loss_damage > 0 {
+        lines.push(format!("Attacker takes: 0-{} damage", loss_damage));
+    }
+    Some(PanelContents { title, defender_at, lines, notes })
+}
+
+// Shows the pending attack, rebuilt whenever the preview changes (a new target,
+// confirmation or cancel), and marks the defender's hex on the map
+pub fn update_combat_panel(
+    mut commands: Commands,
+    combat_state: Res<CombatState>,
+    mut panel_query: Query<(Entity, &mut Node), With<CombatPanel>>,
+    mut highlight_query: Query<(&mut Transform, &mut Visibility), With<CombatTargetHighlight>>,
+    unit_query: Query<&Unit>,
+    city_query: Query<&City>,
+    civ_manager: Res<CivilizationManager>,
+) {
+    if !combat_state.is_changed() {
+        return;
+    }
+    let Ok((panel_entity, mut panel_node)) = panel_query.single_mut() else { return };
+    let Ok((mut highlight_transform, mut highlight_visibility)) = highlight_query.single_mut() else { return };
+    
+    let shown = combat_state
+        .combat_preview
+        .as_ref()
+        .and_then(|preview| panel_contents(preview, &unit_query, &city_query, &civ_manager).map(|contents| (preview, contents)));
+    let Some((preview, contents)) = shown else {
+        panel_node.display = Display::None;
+        *highlight_visibility = Visibility::Hidden;
+        return;
+    };
+    
+    panel_node.display = Display::Flex;
+    let world_pos = contents.defender_at.to_world_pos(HEX_SIZE);
+    highlight_transform.translation.x = world_pos.x;
+    highlight_transform.translation.y = world_pos.y;
+    *highlight_visibility = Visibility::Visible;
+    
+    commands.entity(panel_entity).despawn_related::<Children>();
+    commands.entity(panel_entity).with_children(|panel| {
+        panel.spawn(text(format!("=== {} ===", contents.title), HEADER_COLOR));
+        for line in contents.lines {
+            panel.spawn(text(line, TEXT_COLOR));
+        }
+        spawn_odds_bar(panel, preview.attacker_win_chance);
+        for note in contents.notes {
+            panel.spawn(text(note, NOTE_COLOR));
+        }
+        panel.spawn(text("[Enter] Confirm    [Esc] Cancel".to_string(), HEADER_COLOR));
+    });
+}
@@ -0,0 +1,223 @@
+use bevy::prelude::*;
+use crate::game::cities::City;
+use crate::game::civilization::{Civilization, CivilizationManager};
+use crate::game::game_initialization::GameSettings;
+use crate::game::statistics::{CivSummary, Demographic, Demographics, MilitaryComparison, StatsHistory, shows_everything};
+use crate::game::units::Unit;
+use super::game_panels::UIState;
+
+const HEADER_COLOR: Color = Color::srgb(0.9, 0.9, 0.7);
+const TEXT_COLOR: Color = Color::srgb(0.8, 0.8, 0.8);
+const GRAPH_WIDTH: f32 = 420.0;
+const GRAPH_HEIGHT: f32 = 120.0;
+const GRAPH_POINT_SIZE: f32 = 3.0;
+// Columns of the table: metric, the player's value, their rank, the best value and who holds it
+const COLUMN_WIDTHS: [f32; 5] = [130.0, 70.0, 50.0, 70.0, 130.0];
+// Another civilization's income is only guessed at, to the nearest this many
+const ESTIMATE_STEP: f32 = 10.0;
+
+#[derive(Component)]
+pub struct DemographicsPanel;
+
+pub fn setup_demographics_panel(mut commands: Commands) {
+    // Demographics (center, toggled with F5)
+    commands.spawn((
+        DemographicsPanel,
+        Interaction::default(),
+        BackgroundColor(Color::srgba(0.05, 0.05, 0.1, 0.92)),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(120.0),
+            left: Val::Px(0.0),
+            right: Val::Px(0.0),
+            margin: UiRect::horizontal(Val::Auto),
+            width: Val::Px(480.0),
+            max_width: Val::Percent(96.0),
+            flex_direction: FlexDirection::Column,
+            padding: UiRect::all(Val::Px(6.0)),
+            row_gap: Val::Px(2.0),
+            display: Display::None,
+            ..default()
+        },
+    ));
+}
+
+fn text(value: String, color: Color) -> impl Bundle {
+    (
+        Text::new(value),
+        TextFont {
+            font_size: 11.0,
+            ..default()
+        },
+        TextColor(color),
+    )
+}
+
+fn table_row(parent: &mut ChildSpawnerCommands, cells: [String; 5], color: Color) {
+    parent
+        .spawn(Node {
+            flex_direction: FlexDirection::Row,
+            ..default()
+        })
+        .with_children(|row| {
+            for (cell, width) in cells.into_iter().zip(COLUMN_WIDTHS) {
+                row.spawn(Node {
+                    width: Val::Px(width),
+                    ..default()
+                })
+                .with_child(text(cell, color));
+            }
+        });
+}
+
+// Another civilization's best figure as the player could judge it. Population, land and
+// cities can be counted on the map; an army is only sized up against the player's own,
+// and science and gold are rough estimates.
+fn estimated_best(demographic: Demographic, best: f32, yours: f32) -> String {
+    match demographic {
+        Demographic::Military => MilitaryComparison::between(best, yours).get_name().to_string(),
+        Demographic::Science | Demographic::Gold => format!("~{:.0}", (best / ESTIMATE_STEP).round() * ESTIMATE_STEP),
+        _ => format!("{:.0}", best),
+    }
+}
+
+// "3rd" and so on
+fn ordinal(rank: usize) -> String {
+    let suffix = match (rank % 10, rank % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{}{}", rank, suffix)
+}
+
+// Score over time for every civilization the player knows, one dot per sample in the
+// civilization's color. Long games are thinned out to fit the width.
+pub(super) fn spawn_score_graph(parent: &mut ChildSpawnerCommands, history: &StatsHistory, civs: &[&Civilization]) {
+    let series: Vec<(&Civilization, &[f32])> = civs
+        .iter()
+        .filter_map(|civ| history.civs.get(&civ.id).map(|civ_history| (*civ, civ_history.score.as_slice())))
+        .collect();
+    let samples = history.turns.len();
+    let max_score = series
+        .iter()
+        .flat_map(|(_, scores)| scores.iter().copied())
+        .fold(1.0_f32, f32::max);
+    let step = samples.div_ceil((GRAPH_WIDTH / GRAPH_POINT_SIZE) as usize).max(1);
+    
+    parent.spawn(text(format!("Score history (best {:.0})", max_score), HEADER_COLOR));
+    parent
+        .spawn((
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.4)),
+            Node {
+                width: Val::Px(GRAPH_WIDTH),
+                height: Val::Px(GRAPH_HEIGHT),
+                ..default()
+            },
+        ))
+        .with_children(|graph| {
+            if samples < 2 {
+                graph.spawn(text("Not enough turns played yet".to_string(), TEXT_COLOR));
+                return;
+            }
+            for (civ, scores) in &series {
+                for index in (0..scores.len()).step_by(step) {
+                    let x = index as f32 / (samples - 1) as f32 * (GRAPH_WIDTH - GRAPH_POINT_SIZE);
+                    let y = scores[index] / max_score * (GRAPH_HEIGHT - GRAPH_POINT_SIZE);
+                    graph.spawn((
+                        BackgroundColor(civ.color),
+                        Node {
+                            position_type: PositionType::Absolute,
+                            left: Val::Px(x),
+                            bottom: Val::Px(y),
+                            width: Val::Px(GRAPH_POINT_SIZE),
+                            height: Val::Px(GRAPH_POINT_SIZE),
+                            ..default()
+                        },
+                    ));
+                }
+            }
+        });
+}
+
+// Rebuilds the demographics screen when it's opened and whenever a new turn has been sampled.
+// The player is compared only with the civilizations they have met, and only on what they
+// could tell about them; an observer or --omniscient sees everyone's exact figures.
+#[allow(clippy::too_many_arguments)]
+pub fn update_demographics_panel(
+    mut commands: Commands,
+    ui_state: Res<UIState>,
+    history: Res<StatsHistory>,
+    mut panel_query: Query<(Entity, &mut Node), With<DemographicsPanel>>,
+    civ_manager: Res<CivilizationManager>,
+    settings: Res<GameSettings>,
+    city_query: Query<&City>,
+    unit_query: Query<&Unit>,
+) {
+    let Ok((panel_entity, mut panel_node)) = panel_query.single_mut() else { return };
+    
+    let display = if ui_state.show_demographics { Display::Flex } else { Display::None };
+    if panel_node.display != display {
+        panel_node.display = display;
+    }
+    if !ui_state.show_demographics || (!ui_state.is_changed() && !history.is_changed()) {
+        return;
+    }
+    
+    let Some(player_civ) = civ_manager.get_viewed_civilization() else { return };
+    let mut known: Vec<&Civilization> = civ_manager
+        .civilizations
+        .values()
+        .filter(|civ| !civ.eliminated && civ_manager.player_knows(civ.id))
+        .collect();
+    known.sort_by_key(|civ| civ.id);
+    let summaries = CivSummary::measure_all(&civ_manager, &city_query, &unit_query);
+    let demographics_of = |civ_id: u32| {
+        summaries.iter().find(|summary| summary.civ_id == civ_id).map_or_else(Demographics::default, |summary| summary.demographics)
+    };
+    let demographics: Vec<(&Civilization, Demographics)> = known.iter().map(|civ| (*civ, demographics_of(civ.id))).collect();
+    let player_demographics = demographics_of(player_civ.id);
+    let exact = shows_everything(&settings, &civ_manager);
+    
+    commands.entity(panel_entity).despawn_related::<Children>();
+    commands.entity(panel_entity).with_children(|panel| {
+        panel.spawn(text(
+            format!("=== DEMOGRAPHICS: {} known civilizations (F5 to close) ===", known.len()),
+            HEADER_COLOR,
+        ));
+        table_row(panel, ["", "You", "Rank", "Best", "Leader"].map(String::from), HEADER_COLOR);
+        
+        for demographic in Demographic::ALL {
+            let value = player_demographics.get(demographic);
+            let rank = 1 + demographics
+                .iter()
+                .filter(|(_, other)| other.get(demographic) > value)
+                .count();
+            let leader = demographics
+                .iter()
+                .max_by(|(a_civ, a), (b_civ, b)| {
+                    a.get(demographic).total_cmp(&b.get(demographic)).then(b_civ.id.cmp(&a_civ.id))
+                });
+            let (best, leader_id, leader_name) = leader.map_or((value, player_civ.id, player_civ.name.clone()), |(civ, other)| {
+                (other.get(demographic), civ.id, civ.name.clone())
+            });
+            let best = if exact || leader_id == player_civ.id {
+                format!("{:.0}", best)
+            } else {
+                estimated_best(demographic, best, value)
+            };
+            let cells = [
+                demographic.get_name().to_string(),
+                format!("{:.0}", value),
+                ordinal(rank),
+                best,
+                leader_name,
+            ];
+            table_row(panel, cells, TEXT_COLOR);
+        }
+        
+        spawn_score_graph(panel, &history, &known);
+    });
+}
@@ -0,0 +1,368 @@
+use bevy::prelude::*;
+use crate::game::cities::{City, WAR_WEARINESS_UNHAPPINESS};
+use crate::game::civilization::CivilizationManager;
+use crate::game::diplomacy::{apply_deal, evaluate_deal, war_weariness_combat_modifier, DealContext, DealGold, DealProposal, Relation, WarDeclaredEvent, WarStrength, MAX_WAR_WEARINESS};
+use crate::game::units::{Unit, UnitSelection};
+use super::game_panels::UIState;
+
+const BUTTON_COLOR: Color = Color::srgba(0.25, 0.25, 0.3, 0.9);
+const BUTTON_HOVER_COLOR: Color = Color::srgba(0.4, 0.4, 0.55, 0.9);
+
+#[derive(Component)]
+pub struct DiplomacyPanel;
+
+// Gold choices the deal composer cycles through for each side
+const GOLD_OPTIONS: [DealGold; 5] = [
+    DealGold::None,
+    DealGold::Lump(50.0),
+    DealGold::Lump(100.0),
+    DealGold::PerTurn(3.0),
+    DealGold::PerTurn(6.0),
+];
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum DiplomacyAction {
+    DeclareWar,
+    OpenDeal,
+    TogglePeace,
+    CycleYourGold,
+    CycleTheirGold,
+    Propose,
+    CloseDeal,
+}
+
+/// The deal the player is putting together in the diplomacy panel
+#[derive(Resource, Clone, Default, PartialEq)]
+pub struct DealComposer {
+    pub target: Option<u32>,
+    pub peace: bool,
+    pub your_gold: usize,  // Index into GOLD_OPTIONS
+    pub their_gold: usize, // Index into GOLD_OPTIONS
+    pub last_result: Option<String>,
+}
+
+impl DealComposer {
+    /// A fresh deal with another civilization, offering peace if the player is at war with it
+    pub fn open(civ_id: u32, player_id: u32, civ_manager: &CivilizationManager) -> Self {
+        DealComposer {
+            target: Some(civ_id),
+            peace: civ_manager.diplomacy.at_war(player_id, civ_id),
+            ..default()
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct DiplomacyButton {
+    pub civ_id: u32,
+    pub action: DiplomacyAction,
+}
+
+// What a civilization's row displays; the panel is only rebuilt when these change
+#[derive(Clone, PartialEq)]
+pub struct DiplomacyEntry {
+    civ_id: u32,
+    name: String,
+    label: String,
+    can_declare_war: bool,
+    at_war: bool,
+}
+
+pub fn setup_diplomacy_panel(mut commands: Commands) {
+    // Diplomacy panel (center, toggled with F9)
+    commands.spawn((
+        DiplomacyPanel,
+        Interaction::default(),
+        BackgroundColor(Color::srgba(0.05, 0.05, 0.1, 0.92)),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(120.0),
+            left: Val::Px(0.0),
+            right: Val::Px(0.0),
+            margin: UiRect::horizontal(Val::Auto),
+            width: Val::Px(400.0),
+            max_width: Val::Percent(96.0),
+            flex_direction: FlexDirection::Column,
+            padding: UiRect::all(Val::Px(6.0)),
+            row_gap: Val::Px(4.0),
+            display: Display::None,
+            ..default()
+        },
+    ));
+}
+
+pub(super) fn relation_label(relation: Relation, turn: u32) -> String {
+    match relation {
+        Relation::Peace => "Peace".to_string(),
+        Relation::War(record) => format!("At war since turn {}", record.started_turn),
+        Relation::Truce { until_turn } if until_turn > turn => format!("Truce ({} turns left)", until_turn - turn),
+        Relation::Truce { .. } => "Peace".to_string(),
+    }
+}
+
+// How the player's people feel about their wars, once they feel anything at all
+fn weariness_summary(weariness: f32) -> Option<String> {
+    if weariness < 1.0 {
+        return None;
+    }
+    let mood = if weariness >= MAX_WAR_WEARINESS * 0.5 { " Your people want peace." } else { "" };
+    Some(format!(
+        "War weariness: {:.0}/{:.0} (-{:.1} happiness per city, -{:.0}% unit strength).{}",
+        weariness, MAX_WAR_WEARINESS, weariness * WAR_WEARINESS_UNHAPPINESS,
+        (1.0 - war_weariness_combat_modifier(weariness)) * 100.0, mood,
+    ))
+}
+
+pub fn update_diplomacy_panel(
+    mut commands: Commands,
+    ui_state: Res<UIState>,
+    mut panel_query: Query<(Entity, &mut Node), With<DiplomacyPanel>>,
+    civ_manager: Res<CivilizationManager>,
+    composer: Res<DealComposer>,
+    mut shown_entries: Local<Option<(Vec<DiplomacyEntry>, DealComposer)>>,
+) {
+    let Ok((panel_entity, mut panel_node)) = panel_query.single_mut() else { return };
+    // A new game's panel starts out empty
+    if panel_node.is_added() {
+        *shown_entries = None;
+    }
+    
+    let display = if ui_state.show_diplomacy { Display::Flex } else { Display::None };
+    if panel_node.display != display {
+        panel_node.display = display;
+    }
+    if !ui_state.show_diplomacy {
+        *shown_entries = None;
+        return;
+    }
+    
+    let Some(player_civ) = civ_manager.get_viewed_civilization() else { return };
+    let turn = civ_manager.turn_number;
+    // Only civilizations the player has met can be dealt with
+    let mut civs: Vec<_> = civ_manager
+        .civilizations
+        .values()
+        .filter(|civ| civ.id != player_civ.id && !civ.eliminated && player_civ.has_met(civ.id))
+        .collect();
+    civs.sort_by_key(|civ| civ.id);
+    
+    let entries: Vec<DiplomacyEntry> = civs
+        .into_iter()
+        .map(|civ| {
+            let relation = civ_manager.diplomacy.relation(player_civ.id, civ.id);
+            let mut label = format!("{} ({}): {}", civ.name, civ.leader_name, relation_label(relation, turn));
+            let weariness = civ_manager.diplomacy.war_weariness(player_civ.id, civ.id);
+            if weariness >= 1.0 {
+                label.push_str(&format!(" - weariness {:.0}", weariness));
+            }
+            DiplomacyEntry {
+                civ_id: civ.id,
+                name: civ.name.clone(),
+                label,
+                can_declare_war: civ_manager.diplomacy.can_declare_war(player_civ.id, civ.id, turn),
+                at_war: civ_manager.diplomacy.at_war(player_civ.id, civ.id),
+            }
+        })
+        .collect();
+    let weariness_line = weariness_summary(civ_manager.diplomacy.total_war_weariness(player_civ.id));
+    if shown_entries.as_ref().is_some_and(|(shown, shown_composer)| *shown == entries && shown_composer == &*composer) {
+        return;
+    }
+    
+    commands.entity(panel_entity).despawn_related::<Children>();
+    commands.entity(panel_entity).with_children(|panel| {
+        panel.spawn((
+            Text::new("=== DIPLOMACY (F9 to close) ==="),
+            TextFont {
+                font_size: 12.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.9, 0.9, 0.7)),
+        ));
+        if let Some(line) = weariness_line {
+            panel.spawn((
+                Text::new(line),
+                TextFont {
+                    font_size: 11.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.9, 0.6, 0.5)),
+            ));
+        }
+        if entries.is_empty() {
+            panel.spawn((
+                Text::new("You haven't met any other civilizations yet."),
+                TextFont {
+                    font_size: 11.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.8, 0.8, 0.8)),
+            ));
+        }
+        
+        for entry in &entries {
+            panel
+                .spawn(Node {
+                    flex_direction: FlexDirection::Row,
+                    justify_content: JustifyContent::SpaceBetween,
+                    align_items: AlignItems::Center,
+                    ..default()
+                })
+                .with_children(|row| {
+                    row.spawn((
+                        Text::new(entry.label.clone()),
+                        TextFont {
+                            font_size: 11.0,
+                            ..default()
+                        },
+                        TextColor(Color::srgb(0.8, 0.8, 0.8)),
+                    ));
+                    
+                    row.spawn(Node {
+                        column_gap: Val::Px(4.0),
+                        ..default()
+                    })
+                    .with_children(|buttons| {
+                        if entry.can_declare_war {
+                            spawn_button(buttons, entry.civ_id, DiplomacyAction::DeclareWar, "[Declare War]".to_string());
+                        }
+                        spawn_button(buttons, entry.civ_id, DiplomacyAction::OpenDeal, "[Deal]".to_string());
+                    });
+                });
+        }
+        
+        let Some(entry) = composer.target.and_then(|target| entries.iter().find(|entry| entry.civ_id == target)) else { return };
+        panel.spawn((
+            Text::new(format!("--- Deal with {} ---", entry.name)),
+            TextFont {
+                font_size: 12.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.9, 0.9, 0.7)),
+        ));
+        panel
+            .spawn(Node {
+                flex_direction: FlexDirection::Row,
+                flex_wrap: FlexWrap::Wrap,
+                column_gap: Val::Px(4.0),
+                row_gap: Val::Px(4.0),
+                ..default()
+            })
+            .with_children(|buttons| {
+                if entry.at_war {
+                    let peace = if composer.peace { "[Peace: Yes]" } else { "[Peace: No]" };
+                    spawn_button(buttons, entry.civ_id, DiplomacyAction::TogglePeace, peace.to_string());
+                }
+                let your_gold = GOLD_OPTIONS[composer.your_gold].describe();
+                let their_gold = GOLD_OPTIONS[composer.their_gold].describe();
+                spawn_button(buttons, entry.civ_id, DiplomacyAction::CycleYourGold, format!("[You give: {}]", your_gold));
+                spawn_button(buttons, entry.civ_id, DiplomacyAction::CycleTheirGold, format!("[They give: {}]", their_gold));
+                spawn_button(buttons, entry.civ_id, DiplomacyAction::Propose, "[Propose]".to_string());
+                spawn_button(buttons, entry.civ_id, DiplomacyAction::CloseDeal, "[Cancel]".to_string());
+            });
+        if let Some(result) = &composer.last_result {
+            panel.spawn((
+                Text::new(result.clone()),
+                TextFont {
+                    font_size: 11.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.8, 0.8, 0.8)),
+            ));
+        }
+    });
+    
+    *shown_entries = Some((entries, composer.clone()));
+}
+
+fn spawn_button(parent: &mut ChildSpawnerCommands, civ_id: u32, action: DiplomacyAction, label: String) {
+    parent
+        .spawn((
+            Button,
+            DiplomacyButton { civ_id, action },
+            BackgroundColor(BUTTON_COLOR),
+            Node {
+                padding: UiRect::axes(Val::Px(4.0), Val::Px(1.0)),
+                ..default()
+            },
+        ))
+        .with_child((
+            Text::new(label),
+            TextFont {
+                font_size: 11.0,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+        ));
+}
+
+// Declaring war records both sides' strength; deals are weighed by the AI with evaluate_deal
+pub fn diplomacy_button_system(
+    mut button_query: Query<(&Interaction, &DiplomacyButton, &mut BackgroundColor), Changed<Interaction>>,
+    unit_query: Query<&Unit>,
+    city_query: Query<&City>,
+    mut civ_manager: ResMut<CivilizationManager>,
+    mut unit_selection: ResMut<UnitSelection>,
+    mut composer: ResMut<DealComposer>,
+    mut war_events: EventWriter<WarDeclaredEvent>,
+) {
+    for (interaction, button, mut background) in button_query.iter_mut() {
+        match interaction {
+            Interaction::Pressed => {
+                let Some(player_id) = civ_manager.get_player_civilization().map(|civ| civ.id) else { continue };
+                let other_name = civ_manager.get_civilization(button.civ_id)
+                    .map_or("Unknown".to_string(), |civ| civ.name.clone());
+                let turn = civ_manager.turn_number;
+                let strength = |civ_id| WarStrength::measure(civ_id, unit_query.iter(), city_query.iter());
+                
+                match button.action {
+                    DiplomacyAction::DeclareWar => {
+                        let attacker = (player_id, strength(player_id));
+                        let defender = (button.civ_id, strength(button.civ_id));
+                        if civ_manager.diplomacy.declare_war(attacker, defender, turn) {
+                            println!("You have declared war on {}!", other_name);
+                            war_events.write(WarDeclaredEvent { civilization_id: player_id, other_id: button.civ_id });
+                        }
+                        // Their units and cities are attack targets now
+                        unit_selection.refresh_indicators();
+                    }
+                    DiplomacyAction::OpenDeal => *composer = DealComposer::open(button.civ_id, player_id, &civ_manager),
+                    DiplomacyAction::TogglePeace => composer.peace = !composer.peace,
+                    DiplomacyAction::CycleYourGold => composer.your_gold = (composer.your_gold + 1) % GOLD_OPTIONS.len(),
+                    DiplomacyAction::CycleTheirGold => composer.their_gold = (composer.their_gold + 1) % GOLD_OPTIONS.len(),
+                    DiplomacyAction::Propose => {
+                        let deal = DealProposal {
+                            proposer: player_id,
+                            recipient: button.civ_id,
+                            peace: composer.peace && civ_manager.diplomacy.at_war(player_id, button.civ_id),
+                            proposer_gold: GOLD_OPTIONS[composer.your_gold],
+                            recipient_gold: GOLD_OPTIONS[composer.their_gold],
+                        };
+                        let player_gold = civ_manager.get_player_civilization().map_or(0.0, |civ| civ.gold);
+                        if let DealGold::Lump(amount) = deal.proposer_gold && amount > player_gold {
+                            composer.last_result = Some(format!("You don't have {:.0} gold.", amount));
+                            continue;
+                        }
+                        if !deal.peace && deal.proposer_gold == DealGold::None && deal.recipient_gold == DealGold::None {
+                            composer.last_result = Some("The deal is empty.".to_string());
+                            continue;
+                        }
+                        
+                        let context = DealContext::gather(button.civ_id, player_id, &unit_query, &city_query, &civ_manager);
+                        if evaluate_deal(&deal, &context) >= 0.0 {
+                            apply_deal(&deal, &mut civ_manager);
+                            unit_selection.refresh_indicators();
+                            println!("{} accepts the deal.", other_name);
+                            *composer = DealComposer::default();
+                        } else {
+                            composer.last_result = Some(format!("{} refuses the deal.", other_name));
+                        }
+                    }
+                    DiplomacyAction::CloseDeal => *composer = DealComposer::default(),
+                }
+            }
+            Interaction::Hovered => background.0 = BUTTON_HOVER_COLOR,
+            Interaction::None => background.0 = BUTTON_COLOR,
+        }
+    }
+}
@@ -0,0 +1,328 @@
+use bevy::prelude::*;
+use crate::game::cities::{City, CitySelection, ProductionItem};
+use crate::game::civilization::CivilizationManager;
+use crate::game::hex::HexCoord;
+use crate::game::camera_zoom::CameraTween;
+use crate::game::units::UnitSelection;
+use super::game_panels::UIState;
+
+const ROW_COLOR: Color = Color::srgba(0.15, 0.15, 0.2, 0.8);
+const ROW_HOVER_COLOR: Color = Color::srgba(0.3, 0.3, 0.45, 0.9);
+const WARNING_COLOR: Color = Color::srgb(1.0, 0.6, 0.4);
+
+#[derive(Component)]
+pub struct EmpireOverviewPanel;
+
+// Column header; clicking sorts by that column
+#[derive(Component)]
+pub struct OverviewHeaderButton(pub OverviewColumn);
+
+// One city row; clicking centers the map on the city and opens its panel
+#[derive(Component)]
+pub struct OverviewCityRow {
+    pub city: Entity,
+    pub hex_coord: HexCoord,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum OverviewColumn {
+    #[default]
+    Name,
+    Population,
+    Food,
+    Production,
+    Gold,
+    Science,
+    Culture,
+    Happiness,
+}
+
+impl OverviewColumn {
+    pub const ALL: [OverviewColumn; 8] = [
+        OverviewColumn::Name,
+        OverviewColumn::Population,
+        OverviewColumn::Food,
+        OverviewColumn::Production,
+        OverviewColumn::Gold,
+        OverviewColumn::Science,
+        OverviewColumn::Culture,
+        OverviewColumn::Happiness,
+    ];
+    
+    pub fn get_name(self) -> &'static str {
+        match self {
+            OverviewColumn::Name => "City",
+            OverviewColumn::Population => "Pop",
+            OverviewColumn::Food => "Food",
+            OverviewColumn::Production => "Production",
+            OverviewColumn::Gold => "Gold",
+            OverviewColumn::Science => "Sci",
+            OverviewColumn::Culture => "Cult",
+            OverviewColumn::Happiness => "Happy",
+        }
+    }
+    
+    fn width(self) -> f32 {
+        match self {
+            OverviewColumn::Name => 110.0,
+            OverviewColumn::Production => 130.0,
+            _ => 48.0,
+        }
+    }
+}
+
+/// How the empire overview is sorted
+#[derive(Resource, Default)]
+pub struct OverviewSort {
+    pub column: OverviewColumn,
+    pub descending: bool,
+}
+
+// Width of the trailing status column (growth and starvation warnings)
+const STATUS_WIDTH: f32 = 150.0;
+
+pub fn setup_empire_overview(mut commands: Commands) {
+    // Empire overview (center, toggled with F8)
+    commands.spawn((
+        EmpireOverviewPanel,
+        Interaction::default(),
+        ScrollPosition::default(),
+        BackgroundColor(Color::srgba(0.05, 0.05, 0.1, 0.92)),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(120.0),
+            left: Val::Px(0.0),
+            right: Val::Px(0.0),
+            margin: UiRect::horizontal(Val::Auto),
+            width: Val::Px(table_width() + 12.0), // The columns and the padding
+            max_width: Val::Percent(96.0),
+            max_height: Val::Px(480.0),
+            flex_direction: FlexDirection::Column,
+            overflow: Overflow::scroll_y(),
+            padding: UiRect::all(Val::Px(6.0)),
+            row_gap: Val::Px(2.0),
+            display: Display::None,
+            ..default()
+        },
+    ));
+}
+
+// Text of each column for one city, plus a status warning if it needs attention
+fn overview_cells(city: &City) -> (Vec<String>, String, bool) {
+    let production = match &city.current_production {
+        _ if city.is_razing => "Razing".to_string(),
+        _ if city.resistance_turns > 0 => format!("Resistance ({})", city.resistance_turns),
+        Some(ProductionItem::Wealth) => "Wealth".to_string(),
+        Some(item) => {
+            let remaining = city.required_production(item) - city.production_progress;
+            match city.turns_for(item, remaining) {
+                Some(turns) => format!("{} ({})", item.get_name(), turns),
+                None => format!("{} (-)", item.get_name()),
+            }
+        }
+        None => "Idle".to_string(),
+    };
+    
+    let cells = vec![
+        city.name.clone(),
+        city.population.to_string(),
+        format!("{:+.1}", city.food_per_turn),
+        production,
+        format!("{:+.1}", city.net_gold_per_turn()),
+        format!("{:.1}", city.science_per_turn),
+        format!("{:.1}", city.culture_per_turn),
+        format!("{:+.0}", city.happiness),
+    ];
+    
+    let (status, warning) = if city.food_per_turn < 0.0 {
+        ("Starving!".to_string(), true)
+    } else if city.is_in_unrest() {
+        ("Unrest".to_string(), true)
+    } else if city.population >= city.max_population() {
+        ("Needs an Aqueduct to grow".to_string(), true)
+    } else if city.happiness < 0.0 {
+        ("Too unhappy to grow".to_string(), true)
+    } else if city.food_per_turn > 0.0 {
+        let turns = ((city.food_needed_for_growth - city.food_stored) / city.food_per_turn).ceil().max(0.0);
+        (format!("Grows in {} turns", turns), false)
+    } else {
+        ("Stagnant".to_string(), false)
+    };
+    
+    (cells, status, warning)
+}
+
+fn compare_cities(a: &City, b: &City, column: OverviewColumn) -> std::cmp::Ordering {
+    match column {
+        OverviewColumn::Name => a.name.cmp(&b.name),
+        OverviewColumn::Population => a.population.cmp(&b.population),
+        OverviewColumn::Food => a.food_per_turn.total_cmp(&b.food_per_turn),
+        OverviewColumn::Production => a.production_per_turn.total_cmp(&b.production_per_turn),
+        OverviewColumn::Gold => a.net_gold_per_turn().total_cmp(&b.net_gold_per_turn()),
+        OverviewColumn::Science => a.science_per_turn.total_cmp(&b.science_per_turn),
+        OverviewColumn::Culture => a.culture_per_turn.total_cmp(&b.culture_per_turn),
+        OverviewColumn::Happiness => a.happiness.total_cmp(&b.happiness),
+    }
+}
+
+// Every column, the status included, side by side
+fn table_width() -> f32 {
+    OverviewColumn::ALL.into_iter().map(OverviewColumn::width).sum::<f32>() + STATUS_WIDTH
+}
+
+fn cell(text: String, width: f32, color: Color) -> impl Bundle {
+    (
+        Text::new(text),
+        TextFont {
+            font_size: 11.0,
+            ..default()
+        },
+        TextColor(color),
+        Node {
+            width: Val::Px(width),
+            ..default()
+        },
+    )
+}
+
+// Rebuilds the overview when it's opened, re-sorted, or one of the cities changes.
+// Turn processing changes cities, so a new turn refreshes it too.
+pub fn update_empire_overview(
+    mut commands: Commands,
+    ui_state: Res<UIState>,
+    sort: Res<OverviewSort>,
+    mut panel_query: Query<(Entity, &mut Node), With<EmpireOverviewPanel>>,
+    city_query: Query<(Entity, Ref<City>)>,
+    mut removed_cities: RemovedComponents<City>,
+    civ_manager: Res<CivilizationManager>,
+) {
+    let cities_removed = removed_cities.read().count() > 0;
+    let Ok((panel_entity, mut panel_node)) = panel_query.single_mut() else { return };
+    
+    let display = if ui_state.show_empire_overview { Display::Flex } else { Display::None };
+    if panel_node.display != display {
+        panel_node.display = display;
+    }
+    if !ui_state.show_empire_overview {
+        return;
+    }
+    
+    let cities_changed = city_query.iter().any(|(_, city)| city.is_changed());
+    if !ui_state.is_changed() && !sort.is_changed() && !cities_changed && !cities_removed {
+        return;
+    }
+    
+    let Some(player_civ) = civ_manager.get_viewed_civilization() else { return };
+    let mut cities: Vec<(Entity, &City)> = city_query
+        .iter()
+        .filter(|(_, city)| city.civilization_id == player_civ.id)
+        .map(|(entity, city)| (entity, city.into_inner()))
+        .collect();
+    cities.sort_by(|(_, a), (_, b)| {
+        let ordering = compare_cities(a, b, sort.column);
+        if sort.descending { ordering.reverse() } else { ordering }
+    });
+    
+    commands.entity(panel_entity).despawn_related::<Children>();
+    commands.entity(panel_entity).with_children(|panel| {
+        panel.spawn((
+            Text::new(format!("=== EMPIRE: {} cities (F8 to close) ===", cities.len())),
+            TextFont {
+                font_size: 12.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.9, 0.9, 0.7)),
+        ));
+        
+        panel
+            .spawn(Node {
+                flex_direction: FlexDirection::Row,
+                ..default()
+            })
+            .with_children(|header| {
+                for column in OverviewColumn::ALL {
+                    let arrow = match (sort.column == column, sort.descending) {
+                        (true, false) => " ▲",
+                        (true, true) => " ▼",
+                        (false, _) => "",
+                    };
+                    header
+                        .spawn((
+                            Button,
+                            OverviewHeaderButton(column),
+                            BackgroundColor(ROW_COLOR),
+                            Node {
+                                width: Val::Px(column.width()),
+                                ..default()
+                            },
+                        ))
+                        .with_child(cell(format!("{}{}", column.get_name(), arrow), column.width(), Color::srgb(0.9, 0.9, 0.7)));
+                }
+                header.spawn(cell("Status".to_string(), STATUS_WIDTH, Color::srgb(0.9, 0.9, 0.7)));
+            });
+        
+        for (city_entity, city) in &cities {
+            let (cells, status, warning) = overview_cells(city);
+            panel
+                .spawn((
+                    Button,
+                    OverviewCityRow {
+                        city: *city_entity,
+                        hex_coord: city.hex_coord,
+                    },
+                    BackgroundColor(ROW_COLOR),
+                    Node {
+                        flex_direction: FlexDirection::Row,
+                        padding: UiRect::vertical(Val::Px(1.0)),
+                        ..default()
+                    },
+                ))
+                .with_children(|row| {
+                    for (column, text) in OverviewColumn::ALL.into_iter().zip(cells) {
+                        row.spawn(cell(text, column.width(), Color::srgb(0.8, 0.8, 0.8)));
+                    }
+                    let status_color = if warning { WARNING_COLOR } else { Color::srgb(0.8, 0.8, 0.8) };
+                    row.spawn(cell(status, STATUS_WIDTH, status_color));
+                });
+        }
+    });
+}
+
+// Header clicks sort the overview; row clicks center the map on the city and open its panel
+pub fn empire_overview_interaction_system(
+    header_query: Query<(&Interaction, &OverviewHeaderButton), Changed<Interaction>>,
+    mut row_query: Query<(&Interaction, &OverviewCityRow, &mut BackgroundColor), Changed<Interaction>>,
+    mut camera_tween: ResMut<CameraTween>,
+    city_query: Query<&City>,
+    mut sort: ResMut<OverviewSort>,
+    mut city_selection: ResMut<CitySelection>,
+    mut unit_selection: ResMut<UnitSelection>,
+) {
+    for (interaction, header) in header_query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        if sort.column == header.0 {
+            sort.descending = !sort.descending;
+        } else {
+            sort.column = header.0;
+            sort.descending = header.0 != OverviewColumn::Name;
+        }
+    }
+    
+    for (interaction, row, mut background) in row_query.iter_mut() {
+        match interaction {
+            Interaction::Pressed => {
+                // The row may outlive a city captured or razed this frame
+                if city_query.get(row.city).is_err() {
+                    continue;
+                }
+                city_selection.selected_city = Some(row.city);
+                unit_selection.selected_unit = None;
+                camera_tween.center_on(row.hex_coord);
+            }
+            Interaction::Hovered => background.0 = ROW_HOVER_COLOR,
+            Interaction::None => background.0 = ROW_COLOR,
+        }
+    }
+}
@@ -0,0 +1,215 @@
+use bevy::prelude::*;
+use crate::game::camera_zoom::CameraTween;
+use crate::game::cities::{City, CitySelection};
+use crate::game::civilization::CivilizationManager;
+use crate::game::game_initialization::{EndTurnEvent, GamePhase, GameSettings, GameState, units_needing_orders};
+use crate::game::units::{Unit, UnitSelection};
+use crate::game::input_map::Action;
+use super::production_prompt::ProductionPrompt;
+use super::tooltips::TooltipSource;
+
+const READY_COLOR: Color = Color::srgba(0.15, 0.45, 0.15, 0.95);
+const ORDERS_COLOR: Color = Color::srgba(0.65, 0.45, 0.1, 0.95);
+const PRODUCTION_COLOR: Color = Color::srgba(0.15, 0.3, 0.6, 0.95);
+const WAITING_COLOR: Color = Color::srgba(0.2, 0.2, 0.2, 0.9);
+const TEXT_COLOR: Color = Color::srgb(1.0, 1.0, 0.9);
+const WAITING_TEXT_COLOR: Color = Color::srgb(0.6, 0.6, 0.6);
+
+// Frames of the spinner shown while the AI takes its turn, and how many go by a second
+const SPINNER: [&str; 4] = ["|", "/", "-", "\\"];
+const SPINNER_FRAMES_PER_SECOND: f32 = 8.0;
+
+/// Bottom-right button that ends the turn, or says what's still to be done first
+#[derive(Component)]
+pub struct EndTurnButton;
+
+// What the button offers, worked out fresh each frame
+#[derive(Clone, Copy, PartialEq)]
+enum EndTurnStatus {
+    Ready,                  // Nothing pending; ends the turn
+    UnitsNeedOrders(usize), // Held by the end-turn guard; selects the next waiting unit
+    ChooseProduction,       // A city has nothing to build; opens it
+    Waiting,                // Someone else's turn; does nothing
+}
+
+impl EndTurnStatus {
+    fn label(self, elapsed_secs: f32) -> String {
+        match self {
+            EndTurnStatus::Ready => "End Turn".to_string(),
+            EndTurnStatus::UnitsNeedOrders(count) => format!("Units need orders ({})", count),
+            EndTurnStatus::ChooseProduction => "Choose production".to_string(),
+            EndTurnStatus::Waiting => {
+                let frame = (elapsed_secs * SPINNER_FRAMES_PER_SECOND) as usize % SPINNER.len();
+                format!("{} AI thinking...", SPINNER[frame])
+            }
+        }
+    }
+    
+    fn colors(self) -> (Color, Color) {
+        match self {
+            EndTurnStatus::Ready => (READY_COLOR, TEXT_COLOR),
+            EndTurnStatus::UnitsNeedOrders(_) => (ORDERS_COLOR, TEXT_COLOR),
+            EndTurnStatus::ChooseProduction => (PRODUCTION_COLOR, TEXT_COLOR),
+            EndTurnStatus::Waiting => (WAITING_COLOR, WAITING_TEXT_COLOR),
+        }
+    }
+}
+
+pub fn setup_end_turn_button(mut commands: Commands) {
+    // End Turn button (bottom right, under the game status)
+    commands
+        .spawn((
+            EndTurnButton,
+            Button,
+            TooltipSource::Hotkey(Action::EndTurn, "Ends your turn, or shows what still needs doing first"),
+            BackgroundColor(WAITING_COLOR),
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(10.0),
+                right: Val::Px(10.0),
+                width: Val::Px(250.0),
+                height: Val::Px(36.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                display: Display::None,
+                ..default()
+            },
+        ))
+        .with_child((
+            Text::new(""),
+            TextFont {
+                font_size: 16.0,
+                ..default()
+            },
+            TextColor(WAITING_TEXT_COLOR),
+        ));
+}
+
+// Units waiting for orders and cities waiting for a build
+type PendingWork<'a> = (Vec<(Entity, &'a Unit)>, Vec<(Entity, &'a City)>);
+
+// The player's units waiting for orders, and idle cities they haven't put off until
+// next turn, both oldest first
+fn pending_work<'a>(
+    player_id: u32,
+    unit_query: &'a Query<(Entity, &Unit)>,
+    city_query: &'a Query<(Entity, &City)>,
+    prompt: &ProductionPrompt,
+) -> PendingWork<'a> {
+    let units = units_needing_orders(player_id, unit_query.iter());
+    let mut cities: Vec<(Entity, &City)> = city_query
+        .iter()
+        .filter(|(entity, city)| city.civilization_id == player_id && city.needs_production() && !prompt.is_deferred(*entity))
+        .collect();
+    cities.sort_by_key(|(entity, _)| *entity);
+    (units, cities)
+}
+
+fn end_turn_status(
+    civ_manager: &CivilizationManager,
+    game_state: &GameState,
+    settings: &GameSettings,
+    unit_query: &Query<(Entity, &Unit)>,
+    city_query: &Query<(Entity, &City)>,
+    prompt: &ProductionPrompt,
+) -> Option<EndTurnStatus> {
+    let player = civ_manager.get_player_civilization()?;
+    if game_state.current_phase != GamePhase::PlayerTurn || civ_manager.current_turn_civ != player.id {
+        return Some(EndTurnStatus::Waiting);
+    }
+    let (units, cities) = pending_work(player.id, unit_query, city_query, prompt);
+    Some(if settings.require_orders && !units.is_empty() {
+        EndTurnStatus::UnitsNeedOrders(units.len())
+    } else if !cities.is_empty() {
+        EndTurnStatus::ChooseProduction
+    } else {
+        EndTurnStatus::Ready
+    })
+}
+
+// Shows what the button will do once the frame's input has been dealt with: after the
+// turn has advanced, units have moved and cities have picked their builds. Hidden when
+// no one is playing at this screen.
+#[allow(clippy::too_many_arguments)]
+pub fn update_end_turn_button(
+    mut button_query: Query<(&mut Node, &mut BackgroundColor, &Children, &Interaction), With<EndTurnButton>>,
+    mut text_query: Query<(&mut Text, &mut TextColor)>,
+    unit_query: Query<(Entity, &Unit)>,
+    city_query: Query<(Entity, &City)>,
+    civ_manager: Res<CivilizationManager>,
+    game_state: Res<GameState>,
+    settings: Res<GameSettings>,
+    prompt: Res<ProductionPrompt>,
+    time: Res<Time>,
+) {
+    let Ok((mut node, mut background, children, interaction)) = button_query.single_mut() else { return };
+    let status = end_turn_status(&civ_manager, &game_state, &settings, &unit_query, &city_query, &prompt);
+    let display = if status.is_some() { Display::Flex } else { Display::None };
+    if node.display != display {
+        node.display = display;
+    }
+    let Some(status) = status else { return };
+    
+    let (mut fill, color) = status.colors();
+    if *interaction != Interaction::None && status != EndTurnStatus::Waiting {
+        fill = fill.lighter(0.1);
+    }
+    if background.0 != fill {
+        background.0 = fill;
+    }
+    let Some(&label_entity) = children.first() else { return };
+    let Ok((mut text, mut text_color)) = text_query.get_mut(label_entity) else { return };
+    let label = status.label(time.elapsed_secs());
+    if **text != label {
+        **text = label;
+    }
+    if text_color.0 != color {
+        text_color.0 = color;
+    }
+}
+
+// A click ends the turn through the same request as SPACE, or goes to whatever is
+// holding it up: the next unit waiting for orders after the selected one, or the first
+// idle city, opened with its "choose later" option
+#[allow(clippy::too_many_arguments)]
+pub fn end_turn_button_system(
+    button_query: Query<&Interaction, (Changed<Interaction>, With<EndTurnButton>)>,
+    unit_query: Query<(Entity, &Unit)>,
+    city_query: Query<(Entity, &City)>,
+    civ_manager: Res<CivilizationManager>,
+    game_state: Res<GameState>,
+    settings: Res<GameSettings>,
+    mut prompt: ResMut<ProductionPrompt>,
+    mut unit_selection: ResMut<UnitSelection>,
+    mut city_selection: ResMut<CitySelection>,
+    mut camera_tween: ResMut<CameraTween>,
+    mut end_turn_events: EventWriter<EndTurnEvent>,
+) {
+    if !button_query.iter().any(|interaction| *interaction == Interaction::Pressed) {
+        return;
+    }
+    let Some(player) = civ_manager.get_player_civilization() else { return };
+    match end_turn_status(&civ_manager, &game_state, &settings, &unit_query, &city_query, &prompt) {
+        Some(EndTurnStatus::Ready) => {
+            end_turn_events.write(EndTurnEvent);
+        }
+        Some(EndTurnStatus::UnitsNeedOrders(_)) => {
+            let (units, _) = pending_work(player.id, &unit_query, &city_query, &prompt);
+            let after_selected = units
+                .iter()
+                .position(|(entity, _)| Some(*entity) == unit_selection.selected_unit)
+                .map_or(0, |index| (index + 1) % units.len());
+            let Some(&(next, unit)) = units.get(after_selected) else { return };
+            unit_selection.selected_unit = Some(next);
+            camera_tween.center_on(unit.hex_coord);
+        }
+        Some(EndTurnStatus::ChooseProduction) => {
+            let (_, cities) = pending_work(player.id, &unit_query, &city_query, &prompt);
+            let Some(&(first, city)) = cities.first() else { return };
+            city_selection.selected_city = Some(first);
+            prompt.ask(first);
+            camera_tween.center_on(city.hex_coord);
+        }
+        Some(EndTurnStatus::Waiting) | None => {}
+    }
+}
@@ -0,0 +1,77 @@
+use bevy::prelude::*;
+use bevy::ecs::system::SystemParam;
+use serde::{Deserialize, Serialize};
+use crate::game::camera_zoom::CameraTween;
+use crate::game::hex::HexCoord;
+use crate::game::map::HEX_SIZE;
+use crate::game::notifications::MapEvent;
+
+// How long the ring around a focused tile pulses, and how many times it does
+const PULSE_SECONDS: f32 = 1.5;
+const PULSE_COUNT: f32 = 3.0;
+const PULSE_COLOR: Color = Color::srgb(1.0, 0.95, 0.5);
+
+/// What the player wants done when an event of a kind happens on the map
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AlertStyle {
+    Ignore,
+    Toast,
+    Jump,
+}
+
+impl AlertStyle {
+    pub const ALL: [AlertStyle; 3] = [AlertStyle::Ignore, AlertStyle::Toast, AlertStyle::Jump];
+    
+    pub fn default_for(event: MapEvent) -> Self {
+        match event {
+            MapEvent::CityCaptured => AlertStyle::Jump,
+            _ => AlertStyle::Toast,
+        }
+    }
+    
+    pub fn get_name(self) -> &'static str {
+        match self {
+            AlertStyle::Ignore => "ignore",
+            AlertStyle::Toast => "message",
+            AlertStyle::Jump => "message and jump",
+        }
+    }
+}
+
+/// The tile the camera was last sent to and when, while its ring is still pulsing
+#[derive(Resource, Default)]
+pub struct FocusPulse {
+    target: Option<(HexCoord, f32)>,
+}
+
+/// Sends the camera to where something happened on the map
+#[derive(SystemParam)]
+pub struct EventCamera<'w> {
+    camera_tween: ResMut<'w, CameraTween>,
+    pulse: ResMut<'w, FocusPulse>,
+    time: Res<'w, Time>,
+}
+
+impl EventCamera<'_> {
+    // Glides the camera to the tile and pulses a ring on it so the eye lands in the right
+    // place. There's no fog of war, so every tile is one the player can see; if hidden
+    // tiles are ever added, this is where events on them have to be turned away.
+    pub fn focus_event(&mut self, coord: HexCoord) {
+        self.camera_tween.center_on(coord);
+        self.pulse.target = Some((coord, self.time.elapsed_secs()));
+    }
+}
+
+// Rings the focused tile a few times, each ring growing outward as it fades
+pub fn draw_focus_pulse(mut gizmos: Gizmos, mut pulse: ResMut<FocusPulse>, time: Res<Time>) {
+    let Some((coord, started)) = pulse.target else { return };
+    let age = time.elapsed_secs() - started;
+    if age >= PULSE_SECONDS {
+        pulse.target = None;
+        return;
+    }
+    let phase = (age / PULSE_SECONDS * PULSE_COUNT).fract();
+    let center = coord.to_world_pos(HEX_SIZE);
+    gizmos.circle_2d(center, HEX_SIZE * (0.5 + 0.6 * phase), PULSE_COLOR.with_alpha(1.0 - phase));
+    gizmos.circle_2d(center, HEX_SIZE * 0.5, PULSE_COLOR.with_alpha(1.0 - age / PULSE_SECONDS));
+}
@@ -0,0 +1,223 @@
+use bevy::prelude::*;
+use crate::game::civilization::{Civilization, CivilizationManager};
+use crate::game::game_initialization::AppState;
+use crate::game::replay::{ReplayAction, ReplayLog};
+use crate::game::statistics::StatsHistory;
+use crate::game::victory::{GameResult, VictoryType};
+use super::demographics::spawn_score_graph;
+use super::game_panels::UIState;
+use super::replay_viewer::describe;
+
+const BUTTON_COLOR: Color = Color::srgba(0.25, 0.25, 0.3, 0.9);
+const BUTTON_HOVER_COLOR: Color = Color::srgba(0.4, 0.4, 0.55, 0.9);
+const WINNER_COLOR: Color = Color::srgb(1.0, 0.85, 0.3);
+const ELIMINATED_COLOR: Color = Color::srgb(0.5, 0.5, 0.5);
+const HEADER_COLOR: Color = Color::srgb(0.9, 0.9, 0.7);
+// Lines of the recap's timeline; longer games are sampled evenly across their length
+const TIMELINE_LINES: usize = 12;
+
+#[derive(Component)]
+pub struct GameOverScreen;
+
+#[derive(Component, Clone, Copy, PartialEq)]
+pub enum GameOverButton {
+    Replay,
+    NewGame,
+    Quit,
+}
+
+pub fn setup_game_over_screen(mut commands: Commands) {
+    // Results screen (center, shown once the game ends)
+    commands.spawn((
+        GameOverScreen,
+        Interaction::default(),
+        BackgroundColor(Color::srgba(0.03, 0.03, 0.08, 0.96)),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(40.0),
+            left: Val::Px(0.0),
+            right: Val::Px(0.0),
+            margin: UiRect::horizontal(Val::Auto),
+            width: Val::Px(660.0),
+            max_width: Val::Percent(96.0),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            padding: UiRect::all(Val::Px(12.0)),
+            row_gap: Val::Px(6.0),
+            display: Display::None,
+            ..default()
+        },
+    ));
+}
+
+fn text(value: String, font_size: f32, color: Color) -> impl Bundle {
+    (
+        Text::new(value),
+        TextFont {
+            font_size,
+            ..default()
+        },
+        TextColor(color),
+    )
+}
+
+// One line of the score table: a wide name column followed by numbers
+fn score_row(parent: &mut ChildSpawnerCommands, cells: [String; 8], color: Color) {
+    parent
+        .spawn(Node {
+            flex_direction: FlexDirection::Row,
+            ..default()
+        })
+        .with_children(|row| {
+            for (column, cell) in cells.into_iter().enumerate() {
+                let width = if column == 0 { 220.0 } else { 52.0 };
+                row.spawn(Node {
+                    width: Val::Px(width),
+                    ..default()
+                })
+                .with_child(text(cell, 12.0, color));
+            }
+        });
+}
+
+// The game's turning points from the replay log, each with the turn it happened on
+fn timeline(log: &ReplayLog, civ_manager: &CivilizationManager) -> Vec<String> {
+    let mut turn = 0;
+    let mut events = Vec::new();
+    for action in &log.actions {
+        match action {
+            ReplayAction::Start { turn: start, .. } | ReplayAction::TurnStarted { turn: start, .. } => turn = *start,
+            action if action.is_major() => events.push(format!("Turn {}: {}", turn, describe(action, civ_manager))),
+            _ => {}
+        }
+    }
+    if events.len() <= TIMELINE_LINES {
+        return events;
+    }
+    (0..TIMELINE_LINES).map(|line| events[line * (events.len() - 1) / (TIMELINE_LINES - 1)].clone()).collect()
+}
+
+// Fills in and shows the results screen when the game ends, closing every other panel
+pub fn show_game_over_screen(
+    mut commands: Commands,
+    result: Res<GameResult>,
+    mut screen_query: Query<(Entity, &mut Node), With<GameOverScreen>>,
+    mut ui_state: ResMut<UIState>,
+    civ_manager: Res<CivilizationManager>,
+    replay: Res<ReplayLog>,
+    history: Res<StatsHistory>,
+) {
+    let Ok((screen_entity, mut screen_node)) = screen_query.single_mut() else { return };
+    
+    ui_state.show_unit_list = false;
+    ui_state.show_empire_overview = false;
+    ui_state.show_resources = false;
+    ui_state.show_tech_tree = false;
+    ui_state.show_diplomacy = false;
+    ui_state.show_demographics = false;
+    ui_state.show_combat_log = false;
+    ui_state.show_turn_report = false;
+    ui_state.show_turn_summary = false;
+    ui_state.show_debug_overlay = false;
+    ui_state.show_glyph_sheet = false;
+    screen_node.display = Display::Flex;
+    
+    let winner_name = civ_manager.get_civilization(result.winner).map_or("Unknown", |civ| civ.name.as_str());
+    let headline = match result.victory {
+        VictoryType::Defeat => format!("DEFEAT - your civilization has been destroyed. {} leads the world.", winner_name),
+        _ if civ_manager.get_civilization(result.winner).is_some_and(|civ| civ.is_player) => format!("VICTORY - {} ({})", winner_name, result.victory.get_name()),
+        _ => format!("{} wins a {}", winner_name, result.victory.get_name()),
+    };
+    let eliminated = result.scores.iter().filter(|score| score.eliminated).count();
+    let cities: usize = result.scores.iter().map(|score| score.cities).sum();
+    let population: u32 = result.scores.iter().map(|score| score.population).sum();
+    let recap = format!(
+        "The game lasted {} turns. {} civilizations were destroyed; {} cities with {} citizens remain.",
+        result.final_turn, eliminated, cities, population
+    );
+    let events = timeline(&replay, &civ_manager);
+    let mut civs: Vec<&Civilization> = civ_manager.civilizations.values().collect();
+    civs.sort_by_key(|civ| civ.id);
+    
+    commands.entity(screen_entity).despawn_related::<Children>();
+    commands.entity(screen_entity).with_children(|screen| {
+        screen.spawn(text("=== GAME OVER ===".to_string(), 18.0, Color::srgb(0.9, 0.9, 0.7)));
+        screen.spawn(text(headline, 15.0, WINNER_COLOR));
+        screen.spawn(text(recap, 12.0, Color::srgb(0.8, 0.8, 0.8)));
+        
+        score_row(screen, ["Civilization", "Cities", "Pop", "Land", "Techs", "Wonders", "Gold", "Score"].map(String::from), HEADER_COLOR);
+        for score in &result.scores {
+            let color = if score.civ_id == result.winner {
+                WINNER_COLOR
+            } else if score.eliminated {
+                ELIMINATED_COLOR
+            } else {
+                Color::srgb(0.85, 0.85, 0.85)
+            };
+            let name = if score.eliminated { format!("{} (destroyed)", score.name) } else { score.name.clone() };
+            let cells = [
+                name,
+                score.cities.to_string(),
+                score.population.to_string(),
+                score.territory.to_string(),
+                score.technologies.to_string(),
+                score.wonders.to_string(),
+                format!("{:.0}", score.gold),
+                format!("{:.0}", score.score),
+            ];
+            score_row(screen, cells, color);
+        }
+        
+        if !events.is_empty() {
+            screen.spawn(text("Timeline".to_string(), 13.0, HEADER_COLOR));
+            for event in events {
+                screen.spawn(text(event, 11.0, Color::srgb(0.8, 0.8, 0.8)));
+            }
+        }
+        spawn_score_graph(screen, &history, &civs);
+        
+        screen
+            .spawn(Node {
+                column_gap: Val::Px(12.0),
+                margin: UiRect::top(Val::Px(8.0)),
+                ..default()
+            })
+            .with_children(|buttons| {
+                for (button, label) in [(GameOverButton::Replay, "Watch Replay"), (GameOverButton::NewGame, "New Game"), (GameOverButton::Quit, "Quit")] {
+                    buttons
+                        .spawn((
+                            Button,
+                            button,
+                            BackgroundColor(BUTTON_COLOR),
+                            Node {
+                                padding: UiRect::axes(Val::Px(12.0), Val::Px(4.0)),
+                                ..default()
+                            },
+                        ))
+                        .with_child(text(label.to_string(), 14.0, Color::WHITE));
+                }
+            });
+    });
+}
+
+// Watch Replay plays the game back; New Game goes back to the setup screen for a fresh
+// world; Quit closes the window
+pub fn game_over_button_system(
+    mut button_query: Query<(&Interaction, &GameOverButton, &mut BackgroundColor), Changed<Interaction>>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    for (interaction, button, mut background) in button_query.iter_mut() {
+        match interaction {
+            Interaction::Pressed => match button {
+                GameOverButton::Replay => next_state.set(AppState::Replay),
+                GameOverButton::NewGame => next_state.set(AppState::Setup),
+                GameOverButton::Quit => {
+                    exit.write(AppExit::Success);
+                }
+            },
+            Interaction::Hovered => background.0 = BUTTON_HOVER_COLOR,
+            Interaction::None => background.0 = BUTTON_COLOR,
+        }
+    }
+}
@@ -1,8 +1,26 @@
 use bevy::prelude::*;
-use crate::game::units::{Unit, UnitSelection};
-use crate::game::cities::City;
-use crate::game::civilization::CivilizationManager;
-use crate::game::game_initialization::{GameState, GamePhase};
+use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
+use crate::game::hex::HexCoord;
+use crate::game::map::HEX_SIZE;
+use crate::game::camera_zoom::CameraTween;
+use crate::game::units::{Unit, UnitOrder, UnitSelection};
+use crate::game::cities::{begin_city_rename, City, CITY_MAX_HIT_POINTS, CityFocus, CitySelection, ProductionItem, SpecialistType, GreatPersonType, CityEventWriters, WonderRegistry};
+use crate::game::civilization::{CivilizationManager, UNKNOWN_PLAYER};
+use crate::game::economy::civ_finances;
+use crate::game::fonts::GameFonts;
+use super::research::{player_science_per_turn, research_turns};
+use super::diplomacy::DealComposer;
+use super::notification_feed::NotificationHistory;
+use super::hotseat::HotseatViews;
+use super::turn_report::TurnReport;
+use super::production_prompt::ProductionPrompt;
+use super::user_settings::UserSettings;
+use super::panels::{PanelSpec, spawn_panel};
+use crate::game::input::{InputMode, TextEntry};
+use crate::game::unit_actions::UnitAction;
+use crate::game::input_map::{Action, InputMap};
+use crate::game::game_initialization::{GameState, GamePhase, GameSettings};
+use crate::game::statistics::{MilitaryComparison, military_strength, shows_everything};
 
 #[derive(Component)]
 pub struct GameStatusPanel;
@@ -16,11 +34,84 @@ pub struct HotkeysPanel;
 #[derive(Component)]
 pub struct SelectedUnitInfo;
 
-#[derive(Resource)]
+#[derive(Component)]
+pub struct UnitListPanel;
+
+// One clickable row of the unit list
+#[derive(Component)]
+pub struct UnitListRow {
+    pub unit: Entity,
+    pub hex_coord: HexCoord,
+}
+
+// World-space marker showing the hex of the hovered unit list row
+#[derive(Component)]
+pub struct UnitListHighlight;
+
+// What a unit list row displays; the list is only rebuilt when these change
+#[derive(Clone, PartialEq)]
+pub struct UnitListEntry {
+    unit: Entity,
+    hex_coord: HexCoord,
+    needs_orders: bool,
+    label: String,
+}
+
+#[derive(Component)]
+pub struct CityPanel;
+
+// Clickable entry of the city production panel
+#[derive(Component)]
+pub struct CityPanelButton {
+    pub action: CityPanelAction,
+}
+
+#[derive(Clone, PartialEq)]
+pub enum CityPanelAction {
+    Produce(ProductionItem), // Click builds now, Shift+click appends to the queue
+    Cancel,
+    SetFocus(CityFocus),
+    OpenTradeRoute(Entity),
+    CloseTradeRoute(Entity),
+    Buy,
+    AddSpecialist(SpecialistType),
+    RemoveSpecialist(SpecialistType),
+    AutoSpecialists,
+    AutoTiles,
+    QueueEntry(usize),       // Click removes, Shift+click moves up, Ctrl+click moves down
+    Rename,
+    Annex,
+    Raze,
+    ChooseLater, // Leaves a prompted city idle; handled by choose_later_button_system
+}
+
+// Heads the greyed-out list of items the city can't produce yet
+const UNAVAILABLE_HEADER: &str = "-- Unavailable --";
+
+// Text of a city panel row and the action it triggers, if it's a button
+type CityPanelRow = (String, Option<CityPanelAction>);
+
+const UNIT_ROW_COLOR: Color = Color::srgba(0.15, 0.15, 0.2, 0.8);
+const UNIT_ROW_HOVER_COLOR: Color = Color::srgba(0.3, 0.3, 0.45, 0.9);
+
+#[derive(Resource, Clone)]
 pub struct UIState {
     pub show_hotkeys: bool,
     pub show_unit_status: bool,
     pub show_game_status: bool,
+    pub show_unit_list: bool,
+    pub show_empire_overview: bool,
+    pub show_tech_tree: bool,
+    pub show_diplomacy: bool,
+    pub show_demographics: bool,
+    pub show_resources: bool,
+    pub show_combat_log: bool,
+    pub show_combat_effects: bool,
+    pub show_notification_history: bool,
+    pub show_turn_report: bool,
+    pub show_turn_summary: bool,
+    pub show_debug_overlay: bool,
+    pub show_glyph_sheet: bool,
 }
 
 impl Default for UIState {
@@ -29,14 +120,46 @@ impl Default for UIState {
             show_hotkeys: true,
             show_unit_status: true,
             show_game_status: true,
+            show_unit_list: false,
+            show_empire_overview: false,
+            show_tech_tree: false,
+            show_diplomacy: false,
+            show_demographics: false,
+            show_resources: false,
+            show_combat_log: false,
+            show_combat_effects: true,
+            show_notification_history: false,
+            show_turn_report: false,
+            show_turn_summary: false,
+            show_debug_overlay: false,
+            show_glyph_sheet: false,
         }
     }
 }
 
+// Panels are spawned afresh for every game, so their toggles, the deal being put
+// together, the message history and each hotseat player's view go back to how a
+// fresh launch has them
+pub fn reset_ui_state(mut commands: Commands, user_settings: Res<UserSettings>) {
+    commands.insert_resource(user_settings.ui_state());
+    commands.insert_resource(DealComposer::default());
+    commands.insert_resource(NotificationHistory::default());
+    commands.insert_resource(HotseatViews::default());
+    commands.insert_resource(TurnReport::default());
+    commands.insert_resource(ProductionPrompt::default());
+}
+
 // System to setup improved UI panels
-pub fn setup_ui_panels(mut commands: Commands) {
-    // Game Status Panel (bottom right)
-    commands.spawn((
+pub fn setup_ui_panels(mut commands: Commands, fonts: Res<GameFonts>) {
+    // Game Status window (bottom right, above the End Turn button)
+    let status_body = spawn_panel(&mut commands, PanelSpec {
+        title: "Game Status",
+        place: UiRect { right: Val::Px(10.0), bottom: Val::Px(56.0), ..default() },
+        width: 250.0,
+        max_height: 420.0,
+        shown: |ui_state| &mut ui_state.show_game_status,
+    });
+    commands.entity(status_body).with_child((
         GameStatusPanel,
         Text::new(""),
         TextLayout::new_with_justify(JustifyText::Right),
@@ -45,16 +168,9 @@ pub fn setup_ui_panels(mut commands: Commands) {
             ..default()
         },
         TextColor(Color::srgb(0.9, 0.9, 0.7)),
-        Node {
-            position_type: PositionType::Absolute,
-            bottom: Val::Px(10.0),
-            right: Val::Px(10.0),
-            width: Val::Px(250.0),
-            ..default()
-        },
     ));
     
-    // Selected Unit Info Panel (bottom center)
+    // Selected Unit Info Panel (bottom center, above the unit action bar)
     commands.spawn((
         SelectedUnitInfo,
         Text::new(""),
@@ -66,10 +182,12 @@ pub fn setup_ui_panels(mut commands: Commands) {
         TextColor(Color::srgb(1.0, 1.0, 0.8)),
         Node {
             position_type: PositionType::Absolute,
-            bottom: Val::Px(10.0),
-            left: Val::Percent(50.0),
-            margin: UiRect::left(Val::Px(-200.0)), // Center the 400px wide panel
+            bottom: Val::Px(64.0),
+            left: Val::Px(0.0),
+            right: Val::Px(0.0),
+            margin: UiRect::horizontal(Val::Auto),
             width: Val::Px(400.0),
+            max_width: Val::Percent(96.0),
             ..default()
         },
     ));
@@ -92,6 +210,54 @@ pub fn setup_ui_panels(mut commands: Commands) {
             ..default()
         },
     ));
+    
+    // Unit List Panel (right side, toggled with F7)
+    commands.spawn((
+        UnitListPanel,
+        Interaction::default(),
+        ScrollPosition::default(),
+        BackgroundColor(Color::srgba(0.05, 0.05, 0.1, 0.85)),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(250.0),
+            right: Val::Px(10.0),
+            width: Val::Px(320.0),
+            max_height: Val::Px(360.0),
+            flex_direction: FlexDirection::Column,
+            overflow: Overflow::scroll_y(),
+            padding: UiRect::all(Val::Px(4.0)),
+            row_gap: Val::Px(2.0),
+            display: Display::None,
+            ..default()
+        },
+    ));
+    
+    // City Production Panel (left, next to the hotkeys; opens when a city is selected)
+    commands.spawn((
+        CityPanel,
+        Interaction::default(),
+        BackgroundColor(Color::srgba(0.05, 0.05, 0.1, 0.85)),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(100.0),
+            left: Val::Px(220.0),
+            width: Val::Px(300.0),
+            flex_direction: FlexDirection::Column,
+            padding: UiRect::all(Val::Px(4.0)),
+            row_gap: Val::Px(2.0),
+            display: Display::None,
+            ..default()
+        },
+    ));
+    
+    commands.spawn((
+        UnitListHighlight,
+        Text2d::new("◇"),
+        TextColor(Color::srgb(1.0, 1.0, 0.3)),
+        fonts.symbol(28.0),
+        Transform::from_xyz(0.0, 0.0, 2.5),
+        Visibility::Hidden,
+    ));
 }
 
 // System to update game status panel
@@ -99,66 +265,164 @@ pub fn update_game_status_panel(
     mut status_query: Query<&mut Text, With<GameStatusPanel>>,
     civ_manager: Res<CivilizationManager>,
     game_state: Res<GameState>,
-    unit_query: Query<&Unit>,
     city_query: Query<&City>,
+    unit_query: Query<&Unit>,
     ui_state: Res<UIState>,
 ) {
-    if !ui_state.show_game_status || !game_state.is_initialized {
+    if !ui_state.show_game_status {
         return;
     }
     
     let Ok(mut text) = status_query.single_mut() else { return };
     
-    let current_civ_name = civ_manager.get_civilization(civ_manager.current_turn_civ)
-        .map(|c| c.name.clone())
-        .unwrap_or_else(|| "Unknown".to_string());
+    let current_civ_name = civ_manager.civ_name(civ_manager.current_turn_civ);
     
     let phase_text = match game_state.current_phase {
         GamePhase::PlayerTurn => "Your Turn",
         GamePhase::AITurn(_) => "AI Turn",
+        GamePhase::BarbarianTurn => "Barbarian Turn",
         _ => "Processing",
     };
     
-    // Count units and cities by civilization
-    let mut civ_stats = std::collections::HashMap::new();
-    
-    for unit in unit_query.iter() {
-        let entry = civ_stats.entry(unit.civilization_id).or_insert((0, 0, 0.0)); // (units, cities, military_power)
-        entry.0 += 1;
-        entry.2 += unit.get_combat_strength(true) as f32;
-    }
-    
-    for city in city_query.iter() {
-        let entry = civ_stats.entry(city.civilization_id).or_insert((0, 0, 0.0));
-        entry.1 += 1;
-    }
-    
     let mut status_text = format!(
-        "=== GAME STATUS ===\nTurn: {}\nActive: {} ({})\n\n",
+        "Turn: {}\nDifficulty: {}\nSpeed: {}\nActive: {} ({})\n",
         game_state.game_turn,
+        civ_manager.difficulty.get_name(),
+        civ_manager.speed.get_name(),
         current_civ_name,
         phase_text
     );
     
-    // Show civilization summary
-    status_text.push_str("Civilizations:\n");
-    for (civ_id, civ) in &civ_manager.civilizations {
-        let (units, cities, military) = civ_stats.get(civ_id).unwrap_or(&(0, 0, 0.0));
-        let status_indicator = if *civ_id == civ_manager.current_turn_civ { "►" } else { " " };
+    if let Some(player_civ) = civ_manager.get_viewed_civilization() {
+        // Current research and how long it will take
+        let research = match player_civ.current_research {
+            Some((tech, progress)) => {
+                let science = player_science_per_turn(player_civ.id, &city_query);
+                match research_turns(player_civ, tech, progress, science, civ_manager.speed) {
+                    Some(turns) => format!("{}: {} turns", tech.get_name(), turns),
+                    None => format!("{}: no science", tech.get_name()),
+                }
+            }
+            None => "None (T to choose)".to_string(),
+        };
+        status_text.push_str(&format!("\nEra: {}\nResearch: {}", player_civ.current_era().get_name(), research));
+        status_text.push_str(&format!("\nStrategic: {}", player_civ.describe_strategic_resources()));
         
+        // Where the gold goes, so a deficit is visible before it bites
+        let finances = civ_finances(player_civ, city_query.iter(), unit_query.iter());
         status_text.push_str(&format!(
-            "{} {}: {}C/{}U/{:.0}M\n",
-            status_indicator,
-            civ.name.chars().take(8).collect::<String>(), // Abbreviated name
-            cities,
-            units,
-            military
+            "\nGold: {:.0} ({:+.1}/turn)\n  Income {:.1}, buildings -{:.1}, units -{:.1}",
+            player_civ.gold,
+            finances.net(),
+            finances.income,
+            finances.building_upkeep,
+            finances.unit_upkeep
         ));
+        
+        // Progress toward the player's next Great Person of each type
+        let threshold = player_civ.great_person_threshold();
+        let progress: Vec<String> = GreatPersonType::ALL
+            .iter()
+            .map(|kind| {
+                let points = player_civ.great_people_points.get(kind).copied().unwrap_or(0.0);
+                format!("{} {:.0}", kind.unit_type().get_symbol(), points)
+            })
+            .collect();
+        status_text.push_str(&format!("\nGreat People (/{:.0}): {}\n", threshold, progress.join(" ")));
     }
+    status_text.push_str("\nCivilizations:");
     
     **text = status_text;
 }
 
+// One civilization's line in the status panel
+#[derive(Clone, PartialEq)]
+pub struct StatusCivLine {
+    text: String,
+    eliminated: bool,
+}
+
+// Lists every civilization under the status text, one span each so eliminated
+// civs can be greyed out; the spans are only rebuilt when a line changes. Other
+// civilizations' armies are sized up against the player's rather than counted, unless
+// the game shows everything.
+#[allow(clippy::too_many_arguments)]
+pub fn update_status_civ_list(
+    mut commands: Commands,
+    status_query: Query<(Entity, Ref<GameStatusPanel>)>,
+    civ_manager: Res<CivilizationManager>,
+    settings: Res<GameSettings>,
+    unit_query: Query<&Unit>,
+    city_query: Query<&City>,
+    ui_state: Res<UIState>,
+    mut shown_lines: Local<Vec<StatusCivLine>>,
+) {
+    let Ok((status_entity, panel)) = status_query.single() else { return };
+    // A new game's panel starts out empty
+    if panel.is_added() {
+        shown_lines.clear();
+    }
+    if !ui_state.show_game_status {
+        return;
+    }
+    
+    let player = civ_manager.get_player_civilization().filter(|_| !shows_everything(&settings, &civ_manager));
+    let player_military = player.map_or(0.0, |player| military_strength(player.id, unit_query.iter()));
+    let mut civs: Vec<_> = civ_manager.civilizations.values().collect();
+    civs.sort_by_key(|civ| civ.id);
+    let lines: Vec<StatusCivLine> = civs
+        .into_iter()
+        .map(|civ| {
+            let status_indicator = if civ.id == civ_manager.current_turn_civ { "►" } else { " " };
+            if !civ_manager.player_knows(civ.id) {
+                return StatusCivLine {
+                    text: format!("\n{} {}", status_indicator, UNKNOWN_PLAYER),
+                    eliminated: civ.eliminated,
+                };
+            }
+            let name = civ.name.chars().take(8).collect::<String>(); // Abbreviated name
+            if civ.eliminated {
+                return StatusCivLine {
+                    text: format!("\n  {}: destroyed", name),
+                    eliminated: true,
+                };
+            }
+            
+            let units = unit_query.iter().filter(|unit| unit.civilization_id == civ.id);
+            let military = military_strength(civ.id, unit_query.iter());
+            let cities = city_query.iter().filter(|city| city.civilization_id == civ.id).count();
+            let text = match player {
+                Some(player) if player.id != civ.id => {
+                    let army = MilitaryComparison::between(military, player_military);
+                    format!("\n{} {}: {}C, army {}", status_indicator, name, cities, army.get_name())
+                }
+                _ => format!("\n{} {}: {}C/{}U/{:.0}M", status_indicator, name, cities, units.count(), military),
+            };
+            StatusCivLine { text, eliminated: false }
+        })
+        .collect();
+    if *shown_lines == lines {
+        return;
+    }
+    
+    commands.entity(status_entity).despawn_related::<Children>();
+    commands.entity(status_entity).with_children(|status| {
+        for line in &lines {
+            let color = if line.eliminated { Color::srgb(0.45, 0.45, 0.45) } else { Color::srgb(0.9, 0.9, 0.7) };
+            status.spawn((
+                TextSpan::new(line.text.clone()),
+                TextFont {
+                    font_size: 12.0,
+                    ..default()
+                },
+                TextColor(color),
+            ));
+        }
+    });
+    
+    *shown_lines = lines;
+}
+
 // System to update selected unit info
 pub fn update_selected_unit_info(
     mut info_query: Query<&mut Text, With<SelectedUnitInfo>>,
@@ -166,9 +430,9 @@ pub fn update_selected_unit_info(
     unit_selection: Res<UnitSelection>,
     civ_manager: Res<CivilizationManager>,
     ui_state: Res<UIState>,
-    game_state: Res<GameState>,
+    input_mode: Res<InputMode>,
 ) {
-    if !ui_state.show_unit_status || !game_state.is_initialized {
+    if !ui_state.show_unit_status {
         return;
     }
     
@@ -176,16 +440,14 @@ pub fn update_selected_unit_info(
     
     if let Some(selected_entity) = unit_selection.selected_unit {
         if let Ok(unit) = unit_query.get(selected_entity) {
-            let civ_name = civ_manager.get_civilization(unit.civilization_id)
-                .map(|c| c.name.as_str())
-                .unwrap_or("Unknown");
+            let civ_name = civ_manager.known_civ_name(unit.civilization_id);
             
             let health_bar = create_health_bar(unit.health, unit.max_health);
             let experience_info = format!("XP: {}/10", unit.combat_experience % 10);
             
             let mut unit_info = format!(
                 "=== SELECTED UNIT ===\n{} ({}) at ({}, {})\n{} | MP: {}/{} | {}\n",
-                unit.unit_type.get_name(),
+                unit.title(),
                 civ_name,
                 unit.hex_coord.q,
                 unit.hex_coord.r,
@@ -207,87 +469,120 @@ pub fn update_selected_unit_info(
                 if unit.is_fortified {
                     unit_info.push_str(&format!("FORTIFIED (Turn {})\n", unit.fortification_turns));
                 }
+                if *input_mode == InputMode::AttackTargeting {
+                    unit_info.push_str("ATTACK MODE: click a target (Esc to cancel)\n");
+                }
             }
-            
-            // Add available actions
-            let mut actions = Vec::new();
-            if unit.movement_points > 0 && !unit.has_moved {
-                actions.push("Move");
-            }
-            if unit.can_attack && unit.movement_points > 0 && !unit.has_attacked {
-                actions.push("Attack (A)");
-            }
-            if unit.can_found_cities && unit.movement_points > 0 {
-                actions.push("Found City (F)");
-            }
-            if unit.can_build_improvements && unit.movement_points > 0 {
-                actions.push("Build (B)");
+            if unit.order == UnitOrder::Sentry {
+                unit_info.push_str("ON SENTRY\n");
             }
-            if unit.can_attack {
-                actions.push("Fortify (Shift+F)");
+            if let UnitOrder::Automate(task) = unit.order {
+                match task {
+                    Some(target) => unit_info.push_str(&format!("AUTOMATED: working ({}, {})\n", target.q, target.r)),
+                    None => unit_info.push_str("AUTOMATED: nothing safe to work\n"),
+                }
             }
-            actions.push("Skip (S)");
-            
-            if !actions.is_empty() {
-                unit_info.push_str("Actions: ");
-                unit_info.push_str(&actions.join(" | "));
-                unit_info.push('\n');
+            if *input_mode == InputMode::MoveTargeting {
+                unit_info.push_str("MOVE MODE: click where to go (Esc to cancel)\n");
             }
             
+            // The actions themselves are the buttons of the action bar below
             **text = unit_info;
         } else {
             **text = "".to_string();
         }
     } else {
-        **text = "No unit selected\nClick on a unit to select it".to_string();
+        **text = "No unit selected\nLeft click on a unit to select it".to_string();
     }
 }
 
+// One "KEY - Name" line per action, with the keys as they're bound now
+pub(super) fn hotkey_lines(input_map: &InputMap, actions: &[Action]) -> String {
+    actions
+        .iter()
+        .map(|action| format!("{} - {}\n", input_map.label(*action), action.get_name()))
+        .collect()
+}
+
 // System to update hotkeys panel
 pub fn update_hotkeys_panel(
     mut hotkeys_query: Query<&mut Text, With<HotkeysPanel>>,
     keyboard: Res<ButtonInput<KeyCode>>,
+    input_map: Res<InputMap>,
     mut ui_state: ResMut<UIState>,
-    game_state: Res<GameState>,
 ) {
-    if !game_state.is_initialized {
-        return;
-    }
-    
-    // Toggle hotkeys panel with H
-    if keyboard.just_pressed(KeyCode::KeyH) {
+    if input_map.just_pressed(Action::ToggleHelp, &keyboard) {
         ui_state.show_hotkeys = !ui_state.show_hotkeys;
     }
     
     let Ok(mut text) = hotkeys_query.single_mut() else { return };
     
     if ui_state.show_hotkeys {
+        // The same bindings the action bar's buttons show
+        let unit_actions: String = UnitAction::ALL
+            .iter()
+            .filter(|action| **action != UnitAction::Move)
+            .map(|action| format!("{} - {}\n", action.binding(&input_map), action.get_name()))
+            .collect();
+        let camera = [Action::PanUp, Action::PanLeft, Action::PanDown, Action::PanRight]
+            .map(|action| input_map.label(action))
+            .join("/");
         **text = format!(
             "=== HOTKEYS ===\n\
-            H - Toggle this help\n\
-            Click - Select/Move\n\
-            SPACE - End Turn\n\
-            N - Next Unit\n\
+            {} - Toggle this help\n\
+            {} (or hold {}) - Game guide\n\
+            Left Click - Select\n\
+            Right Click - Move/Attack\n\
+            Numpad / Alt+QEADZC - Step Unit\n\
+            {}\
             \n\
             === UNIT ACTIONS ===\n\
-            A - Attack\n\
-            F - Found City\n\
-            B - Build Improvement\n\
-            S - Skip Unit\n\
-            Shift+F - Fortify\n\
+            {}\
+            {}\
             \n\
             === VIEW ===\n\
-            WASD - Move Camera\n\
+            {} - Move Camera\n\
             Wheel - Zoom\n\
-            G - Toggle Grid\n\
-            E - Toggle Elevation\n\
-            Tab - Info Modes\n\
-            F3 - Debug Info\n\
+            {}\
             \n\
-            ESC - Quit Game"
+            === GAME ===\n\
+            {}\
+            ESC - Pause Menu (Keys to rebind)",
+            input_map.label(Action::ToggleHelp),
+            input_map.label(Action::OpenGuide),
+            input_map.label(Action::ToggleGameStatus),
+            hotkey_lines(&input_map, &[Action::EndTurn, Action::NextUnit, Action::CityScreen]),
+            unit_actions,
+            hotkey_lines(&input_map, &[Action::Rename, Action::Undo]),
+            camera,
+            hotkey_lines(&input_map, &[
+                Action::CenterOnCapital,
+                Action::CenterOnSelection,
+                Action::ToggleGrid,
+                Action::ToggleElevation,
+                Action::ElevationFainter,
+                Action::ElevationStronger,
+                Action::CycleInfoMode,
+                Action::ToggleGameStatus,
+                Action::ToggleUnitStatus,
+                Action::ToggleDebugInfo,
+                Action::ToggleTurnSummary,
+                Action::ToggleDemographics,
+                Action::ToggleCombatEffects,
+                Action::ToggleUnitList,
+                Action::ToggleEmpireOverview,
+                Action::ToggleResources,
+                Action::ToggleDiplomacy,
+                Action::ToggleGlyphSheet,
+                Action::ToggleTechTree,
+                Action::ToggleCombatLog,
+                Action::ToggleMessages,
+                Action::ToggleTurnReport,
+            ]),
+            hotkey_lines(&input_map, &[Action::QuickSave, Action::LoadGame]),
         );
     } else {
-        **text = "Press H for help".to_string();
+        **text = format!("Press {} for help", input_map.label(Action::ToggleHelp));
     }
 }
 
@@ -315,79 +610,673 @@ fn create_health_bar(current: u32, max: u32) -> String {
 // System to toggle UI panels
 pub fn toggle_ui_panels(
     keyboard: Res<ButtonInput<KeyCode>>,
+    input_map: Res<InputMap>,
     mut ui_state: ResMut<UIState>,
 ) {
-    // Toggle game status with F1
-    if keyboard.just_pressed(KeyCode::F1) {
+    // Toggle game status
+    if input_map.just_pressed(Action::ToggleGameStatus, &keyboard) {
         ui_state.show_game_status = !ui_state.show_game_status;
         println!("Game status panel: {}", if ui_state.show_game_status { "ON" } else { "OFF" });
     }
     
-    // Toggle unit status with F2
-    if keyboard.just_pressed(KeyCode::F2) {
+    // Toggle unit status
+    if input_map.just_pressed(Action::ToggleUnitStatus, &keyboard) {
         ui_state.show_unit_status = !ui_state.show_unit_status;
         println!("Unit status panel: {}", if ui_state.show_unit_status { "ON" } else { "OFF" });
     }
+    
+    // Toggle the debug overlay
+    if input_map.just_pressed(Action::ToggleDebugInfo, &keyboard) {
+        ui_state.show_debug_overlay = !ui_state.show_debug_overlay;
+    }
+    
+    // Toggle the current civilization's turn summary
+    if input_map.just_pressed(Action::ToggleTurnSummary, &keyboard) {
+        ui_state.show_turn_summary = !ui_state.show_turn_summary;
+    }
+    
+    // Toggle demographics
+    if input_map.just_pressed(Action::ToggleDemographics, &keyboard) {
+        ui_state.show_demographics = !ui_state.show_demographics;
+    }
+    
+    // Toggle damage numbers and hit flashes on the map
+    if input_map.just_pressed(Action::ToggleCombatEffects, &keyboard) {
+        ui_state.show_combat_effects = !ui_state.show_combat_effects;
+        println!("Combat effects: {}", if ui_state.show_combat_effects { "ON" } else { "OFF" });
+    }
+    
+    // Toggle unit list
+    if input_map.just_pressed(Action::ToggleUnitList, &keyboard) {
+        ui_state.show_unit_list = !ui_state.show_unit_list;
+    }
+    
+    // Toggle empire overview
+    if input_map.just_pressed(Action::ToggleEmpireOverview, &keyboard) {
+        ui_state.show_empire_overview = !ui_state.show_empire_overview;
+    }
+    
+    // Toggle the resources overview
+    if input_map.just_pressed(Action::ToggleResources, &keyboard) {
+        ui_state.show_resources = !ui_state.show_resources;
+    }
+    
+    // Toggle diplomacy
+    if input_map.just_pressed(Action::ToggleDiplomacy, &keyboard) {
+        ui_state.show_diplomacy = !ui_state.show_diplomacy;
+    }
+    
+    // Toggle the sheet of every symbol the game draws
+    if input_map.just_pressed(Action::ToggleGlyphSheet, &keyboard) {
+        ui_state.show_glyph_sheet = !ui_state.show_glyph_sheet;
+    }
+    
+    // Toggle the combat log
+    if input_map.just_pressed(Action::ToggleCombatLog, &keyboard) {
+        ui_state.show_combat_log = !ui_state.show_combat_log;
+    }
+    
+    // Toggle the tech tree
+    if input_map.just_pressed(Action::ToggleTechTree, &keyboard) {
+        ui_state.show_tech_tree = !ui_state.show_tech_tree;
+    }
+    
+    // Toggle the message history
+    if input_map.just_pressed(Action::ToggleMessages, &keyboard) {
+        ui_state.show_notification_history = !ui_state.show_notification_history;
+    }
+    
+    // Toggle the report of what happened since your last turn
+    if input_map.just_pressed(Action::ToggleTurnReport, &keyboard) {
+        ui_state.show_turn_report = !ui_state.show_turn_report;
+    }
 }
 
-// System to provide turn summary
-pub fn turn_summary_system(
-    keyboard: Res<ButtonInput<KeyCode>>,
-    unit_query: Query<&Unit>,
-    city_query: Query<&City>,
+// System to rebuild the unit list panel
+pub fn update_unit_list_panel(
+    mut commands: Commands,
+    ui_state: Res<UIState>,
+    mut panel_query: Query<(Entity, &mut Node), With<UnitListPanel>>,
+    unit_query: Query<(Entity, &Unit)>,
     civ_manager: Res<CivilizationManager>,
-    game_state: Res<GameState>,
+    mut shown_entries: Local<Option<Vec<UnitListEntry>>>,
 ) {
-    if !game_state.is_initialized {
+    let Ok((panel_entity, mut panel_node)) = panel_query.single_mut() else { return };
+    // A new game's panel starts out empty
+    if panel_node.is_added() {
+        *shown_entries = None;
+    }
+    
+    let display = if ui_state.show_unit_list { Display::Flex } else { Display::None };
+    if panel_node.display != display {
+        panel_node.display = display;
+    }
+    if !ui_state.show_unit_list {
         return;
     }
     
-    // Show turn summary with F4
-    if keyboard.just_pressed(KeyCode::F4) {
-        if let Some(current_civ) = civ_manager.get_civilization(civ_manager.current_turn_civ) {
-            println!("=== TURN SUMMARY FOR {} ===", current_civ.name);
-            
-            // Count units by type
-            let mut unit_counts = std::collections::HashMap::new();
-            let mut units_that_can_move = 0;
-            
-            for unit in unit_query.iter() {
-                if unit.civilization_id == current_civ.id {
-                    *unit_counts.entry(unit.unit_type).or_insert(0) += 1;
-                    if unit.movement_points > 0 && !unit.has_moved {
-                        units_that_can_move += 1;
-                    }
+    let Some(player_civ) = civ_manager.get_player_civilization() else { return };
+    
+    let mut entries: Vec<UnitListEntry> = unit_query
+        .iter()
+        .filter(|(_, unit)| unit.civilization_id == player_civ.id)
+        .map(|(entity, unit)| {
+            let order = if unit.is_fortified {
+                "Fortified".to_string()
+            } else {
+                match unit.order {
+                    UnitOrder::Idle => "Idle".to_string(),
+                    UnitOrder::GoTo(target) => format!("GoTo ({}, {})", target.q, target.r),
+                    UnitOrder::Sentry => "Sentry".to_string(),
+                    UnitOrder::Automate(_) => "Automated".to_string(),
                 }
-            }
+            };
+            let needs_orders = unit.needs_orders();
             
-            println!("Units:");
-            for (unit_type, count) in unit_counts {
-                println!("  {}: {}", unit_type.get_name(), count);
+            UnitListEntry {
+                unit: entity,
+                hex_coord: unit.hex_coord,
+                needs_orders,
+                label: format!(
+                    "{}{} ({}, {}) HP {}/{} MP {}/{} - {}",
+                    if needs_orders { "! " } else { "" },
+                    unit.title(),
+                    unit.hex_coord.q,
+                    unit.hex_coord.r,
+                    unit.health,
+                    unit.max_health,
+                    unit.movement_points,
+                    unit.max_movement_points,
+                    order,
+                ),
             }
+        })
+        .collect();
+    
+    // Units that need orders first, then a stable order by entity
+    entries.sort_by_key(|entry| (!entry.needs_orders, entry.unit));
+    
+    if shown_entries.as_ref() == Some(&entries) {
+        return;
+    }
+    
+    commands.entity(panel_entity).despawn_related::<Children>();
+    commands.entity(panel_entity).with_children(|panel| {
+        panel.spawn((
+            Text::new(format!("=== UNITS ({}) ===", entries.len())),
+            TextFont {
+                font_size: 12.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.9, 0.9, 0.7)),
+        ));
+        
+        for entry in &entries {
+            let text_color = if entry.needs_orders {
+                Color::srgb(1.0, 0.85, 0.4)
+            } else {
+                Color::srgb(0.8, 0.8, 0.8)
+            };
             
-            println!("Units that can still move: {}", units_that_can_move);
-            
-            // City information
-            let mut total_population = 0;
-            let mut total_production = 0.0;
-            let mut total_science = 0.0;
-            let mut total_gold = 0.0;
+            panel
+                .spawn((
+                    Button,
+                    UnitListRow {
+                        unit: entry.unit,
+                        hex_coord: entry.hex_coord,
+                    },
+                    BackgroundColor(UNIT_ROW_COLOR),
+                    Node {
+                        padding: UiRect::axes(Val::Px(4.0), Val::Px(1.0)),
+                        ..default()
+                    },
+                ))
+                .with_child((
+                    Text::new(entry.label.clone()),
+                    TextFont {
+                        font_size: 11.0,
+                        ..default()
+                    },
+                    TextColor(text_color),
+                ));
+        }
+    });
+    
+    *shown_entries = Some(entries);
+}
+
+// System handling hover and clicks on unit list rows
+pub fn unit_list_interaction_system(
+    mut row_query: Query<(&Interaction, &UnitListRow, &mut BackgroundColor), Changed<Interaction>>,
+    mut highlight_query: Query<(&mut Transform, &mut Visibility), With<UnitListHighlight>>,
+    mut camera_tween: ResMut<CameraTween>,
+    mut unit_selection: ResMut<UnitSelection>,
+    ui_state: Res<UIState>,
+) {
+    let Ok((mut highlight_transform, mut highlight_visibility)) = highlight_query.single_mut() else { return };
+    
+    if !ui_state.show_unit_list {
+        *highlight_visibility = Visibility::Hidden;
+        return;
+    }
+    
+    // Some(None) hides the highlight, Some(Some(pos)) moves it; hover wins over a row being left
+    let mut highlight: Option<Option<Vec2>> = None;
+    
+    for (interaction, row, mut background) in row_query.iter_mut() {
+        let world_pos = row.hex_coord.to_world_pos(HEX_SIZE);
+        
+        match interaction {
+            Interaction::Pressed => {
+                // Select the unit and pan the camera onto it
+                unit_selection.selected_unit = Some(row.unit);
+                camera_tween.center_on(row.hex_coord);
+            }
+            Interaction::Hovered => {
+                background.0 = UNIT_ROW_HOVER_COLOR;
+                highlight = Some(Some(world_pos));
+            }
+            Interaction::None => {
+                background.0 = UNIT_ROW_COLOR;
+                highlight.get_or_insert(None);
+            }
+        }
+    }
+    
+    match highlight {
+        Some(Some(world_pos)) => {
+            highlight_transform.translation.x = world_pos.x;
+            highlight_transform.translation.y = world_pos.y;
+            *highlight_visibility = Visibility::Visible;
+        }
+        Some(None) => *highlight_visibility = Visibility::Hidden,
+        None => {}
+    }
+}
+
+// System to scroll the unit list with the mouse wheel while hovered
+pub fn unit_list_scroll_system(
+    mut scroll_evr: EventReader<MouseWheel>,
+    mut panel_query: Query<(&Interaction, &mut ScrollPosition), With<UnitListPanel>>,
+    row_query: Query<&Interaction, With<UnitListRow>>,
+) {
+    let Ok((panel_interaction, mut scroll_position)) = panel_query.single_mut() else { return };
+    let hovered = *panel_interaction != Interaction::None
+        || row_query.iter().any(|interaction| *interaction != Interaction::None);
+    
+    for ev in scroll_evr.read() {
+        if !hovered {
+            continue;
+        }
+        let lines = match ev.unit {
+            MouseScrollUnit::Line => ev.y * 16.0,
+            MouseScrollUnit::Pixel => ev.y,
+        };
+        scroll_position.offset_y = (scroll_position.offset_y - lines).max(0.0);
+    }
+}
+
+// Helper function to create a text progress bar
+fn create_progress_bar(progress: f32, required: f32) -> String {
+    let bar_length = 10;
+    let filled_bars = ((progress / required.max(1.0)).clamp(0.0, 1.0) * bar_length as f32) as usize;
+    
+    let mut bar = String::new();
+    for i in 0..bar_length {
+        bar.push(if i < filled_bars { '█' } else { '░' });
+    }
+    bar
+}
+
+fn format_city_defense(city: &City) -> String {
+    let defense = &city.defense_breakdown;
+    format!(
+        "Defense: {:.0} (base {:.0}, garrison {:.0}, buildings {:.0}, terrain {:.0}), HP {}/{}",
+        defense.total(), defense.base, defense.garrison, defense.buildings, defense.terrain,
+        city.hit_points, CITY_MAX_HIT_POINTS,
+    )
+}
+
+fn format_city_happiness(city: &City) -> String {
+    let mut text = format!("Happiness: {:+.0} ({})", city.happiness, city.happiness_status());
+    if city.happiness < 0.0 {
+        text.push_str(&format!(" - not growing, -{:.0}% production", city.unhappiness_production_penalty() * 100.0));
+    }
+    text
+}
+
+fn format_city_health(city: &City) -> String {
+    let mut text = format!("Health: {:+.1}", city.health);
+    if city.health < 0.0 {
+        text.push_str(" - growth slowed");
+    }
+    if city.population >= city.max_population() {
+        text.push_str(" - needs an Aqueduct to grow");
+    }
+    if city.food_per_turn < 0.0 {
+        text.push_str(" - STARVING");
+    }
+    text
+}
+
+fn format_turns(turns: Option<u32>) -> String {
+    turns.map_or_else(|| "never".to_string(), |turns| format!("{} turns", turns))
+}
+
+// System to rebuild the city production panel for the selected city
+#[allow(clippy::too_many_arguments)]
+pub fn update_city_panel(
+    mut commands: Commands,
+    mut panel_query: Query<(Entity, &mut Node), With<CityPanel>>,
+    city_query: Query<(Entity, &City)>,
+    city_selection: Res<CitySelection>,
+    wonders: Res<WonderRegistry>,
+    civ_manager: Res<CivilizationManager>,
+    production_prompt: Res<ProductionPrompt>,
+    mut shown_rows: Local<Option<Vec<CityPanelRow>>>,
+) {
+    let Ok((panel_entity, mut panel_node)) = panel_query.single_mut() else { return };
+    
+    let Some((city_entity, city)) = city_selection.selected_city.and_then(|entity| city_query.get(entity).ok()) else {
+        if panel_node.display != Display::None {
+            panel_node.display = Display::None;
+        }
+        *shown_rows = None;
+        return;
+    };
+    
+    // Rows without an action are plain labels
+    let mut rows: Vec<CityPanelRow> = vec![
+        (format!("=== {} (Pop {}) ===", city.name, city.population), None),
+        ("[Rename (R)]".to_string(), Some(CityPanelAction::Rename)),
+    ];
+    
+    if city.awaiting_capture_decision {
+        rows.push(("Conquered! Annex the city or raze it?".to_string(), None));
+        rows.push((
+            format!("[Annex - resists for {} turns]", city.resistance_turns),
+            Some(CityPanelAction::Annex),
+        ));
+        if city.can_be_razed() {
+            rows.push(("[Raze - loses 1 pop per turn]".to_string(), Some(CityPanelAction::Raze)));
+        } else {
+            rows.push(("Original capitals can't be razed".to_string(), None));
+        }
+    }
+    if city.is_razing {
+        rows.push((format!("Being razed: gone in {} turns", city.population), None));
+    } else if city.resistance_turns > 0 {
+        rows.push((format!("In resistance for {} more turns: produces nothing", city.resistance_turns), None));
+    }
+    
+    rows.extend([
+        (format!(
+            "Per turn: {:.1}F {:.1}P {:.1}S {:.1}G",
+            city.food_per_turn, city.production_per_turn, city.science_per_turn, city.gold_per_turn
+        ), None),
+        (format!(
+            "Gold: +{:.1} income, -{:.1} maintenance = {:+.1} net",
+            city.gold_per_turn, city.maintenance_per_turn, city.net_gold_per_turn()
+        ), None),
+        (format_city_happiness(city), None),
+        (format_city_health(city), None),
+        (format_city_defense(city), None),
+    ]);
+    
+    if !city.wonders.is_empty() {
+        let built: Vec<&str> = city.wonders.iter().map(|wonder| wonder.get_name()).collect();
+        rows.push((format!("Wonders: {}", built.join(", ")), None));
+    }
+    
+    let worked: Vec<String> = city.worked_tiles
+        .iter()
+        .filter(|coord| **coord != city.hex_coord)
+        .map(|coord| format!("({}, {})", coord.q, coord.r))
+        .collect();
+    rows.push((format!("Working center + {} tiles: {}", worked.len(), worked.join(" ")), None));
+    if city.idle_citizens > 0 {
+        rows.push((format!("Idle citizens: {}", city.idle_citizens), None));
+    }
+    if city.tiles_locked {
+        rows.push(("[Let the governor pick tiles]".to_string(), Some(CityPanelAction::AutoTiles)));
+    }
+    
+    for specialist in SpecialistType::ALL {
+        let slots = city.specialist_slots(specialist);
+        if slots == 0 {
+            continue;
+        }
+        let count = city.specialist_count(specialist);
+        rows.push((format!("{}s: {}/{}", specialist.get_name(), count, slots), None));
+        if count < slots && city.specialists.len() < city.population as usize {
+            rows.push((format!("[+ {}]", specialist.get_name()), Some(CityPanelAction::AddSpecialist(specialist))));
+        }
+        if count > 0 {
+            rows.push((format!("[- {}]", specialist.get_name()), Some(CityPanelAction::RemoveSpecialist(specialist))));
+        }
+    }
+    if city.specialists_locked {
+        rows.push(("[Let the governor assign specialists]".to_string(), Some(CityPanelAction::AutoSpecialists)));
+    }
+    
+    rows.push((format!("Focus: {}", city.focus.get_name()), None));
+    for focus in CityFocus::ALL.into_iter().filter(|focus| *focus != city.focus) {
+        rows.push((format!("[Focus {}]", focus.get_name()), Some(CityPanelAction::SetFocus(focus))));
+    }
+    
+    rows.push((format!(
+        "Trade routes ({}/{}): +{:.1} gold",
+        city.trade_routes.len(), city.max_trade_routes(), city.trade_gold_per_turn
+    ), None));
+    for (destination_entity, destination) in city.trade_routes.iter().filter_map(|entity| city_query.get(*entity).ok()) {
+        rows.push((
+            format!("[Close route to {} (+{:.1} gold)]", destination.name, city.trade_route_gold(destination)),
+            Some(CityPanelAction::CloseTradeRoute(destination_entity)),
+        ));
+    }
+    for (destination_entity, destination) in city_query.iter() {
+        if city.can_trade_with(city_entity, destination_entity, destination, &civ_manager) {
+            rows.push((
+                format!("[Open route to {} (+{:.1} gold)]", destination.name, city.trade_route_gold(destination)),
+                Some(CityPanelAction::OpenTradeRoute(destination_entity)),
+            ));
+        }
+    }
+    
+    match &city.current_production {
+        Some(ProductionItem::Wealth) => {
+            rows.push((format!("Building: Wealth (+{:.1} gold)", city.wealth_gold()), None));
+            rows.push(("[Cancel]".to_string(), Some(CityPanelAction::Cancel)));
+        }
+        Some(item) => {
+            let required = city.required_production(item);
+            let eta = match city.missing_resource(item, &civ_manager) {
+                Some(resource) => format!("paused - needs {}", resource.get_name()),
+                None => format_turns(city.turns_for(item, required - city.production_progress)),
+            };
+            rows.push((
+                format!(
+                    "Building: {}\n{} {:.0}/{:.0} ({})",
+                    item.get_name(),
+                    create_progress_bar(city.production_progress, required),
+                    city.production_progress,
+                    required,
+                    eta,
+                ),
+                None,
+            ));
+            rows.push(("[Cancel - progress is lost]".to_string(), Some(CityPanelAction::Cancel)));
             
-            for city in city_query.iter() {
-                if city.civilization_id == current_civ.id {
-                    total_population += city.population;
-                    total_production += city.production_per_turn;
-                    total_science += city.science_per_turn;
-                    total_gold += city.gold_per_turn;
+            let treasury = civ_manager.get_civilization(city.civilization_id).map_or(0.0, |civ| civ.gold);
+            match city.purchase_cost(item) {
+                Some(cost) if cost <= treasury => {
+                    rows.push((format!("[Buy now - {:.0} gold]", cost), Some(CityPanelAction::Buy)));
                 }
+                Some(cost) => rows.push((format!("Buy now - {:.0} gold (have {:.0})", cost, treasury), None)),
+                None => {}
+            }
+        }
+        None => rows.push((format!("Building: nothing (+{:.1} gold)", city.wealth_gold()), None)),
+    }
+    if production_prompt.current == Some(city_entity) {
+        rows.push(("Pick what to build next".to_string(), None));
+        rows.push(("[Choose later - production goes to gold]".to_string(), Some(CityPanelAction::ChooseLater)));
+    }
+    
+    if !city.production_queue.is_empty() {
+        rows.push(("-- Queue: Click removes, Shift+Click moves up, Ctrl+Click moves down --".to_string(), None));
+        for (index, item) in city.production_queue.iter().enumerate() {
+            rows.push((format!("{}. {}", index + 1, item.get_name()), Some(CityPanelAction::QueueEntry(index))));
+        }
+    }
+    
+    rows.push(("-- Click: build now (drops progress), Shift+Click: queue --".to_string(), None));
+    for item in ProductionItem::all().into_iter().filter(|item| city.can_build(item, &wonders, &civ_manager)) {
+        if item == ProductionItem::Wealth {
+            rows.push((format!("Wealth - +{:.1} gold per turn", city.wealth_gold()), Some(CityPanelAction::Produce(item))));
+            continue;
+        }
+        let required = city.required_production(&item);
+        rows.push((
+            format!("{} - {:.0} prod, {}", item.get_name(), required, format_turns(city.turns_for(&item, required))),
+            Some(CityPanelAction::Produce(item)),
+        ));
+    }
+    
+    // Items held back by a missing tech or another requirement, with the reason
+    let mut unavailable: Vec<CityPanelRow> = Vec::new();
+    for item in ProductionItem::all() {
+        if let Some(tech) = city.missing_tech(&item, &civ_manager) {
+            let already_done = match &item {
+                ProductionItem::Building(building) => city.buildings.contains(building),
+                ProductionItem::Wonder(wonder) => wonders.is_built(*wonder),
+                ProductionItem::Unit(_) | ProductionItem::Wealth => false,
+            };
+            if !already_done {
+                unavailable.push((format!("{} - needs {}", item.get_name(), tech.get_name()), None));
             }
+            continue;
+        }
+        if let Some(resource) = city.missing_resource(&item, &civ_manager) {
+            unavailable.push((format!("{} - needs {}", item.get_name(), resource.get_name()), None));
+            continue;
+        }
+        let reason = match &item {
+            ProductionItem::Building(building) if !city.buildings.contains(building) => city.building_requirement_failure(building),
+            ProductionItem::Unit(unit_type) => city.unit_requirement_failure(unit_type),
+            _ => None,
+        };
+        if let Some(reason) = reason {
+            unavailable.push((format!("{} - {}", item.get_name(), reason), None));
+        }
+    }
+    if !unavailable.is_empty() {
+        rows.push((UNAVAILABLE_HEADER.to_string(), None));
+        rows.extend(unavailable);
+    }
+    
+    if panel_node.display != Display::Flex {
+        panel_node.display = Display::Flex;
+    }
+    if shown_rows.as_ref() == Some(&rows) {
+        return;
+    }
+    
+    commands.entity(panel_entity).despawn_related::<Children>();
+    commands.entity(panel_entity).with_children(|panel| {
+        // Everything after the unavailable header is greyed out
+        let mut greyed = false;
+        for (text, action) in &rows {
+            greyed |= text == UNAVAILABLE_HEADER;
+            let color = if greyed { Color::srgb(0.5, 0.5, 0.5) } else { Color::srgb(0.9, 0.9, 0.8) };
+            let label = (
+                Text::new(text.clone()),
+                TextFont {
+                    font_size: 11.0,
+                    ..default()
+                },
+                TextColor(color),
+            );
             
-            println!("Cities: {}", current_civ.cities.len());
-            println!("Total Population: {}", total_population);
-            println!("Per Turn: Production {:.1}, Science {:.1}, Gold {:.1}", 
-                     total_production, total_science, total_gold);
-            println!("Accumulated: Science {:.0}, Gold {:.0}", 
-                     current_civ.science_points, current_civ.gold);
+            match action {
+                Some(action) => {
+                    panel
+                        .spawn((
+                            Button,
+                            CityPanelButton { action: action.clone() },
+                            BackgroundColor(UNIT_ROW_COLOR),
+                            Node {
+                                padding: UiRect::axes(Val::Px(4.0), Val::Px(1.0)),
+                                ..default()
+                            },
+                        ))
+                        .with_child(label);
+                }
+                None => {
+                    panel.spawn(label);
+                }
+            }
+        }
+    });
+    
+    *shown_rows = Some(rows);
+}
+
+// System handling clicks on the city production panel
+pub fn city_panel_button_system(
+    mut button_query: Query<(&Interaction, &CityPanelButton, &mut BackgroundColor), Changed<Interaction>>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    city_selection: Res<CitySelection>,
+    mut city_query: Query<&mut City>,
+    wonders: Res<WonderRegistry>,
+    mut civ_manager: ResMut<CivilizationManager>,
+    mut city_events: CityEventWriters,
+) {
+    let shift_held = keyboard.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    let ctrl_held = keyboard.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+    
+    for (interaction, button, mut background) in button_query.iter_mut() {
+        match interaction {
+            Interaction::Pressed => {
+                let Some(city_entity) = city_selection.selected_city else { continue };
+                
+                // Route checks need both cities, so do them before borrowing the origin mutably
+                if let CityPanelAction::OpenTradeRoute(destination_entity) = button.action {
+                    let Ok([city, destination]) = city_query.get_many([city_entity, destination_entity]) else {
+                        continue;
+                    };
+                    if !city.can_trade_with(city_entity, destination_entity, destination, &civ_manager) {
+                        continue;
+                    }
+                }
+                
+                let Ok(mut city) = city_query.get_mut(city_entity) else { continue };
+                
+                match &button.action {
+                    CityPanelAction::Produce(item) => {
+                        if !city.can_build(item, &wonders, &civ_manager) {
+                            continue;
+                        }
+                        if shift_held {
+                            city.queue_production(item.clone());
+                        } else {
+                            city.set_production(item.clone());
+                        }
+                    }
+                    CityPanelAction::Cancel => city.cancel_production(),
+                    CityPanelAction::SetFocus(focus) => city.focus = *focus,
+                    CityPanelAction::OpenTradeRoute(destination) => city.trade_routes.push(*destination),
+                    CityPanelAction::CloseTradeRoute(destination) => city.trade_routes.retain(|route| route != destination),
+                    CityPanelAction::Buy => {
+                        let Some(item) = city.current_production.clone() else { continue };
+                        match city.buy_with_gold(item.clone(), city_entity, &mut civ_manager, &mut city_events) {
+                            Ok(cost) => println!("Bought {} in {} for {:.0} gold", item.get_name(), city.name, cost),
+                            Err(reason) => println!("Can't buy {}: {}", item.get_name(), reason),
+                        }
+                    }
+                    CityPanelAction::AddSpecialist(specialist) => {
+                        city.add_specialist(*specialist);
+                    }
+                    CityPanelAction::RemoveSpecialist(specialist) => city.remove_specialist(*specialist),
+                    CityPanelAction::AutoSpecialists => city.specialists_locked = false,
+                    CityPanelAction::AutoTiles => city.release_tiles(),
+                    CityPanelAction::QueueEntry(index) => {
+                        if shift_held {
+                            city.move_queued(*index, true);
+                        } else if ctrl_held {
+                            city.move_queued(*index, false);
+                        } else {
+                            city.remove_queued(*index);
+                        }
+                    }
+                    // Opens the text entry, handled by city_rename_button_system
+                    CityPanelAction::Rename => {}
+                    CityPanelAction::ChooseLater => {}
+                    CityPanelAction::Annex => city.decide_capture(false),
+                    CityPanelAction::Raze => city.decide_capture(true),
+                }
+            }
+            Interaction::Hovered => background.0 = UNIT_ROW_HOVER_COLOR,
+            Interaction::None => background.0 = UNIT_ROW_COLOR,
         }
     }
-}
\ No newline at end of file
+}
+
+// The rename button only opens the text entry; the name is applied once it's confirmed
+pub fn city_rename_button_system(
+    button_query: Query<(&Interaction, &CityPanelButton), Changed<Interaction>>,
+    city_selection: Res<CitySelection>,
+    city_query: Query<&City>,
+    mut text_entry: ResMut<TextEntry>,
+) {
+    let pressed = button_query
+        .iter()
+        .any(|(interaction, button)| *interaction == Interaction::Pressed && button.action == CityPanelAction::Rename);
+    if !pressed || text_entry.is_active() {
+        return;
+    }
+    let Some(city_entity) = city_selection.selected_city else { return };
+    let Ok(city) = city_query.get(city_entity) else { return };
+    
+    begin_city_rename(&mut text_entry, city_entity, city);
+}
@@ -0,0 +1,111 @@
+use bevy::prelude::*;
+use crate::game::cities::UnitType;
+use crate::game::fonts::GameFonts;
+use crate::game::resources::ResourceType;
+use super::game_panels::UIState;
+
+// Glyphs drawn on the map outside the unit and resource markers
+const MAP_GLYPHS: [(&str, &str); 10] = [
+    ("●", "City"),
+    ("●!", "City in unrest"),
+    ("○", "Reachable tile"),
+    ("◇", "Unit list highlight"),
+    ("⬡", "Combat target"),
+    ("≈", "River"),
+    ("▲", "Barbarian camp"),
+    ("⬟", "Fortified unit"),
+    ("zz", "Unit on sentry"),
+    ("★", "Veteran unit"),
+];
+
+// Glyphs in the panels' text
+const PANEL_GLYPHS: [(&str, &str); 9] = [
+    ("█░", "Progress and health bars"),
+    ("▲▼", "Empire overview trends"),
+    ("⚔☮", "At war or at peace"),
+    ("►", "Civilization taking its turn"),
+    ("★", "Capital"),
+    ("⚓", "Blockaded city"),
+    ("¤", "Prime trade location"),
+    ("♜", "Excellent defense"),
+    ("⚠°", "Tile info"),
+];
+
+const GLYPH_SIZE: f32 = 18.0;
+
+/// Debug sheet of every symbol the game draws, each in the symbol font and the text font,
+/// so a glyph missing from either shows up as a box
+#[derive(Component)]
+pub struct GlyphSheet;
+
+fn text_font(font_size: f32) -> TextFont {
+    TextFont {
+        font_size,
+        ..default()
+    }
+}
+
+fn glyph_rows() -> Vec<(String, String)> {
+    let units = UnitType::ALL.into_iter().map(|unit_type| (unit_type.get_symbol().to_string(), unit_type.get_name().to_string()));
+    let resources = (1..=u8::MAX)
+        .map(ResourceType::from_u8)
+        .take_while(|resource| *resource != ResourceType::None)
+        .map(|resource| (resource.symbol().to_string(), resource.get_name().to_string()));
+    let others = MAP_GLYPHS.into_iter().chain(PANEL_GLYPHS).map(|(glyph, purpose)| (glyph.to_string(), purpose.to_string()));
+    units.chain(resources).chain(others).collect()
+}
+
+pub fn setup_glyph_sheet(mut commands: Commands, fonts: Res<GameFonts>) {
+    // Glyph sheet (center, toggled with F10)
+    commands
+        .spawn((
+            GlyphSheet,
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(60.0),
+                left: Val::Px(0.0),
+                right: Val::Px(0.0),
+                margin: UiRect::horizontal(Val::Auto),
+                width: Val::Px(760.0),
+                max_width: Val::Percent(96.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(6.0),
+                padding: UiRect::all(Val::Px(8.0)),
+                display: Display::None,
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.9)),
+            GlobalZIndex(5),
+        ))
+        .with_children(|sheet| {
+            sheet.spawn((
+                Text::new("=== GLYPHS: symbol font, text font, use (F10 or Esc closes) ==="),
+                text_font(12.0),
+                TextColor(Color::srgb(0.7, 1.0, 0.7)),
+            ));
+            // Two sets of columns side by side, filled row by row
+            let set = [GridTrack::px(40.0), GridTrack::px(40.0), GridTrack::flex(1.0)];
+            sheet
+                .spawn(Node {
+                    display: Display::Grid,
+                    grid_template_columns: [set.clone(), set].concat(),
+                    column_gap: Val::Px(8.0),
+                    ..default()
+                })
+                .with_children(|grid| {
+                    for (glyph, purpose) in glyph_rows() {
+                        grid.spawn((Text::new(glyph.clone()), fonts.symbol(GLYPH_SIZE)));
+                        grid.spawn((Text::new(glyph), text_font(GLYPH_SIZE)));
+                        grid.spawn((Text::new(purpose), text_font(12.0), TextColor(Color::srgb(0.8, 0.8, 0.8))));
+                    }
+                });
+        });
+}
+
+pub fn update_glyph_sheet(ui_state: Res<UIState>, mut sheet_query: Query<&mut Node, With<GlyphSheet>>) {
+    let Ok(mut node) = sheet_query.single_mut() else { return };
+    let display = if ui_state.show_glyph_sheet { Display::Flex } else { Display::None };
+    if node.display != display {
+        node.display = display;
+    }
+}
@@ -0,0 +1,157 @@
+use bevy::prelude::*;
+use crate::game::cities::City;
+use crate::game::civilization::CivilizationManager;
+use crate::game::input_map::{Action, InputMap};
+use crate::game::statistics::EmpireTotals;
+use crate::game::units::Unit;
+use super::research::research_turns;
+use super::tooltips::TooltipSource;
+
+const HEADER_HEIGHT: f32 = 20.0;
+
+/// Bar across the top of the screen with the player's empire at a glance, in their color
+#[derive(Component)]
+pub struct HeaderBar;
+
+/// One figure in the header bar
+#[derive(Component, Clone, Copy, PartialEq)]
+pub enum HeaderReadout {
+    Gold,
+    Science,
+    Culture,
+    Happiness,
+}
+
+impl HeaderReadout {
+    const ALL: [HeaderReadout; 4] = [HeaderReadout::Gold, HeaderReadout::Science, HeaderReadout::Culture, HeaderReadout::Happiness];
+}
+
+pub fn setup_header_bar(mut commands: Commands) {
+    // Empire header (across the top, above the controls text)
+    commands
+        .spawn((
+            HeaderBar,
+            Interaction::default(),
+            BackgroundColor(Color::srgba(0.05, 0.05, 0.1, 0.9)),
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(0.0),
+                left: Val::Px(0.0),
+                right: Val::Px(0.0),
+                height: Val::Px(HEADER_HEIGHT),
+                align_items: AlignItems::Center,
+                padding: UiRect::horizontal(Val::Px(10.0)),
+                column_gap: Val::Px(18.0),
+                display: Display::None,
+                ..default()
+            },
+        ))
+        .with_children(|bar| {
+            for readout in HeaderReadout::ALL {
+                bar.spawn((
+                    readout,
+                    Interaction::default(),
+                    TooltipSource::Text(String::new()),
+                    Text::new(""),
+                    TextFont {
+                        font_size: 13.0,
+                        ..default()
+                    },
+                    TextColor(Color::WHITE),
+                ));
+            }
+        });
+}
+
+// Keeps the header on the human player's figures, whoever's turn it is. The totals are
+// measured the same way as the demographics; deals are added to the gold since they
+// change hands every turn too. Hidden in observer games, which have no player.
+pub fn update_header_bar(
+    civ_manager: Res<CivilizationManager>,
+    input_map: Res<InputMap>,
+    city_query: Query<&City>,
+    unit_query: Query<&Unit>,
+    mut bar_query: Query<(&mut Node, &mut BackgroundColor), With<HeaderBar>>,
+    mut readout_query: Query<(&HeaderReadout, &mut Text, &mut TooltipSource)>,
+) {
+    let Ok((mut bar_node, mut background)) = bar_query.single_mut() else { return };
+    let Some(player_civ) = civ_manager.get_player_civilization() else {
+        if bar_node.display != Display::None {
+            bar_node.display = Display::None;
+        }
+        return;
+    };
+    if bar_node.display != Display::Flex {
+        bar_node.display = Display::Flex;
+    }
+    // Darkened so the white figures stay readable on a bright color
+    let color = player_civ.color.darker(0.25).with_alpha(0.9);
+    if background.0 != color {
+        background.0 = color;
+    }
+    
+    let totals = EmpireTotals::measure(player_civ, city_query.iter(), unit_query.iter());
+    let finances = totals.finances;
+    let (deals_in, deals_out) = civ_manager.diplomacy.deal_gold(player_civ.id);
+    let gold_per_turn = finances.net() + deals_in - deals_out;
+    
+    for (readout, mut text, mut tooltip) in readout_query.iter_mut() {
+        let (value, description) = match readout {
+            HeaderReadout::Gold => (
+                format!("Gold {:.0} ({:+.0})", player_civ.gold, gold_per_turn),
+                format!(
+                    "Gold {:+.1} per turn\nIncome {:.1}: cities {:.1}, deals {:.1}\nExpenses {:.1}: buildings {:.1}, units {:.1}, deals {:.1}",
+                    gold_per_turn,
+                    finances.income + deals_in,
+                    finances.income,
+                    deals_in,
+                    finances.expenses() + deals_out,
+                    finances.building_upkeep,
+                    finances.unit_upkeep,
+                    deals_out
+                ),
+            ),
+            HeaderReadout::Science => {
+                let research = match player_civ.current_research {
+                    Some((tech, progress)) => match research_turns(player_civ, tech, progress, totals.science, civ_manager.speed) {
+                        Some(turns) => format!("{} in {} turns", tech.get_name(), turns),
+                        None => format!("{}, stalled", tech.get_name()),
+                    },
+                    None => "no research".to_string(),
+                };
+                let detail = match player_civ.current_research {
+                    Some((tech, _)) => format!("Researching {}", tech.get_name()),
+                    None => format!("Nothing being researched: {} opens the tech tree", input_map.label(Action::ToggleTechTree)),
+                };
+                (
+                    format!("Science +{:.0}: {}", totals.science, research),
+                    format!("Science {:.1} per turn from {} cities\n{}", totals.science, totals.cities, detail),
+                )
+            }
+            HeaderReadout::Culture => (
+                format!("Culture +{:.0}", totals.culture),
+                format!("Culture {:.1} per turn from {} cities\nIt pushes each city's borders outward", totals.culture, totals.cities),
+            ),
+            HeaderReadout::Happiness => {
+                let mut value = format!("Content {}/{}", totals.content_cities, totals.cities);
+                if totals.unrest_cities > 0 {
+                    value.push_str(&format!(" ({} in unrest)", totals.unrest_cities));
+                }
+                let unhappy = totals.cities - totals.content_cities;
+                (
+                    value,
+                    format!(
+                        "{} of {} cities content, {} unhappy, {} in unrest\nUnhappy cities don't grow and produce less",
+                        totals.content_cities, totals.cities, unhappy, totals.unrest_cities
+                    ),
+                )
+            }
+        };
+        if **text != value {
+            **text = value;
+        }
+        if *tooltip != TooltipSource::Text(description.clone()) {
+            *tooltip = TooltipSource::Text(description);
+        }
+    }
+}
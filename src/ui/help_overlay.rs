@@ -0,0 +1,433 @@
+use bevy::prelude::*;
+use bevy::ui::FocusPolicy;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use crate::game::cities::CitySelection;
+use crate::game::input::TextEntry;
+use crate::game::input_map::{Action, InputMap};
+use crate::game::units::{Unit, UnitSelection};
+use super::game_panels::{UIState, hotkey_lines};
+use super::menus::{BUTTON_COLOR, BUTTON_HOVER_COLOR, TITLE_COLOR, text};
+use super::tooltips::TooltipSource;
+use super::user_settings::UserSettings;
+
+/// The guide's pages and the hints' text, read while the game runs so they can be edited
+/// without rebuilding
+pub const HELP_FILE: &str = "assets/help.ron";
+
+// How long the game status key has to be held to open the guide instead
+const LONG_PRESS_SECS: f32 = 0.5;
+// Actions listed on each page of the hotkey reference
+const HOTKEYS_PER_PAGE: usize = 22;
+const PANEL_COLOR: Color = Color::srgba(0.08, 0.08, 0.12, 0.97);
+
+/// Something the game explains the first time it comes up, until the player dismisses it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Hint {
+    SettlerSelected,
+    CitySelected,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct HelpPage {
+    pub title: String,
+    pub body: String,
+}
+
+// What the help file holds
+#[derive(Deserialize)]
+struct HelpFile {
+    pages: Vec<HelpPage>,
+    #[serde(default)]
+    hints: BTreeMap<Hint, String>,
+}
+
+impl HelpFile {
+    fn load() -> Result<Self, String> {
+        let text = std::fs::read_to_string(HELP_FILE).map_err(|error| format!("{}: {}", HELP_FILE, error))?;
+        ron::from_str(&text).map_err(|error| format!("{}:{}: {}", HELP_FILE, error.position, error.code))
+    }
+}
+
+// Puts the keys each action is bound to in place of its name in braces, so the text
+// never names a key the player has since rebound
+fn fill_keys(text: &str, input_map: &InputMap) -> String {
+    Action::ALL
+        .iter()
+        .fold(text.to_string(), |text, action| text.replace(&format!("{{{:?}}}", action), &input_map.label(*action)))
+}
+
+// The file's pages, read afresh, then the hotkey reference as the keys are bound now. A
+// missing or broken file is reported in place of its pages.
+fn guide_pages(input_map: &InputMap) -> Vec<HelpPage> {
+    let mut pages: Vec<HelpPage> = match HelpFile::load() {
+        Ok(file) => file
+            .pages
+            .into_iter()
+            .map(|page| HelpPage {
+                body: fill_keys(&page.body, input_map),
+                ..page
+            })
+            .collect(),
+        Err(error) => vec![HelpPage {
+            title: "Guide Unavailable".to_string(),
+            body: format!("The guide's pages could not be read.\n\n{}", error),
+        }],
+    };
+    let chunks = Action::ALL.chunks(HOTKEYS_PER_PAGE);
+    let count = chunks.len();
+    for (index, actions) in chunks.enumerate() {
+        pages.push(HelpPage {
+            title: format!("Hotkeys ({}/{})", index + 1, count),
+            body: hotkey_lines(input_map, actions),
+        });
+    }
+    pages
+}
+
+/// The guide's pages while it's open, and which one is showing
+#[derive(Resource, Default)]
+pub struct Guide {
+    pages: Vec<HelpPage>,
+    page: usize,
+}
+
+impl Guide {
+    fn is_open(&self) -> bool {
+        !self.pages.is_empty()
+    }
+    
+    fn open(&mut self, input_map: &InputMap) {
+        self.pages = guide_pages(input_map);
+        self.page = 0;
+    }
+    
+    fn close(&mut self) {
+        self.pages.clear();
+    }
+    
+    fn turn(&mut self, forward: bool) {
+        self.page = if forward {
+            (self.page + 1).min(self.pages.len().saturating_sub(1))
+        } else {
+            self.page.saturating_sub(1)
+        };
+    }
+}
+
+/// The hint showing, if one is
+#[derive(Resource, Default)]
+pub struct ActiveHint(Option<Hint>);
+
+#[derive(Component)]
+pub struct GuideScreen;
+
+#[derive(Component)]
+pub struct GuideTitle;
+
+#[derive(Component)]
+pub struct GuideBody;
+
+#[derive(Component)]
+pub struct GuidePageNumber;
+
+#[derive(Component, Clone, Copy, PartialEq)]
+pub enum GuideButton {
+    Open,
+    Previous,
+    Next,
+    Close,
+}
+
+// The box a hint is shown in, and the text and button in it
+#[derive(Component)]
+pub struct HintCallout;
+
+#[derive(Component)]
+pub struct HintText;
+
+#[derive(Component)]
+pub struct DismissHint;
+
+fn small_button(parent: &mut ChildSpawnerCommands, marker: impl Component, label: &str) {
+    parent
+        .spawn((
+            Button,
+            marker,
+            BackgroundColor(BUTTON_COLOR),
+            Node {
+                padding: UiRect::axes(Val::Px(12.0), Val::Px(4.0)),
+                ..default()
+            },
+        ))
+        .with_child(text(label, 14.0, Color::WHITE));
+}
+
+pub fn setup_guide(mut commands: Commands, mut guide: ResMut<Guide>, mut active_hint: ResMut<ActiveHint>) {
+    *guide = Guide::default();
+    active_hint.0 = None;
+    
+    // The "?" button, left of End Turn
+    commands
+        .spawn((
+            Button,
+            GuideButton::Open,
+            TooltipSource::Hotkey(Action::OpenGuide, "How to move, fight, found cities and research, and every key"),
+            BackgroundColor(BUTTON_COLOR),
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(10.0),
+                right: Val::Px(270.0),
+                width: Val::Px(36.0),
+                height: Val::Px(36.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+        ))
+        .with_child(text("?", 18.0, Color::WHITE));
+    
+    // The guide, over the map and panels; its Interaction keeps clicks off the map
+    commands
+        .spawn((
+            GuideScreen,
+            Interaction::default(),
+            FocusPolicy::Block,
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+            GlobalZIndex(7),
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                display: Display::None,
+                ..default()
+            },
+        ))
+        .with_children(|screen| {
+            screen
+                .spawn((
+                    BackgroundColor(PANEL_COLOR),
+                    Node {
+                        flex_direction: FlexDirection::Column,
+                        width: Val::Px(640.0),
+                        max_width: Val::Percent(90.0),
+                        padding: UiRect::all(Val::Px(16.0)),
+                        row_gap: Val::Px(10.0),
+                        ..default()
+                    },
+                ))
+                .with_children(|panel| {
+                    panel.spawn((GuideTitle, text("", 20.0, TITLE_COLOR)));
+                    panel.spawn((GuideBody, text("", 14.0, Color::WHITE)));
+                    panel
+                        .spawn(Node {
+                            column_gap: Val::Px(8.0),
+                            align_items: AlignItems::Center,
+                            ..default()
+                        })
+                        .with_children(|footer| {
+                            small_button(footer, GuideButton::Previous, "< Previous");
+                            footer.spawn((GuidePageNumber, text("", 12.0, TITLE_COLOR)));
+                            small_button(footer, GuideButton::Next, "Next >");
+                            small_button(footer, GuideButton::Close, "Close");
+                        });
+                    panel.spawn(text(
+                        "Left/Right or Page Up/Down - turn pages   Esc - close",
+                        12.0,
+                        Color::srgb(0.8, 0.8, 0.8),
+                    ));
+                });
+        });
+    
+    // The hint callout, centred under the top bar
+    commands
+        .spawn((
+            HintCallout,
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(90.0),
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                display: Display::None,
+                ..default()
+            },
+        ))
+        .with_children(|row| {
+            // Its Interaction keeps clicks on it off the map
+            row.spawn((
+                Interaction::default(),
+                FocusPolicy::Block,
+                BackgroundColor(PANEL_COLOR),
+                BorderColor(TITLE_COLOR),
+                Node {
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::FlexEnd,
+                    max_width: Val::Px(420.0),
+                    padding: UiRect::all(Val::Px(10.0)),
+                    border: UiRect::all(Val::Px(1.0)),
+                    row_gap: Val::Px(6.0),
+                    ..default()
+                },
+            ))
+            .with_children(|callout| {
+                callout.spawn((HintText, text("", 14.0, Color::WHITE)));
+                small_button(callout, DismissHint, "Got it");
+            });
+        });
+}
+
+// The guide's keys, before any game key reads the keyboard: the guide key opens and
+// closes it, and so does holding the game status key, whose tap is then taken back.
+// While the guide is open the arrows and Page keys turn its pages, Escape closes it, and
+// no other key reaches the game.
+pub fn guide_keyboard_system(
+    time: Res<Time>,
+    mut keyboard: ResMut<ButtonInput<KeyCode>>,
+    input_map: Res<InputMap>,
+    text_entry: Res<TextEntry>,
+    mut guide: ResMut<Guide>,
+    mut ui_state: ResMut<UIState>,
+    mut held_since: Local<Option<f32>>,
+) {
+    if text_entry.is_active() {
+        return;
+    }
+    let was_open = guide.is_open();
+    let now = time.elapsed_secs();
+    
+    let long_press = if input_map.pressed(Action::ToggleGameStatus, &keyboard) {
+        let since = *held_since.get_or_insert(now);
+        now - since >= LONG_PRESS_SECS
+    } else {
+        *held_since = None;
+        false
+    };
+    if long_press {
+        *held_since = None;
+        ui_state.show_game_status = !ui_state.show_game_status;
+        guide.open(&input_map);
+    } else if !was_open {
+        if input_map.just_pressed(Action::OpenGuide, &keyboard) {
+            guide.open(&input_map);
+        }
+    } else if keyboard.just_pressed(KeyCode::Escape) || input_map.just_pressed(Action::OpenGuide, &keyboard) {
+        guide.close();
+    } else if keyboard.any_just_pressed([KeyCode::ArrowRight, KeyCode::PageDown]) {
+        guide.turn(true);
+    } else if keyboard.any_just_pressed([KeyCode::ArrowLeft, KeyCode::PageUp]) {
+        guide.turn(false);
+    }
+    
+    if was_open || guide.is_open() {
+        keyboard.reset_all();
+    }
+}
+
+// The "?" button and the guide's own buttons
+pub fn guide_button_system(
+    mut button_query: Query<(&Interaction, &GuideButton, &mut BackgroundColor), Changed<Interaction>>,
+    input_map: Res<InputMap>,
+    mut guide: ResMut<Guide>,
+) {
+    for (interaction, button, mut background) in button_query.iter_mut() {
+        match interaction {
+            Interaction::Pressed => match button {
+                GuideButton::Open => guide.open(&input_map),
+                GuideButton::Previous => guide.turn(false),
+                GuideButton::Next => guide.turn(true),
+                GuideButton::Close => guide.close(),
+            },
+            Interaction::Hovered => background.0 = BUTTON_HOVER_COLOR,
+            Interaction::None => background.0 = BUTTON_COLOR,
+        }
+    }
+}
+
+// Shows the guide's current page, or hides it once it's closed
+#[allow(clippy::type_complexity)]
+pub fn update_guide_screen(
+    guide: Res<Guide>,
+    mut screen_query: Query<&mut Node, With<GuideScreen>>,
+    mut title_query: Query<&mut Text, (With<GuideTitle>, Without<GuideBody>, Without<GuidePageNumber>)>,
+    mut body_query: Query<&mut Text, (With<GuideBody>, Without<GuideTitle>, Without<GuidePageNumber>)>,
+    mut number_query: Query<&mut Text, (With<GuidePageNumber>, Without<GuideTitle>, Without<GuideBody>)>,
+) {
+    if !guide.is_changed() {
+        return;
+    }
+    let Ok(mut node) = screen_query.single_mut() else { return };
+    let Some(page) = guide.pages.get(guide.page) else {
+        node.display = Display::None;
+        return;
+    };
+    node.display = Display::Flex;
+    if let Ok(mut title) = title_query.single_mut() {
+        **title = page.title.clone();
+    }
+    if let Ok(mut body) = body_query.single_mut() {
+        **body = page.body.clone();
+    }
+    if let Ok(mut number) = number_query.single_mut() {
+        **number = format!("Page {} of {}", guide.page + 1, guide.pages.len());
+    }
+}
+
+// Shows a hint the first time its moment comes up, until the player dismisses it for
+// good. Dismissed hints are kept in the settings file.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn hint_callout_system(
+    unit_selection: Res<UnitSelection>,
+    city_selection: Res<CitySelection>,
+    unit_query: Query<&Unit>,
+    input_map: Res<InputMap>,
+    mut user_settings: ResMut<UserSettings>,
+    mut active_hint: ResMut<ActiveHint>,
+    mut callout_query: Query<&mut Node, With<HintCallout>>,
+    mut text_query: Query<&mut Text, With<HintText>>,
+    mut dismiss_query: Query<(&Interaction, &mut BackgroundColor), (Changed<Interaction>, With<DismissHint>)>,
+) {
+    let Ok(mut callout) = callout_query.single_mut() else { return };
+    
+    if let Some(hint) = active_hint.0 {
+        for (interaction, mut background) in dismiss_query.iter_mut() {
+            match interaction {
+                Interaction::Pressed => {
+                    user_settings.dismissed_hints.insert(hint);
+                    if let Err(error) = user_settings.write() {
+                        println!("Could not save settings: {}", error);
+                    }
+                    active_hint.0 = None;
+                    callout.display = Display::None;
+                }
+                Interaction::Hovered => background.0 = BUTTON_HOVER_COLOR,
+                Interaction::None => background.0 = BUTTON_COLOR,
+            }
+        }
+        return;
+    }
+    
+    let settler_selected = unit_selection.is_changed()
+        && unit_selection
+            .selected_unit
+            .and_then(|entity| unit_query.get(entity).ok())
+            .is_some_and(|unit| unit.can_found_cities);
+    let city_selected = city_selection.is_changed() && city_selection.selected_city.is_some();
+    let hint = if settler_selected {
+        Hint::SettlerSelected
+    } else if city_selected {
+        Hint::CitySelected
+    } else {
+        return;
+    };
+    if user_settings.dismissed_hints.contains(&hint) {
+        return;
+    }
+    let Some(hint_text) = HelpFile::load().ok().and_then(|mut file| file.hints.remove(&hint)) else { return };
+    if let Ok(mut text) = text_query.single_mut() {
+        **text = fill_keys(&hint_text, &input_map);
+    }
+    active_hint.0 = Some(hint);
+    callout.display = Display::Flex;
+}
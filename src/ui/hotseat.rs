@@ -0,0 +1,138 @@
+use bevy::prelude::*;
+use bevy::ecs::system::SystemParam;
+use std::collections::HashMap;
+use crate::game::camera_zoom::CameraTween;
+use crate::game::cities::CitySelection;
+use crate::game::civilization::CivilizationManager;
+use crate::game::game_initialization::{AppState, TurnStartedEvent};
+use crate::game::map::HEX_SIZE;
+use crate::game::units::{Unit, UnitSelection};
+use super::game_panels::UIState;
+use super::menus::{screen, text};
+use super::user_settings::UserSettings;
+
+const TITLE_COLOR: Color = Color::srgb(0.9, 0.9, 0.7);
+
+/// What a hotseat player was looking at when they handed over the screen
+pub struct PlayerView {
+    camera: Transform,
+    selected_unit: Option<Entity>,
+    selected_city: Option<Entity>,
+    ui_state: UIState,
+}
+
+/// Each hotseat player's view, kept while the others take their turns
+#[derive(Resource, Default)]
+pub struct HotseatViews {
+    views: HashMap<u32, PlayerView>,
+    shown_for: Option<u32>, // The human the screen currently belongs to
+}
+
+/// The parts of the screen that belong to whoever is playing
+#[derive(SystemParam)]
+pub struct PlayerViewState<'w, 's> {
+    camera_query: Query<'w, 's, &'static mut Transform, With<Camera2d>>,
+    unit_selection: ResMut<'w, UnitSelection>,
+    city_selection: ResMut<'w, CitySelection>,
+    ui_state: ResMut<'w, UIState>,
+    user_settings: Res<'w, UserSettings>,
+    camera_tween: ResMut<'w, CameraTween>,
+}
+
+impl PlayerViewState<'_, '_> {
+    fn capture(&self) -> Option<PlayerView> {
+        Some(PlayerView {
+            camera: *self.camera_query.single().ok()?,
+            selected_unit: self.unit_selection.selected_unit,
+            selected_city: self.city_selection.selected_city,
+            ui_state: self.ui_state.clone(),
+        })
+    }
+
+    fn restore(&mut self, view: PlayerView) {
+        if let Ok(mut camera_transform) = self.camera_query.single_mut() {
+            *camera_transform = view.camera;
+        }
+        self.unit_selection.selected_unit = view.selected_unit;
+        self.city_selection.selected_city = view.selected_city;
+        *self.ui_state = view.ui_state;
+    }
+
+    // Nothing of the outgoing player's stays selected or open
+    fn clear(&mut self) {
+        self.unit_selection.selected_unit = None;
+        self.city_selection.selected_city = None;
+        *self.ui_state = self.user_settings.ui_state();
+        self.camera_tween.cancel();
+    }
+}
+
+// Hides the game when the turn passes to a human in a hotseat game, keeping what the
+// outgoing human was looking at for their next turn
+pub fn hotseat_turn_system(
+    mut turn_events: EventReader<TurnStartedEvent>,
+    civ_manager: Res<CivilizationManager>,
+    mut views: ResMut<HotseatViews>,
+    mut view_state: PlayerViewState,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    let human_turn = turn_events
+        .read()
+        .any(|event| civ_manager.get_civilization(event.civilization_id).is_some_and(|civ| civ.is_player));
+    if !human_turn || !civ_manager.is_hotseat() {
+        return;
+    }
+
+    if let Some(previous) = views.shown_for.take()
+        && let Some(view) = view_state.capture()
+    {
+        views.views.insert(previous, view);
+    }
+    view_state.clear();
+    next_state.set(AppState::Handover);
+}
+
+pub fn setup_handover_screen(mut commands: Commands, civ_manager: Res<CivilizationManager>) {
+    let Some(player) = civ_manager.get_player_civilization() else { return };
+    commands.spawn(screen(AppState::Handover, Color::srgb(0.03, 0.03, 0.08))).with_children(|menu| {
+        menu.spawn(text(format!("Pass the device to {}", player.leader_name), 26.0, TITLE_COLOR));
+        menu.spawn(text(format!("{} - Turn {}", player.name, civ_manager.turn_number), 16.0, player.color));
+        menu.spawn(text("Press any key to begin your turn", 14.0, Color::WHITE));
+    });
+}
+
+// Any key or click hands the game to the human whose turn it is, as they left it,
+// or looking at one of their units on their first turn
+pub fn handover_input_system(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    civ_manager: Res<CivilizationManager>,
+    unit_query: Query<(Entity, &Unit)>,
+    mut views: ResMut<HotseatViews>,
+    mut view_state: PlayerViewState,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if keyboard.get_just_pressed().next().is_none() && mouse.get_just_pressed().next().is_none() {
+        return;
+    }
+    let Some(player) = civ_manager.get_player_civilization() else { return };
+
+    match views.views.remove(&player.id) {
+        Some(view) => view_state.restore(view),
+        None => {
+            let first_unit = unit_query
+                .iter()
+                .filter(|(_, unit)| unit.civilization_id == player.id)
+                .min_by_key(|(entity, _)| *entity);
+            if let Some((_, unit)) = first_unit
+                && let Ok(mut camera_transform) = view_state.camera_query.single_mut()
+            {
+                let world_pos = unit.hex_coord.to_world_pos(HEX_SIZE);
+                camera_transform.translation.x = world_pos.x;
+                camera_transform.translation.y = world_pos.y;
+            }
+        }
+    }
+    views.shown_for = Some(player.id);
+    next_state.set(AppState::InGame);
+}
@@ -0,0 +1,613 @@
+use bevy::prelude::*;
+use crate::game::*;
+use crate::game::camera_zoom::CameraTween;
+use crate::game::map::{get_climate_description, evaluate_tile_suitability};
+use crate::game::world_gen::StrategicFeature;
+use crate::game::input_map::{Action, InputMap};
+use crate::game::civilization::CivilizationManager;
+use crate::game::statistics::shows_everything;
+use crate::game::input::{CursorState, InputMode, TextEntry, unit_move_modifier_held, command_modifier_held};
+use crate::game::cities::TerritoryMap;
+use crate::game::game_initialization::{AppState, GameSettings, TurnInfoText};
+use crate::game::combat::CombatState;
+use super::game_panels::UIState;
+
+#[derive(Component)]
+pub struct TileInfoText;
+
+#[derive(Component)]
+pub struct WorldStatsText;
+
+#[derive(Component)]
+pub struct ControlsHintText;
+
+#[derive(Resource, Default)]
+pub struct HoverState {
+    current_hovered: Option<HexCoord>,
+    highlighted: Option<(Entity, HexCoord)>, // The tile wearing its hover material
+}
+
+#[derive(Resource)]
+pub enum InfoDisplayMode {
+    Basic,
+    Climate,
+    Resources,
+    Suitability,
+    Strategic,
+}
+
+impl Default for InfoDisplayMode {
+    fn default() -> Self {
+        InfoDisplayMode::Basic
+    }
+}
+
+pub fn setup_camera(mut commands: Commands) {
+    commands.spawn(Camera2d);
+}
+
+pub fn reset_hover_state(mut hover_state: ResMut<HoverState>) {
+    *hover_state = HoverState::default();
+}
+
+// The one-line reminder of the main keys across the top of the screen
+fn controls_hint(input_map: &InputMap) -> String {
+    let camera = [Action::PanUp, Action::PanLeft, Action::PanDown, Action::PanRight]
+        .map(|action| input_map.label(action))
+        .join("/");
+    let keys: Vec<String> = [
+        Action::ToggleGrid,
+        Action::ToggleHelp,
+        Action::OpenGuide,
+        Action::ToggleUnitList,
+        Action::ToggleEmpireOverview,
+        Action::ToggleDiplomacy,
+        Action::ToggleTechTree,
+        Action::EndTurn,
+        Action::FoundCity,
+        Action::BuildImprovement,
+        Action::Skip,
+        Action::Rename,
+    ]
+    .iter()
+    .map(|action| format!("{}:{}", input_map.label(*action), action.get_name()))
+    .collect();
+    format!(
+        "Civ Game - {}:Camera, Wheel:Zoom, {}, Left Click:Select, Right Click:Move/Attack, ESC:Menu",
+        camera,
+        keys.join(", ")
+    )
+}
+
+pub fn setup_hud(mut commands: Commands, input_map: Res<InputMap>) {
+    // Main controls text, following the key bindings
+    commands.spawn((
+        ControlsHintText,
+        Text::new(controls_hint(&input_map)),
+        TextLayout::new_with_justify(JustifyText::Left),
+        TextFont {
+            font_size: 12.0, // Smaller to fit more text
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(30.0), // Below the header bar
+            left: Val::Px(10.0),
+            right: Val::Px(10.0), // Wraps on narrow windows
+            ..default()
+        },
+    ));
+    
+    // World stats display (top right)
+    commands.spawn((
+        WorldStatsText,
+        Text::new(""),
+        TextLayout::new_with_justify(JustifyText::Right),
+        TextFont {
+            font_size: 14.0,
+            ..default()
+        },
+        TextColor(Color::srgb(0.8, 0.8, 1.0)),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(60.0), // Moved down to make room for turn info
+            right: Val::Px(340.0), // Left of the notification toasts
+            ..default()
+        },
+    ));
+    
+    // Detailed tile info display (bottom left)
+    commands.spawn((
+        TileInfoText,
+        Text::new(""),
+        TextLayout::new_with_justify(JustifyText::Left),
+        TextFont {
+            font_size: 13.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(10.0),
+            left: Val::Px(10.0),
+            width: Val::Px(350.0),
+            ..default()
+        },
+    ));
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn tile_info_system(
+    windows: Query<&Window>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    tiles: TileLookup,
+    unit_query: Query<&crate::game::units::Unit>,
+    city_query: Query<&crate::game::cities::City>,
+    mut info_text_query: Query<&mut Text, (With<TileInfoText>, Without<WorldStatsText>, Without<TurnInfoText>)>,
+    mut world_stats_query: Query<&mut Text, (With<WorldStatsText>, Without<TileInfoText>, Without<TurnInfoText>)>,
+    world_info: Option<Res<WorldInfo>>,
+    info_mode: Res<InfoDisplayMode>,
+    civ_manager: Res<CivilizationManager>,
+    territory: Res<TerritoryMap>,
+    settings: Res<GameSettings>,
+) {
+    let Ok(window) = windows.single() else { return };
+    let Ok((camera, camera_transform)) = camera_query.single() else { return };
+    let Ok(mut info_text) = info_text_query.single_mut() else { return };
+    let Ok(mut world_stats_text) = world_stats_query.single_mut() else { return };
+    
+    // Update world stats with civilization info. The player gets their own counts and
+    // the foreign cities on the map; the totals are for observers and --omniscient.
+    if let Some(world_info) = world_info {
+        let land_percent = (world_info.total_land_tiles as f32 / 
+                          (world_info.total_land_tiles + world_info.total_ocean_tiles) as f32) * 100.0;
+        
+        let civ_count = civ_manager.civilizations.len();
+        let civ_stats = match civ_manager.get_player_civilization() {
+            Some(player_civ) if !shows_everything(&settings, &civ_manager) => {
+                let met = civ_manager.civilizations.keys().filter(|civ_id| **civ_id != player_civ.id && player_civ.has_met(**civ_id)).count();
+                let foreign_cities = city_query.iter().filter(|city| city.civilization_id != player_civ.id).count();
+                format!(
+                    "Civilizations Met: {} of {}\nYour Cities: {}\nYour Units: {}\nForeign Cities Seen: {}",
+                    met,
+                    civ_count.saturating_sub(1),
+                    player_civ.cities.len(),
+                    player_civ.units.len(),
+                    foreign_cities
+                )
+            }
+            _ => {
+                let total_cities: usize = civ_manager.civilizations.values().map(|c| c.cities.len()).sum();
+                let total_units: usize = civ_manager.civilizations.values().map(|c| c.units.len()).sum();
+                format!("Civilizations: {}\nTotal Cities: {}\nTotal Units: {}", civ_count, total_cities, total_units)
+            }
+        };
+        
+        **world_stats_text = format!(
+            "World Stats:\nSea Level: {:.3}\nLand: {:.1}% ({} tiles)\nOcean: {:.1}% ({} tiles)\nContinents: {}\n{}",
+            world_info.sea_level,
+            land_percent, world_info.total_land_tiles,
+            100.0 - land_percent, world_info.total_ocean_tiles,
+            world_info.config.continent_count,
+            civ_stats
+        );
+    }
+    
+    // Update tile info based on cursor position
+    if let Some(world_position) = window
+        .cursor_position()
+        .and_then(|cursor| camera.viewport_to_world_2d(camera_transform, cursor).ok())
+    {
+        let hovered_hex = HexCoord::from_world_pos(world_position, HEX_SIZE);
+        
+        if let Some(tile) = tiles.get(hovered_hex) {
+            let mut info = format_tile_info(tile, &info_mode);
+            info.push_str(&format_territory_info(tile, &territory, &city_query, &civ_manager));
+            
+            // Check for units on this tile
+            let units_here: Vec<_> = unit_query.iter()
+                .filter(|unit| unit.hex_coord == hovered_hex)
+                .collect();
+            
+            if !units_here.is_empty() {
+                info.push_str("\n\n=== UNITS ===");
+                for unit in units_here {
+                    let civ_name = civ_manager.known_civ_name(unit.civilization_id);
+                    
+                    info.push_str(&format!(
+                        "\n{} ({}) - HP: {}/{}, MP: {}/{}",
+                        unit.title(),
+                        civ_name,
+                        unit.health,
+                        unit.max_health,
+                        unit.movement_points,
+                        unit.max_movement_points
+                    ));
+                    
+                    if unit.is_selected {
+                        info.push_str(" [SELECTED]");
+                    }
+                }
+            }
+            
+            // Check for cities on this tile
+            let cities_here: Vec<_> = city_query.iter()
+                .filter(|city| city.hex_coord == hovered_hex)
+                .collect();
+            
+            if !cities_here.is_empty() {
+                info.push_str("\n\n=== CITIES ===");
+                for city in cities_here {
+                    let civ_name = civ_manager.known_civ_name(city.civilization_id);
+                    
+                    info.push_str(&format!(
+                        "\n{} ({}) - Pop: {}, Yields: F{:.1}/P{:.1}/S{:.1}/G{:.1}",
+                        city.name,
+                        civ_name,
+                        city.population,
+                        city.food_per_turn,
+                        city.production_per_turn,
+                        city.science_per_turn,
+                        city.gold_per_turn
+                    ));
+                    
+                    if city.is_capital {
+                        info.push_str(" [CAPITAL]");
+                    }
+                    
+                    info.push_str(&format!("\nHappiness: {:+.0} ({})", city.happiness, city.happiness_status()));
+                }
+            }
+            
+            **info_text = info;
+        } else {
+            **info_text = "".to_string();
+        }
+    } else {
+        **info_text = "".to_string();
+    }
+}
+
+// Who holds the tile, who works it, and what it's worth to the city that owns it, with
+// the same yields that city's totals are made of
+fn format_territory_info(tile: &MapTile, territory: &TerritoryMap, city_query: &Query<&crate::game::cities::City>, civ_manager: &CivilizationManager) -> String {
+    let Some(owner) = territory.owner(tile.hex_coord) else {
+        return "\n\n=== TERRITORY ===\nUnclaimed".to_string();
+    };
+    let Ok(city) = city_query.get(owner.city) else {
+        return String::new();
+    };
+    let own = civ_manager.get_viewed_civilization().is_some_and(|civ| civ.id == owner.civilization_id);
+    let mut info = format!(
+        "\n\n=== TERRITORY ===\nOwner: {} ({})\n{}",
+        civ_manager.known_civ_name(owner.civilization_id),
+        city.name,
+        if own { "Inside your borders" } else { "Foreign territory" }
+    );
+    
+    match city_query.iter().find(|other| other.worked_tiles.contains(&tile.hex_coord)) {
+        Some(worker) => info.push_str(&format!("\nWorked by {}", worker.name)),
+        None => info.push_str("\nNot worked"),
+    }
+    let (food, production, science, gold) = city.tile_yields(tile.hex_coord, tile, civ_manager);
+    info.push_str(&format!(
+        "\nYields to {}: F{:.1}/P{:.1}/S{:.1}/G{:.1}",
+        city.name, food, production, science, gold
+    ));
+    info
+}
+
+fn format_tile_info(tile: &MapTile, mode: &InfoDisplayMode) -> String {
+    let terrain_type = TerrainType::from_u8(tile.terrain);
+    let biome_type = BiomeType::from_u8(tile.biome);
+    
+    let mut info = format!(
+        "Coordinate: ({}, {})\nBiome: {:?}\nElevation: {:.2}m",
+        tile.hex_coord.q, 
+        tile.hex_coord.r, 
+        biome_type,
+        (tile.elevation_raw * 1000.0) // Convert to meters for display
+    );
+    
+    match mode {
+        InfoDisplayMode::Basic => {
+            if tile.has_river {
+                info.push_str(&format!("\nRiver Flow: {:.1}", tile.river_flow));
+            }
+            
+            if tile.is_coastal {
+                info.push_str("\nFeature: Coastal");
+            }
+            
+            if tile.resource != 0 {
+                let resource_type = ResourceType::from_u8(tile.resource);
+                info.push_str(&format!("\nResource: {:?}", resource_type));
+            }
+        },
+        
+        InfoDisplayMode::Climate => {
+            let climate_desc = get_climate_description(tile.temperature, tile.precipitation);
+            info.push_str(&format!(
+                "\nClimate: {}\nTemperature: {:.1}°\nPrecipitation: {:.0}mm\nSoil Fertility: {:.1}%",
+                climate_desc,
+                tile.temperature * 40.0 - 10.0, // Convert to rough Celsius
+                tile.precipitation * 2000.0,     // Convert to mm per year
+                tile.soil_fertility * 100.0
+            ));
+        },
+        
+        InfoDisplayMode::Resources => {
+            let (food, production, science) = terrain_type.base_yields();
+            let fertility_bonus = tile.soil_fertility * 2.0;
+            let river_bonus = if tile.has_river { 1.0 } else { 0.0 };
+            
+            info.push_str(&format!(
+                "\nBase Yields:\n  Food: {:.1} (+{:.1} fertility)\n  Production: {:.1}\n  Science: {:.1}\nRiver Bonus: +{:.1} food",
+                food, fertility_bonus, production, science, river_bonus
+            ));
+            
+            if tile.resource != 0 {
+                let resource_type = ResourceType::from_u8(tile.resource);
+                info.push_str(&format!("\nSpecial Resource: {:?}", resource_type));
+            }
+        },
+        
+        InfoDisplayMode::Suitability => {
+            let suitability = evaluate_tile_suitability(tile);
+            info.push_str(&format!(
+                "\nSuitability Ratings:\n  Agriculture: {:.0}%\n  Industry: {:.0}%\n  Settlement: {:.0}%\n  Defense: {:.0}%",
+                suitability.agriculture * 100.0,
+                suitability.industry * 100.0,
+                suitability.settlement * 100.0,
+                suitability.defensibility * 100.0
+            ));
+            
+            // Add geological info
+            let geology_name = match tile.geology {
+                0 => "Oceanic Crust",
+                1 => "Continental Shelf", 
+                2 => "Sedimentary",
+                3 => "Igneous",
+                4 => "Metamorphic",
+                5 => "Volcanic",
+                6 => "Limestone",
+                7 => "Sandstone",
+                8 => "Granite",
+                9 => "Basalt",
+                _ => "Unknown",
+            };
+            info.push_str(&format!("\nGeology: {}", geology_name));
+        },
+        
+        InfoDisplayMode::Strategic => {
+            // Strategic feature information
+            if tile.strategic_feature != 0 {
+                let feature = StrategicFeature::from_u8(tile.strategic_feature);
+                info.push_str(&format!("\nStrategic Feature: {}", feature.name()));
+            }
+            
+            info.push_str(&format!(
+                "\nStrategic Values:\n  Defensibility: {:.0}%\n  Trade Value: {:.0}%\n  Naval Access: {:.0}%\n  Flood Risk: {:.0}%",
+                tile.defensibility * 100.0,
+                tile.trade_value * 100.0,
+                tile.naval_access * 100.0,
+                tile.flood_risk * 100.0
+            ));
+            
+            // Additional strategic context
+            if tile.has_river {
+                info.push_str(&format!("\nRiver Flow: {:.1}", tile.river_flow));
+            }
+            
+            if tile.flood_risk > 0.6 {
+                info.push_str("\n⚠ High Flood Risk");
+            }
+            
+            if tile.defensibility > 0.8 {
+                info.push_str("\n♜ Excellent Defense");
+            }
+            
+            if tile.trade_value > 0.8 {
+                info.push_str("\n¤ Prime Trade Location");
+            }
+        }
+    }
+    
+    info
+}
+
+pub fn toggle_info_display(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    input_map: Res<InputMap>,
+    mut info_mode: ResMut<InfoDisplayMode>,
+) {
+    if input_map.just_pressed(Action::CycleInfoMode, &keyboard) {
+        *info_mode = match *info_mode {
+            InfoDisplayMode::Basic => InfoDisplayMode::Climate,
+            InfoDisplayMode::Climate => InfoDisplayMode::Resources,
+            InfoDisplayMode::Resources => InfoDisplayMode::Suitability,
+            InfoDisplayMode::Suitability => InfoDisplayMode::Strategic,
+            InfoDisplayMode::Strategic => InfoDisplayMode::Basic,
+        };
+        
+        let mode_name = match *info_mode {
+            InfoDisplayMode::Basic => "Basic",
+            InfoDisplayMode::Climate => "Climate",
+            InfoDisplayMode::Resources => "Resources", 
+            InfoDisplayMode::Suitability => "Suitability",
+            InfoDisplayMode::Strategic => "Strategic",
+        };
+        
+        println!("Info display mode: {}", mode_name);
+    }
+}
+
+// Camera speed, in world units a second at normal zoom, when the cursor rests at a window edge
+const EDGE_PAN_SPEED: f32 = 600.0;
+
+// Which way the cursor at the window's edge pans the camera; both ways at once in a corner
+fn edge_pan_direction(window: &Window, cursor: &CursorState, margin: f32) -> Vec3 {
+    let Some(position) = cursor.screen_pos.filter(|_| window.focused && !cursor.over_ui) else {
+        return Vec3::ZERO;
+    };
+    let mut direction = Vec3::ZERO;
+    if position.x <= margin {
+        direction.x -= 1.0;
+    } else if position.x >= window.width() - margin {
+        direction.x += 1.0;
+    }
+    // Screen y runs down from the top of the window
+    if position.y <= margin {
+        direction.y += 1.0;
+    } else if position.y >= window.height() - margin {
+        direction.y -= 1.0;
+    }
+    direction.normalize_or_zero()
+}
+
+// The camera keys (the arrows unless rebound) pan the camera, as does resting the cursor at a window edge.
+// The two add together; edge panning moves faster zoomed out, and stops while typing or
+// with the cursor over a panel. Either cancels a glide the camera is on.
+#[allow(clippy::too_many_arguments)]
+pub fn camera_movement(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    input_map: Res<InputMap>,
+    mut camera_query: Query<&mut Transform, With<Camera>>,
+    time: Res<Time>,
+    windows: Query<&Window>,
+    cursor: Res<CursorState>,
+    text_entry: Res<TextEntry>,
+    settings: Res<GameSettings>,
+    mut camera_tween: ResMut<CameraTween>,
+    input_mode: Res<InputMode>,
+) {
+    // The city screen holds the camera on its city
+    if let InputMode::CityManagement(_) = *input_mode {
+        return;
+    }
+    let edge_direction = match windows.single() {
+        Ok(window) if settings.edge_pan && !text_entry.is_active() => edge_pan_direction(window, &cursor, settings.edge_pan_margin),
+        _ => Vec3::ZERO,
+    };
+    if edge_direction != Vec3::ZERO
+        && let Ok(mut camera_transform) = camera_query.single_mut()
+    {
+        camera_tween.cancel();
+        let edge_speed = EDGE_PAN_SPEED * camera_transform.scale.x;
+        camera_transform.translation += edge_direction * edge_speed * time.delta_secs();
+    }
+    
+    // Alt + letters steps the selected unit instead, Ctrl + letters saves and loads
+    if unit_move_modifier_held(&keyboard_input) || command_modifier_held(&keyboard_input) {
+        return;
+    }
+    
+    if let Ok(mut camera_transform) = camera_query.single_mut() {
+        let mut movement_speed = 500.0;
+        
+        // Faster movement with shift
+        if keyboard_input.pressed(KeyCode::ShiftLeft) || keyboard_input.pressed(KeyCode::ShiftRight) {
+            movement_speed *= 2.0;
+        }
+        
+        let mut direction = Vec3::ZERO;
+        
+        if input_map.pressed(Action::PanUp, &keyboard_input) {
+            direction.y += 1.0;
+        }
+        if input_map.pressed(Action::PanDown, &keyboard_input) {
+            direction.y -= 1.0;
+        }
+        if input_map.pressed(Action::PanLeft, &keyboard_input) {
+            direction.x -= 1.0;
+        }
+        if input_map.pressed(Action::PanRight, &keyboard_input) {
+            direction.x += 1.0;
+        }
+        
+        if direction.length() > 0.0 {
+            camera_tween.cancel();
+            direction = direction.normalize();
+            camera_transform.translation += direction * movement_speed * time.delta_secs();
+        }
+    }
+}
+
+pub fn basic_input(
+    keyboard_input: Res<ButtonInput<KeyCode>>, 
+    input_mode: Res<InputMode>,
+    combat_state: Res<CombatState>,
+    mut ui_state: ResMut<UIState>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    // Escape backs out of a pending attack, then closes the turn report or the F3/F4/F10 overlays,
+    // before it opens the pause menu
+    let attack_pending = *input_mode != InputMode::Normal || combat_state.combat_preview.is_some();
+    if !keyboard_input.just_pressed(KeyCode::Escape) || attack_pending {
+        return;
+    }
+    if ui_state.show_turn_report {
+        ui_state.show_turn_report = false;
+    } else if ui_state.show_turn_summary || ui_state.show_debug_overlay || ui_state.show_glyph_sheet {
+        ui_state.show_turn_summary = false;
+        ui_state.show_debug_overlay = false;
+        ui_state.show_glyph_sheet = false;
+    } else {
+        next_state.set(AppState::Paused);
+    }
+}
+
+pub fn hex_hover_system(
+    windows: Query<&Window>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    mut tile_materials: Query<&mut MeshMaterial2d<ColorMaterial>, With<MapTile>>,
+    tile_index: Res<TileIndex>,
+    terrain_assets: Res<TerrainAssets>,
+    mut hover_state: ResMut<HoverState>,
+) {
+    let Ok(window) = windows.single() else { return };
+    let Ok((camera, camera_transform)) = camera_query.single() else { return };
+    
+    // Determine what tile we're hovering over (if any)
+    let new_hovered = window
+        .cursor_position()
+        .and_then(|cursor| camera.viewport_to_world_2d(camera_transform, cursor).ok())
+        .map(|world_pos| HexCoord::from_world_pos(world_pos, HEX_SIZE));
+    
+    // Check if hover changed
+    if hover_state.current_hovered == new_hovered {
+        return;
+    }
+    hover_state.current_hovered = new_hovered;
+    
+    // Put the last tile back to its enhanced material (not the base one), culled or not;
+    // one that has since been despawned has nothing to put back
+    if let Some((entity, coord)) = hover_state.highlighted.take()
+        && let Ok(mut material_handle) = tile_materials.get_mut(entity)
+        && let Some(enhanced_material) = terrain_assets.enhanced_materials.get(&coord)
+    {
+        material_handle.0 = enhanced_material.clone();
+    }
+    
+    // Then highlight the hovered tile, found through the index, with the pre-computed hover
+    // material that preserves shading
+    let Some(hovered_coord) = new_hovered else { return };
+    let Some(&entity) = tile_index.tiles.get(&hovered_coord) else { return };
+    if let Ok(mut material_handle) = tile_materials.get_mut(entity)
+        && let Some(hover_material) = terrain_assets.hover_materials.get(&hovered_coord)
+    {
+        material_handle.0 = hover_material.clone();
+        hover_state.highlighted = Some((entity, hovered_coord));
+    }
+}
+
+// Rewrites the controls line after the keys are rebound
+pub fn update_controls_hint(input_map: Res<InputMap>, mut hint_query: Query<&mut Text, With<ControlsHintText>>) {
+    if !input_map.is_changed() {
+        return;
+    }
+    for mut text in &mut hint_query {
+        **text = controls_hint(&input_map);
+    }
+}
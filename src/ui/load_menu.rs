@@ -0,0 +1,77 @@
+use bevy::prelude::*;
+use std::time::Duration;
+use crate::game::save::{LoadMenu, SaveSlot};
+
+#[derive(Component)]
+pub struct LoadMenuPanel;
+
+pub fn setup_load_menu_panel(mut commands: Commands) {
+    // Load menu (top center, opened with Ctrl+L)
+    commands.spawn((
+        LoadMenuPanel,
+        Text::new(""),
+        TextFont {
+            font_size: 14.0,
+            ..default()
+        },
+        TextColor(Color::srgb(0.9, 0.9, 0.9)),
+        BackgroundColor(Color::srgba(0.05, 0.05, 0.1, 0.95)),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(120.0),
+            left: Val::Px(0.0),
+            right: Val::Px(0.0),
+            margin: UiRect::horizontal(Val::Auto),
+            width: Val::Px(360.0),
+            max_width: Val::Percent(96.0),
+            padding: UiRect::all(Val::Px(10.0)),
+            display: Display::None,
+            ..default()
+        },
+    ));
+}
+
+// How long ago a save was written, roughly
+fn format_age(age: Duration) -> String {
+    let minutes = age.as_secs() / 60;
+    match minutes {
+        0 => "just now".to_string(),
+        1..=59 => format!("{} min ago", minutes),
+        60..=1439 => format!("{} h ago", minutes / 60),
+        _ => format!("{} days ago", minutes / 1440),
+    }
+}
+
+pub fn slot_line(number: usize, slot: &SaveSlot) -> String {
+    let turn = match slot.turn {
+        Some(turn) => format!("turn {}", turn),
+        None => "unreadable".to_string(),
+    };
+    let age = slot.age.map(format_age).unwrap_or_default();
+    format!("{} - {}: {}, {}", number, slot.name, turn, age)
+}
+
+// Shows the saves on offer while the load menu is open
+pub fn update_load_menu_panel(
+    menu: Res<LoadMenu>,
+    mut panel_query: Query<(&mut Text, &mut Node), With<LoadMenuPanel>>,
+) {
+    if !menu.is_changed() {
+        return;
+    }
+    let Ok((mut text, mut node)) = panel_query.single_mut() else { return };
+    
+    node.display = if menu.open { Display::Flex } else { Display::None };
+    if !menu.open {
+        return;
+    }
+    
+    let mut lines = vec!["=== LOAD GAME ===".to_string()];
+    if menu.slots.is_empty() {
+        lines.push("No saved games yet (Ctrl+S saves)".to_string());
+    }
+    lines.extend(menu.slots.iter().enumerate().map(|(index, slot)| slot_line(index + 1, slot)));
+    lines.push(String::new());
+    lines.push("Esc - Cancel".to_string());
+    **text = lines.join("\n");
+}
@@ -0,0 +1,521 @@
+use bevy::prelude::*;
+use bevy::ui::FocusPolicy;
+use std::path::PathBuf;
+use crate::game::civilization::{Difficulty, GameSpeed, create_default_civilizations};
+use crate::game::game_initialization::{AppState, GameSettings, MAX_HUMANS, MAX_OPPONENTS, MIN_OPPONENTS, TURN_LIMITS, TURN_TIME_LIMITS};
+use crate::game::input_map::{Action, InputMap, KeyChord};
+use crate::game::save::{PendingLoad, SaveNotice, SaveSources, list_saves};
+use crate::game::world_gen::WorldType;
+use super::load_menu::slot_line;
+use super::settings_menu::SettingsMenu;
+
+pub(super) const BUTTON_COLOR: Color = Color::srgba(0.25, 0.25, 0.3, 0.9);
+pub(super) const BUTTON_HOVER_COLOR: Color = Color::srgba(0.4, 0.4, 0.55, 0.9);
+pub(super) const TITLE_COLOR: Color = Color::srgb(0.9, 0.9, 0.7);
+
+#[derive(Component, Clone, PartialEq)]
+pub enum MenuButton {
+    NewGame,
+    Load(PathBuf),
+    Settings,
+    Quit,
+    // Setup screen
+    WorldType,
+    Civilization,
+    Difficulty,
+    Speed,
+    TurnLimit,
+    TurnTimer,
+    FewerOpponents,
+    MoreOpponents,
+    Barbarians,
+    RequireOrders,
+    Humans,
+    Start,
+    Back,
+}
+
+#[derive(Component, Clone, Copy, PartialEq)]
+pub enum PauseButton {
+    Resume,
+    Save,
+    Settings,
+    KeyBindings,
+    NewGame,
+    MainMenu,
+    Quit,
+    // Key bindings page
+    Rebind(Action),
+    ResetKeys,
+    Back,
+}
+
+#[derive(Clone, Copy, Default, PartialEq, PartialOrd)]
+enum PausePage {
+    #[default]
+    Main,
+    Keys,
+}
+
+impl PausePage {
+    // The page's buttons, top to bottom
+    fn buttons(self) -> Vec<PauseButton> {
+        match self {
+            PausePage::Main => vec![
+                PauseButton::Resume,
+                PauseButton::Save,
+                PauseButton::Settings,
+                PauseButton::KeyBindings,
+                PauseButton::NewGame,
+                PauseButton::MainMenu,
+                PauseButton::Quit,
+            ],
+            PausePage::Keys => Action::ALL
+                .iter()
+                .map(|action| PauseButton::Rebind(*action))
+                .chain([PauseButton::ResetKeys, PauseButton::Back])
+                .collect(),
+        }
+    }
+}
+
+/// Which page of the pause menu is open, the button the keyboard has picked on it, how
+/// the last save went, and the action waiting for a new key if one is
+#[derive(Resource, Default)]
+pub struct PauseMenu {
+    page: PausePage,
+    focus: usize,
+    notice: Option<String>,
+    rebinding: Option<Action>,
+}
+
+#[derive(Component)]
+pub struct PauseMenuScreen;
+
+/// Text on the setup screen showing one of the chosen options
+#[derive(Component, Clone, Copy)]
+pub enum SetupLabel {
+    WorldType,
+    Civilization,
+    Difficulty,
+    Speed,
+    TurnLimit,
+    TurnTimer,
+    Opponents,
+    Barbarians,
+    RequireOrders,
+    Humans,
+}
+
+// Line under the pause menu's buttons saying how the last save went
+#[derive(Component)]
+pub struct PauseStatus;
+
+pub(super) fn text(value: impl Into<String>, font_size: f32, color: Color) -> impl Bundle {
+    (
+        Text::new(value),
+        TextFont {
+            font_size,
+            ..default()
+        },
+        TextColor(color),
+    )
+}
+
+// A whole-window screen that goes away with the state it belongs to and keeps clicks
+// from reaching anything underneath
+pub(super) fn screen(state: AppState, background: Color) -> impl Bundle {
+    (
+        StateScoped(state),
+        BackgroundColor(background),
+        FocusPolicy::Block,
+        GlobalZIndex(10),
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            flex_direction: FlexDirection::Column,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            row_gap: Val::Px(8.0),
+            ..default()
+        },
+    )
+}
+
+fn button(parent: &mut ChildSpawnerCommands, marker: impl Component, label: impl Bundle) {
+    parent
+        .spawn((
+            Button,
+            marker,
+            BackgroundColor(BUTTON_COLOR),
+            Node {
+                min_width: Val::Px(260.0),
+                justify_content: JustifyContent::Center,
+                padding: UiRect::axes(Val::Px(12.0), Val::Px(6.0)),
+                ..default()
+            },
+        ))
+        .with_child(label);
+}
+
+pub fn setup_main_menu(mut commands: Commands, mut settings_menu: ResMut<SettingsMenu>) {
+    settings_menu.close();
+    let saves: Vec<_> = list_saves().into_iter().enumerate().filter(|(_, slot)| slot.turn.is_some()).collect();
+    commands.spawn(screen(AppState::MainMenu, Color::srgb(0.03, 0.03, 0.08))).with_children(|menu| {
+        menu.spawn(text("CIVILIZATION", 32.0, TITLE_COLOR));
+        button(menu, MenuButton::NewGame, text("New Game", 16.0, Color::WHITE));
+        if !saves.is_empty() {
+            menu.spawn(text("Load Game", 14.0, TITLE_COLOR));
+        }
+        for (index, slot) in saves {
+            button(menu, MenuButton::Load(slot.path.clone()), text(slot_line(index + 1, &slot), 13.0, Color::WHITE));
+        }
+        button(menu, MenuButton::Settings, text("Settings", 16.0, Color::WHITE));
+        button(menu, MenuButton::Quit, text("Quit", 16.0, Color::WHITE));
+    });
+}
+
+pub fn setup_setup_screen(mut commands: Commands) {
+    commands.spawn(screen(AppState::Setup, Color::srgb(0.03, 0.03, 0.08))).with_children(|menu| {
+        menu.spawn(text("=== NEW GAME ===", 22.0, TITLE_COLOR));
+        for (marker, label) in [
+            (MenuButton::WorldType, SetupLabel::WorldType),
+            (MenuButton::Civilization, SetupLabel::Civilization),
+            (MenuButton::Difficulty, SetupLabel::Difficulty),
+            (MenuButton::Speed, SetupLabel::Speed),
+            (MenuButton::TurnLimit, SetupLabel::TurnLimit),
+            (MenuButton::TurnTimer, SetupLabel::TurnTimer),
+        ] {
+            button(menu, marker, (label, text("", 14.0, Color::WHITE)));
+        }
+        menu.spawn(Node {
+            column_gap: Val::Px(8.0),
+            align_items: AlignItems::Center,
+            ..default()
+        })
+        .with_children(|row| {
+            row.spawn((Button, MenuButton::FewerOpponents, BackgroundColor(BUTTON_COLOR), Node {
+                padding: UiRect::axes(Val::Px(12.0), Val::Px(6.0)),
+                ..default()
+            }))
+            .with_child(text("-", 14.0, Color::WHITE));
+            row.spawn((SetupLabel::Opponents, text("", 14.0, Color::WHITE)));
+            row.spawn((Button, MenuButton::MoreOpponents, BackgroundColor(BUTTON_COLOR), Node {
+                padding: UiRect::axes(Val::Px(12.0), Val::Px(6.0)),
+                ..default()
+            }))
+            .with_child(text("+", 14.0, Color::WHITE));
+        });
+        button(menu, MenuButton::Barbarians, (SetupLabel::Barbarians, text("", 14.0, Color::WHITE)));
+        button(menu, MenuButton::RequireOrders, (SetupLabel::RequireOrders, text("", 14.0, Color::WHITE)));
+        button(menu, MenuButton::Humans, (SetupLabel::Humans, text("", 14.0, Color::WHITE)));
+        button(menu, MenuButton::Start, text("Start Game", 16.0, Color::WHITE));
+        button(menu, MenuButton::Back, text("Back", 16.0, Color::WHITE));
+    });
+}
+
+pub fn setup_loading_screen(mut commands: Commands, pending: Option<Res<PendingLoad>>) {
+    let message = if pending.is_some() { "Loading saved game..." } else { "Generating the world..." };
+    commands
+        .spawn(screen(AppState::Loading, Color::srgb(0.03, 0.03, 0.08)))
+        .with_child(text(message, 20.0, TITLE_COLOR));
+}
+
+// An action's keys, and what else they'd set off
+fn binding_label(action: Action, input_map: &InputMap, rebinding: Option<Action>) -> String {
+    if rebinding == Some(action) {
+        return format!("{}: press a key...", action.get_name());
+    }
+    let conflicts: Vec<&str> = input_map.conflicts(action).iter().map(Action::get_name).collect();
+    if conflicts.is_empty() {
+        format!("{}: {}", action.get_name(), input_map.label(action))
+    } else {
+        format!("{}: {} (also {})", action.get_name(), input_map.label(action), conflicts.join(", "))
+    }
+}
+
+fn pause_label(button: PauseButton, input_map: &InputMap, rebinding: Option<Action>) -> String {
+    match button {
+        PauseButton::Resume => "Resume".to_string(),
+        PauseButton::Save => "Save Game".to_string(),
+        PauseButton::Settings => "Settings".to_string(),
+        PauseButton::KeyBindings => "Key bindings".to_string(),
+        PauseButton::NewGame => "New Game".to_string(),
+        PauseButton::MainMenu => "Quit to Main Menu".to_string(),
+        PauseButton::Quit => "Quit to Desktop".to_string(),
+        PauseButton::Rebind(action) => binding_label(action, input_map, rebinding),
+        PauseButton::ResetKeys => "Reset all keys".to_string(),
+        PauseButton::Back => "Back".to_string(),
+    }
+}
+
+// The dimmed overlay over the map, showing the menu's current page. The key bindings
+// are too many for one column, so they wrap into several.
+fn spawn_pause_menu(commands: &mut Commands, menu: &PauseMenu, input_map: &InputMap) {
+    let (title, hint) = match menu.page {
+        PausePage::Main => ("=== PAUSED ===", "Up/Down - choose   Enter - select   Esc - resume"),
+        PausePage::Keys if menu.rebinding.is_some() => ("=== KEY BINDINGS ===", "Press the new key, with any modifiers   Esc - cancel"),
+        PausePage::Keys => ("=== KEY BINDINGS ===", "Up/Down - choose   Enter - rebind   Esc - back"),
+    };
+    let font_size = if menu.page == PausePage::Keys { 13.0 } else { 16.0 };
+    commands
+        .spawn((PauseMenuScreen, screen(AppState::Paused, Color::srgba(0.0, 0.0, 0.0, 0.6))))
+        .with_children(|parent| {
+            parent.spawn(text(title, 22.0, TITLE_COLOR));
+            parent
+                .spawn(Node {
+                    flex_direction: if menu.page == PausePage::Keys { FlexDirection::Row } else { FlexDirection::Column },
+                    flex_wrap: FlexWrap::Wrap,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    max_width: Val::Percent(90.0),
+                    row_gap: Val::Px(if menu.page == PausePage::Keys { 4.0 } else { 8.0 }),
+                    column_gap: Val::Px(8.0),
+                    ..default()
+                })
+                .with_children(|list| {
+                    for marker in menu.page.buttons() {
+                        let label = pause_label(marker, input_map, menu.rebinding);
+                        button(list, marker, text(label, font_size, Color::WHITE));
+                    }
+                });
+            let status = menu.notice.clone().unwrap_or_default();
+            parent.spawn((PauseStatus, text(status, 12.0, TITLE_COLOR)));
+            parent.spawn(text(hint, 12.0, Color::srgb(0.8, 0.8, 0.8)));
+        });
+}
+
+pub fn setup_pause_menu(
+    mut commands: Commands,
+    mut menu: ResMut<PauseMenu>,
+    mut settings_menu: ResMut<SettingsMenu>,
+    input_map: Res<InputMap>,
+) {
+    *menu = PauseMenu::default();
+    settings_menu.close();
+    spawn_pause_menu(&mut commands, &menu, &input_map);
+}
+
+// The entry after `current`, wrapping around
+pub(super) fn cycle<T: PartialEq + Clone>(options: &[T], current: &T) -> T {
+    let index = options.iter().position(|option| option == current).unwrap_or(0);
+    options[(index + 1) % options.len()].clone()
+}
+
+// Names of the civilizations on offer, and which of them the player will lead: the
+// one asked for, else the usual default
+fn civilization_choice(settings: &GameSettings) -> (Vec<String>, usize) {
+    let civilizations = create_default_civilizations();
+    let chosen = settings
+        .player_civ
+        .as_deref()
+        .and_then(|query| civilizations.iter().position(|civ| civ.matches_name(query)))
+        .or_else(|| civilizations.iter().position(|civ| civ.is_player))
+        .unwrap_or(0);
+    (civilizations.into_iter().map(|civ| civ.name).collect(), chosen)
+}
+
+// Shows the options chosen so far on the setup screen
+pub fn update_setup_labels(settings: Res<GameSettings>, mut label_query: Query<(Ref<SetupLabel>, &mut Text)>) {
+    let new_labels = label_query.iter().any(|(label, _)| label.is_added());
+    if !settings.is_changed() && !new_labels {
+        return;
+    }
+    let (names, chosen) = civilization_choice(&settings);
+    
+    for (label, mut text) in label_query.iter_mut() {
+        **text = match *label {
+            SetupLabel::WorldType => format!("World: {}", settings.world_type.get_name()),
+            SetupLabel::Civilization => format!("Civilization: {}", names[chosen]),
+            SetupLabel::Difficulty => format!("Difficulty: {}", settings.difficulty.get_name()),
+            SetupLabel::Speed => format!("Game speed: {}", settings.speed.get_name()),
+            SetupLabel::TurnLimit => match settings.turn_limit {
+                0 => "Turn limit: none".to_string(),
+                turns => format!("Turn limit: {}", turns),
+            },
+            SetupLabel::TurnTimer => match settings.turn_time_limit {
+                Some(limit) => format!("Turn timer: {}s", limit.as_secs()),
+                None => "Turn timer: off".to_string(),
+            },
+            SetupLabel::Opponents => format!("Opponents: {}", settings.opponents),
+            SetupLabel::Barbarians => format!("Barbarians: {}", if settings.barbarians { "On" } else { "Off" }),
+            SetupLabel::Humans => match settings.humans {
+                0 => "Human players: none (watch the AI play)".to_string(),
+                humans => format!("Human players (hotseat): {}", humans),
+            },
+            SetupLabel::RequireOrders => format!("Idle units block end of turn: {}", if settings.require_orders { "On" } else { "Off" }),
+        };
+    }
+}
+
+// Buttons of the main menu and the setup screen
+pub fn menu_button_system(
+    mut commands: Commands,
+    mut button_query: Query<(&Interaction, &MenuButton, &mut BackgroundColor), Changed<Interaction>>,
+    mut settings: ResMut<GameSettings>,
+    mut settings_menu: ResMut<SettingsMenu>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    for (interaction, button, mut background) in button_query.iter_mut() {
+        match interaction {
+            Interaction::Pressed => match button {
+                MenuButton::NewGame => next_state.set(AppState::Setup),
+                MenuButton::Load(path) => {
+                    commands.insert_resource(PendingLoad(path.clone()));
+                    next_state.set(AppState::Loading);
+                }
+                MenuButton::Settings => settings_menu.open(),
+                MenuButton::Quit => {
+                    exit.write(AppExit::Success);
+                }
+                MenuButton::WorldType => settings.world_type = cycle(&WorldType::ALL, &settings.world_type),
+                MenuButton::Civilization => {
+                    let (names, chosen) = civilization_choice(&settings);
+                    settings.player_civ = Some(names[(chosen + 1) % names.len()].clone());
+                }
+                MenuButton::Difficulty => settings.difficulty = cycle(&Difficulty::ALL, &settings.difficulty),
+                MenuButton::Speed => settings.speed = cycle(&GameSpeed::ALL, &settings.speed),
+                MenuButton::TurnLimit => settings.turn_limit = cycle(&TURN_LIMITS, &settings.turn_limit),
+                MenuButton::TurnTimer => settings.turn_time_limit = cycle(&TURN_TIME_LIMITS, &settings.turn_time_limit),
+                MenuButton::FewerOpponents => settings.opponents = settings.opponents.saturating_sub(1).max(MIN_OPPONENTS),
+                MenuButton::MoreOpponents => settings.opponents = (settings.opponents + 1).min(MAX_OPPONENTS),
+                MenuButton::Barbarians => settings.barbarians = !settings.barbarians,
+                MenuButton::RequireOrders => settings.require_orders = !settings.require_orders,
+                MenuButton::Humans => settings.humans = (settings.humans + 1) % (MAX_HUMANS + 1),
+                MenuButton::Start => next_state.set(AppState::Loading),
+                MenuButton::Back => next_state.set(AppState::MainMenu),
+            },
+            Interaction::Hovered => background.0 = BUTTON_HOVER_COLOR,
+            Interaction::None => background.0 = BUTTON_COLOR,
+        }
+    }
+}
+
+// The pause menu, by mouse or keyboard: Up and Down pick a button and Enter presses it.
+// Escape steps back from the key bindings, and resumes the game from the main page.
+// Rebinding a key takes the next key pressed, with whatever modifiers are held. The
+// settings screen opens over the menu and has the keyboard while it's up.
+#[allow(clippy::too_many_arguments)]
+pub fn pause_menu_system(
+    mut commands: Commands,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut menu: ResMut<PauseMenu>,
+    mut settings_menu: ResMut<SettingsMenu>,
+    mut button_query: Query<(Ref<Interaction>, &PauseButton, &mut BackgroundColor)>,
+    screen_query: Query<Entity, With<PauseMenuScreen>>,
+    mut status_query: Query<&mut Text, With<PauseStatus>>,
+    save_sources: SaveSources,
+    mut input_map: ResMut<InputMap>,
+    mut notices: EventWriter<SaveNotice>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    if settings_menu.is_open() {
+        return;
+    }
+    if let Some(action) = menu.rebinding {
+        if keyboard.just_pressed(KeyCode::Escape) {
+            menu.rebinding = None;
+        } else if let Some(key) = keyboard.get_just_pressed().copied().find(|key| KeyChord::is_bindable(*key)) {
+            let chord = KeyChord::from_keyboard(key, &keyboard);
+            input_map.rebind(action, chord);
+            menu.rebinding = None;
+            let conflicts: Vec<&str> = input_map.conflicts(action).iter().map(Action::get_name).collect();
+            menu.notice = Some(if conflicts.is_empty() {
+                format!("{} is now {}", action.get_name(), chord)
+            } else {
+                format!("{} is now {}, which {} also uses", action.get_name(), chord, conflicts.join(", "))
+            });
+        } else {
+            return;
+        }
+        redraw_pause_menu(&mut commands, &menu, &screen_query, &input_map);
+        return;
+    }
+    
+    let buttons = menu.page.buttons();
+    let mut pressed = None;
+    for (interaction, button, _) in button_query.iter() {
+        let index = buttons.iter().position(|other| other == button).unwrap_or(0);
+        match *interaction {
+            Interaction::Pressed if interaction.is_changed() => pressed = Some(*button),
+            // The mouse takes the focus when it moves onto a button
+            Interaction::Hovered if interaction.is_changed() && index != menu.focus => menu.focus = index,
+            _ => {}
+        }
+    }
+    if keyboard.just_pressed(KeyCode::ArrowDown) {
+        menu.focus = (menu.focus + 1) % buttons.len();
+    }
+    if keyboard.just_pressed(KeyCode::ArrowUp) {
+        menu.focus = (menu.focus + buttons.len() - 1) % buttons.len();
+    }
+    if keyboard.any_just_pressed([KeyCode::Enter, KeyCode::NumpadEnter]) {
+        pressed = Some(buttons[menu.focus.min(buttons.len() - 1)]);
+    }
+    if keyboard.just_pressed(KeyCode::Escape) {
+        pressed = Some(match menu.page {
+            PausePage::Main => PauseButton::Resume,
+            PausePage::Keys => PauseButton::Back,
+        });
+    }
+    
+    let focused = buttons.get(menu.focus).copied();
+    for (_, button, mut background) in button_query.iter_mut() {
+        let color = if Some(*button) == focused { BUTTON_HOVER_COLOR } else { BUTTON_COLOR };
+        if background.0 != color {
+            background.0 = color;
+        }
+    }
+    
+    let Some(button) = pressed else { return };
+    let mut page = menu.page;
+    match button {
+        PauseButton::Resume => next_state.set(AppState::InGame),
+        PauseButton::Save => {
+            let message = save_sources.quicksave();
+            if let Ok(mut status) = status_query.single_mut() {
+                **status = message.clone();
+            }
+            menu.notice = Some(message.clone());
+            notices.write(SaveNotice { message });
+        }
+        PauseButton::Settings => settings_menu.open(),
+        PauseButton::KeyBindings => page = PausePage::Keys,
+        // Leaving the game tears it down; the setup screen then picks the next one's options
+        PauseButton::NewGame => next_state.set(AppState::Setup),
+        PauseButton::MainMenu => next_state.set(AppState::MainMenu),
+        PauseButton::Quit => {
+            exit.write(AppExit::Success);
+        }
+        PauseButton::Rebind(action) => menu.rebinding = Some(action),
+        PauseButton::ResetKeys => {
+            *input_map = InputMap::default();
+            menu.notice = Some("Keys reset to defaults".to_string());
+        }
+        PauseButton::Back => page = PausePage::Main,
+    }
+    if matches!(button, PauseButton::KeyBindings | PauseButton::Rebind(_) | PauseButton::ResetKeys | PauseButton::Back) {
+        // Redraw the menu with the new page or bindings, keeping the keyboard's place
+        if page != menu.page {
+            // Coming back up, the keyboard lands on the button that led to the page left
+            menu.focus = if page < menu.page {
+                page.buttons().iter().position(|other| *other == PauseButton::KeyBindings).unwrap_or(0)
+            } else {
+                0
+            };
+            menu.page = page;
+        }
+        redraw_pause_menu(&mut commands, &menu, &screen_query, &input_map);
+    }
+}
+
+// Replaces the menu with one showing the current page and bindings
+fn redraw_pause_menu(commands: &mut Commands, menu: &PauseMenu, screen_query: &Query<Entity, With<PauseMenuScreen>>, input_map: &InputMap) {
+    for entity in screen_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    spawn_pause_menu(commands, menu, input_map);
+}
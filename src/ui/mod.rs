@@ -1,3 +1,37 @@
 pub mod game_panels;
+pub mod city_banners;
+pub mod empire_overview;
+pub mod research;
+pub mod diplomacy;
+pub mod demographics;
+pub mod combat_panel;
+pub mod combat_log;
+pub mod load_menu;
+pub mod menus;
+pub mod combat_effects;
+pub mod game_over;
+pub mod notification_feed;
+pub mod replay_viewer;
+pub mod hotseat;
+pub mod observer;
+pub mod turn_report;
+pub mod user_settings;
+pub mod ui_scale;
+pub mod reports;
+pub mod unit_action_bar;
+pub mod production_prompt;
+pub mod end_turn_button;
+pub mod glyph_sheet;
+pub mod tooltips;
+pub mod settings_menu;
+pub mod help_overlay;
+pub mod relations_strip;
+pub mod city_screen;
+pub mod header_bar;
+pub mod event_focus;
+pub mod resources_panel;
+pub mod panels;
+pub mod hud;
+pub mod plugin;
 
 pub use game_panels::*;
\ No newline at end of file
@@ -0,0 +1,273 @@
+use bevy::prelude::*;
+use std::collections::VecDeque;
+use crate::game::hex::HexCoord;
+use crate::game::civilization::CivilizationManager;
+use crate::game::notifications::{GameNotification, NotificationCategory};
+use super::event_focus::{AlertStyle, EventCamera};
+use super::game_panels::UIState;
+use super::user_settings::UserSettings;
+
+// Toasts on screen at once; older ones make way for new ones
+const MAX_TOASTS: usize = 6;
+// Seconds a toast stays fully visible, then how long it takes to fade out
+const TOAST_SECONDS: f32 = 6.0;
+const TOAST_FADE_SECONDS: f32 = 1.0;
+const TOAST_BACKGROUND_ALPHA: f32 = 0.85;
+// Notifications kept for the history panel
+const MAX_HISTORY: usize = 50;
+
+const HEADER_COLOR: Color = Color::srgb(0.9, 0.9, 0.7);
+
+/// One notification the player has been shown, for the history panel
+#[derive(Clone)]
+pub struct HistoryEntry {
+    pub turn: u32,
+    pub category: NotificationCategory,
+    pub text: String,
+}
+
+/// The player's latest notifications, oldest first, capped at MAX_HISTORY
+#[derive(Resource, Default)]
+pub struct NotificationHistory {
+    entries: VecDeque<HistoryEntry>,
+}
+
+impl NotificationHistory {
+    pub fn push(&mut self, entry: HistoryEntry) {
+        self.entries.push_back(entry);
+        while self.entries.len() > MAX_HISTORY {
+            self.entries.pop_front();
+        }
+    }
+}
+
+// Column of toasts in the top right, newest on top
+#[derive(Component)]
+pub struct ToastFeed;
+
+#[derive(Component)]
+pub struct Toast {
+    location: Option<HexCoord>,
+    shown_at: f32,
+    color: Color,
+}
+
+#[derive(Component)]
+pub struct NotificationHistoryPanel;
+
+pub(super) fn category_color(category: NotificationCategory) -> Color {
+    match category {
+        NotificationCategory::City => Color::srgb(0.7, 1.0, 0.7),
+        NotificationCategory::Research => Color::srgb(0.6, 0.8, 1.0),
+        NotificationCategory::Military => Color::srgb(1.0, 0.6, 0.5),
+        NotificationCategory::Diplomacy => Color::srgb(0.9, 0.7, 1.0),
+        NotificationCategory::Warning => Color::srgb(1.0, 0.85, 0.4),
+        NotificationCategory::System => Color::srgb(0.85, 0.85, 0.85),
+    }
+}
+
+fn text(value: String, color: Color) -> impl Bundle {
+    (
+        Text::new(value),
+        TextFont {
+            font_size: 12.0,
+            ..default()
+        },
+        TextColor(color),
+    )
+}
+
+pub fn setup_notification_feed(mut commands: Commands) {
+    commands.spawn((
+        ToastFeed,
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(60.0),
+            right: Val::Px(10.0),
+            width: Val::Px(320.0),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::FlexEnd,
+            row_gap: Val::Px(4.0),
+            ..default()
+        },
+    ));
+    
+    // Notification history (toggled with M)
+    commands.spawn((
+        NotificationHistoryPanel,
+        Interaction::default(),
+        BackgroundColor(Color::srgba(0.05, 0.05, 0.1, 0.9)),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(120.0),
+            right: Val::Px(340.0),
+            width: Val::Px(420.0),
+            max_height: Val::Percent(75.0),
+            overflow: Overflow::clip(),
+            flex_direction: FlexDirection::Column,
+            padding: UiRect::all(Val::Px(6.0)),
+            row_gap: Val::Px(2.0),
+            display: Display::None,
+            ..default()
+        },
+    ));
+}
+
+// Shows the notifications meant for the player as toasts and keeps them for the history.
+// Events on the map follow the player's alert settings: ignored ones go only to the
+// history, and the camera is taken to the newest of those set to jump.
+#[allow(clippy::too_many_arguments)]
+pub fn notification_feed_system(
+    mut commands: Commands,
+    mut notifications: EventReader<GameNotification>,
+    feed_query: Query<Entity, With<ToastFeed>>,
+    toast_query: Query<(Entity, &Toast)>,
+    civ_manager: Res<CivilizationManager>,
+    mut history: ResMut<NotificationHistory>,
+    time: Res<Time>,
+    user_settings: Res<UserSettings>,
+    mut event_camera: EventCamera,
+) {
+    let player = civ_manager.get_viewed_civilization().map(|civ| civ.id);
+    let style = |notification: &GameNotification| notification.event.map_or(AlertStyle::Toast, |event| user_settings.alert_style(event));
+    let mut relevant = Vec::new();
+    for notification in notifications.read().filter(|notification| notification.concerns(player)) {
+        if style(notification) == AlertStyle::Ignore {
+            history.push(HistoryEntry {
+                turn: civ_manager.turn_number,
+                category: notification.category,
+                text: notification.text.clone(),
+            });
+        } else {
+            relevant.push(notification.clone());
+        }
+    }
+    let jump_to = relevant
+        .iter()
+        .rev()
+        .filter(|notification| style(notification) == AlertStyle::Jump)
+        .find_map(|notification| notification.location);
+    if let Some(coord) = jump_to {
+        event_camera.focus_event(coord);
+    }
+    if relevant.is_empty() {
+        return;
+    }
+    let Ok(feed) = feed_query.single() else { return };
+    
+    // Make room, oldest toasts first
+    let mut shown: Vec<_> = toast_query.iter().map(|(entity, toast)| (toast.shown_at, entity)).collect();
+    shown.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let excess = (shown.len() + relevant.len()).saturating_sub(MAX_TOASTS);
+    for (_, entity) in shown.iter().take(excess) {
+        commands.entity(*entity).despawn();
+    }
+    
+    let now = time.elapsed_secs();
+    let skipped = relevant.len().saturating_sub(MAX_TOASTS);
+    let mut toasts = Vec::new();
+    for (index, notification) in relevant.into_iter().enumerate() {
+        history.push(HistoryEntry {
+            turn: civ_manager.turn_number,
+            category: notification.category,
+            text: notification.text.clone(),
+        });
+        if index < skipped {
+            continue;
+        }
+        let color = category_color(notification.category);
+        let toast = commands
+            .spawn((
+                Button,
+                Toast { location: notification.location, shown_at: now, color },
+                BackgroundColor(Color::srgba(0.05, 0.05, 0.1, TOAST_BACKGROUND_ALPHA)),
+                BorderColor(color),
+                Node {
+                    max_width: Val::Px(320.0),
+                    padding: UiRect::axes(Val::Px(8.0), Val::Px(4.0)),
+                    border: UiRect::left(Val::Px(3.0)),
+                    ..default()
+                },
+            ))
+            .with_child(text(notification.text, color))
+            .id();
+        toasts.push(toast);
+    }
+    // Newest first
+    toasts.reverse();
+    commands.entity(feed).insert_children(0, &toasts);
+}
+
+// Fades toasts out once they've been up for a while, then removes them
+pub fn fade_toasts_system(
+    mut commands: Commands,
+    mut toast_query: Query<(Entity, &Toast, &Children, &mut BackgroundColor, &mut BorderColor)>,
+    mut text_query: Query<&mut TextColor>,
+    time: Res<Time>,
+) {
+    let now = time.elapsed_secs();
+    for (entity, toast, children, mut background, mut border) in toast_query.iter_mut() {
+        let age = now - toast.shown_at;
+        if age < TOAST_SECONDS {
+            continue;
+        }
+        if age >= TOAST_SECONDS + TOAST_FADE_SECONDS {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        let alpha = 1.0 - (age - TOAST_SECONDS) / TOAST_FADE_SECONDS;
+        background.0.set_alpha(TOAST_BACKGROUND_ALPHA * alpha);
+        border.0 = toast.color.with_alpha(alpha);
+        for child in children.iter() {
+            if let Ok(mut color) = text_query.get_mut(child) {
+                color.0 = toast.color.with_alpha(alpha);
+            }
+        }
+    }
+}
+
+// Clicking a toast centers the map on where it happened and dismisses it
+pub fn toast_click_system(
+    mut commands: Commands,
+    toast_query: Query<(Entity, &Interaction, &Toast), Changed<Interaction>>,
+    mut event_camera: EventCamera,
+) {
+    for (entity, interaction, toast) in toast_query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        if let Some(location) = toast.location {
+            event_camera.focus_event(location);
+        }
+        commands.entity(entity).despawn();
+    }
+}
+
+// Rebuilds the history panel when it's opened and when something new comes in.
+// Newest entries come first.
+pub fn update_notification_history_panel(
+    mut commands: Commands,
+    ui_state: Res<UIState>,
+    history: Res<NotificationHistory>,
+    mut panel_query: Query<(Entity, &mut Node), With<NotificationHistoryPanel>>,
+) {
+    let Ok((panel_entity, mut panel_node)) = panel_query.single_mut() else { return };
+    let display = if ui_state.show_notification_history { Display::Flex } else { Display::None };
+    if panel_node.display != display {
+        panel_node.display = display;
+    }
+    if !ui_state.show_notification_history || (!ui_state.is_changed() && !history.is_changed()) {
+        return;
+    }
+    
+    commands.entity(panel_entity).despawn_related::<Children>();
+    commands.entity(panel_entity).with_children(|panel| {
+        panel.spawn(text("=== MESSAGES (M to close) ===".to_string(), HEADER_COLOR));
+        if history.entries.is_empty() {
+            panel.spawn(text("Nothing to report yet".to_string(), category_color(NotificationCategory::System)));
+        }
+        for entry in history.entries.iter().rev() {
+            panel.spawn(text(format!("T{} {}", entry.turn, entry.text), category_color(entry.category)));
+        }
+    });
+}
@@ -0,0 +1,145 @@
+use bevy::prelude::*;
+use std::time::Duration;
+use crate::game::ai::AiTurnTimer;
+use crate::game::cities::City;
+use crate::game::civilization::CivilizationManager;
+use crate::game::camera_zoom::CameraTween;
+use crate::game::units::Unit;
+use super::game_panels::UIState;
+
+// Turn delays Minus and Equal step through, in seconds
+const OBSERVER_DELAYS: [f32; 6] = [0.0, 0.1, 0.25, 0.5, 1.0, 2.0];
+
+#[derive(Component)]
+pub struct ObserverHud;
+
+pub fn setup_observer_hud(mut commands: Commands) {
+    // Observer controls (top center, only in games without humans)
+    commands.spawn((
+        ObserverHud,
+        Text::new(""),
+        TextFont {
+            font_size: 13.0,
+            ..default()
+        },
+        TextColor(Color::srgb(0.9, 0.9, 0.7)),
+        BackgroundColor(Color::srgba(0.05, 0.05, 0.1, 0.85)),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.0),
+            left: Val::Px(0.0),
+            right: Val::Px(0.0),
+            margin: UiRect::horizontal(Val::Auto),
+            width: Val::Px(520.0),
+            max_width: Val::Percent(96.0),
+            padding: UiRect::axes(Val::Px(8.0), Val::Px(4.0)),
+            display: Display::None,
+            ..default()
+        },
+    ));
+}
+
+// Looks at a civilization's capital, or failing that one of its units
+fn center_on_civ(
+    civ_id: u32,
+    city_query: &Query<&City>,
+    unit_query: &Query<&Unit>,
+    camera_tween: &mut CameraTween,
+) {
+    let capital = city_query.iter().find(|city| city.civilization_id == civ_id && city.is_capital).map(|city| city.hex_coord);
+    let target = capital.or_else(|| unit_query.iter().find(|unit| unit.civilization_id == civ_id).map(|unit| unit.hex_coord));
+    if let Some(target) = target {
+        camera_tween.center_on(target);
+    }
+}
+
+// In a game without humans: Comma and Period switch the civilization the panels follow,
+// Minus and Equal change how long each AI turn is shown for, and Space pauses
+pub fn observer_control_system(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut civ_manager: ResMut<CivilizationManager>,
+    mut timer: ResMut<AiTurnTimer>,
+    mut ui_state: ResMut<UIState>,
+    city_query: Query<&City>,
+    unit_query: Query<&Unit>,
+    mut camera_tween: ResMut<CameraTween>,
+) {
+    if !civ_manager.is_observer_game() {
+        return;
+    }
+    let order = civ_manager.turn_order();
+    let Some(&first) = order.first() else { return };
+
+    // Start with the first civilization, and move on when the watched one is destroyed
+    let current = civ_manager.observed.and_then(|observed| order.iter().position(|id| *id == observed));
+    let step = if keyboard.just_pressed(KeyCode::Period) {
+        1
+    } else if keyboard.just_pressed(KeyCode::Comma) {
+        order.len() - 1
+    } else {
+        0
+    };
+    let observed = match current {
+        Some(index) => order[(index + step) % order.len()],
+        None => first,
+    };
+    if civ_manager.observed != Some(observed) {
+        civ_manager.observed = Some(observed);
+        center_on_civ(observed, &city_query, &unit_query, &mut camera_tween);
+        // Open panels redraw for the new civilization
+        ui_state.set_changed();
+    }
+
+    let delay = timer.0.duration().as_secs_f32();
+    let index = OBSERVER_DELAYS.iter().position(|step| *step >= delay).unwrap_or(OBSERVER_DELAYS.len() - 1);
+    let new_delay = if keyboard.just_pressed(KeyCode::Equal) {
+        OBSERVER_DELAYS[(index + 1).min(OBSERVER_DELAYS.len() - 1)]
+    } else if keyboard.just_pressed(KeyCode::Minus) {
+        OBSERVER_DELAYS[index.saturating_sub(1)]
+    } else {
+        delay
+    };
+    if new_delay != delay {
+        timer.0.set_duration(Duration::from_secs_f32(new_delay));
+    }
+    if keyboard.just_pressed(KeyCode::Space) {
+        if timer.0.paused() {
+            timer.0.unpause();
+        } else {
+            timer.0.pause();
+        }
+    }
+}
+
+pub fn update_observer_hud(
+    civ_manager: Res<CivilizationManager>,
+    timer: Res<AiTurnTimer>,
+    mut hud_query: Query<(&mut Text, &mut Node), With<ObserverHud>>,
+) {
+    let Ok((mut text, mut node)) = hud_query.single_mut() else { return };
+    let observer = civ_manager.is_observer_game();
+    let display = if observer { Display::Flex } else { Display::None };
+    if node.display != display {
+        node.display = display;
+    }
+    if !observer {
+        return;
+    }
+
+    let watched = civ_manager.get_viewed_civilization().map_or("nobody", |civ| civ.name.as_str());
+    let pace = if timer.0.paused() {
+        "PAUSED - the current turn finishes, then play stops".to_string()
+    } else {
+        format!("Turn delay: {:.2}s", timer.0.duration().as_secs_f32())
+    };
+    let line = format!(
+        "OBSERVING - Turn {}, {} to move\nWatching: {}   {}\n,/. switch civ   -/= turn delay   SPACE pause   F5 score graph",
+        civ_manager.turn_number,
+        civ_manager.civ_name(civ_manager.current_turn_civ),
+        watched,
+        pace,
+    );
+    if **text != line {
+        **text = line;
+    }
+}
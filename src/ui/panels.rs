@@ -0,0 +1,189 @@
+// The window layer the interactive screens are built from: a framed window with a title
+// bar to drag it by, a close button, and a body that scrolls under the mouse wheel. Each
+// window is shown by one UIState flag, which its close button clears. A window takes the
+// pointer while the cursor is over it, so the map's mouse systems (gated on
+// ui_wants_input) leave its clicks and scrolling alone. Read-only HUD text stays plain
+// Text; this is for the screens the player works in.
+use bevy::prelude::*;
+use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
+use bevy::ui::{FocusPolicy, RelativeCursorPosition};
+use super::game_panels::UIState;
+use super::menus::{BUTTON_COLOR, BUTTON_HOVER_COLOR, TITLE_COLOR, text};
+
+const WINDOW_COLOR: Color = Color::srgba(0.05, 0.05, 0.1, 0.85);
+const TITLE_BAR_COLOR: Color = Color::srgba(0.15, 0.15, 0.22, 0.95);
+const SCROLL_LINE: f32 = 16.0;
+
+/// How a window opens: its title, where it sits, how big it gets and the UIState flag
+/// that shows it. `place` is its distance from the screen edges it keeps to, Auto for
+/// the others.
+pub struct PanelSpec {
+    pub title: &'static str,
+    pub place: UiRect,
+    pub width: f32,
+    pub max_height: f32,
+    pub shown: fn(&mut UIState) -> &mut bool,
+}
+
+/// A window from `spawn_panel`; shown while its flag is set
+#[derive(Component)]
+pub struct PanelWindow {
+    shown: fn(&mut UIState) -> &mut bool,
+}
+
+// The bar along a window's top; dragging it moves the window
+#[derive(Component)]
+pub struct PanelTitleBar {
+    window: Entity,
+}
+
+#[derive(Component)]
+pub struct PanelCloseButton {
+    window: Entity,
+}
+
+/// Where a window's contents go; scrolls when they run past the window's height
+#[derive(Component)]
+pub struct PanelBody;
+
+// Spawns a window, hidden until its flag is set, and hands back its body for the caller
+// to fill
+pub fn spawn_panel(commands: &mut Commands, spec: PanelSpec) -> Entity {
+    let node = Node {
+        position_type: PositionType::Absolute,
+        left: spec.place.left,
+        right: spec.place.right,
+        top: spec.place.top,
+        bottom: spec.place.bottom,
+        width: Val::Px(spec.width),
+        flex_direction: FlexDirection::Column,
+        display: Display::None,
+        ..default()
+    };
+    let window = commands
+        .spawn((PanelWindow { shown: spec.shown }, Interaction::default(), FocusPolicy::Block, BackgroundColor(WINDOW_COLOR), node))
+        .id();
+
+    let title_bar = (
+        PanelTitleBar { window },
+        Interaction::default(),
+        BackgroundColor(TITLE_BAR_COLOR),
+        Node {
+            justify_content: JustifyContent::SpaceBetween,
+            align_items: AlignItems::Center,
+            padding: UiRect::axes(Val::Px(6.0), Val::Px(2.0)),
+            ..default()
+        },
+    );
+    let close_button = (
+        Button,
+        PanelCloseButton { window },
+        BackgroundColor(BUTTON_COLOR),
+        Node {
+            padding: UiRect::axes(Val::Px(6.0), Val::Px(0.0)),
+            ..default()
+        },
+    );
+    commands.entity(window).with_children(|window| {
+        window.spawn(title_bar).with_children(|bar| {
+            bar.spawn(text(spec.title, 13.0, TITLE_COLOR));
+            bar.spawn(close_button).with_child(text("x", 13.0, Color::WHITE));
+        });
+    });
+    let body = commands
+        .spawn((
+            PanelBody,
+            Interaction::default(),
+            RelativeCursorPosition::default(),
+            ScrollPosition::default(),
+            Node {
+                max_height: Val::Px(spec.max_height),
+                flex_direction: FlexDirection::Column,
+                overflow: Overflow::scroll_y(),
+                padding: UiRect::all(Val::Px(6.0)),
+                row_gap: Val::Px(2.0),
+                ..default()
+            },
+        ))
+        .id();
+    commands.entity(window).add_child(body);
+    body
+}
+
+// Shows each window while its flag is set, and clears the flag when its close button
+// is clicked
+pub fn panel_window_system(
+    mut ui_state: ResMut<UIState>,
+    close_query: Query<(&Interaction, &PanelCloseButton, &mut BackgroundColor), Changed<Interaction>>,
+    mut window_query: Query<(&PanelWindow, &mut Node)>,
+) {
+    for (interaction, close, mut color) in close_query {
+        match interaction {
+            Interaction::Pressed => {
+                if let Ok((window, _)) = window_query.get(close.window) {
+                    *(window.shown)(&mut ui_state) = false;
+                }
+            }
+            Interaction::Hovered => color.0 = BUTTON_HOVER_COLOR,
+            Interaction::None => color.0 = BUTTON_COLOR,
+        }
+    }
+
+    // Read without marking UIState changed, so the panels that watch it don't redraw
+    let ui_state = ui_state.bypass_change_detection();
+    for (window, mut node) in window_query.iter_mut() {
+        let display = if *(window.shown)(ui_state) { Display::Flex } else { Display::None };
+        if node.display != display {
+            node.display = display;
+        }
+    }
+}
+
+// Moves a window while its title bar is held, by however far the cursor went since last
+// frame
+pub fn panel_drag_system(
+    windows: Query<&Window>,
+    ui_scale: Res<UiScale>,
+    title_query: Query<(&Interaction, &PanelTitleBar)>,
+    mut window_query: Query<&mut Node, With<PanelWindow>>,
+    mut last_cursor: Local<Option<Vec2>>,
+) {
+    let cursor = windows.single().ok().and_then(Window::cursor_position);
+    let held = title_query.iter().find(|(interaction, _)| **interaction == Interaction::Pressed);
+    let (Some((_, title_bar)), Some(cursor), Some(last)) = (held, cursor, *last_cursor) else {
+        *last_cursor = cursor;
+        return;
+    };
+    *last_cursor = Some(cursor);
+
+    let moved = (cursor - last) / ui_scale.0;
+    let Ok(mut node) = window_query.get_mut(title_bar.window) else { return };
+    let nudge = |val: &mut Val, by: f32| {
+        if let Val::Px(px) = val {
+            *px += by;
+        }
+    };
+    nudge(&mut node.left, moved.x);
+    nudge(&mut node.right, -moved.x);
+    nudge(&mut node.top, moved.y);
+    nudge(&mut node.bottom, -moved.y);
+}
+
+// Scrolls whichever window body the cursor is over with the mouse wheel, buttons in it
+// included
+pub fn panel_scroll_system(
+    mut scroll_events: EventReader<MouseWheel>,
+    mut body_query: Query<(&RelativeCursorPosition, &mut ScrollPosition), With<PanelBody>>,
+) {
+    for event in scroll_events.read() {
+        let lines = match event.unit {
+            MouseScrollUnit::Line => event.y * SCROLL_LINE,
+            MouseScrollUnit::Pixel => event.y,
+        };
+        for (cursor, mut scroll_position) in body_query.iter_mut() {
+            if cursor.mouse_over() {
+                scroll_position.offset_y = (scroll_position.offset_y - lines).max(0.0);
+            }
+        }
+    }
+}
@@ -0,0 +1,294 @@
+use bevy::prelude::*;
+use crate::game::*;
+use crate::game::camera_zoom::{camera_zoom_system, clamp_camera_system, camera_tween_system, camera_hotkey_system};
+use crate::game::map::{add_tile_visuals, toggle_elevation_shading, adjust_elevation_intensity, release_terrain_assets};
+use crate::game::civilization::research_system;
+use crate::game::statistics::stats_history_system;
+use crate::game::fonts::load_fonts;
+use crate::game::victory::elimination_system;
+use crate::game::units::{unit_selection_system, unit_order_system, update_selection_indicators, MovementIndicators, setup_movement_indicators, reset_movement_indicators, spawn_unit_markers, update_unit_marker_positions, update_unit_conditions, unit_condition_zoom_system, remove_orphaned_unit_markers};
+use crate::game::input::{attack_cursor_system, ui_wants_input, mouse_intent_system, keyboard_unit_move_system, text_entry_system, text_entry_display_system, setup_text_entry_ui};
+use crate::game::cities::{destroy_razed_cities, despawn_destroyed_city_markers, draw_trade_routes, draw_territory_borders, process_city_turns, update_city_markers, spawn_city_markers};
+use crate::game::ai::{ai_turn_system, auto_production_system};
+use crate::game::game_initialization::{AppState, InSession, end_turn_hotkey_system, end_turn_guard_system, turn_system, display_turn_info, setup_turn_info_ui};
+use crate::game::city_founding::{SettlementPreview, reset_settlement_preview, settlement_preview_system, settlement_preview_text_system, setup_settlement_preview_ui};
+use crate::game::unit_actions::unit_action_system;
+use crate::game::combat::{combat_system, attack_click_system};
+use super::city_banners::{spawn_city_banners, update_city_banners, position_city_banners, city_banner_click_system};
+use super::game_over::{setup_game_over_screen, show_game_over_screen, game_over_button_system};
+use super::diplomacy::{setup_diplomacy_panel, update_diplomacy_panel, diplomacy_button_system, DealComposer};
+use super::research::{setup_tech_tree, research_prompt_system, update_tech_tree, tech_tree_interaction_system};
+use super::demographics::{setup_demographics_panel, update_demographics_panel};
+use super::combat_panel::{setup_combat_panel, update_combat_panel};
+use super::combat_log::{setup_combat_log_panel, update_combat_log_panel};
+use super::load_menu::{setup_load_menu_panel, update_load_menu_panel};
+use super::menus::{setup_main_menu, setup_setup_screen, setup_loading_screen, setup_pause_menu, update_setup_labels, menu_button_system, pause_menu_system, PauseMenu};
+use super::combat_effects::{spawn_combat_effects, animate_damage_popups, animate_flash_effects, animate_dying_markers};
+use super::empire_overview::{OverviewSort, setup_empire_overview, update_empire_overview, empire_overview_interaction_system};
+use super::panels::{panel_window_system, panel_drag_system, panel_scroll_system};
+use super::game_panels::{UIState, reset_ui_state, setup_ui_panels, update_game_status_panel, update_status_civ_list, update_selected_unit_info, update_hotkeys_panel, toggle_ui_panels, update_unit_list_panel, unit_list_interaction_system, unit_list_scroll_system, update_city_panel, city_panel_button_system, city_rename_button_system};
+use super::hotseat::{HotseatViews, hotseat_turn_system, setup_handover_screen, handover_input_system};
+use super::observer::{setup_observer_hud, observer_control_system, update_observer_hud};
+use crate::game::console::{ConsoleRegen, DevConsole, setup_console_ui, console_toggle_system, console_command_system, console_regen_system, console_display_system};
+use super::replay_viewer::{start_replay, end_replay, replay_playback_system, draw_replay, update_replay_panel};
+use super::header_bar::{setup_header_bar, update_header_bar};
+use super::event_focus::{FocusPulse, draw_focus_pulse};
+use super::resources_panel::{ResourceTour, setup_resources_panel, update_resources_panel, resource_row_system};
+use super::city_screen::{setup_city_screen, city_screen_key_system, city_tile_click_system, update_city_screen, draw_city_screen};
+use super::relations_strip::{setup_relations_strip, update_relations_strip, relation_chip_system};
+use super::help_overlay::{ActiveHint, Guide, setup_guide, guide_keyboard_system, guide_button_system, update_guide_screen, hint_callout_system};
+use super::settings_menu::{SettingsMenu, settings_menu_system, update_settings_menu};
+use super::user_settings::{apply_user_settings, save_user_settings_system};
+use super::ui_scale::{InterfaceScale, interface_scale_system};
+use super::glyph_sheet::{setup_glyph_sheet, update_glyph_sheet};
+use super::end_turn_button::{setup_end_turn_button, update_end_turn_button, end_turn_button_system};
+use super::unit_action_bar::{setup_unit_action_bar, update_unit_action_bar, unit_action_button_system};
+use super::tooltips::{setup_tooltip, attach_map_tooltips, tooltip_system};
+use super::reports::{setup_report_overlays, update_turn_summary_overlay, update_debug_overlay};
+use super::production_prompt::{ProductionPrompt, production_prompt_system, choose_later_button_system};
+use super::turn_report::{TurnReport, setup_turn_report_panel, collect_turn_report_system, update_turn_report_panel, turn_report_button_system};
+use super::notification_feed::{NotificationHistory, setup_notification_feed, notification_feed_system, fade_toasts_system, toast_click_system, update_notification_history_panel};
+use crate::game::notifications::relay_notifications_system;
+use super::hud::*;
+
+/// Everything drawn or clicked: the camera, map meshes and markers, the HUD, panels and
+/// menus, and the mouse and camera controls
+pub struct GameRenderPlugin;
+
+impl Plugin for GameRenderPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_plugins(CullingPlugin)
+            .insert_resource(GridSettings::default())
+            .insert_resource(HoverState::default())
+            .insert_resource(InfoDisplayMode::Basic)
+            .init_resource::<SettlementPreview>()
+            .init_resource::<MovementIndicators>()
+            .insert_resource(UIState::default())
+            .init_resource::<OverviewSort>()
+            .init_resource::<ResourceTour>()
+            .init_resource::<DealComposer>()
+            .init_resource::<NotificationHistory>()
+            .init_resource::<FocusPulse>()
+            .init_resource::<HotseatViews>()
+            .init_resource::<DevConsole>()
+            .init_resource::<PauseMenu>()
+            .init_resource::<SettingsMenu>()
+            .init_resource::<Guide>()
+            .init_resource::<ActiveHint>()
+            .init_resource::<TurnReport>()
+            .init_resource::<ProductionPrompt>()
+            .init_resource::<VisualConfig>()
+            .init_resource::<InterfaceScale>()
+            .add_systems(Startup, (setup_camera, load_fonts, apply_user_settings))
+            // Each game gets its own HUD, panels and grid, and they all go when it ends
+            .add_systems(OnEnter(InSession), (
+                setup_hud,
+                (setup_grid_lines, setup_movement_indicators),
+                setup_turn_info_ui,
+                setup_ui_panels,
+                setup_settlement_preview_ui,
+                setup_text_entry_ui,
+                setup_empire_overview,
+                setup_tech_tree,
+                setup_diplomacy_panel,
+                setup_demographics_panel,
+                setup_combat_panel,
+                setup_combat_log_panel,
+                setup_notification_feed,
+                setup_load_menu_panel,
+                setup_game_over_screen,
+                setup_observer_hud,
+                setup_console_ui,
+                setup_turn_report_panel,
+                (setup_report_overlays, setup_glyph_sheet),
+                // The clickable controls along the bottom, and the box their tooltips show in
+                (setup_unit_action_bar, setup_end_turn_button, setup_tooltip, setup_guide, setup_relations_strip, setup_city_screen, setup_header_bar, setup_resources_panel),
+            ))
+            .add_systems(OnExit(InSession), (reset_ui_state, reset_hover_state, reset_settlement_preview, reset_movement_indicators, release_terrain_assets, release_grid_assets))
+            .add_systems(OnEnter(AppState::MainMenu), setup_main_menu)
+            .add_systems(OnEnter(AppState::Setup), setup_setup_screen)
+            .add_systems(OnEnter(AppState::Loading), setup_loading_screen)
+            .add_systems(OnEnter(AppState::Paused), setup_pause_menu)
+            .add_systems(OnEnter(AppState::Handover), setup_handover_screen)
+            .add_systems(OnEnter(AppState::GameOver), show_game_over_screen)
+            .add_systems(OnEnter(AppState::Replay), start_replay)
+            .add_systems(OnExit(AppState::Replay), end_replay)
+            .add_systems(PreUpdate, mouse_intent_system
+                .after(bevy::ui::UiSystem::Focus)
+                .before(keyboard_unit_move_system)
+                .run_if(in_state(AppState::InGame)))
+            // Once the frame's spawning is done, so a generated or loaded map is drawn
+            // from its first frame
+            .add_systems(PostUpdate, add_tile_visuals)
+            // After every system that moves the camera, and before the move is drawn
+            .add_systems(PostUpdate, clamp_camera_system
+                .before(bevy::transform::TransformSystem::TransformPropagate)
+                .run_if(in_state(InSession)))
+            .add_systems(Update, (
+                // Menus
+                menu_button_system.run_if(in_state(AppState::MainMenu).or(in_state(AppState::Setup))),
+                update_setup_labels.run_if(in_state(AppState::Setup)),
+                pause_menu_system.run_if(in_state(AppState::Paused)),
+                (settings_menu_system, update_settings_menu.after(settings_menu_system))
+                    .before(menu_button_system)
+                    .before(pause_menu_system)
+                    .run_if(in_state(AppState::MainMenu).or(in_state(AppState::Paused))),
+                handover_input_system.run_if(in_state(AppState::Handover)),
+                game_over_button_system.run_if(in_state(AppState::GameOver)),
+                hotseat_turn_system.after(turn_system).after(ai_turn_system).after(elimination_system).run_if(in_state(AppState::InGame)),
+            ))
+            .add_systems(Update, (
+                // Replay playback
+                replay_playback_system,
+                draw_replay.after(replay_playback_system),
+                update_replay_panel.after(replay_playback_system),
+            ).run_if(in_state(AppState::Replay)))
+            .add_systems(Update, (
+                // Markers and overlays on the map
+                despawn_destroyed_city_markers.after(destroy_razed_cities),
+                update_selection_indicators.after(unit_order_system).after(unit_selection_system),
+                settlement_preview_system.after(unit_selection_system),
+                settlement_preview_text_system.after(settlement_preview_system),
+            ).run_if(in_state(AppState::InGame)))
+            .add_systems(Update, (
+                // Visual and UI systems (Group 3), which keep the panels current while paused
+                // or on the results screen; their clicks and toggles only work in play. The
+                // readouts wait for the game to be set up.
+                (
+                    display_turn_info,
+                    update_game_status_panel,
+                    update_selected_unit_info,
+                    update_hotkeys_panel,
+                    update_unit_list_panel,
+                ).run_if(not(in_state(AppState::Loading))),
+                spawn_city_markers,
+                update_city_markers.after(spawn_city_markers),
+                (draw_trade_routes, draw_territory_borders),
+                text_entry_display_system,
+                spawn_unit_markers,
+                update_unit_marker_positions,
+                (update_unit_conditions, unit_condition_zoom_system).after(spawn_unit_markers),
+                (
+                    panel_window_system.after(toggle_ui_panels),
+                    (panel_drag_system, panel_scroll_system).run_if(in_state(AppState::InGame)),
+                ),
+                update_unit_action_bar,
+                unit_action_button_system.run_if(in_state(AppState::InGame)),
+                toggle_ui_panels.run_if(in_state(AppState::InGame)),
+                unit_list_interaction_system.run_if(in_state(AppState::InGame)),
+                unit_list_scroll_system.run_if(in_state(AppState::InGame)),
+                update_city_panel,
+                city_panel_button_system.run_if(in_state(AppState::InGame)),
+                city_rename_button_system.run_if(in_state(AppState::InGame)),
+            ).run_if(in_state(InSession)))
+            .add_systems(Update, (
+                // Input and interaction (Group 4)
+                camera_movement, 
+                camera_zoom_system.run_if(not(ui_wants_input)),
+                camera_hotkey_system,
+                // After the keys, so panning by hand cancels a glide before it moves the camera again
+                camera_tween_system.after(camera_movement).after(camera_hotkey_system),
+                // Before combat_system, so the Escape that cancels an attack still sees it pending
+                basic_input.before(combat_system),
+                hex_hover_system,
+                attack_cursor_system.after(attack_click_system),
+            ).run_if(in_state(AppState::InGame)))
+            .add_systems(Update, (
+                // Debug and world info (Group 5)
+                toggle_grid_system,
+                spawn_resource_markers,
+                tile_info_system,
+                toggle_info_display,
+                toggle_elevation_shading,
+                adjust_elevation_intensity,
+                update_controls_hint,
+                attach_map_tooltips,
+                tooltip_system.after(attach_map_tooltips),
+                (guide_button_system, update_guide_screen.after(guide_button_system), hint_callout_system),
+                (city_tile_click_system, update_city_screen.after(city_tile_click_system), draw_city_screen, update_header_bar),
+            ).run_if(in_state(AppState::InGame)))
+            // Before the end-turn key and the pause menu, which share its keys
+            .add_systems(PreUpdate, city_screen_key_system
+                .after(text_entry_system)
+                .before(end_turn_hotkey_system)
+                .run_if(in_state(AppState::InGame)))
+            // Ahead of every game key, so none of them reads a key meant for the guide
+            .add_systems(PreUpdate, guide_keyboard_system
+                .after(bevy::input::InputSystem)
+                .before(text_entry_system)
+                .run_if(in_state(AppState::InGame)))
+            .add_systems(Update, (
+                // City banners (Group 6)
+                spawn_city_banners,
+                update_city_banners.after(spawn_city_banners),
+                position_city_banners
+                    .after(spawn_city_banners)
+                    .after(camera_movement)
+                    .after(camera_zoom_system)
+                    .after(camera_tween_system)
+                    .run_if(not(in_state(AppState::Replay))),
+                city_banner_click_system.run_if(in_state(AppState::InGame)),
+                update_empire_overview,
+                empire_overview_interaction_system.run_if(in_state(AppState::InGame)),
+                (update_resources_panel, resource_row_system.run_if(in_state(AppState::InGame))),
+                research_prompt_system.after(research_system).after(turn_system).after(ai_turn_system),
+                update_tech_tree.after(research_prompt_system),
+                tech_tree_interaction_system.run_if(in_state(AppState::InGame)),
+                update_diplomacy_panel,
+                diplomacy_button_system.run_if(in_state(AppState::InGame)),
+                (update_relations_strip, relation_chip_system.run_if(in_state(AppState::InGame))),
+                update_status_civ_list,
+                update_combat_panel.after(attack_click_system),
+                update_combat_log_panel,
+                update_load_menu_panel,
+                update_demographics_panel.after(stats_history_system),
+            ).run_if(in_state(InSession)))
+            .add_systems(Update, (
+                // Combat effects on the map (Group 9)
+                spawn_combat_effects.after(combat_system).after(ai_turn_system),
+                remove_orphaned_unit_markers.after(spawn_combat_effects),
+                animate_damage_popups,
+                animate_flash_effects,
+                animate_dying_markers,
+            ).run_if(in_state(AppState::InGame)))
+            .add_systems(Update, (
+                // Notifications, the turn report, production prompts, the End Turn button and the observer HUD (Group 10)
+                notification_feed_system.after(relay_notifications_system),
+                fade_toasts_system.run_if(in_state(AppState::InGame)),
+                (toast_click_system, draw_focus_pulse).run_if(in_state(AppState::InGame)),
+                update_notification_history_panel,
+                collect_turn_report_system.after(relay_notifications_system),
+                update_turn_report_panel.after(collect_turn_report_system),
+                update_turn_summary_overlay,
+                update_debug_overlay,
+                update_glyph_sheet,
+                turn_report_button_system.run_if(in_state(AppState::InGame)),
+                production_prompt_system.after(process_city_turns).after(auto_production_system).after(turn_system).after(ai_turn_system),
+                choose_later_button_system.before(production_prompt_system).run_if(in_state(AppState::InGame)),
+                end_turn_button_system.before(end_turn_guard_system).before(turn_system).run_if(in_state(AppState::InGame)),
+                // Last, so the label shows the turn as the frame's clicks and keys have left it
+                update_end_turn_button
+                    .after(end_turn_button_system)
+                    .after(turn_system)
+                    .after(ai_turn_system)
+                    .after(unit_action_system)
+                    .after(unit_order_system)
+                    .after(city_panel_button_system)
+                    .after(production_prompt_system),
+                observer_control_system.run_if(in_state(AppState::InGame)),
+                update_observer_hud,
+            ).run_if(in_state(InSession)))
+            .add_systems(Update, (
+                // Developer console
+                console_toggle_system.run_if(in_state(AppState::InGame)),
+                console_command_system.run_if(in_state(AppState::InGame)),
+                console_display_system.run_if(in_state(InSession)),
+                console_regen_system.run_if(in_state(AppState::Setup).and(resource_exists::<ConsoleRegen>)),
+            ))
+            // Menus included, so every screen fits the window
+            .add_systems(Update, interface_scale_system)
+            // Once the frame's toggles are in, so each change is written as it's made
+            .add_systems(PostUpdate, save_user_settings_system);
+    }
+}
@@ -0,0 +1,98 @@
+use bevy::prelude::*;
+use std::collections::{HashSet, VecDeque};
+use crate::game::camera_zoom::CameraTween;
+use crate::game::cities::{City, CitySelection};
+use crate::game::civilization::CivilizationManager;
+use crate::game::game_initialization::{GameSettings, TurnStartedEvent};
+use super::game_panels::{CityPanelAction, CityPanelButton};
+
+/// Cities of the player's left with nothing to build, opened one at a time in the city
+/// panel as their turn starts
+#[derive(Resource, Default)]
+pub struct ProductionPrompt {
+    pending: VecDeque<Entity>, // Still to be asked about, in order
+    pub current: Option<Entity>, // The city whose panel is open for a choice
+    deferred: HashSet<Entity>, // Put off until the player's next turn
+}
+
+impl ProductionPrompt {
+    // Opens the choice for `city` now, ahead of any still pending
+    pub fn ask(&mut self, city: Entity) {
+        self.deferred.remove(&city);
+        self.current = Some(city);
+    }
+    
+    // Whether the player has put off choosing for `city` this turn
+    pub fn is_deferred(&self, city: Entity) -> bool {
+        self.deferred.contains(&city)
+    }
+}
+
+// Opens the panel of each idle city in turn when the player's turn starts, moving on
+// once something is picked or the city is put off until later. Closing the panel puts
+// off the rest as well.
+pub fn production_prompt_system(
+    mut prompt: ResMut<ProductionPrompt>,
+    mut turn_events: EventReader<TurnStartedEvent>,
+    city_query: Query<&City>,
+    civ_manager: Res<CivilizationManager>,
+    settings: Res<GameSettings>,
+    mut city_selection: ResMut<CitySelection>,
+    mut camera_tween: ResMut<CameraTween>,
+) {
+    let Some(player_civ) = civ_manager.get_player_civilization() else { return };
+    let player_id = player_civ.id;
+    if turn_events.read().any(|event| event.civilization_id == player_id) && !settings.auto_production {
+        prompt.pending = player_civ.cities
+            .iter()
+            .copied()
+            .filter(|entity| city_query.get(*entity).is_ok_and(City::needs_production))
+            .collect();
+        prompt.current = None;
+        prompt.deferred.clear();
+    }
+    
+    if let Some(current) = prompt.current {
+        if city_selection.selected_city != Some(current) {
+            let put_off: Vec<Entity> = prompt.pending.drain(..).chain([current]).collect();
+            prompt.deferred.extend(put_off);
+            prompt.current = None;
+            return;
+        }
+        if city_query.get(current).is_ok_and(City::needs_production) {
+            return;
+        }
+        prompt.current = None;
+    }
+    
+    // Skipping any that were given work some other way in the meantime
+    while let Some(next) = prompt.pending.pop_front() {
+        let Ok(city) = city_query.get(next) else { continue };
+        if city.civilization_id != player_id || !city.needs_production() {
+            continue;
+        }
+        city_selection.selected_city = Some(next);
+        camera_tween.center_on(city.hex_coord);
+        prompt.current = Some(next);
+        return;
+    }
+}
+
+// "Choose later" leaves the city idle, turning its production into gold, and moves on
+// to the next one
+pub fn choose_later_button_system(
+    button_query: Query<(&Interaction, &CityPanelButton), Changed<Interaction>>,
+    mut prompt: ResMut<ProductionPrompt>,
+    mut city_selection: ResMut<CitySelection>,
+) {
+    let pressed = button_query
+        .iter()
+        .any(|(interaction, button)| *interaction == Interaction::Pressed && button.action == CityPanelAction::ChooseLater);
+    if !pressed {
+        return;
+    }
+    if let Some(current) = prompt.current.take() {
+        prompt.deferred.insert(current);
+        city_selection.selected_city = None;
+    }
+}
@@ -0,0 +1,214 @@
+use bevy::prelude::*;
+use crate::game::cities::City;
+use crate::game::civilization::{Civilization, CivilizationManager};
+use crate::game::diplomacy::Relation;
+use crate::game::game_initialization::GameSettings;
+use crate::game::statistics::{CivSummary, Demographic, MilitaryComparison, shows_everything};
+use crate::game::units::Unit;
+use super::diplomacy::{relation_label, DealComposer};
+use super::game_panels::UIState;
+use super::tooltips::TooltipSource;
+
+const CHIP_COLOR: Color = Color::srgba(0.08, 0.08, 0.12, 0.85);
+const CHIP_HOVER_COLOR: Color = Color::srgba(0.25, 0.25, 0.35, 0.9);
+const SWATCH_SIZE: f32 = 10.0;
+
+/// Row of chips along the top edge, one per civilization the player has met
+#[derive(Component)]
+pub struct RelationsStrip;
+
+/// A chip in the strip; clicking it opens the diplomacy panel on that civilization
+#[derive(Component)]
+pub struct RelationChip {
+    pub civ_id: u32,
+}
+
+// What a chip displays; the strip is only rebuilt when these change
+#[derive(Clone, PartialEq)]
+pub struct ChipEntry {
+    civ_id: u32,
+    color: Color,
+    label: String,
+    tooltip: String,
+}
+
+pub fn setup_relations_strip(mut commands: Commands) {
+    // Met civilizations (top left, below the controls text)
+    commands.spawn((
+        RelationsStrip,
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(54.0),
+            left: Val::Px(10.0),
+            flex_direction: FlexDirection::Row,
+            flex_wrap: FlexWrap::Wrap,
+            column_gap: Val::Px(4.0),
+            row_gap: Val::Px(2.0),
+            display: Display::None,
+            ..default()
+        },
+    ));
+}
+
+// Who pays whom under the deals between the two, as the tooltip lists them
+fn deal_lines(civ_manager: &CivilizationManager, player_id: u32, other: &Civilization) -> Vec<String> {
+    civ_manager
+        .diplomacy
+        .payments
+        .iter()
+        .filter_map(|payment| {
+            let payer = if payment.payer == player_id && payment.payee == other.id {
+                "You pay"
+            } else if payment.payer == other.id && payment.payee == player_id {
+                "They pay"
+            } else {
+                return None;
+            };
+            Some(format!("{} {:.0} gold/turn ({} turns left)", payer, payment.amount, payment.turns_left))
+        })
+        .collect()
+}
+
+fn chip_entry(civ_manager: &CivilizationManager, summaries: &[CivSummary], player: &Civilization, other: &Civilization, exact: bool) -> ChipEntry {
+    let summary_of = |civ_id: u32| summaries.iter().find(|summary| summary.civ_id == civ_id);
+    let (score, military) = summary_of(other.id).map_or((0.0, 0.0), |summary| (summary.score, summary.demographics.get(Demographic::Military)));
+    let (your_score, your_military) = summary_of(player.id).map_or((0.0, 0.0), |summary| (summary.score, summary.demographics.get(Demographic::Military)));
+    let cities = summary_of(other.id).map_or(0.0, |summary| summary.demographics.get(Demographic::Cities));
+    
+    let relation = civ_manager.diplomacy.relation(player.id, other.id);
+    let relation_glyph = if matches!(relation, Relation::War(_)) { "⚔" } else { "☮" };
+    // Whether they're ahead of or behind the player on score
+    let trend = match score.total_cmp(&your_score) {
+        std::cmp::Ordering::Greater => "▲",
+        std::cmp::Ordering::Less => "▼",
+        std::cmp::Ordering::Equal => "=",
+    };
+    
+    let deals = deal_lines(civ_manager, player.id, other);
+    let deals = if deals.is_empty() { "None".to_string() } else { deals.join("\n") };
+    // Their army is only sized up against the player's, unless the game shows everything
+    let military = if exact {
+        format!("Military {:.0} (yours {:.0})", military, your_military)
+    } else {
+        format!("Army vs yours: {}", MilitaryComparison::between(military, your_military).get_name())
+    };
+    let tooltip = format!(
+        "{} ({})\n{}\nCities {:.0}  Score {:.0} (yours {:.0})\n{}\nDeals: {}\nClick to open diplomacy",
+        other.name,
+        other.leader_name,
+        relation_label(relation, civ_manager.turn_number),
+        cities,
+        score,
+        your_score,
+        military,
+        deals
+    );
+    ChipEntry {
+        civ_id: other.id,
+        color: other.color,
+        label: format!("{}{}", relation_glyph, trend),
+        tooltip,
+    }
+}
+
+// Rebuilds the strip whenever a met civilization's relation, score or deals change, and
+// hides it until the player has met someone. The numbers are the demographics screen's.
+pub fn update_relations_strip(
+    mut commands: Commands,
+    mut strip_query: Query<(Entity, &mut Node), With<RelationsStrip>>,
+    civ_manager: Res<CivilizationManager>,
+    settings: Res<GameSettings>,
+    city_query: Query<&City>,
+    unit_query: Query<&Unit>,
+    mut shown_entries: Local<Option<Vec<ChipEntry>>>,
+) {
+    let Ok((strip_entity, mut strip_node)) = strip_query.single_mut() else { return };
+    // A new game's strip starts out empty
+    if strip_node.is_added() {
+        *shown_entries = None;
+    }
+    
+    let Some(player_civ) = civ_manager.get_viewed_civilization() else { return };
+    let mut met: Vec<&Civilization> = civ_manager
+        .civilizations
+        .values()
+        .filter(|civ| civ.id != player_civ.id && !civ.eliminated && player_civ.has_met(civ.id))
+        .collect();
+    met.sort_by_key(|civ| civ.id);
+    
+    let display = if met.is_empty() { Display::None } else { Display::Flex };
+    if strip_node.display != display {
+        strip_node.display = display;
+    }
+    if met.is_empty() {
+        return;
+    }
+    
+    let summaries = CivSummary::measure_all(&civ_manager, &city_query, &unit_query);
+    let exact = shows_everything(&settings, &civ_manager);
+    let entries: Vec<ChipEntry> = met
+        .into_iter()
+        .map(|civ| chip_entry(&civ_manager, &summaries, player_civ, civ, exact))
+        .collect();
+    if shown_entries.as_ref().is_some_and(|shown| *shown == entries) {
+        return;
+    }
+    
+    commands.entity(strip_entity).despawn_related::<Children>();
+    commands.entity(strip_entity).with_children(|strip| {
+        for entry in &entries {
+            strip
+                .spawn((
+                    Button,
+                    RelationChip { civ_id: entry.civ_id },
+                    TooltipSource::Text(entry.tooltip.clone()),
+                    BackgroundColor(CHIP_COLOR),
+                    Node {
+                        align_items: AlignItems::Center,
+                        column_gap: Val::Px(3.0),
+                        padding: UiRect::axes(Val::Px(4.0), Val::Px(1.0)),
+                        ..default()
+                    },
+                ))
+                .with_children(|chip| {
+                    chip.spawn((
+                        BackgroundColor(entry.color),
+                        Node {
+                            width: Val::Px(SWATCH_SIZE),
+                            height: Val::Px(SWATCH_SIZE),
+                            ..default()
+                        },
+                    ));
+                    chip.spawn((
+                        Text::new(entry.label.clone()),
+                        TextFont {
+                            font_size: 12.0,
+                            ..default()
+                        },
+                        TextColor(Color::WHITE),
+                    ));
+                });
+        }
+    });
+    
+    *shown_entries = Some(entries);
+}
+
+pub fn relation_chip_system(
+    mut chip_query: Query<(&Interaction, &RelationChip, &mut BackgroundColor), Changed<Interaction>>,
+    civ_manager: Res<CivilizationManager>,
+    mut ui_state: ResMut<UIState>,
+    mut composer: ResMut<DealComposer>,
+) {
+    for (interaction, chip, mut background) in chip_query.iter_mut() {
+        match interaction {
+            Interaction::Pressed => {
+                let Some(player_id) = civ_manager.get_player_civilization().map(|civ| civ.id) else { continue };
+                ui_state.show_diplomacy = true;
+                *composer = DealComposer::open(chip.civ_id, player_id, &civ_manager);
+            }
+            Interaction::Hovered => background.0 = CHIP_HOVER_COLOR,
+            Interaction::None => background.0 = CHIP_COLOR,
+        }
+    }
+}
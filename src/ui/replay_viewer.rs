@@ -0,0 +1,336 @@
+use bevy::prelude::*;
+use bevy::tasks::{AsyncComputeTaskPool, Task, futures::check_ready};
+use std::collections::VecDeque;
+use crate::game::cities::CityMarker;
+use crate::game::civilization::CivilizationManager;
+use crate::game::culling::Cullable;
+use crate::game::game_initialization::AppState;
+use crate::game::hex::HexCoord;
+use crate::game::map::HEX_SIZE;
+use crate::game::replay::{ReplayAction, ReplayLog, ReplayState, ReplayWorld};
+use crate::game::units::UnitMarker;
+use super::city_banners::CityBanner;
+use super::game_over::GameOverScreen;
+use super::menus::text;
+
+// Actions played per second at each speed step; Up and Down move between them
+const PLAYBACK_SPEEDS: [f32; 6] = [2.0, 5.0, 10.0, 25.0, 50.0, 100.0];
+const DEFAULT_SPEED: usize = 2;
+// How long a played action stays drawn on the map, and how many are drawn at most
+const TRAIL_SECONDS: f32 = 1.5;
+const MAX_TRAIL: usize = 64;
+const CAMERA_FOLLOW_RATE: f32 = 4.0;
+
+const DESYNC_COLOR: Color = Color::srgb(1.0, 0.5, 0.4);
+
+// The recorded world being generated again, before anything plays
+struct WorldRebuild {
+    world: ReplayWorld,
+    task: Task<u64>,
+}
+
+/// The replay being watched: the recorded actions, how far it has got and the state
+/// rebuilt from the recorded world and what has played so far
+#[derive(Resource)]
+pub struct ReplayPlayback {
+    actions: Vec<ReplayAction>,
+    rebuild: Option<WorldRebuild>,
+    next: usize,
+    state: ReplayState,
+    turn: u32,
+    speed: usize,
+    paused: bool,
+    due: f32,          // Actions owed to the clock; whole ones play as they come due
+    skip_turn: bool,   // Play straight through to the end of the turn
+    trail: VecDeque<(ReplayAction, f32)>, // Recently played actions and when they played
+    focus: Option<HexCoord>,
+    caption: String,
+    desyncs: Vec<String>,
+}
+
+impl ReplayPlayback {
+    fn new(actions: Vec<ReplayAction>) -> Self {
+        let world = actions.iter().find_map(|action| match action {
+            ReplayAction::Start { world, .. } => world.clone(),
+            _ => None,
+        });
+        let rebuild = world.map(|world| {
+            let generating = world.clone();
+            WorldRebuild { world, task: AsyncComputeTaskPool::get().spawn(async move { generating.regenerate() }) }
+        });
+        Self {
+            actions,
+            rebuild,
+            next: 0,
+            state: ReplayState::default(),
+            turn: 1,
+            speed: DEFAULT_SPEED,
+            paused: false,
+            due: 0.0,
+            skip_turn: false,
+            trail: VecDeque::new(),
+            focus: None,
+            caption: String::new(),
+            desyncs: Vec::new(),
+        }
+    }
+    
+    fn finished(&self) -> bool {
+        self.next >= self.actions.len()
+    }
+    
+    // Whether the recorded world is still being generated again. Once it is, its terrain
+    // goes into every turn's checksum, and a map that came out different is a desync.
+    fn rebuilding(&mut self) -> bool {
+        let Some(rebuild) = self.rebuild.as_mut() else { return false };
+        let Some(terrain) = check_ready(&mut rebuild.task) else { return true };
+        let seed = rebuild.world.origin.seed;
+        if terrain != rebuild.world.terrain {
+            let desync = format!("The world generated from seed {} differs from the recorded map", seed);
+            println!("Replay desync - {}", desync);
+            self.desyncs.push(desync);
+        }
+        self.state.terrain = Some(terrain);
+        self.rebuild = None;
+        self.caption = format!("Generated the world again from seed {}", seed);
+        false
+    }
+    
+    // Plays the next action; returns whether it closed a turn
+    fn play_next(&mut self, now: f32, civ_manager: &CivilizationManager) -> bool {
+        let action = self.actions[self.next].clone();
+        self.next += 1;
+        self.state.apply(&action);
+        
+        let mut turn_ended = false;
+        match &action {
+            ReplayAction::Start { turn, .. } | ReplayAction::TurnStarted { turn, .. } => self.turn = *turn,
+            ReplayAction::TurnEnded { turn, checksum } => {
+                if self.state.checksum() != *checksum {
+                    let desync = format!("Turn {}: the replayed map, cities and technologies differ from the recorded game", turn);
+                    println!("Replay desync - {}", desync);
+                    self.desyncs.push(desync);
+                }
+                turn_ended = true;
+            }
+            _ => {}
+        }
+        
+        self.caption = describe(&action, civ_manager);
+        if let Some(location) = action.location() {
+            self.focus = Some(location);
+        }
+        self.trail.push_back((action, now));
+        if self.trail.len() > MAX_TRAIL {
+            self.trail.pop_front();
+        }
+        turn_ended
+    }
+}
+
+// One line about an action, for the caption under the map
+pub(super) fn describe(action: &ReplayAction, civ_manager: &CivilizationManager) -> String {
+    let name = |civ: &u32| civ_manager.civ_name(*civ).to_string();
+    match action {
+        ReplayAction::Start { turn, .. } => format!("The recording begins on turn {}", turn),
+        ReplayAction::TurnStarted { civ, .. } => format!("The {} take their turn", name(civ)),
+        ReplayAction::UnitMoved { civ, unit, to, .. } => format!("{} {:?} moves to ({}, {})", name(civ), unit, to.q, to.r),
+        ReplayAction::Combat(entry) => format!("{}{}{}{}", entry.attacker, entry.verb(), entry.defender, entry.details()),
+        ReplayAction::CityFounded { civ, name: city, .. } => format!("The {} found {}", name(civ), city),
+        ReplayAction::CityCaptured { civ, previous_civ, name: city, .. } => {
+            format!("The {} take {} from the {}", name(civ), city, name(previous_civ))
+        }
+        ReplayAction::CityDestroyed { civ, name: city, .. } => format!("{} of the {} is no more", city, name(civ)),
+        ReplayAction::TechResearched { civ, tech } => format!("The {} learn {}", name(civ), tech.get_name()),
+        ReplayAction::WarDeclared { civ, other } => format!("The {} declare war on the {}", name(civ), name(other)),
+        ReplayAction::PeaceMade { civ, other } => format!("The {} and the {} make peace", name(civ), name(other)),
+        ReplayAction::WonderBuilt { civ, wonder, city, .. } => format!("The {} complete {} in {}", name(civ), wonder.get_name(), city),
+        ReplayAction::TurnEnded { turn, .. } => format!("Turn {} is over", turn),
+    }
+}
+
+#[derive(Component)]
+pub struct ReplayPanelText;
+
+// What the finished game shows on the map; hidden while the replay draws its own
+type GameMarkers = Or<(With<CityMarker>, With<UnitMarker>)>;
+type GameMarkersAndBanners = Or<(With<CityMarker>, With<UnitMarker>, With<CityBanner>)>;
+
+// Starts the replay from the results screen: the screen and the finished game's cities
+// and units make way for what the replay draws
+pub fn start_replay(
+    mut commands: Commands,
+    log: Res<ReplayLog>,
+    mut screen_query: Query<&mut Node, With<GameOverScreen>>,
+    mut marker_query: Query<(Entity, &mut Visibility), GameMarkersAndBanners>,
+) {
+    if let Ok(mut screen_node) = screen_query.single_mut() {
+        screen_node.display = Display::None;
+    }
+    // Out of culling's hands too, or panning would bring them back
+    for (entity, mut visibility) in marker_query.iter_mut() {
+        *visibility = Visibility::Hidden;
+        commands.entity(entity).try_remove::<Cullable>();
+    }
+    commands.insert_resource(ReplayPlayback::new(log.actions.clone()));
+    
+    commands
+        .spawn((
+            StateScoped(AppState::Replay),
+            BackgroundColor(Color::srgba(0.05, 0.05, 0.1, 0.9)),
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(10.0),
+                left: Val::Px(0.0),
+                right: Val::Px(0.0),
+                margin: UiRect::horizontal(Val::Auto),
+                width: Val::Px(640.0),
+                max_width: Val::Percent(96.0),
+                padding: UiRect::all(Val::Px(8.0)),
+                ..default()
+            },
+        ))
+        .with_child((ReplayPanelText, text("", 13.0, Color::WHITE)));
+}
+
+// Brings the finished game's cities and units back; banners come back on their own
+pub fn end_replay(
+    mut commands: Commands,
+    mut marker_query: Query<(Entity, &mut Visibility), GameMarkers>,
+) {
+    for (entity, mut visibility) in marker_query.iter_mut() {
+        *visibility = Visibility::Inherited;
+        commands.entity(entity).insert(Cullable);
+    }
+    commands.remove_resource::<ReplayPlayback>();
+}
+
+// Plays the replay against the clock and keeps the camera on the action. Space pauses,
+// Up and Down change the speed, N plays to the end of the turn and Escape goes back to
+// the results.
+pub fn replay_playback_system(
+    mut playback: ResMut<ReplayPlayback>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+    civ_manager: Res<CivilizationManager>,
+    mut camera_query: Query<&mut Transform, With<Camera2d>>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if keyboard.just_pressed(KeyCode::Escape) {
+        next_state.set(AppState::GameOver);
+        return;
+    }
+    if keyboard.just_pressed(KeyCode::Space) {
+        playback.paused = !playback.paused;
+    }
+    if keyboard.just_pressed(KeyCode::ArrowUp) {
+        playback.speed = (playback.speed + 1).min(PLAYBACK_SPEEDS.len() - 1);
+    }
+    if keyboard.just_pressed(KeyCode::ArrowDown) {
+        playback.speed = playback.speed.saturating_sub(1);
+    }
+    if keyboard.just_pressed(KeyCode::KeyN) {
+        playback.skip_turn = true;
+    }
+    
+    if playback.rebuilding() {
+        return;
+    }
+    
+    let now = time.elapsed_secs();
+    playback.trail.retain(|(_, played_at)| now - played_at < TRAIL_SECONDS);
+    if !playback.paused {
+        playback.due += time.delta_secs() * PLAYBACK_SPEEDS[playback.speed];
+    }
+    while !playback.finished() && (playback.skip_turn || playback.due >= 1.0) {
+        if !playback.skip_turn {
+            playback.due -= 1.0;
+        }
+        if playback.play_next(now, &civ_manager) {
+            playback.skip_turn = false;
+        }
+    }
+    if playback.finished() {
+        playback.due = 0.0;
+        playback.skip_turn = false;
+    }
+    
+    if let Some(focus) = playback.focus
+        && let Ok(mut camera_transform) = camera_query.single_mut()
+    {
+        let target = focus.to_world_pos(HEX_SIZE);
+        let step = 1.0 - (-CAMERA_FOLLOW_RATE * time.delta_secs()).exp();
+        let current = camera_transform.translation.truncate();
+        let position = current.lerp(target, step);
+        camera_transform.translation.x = position.x;
+        camera_transform.translation.y = position.y;
+    }
+}
+
+// Draws the rebuilt cities in their owners' colors and the last few actions over the map
+pub fn draw_replay(mut gizmos: Gizmos, playback: Res<ReplayPlayback>, civ_manager: Res<CivilizationManager>, time: Res<Time>) {
+    let color_of = |civ: u32| civ_manager.get_civilization(civ).map_or(Color::WHITE, |civ| civ.color);
+    
+    for (at, (civ, _)) in &playback.state.cities {
+        let position = at.to_world_pos(HEX_SIZE);
+        gizmos.circle_2d(position, HEX_SIZE * 0.45, color_of(*civ));
+        gizmos.circle_2d(position, HEX_SIZE * 0.3, color_of(*civ));
+    }
+    
+    let now = time.elapsed_secs();
+    for (action, played_at) in &playback.trail {
+        let alpha = 1.0 - (now - played_at) / TRAIL_SECONDS;
+        match action {
+            ReplayAction::UnitMoved { civ, from, to, .. } => {
+                gizmos.arrow_2d(from.to_world_pos(HEX_SIZE), to.to_world_pos(HEX_SIZE), color_of(*civ).with_alpha(alpha));
+            }
+            ReplayAction::Combat(entry) => {
+                let center = entry.location.to_world_pos(HEX_SIZE);
+                let size = HEX_SIZE * 0.5;
+                let color = Color::srgb(1.0, 0.3, 0.2).with_alpha(alpha);
+                gizmos.line_2d(center - Vec2::splat(size), center + Vec2::splat(size), color);
+                gizmos.line_2d(center + Vec2::new(-size, size), center + Vec2::new(size, -size), color);
+            }
+            ReplayAction::CityFounded { civ, at, .. }
+            | ReplayAction::CityCaptured { civ, at, .. }
+            | ReplayAction::CityDestroyed { civ, at, .. } => {
+                gizmos.circle_2d(at.to_world_pos(HEX_SIZE), HEX_SIZE * 0.9, color_of(*civ).with_alpha(alpha));
+            }
+            _ => {}
+        }
+    }
+}
+
+pub fn update_replay_panel(
+    playback: Res<ReplayPlayback>,
+    mut text_query: Query<(&mut Text, &mut TextColor), With<ReplayPanelText>>,
+) {
+    let Ok((mut text, mut color)) = text_query.single_mut() else { return };
+    if !playback.is_changed() {
+        return;
+    }
+    
+    let progress = if let Some(rebuild) = &playback.rebuild {
+        format!("generating the world from seed {}", rebuild.world.origin.seed)
+    } else if playback.finished() {
+        "finished".to_string()
+    } else if playback.paused {
+        "paused".to_string()
+    } else {
+        format!("{:.0} actions/s", PLAYBACK_SPEEDS[playback.speed])
+    };
+    let checks = match playback.desyncs.last() {
+        None => "Every turn so far matches the recorded game".to_string(),
+        Some(last) => format!("{} desync(s); latest: {}", playback.desyncs.len(), last),
+    };
+    **text = format!(
+        "=== REPLAY - Turn {} ({}) - {}/{} ===\n{}\n{}\nSpace: pause   Up/Down: speed   N: next turn   Esc: back",
+        playback.turn,
+        progress,
+        playback.next,
+        playback.actions.len(),
+        playback.caption,
+        checks,
+    );
+    color.0 = if playback.desyncs.is_empty() { Color::WHITE } else { DESYNC_COLOR };
+}
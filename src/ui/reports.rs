@@ -0,0 +1,107 @@
+use bevy::prelude::*;
+use crate::game::game_initialization::{GameSettings, TurnStartedEvent};
+use crate::game::reports::{DebugSources, TurnSummarySources};
+use super::game_panels::UIState;
+
+#[derive(Component)]
+pub struct TurnSummaryOverlay;
+
+#[derive(Component)]
+pub struct DebugOverlay;
+
+fn overlay_node(left: Val, right: Val) -> Node {
+    Node {
+        position_type: PositionType::Absolute,
+        top: Val::Px(60.0),
+        left,
+        right,
+        padding: UiRect::all(Val::Px(8.0)),
+        display: Display::None,
+        ..default()
+    }
+}
+
+pub fn setup_report_overlays(mut commands: Commands) {
+    // Turn summary (top left, toggled with F4)
+    commands.spawn((
+        TurnSummaryOverlay,
+        Text::new(""),
+        TextFont {
+            font_size: 12.0,
+            ..default()
+        },
+        TextColor(Color::srgb(0.9, 0.9, 0.7)),
+        BackgroundColor(Color::srgba(0.05, 0.05, 0.1, 0.92)),
+        overlay_node(Val::Px(10.0), Val::Auto),
+        GlobalZIndex(5),
+    ));
+    
+    // Debug overlay (top right, toggled with F3)
+    commands.spawn((
+        DebugOverlay,
+        Text::new(""),
+        TextFont {
+            font_size: 12.0,
+            ..default()
+        },
+        TextColor(Color::srgb(0.7, 1.0, 0.7)),
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.85)),
+        overlay_node(Val::Auto, Val::Px(10.0)),
+        GlobalZIndex(5),
+    ));
+}
+
+// Shows or hides an overlay, returning whether its text wants rebuilding: just opened,
+// or a new turn began while it was up
+fn needs_refresh(node: &mut Node, open: bool, was_open: &mut bool, new_turn: bool) -> bool {
+    let display = if open { Display::Flex } else { Display::None };
+    if node.display != display {
+        node.display = display;
+    }
+    let just_opened = open && !*was_open;
+    *was_open = open;
+    just_opened || (open && new_turn)
+}
+
+// Fills in the F4 summary when it's opened and again as each turn begins, rather than
+// every frame
+pub fn update_turn_summary_overlay(
+    ui_state: Res<UIState>,
+    settings: Res<GameSettings>,
+    mut turn_events: EventReader<TurnStartedEvent>,
+    sources: TurnSummarySources,
+    mut overlay_query: Query<(&mut Text, &mut Node), With<TurnSummaryOverlay>>,
+    mut was_open: Local<bool>,
+) {
+    let new_turn = turn_events.read().count() > 0;
+    let Ok((mut text, mut node)) = overlay_query.single_mut() else { return };
+    if !needs_refresh(&mut node, ui_state.show_turn_summary, &mut was_open, new_turn) {
+        return;
+    }
+    let report = sources.lines().join("\n");
+    if settings.log_reports {
+        println!("{}", report);
+    }
+    **text = format!("{}\n\nF4 or Esc closes", report);
+}
+
+// Fills in the F3 overlay the same way: when it's opened and as each turn begins
+pub fn update_debug_overlay(
+    ui_state: Res<UIState>,
+    settings: Res<GameSettings>,
+    mut turn_events: EventReader<TurnStartedEvent>,
+    sources: DebugSources,
+    mut overlay_query: Query<(&mut Text, &mut Node), With<DebugOverlay>>,
+    mut was_open: Local<bool>,
+) {
+    let new_turn = turn_events.read().count() > 0;
+    let Ok((mut text, mut node)) = overlay_query.single_mut() else { return };
+    if !needs_refresh(&mut node, ui_state.show_debug_overlay, &mut was_open, new_turn) {
+        return;
+    }
+    let report = sources.lines().join("\n");
+    if settings.log_reports {
+        println!("{}", report);
+    }
+    **text = format!("{}\n\nF3 or Esc closes", report);
+}
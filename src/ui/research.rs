@@ -0,0 +1,339 @@
+use bevy::prelude::*;
+use crate::game::cities::{City, ProductionItem};
+use crate::game::civilization::{Civilization, CivilizationManager, GameSpeed, Technology, TechResearchedEvent};
+use crate::game::game_initialization::{AppState, TurnStartedEvent};
+use super::game_panels::UIState;
+
+// Tree layout: one column per prerequisite depth, one row per tech in that column
+const NODE_WIDTH: f32 = 150.0;
+const NODE_HEIGHT: f32 = 58.0;
+const COLUMN_GAP: f32 = 28.0;
+const ROW_GAP: f32 = 10.0;
+const LINE_WIDTH: f32 = 2.0;
+
+const LINE_COLOR: Color = Color::srgb(0.45, 0.45, 0.5);
+const HOVER_COLOR: Color = Color::srgba(0.35, 0.35, 0.55, 0.95);
+
+#[derive(Component)]
+pub struct TechTreePanel;
+
+// One technology in the tree; clicking it researches it, queueing any missing prerequisites
+#[derive(Component)]
+pub struct TechNodeButton {
+    pub tech: Technology,
+    color: Color,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum TechState {
+    Researched,
+    Current,
+    Queued,
+    Researchable,
+    Locked,
+}
+
+impl TechState {
+    fn color(self) -> Color {
+        match self {
+            TechState::Researched => Color::srgba(0.15, 0.4, 0.15, 0.95),
+            TechState::Current => Color::srgba(0.55, 0.45, 0.1, 0.95),
+            TechState::Queued => Color::srgba(0.4, 0.33, 0.12, 0.95),
+            TechState::Researchable => Color::srgba(0.15, 0.25, 0.45, 0.95),
+            TechState::Locked => Color::srgba(0.18, 0.18, 0.2, 0.95),
+        }
+    }
+}
+
+// What a tree node displays; the tree is only rebuilt when these change
+#[derive(Clone, PartialEq)]
+pub struct TechNodeEntry {
+    tech: Technology,
+    state: TechState,
+    label: String,
+}
+
+pub fn setup_tech_tree(mut commands: Commands) {
+    // Tech tree (center, toggled with T and opened when research is needed)
+    commands.spawn((
+        TechTreePanel,
+        Interaction::default(),
+        BackgroundColor(Color::srgba(0.05, 0.05, 0.1, 0.95)),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(60.0),
+            left: Val::Px(20.0),
+            max_width: Val::Percent(95.0),
+            max_height: Val::Percent(85.0),
+            flex_direction: FlexDirection::Column,
+            overflow: Overflow::scroll(),
+            padding: UiRect::all(Val::Px(8.0)),
+            row_gap: Val::Px(6.0),
+            display: Display::None,
+            ..default()
+        },
+    ));
+}
+
+// Total science the player's cities make each turn
+pub fn player_science_per_turn(civ_id: u32, city_query: &Query<&City>) -> f32 {
+    city_query
+        .iter()
+        .filter(|city| city.civilization_id == civ_id)
+        .map(|city| city.science_per_turn)
+        .sum()
+}
+
+// Turns until `civ` finishes a technology given the science already in its pool and put into it
+pub fn research_turns(civ: &Civilization, tech: Technology, progress: f32, science_per_turn: f32, speed: GameSpeed) -> Option<u32> {
+    let remaining = (civ.research_cost(tech, speed) - progress - civ.science_points).max(0.0);
+    if remaining <= 0.0 {
+        Some(1)
+    } else if science_per_turn > 0.0 {
+        Some((remaining / science_per_turn).ceil() as u32)
+    } else {
+        None
+    }
+}
+
+// Opens the tree when the player has nothing to research: once a game has loaded, at
+// the start of each of their turns, and when a technology completes
+pub fn research_prompt_system(
+    mut ui_state: ResMut<UIState>,
+    civ_manager: Res<CivilizationManager>,
+    mut turn_events: EventReader<TurnStartedEvent>,
+    mut researched_events: EventReader<TechResearchedEvent>,
+    mut transitions: EventReader<StateTransitionEvent<AppState>>,
+) {
+    let game_started = transitions.read().any(|transition| transition.exited == Some(AppState::Loading));
+    let Some(player_civ) = civ_manager.get_player_civilization() else { return };
+    let turn_started = turn_events.read().any(|event| event.civilization_id == player_civ.id);
+    let researched = researched_events.read().any(|event| event.civilization_id == player_civ.id);
+    
+    if (turn_started || researched || game_started)
+        && player_civ.current_research.is_none()
+        && !player_civ.available_research().is_empty()
+    {
+        ui_state.show_tech_tree = true;
+    }
+}
+
+// Buildings, units and wonders a technology makes available
+fn unlocks(tech: Technology) -> Vec<String> {
+    ProductionItem::all()
+        .into_iter()
+        .filter(|item| item.required_tech() == Some(tech))
+        .map(|item| item.get_name())
+        .collect()
+}
+
+fn tech_entries(civ: &Civilization, science_per_turn: f32, speed: GameSpeed) -> Vec<TechNodeEntry> {
+    Technology::ALL
+        .into_iter()
+        .map(|tech| {
+            let state = if civ.has_technology(tech) {
+                TechState::Researched
+            } else if civ.current_research.is_some_and(|(current, _)| current == tech) {
+                TechState::Current
+            } else if civ.research_queue.contains(&tech) {
+                TechState::Queued
+            } else if civ.can_research(tech) {
+                TechState::Researchable
+            } else {
+                TechState::Locked
+            };
+            
+            let progress = match civ.current_research {
+                Some((current, progress)) if current == tech => progress,
+                _ => 0.0,
+            };
+            let status = match state {
+                TechState::Researched => "Researched".to_string(),
+                _ => match research_turns(civ, tech, progress, science_per_turn, speed) {
+                    Some(turns) => format!("{:.0} science - {} turns", civ.research_cost(tech, speed), turns),
+                    None => format!("{:.0} science", civ.research_cost(tech, speed)),
+                },
+            };
+            let unlocked = unlocks(tech);
+            let unlocks_line = if unlocked.is_empty() { String::new() } else { format!("\n{}", unlocked.join(", ")) };
+            
+            TechNodeEntry {
+                tech,
+                state,
+                label: format!("{}\n{}{}", tech.get_name(), status, unlocks_line),
+            }
+        })
+        .collect()
+}
+
+// Top-left corner of each technology's node in the tree
+fn node_position(tech: Technology) -> Vec2 {
+    let column = tech.depth();
+    let row = Technology::ALL
+        .iter()
+        .filter(|other| other.depth() == column)
+        .position(|&other| other == tech)
+        .unwrap_or(0);
+    Vec2::new(
+        column as f32 * (NODE_WIDTH + COLUMN_GAP),
+        row as f32 * (NODE_HEIGHT + ROW_GAP),
+    )
+}
+
+fn line_segment(left: f32, top: f32, width: f32, height: f32) -> impl Bundle {
+    (
+        BackgroundColor(LINE_COLOR),
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(left),
+            top: Val::Px(top),
+            width: Val::Px(width),
+            height: Val::Px(height),
+            ..default()
+        },
+    )
+}
+
+// Rebuilds the tree when it's opened or a node's state or estimate changes
+pub fn update_tech_tree(
+    mut commands: Commands,
+    ui_state: Res<UIState>,
+    mut panel_query: Query<(Entity, &mut Node), With<TechTreePanel>>,
+    city_query: Query<&City>,
+    civ_manager: Res<CivilizationManager>,
+    mut shown_entries: Local<Option<Vec<TechNodeEntry>>>,
+) {
+    let Ok((panel_entity, mut panel_node)) = panel_query.single_mut() else { return };
+    // A new game's panel starts out empty
+    if panel_node.is_added() {
+        *shown_entries = None;
+    }
+    
+    let display = if ui_state.show_tech_tree { Display::Flex } else { Display::None };
+    if panel_node.display != display {
+        panel_node.display = display;
+    }
+    if !ui_state.show_tech_tree {
+        *shown_entries = None;
+        return;
+    }
+    
+    let Some(player_civ) = civ_manager.get_viewed_civilization() else { return };
+    let science = player_science_per_turn(player_civ.id, &city_query);
+    let entries = tech_entries(player_civ, science, civ_manager.speed);
+    if shown_entries.as_ref() == Some(&entries) {
+        return;
+    }
+    
+    let columns = Technology::ALL.iter().map(|tech| tech.depth() + 1).max().unwrap_or(1);
+    let rows = (0..columns)
+        .map(|column| Technology::ALL.iter().filter(|tech| tech.depth() == column).count())
+        .max()
+        .unwrap_or(1);
+    let canvas_size = Vec2::new(
+        columns as f32 * (NODE_WIDTH + COLUMN_GAP) - COLUMN_GAP,
+        rows as f32 * (NODE_HEIGHT + ROW_GAP) - ROW_GAP,
+    );
+    
+    commands.entity(panel_entity).despawn_related::<Children>();
+    commands.entity(panel_entity).with_children(|panel| {
+        let current = match player_civ.current_research {
+            Some((tech, _)) => tech.get_name(),
+            None => "nothing",
+        };
+        panel.spawn((
+            Text::new(format!(
+                "=== TECHNOLOGY: {} Era, researching {}, {:.1} science/turn (T to close) ===\n\
+                Click a technology to research it; locked ones queue their prerequisites",
+                player_civ.current_era().get_name(), current, science
+            )),
+            TextFont {
+                font_size: 12.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.9, 0.9, 0.7)),
+        ));
+        
+        panel
+            .spawn(Node {
+                width: Val::Px(canvas_size.x),
+                height: Val::Px(canvas_size.y),
+                flex_shrink: 0.0,
+                ..default()
+            })
+            .with_children(|canvas| {
+                // Prerequisite lines go first so the nodes draw over them: out of the
+                // prerequisite's right edge, down the gap before the column, into the node
+                for tech in Technology::ALL {
+                    let to = node_position(tech) + Vec2::new(0.0, NODE_HEIGHT / 2.0);
+                    let elbow_x = to.x - COLUMN_GAP / 2.0;
+                    for &prereq in tech.prerequisites() {
+                        let from = node_position(prereq) + Vec2::new(NODE_WIDTH, NODE_HEIGHT / 2.0);
+                        canvas.spawn(line_segment(from.x, from.y - LINE_WIDTH / 2.0, elbow_x - from.x, LINE_WIDTH));
+                        canvas.spawn(line_segment(
+                            elbow_x - LINE_WIDTH / 2.0,
+                            from.y.min(to.y) - LINE_WIDTH / 2.0,
+                            LINE_WIDTH,
+                            (to.y - from.y).abs() + LINE_WIDTH,
+                        ));
+                        canvas.spawn(line_segment(elbow_x, to.y - LINE_WIDTH / 2.0, to.x - elbow_x, LINE_WIDTH));
+                    }
+                }
+                
+                for entry in &entries {
+                    let position = node_position(entry.tech);
+                    let color = entry.state.color();
+                    let text_color = if entry.state == TechState::Locked {
+                        Color::srgb(0.55, 0.55, 0.55)
+                    } else {
+                        Color::srgb(0.9, 0.9, 0.9)
+                    };
+                    canvas
+                        .spawn((
+                            Button,
+                            TechNodeButton { tech: entry.tech, color },
+                            BackgroundColor(color),
+                            Node {
+                                position_type: PositionType::Absolute,
+                                left: Val::Px(position.x),
+                                top: Val::Px(position.y),
+                                width: Val::Px(NODE_WIDTH),
+                                height: Val::Px(NODE_HEIGHT),
+                                padding: UiRect::all(Val::Px(3.0)),
+                                ..default()
+                            },
+                        ))
+                        .with_child((
+                            Text::new(entry.label.clone()),
+                            TextFont {
+                                font_size: 10.0,
+                                ..default()
+                            },
+                            TextColor(text_color),
+                        ));
+                }
+            });
+    });
+    
+    *shown_entries = Some(entries);
+}
+
+// Clicking a technology researches it, queueing its missing prerequisites first
+pub fn tech_tree_interaction_system(
+    mut node_query: Query<(&Interaction, &TechNodeButton, &mut BackgroundColor), Changed<Interaction>>,
+    mut civ_manager: ResMut<CivilizationManager>,
+) {
+    for (interaction, node, mut background) in node_query.iter_mut() {
+        match interaction {
+            Interaction::Pressed => {
+                if let Some(civ) = civ_manager.get_player_civilization_mut()
+                    && civ.queue_research(node.tech)
+                {
+                    println!("Now researching toward {}", node.tech.get_name());
+                }
+            }
+            Interaction::Hovered => background.0 = HOVER_COLOR,
+            Interaction::None => background.0 = node.color,
+        }
+    }
+}
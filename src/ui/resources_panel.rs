@@ -0,0 +1,219 @@
+use bevy::prelude::*;
+use std::collections::HashSet;
+use crate::game::cities::{City, LUXURY_HAPPINESS};
+use crate::game::civilization::CivilizationManager;
+use crate::game::input_map::{Action, InputMap};
+use crate::game::map::TileIndex;
+use crate::game::resources::ResourceType;
+use crate::game::units::Unit;
+use super::event_focus::EventCamera;
+use super::game_panels::UIState;
+
+const ROW_COLOR: Color = Color::srgba(0.15, 0.15, 0.2, 0.8);
+const ROW_HOVER_COLOR: Color = Color::srgba(0.3, 0.3, 0.45, 0.9);
+const HEADER_COLOR: Color = Color::srgb(0.9, 0.9, 0.7);
+const TEXT_COLOR: Color = Color::srgb(0.8, 0.8, 0.8);
+const WARNING_COLOR: Color = Color::srgb(1.0, 0.6, 0.4);
+// Columns: resource, sites found, sites in the player's territory, sites worked, notes
+const COLUMN_WIDTHS: [f32; 5] = [90.0, 50.0, 50.0, 55.0, 230.0];
+
+#[derive(Component)]
+pub struct ResourcesPanel;
+
+// One resource's row; clicking it takes the camera to its sites one after another
+#[derive(Component)]
+pub struct ResourceRow(pub ResourceType);
+
+/// The resource whose sites the camera last stepped through, and the site it showed
+#[derive(Resource, Default)]
+pub struct ResourceTour {
+    resource: Option<ResourceType>,
+    site: usize,
+}
+
+pub fn setup_resources_panel(mut commands: Commands) {
+    // Resources (center, toggled with F11)
+    commands.spawn((
+        ResourcesPanel,
+        Interaction::default(),
+        ScrollPosition::default(),
+        BackgroundColor(Color::srgba(0.05, 0.05, 0.1, 0.92)),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(120.0),
+            left: Val::Px(0.0),
+            right: Val::Px(0.0),
+            margin: UiRect::horizontal(Val::Auto),
+            width: Val::Px(COLUMN_WIDTHS.iter().sum::<f32>() + 12.0),
+            max_width: Val::Percent(96.0),
+            max_height: Val::Px(480.0),
+            flex_direction: FlexDirection::Column,
+            overflow: Overflow::scroll_y(),
+            padding: UiRect::all(Val::Px(6.0)),
+            row_gap: Val::Px(2.0),
+            display: Display::None,
+            ..default()
+        },
+    ));
+}
+
+fn cell(text: String, width: f32, color: Color) -> impl Bundle {
+    (
+        Text::new(text),
+        TextFont {
+            font_size: 11.0,
+            ..default()
+        },
+        TextColor(color),
+        Node {
+            width: Val::Px(width),
+            ..default()
+        },
+    )
+}
+
+// Rebuilds the list when it's opened and whenever a city changes, which covers borders
+// growing, tiles being worked and every new turn. The sites come from the tile index
+// rather than a scan of the map. There's no fog of war, so every site counts as found.
+// Tile improvements don't exist yet either: a resource is connected once a city works it.
+#[allow(clippy::too_many_arguments)]
+pub fn update_resources_panel(
+    mut commands: Commands,
+    ui_state: Res<UIState>,
+    input_map: Res<InputMap>,
+    tile_index: Res<TileIndex>,
+    mut panel_query: Query<(Entity, &mut Node), With<ResourcesPanel>>,
+    city_query: Query<Ref<City>>,
+    mut removed_cities: RemovedComponents<City>,
+    unit_query: Query<&Unit>,
+    civ_manager: Res<CivilizationManager>,
+) {
+    let cities_removed = removed_cities.read().count() > 0;
+    let Ok((panel_entity, mut panel_node)) = panel_query.single_mut() else { return };
+    
+    let display = if ui_state.show_resources { Display::Flex } else { Display::None };
+    if panel_node.display != display {
+        panel_node.display = display;
+    }
+    if !ui_state.show_resources {
+        return;
+    }
+    let cities_changed = city_query.iter().any(|city| city.is_changed());
+    if !ui_state.is_changed() && !cities_changed && !cities_removed {
+        return;
+    }
+    
+    let Some(player_civ) = civ_manager.get_viewed_civilization() else { return };
+    let cities: Vec<&City> = city_query
+        .iter()
+        .filter(|city| city.civilization_id == player_civ.id)
+        .map(|city| city.into_inner())
+        .collect();
+    let territory: HashSet<_> = cities.iter().flat_map(|city| city.territory_tiles.iter().copied()).collect();
+    let worked: HashSet<_> = cities.iter().flat_map(|city| city.worked_tiles.iter().copied()).collect();
+    
+    let mut resources: Vec<ResourceType> = tile_index.resources.keys().copied().collect();
+    resources.sort_by_key(|resource| *resource as u8);
+    
+    commands.entity(panel_entity).despawn_related::<Children>();
+    commands.entity(panel_entity).with_children(|panel| {
+        panel.spawn(cell(
+            format!("=== RESOURCES: {} kinds found ({} to close) ===", resources.len(), input_map.label(Action::ToggleResources)),
+            COLUMN_WIDTHS.iter().sum(),
+            HEADER_COLOR,
+        ));
+        panel
+            .spawn(Node {
+                flex_direction: FlexDirection::Row,
+                ..default()
+            })
+            .with_children(|header| {
+                for (title, width) in ["Resource", "Found", "Yours", "Worked", "Click a row to visit its sites"].into_iter().zip(COLUMN_WIDTHS) {
+                    header.spawn(cell(title.to_string(), width, HEADER_COLOR));
+                }
+            });
+        
+        for resource in resources {
+            let sites = tile_index.resource_sites(resource);
+            let owned = sites.iter().filter(|coord| territory.contains(coord)).count();
+            let connected = sites.iter().filter(|coord| worked.contains(coord)).count();
+            
+            let (note, warning) = if resource.is_strategic() {
+                let needing = unit_query
+                    .iter()
+                    .filter(|unit| unit.civilization_id == player_civ.id && unit.unit_type.required_resource() == Some(resource))
+                    .count();
+                match (needing, player_civ.has_resource(resource)) {
+                    (0, _) => ("Strategic: no units of yours need it".to_string(), false),
+                    (units, true) => (format!("Strategic: enough for your {} units that need it", units), false),
+                    (units, false) => (format!("Strategic: {} of your units need it, none held", units), true),
+                }
+            } else if resource.is_luxury() {
+                let cities_with = cities
+                    .iter()
+                    .filter(|city| sites.iter().any(|coord| city.territory_tiles.contains(coord)))
+                    .count();
+                (format!("Luxury: +{:.0} happiness in {} cities", cities_with as f32 * LUXURY_HAPPINESS, cities_with), false)
+            } else {
+                (resource.effect_summary(), false)
+            };
+            
+            let cells = [
+                resource.get_name().to_string(),
+                sites.len().to_string(),
+                owned.to_string(),
+                connected.to_string(),
+                note,
+            ];
+            panel
+                .spawn((
+                    Button,
+                    ResourceRow(resource),
+                    BackgroundColor(ROW_COLOR),
+                    Node {
+                        flex_direction: FlexDirection::Row,
+                        padding: UiRect::vertical(Val::Px(1.0)),
+                        ..default()
+                    },
+                ))
+                .with_children(|row| {
+                    for (index, (text, width)) in cells.into_iter().zip(COLUMN_WIDTHS).enumerate() {
+                        let color = match index {
+                            0 => resource.color(),
+                            4 if warning => WARNING_COLOR,
+                            _ => TEXT_COLOR,
+                        };
+                        row.spawn(cell(text, width, color));
+                    }
+                });
+        }
+    });
+}
+
+// Each click on a row moves the camera to the resource's next site, starting over at
+// the first after the last or when another resource is picked
+pub fn resource_row_system(
+    mut row_query: Query<(&Interaction, &ResourceRow, &mut BackgroundColor), Changed<Interaction>>,
+    tile_index: Res<TileIndex>,
+    mut tour: ResMut<ResourceTour>,
+    mut event_camera: EventCamera,
+) {
+    for (interaction, row, mut background) in row_query.iter_mut() {
+        match interaction {
+            Interaction::Pressed => {
+                let sites = tile_index.resource_sites(row.0);
+                if sites.is_empty() {
+                    continue;
+                }
+                let site = if tour.resource == Some(row.0) { (tour.site + 1) % sites.len() } else { 0 };
+                *tour = ResourceTour {
+                    resource: Some(row.0),
+                    site,
+                };
+                event_camera.focus_event(sites[site]);
+            }
+            Interaction::Hovered => background.0 = ROW_HOVER_COLOR,
+            Interaction::None => background.0 = ROW_COLOR,
+        }
+    }
+}
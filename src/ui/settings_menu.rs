@@ -0,0 +1,526 @@
+use bevy::prelude::*;
+use bevy::ui::RelativeCursorPosition;
+use crate::game::civilization::{CivilizationManager, GameSpeed};
+use crate::game::game_initialization::AppState;
+use crate::game::map::{ELEVATION_INTENSITY_STEP, MAX_ELEVATION_INTENSITY};
+use crate::game::notifications::MapEvent;
+use super::menus::{BUTTON_COLOR, BUTTON_HOVER_COLOR, TITLE_COLOR, cycle, screen, text};
+use super::ui_scale::{MAX_INTERFACE_SCALE, MIN_INTERFACE_SCALE};
+use super::user_settings::Preferences;
+
+const TRACK_WIDTH: f32 = 200.0;
+const TRACK_HEIGHT: f32 = 10.0;
+const TRACK_COLOR: Color = Color::srgba(0.1, 0.1, 0.12, 0.95);
+const FILL_COLOR: Color = Color::srgb(0.55, 0.65, 0.85);
+const CHECK_COLOR: Color = Color::srgb(0.9, 0.9, 0.7);
+const CONTROL_WIDTH: f32 = 250.0;
+
+// Ranges the sliders cover that aren't set elsewhere
+const INTERFACE_SCALE_STEP: f32 = 0.05;
+const MIN_EDGE_PAN_MARGIN: f32 = 2.0;
+const MAX_EDGE_PAN_MARGIN: f32 = 60.0;
+const MAX_AUTOSAVE_INTERVAL: f32 = 20.0;
+
+/// An on/off setting, shown as a checkbox
+#[derive(Clone, Copy, PartialEq)]
+pub enum Toggle {
+    ElevationShading,
+    WaterShading,
+    StrategicHighlights,
+    RiverHighlights,
+    Grid,
+    EdgePan,
+    TurnReport,
+    AutoProduction,
+    QuickMovementAi,
+    QuickCombatAi,
+}
+
+impl Toggle {
+    fn get_name(self) -> &'static str {
+        match self {
+            Toggle::ElevationShading => "Elevation shading",
+            Toggle::WaterShading => "Water depth shading",
+            Toggle::StrategicHighlights => "Highlight strategic resources",
+            Toggle::RiverHighlights => "Highlight rivers",
+            Toggle::Grid => "Grid lines",
+            Toggle::EdgePan => "Pan at screen edges",
+            Toggle::TurnReport => "Report between turns",
+            Toggle::AutoProduction => "Idle cities pick their own builds",
+            Toggle::QuickMovementAi => "Quick AI movement",
+            Toggle::QuickCombatAi => "Quick combat between AIs",
+        }
+    }
+}
+
+/// A setting with a range of values, shown as a slider
+#[derive(Clone, Copy, PartialEq)]
+pub enum Slider {
+    ElevationIntensity,
+    InterfaceScale,
+    EdgePanMargin,
+    Autosave,
+}
+
+impl Slider {
+    // The lowest and highest values, and the step between them
+    fn range(self) -> (f32, f32, f32) {
+        match self {
+            Slider::ElevationIntensity => (0.0, MAX_ELEVATION_INTENSITY, ELEVATION_INTENSITY_STEP),
+            Slider::InterfaceScale => (MIN_INTERFACE_SCALE, MAX_INTERFACE_SCALE, INTERFACE_SCALE_STEP),
+            Slider::EdgePanMargin => (MIN_EDGE_PAN_MARGIN, MAX_EDGE_PAN_MARGIN, 1.0),
+            Slider::Autosave => (0.0, MAX_AUTOSAVE_INTERVAL, 1.0),
+        }
+    }
+}
+
+/// Something on the settings screen that can be picked and changed
+#[derive(Component, Clone, Copy, PartialEq)]
+pub enum SettingsControl {
+    Toggle(Toggle),
+    Slider(Slider),
+    Speed,
+    Alert(MapEvent),
+    ResetDefaults,
+    Back,
+}
+
+// The screen's sections, side by side, each a column of controls
+const SECTIONS: [(&str, &[SettingsControl]); 4] = [
+    ("Map", &[
+        SettingsControl::Toggle(Toggle::ElevationShading),
+        SettingsControl::Slider(Slider::ElevationIntensity),
+        SettingsControl::Toggle(Toggle::WaterShading),
+        SettingsControl::Toggle(Toggle::StrategicHighlights),
+        SettingsControl::Toggle(Toggle::RiverHighlights),
+        SettingsControl::Toggle(Toggle::Grid),
+    ]),
+    ("Interface", &[
+        SettingsControl::Slider(Slider::InterfaceScale),
+        SettingsControl::Toggle(Toggle::EdgePan),
+        SettingsControl::Slider(Slider::EdgePanMargin),
+        SettingsControl::Toggle(Toggle::TurnReport),
+    ]),
+    ("Game", &[
+        SettingsControl::Speed,
+        SettingsControl::Slider(Slider::Autosave),
+        SettingsControl::Toggle(Toggle::AutoProduction),
+        SettingsControl::Toggle(Toggle::QuickMovementAi),
+        SettingsControl::Toggle(Toggle::QuickCombatAi),
+    ]),
+    ("Alerts", &[
+        SettingsControl::Alert(MapEvent::Combat),
+        SettingsControl::Alert(MapEvent::CityCaptured),
+        SettingsControl::Alert(MapEvent::BorderFlip),
+        SettingsControl::Alert(MapEvent::BarbarianSpawn),
+        SettingsControl::Alert(MapEvent::CampCleared),
+    ]),
+];
+
+// Every control in the order the keyboard steps through them
+fn controls() -> Vec<SettingsControl> {
+    SECTIONS
+        .iter()
+        .flat_map(|(_, controls)| controls.iter().copied())
+        .chain([SettingsControl::ResetDefaults, SettingsControl::Back])
+        .collect()
+}
+
+/// Whether the settings screen is open over the main or pause menu, the control the
+/// keyboard has picked, and what the last reset said
+#[derive(Resource, Default)]
+pub struct SettingsMenu {
+    open: bool,
+    focus: usize,
+    notice: Option<String>,
+}
+
+impl SettingsMenu {
+    pub fn open(&mut self) {
+        *self = Self {
+            open: true,
+            ..default()
+        };
+    }
+    
+    pub fn close(&mut self) {
+        self.open = false;
+    }
+    
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+}
+
+#[derive(Component)]
+pub struct SettingsScreen;
+
+// The part of a control that lights up when it has the focus
+#[derive(Component)]
+pub struct SettingsRow(SettingsControl);
+
+// Text naming a control and its value
+#[derive(Component)]
+pub struct SettingsLabel(SettingsControl);
+
+// The filled part of a slider's track
+#[derive(Component)]
+pub struct SliderFill(Slider);
+
+// The mark inside a checkbox
+#[derive(Component)]
+pub struct CheckMark(Toggle);
+
+// Line under the buttons saying how the last reset went
+#[derive(Component)]
+pub struct SettingsStatus;
+
+fn toggle_value(preferences: &Preferences, toggle: Toggle) -> bool {
+    let visuals = preferences.shading.config();
+    match toggle {
+        Toggle::ElevationShading => visuals.elevation_shading,
+        Toggle::WaterShading => visuals.water_depth_shading,
+        Toggle::StrategicHighlights => visuals.strategic_highlighting,
+        Toggle::RiverHighlights => visuals.river_highlighting,
+        Toggle::Grid => preferences.grid.show_grid,
+        Toggle::EdgePan => preferences.settings.edge_pan,
+        Toggle::TurnReport => preferences.settings.turn_report,
+        Toggle::AutoProduction => preferences.settings.auto_production,
+        Toggle::QuickMovementAi => preferences.settings.quick_movement_ai,
+        Toggle::QuickCombatAi => preferences.settings.quick_combat_ai,
+    }
+}
+
+fn flip_toggle(preferences: &mut Preferences, toggle: Toggle) {
+    match toggle {
+        Toggle::ElevationShading => preferences.shading.update(|visuals| visuals.elevation_shading = !visuals.elevation_shading),
+        Toggle::WaterShading => preferences.shading.update(|visuals| visuals.water_depth_shading = !visuals.water_depth_shading),
+        Toggle::StrategicHighlights => preferences.shading.update(|visuals| visuals.strategic_highlighting = !visuals.strategic_highlighting),
+        Toggle::RiverHighlights => preferences.shading.update(|visuals| visuals.river_highlighting = !visuals.river_highlighting),
+        Toggle::Grid => preferences.toggle_grid(),
+        Toggle::EdgePan => preferences.settings.edge_pan = !preferences.settings.edge_pan,
+        Toggle::TurnReport => preferences.settings.turn_report = !preferences.settings.turn_report,
+        Toggle::AutoProduction => preferences.settings.auto_production = !preferences.settings.auto_production,
+        Toggle::QuickMovementAi => preferences.settings.quick_movement_ai = !preferences.settings.quick_movement_ai,
+        Toggle::QuickCombatAi => preferences.settings.quick_combat_ai = !preferences.settings.quick_combat_ai,
+    }
+}
+
+fn slider_value(preferences: &Preferences, slider: Slider) -> f32 {
+    match slider {
+        Slider::ElevationIntensity => preferences.shading.config().elevation_intensity,
+        Slider::InterfaceScale => preferences.interface_scale.0,
+        Slider::EdgePanMargin => preferences.settings.edge_pan_margin,
+        Slider::Autosave => preferences.settings.autosave_interval as f32,
+    }
+}
+
+// Puts a slider on the step nearest `value`. Nothing is touched when that's where it
+// already is, so dragging along a step doesn't recolour the map every frame.
+fn set_slider(preferences: &mut Preferences, slider: Slider, value: f32) {
+    let (min, max, step) = slider.range();
+    let value = (min + ((value - min) / step).round() * step).clamp(min, max);
+    if (value - slider_value(preferences, slider)).abs() < step * 0.01 {
+        return;
+    }
+    match slider {
+        Slider::ElevationIntensity => preferences.shading.update(|visuals| visuals.elevation_intensity = value),
+        Slider::InterfaceScale => preferences.interface_scale.0 = value,
+        Slider::EdgePanMargin => preferences.settings.edge_pan_margin = value,
+        Slider::Autosave => preferences.settings.autosave_interval = value as u32,
+    }
+}
+
+// How far along its track a slider is, from 0 to 1
+fn slider_fraction(preferences: &Preferences, slider: Slider) -> f32 {
+    let (min, max, _) = slider.range();
+    ((slider_value(preferences, slider) - min) / (max - min)).clamp(0.0, 1.0)
+}
+
+fn control_label(control: SettingsControl, preferences: &Preferences, speed: GameSpeed) -> String {
+    match control {
+        SettingsControl::Toggle(toggle) => toggle.get_name().to_string(),
+        SettingsControl::Slider(slider) => {
+            let value = slider_value(preferences, slider);
+            match slider {
+                Slider::ElevationIntensity => format!("Elevation intensity: {:.2}", value),
+                Slider::InterfaceScale => format!("Interface size: {:.0}%", value * 100.0),
+                Slider::EdgePanMargin => format!("Edge panning margin: {:.0} px", value),
+                Slider::Autosave => match preferences.settings.autosave_interval {
+                    0 => "Autosave: off".to_string(),
+                    1 => "Autosave: every turn".to_string(),
+                    turns => format!("Autosave: every {} turns", turns),
+                },
+            }
+        }
+        SettingsControl::Speed => format!("Game speed: {}", speed.get_name()),
+        SettingsControl::Alert(event) => format!("{}: {}", event.get_name(), preferences.alert_style(event).get_name()),
+        SettingsControl::ResetDefaults => "Reset to defaults".to_string(),
+        SettingsControl::Back => "Back".to_string(),
+    }
+}
+
+// In a game the speed is that game's; from the main menu it's what new games start with
+fn current_speed(state: &State<AppState>, preferences: &Preferences, civ_manager: &CivilizationManager) -> GameSpeed {
+    if *state.get() == AppState::Paused { civ_manager.speed } else { preferences.settings.speed }
+}
+
+fn spawn_control(parent: &mut ChildSpawnerCommands, control: SettingsControl, preferences: &Preferences, speed: GameSpeed) {
+    let label = (SettingsLabel(control), text(control_label(control, preferences, speed), 14.0, Color::WHITE));
+    let row = Node {
+        width: Val::Px(CONTROL_WIDTH),
+        padding: UiRect::axes(Val::Px(12.0), Val::Px(6.0)),
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    match control {
+        SettingsControl::Toggle(toggle) => {
+            let checked = toggle_value(preferences, toggle);
+            parent
+                .spawn((Button, control, SettingsRow(control), BackgroundColor(BUTTON_COLOR), Node {
+                    column_gap: Val::Px(8.0),
+                    ..row
+                }))
+                .with_children(|button| {
+                    button
+                        .spawn((BorderColor(Color::WHITE), Node {
+                            width: Val::Px(14.0),
+                            height: Val::Px(14.0),
+                            border: UiRect::all(Val::Px(1.0)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        }))
+                        .with_child((
+                            CheckMark(toggle),
+                            BackgroundColor(if checked { CHECK_COLOR } else { Color::NONE }),
+                            Node {
+                                width: Val::Px(8.0),
+                                height: Val::Px(8.0),
+                                ..default()
+                            },
+                        ));
+                    button.spawn(label);
+                });
+        }
+        SettingsControl::Slider(slider) => {
+            // Clicking or dragging along the track sets the value under the cursor
+            parent
+                .spawn((SettingsRow(control), BackgroundColor(BUTTON_COLOR), Node {
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::FlexStart,
+                    row_gap: Val::Px(4.0),
+                    ..row
+                }))
+                .with_children(|column| {
+                    column.spawn(label);
+                    column
+                        .spawn((Button, control, RelativeCursorPosition::default(), BackgroundColor(TRACK_COLOR), Node {
+                            width: Val::Px(TRACK_WIDTH),
+                            height: Val::Px(TRACK_HEIGHT),
+                            ..default()
+                        }))
+                        .with_child((SliderFill(slider), BackgroundColor(FILL_COLOR), Node {
+                            width: Val::Percent(slider_fraction(preferences, slider) * 100.0),
+                            height: Val::Percent(100.0),
+                            ..default()
+                        }));
+                });
+        }
+        SettingsControl::Speed | SettingsControl::Alert(_) | SettingsControl::ResetDefaults | SettingsControl::Back => {
+            parent
+                .spawn((Button, control, SettingsRow(control), BackgroundColor(BUTTON_COLOR), Node {
+                    justify_content: JustifyContent::Center,
+                    ..row
+                }))
+                .with_child(label);
+        }
+    }
+}
+
+// The settings screen, over whichever menu opened it and going away with it
+fn spawn_settings_screen(commands: &mut Commands, state: AppState, preferences: &Preferences, speed: GameSpeed) {
+    commands
+        .spawn((SettingsScreen, screen(state, Color::srgba(0.02, 0.02, 0.06, 0.95))))
+        // Over the pause menu, which shares the screen's usual layer
+        .insert(GlobalZIndex(11))
+        .with_children(|parent| {
+            parent.spawn(text("=== SETTINGS ===", 22.0, TITLE_COLOR));
+            parent
+                .spawn(Node {
+                    column_gap: Val::Px(24.0),
+                    align_items: AlignItems::FlexStart,
+                    ..default()
+                })
+                .with_children(|sections| {
+                    for (title, controls) in SECTIONS {
+                        sections
+                            .spawn(Node {
+                                flex_direction: FlexDirection::Column,
+                                row_gap: Val::Px(6.0),
+                                ..default()
+                            })
+                            .with_children(|section| {
+                                section.spawn(text(title, 16.0, TITLE_COLOR));
+                                for control in controls {
+                                    spawn_control(section, *control, preferences, speed);
+                                }
+                            });
+                    }
+                });
+            parent
+                .spawn(Node {
+                    column_gap: Val::Px(8.0),
+                    ..default()
+                })
+                .with_children(|footer| {
+                    spawn_control(footer, SettingsControl::ResetDefaults, preferences, speed);
+                    spawn_control(footer, SettingsControl::Back, preferences, speed);
+                });
+            parent.spawn((SettingsStatus, text("", 12.0, TITLE_COLOR)));
+            parent.spawn(text(
+                "Up/Down - choose   Left/Right - adjust   Enter - change   Esc - back",
+                12.0,
+                Color::srgb(0.8, 0.8, 0.8),
+            ));
+        });
+}
+
+// Opens and closes the settings screen, and takes the mouse and keyboard while it's up:
+// Up and Down pick a control, Left and Right move a slider a step, Enter flips or
+// presses, and Escape goes back to the menu underneath. Every change takes effect at
+// once, and the settings file picks it up.
+#[allow(clippy::too_many_arguments)]
+pub fn settings_menu_system(
+    mut commands: Commands,
+    mut keyboard: ResMut<ButtonInput<KeyCode>>,
+    mut menu: ResMut<SettingsMenu>,
+    state: Res<State<AppState>>,
+    screen_query: Query<Entity, With<SettingsScreen>>,
+    control_query: Query<(Ref<Interaction>, &SettingsControl, Option<&RelativeCursorPosition>)>,
+    mut preferences: Preferences,
+    mut civ_manager: ResMut<CivilizationManager>,
+) {
+    if !menu.open {
+        for entity in screen_query.iter() {
+            commands.entity(entity).despawn();
+        }
+        return;
+    }
+    if screen_query.is_empty() {
+        let speed = current_speed(&state, &preferences, &civ_manager);
+        spawn_settings_screen(&mut commands, *state.get(), &preferences, speed);
+        return;
+    }
+    
+    let controls = controls();
+    let mut pressed = None;
+    for (interaction, control, cursor) in control_query.iter() {
+        let index = controls.iter().position(|other| other == control).unwrap_or(0);
+        match (*interaction, control) {
+            // A slider follows the cursor for as long as the button is held on it
+            (Interaction::Pressed, SettingsControl::Slider(slider)) => {
+                if let Some(position) = cursor.and_then(|cursor| cursor.normalized) {
+                    let (min, max, _) = slider.range();
+                    set_slider(&mut preferences, *slider, min + position.x.clamp(0.0, 1.0) * (max - min));
+                }
+                menu.focus = index;
+            }
+            (Interaction::Pressed, _) if interaction.is_changed() => pressed = Some(*control),
+            // The mouse takes the focus when it moves onto a control
+            (Interaction::Hovered, _) if interaction.is_changed() => menu.focus = index,
+            _ => {}
+        }
+    }
+    if keyboard.just_pressed(KeyCode::ArrowDown) {
+        menu.focus = (menu.focus + 1) % controls.len();
+    }
+    if keyboard.just_pressed(KeyCode::ArrowUp) {
+        menu.focus = (menu.focus + controls.len() - 1) % controls.len();
+    }
+    let focused = controls[menu.focus.min(controls.len() - 1)];
+    if let SettingsControl::Slider(slider) = focused {
+        let (_, _, step) = slider.range();
+        let value = slider_value(&preferences, slider);
+        if keyboard.just_pressed(KeyCode::ArrowLeft) {
+            set_slider(&mut preferences, slider, value - step);
+        }
+        if keyboard.just_pressed(KeyCode::ArrowRight) {
+            set_slider(&mut preferences, slider, value + step);
+        }
+    }
+    if keyboard.any_just_pressed([KeyCode::Enter, KeyCode::NumpadEnter, KeyCode::Space]) {
+        pressed = Some(focused);
+    }
+    // Used up here, so the menu underneath doesn't take it as well
+    if keyboard.clear_just_pressed(KeyCode::Escape) {
+        pressed = Some(SettingsControl::Back);
+    }
+    
+    let Some(control) = pressed else { return };
+    match control {
+        SettingsControl::Toggle(toggle) => flip_toggle(&mut preferences, toggle),
+        SettingsControl::Slider(_) => {}
+        // Cities pick a game's new speed up when their yields are next worked out
+        SettingsControl::Speed if *state.get() == AppState::Paused => civ_manager.speed = cycle(&GameSpeed::ALL, &civ_manager.speed),
+        SettingsControl::Speed => {
+            let settings = &mut preferences.settings;
+            settings.speed = cycle(&GameSpeed::ALL, &settings.speed);
+        }
+        SettingsControl::Alert(event) => preferences.cycle_alert(event),
+        SettingsControl::ResetDefaults => menu.notice = Some(preferences.reset()),
+        SettingsControl::Back => menu.close(),
+    }
+}
+
+// Keeps the labels, checkboxes, sliders and focus on the screen in step with the settings,
+// however they were changed
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn update_settings_menu(
+    menu: Res<SettingsMenu>,
+    state: Res<State<AppState>>,
+    preferences: Preferences,
+    civ_manager: Res<CivilizationManager>,
+    mut label_query: Query<(&SettingsLabel, &mut Text), Without<SettingsStatus>>,
+    mut status_query: Query<&mut Text, With<SettingsStatus>>,
+    mut fill_query: Query<(&SliderFill, &mut Node)>,
+    mut mark_query: Query<(&CheckMark, &mut BackgroundColor), Without<SettingsRow>>,
+    mut row_query: Query<(&SettingsRow, &mut BackgroundColor)>,
+) {
+    if !menu.open {
+        return;
+    }
+    let speed = current_speed(&state, &preferences, &civ_manager);
+    for (label, mut text) in label_query.iter_mut() {
+        let value = control_label(label.0, &preferences, speed);
+        if **text != value {
+            **text = value;
+        }
+    }
+    if let Ok(mut status) = status_query.single_mut() {
+        let notice = menu.notice.clone().unwrap_or_default();
+        if **status != notice {
+            **status = notice;
+        }
+    }
+    for (fill, mut node) in fill_query.iter_mut() {
+        let width = Val::Percent(slider_fraction(&preferences, fill.0) * 100.0);
+        if node.width != width {
+            node.width = width;
+        }
+    }
+    for (mark, mut background) in mark_query.iter_mut() {
+        let color = if toggle_value(&preferences, mark.0) { CHECK_COLOR } else { Color::NONE };
+        if background.0 != color {
+            background.0 = color;
+        }
+    }
+    let controls = controls();
+    let focused = controls.get(menu.focus).copied();
+    for (row, mut background) in row_query.iter_mut() {
+        let color = if Some(row.0) == focused { BUTTON_HOVER_COLOR } else { BUTTON_COLOR };
+        if background.0 != color {
+            background.0 = color;
+        }
+    }
+}
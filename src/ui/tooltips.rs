@@ -0,0 +1,223 @@
+use bevy::prelude::*;
+use bevy::ecs::system::SystemParam;
+use crate::game::cities::{City, CityMarker};
+use crate::game::civilization::CivilizationManager;
+use crate::game::input::CursorState;
+use crate::game::input_map::{Action, InputMap};
+use crate::game::map::HEX_SIZE;
+use crate::game::resources::{ResourceMarker, ResourceType};
+use crate::game::units::{Unit, UnitMarker, UnitOrder};
+use super::city_banners::production_label;
+
+// How long the cursor has to rest on something before its tooltip shows
+const TOOLTIP_DELAY_SECS: f32 = 0.4;
+// Gap between the cursor and the tooltip's corner, in interface pixels
+const CURSOR_OFFSET: f32 = 16.0;
+// How near a map marker's centre the cursor has to be to count as on it
+const MARKER_HOVER_RADIUS: f32 = HEX_SIZE * 0.4;
+
+/// Something that explains itself when the cursor rests on it: a UI node with an
+/// Interaction, or a marker on the map. Units and cities are described as they are at
+/// that moment; the rest carry what they say.
+#[derive(Component, Clone, PartialEq)]
+pub enum TooltipSource {
+    Text(String),
+    Hotkey(Action, &'static str), // The action's keys, as bound now, and what it does
+    Unit(Entity),
+    City(Entity),
+    Resource(ResourceType),
+}
+
+/// The one box every tooltip is shown in
+#[derive(Component)]
+pub struct Tooltip;
+
+/// What the tooltips describe units and cities with
+#[derive(SystemParam)]
+pub struct TooltipContext<'w, 's> {
+    units: Query<'w, 's, &'static Unit>,
+    cities: Query<'w, 's, &'static City>,
+    civ_manager: Res<'w, CivilizationManager>,
+    input_map: Res<'w, InputMap>,
+}
+
+impl TooltipContext<'_, '_> {
+    fn describe(&self, source: &TooltipSource) -> Option<String> {
+        match source {
+            TooltipSource::Text(text) => Some(text.clone()),
+            TooltipSource::Hotkey(action, description) => Some(format!("{} [{}]\n{}", action.get_name(), self.input_map.label(*action), description)),
+            TooltipSource::Unit(entity) => self.units.get(*entity).ok().map(|unit| self.describe_unit(unit)),
+            TooltipSource::City(entity) => self.cities.get(*entity).ok().map(|city| self.describe_city(city)),
+            TooltipSource::Resource(resource) => Some(format!("{}\n{}", resource.get_name(), resource.effect_summary())),
+        }
+    }
+
+    fn owner_name(&self, civilization_id: u32) -> &str {
+        self.civ_manager.get_civilization(civilization_id).map_or("Unknown", |civ| civ.name.as_str())
+    }
+
+    fn describe_unit(&self, unit: &Unit) -> String {
+        let order = if unit.is_fortified {
+            "Fortified".to_string()
+        } else {
+            match unit.order {
+                UnitOrder::Idle => "Idle".to_string(),
+                UnitOrder::GoTo(target) => format!("GoTo ({}, {})", target.q, target.r),
+                UnitOrder::Sentry => "Sentry".to_string(),
+                UnitOrder::Automate(_) => "Automated".to_string(),
+            }
+        };
+        format!(
+            "{} ({})\n{}\nHP {}/{}  Attack {}  Defense {}\nOrder: {}",
+            unit.name,
+            unit.unit_type.get_name(),
+            self.owner_name(unit.civilization_id),
+            unit.health,
+            unit.max_health,
+            unit.attack_strength,
+            unit.defense_strength,
+            order
+        )
+    }
+
+    // Only the viewer's own cities show their yields; anyone's shows who holds it
+    fn describe_city(&self, city: &City) -> String {
+        let header = format!("{} ({})\n{}", city.name, city.population, self.owner_name(city.civilization_id));
+        let own = self.civ_manager.get_viewed_civilization().is_some_and(|civ| civ.id == city.civilization_id);
+        if !own {
+            return header;
+        }
+        format!(
+            "{}\nFood {:+.1}  Production {:.1}  Gold {:+.1}\nScience {:.1}  Culture {:.1}\nBuilding: {}",
+            header,
+            city.food_per_turn,
+            city.production_per_turn,
+            city.gold_per_turn,
+            city.science_per_turn,
+            city.culture_per_turn,
+            production_label(city)
+        )
+    }
+}
+
+pub fn setup_tooltip(mut commands: Commands) {
+    commands.spawn((
+        Tooltip,
+        Text::new(""),
+        TextFont {
+            font_size: 12.0,
+            ..default()
+        },
+        TextColor(Color::srgb(0.95, 0.95, 0.85)),
+        BackgroundColor(Color::srgba(0.05, 0.05, 0.08, 0.92)),
+        Node {
+            position_type: PositionType::Absolute,
+            max_width: Val::Px(300.0),
+            padding: UiRect::axes(Val::Px(6.0), Val::Px(4.0)),
+            display: Display::None,
+            ..default()
+        },
+        // Over the panels and overlays
+        GlobalZIndex(8),
+    ));
+}
+
+// Map markers are spawned by the game; they're given their tooltips here
+pub fn attach_map_tooltips(
+    mut commands: Commands,
+    unit_markers: Query<(Entity, &UnitMarker), Added<UnitMarker>>,
+    city_markers: Query<(Entity, &CityMarker), Added<CityMarker>>,
+    resource_markers: Query<(Entity, &ResourceMarker), Added<ResourceMarker>>,
+) {
+    for (entity, marker) in unit_markers.iter() {
+        commands.entity(entity).insert(TooltipSource::Unit(marker.unit));
+    }
+    for (entity, marker) in city_markers.iter() {
+        commands.entity(entity).insert(TooltipSource::City(marker.city_entity));
+    }
+    for (entity, marker) in resource_markers.iter() {
+        commands.entity(entity).insert(TooltipSource::Resource(marker.resource_type));
+    }
+}
+
+// Shows the tooltip of whatever the cursor has rested on, beside the cursor and kept
+// inside the window. Holding a mouse button or moving the camera hides it and starts
+// the wait over.
+#[allow(clippy::too_many_arguments)]
+pub fn tooltip_system(
+    time: Res<Time>,
+    cursor: Res<CursorState>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    camera_query: Query<Ref<Transform>, With<Camera>>,
+    windows: Query<&Window>,
+    ui_scale: Res<UiScale>,
+    ui_sources: Query<(Entity, &Interaction, &TooltipSource, &InheritedVisibility)>,
+    map_sources: Query<(Entity, &GlobalTransform, &TooltipSource, &InheritedVisibility), Without<Node>>,
+    context: TooltipContext,
+    mut tooltip_query: Query<(&mut Node, &mut Text, &ComputedNode), With<Tooltip>>,
+    mut resting_on: Local<Option<(Entity, f32)>>, // What the cursor is on, and since when
+) {
+    let Ok((mut node, mut text, computed)) = tooltip_query.single_mut() else { return };
+    let busy = mouse.get_pressed().next().is_some() || camera_query.iter().any(|transform| transform.is_changed());
+
+    let hovered = if busy {
+        None
+    } else if cursor.over_ui {
+        ui_sources
+            .iter()
+            .find(|(_, interaction, _, visibility)| **interaction != Interaction::None && visibility.get())
+            .map(|(entity, _, source, _)| (entity, source))
+    } else {
+        cursor.world_pos.and_then(|world_pos| {
+            map_sources
+                .iter()
+                .filter(|(_, _, _, visibility)| visibility.get())
+                .map(|(entity, transform, source, _)| (entity, source, transform.translation().truncate().distance(world_pos)))
+                .filter(|(_, _, distance)| *distance <= MARKER_HOVER_RADIUS)
+                .min_by(|a, b| a.2.total_cmp(&b.2))
+                .map(|(entity, source, _)| (entity, source))
+        })
+    };
+
+    let now = time.elapsed_secs();
+    let shown = match hovered {
+        Some((entity, source)) => {
+            if resting_on.is_none_or(|(on, _)| on != entity) {
+                *resting_on = Some((entity, now));
+            }
+            let rested = resting_on.is_some_and(|(_, since)| now - since >= TOOLTIP_DELAY_SECS);
+            if rested { context.describe(source) } else { None }
+        }
+        None => {
+            *resting_on = None;
+            None
+        }
+    };
+
+    let (Some(description), Some(screen_pos), Ok(window)) = (shown, cursor.screen_pos, windows.single()) else {
+        if node.display != Display::None {
+            node.display = Display::None;
+        }
+        return;
+    };
+    if **text != description {
+        **text = description;
+    }
+
+    // Below and right of the cursor, flipped to the other side where that would run off
+    // the window. Positions are in interface pixels, which the interface scale enlarges.
+    let cursor_pos = screen_pos / ui_scale.0;
+    let window_size = Vec2::new(window.width(), window.height()) / ui_scale.0;
+    let size = computed.size() * computed.inverse_scale_factor();
+    let mut corner = cursor_pos + Vec2::splat(CURSOR_OFFSET);
+    if corner.x + size.x > window_size.x {
+        corner.x = cursor_pos.x - CURSOR_OFFSET - size.x;
+    }
+    if corner.y + size.y > window_size.y {
+        corner.y = cursor_pos.y - CURSOR_OFFSET - size.y;
+    }
+    let corner = corner.clamp(Vec2::ZERO, (window_size - size).max(Vec2::ZERO));
+    node.left = Val::Px(corner.x);
+    node.top = Val::Px(corner.y);
+    node.display = Display::Flex;
+}
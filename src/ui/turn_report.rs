@@ -0,0 +1,239 @@
+use bevy::prelude::*;
+use std::collections::HashMap;
+use crate::game::civilization::CivilizationManager;
+use crate::game::game_initialization::{AppState, GameSettings, TurnStartedEvent};
+use crate::game::hex::HexCoord;
+use crate::game::camera_zoom::CameraTween;
+use crate::game::notifications::{GameNotification, NotificationCategory};
+use super::game_panels::UIState;
+use super::notification_feed::category_color;
+
+// Frames a human's turn has been running before their report opens, so the news their
+// own cities bring at the start of the turn makes it in
+const GATHER_FRAMES: u32 = 5;
+// Entries kept for one report, and how many of them the panel lists
+const MAX_ENTRIES: usize = 60;
+const ENTRIES_SHOWN: usize = 16;
+// The report leads with fighting and diplomacy, then the player's own cities and research
+const CATEGORY_ORDER: [NotificationCategory; 5] = [
+    NotificationCategory::Military,
+    NotificationCategory::Diplomacy,
+    NotificationCategory::Warning,
+    NotificationCategory::City,
+    NotificationCategory::Research,
+];
+
+const HEADER_COLOR: Color = Color::srgb(0.9, 0.9, 0.7);
+const ROW_COLOR: Color = Color::srgba(0.15, 0.15, 0.2, 0.9);
+const ROW_HOVER_COLOR: Color = Color::srgba(0.3, 0.3, 0.45, 0.9);
+
+/// One thing that happened while a human was waiting for their turn
+#[derive(Clone)]
+pub struct ReportEntry {
+    category: NotificationCategory,
+    text: String,
+    location: Option<HexCoord>,
+}
+
+/// What each human has missed since their last turn, and the report shown at the start
+/// of the current one
+#[derive(Resource, Default)]
+pub struct TurnReport {
+    pending: HashMap<u32, Vec<ReportEntry>>,
+    gathering: Option<(u32, u32)>, // The human whose turn just began, and frames left to gather for
+    turn: u32,
+    entries: Vec<ReportEntry>,
+}
+
+impl TurnReport {
+    fn push(&mut self, civ_id: u32, entry: ReportEntry) {
+        let pending = self.pending.entry(civ_id).or_default();
+        if pending.len() < MAX_ENTRIES {
+            pending.push(entry);
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct TurnReportPanel;
+
+// A report line with a place on the map; clicking it looks there
+#[derive(Component)]
+pub struct ReportJump(HexCoord);
+
+#[derive(Component)]
+pub struct ReportClose;
+
+type ReportButtonFilter = (Changed<Interaction>, Or<(With<ReportJump>, With<ReportClose>)>);
+
+fn text(value: String, color: Color) -> impl Bundle {
+    (
+        Text::new(value),
+        TextFont {
+            font_size: 12.0,
+            ..default()
+        },
+        TextColor(color),
+    )
+}
+
+pub fn setup_turn_report_panel(mut commands: Commands) {
+    // Turn report (center, opened when a human's turn starts, toggled with O)
+    commands.spawn((
+        TurnReportPanel,
+        Interaction::default(),
+        BackgroundColor(Color::srgba(0.05, 0.05, 0.1, 0.94)),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(120.0),
+            left: Val::Px(0.0),
+            right: Val::Px(0.0),
+            margin: UiRect::horizontal(Val::Auto),
+            width: Val::Px(500.0),
+            max_width: Val::Percent(96.0),
+            flex_direction: FlexDirection::Column,
+            padding: UiRect::all(Val::Px(8.0)),
+            row_gap: Val::Px(2.0),
+            display: Display::None,
+            ..default()
+        },
+    ));
+}
+
+// Keeps the news meant for each human while others play, and opens their report once
+// their own turn has begun. In hotseat that waits for the handover, so the report is the
+// incoming player's to read. Everyone's news goes to every human; turn and save notices
+// are left to the toasts.
+pub fn collect_turn_report_system(
+    mut notifications: EventReader<GameNotification>,
+    mut turn_events: EventReader<TurnStartedEvent>,
+    civ_manager: Res<CivilizationManager>,
+    settings: Res<GameSettings>,
+    state: Res<State<AppState>>,
+    mut report: ResMut<TurnReport>,
+    mut ui_state: ResMut<UIState>,
+) {
+    let humans: Vec<u32> = civ_manager.civilizations.values().filter(|civ| civ.is_player && !civ.eliminated).map(|civ| civ.id).collect();
+    for event in turn_events.read() {
+        if humans.contains(&event.civilization_id) {
+            report.gathering = Some((event.civilization_id, GATHER_FRAMES));
+        }
+    }
+    
+    let gathering = report.gathering.map(|(civ_id, _)| civ_id);
+    for notification in notifications.read() {
+        if notification.category == NotificationCategory::System {
+            continue;
+        }
+        let entry = ReportEntry {
+            category: notification.category,
+            text: notification.text.clone(),
+            location: notification.location,
+        };
+        // Not news to whoever is playing, unless their turn has only just begun
+        let missed = |civ_id: &&u32| **civ_id != civ_manager.current_turn_civ || gathering == Some(**civ_id);
+        for civ_id in humans.iter().filter(|civ_id| notification.civ.is_none_or(|civ| civ == **civ_id)).filter(missed) {
+            report.push(*civ_id, entry.clone());
+        }
+    }
+    
+    let Some((civ_id, frames_left)) = report.gathering else { return };
+    if *state.get() == AppState::Handover {
+        return;
+    }
+    if frames_left > 0 {
+        report.gathering = Some((civ_id, frames_left - 1));
+        return;
+    }
+    report.gathering = None;
+    let mut entries = report.pending.remove(&civ_id).unwrap_or_default();
+    if entries.is_empty() {
+        return;
+    }
+    entries.sort_by_key(|entry| CATEGORY_ORDER.iter().position(|category| *category == entry.category));
+    report.entries = entries;
+    report.turn = civ_manager.turn_number;
+    if settings.turn_report {
+        ui_state.show_turn_report = true;
+    }
+}
+
+// Rebuilds the report when it's opened or a new one comes in
+pub fn update_turn_report_panel(
+    mut commands: Commands,
+    ui_state: Res<UIState>,
+    report: Res<TurnReport>,
+    mut panel_query: Query<(Entity, &mut Node), With<TurnReportPanel>>,
+) {
+    let Ok((panel_entity, mut panel_node)) = panel_query.single_mut() else { return };
+    let display = if ui_state.show_turn_report { Display::Flex } else { Display::None };
+    if panel_node.display != display {
+        panel_node.display = display;
+    }
+    if !ui_state.show_turn_report || (!ui_state.is_changed() && !report.is_changed()) {
+        return;
+    }
+    
+    commands.entity(panel_entity).despawn_related::<Children>();
+    commands.entity(panel_entity).with_children(|panel| {
+        if report.entries.is_empty() {
+            panel.spawn(text("=== SINCE YOUR LAST TURN ===".to_string(), HEADER_COLOR));
+            panel.spawn(text("Nothing to report".to_string(), category_color(NotificationCategory::System)));
+        } else {
+            panel.spawn(text(format!("=== SINCE YOUR LAST TURN (turn {}) ===", report.turn), HEADER_COLOR));
+        }
+        for entry in report.entries.iter().take(ENTRIES_SHOWN) {
+            let color = category_color(entry.category);
+            let mut row = panel.spawn((
+                BackgroundColor(ROW_COLOR),
+                BorderColor(color),
+                Node {
+                    padding: UiRect::axes(Val::Px(6.0), Val::Px(2.0)),
+                    border: UiRect::left(Val::Px(3.0)),
+                    ..default()
+                },
+            ));
+            match entry.location {
+                Some(location) => row.insert((Button, ReportJump(location))).with_child(text(format!("{}  >", entry.text), color)),
+                None => row.with_child(text(entry.text.clone(), color)),
+            };
+        }
+        if report.entries.len() > ENTRIES_SHOWN {
+            let more = format!("...and {} more", report.entries.len() - ENTRIES_SHOWN);
+            panel.spawn(text(more, category_color(NotificationCategory::System)));
+        }
+        panel.spawn(text(
+            "Click an entry marked > to look there.  O or Esc closes; turn the report off in the pause menu's settings".to_string(),
+            category_color(NotificationCategory::System),
+        ));
+        panel
+            .spawn((
+                Button,
+                ReportClose,
+                BackgroundColor(ROW_COLOR),
+                Node {
+                    align_self: AlignSelf::Center,
+                    padding: UiRect::axes(Val::Px(16.0), Val::Px(4.0)),
+                    margin: UiRect::top(Val::Px(4.0)),
+                    ..default()
+                },
+            ))
+            .with_child(text("Close".to_string(), Color::WHITE));
+    });
+}
+
+// Clicking an entry centers the map on where it happened; Close puts the report away
+pub fn turn_report_button_system(
+    mut button_query: Query<(&Interaction, Option<&ReportJump>, &mut BackgroundColor), ReportButtonFilter>,
+    mut camera_tween: ResMut<CameraTween>,
+    mut ui_state: ResMut<UIState>,
+) {
+    for (interaction, jump, mut background) in button_query.iter_mut() {
+        match (interaction, jump) {
+            (Interaction::Pressed, Some(jump)) => camera_tween.center_on(jump.0),
+            (Interaction::Pressed, None) => ui_state.show_turn_report = false,
+            (Interaction::Hovered, _) => background.0 = ROW_HOVER_COLOR,
+            (Interaction::None, _) => background.0 = ROW_COLOR,
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use bevy::prelude::*;
+use bevy::window::{PrimaryWindow, WindowResized};
+
+// The window size the panels were laid out for. Smaller windows shrink the interface to
+// fit and larger ones grow it, within these bounds; the window's own scale factor has
+// already turned the screen's DPI into logical pixels.
+const DESIGN_SIZE: Vec2 = Vec2::new(1400.0, 800.0);
+const MIN_FIT: f32 = 0.75;
+const MAX_FIT: f32 = 2.0;
+
+/// The range of interface sizes the settings menu offers
+pub const MIN_INTERFACE_SCALE: f32 = 0.75;
+pub const MAX_INTERFACE_SCALE: f32 = 1.5;
+
+/// How large the player wants the interface, on top of fitting it to the window
+#[derive(Resource, Clone, Copy, PartialEq)]
+pub struct InterfaceScale(pub f32);
+
+impl Default for InterfaceScale {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+fn window_fit(window: &Window) -> f32 {
+    let fit = window.size() / DESIGN_SIZE;
+    fit.x.min(fit.y).clamp(MIN_FIT, MAX_FIT)
+}
+
+// Sizes every font, panel and gap for the window and the player's choice, and again each
+// time the window is resized
+pub fn interface_scale_system(
+    mut resized: EventReader<WindowResized>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    interface_scale: Res<InterfaceScale>,
+    mut ui_scale: ResMut<UiScale>,
+) {
+    let resized = resized.read().count() > 0;
+    if !resized && !interface_scale.is_changed() {
+        return;
+    }
+    let Ok(window) = windows.single() else { return };
+    let scale = interface_scale.0 * window_fit(window);
+    if ui_scale.0 != scale {
+        ui_scale.0 = scale;
+    }
+}
@@ -0,0 +1,168 @@
+use bevy::prelude::*;
+use crate::game::cities::City;
+use crate::game::civilization::CivilizationManager;
+use crate::game::input_map::InputMap;
+use crate::game::map::TileLookup;
+use crate::game::unit_actions::{ActionSite, UnitAction, UnitActionEvent};
+use crate::game::units::{Unit, UnitSelection};
+use super::game_panels::UIState;
+use super::tooltips::TooltipSource;
+
+const BUTTON_COLOR: Color = Color::srgba(0.15, 0.15, 0.2, 0.9);
+const BUTTON_HOVER_COLOR: Color = Color::srgba(0.3, 0.3, 0.45, 0.9);
+const DISABLED_COLOR: Color = Color::srgba(0.1, 0.1, 0.1, 0.8);
+const TEXT_COLOR: Color = Color::srgb(1.0, 1.0, 0.8);
+const DISABLED_TEXT_COLOR: Color = Color::srgb(0.45, 0.45, 0.45);
+
+/// Row of buttons under the selected unit's info, one for each thing it can do
+#[derive(Component)]
+pub struct UnitActionBar;
+
+#[derive(Component)]
+pub struct UnitActionButton {
+    action: UnitAction,
+    blocked: bool, // Greyed out; pressing it does nothing
+}
+
+// What one button shows; the bar is only rebuilt when these change
+#[derive(Clone, PartialEq)]
+pub struct ActionBarEntry {
+    action: UnitAction,
+    label: String,
+    blocked_by: Option<String>,
+}
+
+fn text(value: String, color: Color) -> impl Bundle {
+    (
+        Text::new(value),
+        TextFont {
+            font_size: 12.0,
+            ..default()
+        },
+        TextColor(color),
+    )
+}
+
+pub fn setup_unit_action_bar(mut commands: Commands) {
+    // Unit action bar (bottom center, under the selected unit's info)
+    commands
+        .spawn((
+            UnitActionBar,
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(10.0),
+                left: Val::Px(0.0),
+                right: Val::Px(0.0),
+                margin: UiRect::horizontal(Val::Auto),
+                width: Val::Px(1000.0),
+                max_width: Val::Percent(96.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(4.0),
+                display: Display::None,
+                ..default()
+            },
+        ))
+        .with_child(Node {
+            flex_wrap: FlexWrap::Wrap,
+            justify_content: JustifyContent::Center,
+            column_gap: Val::Px(4.0),
+            row_gap: Val::Px(4.0),
+            ..default()
+        });
+}
+
+// Rebuilds the buttons whenever what the selected unit can do changes
+#[allow(clippy::too_many_arguments)]
+pub fn update_unit_action_bar(
+    mut commands: Commands,
+    mut bar_query: Query<(Entity, &mut Node, &Children), With<UnitActionBar>>,
+    unit_query: Query<&Unit>,
+    city_query: Query<&City>,
+    tiles: TileLookup,
+    unit_selection: Res<UnitSelection>,
+    civ_manager: Res<CivilizationManager>,
+    ui_state: Res<UIState>,
+    input_map: Res<InputMap>,
+    mut shown: Local<(Option<Entity>, Vec<ActionBarEntry>)>, // The bar and what it was built for
+) {
+    let Ok((bar_entity, mut bar_node, children)) = bar_query.single_mut() else { return };
+    let unit = unit_selection.selected_unit.and_then(|entity| unit_query.get(entity).ok());
+    
+    let entries: Vec<ActionBarEntry> = match unit.filter(|_| ui_state.show_unit_status) {
+        Some(unit) => {
+            let site = ActionSite::new(unit, city_query.iter(), &tiles, &civ_manager);
+            UnitAction::ALL
+                .iter()
+                .filter_map(|action| {
+                    let check = action.check(unit, &site)?;
+                    Some(ActionBarEntry {
+                        action: *action,
+                        label: format!("{} [{}]", action.label(unit), action.binding(&input_map)),
+                        blocked_by: check.err(),
+                    })
+                })
+                .collect()
+        }
+        None => Vec::new(),
+    };
+    
+    let display = if entries.is_empty() { Display::None } else { Display::Flex };
+    if bar_node.display != display {
+        bar_node.display = display;
+    }
+    if shown.0 == Some(bar_entity) && shown.1 == entries {
+        return;
+    }
+    let Some(&row_entity) = children.first() else { return };
+    
+    commands.entity(row_entity).despawn_related::<Children>();
+    commands.entity(row_entity).with_children(|row| {
+        for entry in &entries {
+            let (background, color) = if entry.blocked_by.is_some() { (DISABLED_COLOR, DISABLED_TEXT_COLOR) } else { (BUTTON_COLOR, TEXT_COLOR) };
+            // What it does, or why it's greyed out
+            let tooltip = match &entry.blocked_by {
+                Some(reason) => format!("{}\n{}", entry.label, reason),
+                None => format!("{}\n{}", entry.label, entry.action.description()),
+            };
+            row.spawn((
+                Button,
+                UnitActionButton {
+                    action: entry.action,
+                    blocked: entry.blocked_by.is_some(),
+                },
+                TooltipSource::Text(tooltip),
+                BackgroundColor(background),
+                Node {
+                    padding: UiRect::axes(Val::Px(8.0), Val::Px(3.0)),
+                    ..default()
+                },
+            ))
+            .with_child(text(entry.label.clone(), color));
+        }
+    });
+    *shown = (Some(bar_entity), entries);
+}
+
+// Pressing a button asks for its action just as its hotkey does; its tooltip says what
+// it does, or why it's greyed out
+pub fn unit_action_button_system(
+    mut button_query: Query<(&Interaction, &UnitActionButton, &mut BackgroundColor), Changed<Interaction>>,
+    mut action_events: EventWriter<UnitActionEvent>,
+) {
+    for (interaction, button, mut background) in button_query.iter_mut() {
+        match interaction {
+            Interaction::Pressed if !button.blocked => {
+                action_events.write(UnitActionEvent { action: button.action });
+            }
+            Interaction::Pressed | Interaction::Hovered => {
+                if !button.blocked {
+                    background.0 = BUTTON_HOVER_COLOR;
+                }
+            }
+            Interaction::None => {
+                background.0 = if button.blocked { DISABLED_COLOR } else { BUTTON_COLOR };
+            }
+        }
+    }
+}
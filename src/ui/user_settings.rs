@@ -0,0 +1,325 @@
+use bevy::prelude::*;
+use bevy::ecs::system::SystemParam;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+use crate::game::civilization::GameSpeed;
+use crate::game::game_initialization::GameSettings;
+use crate::game::grid::{GridRoot, GridSettings, show_grid_lines};
+use crate::game::input_map::InputMap;
+use crate::game::map::{TerrainAssets, TileShading, VisualConfig};
+use crate::game::notifications::MapEvent;
+use crate::game::save::config_dir;
+use super::event_focus::AlertStyle;
+use super::game_panels::UIState;
+use super::help_overlay::Hint;
+use super::menus::cycle;
+use super::ui_scale::InterfaceScale;
+
+pub const SETTINGS_FILE: &str = "settings.ron";
+
+/// The side panels left open or closed. Full-screen panels (the tech tree, diplomacy and
+/// the like) always start closed.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PanelToggles {
+    pub hotkeys: bool,
+    pub unit_status: bool,
+    pub game_status: bool,
+    pub unit_list: bool,
+    pub combat_log: bool,
+    pub combat_effects: bool,
+}
+
+impl Default for PanelToggles {
+    fn default() -> Self {
+        Self::from_ui(&UIState::default())
+    }
+}
+
+impl PanelToggles {
+    fn from_ui(ui_state: &UIState) -> Self {
+        Self {
+            hotkeys: ui_state.show_hotkeys,
+            unit_status: ui_state.show_unit_status,
+            game_status: ui_state.show_game_status,
+            unit_list: ui_state.show_unit_list,
+            combat_log: ui_state.show_combat_log,
+            combat_effects: ui_state.show_combat_effects,
+        }
+    }
+    
+    fn apply(&self, ui_state: &mut UIState) {
+        ui_state.show_hotkeys = self.hotkeys;
+        ui_state.show_unit_status = self.unit_status;
+        ui_state.show_game_status = self.game_status;
+        ui_state.show_unit_list = self.unit_list;
+        ui_state.show_combat_log = self.combat_log;
+        ui_state.show_combat_effects = self.combat_effects;
+    }
+}
+
+/// The player's choices that outlast a game: how the map is drawn, which panels are
+/// open, what new games start with, how to be told of events on the map, and the hints already dismissed. Kept in settings.ron in the user's config
+/// directory; the command line still overrides the game settings for one run.
+#[derive(Resource, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UserSettings {
+    pub visuals: VisualConfig,
+    pub show_grid: bool,
+    pub panels: PanelToggles,
+    pub speed: GameSpeed,
+    pub autosave_interval: u32,
+    pub turn_report: bool,
+    pub auto_production: bool,
+    pub quick_movement_ai: bool,
+    pub quick_combat_ai: bool,
+    pub edge_pan: bool,
+    pub edge_pan_margin: f32,
+    pub interface_scale: f32,
+    pub key_bindings: InputMap,
+    pub dismissed_hints: BTreeSet<Hint>,
+    pub alerts: BTreeMap<MapEvent, AlertStyle>,
+}
+
+impl Default for UserSettings {
+    fn default() -> Self {
+        let settings = GameSettings::default();
+        Self {
+            visuals: VisualConfig::default(),
+            show_grid: GridSettings::default().show_grid,
+            panels: PanelToggles::default(),
+            speed: settings.speed,
+            autosave_interval: settings.autosave_interval,
+            turn_report: settings.turn_report,
+            auto_production: settings.auto_production,
+            quick_movement_ai: settings.quick_movement_ai,
+            quick_combat_ai: settings.quick_combat_ai,
+            edge_pan: settings.edge_pan,
+            edge_pan_margin: settings.edge_pan_margin,
+            interface_scale: InterfaceScale::default().0,
+            key_bindings: InputMap::default(),
+            dismissed_hints: BTreeSet::new(),
+            alerts: MapEvent::ALL.into_iter().map(|event| (event, AlertStyle::default_for(event))).collect(),
+        }
+    }
+}
+
+fn settings_path() -> PathBuf {
+    config_dir().join(SETTINGS_FILE)
+}
+
+impl UserSettings {
+    // Reads the saved settings; a missing or broken file leaves the defaults in place
+    pub fn load() -> Self {
+        let path = settings_path();
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Self::default(),
+            Err(error) => {
+                println!("Warning: could not read {}: {}; using the default settings", path.display(), error);
+                return Self::default();
+            }
+        };
+        match ron::from_str(&text) {
+            Ok(settings) => settings,
+            Err(error) => {
+                println!("Warning: {}:{}: {}; using the default settings", path.display(), error.position, error.code);
+                Self::default()
+            }
+        }
+    }
+    
+    pub fn write(&self) -> Result<PathBuf, String> {
+        let path = settings_path();
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()).map_err(|error| error.to_string())?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|error| format!("{}: {}", dir.display(), error))?;
+        }
+        std::fs::write(&path, text).map_err(|error| format!("{}: {}", path.display(), error))?;
+        Ok(path)
+    }
+    
+    // Starts the game settings from the saved choices, before the command line has its say
+    pub fn apply_to_game(&self, settings: &mut GameSettings) {
+        settings.speed = self.speed;
+        settings.autosave_interval = self.autosave_interval;
+        settings.turn_report = self.turn_report;
+        settings.auto_production = self.auto_production;
+        settings.quick_movement_ai = self.quick_movement_ai;
+        settings.quick_combat_ai = self.quick_combat_ai;
+        settings.edge_pan = self.edge_pan;
+        settings.edge_pan_margin = self.edge_pan_margin;
+    }
+    
+    // Takes up each game setting the file keeps that differs between `before` and `after`
+    fn keep_changed_game_settings(&mut self, before: &UserSettings, after: &UserSettings) {
+        if before.speed != after.speed {
+            self.speed = after.speed;
+        }
+        if before.autosave_interval != after.autosave_interval {
+            self.autosave_interval = after.autosave_interval;
+        }
+        if before.turn_report != after.turn_report {
+            self.turn_report = after.turn_report;
+        }
+        if before.auto_production != after.auto_production {
+            self.auto_production = after.auto_production;
+        }
+        if before.quick_movement_ai != after.quick_movement_ai {
+            self.quick_movement_ai = after.quick_movement_ai;
+        }
+        if before.quick_combat_ai != after.quick_combat_ai {
+            self.quick_combat_ai = after.quick_combat_ai;
+        }
+        if before.edge_pan != after.edge_pan {
+            self.edge_pan = after.edge_pan;
+        }
+        if before.edge_pan_margin != after.edge_pan_margin {
+            self.edge_pan_margin = after.edge_pan_margin;
+        }
+    }
+    
+    // The game settings the file keeps, as `settings` has them
+    fn with_game_settings(&self, settings: &GameSettings) -> Self {
+        Self {
+            speed: settings.speed,
+            autosave_interval: settings.autosave_interval,
+            turn_report: settings.turn_report,
+            auto_production: settings.auto_production,
+            quick_movement_ai: settings.quick_movement_ai,
+            quick_combat_ai: settings.quick_combat_ai,
+            edge_pan: settings.edge_pan,
+            edge_pan_margin: settings.edge_pan_margin,
+            ..self.clone()
+        }
+    }
+    
+    // Kinds of event missing from an older settings file get their default
+    pub fn alert_style(&self, event: MapEvent) -> AlertStyle {
+        self.alerts.get(&event).copied().unwrap_or_else(|| AlertStyle::default_for(event))
+    }
+    
+    // The panels as a fresh game opens them
+    pub fn ui_state(&self) -> UIState {
+        let mut ui_state = UIState::default();
+        self.panels.apply(&mut ui_state);
+        ui_state
+    }
+}
+
+/// Everything the saved settings reach, for changing them all at once
+#[derive(SystemParam)]
+pub struct Preferences<'w, 's> {
+    pub shading: TileShading<'w, 's>,
+    pub grid: ResMut<'w, GridSettings>,
+    grid_query: Query<'w, 's, &'static mut Visibility, With<GridRoot>>,
+    pub settings: ResMut<'w, GameSettings>,
+    pub interface_scale: ResMut<'w, InterfaceScale>,
+    pub input_map: ResMut<'w, InputMap>,
+    ui_state: ResMut<'w, UIState>,
+    user_settings: ResMut<'w, UserSettings>,
+}
+
+impl Preferences<'_, '_> {
+    pub fn alert_style(&self, event: MapEvent) -> AlertStyle {
+        self.user_settings.alert_style(event)
+    }
+    
+    // Moves an event's alert on to the next style and saves it straight away, as it isn't
+    // kept anywhere the settings file is written from
+    pub fn cycle_alert(&mut self, event: MapEvent) {
+        let style = cycle(&AlertStyle::ALL, &self.user_settings.alert_style(event));
+        self.user_settings.alerts.insert(event, style);
+        if let Err(error) = self.user_settings.write() {
+            println!("Could not save settings: {}", error);
+        }
+    }
+    
+    pub fn toggle_grid(&mut self) {
+        self.grid.show_grid = !self.grid.show_grid;
+        show_grid_lines(&self.grid, &mut self.grid_query);
+    }
+    
+    // Puts every saved setting back to its default and rewrites the file, returning what
+    // to tell the player
+    pub fn reset(&mut self) -> String {
+        let defaults = UserSettings::default();
+        self.shading.update(|visuals| *visuals = defaults.visuals.clone());
+        self.grid.show_grid = defaults.show_grid;
+        show_grid_lines(&self.grid, &mut self.grid_query);
+        defaults.apply_to_game(&mut self.settings);
+        defaults.panels.apply(&mut self.ui_state);
+        self.interface_scale.0 = defaults.interface_scale;
+        *self.input_map = defaults.key_bindings.clone();
+        let message = match defaults.write() {
+            Ok(path) => format!("Settings reset to defaults ({})", path.display()),
+            Err(error) => format!("Settings reset, but could not be saved: {}", error),
+        };
+        *self.user_settings = defaults;
+        message
+    }
+}
+
+// Puts the saved map shading, grid, panels, interface size and keys in place at launch
+pub fn apply_user_settings(
+    user_settings: Res<UserSettings>,
+    mut visual_config: ResMut<VisualConfig>,
+    mut grid: ResMut<GridSettings>,
+    mut ui_state: ResMut<UIState>,
+    mut interface_scale: ResMut<InterfaceScale>,
+    mut input_map: ResMut<InputMap>,
+) {
+    *visual_config = user_settings.visuals.clone();
+    grid.show_grid = user_settings.show_grid;
+    *ui_state = user_settings.ui_state();
+    interface_scale.0 = user_settings.interface_scale;
+    *input_map = user_settings.key_bindings.clone();
+}
+
+// Writes the settings file whenever the player changes one of the settings it keeps,
+// whether by key, from the settings menu or on the setup screen. Of the game settings only
+// the ones changed since launch are written, so the command line's overrides, and console
+// commands that touch other settings, never reach the file.
+#[allow(clippy::too_many_arguments)]
+pub fn save_user_settings_system(
+    mut user_settings: ResMut<UserSettings>,
+    mut visual_config: ResMut<VisualConfig>,
+    terrain_assets: Option<Res<TerrainAssets>>,
+    grid: Res<GridSettings>,
+    ui_state: Res<UIState>,
+    settings: Res<GameSettings>,
+    interface_scale: Res<InterfaceScale>,
+    input_map: Res<InputMap>,
+    mut last_game_settings: Local<Option<UserSettings>>,
+) {
+    // In a game the map's shading is the one to keep; in the menus it's the one new
+    // games will start from
+    let mut current = user_settings.clone();
+    current.visuals = terrain_assets.map_or_else(|| visual_config.clone(), |terrain_assets| terrain_assets.visual_config.clone());
+    current.show_grid = grid.show_grid;
+    current.panels = PanelToggles::from_ui(&ui_state);
+    current.interface_scale = interface_scale.0;
+    if input_map.is_changed() {
+        current.key_bindings = input_map.clone();
+    }
+    if settings.is_changed() {
+        let now = user_settings.with_game_settings(&settings);
+        if let Some(before) = last_game_settings.as_ref() {
+            current.keep_changed_game_settings(before, &now);
+        }
+        *last_game_settings = Some(now);
+    }
+    // New games start from the latest shading, even one put back by a reset
+    if *visual_config != current.visuals {
+        *visual_config = current.visuals.clone();
+    }
+    if current == *user_settings {
+        return;
+    }
+    
+    if let Err(error) = current.write() {
+        println!("Could not save settings: {}", error);
+    }
+    *user_settings = current;
+}